@@ -40,6 +40,19 @@ impl JsonRpcBatch {
         Ok(())
     }
 
+    pub fn add_simulate_transaction_request(
+        &mut self,
+        transaction: SignedTransaction,
+        check_signature: bool,
+    ) -> Result<()> {
+        let txn_payload = hex::encode(lcs::to_bytes(&transaction)?);
+        self.add_request(
+            "simulate_transaction".to_string(),
+            vec![Value::String(txn_payload), json!(check_signature)],
+        );
+        Ok(())
+    }
+
     pub fn add_get_account_request(&mut self, address: AccountAddress) {
         self.add_request(
             "get_account".to_string(),
@@ -67,6 +80,18 @@ impl JsonRpcBatch {
         );
     }
 
+    pub fn add_get_transactions_with_proofs_request(
+        &mut self,
+        start_version: u64,
+        limit: u64,
+        include_events: bool,
+    ) {
+        self.add_request(
+            "get_transactions_with_proofs".to_string(),
+            vec![json!(start_version), json!(limit), json!(include_events)],
+        );
+    }
+
     pub fn add_get_account_transaction_request(
         &mut self,
         account: AccountAddress,
@@ -83,6 +108,13 @@ impl JsonRpcBatch {
         );
     }
 
+    pub fn add_get_transaction_by_hash_request(&mut self, hash: String, include_events: bool) {
+        self.add_request(
+            "get_transaction_by_hash".to_string(),
+            vec![json!(hash), json!(include_events)],
+        );
+    }
+
     pub fn add_get_events_request(&mut self, event_key: String, start: u64, limit: u64) {
         self.add_request(
             "get_events".to_string(),
@@ -110,6 +142,41 @@ impl JsonRpcBatch {
         );
     }
 
+    pub fn add_get_resource_request(
+        &mut self,
+        account: AccountAddress,
+        resource: String,
+        version: Option<u64>,
+    ) {
+        self.add_request(
+            "get_resource".to_string(),
+            vec![json!(account.to_string()), json!(resource), json!(version)],
+        );
+    }
+
+    pub fn add_get_account_resources_request(
+        &mut self,
+        account: AccountAddress,
+        version: Option<u64>,
+    ) {
+        self.add_request(
+            "get_account_resources".to_string(),
+            vec![json!(account.to_string()), json!(version)],
+        );
+    }
+
+    pub fn add_get_module_request(
+        &mut self,
+        account: AccountAddress,
+        name: String,
+        version: Option<u64>,
+    ) {
+        self.add_request(
+            "get_module".to_string(),
+            vec![json!(account.to_string()), json!(name), json!(version)],
+        );
+    }
+
     pub fn add_get_network_status_request(&mut self) {
         self.add_request("get_network_status".to_string(), vec![]);
     }
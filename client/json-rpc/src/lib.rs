@@ -4,6 +4,7 @@
 mod blocking;
 mod client;
 mod response;
+mod verifying;
 
 pub use blocking::JsonRpcClient;
 pub use client::{
@@ -12,3 +13,4 @@ pub use client::{
 pub use libra_json_rpc_types::{errors, views};
 pub use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
 pub use response::{JsonRpcResponse, ResponseAsView};
+pub use verifying::VerifyingClient;
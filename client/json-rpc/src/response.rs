@@ -3,7 +3,8 @@
 
 use crate::views::{
     AccountStateWithProofView, AccountView, BlockMetadata, CurrencyInfoView, EventView,
-    StateProofView, TransactionView,
+    MoveModuleView, MoveResourceView, StateProofView, TransactionListWithProofView,
+    TransactionSimulationView, TransactionView,
 };
 use anyhow::{ensure, format_err, Error, Result};
 
@@ -14,14 +15,20 @@ use std::convert::TryFrom;
 #[derive(Clone, PartialEq, Debug)]
 pub enum JsonRpcResponse {
     SubmissionResponse,
+    SimulationResponse(TransactionSimulationView),
     AccountResponse(Option<AccountView>),
     StateProofResponse(StateProofView),
     AccountTransactionResponse(Option<TransactionView>),
+    TransactionByHashResponse(Option<TransactionView>),
     TransactionsResponse(Vec<TransactionView>),
+    TransactionsWithProofsResponse(TransactionListWithProofView),
     EventsResponse(Vec<EventView>),
     BlockMetadataResponse(BlockMetadata),
     CurrenciesResponse(Vec<CurrencyInfoView>),
     AccountStateWithProofResponse(AccountStateWithProofView),
+    ResourceResponse(Option<MoveResourceView>),
+    AccountResourcesResponse(Vec<MoveResourceView>),
+    ModuleResponse(Option<MoveModuleView>),
     NetworkStatusResponse(Number),
     UnknownResponse(Value),
 }
@@ -39,6 +46,10 @@ impl TryFrom<(String, Value)> for JsonRpcResponse {
                 );
                 Ok(JsonRpcResponse::SubmissionResponse)
             }
+            "simulate_transaction" => {
+                let simulation: TransactionSimulationView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::SimulationResponse(simulation))
+            }
             "get_account" => {
                 let account = match value {
                     Value::Null => None,
@@ -81,10 +92,36 @@ impl TryFrom<(String, Value)> for JsonRpcResponse {
                 };
                 Ok(JsonRpcResponse::AccountTransactionResponse(txn))
             }
+            "get_transaction_by_hash" => {
+                let txn = match value {
+                    Value::Null => None,
+                    _ => {
+                        let txn: TransactionView = serde_json::from_value(value)?;
+                        Some(txn)
+                    }
+                };
+                Ok(JsonRpcResponse::TransactionByHashResponse(txn))
+            }
             "get_transactions" => {
                 let txns: Vec<TransactionView> = serde_json::from_value(value)?;
                 Ok(JsonRpcResponse::TransactionsResponse(txns))
             }
+            "get_resource" => {
+                let resource: Option<MoveResourceView> = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::ResourceResponse(resource))
+            }
+            "get_account_resources" => {
+                let resources: Vec<MoveResourceView> = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::AccountResourcesResponse(resources))
+            }
+            "get_module" => {
+                let module: Option<MoveModuleView> = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::ModuleResponse(module))
+            }
+            "get_transactions_with_proofs" => {
+                let txns: TransactionListWithProofView = serde_json::from_value(value)?;
+                Ok(JsonRpcResponse::TransactionsWithProofsResponse(txns))
+            }
             "get_network_status" => {
                 let connected_peers_count: Number = serde_json::from_value(value)?;
                 Ok(JsonRpcResponse::NetworkStatusResponse(
@@ -158,10 +195,10 @@ impl ResponseAsView for CurrencyInfoView {
 
 impl ResponseAsView for TransactionView {
     fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
-        if let JsonRpcResponse::AccountTransactionResponse(view) = response {
-            Ok(view)
-        } else {
-            Self::unexpected_response_error::<Option<Self>>(response)
+        match response {
+            JsonRpcResponse::AccountTransactionResponse(view)
+            | JsonRpcResponse::TransactionByHashResponse(view) => Ok(view),
+            _ => Self::unexpected_response_error::<Option<Self>>(response),
         }
     }
 
@@ -174,6 +211,26 @@ impl ResponseAsView for TransactionView {
     }
 }
 
+impl ResponseAsView for TransactionListWithProofView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::TransactionsWithProofsResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
+impl ResponseAsView for TransactionSimulationView {
+    fn from_response(response: JsonRpcResponse) -> Result<Self> {
+        if let JsonRpcResponse::SimulationResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Self>(response)
+        }
+    }
+}
+
 impl ResponseAsView for StateProofView {
     fn from_response(response: JsonRpcResponse) -> Result<Self> {
         if let JsonRpcResponse::StateProofResponse(view) = response {
@@ -193,3 +250,31 @@ impl ResponseAsView for AccountStateWithProofView {
         }
     }
 }
+
+impl ResponseAsView for MoveResourceView {
+    fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
+        if let JsonRpcResponse::ResourceResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Option<Self>>(response)
+        }
+    }
+
+    fn vec_from_response(response: JsonRpcResponse) -> Result<Vec<Self>> {
+        if let JsonRpcResponse::AccountResourcesResponse(views) = response {
+            Ok(views)
+        } else {
+            Self::unexpected_response_error::<Vec<Self>>(response)
+        }
+    }
+}
+
+impl ResponseAsView for MoveModuleView {
+    fn optional_from_response(response: JsonRpcResponse) -> Result<Option<Self>> {
+        if let JsonRpcResponse::ModuleResponse(view) = response {
+            Ok(view)
+        } else {
+            Self::unexpected_response_error::<Option<Self>>(response)
+        }
+    }
+}
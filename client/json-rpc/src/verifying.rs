@@ -0,0 +1,411 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    views::{AccountStateWithProofView, EventView, StateProofView, TransactionListWithProofView},
+    JsonRpcBatch, JsonRpcClient, JsonRpcResponse, ResponseAsView,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use libra_crypto::{
+    hash::{CryptoHash, TransactionAccumulatorHasher},
+    HashValue,
+};
+use libra_types::{
+    account_address::AccountAddress,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
+    event::EventKey,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{accumulator::InMemoryAccumulator, AccumulatorConsistencyProof},
+    transaction::{TransactionListWithProof, Version},
+    trusted_state::{TrustedState, TrustedStateChange},
+    waypoint::Waypoint,
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fs,
+    path::PathBuf,
+    vec,
+};
+
+type TransactionAccumulator = InMemoryAccumulator<TransactionAccumulatorHasher>;
+
+/// A client that doesn't trust the node it's connected to. Starting from a [`Waypoint`], it
+/// ratchets its trusted state through the epoch changes of the chain, checks that every new ledger
+/// info extends the transaction accumulator of the previous one, and verifies the data it returns
+/// against the latest ledger info. Any proof that doesn't check out is an error, and leaves the
+/// trusted state untouched.
+///
+/// The trusted state can be persisted to a file, so that it doesn't have to be re-verified from
+/// the waypoint on every run.
+pub struct VerifyingClient {
+    client: JsonRpcClient,
+    waypoint: Waypoint,
+    trusted_state: TrustedState,
+    /// `None` until the first sync with the node.
+    synced: Option<SyncedState>,
+    storage_path: Option<PathBuf>,
+}
+
+struct SyncedState {
+    latest_li: LedgerInfoWithSignatures,
+    /// The ledger info that started the current epoch, i.e. that carries its validator set.
+    latest_epoch_change_li: LedgerInfoWithSignatures,
+    /// The transaction accumulator as of `latest_li`.
+    accumulator: TransactionAccumulator,
+}
+
+#[derive(Deserialize, Serialize)]
+struct PersistedState {
+    waypoint: Waypoint,
+    latest_li: LedgerInfoWithSignatures,
+    latest_epoch_change_li: LedgerInfoWithSignatures,
+    frozen_subtree_roots: Vec<HashValue>,
+}
+
+impl VerifyingClient {
+    pub fn new(url: Url, waypoint: Waypoint) -> Result<Self> {
+        Ok(Self {
+            client: JsonRpcClient::new(url)?,
+            waypoint,
+            trusted_state: TrustedState::from(waypoint),
+            synced: None,
+            storage_path: None,
+        })
+    }
+
+    /// Same as `new`, but the trusted state is restored from `path` if it exists, and saved to it
+    /// whenever it changes. The persisted state must have been verified from the same waypoint.
+    pub fn new_with_storage(url: Url, waypoint: Waypoint, path: PathBuf) -> Result<Self> {
+        let mut client = Self::new(url, waypoint)?;
+        if path.exists() {
+            let persisted: PersistedState = lcs::from_bytes(&fs::read(&path)?)?;
+            ensure!(
+                persisted.waypoint == waypoint,
+                "Trusted state in {:?} was verified from waypoint {}, not {}",
+                path,
+                persisted.waypoint,
+                waypoint,
+            );
+            client.restore(persisted)?;
+        }
+        client.storage_path = Some(path);
+        Ok(client)
+    }
+
+    /// The latest verified ledger info, `None` until the first sync.
+    pub fn latest_ledger_info(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.synced.as_ref().map(|synced| &synced.latest_li)
+    }
+
+    /// The ledger info that started the latest verified epoch, `None` until the first sync.
+    pub fn latest_epoch_change_li(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.synced
+            .as_ref()
+            .map(|synced| &synced.latest_epoch_change_li)
+    }
+
+    /// Ratchets the trusted state to the latest ledger info of the node.
+    pub fn sync(&mut self) -> Result<()> {
+        self.execute(JsonRpcBatch::new()).map(|_| ())
+    }
+
+    /// Returns the state of an account as of the latest ledger info, or `None` if the account
+    /// doesn't exist.
+    pub fn get_account_state(
+        &mut self,
+        address: AccountAddress,
+    ) -> Result<Option<AccountStateBlob>> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_account_state_with_proof_request(address, None, None);
+        let mut responses = self.execute(batch)?;
+        let account_state = AccountStateWithProof::try_from(
+            AccountStateWithProofView::from_response(next_response(&mut responses)?)?,
+        )?;
+
+        let latest_li = self.latest_li().ledger_info();
+        account_state.verify(latest_li, latest_li.version(), address)?;
+        Ok(account_state.blob)
+    }
+
+    /// Returns up to `limit` transactions starting at `start_version`, with their events if
+    /// requested. Fewer transactions are only returned if the ledger doesn't have more.
+    pub fn get_transactions(
+        &mut self,
+        start_version: Version,
+        limit: u64,
+        include_events: bool,
+    ) -> Result<TransactionListWithProof> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_transactions_with_proofs_request(start_version, limit, include_events);
+        let mut responses = self.execute(batch)?;
+        let txn_list = TransactionListWithProof::try_from(
+            TransactionListWithProofView::from_response(next_response(&mut responses)?)?,
+        )?;
+
+        let latest_li = self.latest_li().ledger_info();
+        let num_txns = if start_version <= latest_li.version() {
+            min(limit, latest_li.version() - start_version + 1)
+        } else {
+            0
+        };
+        txn_list.verify(
+            latest_li,
+            if num_txns > 0 {
+                Some(start_version)
+            } else {
+                None
+            },
+        )?;
+        ensure!(
+            txn_list.transactions.len() as u64 == num_txns,
+            "Expected {} transactions, got {}",
+            num_txns,
+            txn_list.transactions.len(),
+        );
+        ensure!(
+            num_txns == 0 || txn_list.events.is_some() == include_events,
+            "Events were {}requested",
+            if include_events { "" } else { "not " },
+        );
+        Ok(txn_list)
+    }
+
+    /// Returns up to `limit` events of the stream with the given key, starting at sequence number
+    /// `start`, along with the versions of the transactions that emitted them. The events are
+    /// verified by fetching these transactions.
+    pub fn get_events(
+        &mut self,
+        key: &EventKey,
+        start: u64,
+        limit: u64,
+    ) -> Result<Vec<(Version, ContractEvent)>> {
+        let mut batch = JsonRpcBatch::new();
+        batch.add_get_events_request(hex::encode(key.as_bytes()), start, limit);
+        let mut responses = self.execute(batch)?;
+        let event_views = EventView::vec_from_response(next_response(&mut responses)?)?;
+        ensure!(
+            event_views.len() as u64 <= limit,
+            "Expected at most {} events, got {}",
+            limit,
+            event_views.len(),
+        );
+
+        let versions: BTreeSet<_> = event_views
+            .iter()
+            .map(|event| event.transaction_version)
+            .collect();
+        let mut batch = JsonRpcBatch::new();
+        for version in &versions {
+            batch.add_get_transactions_with_proofs_request(*version, 1, true);
+        }
+        let mut responses = self.execute(batch)?;
+        let latest_li = self.latest_li().ledger_info();
+        let mut events_by_version = BTreeMap::new();
+        for version in versions {
+            let mut txn_list = TransactionListWithProof::try_from(
+                TransactionListWithProofView::from_response(next_response(&mut responses)?)?,
+            )?;
+            txn_list.verify(latest_li, Some(version))?;
+            ensure!(
+                txn_list.transactions.len() == 1,
+                "Expected transaction {} only, got {} transactions",
+                version,
+                txn_list.transactions.len(),
+            );
+            let events = txn_list
+                .events
+                .as_mut()
+                .and_then(Vec::pop)
+                .ok_or_else(|| format_err!("Missing events of transaction {}", version))?;
+            events_by_version.insert(version, events);
+        }
+
+        event_views
+            .into_iter()
+            .zip(start..)
+            .map(|(view, sequence_number)| {
+                ensure!(
+                    view.sequence_number == sequence_number,
+                    "Expected event {}, got {}",
+                    sequence_number,
+                    view.sequence_number,
+                );
+                let event = events_by_version[&view.transaction_version]
+                    .iter()
+                    .find(|event| event.key() == key && event.sequence_number() == sequence_number)
+                    .ok_or_else(|| {
+                        format_err!(
+                            "Event {} of stream {} isn't emitted by transaction {}",
+                            sequence_number,
+                            key,
+                            view.transaction_version,
+                        )
+                    })?;
+                Ok((view.transaction_version, event.clone()))
+            })
+            .collect()
+    }
+
+    /// Executes `batch` along with the requests proving the latest ledger info of the node, then
+    /// ratchets the trusted state to that ledger info. The node serves all the requests of a batch
+    /// from the same ledger info, so the remaining responses can be verified against it.
+    fn execute(&mut self, batch: JsonRpcBatch) -> Result<vec::IntoIter<Result<JsonRpcResponse>>> {
+        let mut full_batch = JsonRpcBatch::new();
+        match &self.synced {
+            Some(synced) => {
+                full_batch.add_get_state_proof_request(synced.latest_li.ledger_info().version())
+            }
+            None => {
+                // Without a transaction accumulator to extend, we build the first one from the
+                // first transaction, which the account state proof at version 0 carries.
+                full_batch.add_get_state_proof_request(0);
+                full_batch.add_get_account_state_with_proof_request(
+                    AccountAddress::ZERO,
+                    Some(0),
+                    None,
+                );
+            }
+        }
+        full_batch.requests.extend(batch.requests);
+
+        let mut responses = self.client.execute(full_batch)?.into_iter();
+        self.verify_state_proof(&mut responses)?;
+        Ok(responses)
+    }
+
+    fn verify_state_proof(
+        &mut self,
+        responses: &mut impl Iterator<Item = Result<JsonRpcResponse>>,
+    ) -> Result<()> {
+        let state_proof = StateProofView::from_response(next_response(responses)?)?;
+        let li: LedgerInfoWithSignatures =
+            lcs::from_bytes(&state_proof.ledger_info_with_signatures.into_bytes()?)?;
+        let epoch_change_proof: EpochChangeProof =
+            lcs::from_bytes(&state_proof.epoch_change_proof.into_bytes()?)?;
+        let consistency_proof: AccumulatorConsistencyProof =
+            lcs::from_bytes(&state_proof.ledger_consistency_proof.into_bytes()?)?;
+
+        let (trusted_state, latest_epoch_change_li) = match self
+            .trusted_state
+            .verify_and_ratchet(&li, &epoch_change_proof)?
+        {
+            TrustedStateChange::Epoch {
+                new_state,
+                latest_epoch_change_li,
+            } => (new_state, latest_epoch_change_li.clone()),
+            TrustedStateChange::Version { new_state } => {
+                (new_state, self.current_epoch_change_li()?.clone())
+            }
+            TrustedStateChange::NoChange => (
+                self.trusted_state.clone(),
+                self.current_epoch_change_li()?.clone(),
+            ),
+        };
+
+        let num_leaves = li.ledger_info().version() + 1;
+        let accumulator = match &self.synced {
+            Some(synced) => synced.accumulator.append_subtrees(
+                consistency_proof.subtrees(),
+                num_leaves - synced.accumulator.num_leaves(),
+            )?,
+            None => {
+                let first_txn = AccountStateWithProof::try_from(
+                    AccountStateWithProofView::from_response(next_response(responses)?)?,
+                )?;
+                let txn_info_with_proof = first_txn.proof.transaction_info_with_proof();
+                txn_info_with_proof.verify(li.ledger_info(), 0)?;
+                TransactionAccumulator::new(vec![txn_info_with_proof.transaction_info().hash()], 1)?
+                    .append_subtrees(consistency_proof.subtrees(), num_leaves - 1)?
+            }
+        };
+        ensure!(
+            accumulator.root_hash() == li.ledger_info().transaction_accumulator_hash(),
+            "Ledger info at version {} doesn't extend the trusted transaction accumulator",
+            li.ledger_info().version(),
+        );
+
+        self.trusted_state = trusted_state;
+        self.synced = Some(SyncedState {
+            latest_li: li,
+            latest_epoch_change_li,
+            accumulator,
+        });
+        self.persist()
+    }
+
+    fn current_epoch_change_li(&self) -> Result<&LedgerInfoWithSignatures> {
+        self.latest_epoch_change_li()
+            .ok_or_else(|| format_err!("Ratcheted from a waypoint without an epoch change"))
+    }
+
+    fn latest_li(&self) -> &LedgerInfoWithSignatures {
+        self.latest_ledger_info()
+            .expect("Trusted state must be synced by execute")
+    }
+
+    /// Restores a persisted state. Its ledger infos were verified before being persisted, so we
+    /// only make sure that it's consistent.
+    fn restore(&mut self, persisted: PersistedState) -> Result<()> {
+        let epoch_state = TrustedState::try_from(persisted.latest_epoch_change_li.ledger_info())?;
+        let trusted_state = match epoch_state.verify_and_ratchet(
+            &persisted.latest_li,
+            &EpochChangeProof::new(vec![], /* more = */ false),
+        )? {
+            TrustedStateChange::Version { new_state } => new_state,
+            TrustedStateChange::NoChange => epoch_state,
+            TrustedStateChange::Epoch { .. } => {
+                bail!("Persisted ledger info isn't in the epoch of the persisted epoch change")
+            }
+        };
+        let accumulator = TransactionAccumulator::new(
+            persisted.frozen_subtree_roots,
+            persisted.latest_li.ledger_info().version() + 1,
+        )?;
+        ensure!(
+            accumulator.root_hash()
+                == persisted
+                    .latest_li
+                    .ledger_info()
+                    .transaction_accumulator_hash(),
+            "Persisted transaction accumulator doesn't match the persisted ledger info",
+        );
+
+        self.trusted_state = trusted_state;
+        self.synced = Some(SyncedState {
+            latest_li: persisted.latest_li,
+            latest_epoch_change_li: persisted.latest_epoch_change_li,
+            accumulator,
+        });
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<()> {
+        if let (Some(path), Some(synced)) = (&self.storage_path, &self.synced) {
+            let persisted = PersistedState {
+                waypoint: self.waypoint,
+                latest_li: synced.latest_li.clone(),
+                latest_epoch_change_li: synced.latest_epoch_change_li.clone(),
+                frozen_subtree_roots: synced.accumulator.frozen_subtree_roots().clone(),
+            };
+            // Write to a temporary file first, so that a crash can't leave a truncated state.
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, lcs::to_bytes(&persisted)?)?;
+            fs::rename(&tmp_path, path)?;
+        }
+        Ok(())
+    }
+}
+
+fn next_response(
+    responses: &mut impl Iterator<Item = Result<JsonRpcResponse>>,
+) -> Result<JsonRpcResponse> {
+    responses
+        .next()
+        .ok_or_else(|| format_err!("Missing response in batch"))?
+}
@@ -4,11 +4,12 @@
 use libra_types::{
     account_address::AccountAddress,
     account_config::{from_currency_code_string, type_tag_for_currency_code},
+    chain_id::ChainId,
 };
 use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use serde_json::to_string_pretty;
-use std::{fmt::Display, io::Read};
+use std::{fmt::Display, io::Read, str::FromStr};
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -83,3 +84,9 @@ pub fn account_address_parser(address: &str) -> AccountAddress {
         })
         .unwrap()
 }
+
+pub fn chain_id_parser(chain_id: &str) -> ChainId {
+    ChainId::from_str(chain_id)
+        .map_err(|err| exit_with_error(format!("Failed to parse chain_id {} : {}", chain_id, err)))
+        .unwrap()
+}
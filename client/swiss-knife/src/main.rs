@@ -14,9 +14,13 @@ use libra_types::{
         TransactionPayload,
     },
 };
+use libra_wallet::{
+    offline::{self, SignedTransactionFile, TransactionSummary, UnsignedTransactionFile},
+    WalletLibrary,
+};
 use rand::{prelude::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{path::PathBuf, str::FromStr};
 use structopt::StructOpt;
 use swiss_knife::helpers;
 
@@ -48,6 +52,17 @@ enum Command {
     /// Verifies the Ed25519 signature using the provided Ed25519 public
     /// key. Handles producing the binary representation of that transaction.
     VerifyTransactionEd25519Signature,
+    /// Generates a RawTransaction like generate-raw-txn, and writes it to a file to be signed
+    /// offline, along with a summary of the transaction for review.
+    /// Takes the input json payload from stdin. Writes the output json payload to stdout.
+    /// Refer to README.md for examples.
+    GenerateUnsignedTxnFile,
+    /// Signs the transaction of a file written by generate-unsigned-txn-file, with an Ed25519
+    /// private key or with a libra-wallet keystore, and writes the SignedTransaction to a file to
+    /// be submitted. Fails if the transaction is for another chain, or is expired.
+    /// Takes the input json payload from stdin. Writes the output json payload to stdout.
+    /// Refer to README.md for examples.
+    SignUnsignedTxnFile,
 }
 
 #[derive(Debug, StructOpt)]
@@ -111,6 +126,24 @@ fn main() {
                 .unwrap();
             helpers::exit_success_with_data(verify_transaction_signature_using_ed25519(request));
         }
+        Command::GenerateUnsignedTxnFile => {
+            let input = helpers::read_stdin();
+            let request: GenerateUnsignedTxnFileRequest = serde_json::from_str(&input)
+                .map_err(|err| {
+                    helpers::exit_with_error(format!("Failed to deserialize json : {}", err))
+                })
+                .unwrap();
+            helpers::exit_success_with_data(generate_unsigned_txn_file(request));
+        }
+        Command::SignUnsignedTxnFile => {
+            let input = helpers::read_stdin();
+            let request: SignUnsignedTxnFileRequest = serde_json::from_str(&input)
+                .map_err(|err| {
+                    helpers::exit_with_error(format!("Failed to deserialize json : {}", err))
+                })
+                .unwrap();
+            helpers::exit_success_with_data(sign_unsigned_txn_file(request));
+        }
     }
 }
 
@@ -168,6 +201,22 @@ struct GenerateRawTxnResponse {
 }
 
 fn generate_raw_txn(g: GenerateRawTxnRequest) -> GenerateRawTxnResponse {
+    let raw_txn = build_raw_txn(g);
+    GenerateRawTxnResponse {
+        raw_txn: hex::encode(
+            lcs::to_bytes(&raw_txn)
+                .map_err(|err| {
+                    helpers::exit_with_error(format!(
+                        "lcs serialization failure of raw_txn : {}",
+                        err
+                    ))
+                })
+                .unwrap(),
+        ),
+    }
+}
+
+fn build_raw_txn(g: GenerateRawTxnRequest) -> RawTransaction {
     let script = match g.script_params {
         MoveScriptParams::Preburn { coin_tag, amount } => {
             let coin_tag = helpers::coin_tag_parser(&coin_tag);
@@ -191,7 +240,7 @@ fn generate_raw_txn(g: GenerateRawTxnRequest) -> GenerateRawTxnResponse {
             )
         }
     };
-    let raw_txn = RawTransaction::new(
+    RawTransaction::new(
         helpers::account_address_parser(&g.txn_params.sender_address),
         g.txn_params.sequence_number,
         TransactionPayload::Script(script),
@@ -200,19 +249,7 @@ fn generate_raw_txn(g: GenerateRawTxnRequest) -> GenerateRawTxnResponse {
         g.txn_params.gas_currency_code,
         g.txn_params.expiration_timestamp_secs,
         ChainId::from_str(&g.txn_params.chain_id).expect("Failed to convert str to ChainId"),
-    );
-    GenerateRawTxnResponse {
-        raw_txn: hex::encode(
-            lcs::to_bytes(&raw_txn)
-                .map_err(|err| {
-                    helpers::exit_with_error(format!(
-                        "lcs serialization failure of raw_txn : {}",
-                        err
-                    ))
-                })
-                .unwrap(),
-        ),
-    }
+    )
 }
 
 #[derive(Deserialize, Serialize)]
@@ -457,3 +494,142 @@ fn verify_transaction_signature_using_ed25519(
     let valid_signature = signature.verify(&raw_txn, &public_key).is_ok();
     VerifyTransactionEd25519SignatureResponse { valid_signature }
 }
+
+//////////////////////////////
+// Offline transaction files //
+//////////////////////////////
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+struct GenerateUnsignedTxnFileRequest {
+    pub txn_params: TxnParams,
+    pub script_params: MoveScriptParams,
+    // Path of the file to write the unsigned transaction to
+    pub output_file: PathBuf,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+struct GenerateUnsignedTxnFileResponse {
+    pub summary: TransactionSummary,
+}
+
+fn generate_unsigned_txn_file(
+    request: GenerateUnsignedTxnFileRequest,
+) -> GenerateUnsignedTxnFileResponse {
+    let chain_id = helpers::chain_id_parser(&request.txn_params.chain_id);
+    let raw_txn = build_raw_txn(GenerateRawTxnRequest {
+        txn_params: request.txn_params,
+        script_params: request.script_params,
+    });
+    offline::validate(&raw_txn, chain_id, offline::now_secs())
+        .map_err(|err| helpers::exit_with_error(format!("Invalid transaction : {}", err)))
+        .unwrap();
+    let file = UnsignedTransactionFile::new(&raw_txn)
+        .map_err(|err| {
+            helpers::exit_with_error(format!("lcs serialization failure of raw_txn : {}", err))
+        })
+        .unwrap();
+    file.write(&request.output_file)
+        .map_err(|err| {
+            helpers::exit_with_error(format!(
+                "Failed to write {} : {}",
+                request.output_file.display(),
+                err
+            ))
+        })
+        .unwrap();
+    GenerateUnsignedTxnFileResponse {
+        summary: file.summary().clone(),
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+struct SignUnsignedTxnFileRequest {
+    // Path of the file written by generate-unsigned-txn-file
+    pub input_file: PathBuf,
+    // Path of the file to write the signed transaction to
+    pub output_file: PathBuf,
+    // Chain ID of the Libra network the transaction must be intended for
+    pub chain_id: String,
+    // Hex-encoded Ed25519 private key of the sender. Exclusive with keystore_file
+    pub private_key: Option<String>,
+    // Keystore of a libra-wallet holding the key of the sender. Exclusive with private_key
+    pub keystore_file: Option<PathBuf>,
+    pub keystore_password: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+struct SignUnsignedTxnFileResponse {
+    pub txn_hash: String,
+    pub summary: TransactionSummary,
+}
+
+fn sign_unsigned_txn_file(request: SignUnsignedTxnFileRequest) -> SignUnsignedTxnFileResponse {
+    let chain_id = helpers::chain_id_parser(&request.chain_id);
+    let raw_txn = UnsignedTransactionFile::read(&request.input_file)
+        .and_then(|file| file.raw_txn(chain_id))
+        .map_err(|err| {
+            helpers::exit_with_error(format!(
+                "Failed to read the transaction of {} : {}",
+                request.input_file.display(),
+                err
+            ))
+        })
+        .unwrap();
+    let signed_txn = match (
+        request.private_key,
+        request.keystore_file,
+        request.keystore_password,
+    ) {
+        (Some(private_key), None, None) => {
+            let private_key = Ed25519PrivateKey::from_encoded_string(&private_key)
+                .map_err(|err| {
+                    helpers::exit_with_error(format!(
+                        "Failed to hex decode private_key {} : {}",
+                        private_key, err
+                    ))
+                })
+                .unwrap();
+            offline::sign_with_key(raw_txn, &private_key)
+        }
+        (None, Some(keystore_file), Some(password)) => {
+            WalletLibrary::recover_from_keystore(&keystore_file, &password)
+                .and_then(|wallet| wallet.sign_txn(raw_txn))
+                .map_err(|err| {
+                    helpers::exit_with_error(format!(
+                        "Failed to sign with keystore {} : {}",
+                        keystore_file.display(),
+                        err
+                    ))
+                })
+                .unwrap()
+        }
+        _ => {
+            helpers::exit_with_error(
+                "Either private_key, or keystore_file and keystore_password, must be provided",
+            );
+            unreachable!()
+        }
+    };
+    let file = SignedTransactionFile::new(&signed_txn)
+        .map_err(|err| {
+            helpers::exit_with_error(format!("lcs serialization failure of signed_txn : {}", err))
+        })
+        .unwrap();
+    file.write(&request.output_file)
+        .map_err(|err| {
+            helpers::exit_with_error(format!(
+                "Failed to write {} : {}",
+                request.output_file.display(),
+                err
+            ))
+        })
+        .unwrap();
+    SignUnsignedTxnFileResponse {
+        txn_hash: file.txn_hash().to_string(),
+        summary: file.summary().clone(),
+    }
+}
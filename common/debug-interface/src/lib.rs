@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use consensus_types::equivocation_evidence::EquivocationEvidence;
 use libra_logger::json_log::JsonLogEntry;
+use libra_mempool::{PendingTransactionInfo, TransactionReadiness};
+use libra_types::account_address::AccountAddress;
 use reqwest::blocking;
 use std::collections::HashMap;
 
@@ -50,6 +53,46 @@ impl NodeDebugClient {
 
         Ok(response.json()?)
     }
+
+    /// Lists the transactions of `account` pending in the node's mempool.
+    pub fn get_mempool_transactions(
+        &mut self,
+        account: &AccountAddress,
+    ) -> Result<Vec<PendingTransactionInfo>> {
+        let response = self
+            .client
+            .get(&format!("{}/mempool/{:x}", self.addr, account))
+            .send()?;
+
+        Ok(response.json()?)
+    }
+
+    /// Explains whether the transaction of `account` with `sequence_number` is ready in the
+    /// node's mempool.
+    pub fn get_mempool_transaction_readiness(
+        &mut self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Result<TransactionReadiness> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/mempool/{:x}/{}",
+                self.addr, account, sequence_number
+            ))
+            .send()?;
+
+        Ok(response.json()?)
+    }
+    /// Lists the evidence of the equivocations recorded by the node's consensus.
+    pub fn get_equivocation_evidence(&mut self) -> Result<Vec<EquivocationEvidence>> {
+        let response = self
+            .client
+            .get(&format!("{}/consensus/equivocations", self.addr))
+            .send()?;
+
+        Ok(response.json()?)
+    }
 }
 
 /// Implement default utility client for AsyncNodeDebugInterface
@@ -102,4 +145,48 @@ impl AsyncNodeDebugClient {
 
         Ok(response.json().await?)
     }
+
+    /// Lists the transactions of `account` pending in the node's mempool.
+    pub async fn get_mempool_transactions(
+        &mut self,
+        account: &AccountAddress,
+    ) -> Result<Vec<PendingTransactionInfo>> {
+        let response = self
+            .client
+            .get(&format!("{}/mempool/{:x}", self.addr, account))
+            .send()
+            .await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Explains whether the transaction of `account` with `sequence_number` is ready in the
+    /// node's mempool.
+    pub async fn get_mempool_transaction_readiness(
+        &mut self,
+        account: &AccountAddress,
+        sequence_number: u64,
+    ) -> Result<TransactionReadiness> {
+        let response = self
+            .client
+            .get(&format!(
+                "{}/mempool/{:x}/{}",
+                self.addr, account, sequence_number
+            ))
+            .send()
+            .await?;
+
+        Ok(response.json().await?)
+    }
+
+    /// Lists the evidence of the equivocations recorded by the node's consensus.
+    pub async fn get_equivocation_evidence(&mut self) -> Result<Vec<EquivocationEvidence>> {
+        let response = self
+            .client
+            .get(&format!("{}/consensus/equivocations", self.addr))
+            .send()
+            .await?;
+
+        Ok(response.json().await?)
+    }
 }
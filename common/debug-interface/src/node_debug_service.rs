@@ -3,7 +3,10 @@
 
 //! Debug interface to access information in a specific node.
 
+use consensus::consensus_provider::ConsensusDebugHandle;
 use libra_logger::json_log;
+use libra_mempool::MempoolDebugHandle;
+use libra_types::account_address::AccountAddress;
 use std::net::SocketAddr;
 use tokio::runtime::{Builder, Runtime};
 use warp::Filter;
@@ -14,7 +17,11 @@ pub struct NodeDebugService {
 }
 
 impl NodeDebugService {
-    pub fn new(address: SocketAddr) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        mempool: MempoolDebugHandle,
+        consensus: Option<ConsensusDebugHandle>,
+    ) -> Self {
         let runtime = Builder::new()
             .thread_name("nodedebug-")
             .threaded_scheduler()
@@ -29,7 +36,35 @@ impl NodeDebugService {
         // GET /evnets
         let events = warp::path("events").map(|| warp::reply::json(&json_log::pop_last_entries()));
 
-        let routes = warp::get().and(metrics.or(events));
+        // GET /mempool/<account>
+        let mempool_clone = mempool.clone();
+        let mempool_transactions =
+            warp::path!("mempool" / AccountAddress).map(move |address: AccountAddress| {
+                warp::reply::json(&mempool_clone.get_account_transactions(&address))
+            });
+
+        // GET /mempool/<account>/<sequence_number>
+        let mempool_readiness = warp::path!("mempool" / AccountAddress / u64).map(
+            move |address: AccountAddress, sequence_number: u64| {
+                warp::reply::json(&mempool.get_transaction_readiness(&address, sequence_number))
+            },
+        );
+
+        // GET /consensus/equivocations
+        let consensus_equivocations = warp::path!("consensus" / "equivocations").map(move || {
+            let evidence = consensus
+                .as_ref()
+                .map_or_else(Vec::new, |consensus| consensus.get_equivocation_evidence());
+            warp::reply::json(&evidence)
+        });
+
+        let routes = warp::get().and(
+            metrics
+                .or(events)
+                .or(mempool_transactions)
+                .or(mempool_readiness)
+                .or(consensus_equivocations),
+        );
 
         let server = runtime.enter(move || warp::serve(routes).bind(address));
         runtime.handle().spawn(server);
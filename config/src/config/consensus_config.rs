@@ -15,6 +15,26 @@ pub struct ConsensusConfig {
     pub round_initial_timeout_ms: u64,
     pub proposer_type: ConsensusProposerType,
     pub safety_rules: SafetyRulesConfig,
+    /// Vote on the ordering of blocks only and execute / commit the ordered blocks in a
+    /// separate pipeline that certifies the execution results with commit votes. SafetyRules is
+    /// configured separately by `SafetyRulesConfig::decoupled_execution`, which must match.
+    pub decoupled_execution: bool,
+    /// Disseminate the transactions in batches certified for availability by a quorum of
+    /// validators: the proposals only carry the digests of the certified batches.
+    pub batch_dissemination: bool,
+    /// Max number of transactions in a batch.
+    pub max_batch_size: u64,
+    /// Number of rounds after its creation during which a batch can be proposed.
+    pub batch_expiry_rounds: u64,
+    pub batch_request_timeout_ms: u64,
+    /// Max number of batches stored for each author.
+    pub max_batches_per_author: usize,
+    /// Max number of transaction bytes in the batches stored for each author.
+    pub max_batch_bytes_per_author: u64,
+    /// Gossip the evidence of the equivocations detected locally to the other validators.
+    pub gossip_equivocation_evidence: bool,
+    /// Record the inputs of consensus to the given file for offline replay.
+    pub recording_path: Option<PathBuf>,
 }
 
 impl Default for ConsensusConfig {
@@ -29,6 +49,15 @@ impl Default for ConsensusConfig {
                 inactive_weights: 1,
             }),
             safety_rules: SafetyRulesConfig::default(),
+            decoupled_execution: false,
+            batch_dissemination: false,
+            max_batch_size: 250,
+            batch_expiry_rounds: 20,
+            batch_request_timeout_ms: 1000,
+            max_batches_per_author: 50,
+            max_batch_bytes_per_author: 10 * 1024 * 1024,
+            gossip_equivocation_evidence: false,
+            recording_path: None,
         }
     }
 }
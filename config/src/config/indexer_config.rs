@@ -0,0 +1,36 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    // Whether the node indexes the events of committed transactions, in its own DB next to the
+    // LibraDB, and serves queries on them
+    pub enabled: bool,
+    // Address of the JSON RPC endpoint of the indexer
+    pub address: SocketAddr,
+    // Max number of transactions indexed in a single DB write
+    pub batch_size: u64,
+}
+
+pub const DEFAULT_INDEXER_PORT: u16 = 8081;
+
+impl Default for IndexerConfig {
+    fn default() -> IndexerConfig {
+        IndexerConfig {
+            enabled: false,
+            address: format!("0.0.0.0:{}", DEFAULT_INDEXER_PORT).parse().unwrap(),
+            batch_size: 1000,
+        }
+    }
+}
+
+impl IndexerConfig {
+    pub fn randomize_ports(&mut self) {
+        self.address.set_port(utils::get_available_port());
+    }
+}
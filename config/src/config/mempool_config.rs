@@ -2,19 +2,27 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct MempoolConfig {
     pub capacity: usize,
     pub capacity_per_user: usize,
+    // Journal of the pending transactions, replayed on restart. None disables persistence
+    pub journal_path: Option<PathBuf>,
     pub max_broadcasts_per_peer: usize,
+    // Minimum increase of the gas price, in percent, for a transaction to replace a pending one
+    // with the same sender and sequence number
+    pub min_gas_price_bump_percent: u64,
     pub shared_mempool_backoff_interval_ms: u64,
     pub shared_mempool_batch_size: usize,
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    #[serde(skip)]
+    data_dir: PathBuf,
 }
 
 impl Default for MempoolConfig {
@@ -25,10 +33,30 @@ impl Default for MempoolConfig {
             shared_mempool_batch_size: 100,
             shared_mempool_max_concurrent_inbound_syncs: 100,
             max_broadcasts_per_peer: 25,
+            min_gas_price_bump_percent: 10,
             capacity: 1_000_000,
             capacity_per_user: 100,
+            journal_path: None,
             system_transaction_timeout_secs: 86400,
             system_transaction_gc_interval_ms: 180_000,
+            data_dir: PathBuf::from("/opt/libra/data/common"),
         }
     }
 }
+
+impl MempoolConfig {
+    /// Path of the journal, if enabled. A relative `journal_path` is relative to the data dir
+    pub fn journal_path(&self) -> Option<PathBuf> {
+        self.journal_path.as_ref().map(|path| {
+            if path.is_relative() {
+                self.data_dir.join(path)
+            } else {
+                path.clone()
+            }
+        })
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
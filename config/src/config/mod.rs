@@ -21,6 +21,8 @@ mod error;
 pub use error::*;
 mod execution_config;
 pub use execution_config::*;
+mod indexer_config;
+pub use indexer_config::*;
 mod key_manager_config;
 pub use key_manager_config::*;
 mod logger_config;
@@ -71,6 +73,8 @@ pub struct NodeConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub full_node_networks: Vec<NetworkConfig>,
     #[serde(default)]
+    pub indexer: IndexerConfig,
+    #[serde(default)]
     pub logger: LoggerConfig,
     #[serde(default)]
     pub metrics: MetricsConfig,
@@ -198,6 +202,7 @@ impl NodeConfig {
         self.base.data_dir = data_dir.clone();
         self.consensus.set_data_dir(data_dir.clone());
         self.execution.set_data_dir(data_dir.clone());
+        self.mempool.set_data_dir(data_dir.clone());
         self.metrics.set_data_dir(data_dir.clone());
         self.storage.set_data_dir(data_dir);
     }
@@ -216,6 +221,7 @@ impl NodeConfig {
                 .iter()
                 .map(|c| c.clone_for_template())
                 .collect(),
+            indexer: self.indexer.clone(),
             logger: self.logger.clone(),
             metrics: self.metrics.clone(),
             mempool: self.mempool.clone(),
@@ -247,6 +253,13 @@ impl NodeConfig {
             )?;
         }
 
+        config.rpc.verify()?;
+        invariant(
+            config.consensus.decoupled_execution
+                == config.consensus.safety_rules.decoupled_execution,
+            "consensus and safety_rules disagree on decoupled_execution".into(),
+        )?;
+
         let mut network_ids = HashSet::new();
         let input_dir = RootPath::new(input_path);
         config.execution.load(&input_dir)?;
@@ -279,6 +292,7 @@ impl NodeConfig {
 
     pub fn randomize_ports(&mut self) {
         self.debug_interface.randomize_ports();
+        self.indexer.randomize_ports();
         self.rpc.randomize_ports();
         self.storage.randomize_ports();
 
@@ -468,4 +482,14 @@ mod test {
         SafetyRulesConfig::parse(&contents)
             .unwrap_or_else(|e| panic!("Error in safety_rules.yaml: {}", e));
     }
+
+    #[test]
+    fn verify_rpc_tls_paths() {
+        let mut config = RpcConfig::default();
+        config.verify().unwrap();
+        config.tls_cert_path = Some(PathBuf::from("cert.pem"));
+        config.verify().unwrap_err();
+        config.tls_key_path = Some(PathBuf::from("key.pem"));
+        config.verify().unwrap();
+    }
 }
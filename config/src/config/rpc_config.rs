@@ -1,14 +1,34 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::utils;
+use crate::{
+    config::{invariant, Error},
+    utils,
+};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RpcConfig {
     pub address: SocketAddr,
+    // Max number of subscriptions on a single WebSocket connection
+    pub max_subscriptions_per_connection: usize,
+    // Max number of WebSocket connections served at once
+    pub max_websocket_connections: usize,
+    // Number of messages queued for a WebSocket client, beyond which its subscriptions stop
+    // reading new data until the client catches up
+    pub websocket_send_buffer_size: usize,
+    // PEM files of the certificate chain and of the private key. If both are set, the endpoint
+    // is served over TLS
+    pub tls_cert_path: Option<PathBuf>,
+    pub tls_key_path: Option<PathBuf>,
+    // Tokens accepted in the `Authorization: Bearer <token>` header. If empty, requests aren't
+    // authenticated
+    pub auth_tokens: Vec<String>,
+    // Limits on the requests of each client, identified by its token if authenticated, or by
+    // its IP address
+    pub rate_limit: RateLimitConfig,
 }
 
 pub const DEFAULT_JSON_RPC_PORT: u16 = 8080;
@@ -19,6 +39,13 @@ impl Default for RpcConfig {
             address: format!("0.0.0.0:{}", DEFAULT_JSON_RPC_PORT)
                 .parse()
                 .unwrap(),
+            max_subscriptions_per_connection: 16,
+            max_websocket_connections: 100,
+            websocket_send_buffer_size: 100,
+            tls_cert_path: None,
+            tls_key_path: None,
+            auth_tokens: vec![],
+            rate_limit: RateLimitConfig::default(),
         }
     }
 }
@@ -27,4 +54,31 @@ impl RpcConfig {
     pub fn randomize_ports(&mut self) {
         self.address.set_port(utils::get_available_port());
     }
+
+    /// Checks that TLS is either fully configured or not at all.
+    pub fn verify(&self) -> Result<(), Error> {
+        invariant(
+            self.tls_cert_path.is_some() == self.tls_key_path.is_some(),
+            "tls_cert_path and tls_key_path must be set together".into(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    // Limit of each method not in `methods`. If None, these methods aren't limited
+    pub default: Option<TokenBucketConfig>,
+    // Limits of specific methods, by method name
+    pub methods: BTreeMap<String, TokenBucketConfig>,
+}
+
+/// A token bucket: every request takes a token, and is rejected if the bucket is empty.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenBucketConfig {
+    // Max number of tokens in the bucket, i.e. of requests in a burst
+    pub capacity: u64,
+    // Number of tokens added to the bucket per second, i.e. of requests per second sustained
+    pub refill_rate: u64,
 }
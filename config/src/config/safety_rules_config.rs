@@ -23,6 +23,9 @@ pub struct SafetyRulesConfig {
     pub service: SafetyRulesService,
     pub test: Option<SafetyRulesTestConfig>,
     pub verify_vote_proposal_signature: bool,
+    // Vote on the ordering of blocks only, and sign the commit votes certifying their execution.
+    // Must match `ConsensusConfig::decoupled_execution`
+    pub decoupled_execution: bool,
 }
 
 impl Default for SafetyRulesConfig {
@@ -33,6 +36,7 @@ impl Default for SafetyRulesConfig {
             service: SafetyRulesService::Thread,
             test: None,
             verify_vote_proposal_signature: true,
+            decoupled_execution: false,
         }
     }
 }
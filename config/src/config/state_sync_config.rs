@@ -6,14 +6,26 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StateSyncConfig {
+    // Number of accounts to request in a chunk of the state snapshot in fast sync
+    pub account_chunk_limit: u64,
     // Size of chunk to request for state synchronization
     pub chunk_limit: u64,
     // default timeout used for long polling to remote peer
     pub long_poll_timeout_ms: u64,
+    // valid maximum account chunk limit for sanity check
+    pub max_account_chunk_limit: u64,
     // valid maximum chunk limit for sanity check
     pub max_chunk_limit: u64,
+    // Number of chunk requests kept in flight to different peers while catching up to a known
+    // LedgerInfo of the current epoch. 1 fetches the chunks one at a time.
+    pub max_in_flight_chunk_requests: u64,
+    // Number of failed or timed out state chunk requests in a row after which fast sync gives
+    // up on the target of the state snapshot, e.g. pruned by the peers, and asks for a new one
+    pub max_state_snapshot_failures: u64,
     // valid maximum timeout limit for sanity check
     pub max_timeout_ms: u64,
+    // How a node with empty storage catches up with the network
+    pub sync_mode: SyncMode,
     // default timeout for sync request
     pub sync_request_timeout_ms: u64,
     // interval used for checking state synchronization progress
@@ -23,12 +35,27 @@ pub struct StateSyncConfig {
 impl Default for StateSyncConfig {
     fn default() -> Self {
         Self {
+            account_chunk_limit: 1000,
             chunk_limit: 250,
             long_poll_timeout_ms: 10000,
+            max_account_chunk_limit: 5000,
             max_chunk_limit: 1000,
+            max_in_flight_chunk_requests: 1,
+            max_state_snapshot_failures: 5,
             max_timeout_ms: 120_000,
+            sync_mode: SyncMode::ReplayTransactions,
             sync_request_timeout_ms: 60_000,
             tick_interval_ms: 100,
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    // Replay all transactions from genesis
+    ReplayTransactions,
+    // Only applies to full nodes with nothing but genesis in storage: download the account state
+    // at the latest verified version of a peer, then replay the transactions after it
+    FastSync,
+}
@@ -0,0 +1,282 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Payload, Round};
+use anyhow::{ensure, Context};
+use libra_crypto::{
+    ed25519::Ed25519Signature,
+    hash::{CryptoHash, CryptoHasher, HashValue},
+};
+use libra_crypto_derive::{CryptoHasher, LCSCryptoHash};
+use libra_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// BatchInfo describes a batch of transactions disseminated by a validator independently of the
+/// proposals. The availability votes sign the BatchInfo and the proposals refer to the batch by
+/// its digest, which is the hash of the BatchInfo.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, LCSCryptoHash)]
+pub struct BatchInfo {
+    /// The validator that created the batch.
+    author: Author,
+    epoch: u64,
+    /// Sequence number of the batch among the batches of the author in the epoch.
+    batch_id: u64,
+    /// The last round in which the batch can be proposed.
+    expiration: Round,
+    /// Hash of the transactions of the batch.
+    payload_digest: HashValue,
+    num_txns: u64,
+}
+
+impl Display for BatchInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "BatchInfo: [author: {}, epoch: {}, batch_id: {}, expiration: {}, num_txns: {}]",
+            self.author.short_str(),
+            self.epoch,
+            self.batch_id,
+            self.expiration,
+            self.num_txns
+        )
+    }
+}
+
+impl BatchInfo {
+    pub fn new(
+        author: Author,
+        epoch: u64,
+        batch_id: u64,
+        expiration: Round,
+        payload: &Payload,
+    ) -> Self {
+        Self {
+            author,
+            epoch,
+            batch_id,
+            expiration,
+            payload_digest: payload_digest(payload),
+            num_txns: payload.len() as u64,
+        }
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn batch_id(&self) -> u64 {
+        self.batch_id
+    }
+
+    pub fn expiration(&self) -> Round {
+        self.expiration
+    }
+
+    pub fn payload_digest(&self) -> HashValue {
+        self.payload_digest
+    }
+
+    pub fn num_txns(&self) -> u64 {
+        self.num_txns
+    }
+
+    /// The digest identifying the batch in the proposals.
+    pub fn digest(&self) -> HashValue {
+        self.hash()
+    }
+}
+
+/// Returns the hash of the transactions of a batch.
+pub fn payload_digest(payload: &Payload) -> HashValue {
+    let mut state = BatchHasher::default();
+    lcs::serialize_into(&mut state, payload).expect("LCS serialization of Payload should not fail");
+    state.finish()
+}
+
+/// Batch carries the transactions disseminated by its author along with the author's signature
+/// of the BatchInfo, the signature counts as the availability vote of the author.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher)]
+pub struct Batch {
+    info: BatchInfo,
+    payload: Payload,
+    signature: Ed25519Signature,
+}
+
+impl Display for Batch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Batch: [{}, digest: {}]", self.info, self.digest())
+    }
+}
+
+impl Batch {
+    /// Generates a new Batch from an already signed BatchInfo (signed by SafetyRules).
+    pub fn new_with_signature(
+        info: BatchInfo,
+        payload: Payload,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            info,
+            payload,
+            signature,
+        }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    pub fn author(&self) -> Author {
+        self.info.author()
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch()
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.info.digest()
+    }
+
+    /// Verifies that the transactions match the BatchInfo and that it is signed by the author.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        ensure!(
+            self.info.num_txns() == self.payload.len() as u64,
+            "Batch carries {} txns, expected {}",
+            self.payload.len(),
+            self.info.num_txns()
+        );
+        ensure!(
+            self.info.payload_digest() == payload_digest(&self.payload),
+            "Batch payload does not match its digest"
+        );
+        validator
+            .verify(self.author(), &self.info, &self.signature)
+            .context("Failed to verify Batch")
+    }
+}
+
+/// BatchVote is sent back to the author of a batch by a validator that has stored the batch:
+/// the voter commits to serve the batch to the validators missing it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchVote {
+    /// The identity of the voter.
+    author: Author,
+    info: BatchInfo,
+    /// Signature of the BatchInfo
+    signature: Ed25519Signature,
+}
+
+impl Display for BatchVote {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "BatchVote: [author: {}, {}]",
+            self.author.short_str(),
+            self.info
+        )
+    }
+}
+
+impl BatchVote {
+    /// Generates a new BatchVote from an already signed BatchInfo (signed by SafetyRules).
+    pub fn new_with_signature(
+        author: Author,
+        info: BatchInfo,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            author,
+            info,
+            signature,
+        }
+    }
+
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch()
+    }
+
+    /// Verifies the signature of the batch vote.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(self.author, &self.info, &self.signature)
+            .context("Failed to verify BatchVote")
+    }
+}
+
+/// AvailabilityCertificate aggregates a quorum of batch votes: at least one honest validator
+/// stores the batch, hence the batch can be proposed by its digest only.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AvailabilityCertificate {
+    info: BatchInfo,
+    /// The signatures of the BatchInfo by the validators storing the batch.
+    signatures: BTreeMap<Author, Ed25519Signature>,
+}
+
+impl Display for AvailabilityCertificate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "AvailabilityCertificate: [{}, digest: {}]",
+            self.info,
+            self.digest()
+        )
+    }
+}
+
+impl AvailabilityCertificate {
+    pub fn new(info: BatchInfo, signatures: BTreeMap<Author, Ed25519Signature>) -> Self {
+        Self { info, signatures }
+    }
+
+    pub fn info(&self) -> &BatchInfo {
+        &self.info
+    }
+
+    pub fn signatures(&self) -> &BTreeMap<Author, Ed25519Signature> {
+        &self.signatures
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.info.epoch()
+    }
+
+    pub fn digest(&self) -> HashValue {
+        self.info.digest()
+    }
+
+    /// Verifies that the certificate is signed by a quorum of validators.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify_aggregated_struct_signature(&self.info, &self.signatures)
+            .context("Failed to verify AvailabilityCertificate")
+    }
+}
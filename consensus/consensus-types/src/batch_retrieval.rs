@@ -0,0 +1,78 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::batch::Batch;
+use anyhow::{ensure, format_err};
+use libra_crypto::hash::HashValue;
+use libra_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// RPC to get the batch with the given digest, used for fetching the batches referred by a
+/// proposal that have not been received locally.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchRetrievalRequest {
+    digest: HashValue,
+}
+
+impl BatchRetrievalRequest {
+    pub fn new(digest: HashValue) -> Self {
+        Self { digest }
+    }
+    pub fn digest(&self) -> HashValue {
+        self.digest
+    }
+}
+
+impl fmt::Display for BatchRetrievalRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[BatchRetrievalRequest for digest {}]", self.digest)
+    }
+}
+
+/// Carries the requested batch if it is available.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchRetrievalResponse {
+    batch: Option<Batch>,
+}
+
+impl BatchRetrievalResponse {
+    pub fn new(batch: Option<Batch>) -> Self {
+        Self { batch }
+    }
+
+    pub fn batch(&self) -> Option<&Batch> {
+        self.batch.as_ref()
+    }
+
+    pub fn take_batch(self) -> Option<Batch> {
+        self.batch
+    }
+
+    pub fn verify(
+        &self,
+        digest: HashValue,
+        sig_verifier: &ValidatorVerifier,
+    ) -> anyhow::Result<()> {
+        let batch = self
+            .batch
+            .as_ref()
+            .ok_or_else(|| format_err!("Batch {} not found", digest))?;
+        ensure!(
+            batch.digest() == digest,
+            "Unexpected batch returned, expect {}, get {}",
+            digest,
+            batch.digest()
+        );
+        batch.verify(sig_verifier)
+    }
+}
+
+impl fmt::Display for BatchRetrievalResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.batch {
+            Some(batch) => write!(f, "[BatchRetrievalResponse: {}]", batch),
+            None => write!(f, "[BatchRetrievalResponse: not found]"),
+        }
+    }
+}
@@ -192,6 +192,19 @@ impl Block {
         }
     }
 
+    /// Rebuilds a proposal from its block data and the signature of the proposer, the signature
+    /// is verified by `validate_signature`.
+    pub fn new_proposal_from_block_data_and_signature(
+        block_data: BlockData,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Block {
+            id: block_data.hash(),
+            block_data,
+            signature: Some(signature),
+        }
+    }
+
     /// Verifies that the proposal and the QC are correctly signed.
     /// If this is the genesis block, we skip these checks.
     pub fn validate_signature(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
@@ -0,0 +1,80 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{Author, Round};
+use anyhow::Context;
+use libra_crypto::ed25519::Ed25519Signature;
+use libra_types::{ledger_info::LedgerInfo, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// CommitVote is sent by a validator in decoupled execution mode once it has executed a prefix
+/// of ordered blocks. It carries the `LedgerInfo` with the execution result of the last block of
+/// the prefix, a quorum of matching commit votes certifies the result for committing.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommitVote {
+    /// The identity of the voter.
+    author: Author,
+    /// LedgerInfo of the executed block that is going to be committed.
+    ledger_info: LedgerInfo,
+    /// Signature of the LedgerInfo
+    signature: Ed25519Signature,
+}
+
+impl Display for CommitVote {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "CommitVote: [author: {}, {}]",
+            self.author.short_str(),
+            self.ledger_info
+        )
+    }
+}
+
+impl CommitVote {
+    /// Generates a new CommitVote from an already signed LedgerInfo (signed by SafetyRules).
+    pub fn new_with_signature(
+        author: Author,
+        ledger_info: LedgerInfo,
+        signature: Ed25519Signature,
+    ) -> Self {
+        Self {
+            author,
+            ledger_info,
+            signature,
+        }
+    }
+
+    /// Return the author of the commit vote
+    pub fn author(&self) -> Author {
+        self.author
+    }
+
+    /// Return the LedgerInfo associated with this commit vote
+    pub fn ledger_info(&self) -> &LedgerInfo {
+        &self.ledger_info
+    }
+
+    /// Return the signature of the commit vote
+    pub fn signature(&self) -> &Ed25519Signature {
+        &self.signature
+    }
+
+    /// Return the epoch of the commit vote
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.epoch()
+    }
+
+    /// Return the round of the block being committed
+    pub fn round(&self) -> Round {
+        self.ledger_info.round()
+    }
+
+    /// Verifies the signature of the commit vote.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        validator
+            .verify(self.author(), &self.ledger_info, &self.signature)
+            .context("Failed to verify CommitVote")
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    batch::AvailabilityCertificate,
+    block::Block,
+    block_data::{BlockData, BlockType},
+    common::{Author, Payload, Round},
+    proposal_msg::ProposalMsg,
+    sync_info::SyncInfo,
+};
+use anyhow::{ensure, format_err, Context, Result};
+use libra_crypto::{
+    ed25519::Ed25519Signature,
+    hash::{CryptoHash, CryptoHasher, HashValue},
+};
+use libra_crypto_derive::{CryptoHasher, LCSCryptoHash};
+use libra_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
+
+/// CompactBlock is what the proposer of a compact proposal signs in addition to the block: the
+/// block without its payload and the digests of its batches. The validators authenticate the
+/// compact proposal with it before fetching the batches they miss.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, CryptoHasher, LCSCryptoHash)]
+pub struct CompactBlock {
+    block_data: BlockData,
+    batch_digests: Vec<HashValue>,
+}
+
+impl CompactBlock {
+    /// The compact version of the given proposed block, whose payload is made of the batches.
+    pub fn new(block: &Block, batches: &[AvailabilityCertificate]) -> Result<Self> {
+        Ok(Self {
+            block_data: compact_block_data(block)?,
+            batch_digests: batches.iter().map(|batch| batch.digest()).collect(),
+        })
+    }
+
+    pub fn block_data(&self) -> &BlockData {
+        &self.block_data
+    }
+}
+
+/// The data of the proposed block with an empty payload.
+fn compact_block_data(block: &Block) -> Result<BlockData> {
+    let author = block
+        .author()
+        .ok_or_else(|| format_err!("Proposal {} does not define an author", block))?;
+    Ok(BlockData::new_proposal(
+        vec![],
+        author,
+        block.round(),
+        block.timestamp_usecs(),
+        block.quorum_cert().clone(),
+    ))
+}
+
+/// CompactProposalMsg replaces the ProposalMsg when the transactions are disseminated in batches:
+/// it carries the availability certificates of the batches instead of the transactions. The
+/// payload of the proposed block is the concatenation of the batches in the given order.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CompactProposalMsg {
+    /// The proposed block with an empty payload.
+    block_data: BlockData,
+    /// Signature of the proposer on the block with the full payload.
+    signature: Ed25519Signature,
+    batches: Vec<AvailabilityCertificate>,
+    sync_info: SyncInfo,
+    /// Signature of the proposer on the CompactBlock.
+    compact_signature: Ed25519Signature,
+}
+
+impl CompactProposalMsg {
+    /// Creates the compact version of the proposal, whose payload is made of the given batches.
+    /// The compact signature is the signature of the proposer on the CompactBlock.
+    pub fn new(
+        proposal: &ProposalMsg,
+        batches: Vec<AvailabilityCertificate>,
+        compact_signature: Ed25519Signature,
+    ) -> Result<Self> {
+        let block = proposal.proposal();
+        let signature = block
+            .signature()
+            .cloned()
+            .ok_or_else(|| format_err!("Missing signature in Proposal {}", block))?;
+        Ok(Self {
+            block_data: compact_block_data(block)?,
+            signature,
+            batches,
+            sync_info: proposal.sync_info().clone(),
+            compact_signature,
+        })
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.block_data.epoch()
+    }
+
+    pub fn round(&self) -> Round {
+        self.block_data.round()
+    }
+
+    pub fn proposer(&self) -> Option<Author> {
+        self.block_data.author()
+    }
+
+    pub fn batches(&self) -> &[AvailabilityCertificate] {
+        &self.batches
+    }
+
+    pub fn sync_info(&self) -> &SyncInfo {
+        &self.sync_info
+    }
+
+    /// The CompactBlock signed by the proposer.
+    pub fn compact_block(&self) -> CompactBlock {
+        CompactBlock {
+            block_data: self.block_data.clone(),
+            batch_digests: self.batches.iter().map(|batch| batch.digest()).collect(),
+        }
+    }
+
+    /// Verifies the signature of the proposer on the CompactBlock and the availability
+    /// certificates, the block itself can only be verified once the payload is known.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> Result<()> {
+        match self.block_data.block_type() {
+            BlockType::Proposal { payload, .. } => ensure!(
+                payload.is_empty(),
+                "CompactProposalMsg should not carry transactions"
+            ),
+            _ => return Err(format_err!("CompactProposalMsg for a non proposal block")),
+        }
+        let author = self
+            .proposer()
+            .ok_or_else(|| format_err!("CompactProposalMsg does not define an author"))?;
+        validator
+            .verify(author, &self.compact_block(), &self.compact_signature)
+            .context("Failed to verify the compact signature")?;
+        let mut digests = HashSet::new();
+        for batch in &self.batches {
+            ensure!(
+                batch.epoch() == self.epoch(),
+                "{} is from a different epoch than the proposal",
+                batch
+            );
+            ensure!(
+                batch.info().expiration() >= self.round(),
+                "{} expired before round {}",
+                batch,
+                self.round()
+            );
+            ensure!(
+                digests.insert(batch.digest()),
+                "{} is proposed twice",
+                batch
+            );
+            batch.verify(validator)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the ProposalMsg given the payloads of the batches in order.
+    pub fn into_proposal(self, payloads: Vec<Payload>) -> Result<ProposalMsg> {
+        ensure!(
+            payloads.len() == self.batches.len(),
+            "Got {} payloads for {} batches",
+            payloads.len(),
+            self.batches.len()
+        );
+        let author = self
+            .proposer()
+            .ok_or_else(|| format_err!("CompactProposalMsg does not define an author"))?;
+        let block_data = BlockData::new_proposal(
+            payloads.into_iter().flatten().collect(),
+            author,
+            self.block_data.round(),
+            self.block_data.timestamp_usecs(),
+            self.block_data.quorum_cert().clone(),
+        );
+        Ok(ProposalMsg::new(
+            Block::new_proposal_from_block_data_and_signature(block_data, self.signature),
+            self.sync_info,
+        ))
+    }
+}
+
+impl fmt::Display for CompactProposalMsg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let author = match self.proposer() {
+            Some(author) => author.short_str(),
+            None => String::from("NIL"),
+        };
+        write!(
+            f,
+            "[compact proposal at epoch {}, round {} with {} batches from {}]",
+            self.epoch(),
+            self.round(),
+            self.batches.len(),
+            author
+        )
+    }
+}
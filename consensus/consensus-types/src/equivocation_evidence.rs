@@ -0,0 +1,156 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::Block,
+    common::{Author, Round},
+    vote::Vote,
+};
+use anyhow::{ensure, format_err, Context};
+use libra_crypto::hash::{CryptoHash, HashValue};
+use libra_crypto_derive::{CryptoHasher, LCSCryptoHash};
+use libra_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// EquivocationEvidence proves that a validator signed two conflicting messages in the same
+/// round. The evidence is self-contained: anyone knowing the validator set of the epoch can
+/// verify it.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq, CryptoHasher, LCSCryptoHash)]
+pub enum EquivocationEvidence {
+    /// Two votes of the same author for different LedgerInfos.
+    Vote { first: Vote, second: Vote },
+    /// Two different proposals of the same author.
+    Proposal { first: Block, second: Block },
+}
+
+impl Display for EquivocationEvidence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            EquivocationEvidence::Vote { first, second } => write!(
+                f,
+                "EquivocationEvidence: [author: {}, votes: {} and {}]",
+                first.author().short_str(),
+                first,
+                second
+            ),
+            EquivocationEvidence::Proposal { first, second } => write!(
+                f,
+                "EquivocationEvidence: [author: {}, proposals: {} and {}]",
+                first.author().map_or("None".to_string(), |a| a.short_str()),
+                first,
+                second
+            ),
+        }
+    }
+}
+
+impl EquivocationEvidence {
+    /// The votes are ordered by the hash of their LedgerInfo, so that the evidence does not
+    /// depend on the order in which the votes were received.
+    pub fn new_vote_evidence(first: Vote, second: Vote) -> Self {
+        if first.ledger_info().hash() <= second.ledger_info().hash() {
+            EquivocationEvidence::Vote { first, second }
+        } else {
+            EquivocationEvidence::Vote {
+                first: second,
+                second: first,
+            }
+        }
+    }
+
+    /// The proposals are ordered by id, so that the evidence does not depend on the order in
+    /// which the proposals were received.
+    pub fn new_proposal_evidence(first: Block, second: Block) -> Self {
+        if first.id() <= second.id() {
+            EquivocationEvidence::Proposal { first, second }
+        } else {
+            EquivocationEvidence::Proposal {
+                first: second,
+                second: first,
+            }
+        }
+    }
+
+    /// The identifier of the evidence.
+    pub fn id(&self) -> HashValue {
+        self.hash()
+    }
+
+    /// The validator that equivocated.
+    pub fn author(&self) -> Option<Author> {
+        match self {
+            EquivocationEvidence::Vote { first, .. } => Some(first.author()),
+            EquivocationEvidence::Proposal { first, .. } => first.author(),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            EquivocationEvidence::Vote { first, .. } => first.epoch(),
+            EquivocationEvidence::Proposal { first, .. } => first.epoch(),
+        }
+    }
+
+    pub fn round(&self) -> Round {
+        match self {
+            EquivocationEvidence::Vote { first, .. } => first.vote_data().proposed().round(),
+            EquivocationEvidence::Proposal { first, .. } => first.round(),
+        }
+    }
+
+    /// The kind of the equivocating messages, used as a metric label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EquivocationEvidence::Vote { .. } => "vote",
+            EquivocationEvidence::Proposal { .. } => "proposal",
+        }
+    }
+
+    /// Verifies that both messages are signed by the same author for the same round and that
+    /// they conflict.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        self.verify_messages(validator)
+            .context("Failed to verify EquivocationEvidence")
+    }
+
+    fn verify_messages(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            EquivocationEvidence::Vote { first, second } => {
+                ensure!(
+                    first.author() == second.author(),
+                    "Votes of different authors"
+                );
+                ensure!(
+                    first.epoch() == second.epoch()
+                        && first.vote_data().proposed().round()
+                            == second.vote_data().proposed().round(),
+                    "Votes for different rounds"
+                );
+                ensure!(
+                    first.ledger_info() != second.ledger_info(),
+                    "Votes for the same LedgerInfo"
+                );
+                first.verify(validator)?;
+                second.verify(validator)?;
+            }
+            EquivocationEvidence::Proposal { first, second } => {
+                let author = first
+                    .author()
+                    .ok_or_else(|| format_err!("Evidence carries a block without author"))?;
+                ensure!(
+                    second.author() == Some(author),
+                    "Proposals of different authors"
+                );
+                ensure!(
+                    first.epoch() == second.epoch() && first.round() == second.round(),
+                    "Proposals for different rounds"
+                );
+                ensure!(first.id() != second.id(), "Proposals of the same block");
+                first.validate_signature(validator)?;
+                second.validate_signature(validator)?;
+            }
+        }
+        Ok(())
+    }
+}
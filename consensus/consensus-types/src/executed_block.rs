@@ -8,7 +8,7 @@ use crate::{
     vote_proposal::{MaybeSignedVoteProposal, VoteProposal},
 };
 use executor_types::StateComputeResult;
-use libra_crypto::hash::HashValue;
+use libra_crypto::hash::{HashValue, ACCUMULATOR_PLACEHOLDER_HASH};
 use libra_types::block_info::BlockInfo;
 use std::fmt::{Display, Formatter};
 
@@ -39,6 +39,24 @@ impl ExecutedBlock {
         }
     }
 
+    /// Creates an ExecutedBlock for a block that is only ordered, the execution happens later
+    /// in the execution pipeline. The placeholder result leads to an ordered-only `BlockInfo`.
+    pub fn new_ordered(block: Block) -> Self {
+        Self::new(
+            block,
+            StateComputeResult::new(
+                *ACCUMULATOR_PLACEHOLDER_HASH,
+                vec![], /* frozen_subtree_roots */
+                0,      /* num_leaves */
+                vec![], /* parent_frozen_subtree_roots */
+                0,      /* parent_num_leaves */
+                None,   /* epoch_state */
+                vec![], /* compute_status */
+                vec![], /* transaction_info_hashes */
+            ),
+        )
+    }
+
     pub fn block(&self) -> &Block {
         &self.block
     }
@@ -89,4 +107,13 @@ impl ExecutedBlock {
             signature: self.compute_result().signature().clone(),
         }
     }
+
+    /// The vote proposal for voting on the ordering of the block only, it is not signed by the
+    /// execution correctness service since there is no execution result yet.
+    pub fn maybe_signed_ordered_vote_proposal(&self) -> MaybeSignedVoteProposal {
+        MaybeSignedVoteProposal {
+            vote_proposal: VoteProposal::new_ordered(self.block.clone()),
+            signature: None,
+        }
+    }
 }
@@ -3,11 +3,16 @@
 
 #![forbid(unsafe_code)]
 
+pub mod batch;
+pub mod batch_retrieval;
 pub mod block;
 pub mod block_data;
 pub mod block_retrieval;
+pub mod commit_vote;
 pub mod common;
+pub mod compact_proposal_msg;
 pub mod epoch_retrieval;
+pub mod equivocation_evidence;
 pub mod executed_block;
 pub mod proposal_msg;
 pub mod quorum_cert;
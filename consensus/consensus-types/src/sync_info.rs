@@ -3,7 +3,10 @@
 
 use crate::{common::Round, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate};
 use anyhow::{ensure, Context};
-use libra_types::{block_info::BlockInfo, validator_verifier::ValidatorVerifier};
+use libra_types::{
+    block_info::BlockInfo, ledger_info::LedgerInfoWithSignatures,
+    validator_verifier::ValidatorVerifier,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -14,6 +17,9 @@ pub struct SyncInfo {
     highest_quorum_cert: QuorumCert,
     /// Highest ledger info known to the peer.
     highest_commit_cert: Option<QuorumCert>,
+    /// Highest LedgerInfo certifying an execution result known to the peer, only available in
+    /// decoupled execution mode where the commit cert only certifies the ordering.
+    highest_commit_proof: Option<LedgerInfoWithSignatures>,
     /// Optional highest timeout certificate if available.
     highest_timeout_cert: Option<TimeoutCertificate>,
 }
@@ -39,6 +45,22 @@ impl SyncInfo {
         highest_quorum_cert: QuorumCert,
         highest_commit_cert: QuorumCert,
         highest_timeout_cert: Option<TimeoutCertificate>,
+    ) -> Self {
+        Self::new_decoupled(
+            highest_quorum_cert,
+            highest_commit_cert,
+            None,
+            highest_timeout_cert,
+        )
+    }
+
+    /// SyncInfo carrying the highest commit proof of the execution pipeline in decoupled
+    /// execution mode.
+    pub fn new_decoupled(
+        highest_quorum_cert: QuorumCert,
+        highest_commit_cert: QuorumCert,
+        highest_commit_proof: Option<LedgerInfoWithSignatures>,
+        highest_timeout_cert: Option<TimeoutCertificate>,
     ) -> Self {
         let commit_cert = if highest_quorum_cert == highest_commit_cert {
             None
@@ -51,6 +73,7 @@ impl SyncInfo {
         Self {
             highest_quorum_cert,
             highest_commit_cert: commit_cert,
+            highest_commit_proof,
             highest_timeout_cert,
         }
     }
@@ -67,6 +90,11 @@ impl SyncInfo {
             .unwrap_or(&self.highest_quorum_cert)
     }
 
+    /// Highest commit proof of the execution pipeline if available
+    pub fn highest_commit_proof(&self) -> Option<&LedgerInfoWithSignatures> {
+        self.highest_commit_proof.as_ref()
+    }
+
     /// Highest timeout certificate if available
     pub fn highest_timeout_certificate(&self) -> Option<&TimeoutCertificate> {
         self.highest_timeout_cert.as_ref()
@@ -85,6 +113,11 @@ impl SyncInfo {
         self.highest_commit_cert().commit_info().round()
     }
 
+    pub fn highest_executed_round(&self) -> Round {
+        self.highest_commit_proof()
+            .map_or(0, |proof| proof.ledger_info().round())
+    }
+
     /// The highest round the SyncInfo carries.
     pub fn highest_round(&self) -> Round {
         std::cmp::max(self.highest_certified_round(), self.highest_timeout_round())
@@ -99,6 +132,16 @@ impl SyncInfo {
         if let Some(tc) = &self.highest_timeout_cert {
             ensure!(epoch == tc.epoch(), "Multi epoch in SyncInfo - TC and HQC");
         }
+        if let Some(proof) = &self.highest_commit_proof {
+            ensure!(
+                epoch == proof.ledger_info().epoch(),
+                "Multi epoch in SyncInfo - commit proof and HQC"
+            );
+            ensure!(
+                self.highest_commit_round() >= proof.ledger_info().round(),
+                "HCC has lower round than the commit proof"
+            );
+        }
 
         ensure!(
             self.highest_quorum_cert.certified_block().round()
@@ -116,6 +159,12 @@ impl SyncInfo {
                     .as_ref()
                     .map_or(Ok(()), |cert| cert.verify(validator))
             })
+            .and_then(|_| {
+                if let Some(proof) = &self.highest_commit_proof {
+                    proof.verify_signatures(validator)?;
+                }
+                Ok(())
+            })
             .and_then(|_| {
                 if let Some(tc) = &self.highest_timeout_cert {
                     tc.verify(validator)?;
@@ -134,5 +183,6 @@ impl SyncInfo {
         self.highest_certified_round() > other.highest_certified_round()
             || self.highest_timeout_round() > other.highest_timeout_round()
             || self.highest_commit_round() > other.highest_commit_round()
+            || self.highest_executed_round() > other.highest_executed_round()
     }
 }
@@ -55,8 +55,9 @@ impl VoteData {
             self.parent.timestamp_usecs() <= self.proposed.timestamp_usecs(),
             "Proposed happened before parent",
         );
+        // Votes on the ordering only carry no execution output, see `BlockInfo::is_ordered_only`.
         anyhow::ensure!(
-            self.parent.version() <= self.proposed.version(),
+            self.proposed.is_ordered_only() || self.parent.version() <= self.proposed.version(),
             "Proposed version is less than parent version",
         );
         Ok(())
@@ -23,6 +23,9 @@ pub struct VoteProposal {
     block: Block,
     /// An optional field containing the next epoch info.
     next_epoch_state: Option<EpochState>,
+    /// Whether the vote is on the ordering of the block only, in which case the block has not
+    /// been executed and the accumulator extension proof is empty.
+    decoupled_execution: bool,
 }
 
 impl VoteProposal {
@@ -35,6 +38,17 @@ impl VoteProposal {
             accumulator_extension_proof,
             block,
             next_epoch_state,
+            decoupled_execution: false,
+        }
+    }
+
+    /// Creates a VoteProposal for voting on the ordering of a block without executing it.
+    pub fn new_ordered(block: Block) -> Self {
+        Self {
+            accumulator_extension_proof: AccumulatorExtensionProof::new(vec![], 0, vec![]),
+            block,
+            next_epoch_state: None,
+            decoupled_execution: true,
         }
     }
 
@@ -51,6 +65,10 @@ impl VoteProposal {
     pub fn next_epoch_state(&self) -> Option<&EpochState> {
         self.next_epoch_state.as_ref()
     }
+
+    pub fn decoupled_execution(&self) -> bool {
+        self.decoupled_execution
+    }
 }
 
 impl Display for VoteProposal {
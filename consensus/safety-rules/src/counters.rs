@@ -34,6 +34,42 @@ define_counters![
         "The number of successful requests to construct_and_sign_vote"
     ),
     (epoch: Gauge, "The current epoch"),
+    (
+        sign_batch_info_error: Counter,
+        "The number of unsuccessful requests to sign_batch_info"
+    ),
+    (
+        sign_batch_info_request: Counter,
+        "The number of requests to sign_batch_info"
+    ),
+    (
+        sign_batch_info_success: Counter,
+        "The number of successful requests to sign_batch_info"
+    ),
+    (
+        sign_compact_block_error: Counter,
+        "The number of unsuccessful requests to sign_compact_block"
+    ),
+    (
+        sign_compact_block_request: Counter,
+        "The number of requests to sign_compact_block"
+    ),
+    (
+        sign_compact_block_success: Counter,
+        "The number of successful requests to sign_compact_block"
+    ),
+    (
+        sign_commit_vote_error: Counter,
+        "The number of unsuccessful requests to sign_commit_vote"
+    ),
+    (
+        sign_commit_vote_request: Counter,
+        "The number of requests to sign_commit_vote"
+    ),
+    (
+        sign_commit_vote_success: Counter,
+        "The number of successful requests to sign_commit_vote"
+    ),
     (
         initialize_error: Counter,
         "The number of unsuccessful requests to sign_proposal"
@@ -15,14 +15,22 @@ pub enum Error {
     IncorrectLastVotedRound(u64, u64),
     #[error("Provided round, {0}, is incompatible with preferred round, {1}")]
     IncorrectPreferredRound(u64, u64),
+    #[error("Commit vote for round {0} conflicts with the last commit vote, for round {1}")]
+    ConflictingCommitVote(u64, u64),
     #[error("Unable to verify that the new tree extneds the parent: {0}")]
     InvalidAccumulatorExtension(String),
+    #[error("Invalid BatchInfo: {0}")]
+    InvalidBatchInfo(String),
     #[error("Invalid EpochChangeProof: {0}")]
     InvalidEpochChangeProof(String),
+    #[error("Vote proposal does not match the execution mode, decoupled execution: {0}")]
+    ExecutionModeMismatch(bool),
     #[error("Internal error: {0}")]
     InternalError(String),
     #[error("No next_epoch_state specified in the provided Ledger Info")]
     InvalidLedgerInfo,
+    #[error("Invalid ordered LedgerInfo: {0}")]
+    InvalidOrderedLedgerInfo(String),
     #[error("Invalid proposal: {}", {0})]
     InvalidProposal(String),
     #[error("Invalid QC: {}", {0})]
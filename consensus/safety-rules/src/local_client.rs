@@ -3,11 +3,14 @@
 
 use crate::{ConsensusState, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
-    vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo, block::Block, block_data::BlockData, compact_proposal_msg::CompactBlock,
+    timeout::Timeout, vote::Vote, vote_proposal::MaybeSignedVoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 use std::sync::{Arc, RwLock};
 
 /// A local interface into SafetyRules. Constructed in such a way that the container / caller
@@ -49,4 +52,31 @@ impl TSafetyRules for LocalClient {
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error> {
         self.internal.write().unwrap().sign_timeout(timeout)
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        self.internal.write().unwrap().sign_commit_vote(
+            ordered_ledger_info,
+            executed_ledger_info,
+            executed_block,
+        )
+    }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        self.internal.write().unwrap().sign_batch_info(batch_info)
+    }
+
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        self.internal
+            .write()
+            .unwrap()
+            .sign_compact_block(compact_block)
+    }
 }
@@ -17,6 +17,9 @@ pub enum LogEntry {
     KeyReconciliation,
     LastVotedRound,
     PreferredRound,
+    SignBatchInfo,
+    SignCommitVote,
+    SignCompactBlock,
     SignProposal,
     SignTimeout,
     Waypoint,
@@ -32,6 +35,9 @@ impl LogEntry {
             LogEntry::LastVotedRound => "last_voted_round",
             LogEntry::KeyReconciliation => "key_reconciliation",
             LogEntry::PreferredRound => "preferred_round",
+            LogEntry::SignBatchInfo => "sign_batch_info",
+            LogEntry::SignCommitVote => "sign_commit_vote",
+            LogEntry::SignCompactBlock => "sign_compact_block",
             LogEntry::SignProposal => "sign_proposal",
             LogEntry::SignTimeout => "sign_timeout",
             LogEntry::Waypoint => "waypoint",
@@ -10,10 +10,13 @@ use consensus_types::{
     common::{Author, Round},
     vote::Vote,
 };
-use libra_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use libra_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    HashValue,
+};
 use libra_global_constants::{
-    CONSENSUS_KEY, EPOCH, EXECUTION_KEY, LAST_VOTE, LAST_VOTED_ROUND, OWNER_ACCOUNT,
-    PREFERRED_ROUND, WAYPOINT,
+    CONSENSUS_KEY, EPOCH, EXECUTION_KEY, LAST_COMMIT_VOTE, LAST_VOTE, LAST_VOTED_ROUND,
+    OWNER_ACCOUNT, PREFERRED_ROUND, WAYPOINT,
 };
 use libra_logger::prelude::*;
 use libra_secure_storage::{CryptoStorage, InMemoryStorage, KVStorage, Storage, Value};
@@ -92,6 +95,10 @@ impl PersistentSafetyStorage {
             LAST_VOTE,
             Value::Bytes(lcs::to_bytes::<Option<Vote>>(&None)?),
         )?;
+        internal_store.set(
+            LAST_COMMIT_VOTE,
+            Value::Bytes(lcs::to_bytes::<Option<(Round, HashValue)>>(&None)?),
+        )?;
         Ok(())
     }
 
@@ -151,6 +158,22 @@ impl PersistentSafetyStorage {
         Ok(())
     }
 
+    /// The round and the hash of the LedgerInfo of the last commit vote in the current epoch.
+    pub fn last_commit_vote(&self) -> Result<Option<(Round, HashValue)>> {
+        match self.internal_store.get(LAST_COMMIT_VOTE) {
+            Ok(response) => Ok(lcs::from_bytes(&response.value.bytes()?)?),
+            // Storage initialized before commit votes were persisted
+            Err(libra_secure_storage::Error::KeyNotSet(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set_last_commit_vote(&mut self, commit_vote: Option<(Round, HashValue)>) -> Result<()> {
+        self.internal_store
+            .set(LAST_COMMIT_VOTE, Value::Bytes(lcs::to_bytes(&commit_vote)?))?;
+        Ok(())
+    }
+
     pub fn last_voted_round(&self) -> Result<Round> {
         Ok(self
             .internal_store
@@ -226,11 +249,15 @@ mod tests {
         assert_eq!(storage.epoch().unwrap(), 1);
         assert_eq!(storage.last_voted_round().unwrap(), 0);
         assert_eq!(storage.preferred_round().unwrap(), 0);
+        assert_eq!(storage.last_commit_vote().unwrap(), None);
         storage.set_epoch(9).unwrap();
         storage.set_last_voted_round(8).unwrap();
         storage.set_preferred_round(1).unwrap();
         assert_eq!(storage.epoch().unwrap(), 9);
         assert_eq!(storage.last_voted_round().unwrap(), 8);
         assert_eq!(storage.preferred_round().unwrap(), 1);
+        let commit_vote = (7, HashValue::random());
+        storage.set_last_commit_vote(Some(commit_vote)).unwrap();
+        assert_eq!(storage.last_commit_vote().unwrap(), Some(commit_vote));
     }
 }
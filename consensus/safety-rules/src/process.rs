@@ -19,6 +19,7 @@ impl Process {
         let storage = safety_rules_manager::storage(&mut config);
 
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
+        let decoupled_execution = config.decoupled_execution;
         let service = match &config.service {
             SafetyRulesService::Process(service) => service,
             SafetyRulesService::SpawnedProcess(service) => service,
@@ -31,6 +32,7 @@ impl Process {
                 server_addr,
                 storage,
                 verify_vote_proposal_signature,
+                decoupled_execution,
             }),
         }
     }
@@ -41,6 +43,7 @@ impl Process {
             data.storage,
             data.server_addr,
             data.verify_vote_proposal_signature,
+            data.decoupled_execution,
         );
     }
 }
@@ -49,6 +52,7 @@ struct ProcessData {
     server_addr: SocketAddr,
     storage: PersistentSafetyStorage,
     verify_vote_proposal_signature: bool,
+    decoupled_execution: bool,
 }
 
 pub struct ProcessService {
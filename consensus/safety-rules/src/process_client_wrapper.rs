@@ -3,15 +3,19 @@
 
 use crate::{test_utils, ConsensusState, Error, SafetyRulesManager, TSafetyRules};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
-    vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo, block::Block, block_data::BlockData, compact_proposal_msg::CompactBlock,
+    timeout::Timeout, vote::Vote, vote_proposal::MaybeSignedVoteProposal,
 };
 use libra_config::{
     config::{NodeConfig, RemoteService, SafetyRulesService, SecureBackend},
     utils,
 };
 use libra_crypto::ed25519::{Ed25519PrivateKey, Ed25519Signature};
-use libra_types::{epoch_change::EpochChangeProof, validator_signer::ValidatorSigner};
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+};
 use std::{
     marker::{Send, Sync},
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -101,4 +105,28 @@ impl TSafetyRules for ProcessClientWrapper {
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error> {
         self.safety_rules.sign_timeout(timeout)
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        self.safety_rules.sign_commit_vote(
+            ordered_ledger_info,
+            executed_ledger_info,
+            executed_block,
+        )
+    }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        self.safety_rules.sign_batch_info(batch_info)
+    }
+
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        self.safety_rules.sign_compact_block(compact_block)
+    }
 }
@@ -24,8 +24,10 @@ pub fn execute(
     storage: PersistentSafetyStorage,
     listen_addr: SocketAddr,
     verify_vote_proposal_signature: bool,
+    decoupled_execution: bool,
 ) {
-    let safety_rules = SafetyRules::new(storage, verify_vote_proposal_signature);
+    let safety_rules =
+        SafetyRules::new(storage, verify_vote_proposal_signature, decoupled_execution);
     let mut serializer_service = SerializerService::new(safety_rules);
     let mut network_server = NetworkServer::new(listen_addr);
 
@@ -10,9 +10,11 @@ use crate::{
     COUNTERS,
 };
 use consensus_types::{
+    batch::BatchInfo,
     block::Block,
     block_data::BlockData,
     common::Author,
+    compact_proposal_msg::CompactBlock,
     quorum_cert::QuorumCert,
     timeout::Timeout,
     vote::Vote,
@@ -21,13 +23,17 @@ use consensus_types::{
 };
 use libra_crypto::{
     ed25519::{Ed25519PublicKey, Ed25519Signature},
-    hash::HashValue,
+    hash::{CryptoHash, HashValue, ACCUMULATOR_PLACEHOLDER_HASH},
     traits::Signature,
 };
 use libra_logger::prelude::*;
 use libra_types::{
-    block_info::BlockInfo, epoch_change::EpochChangeProof, epoch_state::EpochState,
-    ledger_info::LedgerInfo, validator_signer::ValidatorSigner, waypoint::Waypoint,
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    waypoint::Waypoint,
 };
 use std::cmp::Ordering;
 
@@ -35,16 +41,19 @@ use std::cmp::Ordering;
 pub struct SafetyRules {
     persistent_storage: PersistentSafetyStorage,
     execution_public_key: Option<Ed25519PublicKey>,
+    decoupled_execution: bool,
     validator_signer: Option<ValidatorSigner>,
     epoch_state: Option<EpochState>,
 }
 
 impl SafetyRules {
     /// Constructs a new instance of SafetyRules with the given persistent storage and the
-    /// consensus private keys
+    /// consensus private keys. In decoupled execution mode, it only votes on the ordering of
+    /// blocks and signs commit votes, otherwise it only votes on executed blocks.
     pub fn new(
         persistent_storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
+        decoupled_execution: bool,
     ) -> Self {
         let execution_public_key = if verify_vote_proposal_signature {
             Some(
@@ -58,6 +67,7 @@ impl SafetyRules {
         Self {
             persistent_storage,
             execution_public_key,
+            decoupled_execution,
             validator_signer: None,
             epoch_state: None,
         }
@@ -78,6 +88,14 @@ impl SafetyRules {
     /// Check if the executed result extends the parent result.
     fn extension_check(&self, vote_proposal: &VoteProposal) -> Result<VoteData, Error> {
         let proposed_block = vote_proposal.block();
+        // In decoupled execution mode the vote only certifies the ordering of the block, the
+        // execution result is certified separately by the commit votes.
+        if self.decoupled_execution {
+            return Ok(VoteData::new(
+                proposed_block.gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, None),
+                proposed_block.quorum_cert().certified_block().clone(),
+            ));
+        }
         let new_tree = vote_proposal
             .accumulator_extension_proof()
             .verify(
@@ -269,6 +287,7 @@ impl SafetyRules {
             self.persistent_storage.set_last_voted_round(0)?;
             self.persistent_storage.set_preferred_round(0)?;
             self.persistent_storage.set_last_vote(None)?;
+            self.persistent_storage.set_last_commit_vote(None)?;
             self.persistent_storage.set_epoch(epoch_state.epoch)?;
         }
         self.epoch_state = Some(epoch_state);
@@ -287,8 +306,13 @@ impl SafetyRules {
             &maybe_signed_vote_proposal.vote_proposal,
             maybe_signed_vote_proposal.signature.as_ref(),
         );
+        if vote_proposal.decoupled_execution() != self.decoupled_execution {
+            return Err(Error::ExecutionModeMismatch(self.decoupled_execution));
+        }
 
-        if let Some(public_key) = self.execution_public_key.as_ref() {
+        if let (Some(public_key), false) =
+            (self.execution_public_key.as_ref(), self.decoupled_execution)
+        {
             execution_signature
                 .ok_or_else(|| Error::VoteProposalSignatureNotFound)?
                 .verify(vote_proposal, public_key)?
@@ -372,6 +396,150 @@ impl SafetyRules {
 
         Ok(signature)
     }
+
+    fn guarded_sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        self.signer()?;
+        if !self.decoupled_execution {
+            return Err(Error::ExecutionModeMismatch(false));
+        }
+
+        let ordered = ordered_ledger_info.ledger_info();
+        self.verify_epoch(ordered.epoch())?;
+        ordered_ledger_info
+            .verify_signatures(&self.epoch_state()?.verifier)
+            .map_err(|e| Error::InvalidOrderedLedgerInfo(e.to_string()))?;
+
+        if !ordered.commit_info().is_ordered_only() {
+            return Err(Error::InvalidOrderedLedgerInfo(
+                "LedgerInfo carries an execution result".into(),
+            ));
+        }
+        if !ordered
+            .commit_info()
+            .match_ordered_only(executed_ledger_info.commit_info())
+        {
+            return Err(Error::InvalidOrderedLedgerInfo(format!(
+                "{} does not match the executed {}",
+                ordered.commit_info(),
+                executed_ledger_info.commit_info()
+            )));
+        }
+        if ordered.consensus_data_hash() != executed_ledger_info.consensus_data_hash() {
+            return Err(Error::InvalidOrderedLedgerInfo(
+                "Consensus data hash mismatch".into(),
+            ));
+        }
+        self.verify_execution_result(&executed_ledger_info, executed_block)?;
+
+        // Only one execution result is signed per round, and never one of an older round
+        let round = executed_ledger_info.round();
+        let ledger_info_hash = executed_ledger_info.hash();
+        if let Some((last_round, last_hash)) = self.persistent_storage.last_commit_vote()? {
+            if round < last_round || (round == last_round && ledger_info_hash != last_hash) {
+                return Err(Error::ConflictingCommitVote(round, last_round));
+            }
+        }
+        self.persistent_storage
+            .set_last_commit_vote(Some((round, ledger_info_hash)))?;
+
+        let validator_signer = self.signer()?;
+        Ok(validator_signer.sign(&executed_ledger_info))
+    }
+
+    /// Checks that the executed LedgerInfo carries the execution result of the executed block,
+    /// which must be signed by the execution correctness service if its signature is verified.
+    fn verify_execution_result(
+        &self,
+        executed_ledger_info: &LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<(), Error> {
+        if executed_block.decoupled_execution() {
+            return Err(Error::ExecutionModeMismatch(true));
+        }
+        if let Some(public_key) = self.execution_public_key.as_ref() {
+            executed_block
+                .signature
+                .as_ref()
+                .ok_or_else(|| Error::VoteProposalSignatureNotFound)?
+                .verify(&executed_block.vote_proposal, public_key)?
+        }
+
+        // The execution result of the parent isn't certified by its QC in decoupled execution
+        // mode, the extended tree is the one of the vote proposal.
+        let proof = executed_block.accumulator_extension_proof();
+        let new_tree = proof
+            .original_tree()
+            .and_then(|tree| proof.verify(tree.root_hash()))
+            .map_err(|e| Error::InvalidAccumulatorExtension(e.to_string()))?;
+        let block_info = executed_block.block().gen_block_info(
+            new_tree.root_hash(),
+            new_tree.version(),
+            executed_block.next_epoch_state().cloned(),
+        );
+        if &block_info != executed_ledger_info.commit_info() {
+            return Err(Error::InvalidOrderedLedgerInfo(format!(
+                "{} does not match the execution result {}",
+                executed_ledger_info.commit_info(),
+                block_info
+            )));
+        }
+        Ok(())
+    }
+
+    fn guarded_sign_batch_info(
+        &mut self,
+        batch_info: &BatchInfo,
+    ) -> Result<Ed25519Signature, Error> {
+        self.signer()?;
+        self.verify_epoch(batch_info.epoch())?;
+
+        if self
+            .epoch_state()?
+            .verifier
+            .get_public_key(&batch_info.author())
+            .is_none()
+        {
+            return Err(Error::InvalidBatchInfo(format!(
+                "{} is not a validator",
+                batch_info.author()
+            )));
+        }
+        if batch_info.num_txns() == 0 {
+            return Err(Error::InvalidBatchInfo("empty batch".into()));
+        }
+        // An expired batch cannot be proposed anymore
+        let last_voted_round = self.persistent_storage.last_voted_round()?;
+        if batch_info.expiration() < last_voted_round {
+            return Err(Error::InvalidBatchInfo(format!(
+                "expired at round {}, last voted round {}",
+                batch_info.expiration(),
+                last_voted_round
+            )));
+        }
+
+        let validator_signer = self.signer()?;
+        Ok(validator_signer.sign(batch_info))
+    }
+
+    fn guarded_sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        let block_data = compact_block.block_data();
+        self.signer()?;
+        self.verify_author(block_data.author())?;
+        self.verify_epoch(block_data.epoch())?;
+        self.verify_last_vote_round(block_data)?;
+        self.verify_qc(block_data.quorum_cert())?;
+
+        let validator_signer = self.signer()?;
+        Ok(validator_signer.sign(compact_block))
+    }
 }
 
 impl TSafetyRules for SafetyRules {
@@ -444,6 +612,57 @@ impl TSafetyRules for SafetyRules {
             LogEntry::SignTimeout,
         )
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        let round = executed_ledger_info.round();
+        let log_cb = |log: StructuredLogEntry| log.data(LogField::Round.as_str(), round);
+        let cb = || {
+            self.guarded_sign_commit_vote(ordered_ledger_info, executed_ledger_info, executed_block)
+        };
+        run_and_log(
+            cb,
+            &COUNTERS.sign_commit_vote_request,
+            &COUNTERS.sign_commit_vote_success,
+            &COUNTERS.sign_commit_vote_error,
+            log_cb,
+            LogEntry::SignCommitVote,
+        )
+    }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        let log_cb = |log: StructuredLogEntry| log;
+        let cb = || self.guarded_sign_batch_info(batch_info);
+        run_and_log(
+            cb,
+            &COUNTERS.sign_batch_info_request,
+            &COUNTERS.sign_batch_info_success,
+            &COUNTERS.sign_batch_info_error,
+            log_cb,
+            LogEntry::SignBatchInfo,
+        )
+    }
+
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        let round = compact_block.block_data().round();
+        let log_cb = |log: StructuredLogEntry| log.data(LogField::Round.as_str(), round);
+        let cb = || self.guarded_sign_compact_block(compact_block);
+        run_and_log(
+            cb,
+            &COUNTERS.sign_compact_block_request,
+            &COUNTERS.sign_compact_block_success,
+            &COUNTERS.sign_compact_block_error,
+            log_cb,
+            LogEntry::SignCompactBlock,
+        )
+    }
 }
 
 fn run_and_log<F, L, R>(
@@ -87,12 +87,17 @@ impl SafetyRulesManager {
 
         let storage = storage(config);
         let verify_vote_proposal_signature = config.verify_vote_proposal_signature;
+        let decoupled_execution = config.decoupled_execution;
         match config.service {
-            SafetyRulesService::Local => Self::new_local(storage, verify_vote_proposal_signature),
+            SafetyRulesService::Local => {
+                Self::new_local(storage, verify_vote_proposal_signature, decoupled_execution)
+            }
             SafetyRulesService::Serializer => {
-                Self::new_serializer(storage, verify_vote_proposal_signature)
+                Self::new_serializer(storage, verify_vote_proposal_signature, decoupled_execution)
+            }
+            SafetyRulesService::Thread => {
+                Self::new_thread(storage, verify_vote_proposal_signature, decoupled_execution)
             }
-            SafetyRulesService::Thread => Self::new_thread(storage, verify_vote_proposal_signature),
             _ => panic!("Unimplemented SafetyRulesService: {:?}", config.service),
         }
     }
@@ -100,8 +105,10 @@ impl SafetyRulesManager {
     pub fn new_local(
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
+        decoupled_execution: bool,
     ) -> Self {
-        let safety_rules = SafetyRules::new(storage, verify_vote_proposal_signature);
+        let safety_rules =
+            SafetyRules::new(storage, verify_vote_proposal_signature, decoupled_execution);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Local(Arc::new(RwLock::new(safety_rules))),
         }
@@ -117,8 +124,10 @@ impl SafetyRulesManager {
     pub fn new_serializer(
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
+        decoupled_execution: bool,
     ) -> Self {
-        let safety_rules = SafetyRules::new(storage, verify_vote_proposal_signature);
+        let safety_rules =
+            SafetyRules::new(storage, verify_vote_proposal_signature, decoupled_execution);
         let serializer_service = SerializerService::new(safety_rules);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Serializer(Arc::new(RwLock::new(
@@ -137,8 +146,10 @@ impl SafetyRulesManager {
     pub fn new_thread(
         storage: PersistentSafetyStorage,
         verify_vote_proposal_signature: bool,
+        decoupled_execution: bool,
     ) -> Self {
-        let thread = ThreadService::new(storage, verify_vote_proposal_signature);
+        let thread =
+            ThreadService::new(storage, verify_vote_proposal_signature, decoupled_execution);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Thread(thread),
         }
@@ -3,11 +3,14 @@
 
 use crate::{ConsensusState, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
-    vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo, block::Block, block_data::BlockData, compact_proposal_msg::CompactBlock,
+    timeout::Timeout, vote::Vote, vote_proposal::MaybeSignedVoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 
@@ -18,6 +21,13 @@ pub enum SafetyRulesInput {
     ConstructAndSignVote(Box<MaybeSignedVoteProposal>),
     SignProposal(Box<BlockData>),
     SignTimeout(Box<Timeout>),
+    SignCommitVote(
+        Box<LedgerInfoWithSignatures>,
+        Box<LedgerInfo>,
+        Box<MaybeSignedVoteProposal>,
+    ),
+    SignBatchInfo(Box<BatchInfo>),
+    SignCompactBlock(Box<CompactBlock>),
 }
 
 pub struct SerializerService {
@@ -44,6 +54,21 @@ impl SerializerService {
             SafetyRulesInput::SignTimeout(timeout) => {
                 lcs::to_bytes(&self.internal.sign_timeout(&timeout))
             }
+            SafetyRulesInput::SignCommitVote(
+                ordered_ledger_info,
+                executed_ledger_info,
+                executed_block,
+            ) => lcs::to_bytes(&self.internal.sign_commit_vote(
+                *ordered_ledger_info,
+                *executed_ledger_info,
+                &executed_block,
+            )),
+            SafetyRulesInput::SignBatchInfo(batch_info) => {
+                lcs::to_bytes(&self.internal.sign_batch_info(&batch_info))
+            }
+            SafetyRulesInput::SignCompactBlock(compact_block) => {
+                lcs::to_bytes(&self.internal.sign_compact_block(&compact_block))
+            }
         };
 
         Ok(output?)
@@ -99,6 +124,37 @@ impl TSafetyRules for SerializerClient {
         let response = self.request(SafetyRulesInput::SignTimeout(Box::new(timeout.clone())))?;
         lcs::from_bytes(&response)?
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        let response = self.request(SafetyRulesInput::SignCommitVote(
+            Box::new(ordered_ledger_info),
+            Box::new(executed_ledger_info),
+            Box::new(executed_block.clone()),
+        ))?;
+        lcs::from_bytes(&response)?
+    }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        let response = self.request(SafetyRulesInput::SignBatchInfo(Box::new(
+            batch_info.clone(),
+        )))?;
+        lcs::from_bytes(&response)?
+    }
+
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        let response = self.request(SafetyRulesInput::SignCompactBlock(Box::new(
+            compact_block.clone(),
+        )))?;
+        lcs::from_bytes(&response)?
+    }
 }
 
 pub trait TSerializerClient: Send + Sync {
@@ -3,11 +3,14 @@
 
 use crate::{ConsensusState, Error};
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
-    vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo, block::Block, block_data::BlockData, compact_proposal_msg::CompactBlock,
+    timeout::Timeout, vote::Vote, vote_proposal::MaybeSignedVoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 
 /// Interface for SafetyRules
 pub trait TSafetyRules {
@@ -34,4 +37,27 @@ pub trait TSafetyRules {
     /// As the holder of the private key, SafetyRules also signs what is effectively a
     /// timeout message. This returns the signature for that timeout message.
     fn sign_timeout(&mut self, timeout: &Timeout) -> Result<Ed25519Signature, Error>;
+
+    /// In decoupled execution mode, signs the LedgerInfo carrying the execution result of a
+    /// block whose ordering has been certified by the given ordered LedgerInfo. The execution
+    /// result is the one of the vote proposal of the executed block, signed by the execution
+    /// correctness service if the signature is to be verified. This returns the signature for
+    /// the commit vote.
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error>;
+
+    /// Signs the BatchInfo of a batch of transactions stored locally, the signature is an
+    /// availability vote for the batch.
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error>;
+
+    /// Signs the CompactBlock of a proposal whose payload is made of certified batches, the
+    /// signature lets the validators authenticate the proposal before fetching the batches.
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error>;
 }
@@ -214,13 +214,20 @@ pub fn validator_signers_to_waypoint(signers: &[&ValidatorSigner]) -> Waypoint {
 }
 
 pub fn test_storage(signer: &ValidatorSigner) -> PersistentSafetyStorage {
+    test_storage_with_execution_key(signer, Ed25519PrivateKey::generate_for_testing())
+}
+
+pub fn test_storage_with_execution_key(
+    signer: &ValidatorSigner,
+    execution_key: Ed25519PrivateKey,
+) -> PersistentSafetyStorage {
     let waypoint = validator_signers_to_waypoint(&[signer]);
     let storage = Storage::from(InMemoryStorage::new());
     PersistentSafetyStorage::initialize(
         storage,
         signer.author(),
         signer.private_key().clone(),
-        Ed25519PrivateKey::generate_for_testing(),
+        execution_key,
         waypoint,
     )
 }
@@ -8,7 +8,7 @@ use libra_types::validator_signer::ValidatorSigner;
 fn test_reconnect() {
     let signer = ValidatorSigner::from_int(0);
     let storage = test_utils::test_storage(&signer);
-    let safety_rules_manager = SafetyRulesManager::new_thread(storage, false);
+    let safety_rules_manager = SafetyRulesManager::new_thread(storage, false, false);
 
     // Verify that after a client has disconnected a new client will connect and resume operations
     let state0 = safety_rules_manager.client().consensus_state().unwrap();
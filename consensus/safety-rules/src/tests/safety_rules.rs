@@ -15,7 +15,11 @@ fn safety_rules(verify_vote_proposal_signature: bool) -> suite::Callback {
     Box::new(move || {
         let signer = ValidatorSigner::from_int(0);
         let storage = test_utils::test_storage(&signer);
-        let safety_rules = Box::new(SafetyRules::new(storage, verify_vote_proposal_signature));
+        let safety_rules = Box::new(SafetyRules::new(
+            storage,
+            verify_vote_proposal_signature,
+            false,
+        ));
         (
             safety_rules,
             signer,
@@ -16,7 +16,7 @@ fn safety_rules(verify_vote_proposal_signature: bool) -> suite::Callback {
         let signer = ValidatorSigner::from_int(0);
         let storage = test_utils::test_storage(&signer);
         let safety_rules_manager =
-            SafetyRulesManager::new_serializer(storage, verify_vote_proposal_signature);
+            SafetyRulesManager::new_serializer(storage, verify_vote_proposal_signature, false);
         let safety_rules = safety_rules_manager.client();
         (
             safety_rules,
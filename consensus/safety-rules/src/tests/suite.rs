@@ -3,19 +3,29 @@
 
 use crate::{test_utils, Error, SafetyRules, TSafetyRules};
 use consensus_types::{
-    block::block_test_utils::random_payload, common::Round, quorum_cert::QuorumCert,
-    timeout::Timeout, vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo,
+    block::block_test_utils::random_payload,
+    common::Round,
+    compact_proposal_msg::CompactBlock,
+    quorum_cert::QuorumCert,
+    timeout::Timeout,
+    vote_proposal::{MaybeSignedVoteProposal, VoteProposal},
 };
 use libra_crypto::{
     ed25519::Ed25519PrivateKey,
-    hash::{CryptoHash, HashValue},
+    hash::{CryptoHash, HashValue, ACCUMULATOR_PLACEHOLDER_HASH},
+    traits::{Signature, SigningKey},
+    Uniform,
 };
 use libra_global_constants::CONSENSUS_KEY;
 use libra_secure_storage::CryptoStorage;
 use libra_types::{
-    epoch_state::EpochState, validator_signer::ValidatorSigner,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
     validator_verifier::ValidatorVerifier,
 };
+use std::collections::BTreeMap;
 
 type Proof = test_utils::Proof;
 
@@ -54,6 +64,9 @@ pub fn run_test_suite(safety_rules: &Callback) {
     test_initialize(safety_rules);
     test_preferred_block_rule(safety_rules);
     test_sign_timeout(safety_rules);
+    test_sign_commit_vote(safety_rules);
+    test_sign_batch_info(safety_rules);
+    test_sign_compact_block(safety_rules);
     test_voting(safety_rules);
     test_voting_potential_commit_id(safety_rules);
     test_voting_bad_epoch(safety_rules);
@@ -345,6 +358,219 @@ fn test_sign_timeout(safety_rules: &Callback) {
     assert_eq!(actual_err, expected_err);
 }
 
+fn test_sign_commit_vote(safety_rules: &Callback) {
+    // Vote on the ordering of a1 only and then sign the commit vote for its execution result
+    let (mut coupled_safety_rules, signer, key) = safety_rules();
+    let execution_key = Ed25519PrivateKey::generate_for_testing();
+    let storage = test_utils::test_storage_with_execution_key(&signer, execution_key.clone());
+    let mut safety_rules = Box::new(SafetyRules::new(storage, true, true));
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+    let a1 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer, key.as_ref());
+    let ordered_a1 = MaybeSignedVoteProposal {
+        vote_proposal: VoteProposal::new_ordered(a1.block().clone()),
+        signature: None,
+    };
+
+    // The execution mode is set by SafetyRules, not by the vote proposal
+    coupled_safety_rules.initialize(&proof).unwrap();
+    assert_eq!(
+        coupled_safety_rules.construct_and_sign_vote(&ordered_a1),
+        Err(Error::ExecutionModeMismatch(false))
+    );
+    safety_rules.initialize(&proof).unwrap();
+    assert_eq!(
+        safety_rules.construct_and_sign_vote(&a1),
+        Err(Error::ExecutionModeMismatch(true))
+    );
+
+    let vote = safety_rules.construct_and_sign_vote(&ordered_a1).unwrap();
+    assert!(vote.vote_data().proposed().is_ordered_only());
+
+    let consensus_data_hash = HashValue::random();
+    let ordered_li = LedgerInfo::new(vote.vote_data().proposed().clone(), consensus_data_hash);
+    let mut signatures = BTreeMap::new();
+    signatures.insert(signer.author(), signer.sign(&ordered_li));
+    let ordered_proof = LedgerInfoWithSignatures::new(ordered_li, signatures);
+
+    // The execution result is the one of the vote proposal signed by the execution service
+    let execution_proof = Proof::new(vec![], 0, vec![HashValue::random()]);
+    let new_tree = execution_proof
+        .verify(*ACCUMULATOR_PLACEHOLDER_HASH)
+        .unwrap();
+    let executed_li = LedgerInfo::new(
+        a1.block()
+            .gen_block_info(new_tree.root_hash(), new_tree.version(), None),
+        consensus_data_hash,
+    );
+    let executed_a1 = MaybeSignedVoteProposal {
+        vote_proposal: VoteProposal::new(execution_proof, a1.block().clone(), None),
+        signature: None,
+    };
+    let signed_executed_a1 = MaybeSignedVoteProposal {
+        signature: Some(execution_key.sign(&executed_a1.vote_proposal)),
+        ..executed_a1.clone()
+    };
+    assert_eq!(
+        safety_rules.sign_commit_vote(ordered_proof.clone(), executed_li.clone(), &executed_a1),
+        Err(Error::VoteProposalSignatureNotFound)
+    );
+    let signature = safety_rules
+        .sign_commit_vote(
+            ordered_proof.clone(),
+            executed_li.clone(),
+            &signed_executed_a1,
+        )
+        .unwrap();
+    signature
+        .verify(&executed_li, &signer.public_key())
+        .unwrap();
+    assert_eq!(
+        coupled_safety_rules.sign_commit_vote(
+            ordered_proof.clone(),
+            executed_li.clone(),
+            &signed_executed_a1
+        ),
+        Err(Error::ExecutionModeMismatch(false))
+    );
+
+    // The executed LedgerInfo has to carry the same consensus data and execution result
+    let other_li = LedgerInfo::new(executed_li.commit_info().clone(), HashValue::random());
+    assert!(matches!(
+        safety_rules.sign_commit_vote(ordered_proof.clone(), other_li, &signed_executed_a1),
+        Err(Error::InvalidOrderedLedgerInfo(_))
+    ));
+    let other_li = LedgerInfo::new(
+        a1.block().gen_block_info(HashValue::random(), 0, None),
+        consensus_data_hash,
+    );
+    assert!(matches!(
+        safety_rules.sign_commit_vote(ordered_proof.clone(), other_li, &signed_executed_a1),
+        Err(Error::InvalidOrderedLedgerInfo(_))
+    ));
+
+    // The ordered LedgerInfo must not carry an execution result
+    let mut signatures = BTreeMap::new();
+    signatures.insert(signer.author(), signer.sign(&executed_li));
+    let executed_proof = LedgerInfoWithSignatures::new(executed_li.clone(), signatures);
+    assert!(matches!(
+        safety_rules.sign_commit_vote(executed_proof, executed_li.clone(), &signed_executed_a1),
+        Err(Error::InvalidOrderedLedgerInfo(_))
+    ));
+
+    // The same execution result can be signed again, but not a conflicting one of the round
+    assert!(safety_rules
+        .sign_commit_vote(
+            ordered_proof.clone(),
+            executed_li.clone(),
+            &signed_executed_a1
+        )
+        .is_ok());
+    let conflicting_a1 = MaybeSignedVoteProposal {
+        vote_proposal: VoteProposal::new(
+            Proof::new(vec![], 0, vec![HashValue::random()]),
+            a1.block().clone(),
+            None,
+        ),
+        signature: None,
+    };
+    let conflicting_a1 = MaybeSignedVoteProposal {
+        signature: Some(execution_key.sign(&conflicting_a1.vote_proposal)),
+        ..conflicting_a1
+    };
+    let new_tree = conflicting_a1
+        .accumulator_extension_proof()
+        .verify(*ACCUMULATOR_PLACEHOLDER_HASH)
+        .unwrap();
+    let conflicting_li = LedgerInfo::new(
+        a1.block()
+            .gen_block_info(new_tree.root_hash(), new_tree.version(), None),
+        consensus_data_hash,
+    );
+    let round = a1.block().round();
+    assert_eq!(
+        safety_rules.sign_commit_vote(ordered_proof, conflicting_li, &conflicting_a1),
+        Err(Error::ConflictingCommitVote(round, round))
+    );
+}
+
+fn test_sign_batch_info(safety_rules: &Callback) {
+    let (mut safety_rules, signer, key) = safety_rules();
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+    let epoch = genesis_qc.certified_block().epoch();
+    let payload = random_payload(2);
+
+    safety_rules.initialize(&proof).unwrap();
+    let batch_info = BatchInfo::new(signer.author(), epoch, 0, round + 10, &payload);
+    let signature = safety_rules.sign_batch_info(&batch_info).unwrap();
+    signature.verify(&batch_info, &signer.public_key()).unwrap();
+
+    // Cannot sign a batch from another epoch
+    let batch_info = BatchInfo::new(signer.author(), epoch + 1, 1, round + 10, &payload);
+    let actual_err = safety_rules.sign_batch_info(&batch_info).unwrap_err();
+    let expected_err = Error::IncorrectEpoch(epoch + 1, epoch);
+    assert_eq!(actual_err, expected_err);
+
+    // Cannot sign a batch from a non validator
+    let other = ValidatorSigner::from_int(100);
+    let batch_info = BatchInfo::new(other.author(), epoch, 2, round + 10, &payload);
+    assert!(matches!(
+        safety_rules.sign_batch_info(&batch_info),
+        Err(Error::InvalidBatchInfo(_))
+    ));
+
+    // Cannot sign an empty batch
+    let batch_info = BatchInfo::new(signer.author(), epoch, 3, round + 10, &vec![]);
+    assert!(matches!(
+        safety_rules.sign_batch_info(&batch_info),
+        Err(Error::InvalidBatchInfo(_))
+    ));
+
+    // Cannot sign a batch expiring before the last voted round
+    let a1 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer, key.as_ref());
+    safety_rules.construct_and_sign_vote(&a1).unwrap();
+    let batch_info = BatchInfo::new(signer.author(), epoch, 4, round, &payload);
+    assert!(matches!(
+        safety_rules.sign_batch_info(&batch_info),
+        Err(Error::InvalidBatchInfo(_))
+    ));
+}
+
+fn test_sign_compact_block(safety_rules: &Callback) {
+    let (mut safety_rules, signer, key) = safety_rules();
+
+    let (proof, genesis_qc) = test_utils::make_genesis(&signer);
+    let round = genesis_qc.certified_block().round();
+    safety_rules.initialize(&proof).unwrap();
+
+    let a1 = test_utils::make_proposal_with_qc(round + 1, genesis_qc, &signer, key.as_ref());
+    let compact_block = CompactBlock::new(a1.block(), &[]).unwrap();
+    let signature = safety_rules.sign_compact_block(&compact_block).unwrap();
+    signature
+        .verify(&compact_block, &signer.public_key())
+        .unwrap();
+
+    // Cannot sign the compact block of another proposer
+    let bad_signer = ValidatorSigner::from_int(0xef);
+    let a2 = make_proposal_with_parent(round + 2, &a1, None, &bad_signer, key.as_ref());
+    let compact_block = CompactBlock::new(a2.block(), &[]).unwrap();
+    assert_eq!(
+        safety_rules.sign_compact_block(&compact_block).unwrap_err(),
+        Error::InvalidProposal("Proposal author is not validator signer!".into())
+    );
+
+    // Cannot sign the compact block of a round already voted
+    safety_rules.construct_and_sign_vote(&a1).unwrap();
+    let compact_block = CompactBlock::new(a1.block(), &[]).unwrap();
+    assert_eq!(
+        safety_rules.sign_compact_block(&compact_block).unwrap_err(),
+        Error::IncorrectLastVotedRound(round + 1, round + 1)
+    );
+}
+
 fn test_voting(safety_rules: &Callback) {
     // build a tree of the following form:
     //             _____    __________
@@ -681,7 +907,7 @@ fn test_reconcile_key(_safety_rules: &Callback) {
     let mut storage = test_utils::test_storage(&signer);
 
     let new_pub_key = storage.internal_store().rotate_key(CONSENSUS_KEY).unwrap();
-    let mut safety_rules = Box::new(SafetyRules::new(storage, false));
+    let mut safety_rules = Box::new(SafetyRules::new(storage, false, false));
 
     let (mut proof, genesis_qc) = test_utils::make_genesis(&signer);
     let round = genesis_qc.certified_block().round();
@@ -0,0 +1,352 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! In batch dissemination mode every validator pulls the transactions from its mempool into
+//! batches and broadcasts them independently of the proposals. The validators storing a batch
+//! sign its BatchInfo, and a quorum of signatures forms an availability certificate. The proposals
+//! only carry the certificates of the batches, which makes the bandwidth of the leader
+//! independent of the throughput.
+
+use crate::state_replication::TxnManager;
+use anyhow::Result;
+use consensus_types::{
+    batch::{payload_digest, AvailabilityCertificate, Batch, BatchInfo, BatchVote},
+    block::Block,
+    common::{Author, Payload, Round},
+    proposal_msg::ProposalMsg,
+};
+use executor_types::StateComputeResult;
+use libra_config::config::ConsensusConfig;
+use libra_crypto::{ed25519::Ed25519Signature, HashValue};
+use libra_types::{account_address::AccountAddress, validator_verifier::ValidatorVerifier};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(test)]
+#[path = "batch_manager_test.rs"]
+mod batch_manager_test;
+
+/// A certified batch that can be proposed.
+struct CertifiedBatch {
+    certificate: AvailabilityCertificate,
+    /// The highest round in which the batch has been proposed.
+    proposed_round: Option<Round>,
+}
+
+/// The storage used by the batches of an author.
+#[derive(Clone, Copy, Default)]
+struct Usage {
+    num_batches: usize,
+    num_bytes: u64,
+}
+
+#[derive(Default)]
+struct BatchState {
+    /// The round consensus is currently in.
+    round: Round,
+    /// The id of the next batch created locally.
+    next_batch_id: u64,
+    /// The batches stored locally by digest.
+    batches: HashMap<HashValue, Batch>,
+    /// The storage used by the stored batches of each author.
+    usage: HashMap<Author, Usage>,
+    /// The votes collected for the batches created locally and not certified yet.
+    votes: HashMap<HashValue, BTreeMap<Author, Ed25519Signature>>,
+    /// The certified batches that can be proposed, in the order they were certified.
+    certified: Vec<CertifiedBatch>,
+    /// The certificates of the payloads pulled for the proposals of the current round, by the
+    /// digest of the payload.
+    pulled: HashMap<HashValue, Vec<AvailabilityCertificate>>,
+}
+
+/// BatchManager keeps the batches and the availability certificates of an epoch. It is the
+/// TxnManager of the ProposalGenerator in batch dissemination mode: the payload of a proposal is
+/// made of certified batches only.
+pub struct BatchManager {
+    author: Author,
+    epoch: u64,
+    // Mempool delivering the transactions of the batches created locally.
+    txn_manager: Arc<dyn TxnManager>,
+    // Max number of transactions in a batch created locally.
+    max_batch_size: u64,
+    // Number of rounds during which a batch can be proposed.
+    batch_expiry_rounds: Round,
+    // Timeout of the requests for the missing batches.
+    request_timeout: Duration,
+    // Max number of batches stored for each author.
+    max_batches_per_author: usize,
+    // Max number of transaction bytes in the batches stored for each author.
+    max_batch_bytes_per_author: u64,
+    state: Mutex<BatchState>,
+}
+
+impl BatchManager {
+    pub fn new(
+        author: Author,
+        epoch: u64,
+        txn_manager: Arc<dyn TxnManager>,
+        config: &ConsensusConfig,
+    ) -> Self {
+        Self {
+            author,
+            epoch,
+            txn_manager,
+            max_batch_size: config.max_batch_size,
+            batch_expiry_rounds: config.batch_expiry_rounds,
+            request_timeout: Duration::from_millis(config.batch_request_timeout_ms),
+            max_batches_per_author: config.max_batches_per_author,
+            max_batch_bytes_per_author: config.max_batch_bytes_per_author,
+            state: Mutex::new(BatchState::default()),
+        }
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Moves to the given round and prunes the batches that cannot be proposed anymore: the ones
+    /// that expired and the ones proposed in a block that is not above the committed round.
+    pub fn update_round(&self, round: Round, committed_round: Round) {
+        let mut state = self.state.lock().unwrap();
+        state.round = round;
+        state.pulled.clear();
+        state.certified.retain(|certified| {
+            certified.certificate.info().expiration() >= round
+                && certified
+                    .proposed_round
+                    .map_or(true, |proposed_round| proposed_round > committed_round)
+        });
+        // The pending blocks are all above the committed round, the batches that expired
+        // before cannot be referred by them.
+        state
+            .batches
+            .retain(|_, batch| batch.info().expiration() >= committed_round);
+        let BatchState {
+            batches,
+            usage,
+            votes,
+            ..
+        } = &mut *state;
+        votes.retain(|digest, _| batches.contains_key(digest));
+        usage.clear();
+        for batch in batches.values() {
+            let author_usage = usage.entry(batch.author()).or_default();
+            author_usage.num_batches += 1;
+            author_usage.num_bytes += payload_bytes(batch.payload());
+        }
+    }
+
+    /// Whether the batch expires within the expiry rounds of the current round: a batch
+    /// expiring later could be kept forever.
+    fn valid_expiration(&self, state: &BatchState, info: &BatchInfo) -> bool {
+        info.expiration() >= state.round
+            && info.expiration() <= state.round.saturating_add(self.batch_expiry_rounds)
+    }
+
+    /// Whether the author can store one more batch of the given size.
+    fn has_quota(&self, state: &BatchState, author: Author, num_bytes: u64) -> bool {
+        let usage = state.usage.get(&author).copied().unwrap_or_default();
+        usage.num_batches < self.max_batches_per_author
+            && usage.num_bytes + num_bytes <= self.max_batch_bytes_per_author
+    }
+
+    /// Pulls the transactions of a new batch from mempool, returns None if there is no new
+    /// transaction. The returned BatchInfo has to be signed before the batch is broadcast.
+    pub async fn pull_batch(&self) -> Result<Option<(BatchInfo, Payload)>> {
+        // The transactions of the batches created locally are pending until they expire.
+        let pending_payloads: Vec<Payload> = {
+            let state = self.state.lock().unwrap();
+            state
+                .batches
+                .values()
+                .filter(|batch| batch.author() == self.author)
+                .map(|batch| batch.payload().clone())
+                .collect()
+        };
+        let payload = self
+            .txn_manager
+            .pull_txns(self.max_batch_size, pending_payloads.iter().collect())
+            .await?;
+        if payload.is_empty() {
+            return Ok(None);
+        }
+        let mut state = self.state.lock().unwrap();
+        // The other validators would not store the batch.
+        if !self.has_quota(&state, self.author, payload_bytes(&payload)) {
+            return Ok(None);
+        }
+        let batch_info = BatchInfo::new(
+            self.author,
+            self.epoch,
+            state.next_batch_id,
+            state.round + self.batch_expiry_rounds,
+            &payload,
+        );
+        state.next_batch_id += 1;
+        Ok(Some((batch_info, payload)))
+    }
+
+    /// Stores a verified batch, returns false if the batch is already stored, its expiration is
+    /// not within the expiry rounds or its author used up its storage.
+    pub fn add_batch(&self, batch: Batch) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let num_bytes = payload_bytes(batch.payload());
+        if !self.valid_expiration(&state, batch.info())
+            || state.batches.contains_key(&batch.digest())
+            || !self.has_quota(&state, batch.author(), num_bytes)
+        {
+            return false;
+        }
+        let usage = state.usage.entry(batch.author()).or_default();
+        usage.num_batches += 1;
+        usage.num_bytes += num_bytes;
+        state.batches.insert(batch.digest(), batch);
+        true
+    }
+
+    pub fn get_batch(&self, digest: HashValue) -> Option<Batch> {
+        self.state.lock().unwrap().batches.get(&digest).cloned()
+    }
+
+    /// Adds a verified vote for a batch created locally, returns the availability certificate of
+    /// the batch once the votes form a quorum.
+    pub fn add_vote(
+        &self,
+        vote: &BatchVote,
+        verifier: &ValidatorVerifier,
+    ) -> Option<AvailabilityCertificate> {
+        let mut state = self.state.lock().unwrap();
+        let digest = vote.info().digest();
+        if vote.info().author() != self.author || !state.batches.contains_key(&digest) {
+            return None;
+        }
+        if state
+            .certified
+            .iter()
+            .any(|c| c.certificate.digest() == digest)
+        {
+            return None;
+        }
+        let signatures = state.votes.entry(digest).or_insert_with(BTreeMap::new);
+        signatures.insert(vote.author(), vote.signature().clone());
+        verifier.check_voting_power(signatures.keys()).ok()?;
+        let signatures = state.votes.remove(&digest)?;
+        Some(AvailabilityCertificate::new(
+            vote.info().clone(),
+            signatures,
+        ))
+    }
+
+    /// Adds a verified availability certificate, returns false if the certificate is already
+    /// known or its expiration is not within the expiry rounds.
+    pub fn add_certificate(&self, certificate: AvailabilityCertificate) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !self.valid_expiration(&state, certificate.info())
+            || state
+                .certified
+                .iter()
+                .any(|c| c.certificate.digest() == certificate.digest())
+        {
+            return false;
+        }
+        state.votes.remove(&certificate.digest());
+        state.certified.push(CertifiedBatch {
+            certificate,
+            proposed_round: None,
+        });
+        true
+    }
+
+    /// Records that the certified batches have been proposed in the given round.
+    pub fn mark_proposed(&self, certificates: &[AvailabilityCertificate], round: Round) {
+        let mut state = self.state.lock().unwrap();
+        for certificate in certificates {
+            match state
+                .certified
+                .iter_mut()
+                .find(|c| c.certificate.digest() == certificate.digest())
+            {
+                Some(certified) => {
+                    certified.proposed_round = std::cmp::max(certified.proposed_round, Some(round));
+                }
+                None => state.certified.push(CertifiedBatch {
+                    certificate: certificate.clone(),
+                    proposed_round: Some(round),
+                }),
+            }
+        }
+    }
+
+    /// Returns the certificates of the batches making up the payload of a proposal generated
+    /// locally, whose payload has been pulled from the certified batches in the current round.
+    pub fn proposed_batches(&self, proposal: &ProposalMsg) -> Option<Vec<AvailabilityCertificate>> {
+        let payload = proposal.proposal().payload()?;
+        self.state
+            .lock()
+            .unwrap()
+            .pulled
+            .get(&payload_digest(payload))
+            .cloned()
+    }
+}
+
+/// The number of transaction bytes of a batch payload.
+fn payload_bytes(payload: &Payload) -> u64 {
+    payload
+        .iter()
+        .map(|txn| txn.raw_txn_bytes_len() as u64)
+        .sum()
+}
+
+#[async_trait::async_trait]
+impl TxnManager for BatchManager {
+    /// Concatenates the certified batches stored locally that do not carry any of the excluded
+    /// transactions, the batches are taken as a whole.
+    async fn pull_txns(&self, max_size: u64, exclude_payloads: Vec<&Payload>) -> Result<Payload> {
+        let mut exclude_txns: HashSet<(AccountAddress, u64)> = exclude_payloads
+            .iter()
+            .flat_map(|payload| payload.iter())
+            .map(|txn| (txn.sender(), txn.sequence_number()))
+            .collect();
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let mut payload = vec![];
+        let mut certificates = vec![];
+        for certified in &state.certified {
+            let certificate = &certified.certificate;
+            if certificate.info().expiration() < state.round {
+                continue;
+            }
+            let batch = match state.batches.get(&certificate.digest()) {
+                Some(batch) => batch,
+                None => continue,
+            };
+            if (payload.len() + batch.payload().len()) as u64 > max_size
+                || batch
+                    .payload()
+                    .iter()
+                    .any(|txn| exclude_txns.contains(&(txn.sender(), txn.sequence_number())))
+            {
+                continue;
+            }
+            for txn in batch.payload() {
+                exclude_txns.insert((txn.sender(), txn.sequence_number()));
+                payload.push(txn.clone());
+            }
+            certificates.push(certificate.clone());
+        }
+        state.pulled.insert(payload_digest(&payload), certificates);
+        Ok(payload)
+    }
+
+    async fn notify(&self, block: &Block, compute_result: &StateComputeResult) -> Result<()> {
+        self.txn_manager.notify(block, compute_result).await
+    }
+
+    fn trace_transactions(&self, block: &Block) {
+        self.txn_manager.trace_transactions(block)
+    }
+}
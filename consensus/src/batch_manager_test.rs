@@ -0,0 +1,240 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    batch_manager::BatchManager, state_replication::TxnManager, test_utils::MockTransactionManager,
+};
+use consensus_types::{
+    batch::{AvailabilityCertificate, Batch, BatchInfo, BatchVote},
+    block::block_test_utils::random_payload,
+    common::Round,
+};
+use libra_config::config::ConsensusConfig;
+use libra_types::{
+    validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
+};
+use std::sync::Arc;
+
+fn batch_config() -> ConsensusConfig {
+    ConsensusConfig {
+        max_batch_size: 10,
+        batch_expiry_rounds: 10,
+        batch_request_timeout_ms: 100,
+        max_batches_per_author: 3,
+        ..ConsensusConfig::default()
+    }
+}
+
+fn create_batch_manager(signer: &ValidatorSigner) -> BatchManager {
+    create_batch_manager_with_config(signer, &batch_config())
+}
+
+fn create_batch_manager_with_config(
+    signer: &ValidatorSigner,
+    config: &ConsensusConfig,
+) -> BatchManager {
+    BatchManager::new(
+        signer.author(),
+        1,
+        Arc::new(MockTransactionManager::new(None)),
+        config,
+    )
+}
+
+/// Creates a batch of the given author certified by all the signers.
+fn certified_batch(
+    signers: &[ValidatorSigner],
+    author: &ValidatorSigner,
+    batch_id: u64,
+    expiration: Round,
+    num_txns: usize,
+) -> (Batch, AvailabilityCertificate) {
+    let payload = random_payload(num_txns);
+    let info = BatchInfo::new(author.author(), 1, batch_id, expiration, &payload);
+    let signatures = signers
+        .iter()
+        .map(|signer| (signer.author(), signer.sign(&info)))
+        .collect();
+    let batch = Batch::new_with_signature(info.clone(), payload, author.sign(&info));
+    (batch, AvailabilityCertificate::new(info, signatures))
+}
+
+#[tokio::test]
+async fn test_batch_certification() {
+    let (signers, validators) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    batch_manager.update_round(3, 0);
+
+    let (info, payload) = batch_manager.pull_batch().await.unwrap().unwrap();
+    assert_eq!(info.author(), signers[0].author());
+    assert_eq!(info.expiration(), 13);
+    assert_eq!(info.num_txns(), 10);
+    let batch = Batch::new_with_signature(info.clone(), payload, signers[0].sign(&info));
+    assert!(batch.verify(&validators).is_ok());
+    assert!(batch_manager.add_batch(batch.clone()));
+    assert!(!batch_manager.add_batch(batch));
+
+    // A quorum of votes is required for the certificate.
+    let votes: Vec<_> = signers
+        .iter()
+        .map(|signer| {
+            BatchVote::new_with_signature(signer.author(), info.clone(), signer.sign(&info))
+        })
+        .collect();
+    assert!(batch_manager.add_vote(&votes[0], &validators).is_none());
+    assert!(batch_manager.add_vote(&votes[1], &validators).is_none());
+    let certificate = batch_manager.add_vote(&votes[2], &validators).unwrap();
+    assert!(certificate.verify(&validators).is_ok());
+    assert_eq!(certificate.digest(), info.digest());
+
+    // The late votes are ignored once the batch is certified.
+    assert!(batch_manager.add_certificate(certificate));
+    assert!(batch_manager.add_vote(&votes[3], &validators).is_none());
+}
+
+#[tokio::test]
+async fn test_votes_for_other_batches() {
+    let (signers, validators) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    let (batch, _) = certified_batch(&signers, &signers[1], 0, 10, 2);
+    assert!(batch_manager.add_batch(batch.clone()));
+
+    // Only the author of a batch collects its votes.
+    for signer in &signers {
+        let vote = BatchVote::new_with_signature(
+            signer.author(),
+            batch.info().clone(),
+            signer.sign(batch.info()),
+        );
+        assert!(batch_manager.add_vote(&vote, &validators).is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_pull_certified_batches() {
+    let (signers, _) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    batch_manager.update_round(1, 0);
+
+    let (b1, c1) = certified_batch(&signers, &signers[1], 0, 10, 3);
+    let (b2, c2) = certified_batch(&signers, &signers[2], 0, 10, 4);
+    let (b3, c3) = certified_batch(&signers, &signers[3], 0, 10, 5);
+    // The batch is certified but not stored locally.
+    let (_, c4) = certified_batch(&signers, &signers[3], 1, 10, 1);
+    for (batch, certificate) in vec![(b1.clone(), c1), (b2.clone(), c2), (b3.clone(), c3)] {
+        assert!(batch_manager.add_batch(batch));
+        assert!(batch_manager.add_certificate(certificate));
+    }
+    assert!(batch_manager.add_certificate(c4));
+
+    // The batches are taken as a whole: b3 does not fit in the block.
+    let payload = batch_manager.pull_txns(8, vec![]).await.unwrap();
+    let mut expected = b1.payload().clone();
+    expected.extend(b2.payload().iter().cloned());
+    assert_eq!(payload, expected);
+
+    // The batches carrying the excluded transactions are skipped.
+    let payload = batch_manager
+        .pull_txns(9, vec![b1.payload()])
+        .await
+        .unwrap();
+    let mut expected = b2.payload().clone();
+    expected.extend(b3.payload().iter().cloned());
+    assert_eq!(payload, expected);
+}
+
+#[tokio::test]
+async fn test_prune_batches() {
+    let (signers, _) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    batch_manager.update_round(1, 0);
+
+    let (b1, c1) = certified_batch(&signers, &signers[1], 0, 3, 1);
+    let (b2, c2) = certified_batch(&signers, &signers[2], 0, 10, 1);
+    let (b3, c3) = certified_batch(&signers, &signers[3], 0, 10, 1);
+    for (batch, certificate) in vec![(b1.clone(), c1), (b2.clone(), c2.clone()), (b3.clone(), c3)] {
+        assert!(batch_manager.add_batch(batch));
+        assert!(batch_manager.add_certificate(certificate));
+    }
+    batch_manager.mark_proposed(&[c2], 2);
+
+    // b1 expired and b2 has been committed, b1 is still stored for the pending blocks.
+    batch_manager.update_round(4, 2);
+    let payload = batch_manager.pull_txns(10, vec![]).await.unwrap();
+    assert_eq!(&payload, b3.payload());
+    assert!(batch_manager.get_batch(b1.digest()).is_some());
+
+    // The expired batches are dropped once they are below the committed round.
+    batch_manager.update_round(5, 4);
+    assert!(batch_manager.get_batch(b1.digest()).is_none());
+    assert!(batch_manager.get_batch(b2.digest()).is_some());
+    assert!(!batch_manager.add_batch(b1));
+}
+
+#[tokio::test]
+async fn test_batch_expiration_bound() {
+    let (signers, _) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    batch_manager.update_round(5, 0);
+
+    // The batches expiring beyond the expiry rounds are rejected.
+    let (far, far_certificate) = certified_batch(&signers, &signers[1], 0, 16, 1);
+    assert!(!batch_manager.add_batch(far));
+    assert!(!batch_manager.add_certificate(far_certificate));
+    let (expired, expired_certificate) = certified_batch(&signers, &signers[1], 1, 4, 1);
+    assert!(!batch_manager.add_batch(expired));
+    assert!(!batch_manager.add_certificate(expired_certificate));
+    let (batch, certificate) = certified_batch(&signers, &signers[1], 2, 15, 1);
+    assert!(batch_manager.add_batch(batch));
+    assert!(batch_manager.add_certificate(certificate));
+}
+
+#[tokio::test]
+async fn test_batches_per_author_bounded() {
+    let (signers, _) = random_validator_verifier(4, None, false);
+    let batch_manager = create_batch_manager(&signers[0]);
+    batch_manager.update_round(1, 0);
+
+    let batches: Vec<_> = (0..4)
+        .map(|batch_id| certified_batch(&signers, &signers[1], batch_id, 2, 1).0)
+        .collect();
+    for batch in &batches[..3] {
+        assert!(batch_manager.add_batch(batch.clone()));
+    }
+    assert!(!batch_manager.add_batch(batches[3].clone()));
+    // The other authors are not affected.
+    let (other, _) = certified_batch(&signers, &signers[2], 0, 2, 1);
+    assert!(batch_manager.add_batch(other));
+
+    // The pruned batches free their storage.
+    batch_manager.update_round(4, 3);
+    assert!(batch_manager.get_batch(batches[0].digest()).is_none());
+    let (batch, _) = certified_batch(&signers, &signers[1], 4, 10, 1);
+    assert!(batch_manager.add_batch(batch));
+}
+
+#[tokio::test]
+async fn test_batch_bytes_per_author_bounded() {
+    let (signers, _) = random_validator_verifier(4, None, false);
+    let (first, _) = certified_batch(&signers, &signers[1], 0, 10, 2);
+    let first_bytes: usize = first
+        .payload()
+        .iter()
+        .map(|txn| txn.raw_txn_bytes_len())
+        .sum();
+    let config = ConsensusConfig {
+        max_batch_bytes_per_author: first_bytes as u64,
+        ..batch_config()
+    };
+    let batch_manager = create_batch_manager_with_config(&signers[0], &config);
+    batch_manager.update_round(1, 0);
+
+    assert!(batch_manager.add_batch(first));
+    let (second, _) = certified_batch(&signers, &signers[1], 1, 10, 1);
+    assert!(!batch_manager.add_batch(second));
+
+    // The own batches are not pulled once the storage is used up.
+    let (own, _) = certified_batch(&signers, &signers[0], 0, 10, 2);
+    assert!(batch_manager.add_batch(own));
+    assert!(batch_manager.pull_batch().await.unwrap().is_none());
+}
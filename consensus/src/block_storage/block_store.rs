@@ -4,6 +4,7 @@
 use crate::{
     block_storage::{block_tree::BlockTree, BlockReader},
     counters,
+    execution_pipeline::{ExecutedBlocks, ExecutionRequest, OrderedBlocks},
     persistent_liveness_storage::{
         PersistentLivenessStorage, RecoveryData, RootInfo, RootMetadata,
     },
@@ -16,6 +17,7 @@ use consensus_types::{
     timeout_certificate::TimeoutCertificate,
 };
 use executor_types::{Error, StateComputeResult};
+use futures::SinkExt;
 use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_trace::prelude::*;
@@ -99,6 +101,9 @@ pub struct BlockStore {
     storage: Arc<dyn PersistentLivenessStorage>,
     /// Used to ensure that any block stored will have a timestamp < the local time
     time_service: Arc<dyn TimeService>,
+    /// In decoupled execution mode the blocks are inserted without being executed and the
+    /// committed blocks are sent to the execution pipeline.
+    execution_pipeline: Option<channel::Sender<ExecutionRequest>>,
 }
 
 impl BlockStore {
@@ -108,6 +113,7 @@ impl BlockStore {
         state_computer: Arc<dyn StateComputer>,
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
+        execution_pipeline: Option<channel::Sender<ExecutionRequest>>,
     ) -> Self {
         let highest_tc = initial_data.highest_timeout_certificate();
         let (root, root_metadata, blocks, quorum_certs) = initial_data.take();
@@ -121,6 +127,7 @@ impl BlockStore {
             storage,
             max_pruned_blocks_in_mem,
             time_service,
+            execution_pipeline,
         )
     }
 
@@ -134,23 +141,28 @@ impl BlockStore {
         storage: Arc<dyn PersistentLivenessStorage>,
         max_pruned_blocks_in_mem: usize,
         time_service: Arc<dyn TimeService>,
+        execution_pipeline: Option<channel::Sender<ExecutionRequest>>,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_li) = root;
-        //verify root is correct
-        assert_eq!(
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-            "root qc version {} doesn't match committed trees {}",
-            root_qc.certified_block().version(),
-            root_metadata.version(),
-        );
-        assert_eq!(
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-            "root qc state id {} doesn't match committed trees {}",
-            root_qc.certified_block().executed_state_id(),
-            root_metadata.accu_hash,
-        );
+        // In decoupled execution mode the root qc may only certify the ordering of the root,
+        // the committed trees are then verified by the commit votes instead.
+        if execution_pipeline.is_none() || !root_qc.certified_block().is_ordered_only() {
+            //verify root is correct
+            assert_eq!(
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+                "root qc version {} doesn't match committed trees {}",
+                root_qc.certified_block().version(),
+                root_metadata.version(),
+            );
+            assert_eq!(
+                root_qc.certified_block().executed_state_id(),
+                root_metadata.accu_hash,
+                "root qc state id {} doesn't match committed trees {}",
+                root_qc.certified_block().executed_state_id(),
+                root_metadata.accu_hash,
+            );
+        }
 
         let result = StateComputeResult::new(
             root_metadata.accu_hash,
@@ -181,6 +193,7 @@ impl BlockStore {
             state_computer,
             storage,
             time_service,
+            execution_pipeline,
         };
         for block in blocks {
             block_store
@@ -200,6 +213,9 @@ impl BlockStore {
     }
 
     /// Commit the given block id with the proof, returns () on success or error
+    ///
+    /// In decoupled execution mode the proof only certifies the ordering: the blocks are sent to
+    /// the execution pipeline instead and pruned once their execution result is committed.
     pub async fn commit(&self, finality_proof: LedgerInfoWithSignatures) -> anyhow::Result<()> {
         let block_id_to_commit = finality_proof.ledger_info().consensus_block_id();
         let block_to_commit = self
@@ -208,10 +224,35 @@ impl BlockStore {
 
         // First make sure that this commit is new.
         ensure!(
-            block_to_commit.round() > self.root().round(),
+            block_to_commit.round() > self.ordered_root().round(),
             "Committed block round lower than root"
         );
 
+        if let Some(execution_pipeline) = &self.execution_pipeline {
+            let blocks_to_execute = self
+                .inner
+                .read()
+                .unwrap()
+                .path_from_ordered_root(block_id_to_commit)
+                .unwrap_or_else(Vec::new);
+            self.inner
+                .write()
+                .unwrap()
+                .update_ordered_root(block_id_to_commit);
+            debug!("{}Ordered{} {}", Fg(Blue), Fg(Reset), *block_to_commit);
+            return execution_pipeline
+                .clone()
+                .send(ExecutionRequest::Execute(OrderedBlocks {
+                    blocks: blocks_to_execute
+                        .iter()
+                        .map(|b| b.block().clone())
+                        .collect(),
+                    ordered_proof: finality_proof,
+                }))
+                .await
+                .context("Failed to send ordered blocks to the execution pipeline");
+        }
+
         let blocks_to_commit = self
             .path_from_root(block_id_to_commit)
             .unwrap_or_else(Vec::new);
@@ -237,6 +278,80 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Send the executed blocks to the execution pipeline for committing them with the quorum
+    /// of commit votes in decoupled execution mode.
+    pub async fn commit_executed(
+        &self,
+        executed_blocks: ExecutedBlocks,
+        commit_proof: LedgerInfoWithSignatures,
+    ) -> anyhow::Result<()> {
+        let mut execution_pipeline = self
+            .execution_pipeline
+            .clone()
+            .ok_or_else(|| format_err!("Execution pipeline is not enabled"))?;
+        execution_pipeline
+            .send(ExecutionRequest::Commit(executed_blocks, commit_proof))
+            .await
+            .context("Failed to send executed blocks to the execution pipeline")
+    }
+
+    /// Prune the tree once the execution pipeline committed the given blocks, the commit proof
+    /// is then shared with the peers in the SyncInfo.
+    pub fn process_committed(
+        &self,
+        committed_blocks: &[Arc<ExecutedBlock>],
+        commit_proof: &LedgerInfoWithSignatures,
+    ) {
+        let block_to_commit = match committed_blocks.last() {
+            Some(block) => block,
+            None => return,
+        };
+        for block in committed_blocks {
+            end_trace!("commit", {"block", block.id()});
+        }
+        update_counters_for_committed_blocks(committed_blocks);
+        debug!("{}Committed{} {}", Fg(Blue), Fg(Reset), **block_to_commit);
+        event!("committed",
+            "block_id": block_to_commit.id().short_str(),
+            "round": block_to_commit.round(),
+            "parent_id": block_to_commit.parent_id().short_str(),
+        );
+        if block_to_commit.round() > self.root().round() {
+            self.prune_tree(block_to_commit.id());
+        }
+        self.inner
+            .write()
+            .unwrap()
+            .update_highest_commit_proof(commit_proof.clone());
+    }
+
+    /// Whether the blocks are executed in the separate execution pipeline.
+    pub fn decoupled_execution(&self) -> bool {
+        self.execution_pipeline.is_some()
+    }
+
+    /// The last block sent for execution, it is the same as the root unless in decoupled
+    /// execution mode.
+    pub fn ordered_root(&self) -> Arc<ExecutedBlock> {
+        self.inner.read().unwrap().ordered_root()
+    }
+
+    /// The highest LedgerInfo committed by the execution pipeline, or the node synced to, in
+    /// decoupled execution mode.
+    pub fn highest_commit_proof(&self) -> Option<Arc<LedgerInfoWithSignatures>> {
+        self.inner.read().unwrap().highest_commit_proof()
+    }
+
+    /// Whether the ordered blocks failed to execute in decoupled execution mode, the node then
+    /// syncs to the next commit proof ahead of the root. It is reset when the tree is rebuilt.
+    pub fn execution_failed(&self) -> bool {
+        self.inner.read().unwrap().execution_failed()
+    }
+
+    pub fn set_execution_failed(&self) {
+        self.inner.write().unwrap().set_execution_failed()
+    }
+
     pub async fn rebuild(
         &self,
         root: RootInfo,
@@ -257,6 +372,7 @@ impl BlockStore {
             Arc::clone(&self.storage),
             max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
+            self.execution_pipeline.clone(),
         );
         let to_remove = self.inner.read().unwrap().get_all_block_id();
         if let Err(e) = self.storage.prune_tree(to_remove) {
@@ -273,7 +389,7 @@ impl BlockStore {
         // This introduces an inconsistent state if we send out SyncInfo and others try to sync to
         // B_i and figure out we only have B_j.
         // Here we commit up to the highest_commit_cert to maintain highest_commit_cert == state_computer.committed_trees.
-        if self.highest_commit_cert().commit_info().round() > self.root().round() {
+        if self.highest_commit_cert().commit_info().round() > self.ordered_root().round() {
             let finality_proof = self.highest_commit_cert().ledger_info().clone();
             if let Err(e) = self.commit(finality_proof).await {
                 warn!("{:?}", e);
@@ -326,6 +442,11 @@ impl BlockStore {
     fn execute_block(&self, block: Block) -> anyhow::Result<ExecutedBlock, Error> {
         trace_code_block!("block_store::execute_block", {"block", block.id()});
 
+        // The ordered blocks are executed later by the execution pipeline.
+        if self.decoupled_execution() {
+            return Ok(ExecutedBlock::new_ordered(block));
+        }
+
         // Although NIL blocks don't have a payload, we still send a T::default() to compute
         // because we may inject a block prologue transaction.
         let state_compute_result = self.state_computer.compute(&block, block.parent_id())?;
@@ -448,9 +569,11 @@ impl BlockReader for BlockStore {
     }
 
     fn sync_info(&self) -> SyncInfo {
-        SyncInfo::new(
+        SyncInfo::new_decoupled(
             self.highest_quorum_cert().as_ref().clone(),
             self.highest_commit_cert().as_ref().clone(),
+            self.highest_commit_proof()
+                .map(|proof| proof.as_ref().clone()),
             self.highest_timeout_cert().map(|tc| tc.as_ref().clone()),
         )
     }
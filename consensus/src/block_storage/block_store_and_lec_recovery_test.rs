@@ -88,6 +88,7 @@ fn build_inserter(
             state_computer,
             10, // max pruned blocks in mem
             Arc::new(SimulatedTimeService::new()),
+            None, // execution pipeline
         )),
     )
 }
@@ -8,6 +8,7 @@ use consensus_types::{
 };
 use libra_crypto::HashValue;
 use libra_logger::prelude::*;
+use libra_types::ledger_info::LedgerInfoWithSignatures;
 use mirai_annotations::{checked_verify_eq, precondition};
 use std::{
     collections::{vec_deque::VecDeque, HashMap, HashSet},
@@ -62,6 +63,9 @@ pub struct BlockTree {
     id_to_block: HashMap<HashValue, LinkableBlock>,
     /// Root of the tree.
     root_id: HashValue,
+    /// The last block sent for execution in decoupled execution mode, it is the same as the root
+    /// otherwise. The blocks between the root and the ordered root are being executed.
+    ordered_root_id: HashValue,
     /// A certified block id with highest round
     highest_certified_block_id: HashValue,
 
@@ -71,6 +75,11 @@ pub struct BlockTree {
     highest_timeout_cert: Option<Arc<TimeoutCertificate>>,
     /// The quorum certificate that has highest commit info.
     highest_commit_cert: Arc<QuorumCert>,
+    /// The highest LedgerInfo committed by the execution pipeline in decoupled execution mode.
+    highest_commit_proof: Option<Arc<LedgerInfoWithSignatures>>,
+    /// Whether the execution pipeline failed to execute ordered blocks, the blocks after the
+    /// root then have to be synced from the peers.
+    execution_failed: bool,
     /// Map of block id to its completed quorum certificate (2f + 1 votes)
     id_to_quorum_cert: HashMap<HashValue, Arc<QuorumCert>>,
     /// To keep the IDs of the elements that have been pruned from the tree but not cleaned up yet.
@@ -110,10 +119,13 @@ impl BlockTree {
         BlockTree {
             id_to_block,
             root_id,
+            ordered_root_id: root_id,
             highest_certified_block_id: root_id,
             highest_quorum_cert: Arc::clone(&root_quorum_cert),
             highest_timeout_cert,
             highest_commit_cert: Arc::new(root_ledger_info),
+            highest_commit_proof: None,
+            execution_failed: false,
             id_to_quorum_cert,
             pruned_block_ids,
             max_pruned_blocks_in_mem,
@@ -155,6 +167,16 @@ impl BlockTree {
         self.get_block(&self.root_id).expect("Root must exist")
     }
 
+    pub(super) fn ordered_root(&self) -> Arc<ExecutedBlock> {
+        self.get_block(&self.ordered_root_id)
+            .expect("Ordered root must exist")
+    }
+
+    pub(super) fn update_ordered_root(&mut self, ordered_root_id: HashValue) {
+        assert!(self.block_exists(&ordered_root_id));
+        self.ordered_root_id = ordered_root_id;
+    }
+
     pub(super) fn highest_certified_block(&self) -> Arc<ExecutedBlock> {
         self.get_block(&self.highest_certified_block_id)
             .expect("Highest cerfified block must exist")
@@ -177,6 +199,30 @@ impl BlockTree {
         Arc::clone(&self.highest_commit_cert)
     }
 
+    pub(super) fn highest_commit_proof(&self) -> Option<Arc<LedgerInfoWithSignatures>> {
+        self.highest_commit_proof.clone()
+    }
+
+    /// Replace the highest commit proof if the given one is higher and in the epoch of the tree.
+    pub(super) fn update_highest_commit_proof(&mut self, proof: LedgerInfoWithSignatures) {
+        if proof.ledger_info().epoch() != self.root().epoch() {
+            return;
+        }
+        if self.highest_commit_proof.as_ref().map_or(true, |highest| {
+            highest.ledger_info().round() < proof.ledger_info().round()
+        }) {
+            self.highest_commit_proof = Some(Arc::new(proof));
+        }
+    }
+
+    pub(super) fn execution_failed(&self) -> bool {
+        self.execution_failed
+    }
+
+    pub(super) fn set_execution_failed(&mut self) {
+        self.execution_failed = true;
+    }
+
     pub(super) fn get_quorum_cert_for_block(
         &self,
         block_id: &HashValue,
@@ -298,6 +344,9 @@ impl BlockTree {
         assert!(self.block_exists(&root_id));
         // Update the next root
         self.root_id = root_id;
+        if self.ordered_root().round() < self.root().round() {
+            self.ordered_root_id = root_id;
+        }
         counters::NUM_BLOCKS_IN_TREE.sub(newly_pruned_blocks.len() as i64);
         // The newly pruned blocks are pushed back to the deque pruned_block_ids.
         // In case the overall number of the elements is greater than the predefined threshold,
@@ -321,11 +370,29 @@ impl BlockTree {
     /// and getting its path from root (e.g., at proposal generator). Hence, we don't want to panic
     /// and prefer to return None instead.
     pub(super) fn path_from_root(&self, block_id: HashValue) -> Option<Vec<Arc<ExecutedBlock>>> {
+        self.path_from(self.root_id, block_id)
+    }
+
+    /// Returns all the blocks between the ordered root and the given block, including the given
+    /// block but excluding the ordered root.
+    pub(super) fn path_from_ordered_root(
+        &self,
+        block_id: HashValue,
+    ) -> Option<Vec<Arc<ExecutedBlock>>> {
+        self.path_from(self.ordered_root_id, block_id)
+    }
+
+    fn path_from(
+        &self,
+        ancestor_id: HashValue,
+        block_id: HashValue,
+    ) -> Option<Vec<Arc<ExecutedBlock>>> {
+        let ancestor_round = self.get_block(&ancestor_id)?.round();
         let mut res = vec![];
         let mut cur_block_id = block_id;
         loop {
             match self.get_block(&cur_block_id) {
-                Some(ref block) if block.round() <= self.root().round() => {
+                Some(ref block) if block.round() <= ancestor_round => {
                     break;
                 }
                 Some(block) => {
@@ -335,8 +402,8 @@ impl BlockTree {
                 None => return None,
             }
         }
-        // At this point cur_block.round() <= self.root.round()
-        if cur_block_id != self.root_id {
+        // At this point cur_block.round() <= ancestor_round
+        if cur_block_id != ancestor_id {
             return None;
         }
         // Called `.reverse()` to get the chronically increased order.
@@ -7,7 +7,7 @@ use crate::{
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
     state_replication::StateComputer,
 };
-use anyhow::{bail, format_err};
+use anyhow::{bail, ensure, format_err};
 use consensus_types::{
     block::Block,
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
@@ -16,7 +16,10 @@ use consensus_types::{
     sync_info::SyncInfo,
 };
 use libra_logger::prelude::*;
-use libra_types::{account_address::AccountAddress, epoch_change::EpochChangeProof};
+use libra_types::{
+    account_address::AccountAddress, epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+};
 use mirai_annotations::checked_precondition;
 use rand::{prelude::*, Rng};
 use std::{clone::Clone, sync::Arc, time::Duration};
@@ -41,10 +44,10 @@ impl BlockStore {
         checked_precondition!(self.root().round() < std::u64::MAX - 1);
 
         // If we have the block locally, we're not far from this QC thus don't need to sync.
-        // In case root().round() is greater than that the committed
+        // In case ordered_root().round() is greater than that the committed
         // block carried by LI is older than my current commit.
         !(self.block_exists(qc.commit_info().id())
-            || self.root().round() >= qc.commit_info().round())
+            || self.ordered_root().round() >= qc.commit_info().round())
     }
 
     /// Checks if quorum certificate can be inserted in block store without RPC
@@ -73,8 +76,17 @@ impl BlockStore {
         sync_info: &SyncInfo,
         mut retriever: BlockRetriever,
     ) -> anyhow::Result<()> {
-        self.sync_to_highest_commit_cert(sync_info.highest_commit_cert().clone(), &mut retriever)
-            .await?;
+        self.sync_to_highest_commit_cert(
+            sync_info.highest_commit_cert().clone(),
+            sync_info.highest_commit_proof(),
+            &mut retriever,
+        )
+        .await?;
+        // The synced ledger info ended the epoch, the remaining certificates belong to the
+        // previous epoch.
+        if self.root().epoch() > sync_info.epoch() {
+            return Ok(());
+        }
 
         self.insert_quorum_cert(sync_info.highest_commit_cert(), &mut retriever)
             .await?;
@@ -98,7 +110,7 @@ impl BlockStore {
             NeedFetchResult::QCBlockExist => self.insert_single_quorum_cert(qc.clone())?,
             _ => (),
         }
-        if self.root().round() < qc.commit_info().round() {
+        if self.ordered_root().round() < qc.commit_info().round() {
             let finality_proof = qc.ledger_info();
             self.commit(finality_proof.clone()).await?;
             if qc.ends_epoch() {
@@ -152,16 +164,42 @@ impl BlockStore {
     /// 2. We persist the 3-chain to storage before start sync to ensure we could restart if we
     /// crash in the middle of the sync.
     /// 3. We prune the old tree and replace with a new tree built with the 3-chain.
+    ///
+    /// In decoupled execution mode the commit cert only certifies the ordering, the node syncs to
+    /// the highest commit proof of the execution pipeline instead and the ordered blocks above it
+    /// are sent for execution again.
     async fn sync_to_highest_commit_cert(
         &self,
         highest_commit_cert: QuorumCert,
+        highest_commit_proof: Option<&LedgerInfoWithSignatures>,
         retriever: &mut BlockRetriever,
     ) -> anyhow::Result<()> {
-        if !self.need_sync_for_quorum_cert(&highest_commit_cert) {
+        // The blocks which failed to execute are synced even if their ordering is up to date.
+        if !self.need_sync_for_quorum_cert(&highest_commit_cert) && !self.execution_failed() {
             return Ok(());
         }
+        let highest_commit_proof = if self.decoupled_execution() {
+            match highest_commit_proof
+                .filter(|proof| proof.ledger_info().round() > self.root().round())
+            {
+                Some(proof) => Some(proof),
+                None => {
+                    // Without a newer commit proof the missing blocks are fetched when the
+                    // commit cert is inserted.
+                    debug!(
+                        "No commit proof ahead of the root {} to sync to {}",
+                        self.root(),
+                        highest_commit_cert.commit_info()
+                    );
+                    return Ok(());
+                }
+            }
+        } else {
+            None
+        };
         let (root, root_metadata, blocks, quorum_certs) = Self::fast_forward_sync(
             &highest_commit_cert,
+            highest_commit_proof,
             retriever,
             self.storage.clone(),
             self.state_computer.clone(),
@@ -172,11 +210,21 @@ impl BlockStore {
         self.rebuild(root, root_metadata, blocks, quorum_certs)
             .await;
 
-        if highest_commit_cert.ends_epoch() {
+        let synced_ledger_info = match highest_commit_proof {
+            Some(proof) => {
+                self.inner
+                    .write()
+                    .unwrap()
+                    .update_highest_commit_proof(proof.clone());
+                proof
+            }
+            None => highest_commit_cert.ledger_info(),
+        };
+        if synced_ledger_info.ledger_info().ends_epoch() {
             retriever
                 .network
                 .notify_epoch_change(EpochChangeProof::new(
-                    vec![highest_commit_cert.ledger_info().clone()],
+                    vec![synced_ledger_info.clone()],
                     /* more = */ false,
                 ))
                 .await;
@@ -184,12 +232,25 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Syncs to the highest commit cert, or to the given commit proof of the execution pipeline
+    /// in decoupled execution mode, and returns the recovery data of the synced tree.
     pub async fn fast_forward_sync<'a>(
         highest_commit_cert: &'a QuorumCert,
+        highest_commit_proof: Option<&'a LedgerInfoWithSignatures>,
         retriever: &'a mut BlockRetriever,
         storage: Arc<dyn PersistentLivenessStorage>,
         state_computer: Arc<dyn StateComputer>,
     ) -> anyhow::Result<RecoveryData> {
+        if let Some(commit_proof) = highest_commit_proof {
+            return Self::fast_forward_sync_to_commit_proof(
+                highest_commit_cert,
+                commit_proof,
+                retriever,
+                storage,
+                state_computer,
+            )
+            .await;
+        }
         debug!(
             "Start state sync with peer: {}, to block: {}",
             retriever.preferred_peer.short_str(),
@@ -227,6 +288,63 @@ impl BlockStore {
 
         Ok(recovery_data)
     }
+
+    /// The committed block of the commit proof becomes the new root: the chain from the block
+    /// certified by the highest commit cert down to it is retrieved, so that the new tree keeps
+    /// the ordered blocks above the root along with the quorum certs ordering the root.
+    async fn fast_forward_sync_to_commit_proof<'a>(
+        highest_commit_cert: &'a QuorumCert,
+        commit_proof: &'a LedgerInfoWithSignatures,
+        retriever: &'a mut BlockRetriever,
+        storage: Arc<dyn PersistentLivenessStorage>,
+        state_computer: Arc<dyn StateComputer>,
+    ) -> anyhow::Result<RecoveryData> {
+        debug!(
+            "Start state sync with peer: {}, to commit proof: {}",
+            retriever.preferred_peer.short_str(),
+            commit_proof.ledger_info(),
+        );
+        let committed_block_id = commit_proof.ledger_info().consensus_block_id();
+        let committed_round = commit_proof.ledger_info().round();
+        ensure!(
+            highest_commit_cert.commit_info().round() >= committed_round,
+            "Commit proof {} is ahead of the commit cert {}",
+            commit_proof.ledger_info(),
+            highest_commit_cert.commit_info()
+        );
+
+        let mut blocks = vec![];
+        let mut quorum_certs = vec![highest_commit_cert.clone()];
+        let mut retrieve_qc = highest_commit_cert.clone();
+        loop {
+            let mut retrieved = retriever.retrieve_block_for_qc(&retrieve_qc, 1).await?;
+            // retrieve_block_for_qc guarantees that blocks has exactly 1 element
+            let block = retrieved.remove(0);
+            ensure!(
+                block.round() >= committed_round,
+                "Committed block {} is not an ancestor of {}",
+                committed_block_id,
+                highest_commit_cert.certified_block()
+            );
+            let reached = block.id() == committed_block_id;
+            retrieve_qc = block.quorum_cert().clone();
+            blocks.push(block);
+            if reached {
+                break;
+            }
+            quorum_certs.push(retrieve_qc.clone());
+        }
+
+        // If a node restarts in the middle of state synchronization, it is going to try to catch up
+        // to the stored quorum certs as the new root.
+        storage.save_tree(blocks, quorum_certs)?;
+        state_computer.sync_to(commit_proof.clone()).await?;
+        let recovery_data = storage
+            .start()
+            .expect_recovery_data("Failed to construct recovery data after fast forward sync");
+
+        Ok(recovery_data)
+    }
 }
 
 /// BlockRetriever is used internally to retrieve blocks
@@ -6,12 +6,13 @@ use crate::{
     epoch_manager::EpochManager,
     network::NetworkTask,
     network_interface::{ConsensusNetworkEvents, ConsensusNetworkSender},
-    persistent_liveness_storage::StorageWriteProxy,
+    persistent_liveness_storage::{PersistentLivenessStorage, StorageWriteProxy},
     state_computer::ExecutionProxy,
     txn_manager::MempoolProxy,
     util::time_service::ClockTimeService,
 };
 use channel::libra_channel;
+use consensus_types::equivocation_evidence::EquivocationEvidence;
 use execution_correctness::ExecutionCorrectnessManager;
 use futures::channel::mpsc;
 use libra_config::config::NodeConfig;
@@ -23,7 +24,26 @@ use std::sync::Arc;
 use storage_interface::DbReader;
 use tokio::runtime::{self, Runtime};
 
-/// Helper function to start consensus based on configuration and return the runtime
+/// Read access to the state of consensus for the node debug interface.
+#[derive(Clone)]
+pub struct ConsensusDebugHandle {
+    storage: Arc<dyn PersistentLivenessStorage>,
+}
+
+impl ConsensusDebugHandle {
+    /// Returns the evidence of the equivocations recorded by this node.
+    pub fn get_equivocation_evidence(&self) -> Vec<EquivocationEvidence> {
+        self.storage
+            .retrieve_equivocation_evidence()
+            .unwrap_or_else(|e| {
+                error!("Failed to retrieve equivocation evidence: {:?}", e);
+                vec![]
+            })
+    }
+}
+
+/// Helper function to start consensus based on configuration and return the runtime along with
+/// the handle for the debug interface
 pub fn start_consensus(
     node_config: &mut NodeConfig,
     network_sender: ConsensusNetworkSender,
@@ -32,7 +52,7 @@ pub fn start_consensus(
     consensus_to_mempool_sender: mpsc::Sender<ConsensusRequest>,
     libra_db: Arc<dyn DbReader>,
     reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
-) -> Runtime {
+) -> (Runtime, ConsensusDebugHandle) {
     let runtime = runtime::Builder::new()
         .thread_name("consensus-")
         .threaded_scheduler()
@@ -40,6 +60,9 @@ pub fn start_consensus(
         .build()
         .expect("Failed to create Tokio runtime!");
     let storage = Arc::new(StorageWriteProxy::new(node_config, libra_db));
+    let debug_handle = ConsensusDebugHandle {
+        storage: storage.clone(),
+    };
     let txn_manager = Arc::new(MempoolProxy::new(consensus_to_mempool_sender));
     let execution_correctness_manager = ExecutionCorrectnessManager::new(node_config);
     let state_computer = Arc::new(ExecutionProxy::new(
@@ -50,6 +73,8 @@ pub fn start_consensus(
 
     let (timeout_sender, timeout_receiver) = channel::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
     let (self_sender, self_receiver) = channel::new(1_024, &counters::PENDING_SELF_MESSAGES);
+    let (execution_event_sender, execution_event_receiver) =
+        channel::new(1_024, &counters::PENDING_EXECUTION_EVENTS);
 
     let epoch_mgr = EpochManager::new(
         node_config,
@@ -57,6 +82,7 @@ pub fn start_consensus(
         self_sender,
         network_sender,
         timeout_sender,
+        execution_event_sender,
         txn_manager,
         state_computer,
         storage,
@@ -65,8 +91,13 @@ pub fn start_consensus(
     let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
     runtime.spawn(network_task.start());
-    runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
+    runtime.spawn(epoch_mgr.start(
+        timeout_receiver,
+        network_receiver,
+        reconfig_events,
+        execution_event_receiver,
+    ));
 
     debug!("Consensus started.");
-    runtime
+    (runtime, debug_handle)
 }
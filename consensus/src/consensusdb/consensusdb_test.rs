@@ -4,6 +4,7 @@
 use super::*;
 use consensus_types::block::block_test_utils::certificate_for_genesis;
 use libra_temppath::TempPath;
+use libra_types::validator_signer::ValidatorSigner;
 
 #[test]
 fn test_put_get() {
@@ -48,3 +49,34 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_save_equivocation_evidence() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+    assert!(db.get_equivocation_evidence().unwrap().is_empty());
+
+    let signer = ValidatorSigner::random(None);
+    let evidence = EquivocationEvidence::new_proposal_evidence(
+        Block::new_proposal(vec![], 1, 1, certificate_for_genesis(), &signer),
+        Block::new_proposal(vec![], 1, 2, certificate_for_genesis(), &signer),
+    );
+    assert!(db.save_equivocation_evidence(&evidence).unwrap());
+    // The same evidence is stored only once
+    assert!(!db.save_equivocation_evidence(&evidence).unwrap());
+    // Only the first evidence against an author in an epoch is kept
+    let later_evidence = EquivocationEvidence::new_proposal_evidence(
+        Block::new_proposal(vec![], 2, 1, certificate_for_genesis(), &signer),
+        Block::new_proposal(vec![], 2, 2, certificate_for_genesis(), &signer),
+    );
+    assert!(!db.save_equivocation_evidence(&later_evidence).unwrap());
+    assert_eq!(db.get_equivocation_evidence().unwrap(), vec![evidence]);
+
+    let other_signer = ValidatorSigner::random(None);
+    let other_evidence = EquivocationEvidence::new_proposal_evidence(
+        Block::new_proposal(vec![], 1, 1, certificate_for_genesis(), &other_signer),
+        Block::new_proposal(vec![], 1, 2, certificate_for_genesis(), &other_signer),
+    );
+    assert!(db.save_equivocation_evidence(&other_evidence).unwrap());
+    assert_eq!(db.get_equivocation_evidence().unwrap().len(), 2);
+}
@@ -7,14 +7,17 @@ mod schema;
 
 use crate::consensusdb::schema::{
     block::{BlockSchema, SchemaBlock},
+    equivocation_evidence::EquivocationEvidenceSchema,
     quorum_certificate::QCSchema,
     single_entry::{SingleEntryKey, SingleEntrySchema},
 };
-use anyhow::{ensure, Result};
-use consensus_types::{block::Block, quorum_cert::QuorumCert};
+use anyhow::{ensure, format_err, Result};
+use consensus_types::{
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+};
 use libra_crypto::HashValue;
 use libra_logger::prelude::*;
-use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
+use schema::{BLOCK_CF_NAME, EQUIVOCATION_EVIDENCE_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
 use schemadb::{ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
@@ -29,6 +32,7 @@ impl ConsensusDB {
             BLOCK_CF_NAME,
             QC_CF_NAME,
             SINGLE_ENTRY_CF_NAME,
+            EQUIVOCATION_EVIDENCE_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join("consensusdb");
@@ -127,6 +131,33 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Persist the evidence of an equivocation, returns false if evidence against the same author
+    /// in the same epoch is already stored. Only the first evidence is kept, so that a faulty
+    /// validator equivocating in every round can't grow the storage without bound.
+    pub fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<bool> {
+        let author = evidence
+            .author()
+            .ok_or_else(|| format_err!("Equivocation evidence without author: {}", evidence))?;
+        let key = (evidence.epoch(), author);
+        if self.db.get::<EquivocationEvidenceSchema>(&key)?.is_some() {
+            return Ok(false);
+        }
+        let mut batch = SchemaBatch::new();
+        batch.put::<EquivocationEvidenceSchema>(&key, evidence)?;
+        self.commit(batch)?;
+        Ok(true)
+    }
+
+    /// Get all the evidence of equivocations.
+    pub fn get_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        let mut iter = self
+            .db
+            .iter::<EquivocationEvidenceSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|value| value.map(|(_key, evidence)| evidence))
+            .collect::<Result<Vec<EquivocationEvidence>>>()
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<()> {
@@ -0,0 +1,65 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the evidence of equivocations.
+//!
+//! Serialized evidence bytes identified by the epoch and the author of the equivocation, only
+//! the first evidence of an author in an epoch is stored.
+//! ```text
+//! |<-----key------>|<--------value-------->|
+//! | epoch | author | EquivocationEvidence  |
+//! ```
+
+use super::{ensure_slice_len_eq, EQUIVOCATION_EVIDENCE_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use consensus_types::{common::Author, equivocation_evidence::EquivocationEvidence};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    EquivocationEvidenceSchema,
+    Key,
+    EquivocationEvidence,
+    EQUIVOCATION_EVIDENCE_CF_NAME
+);
+
+type Epoch = u64;
+pub(crate) type Key = (Epoch, Author);
+
+impl KeyCodec<EquivocationEvidenceSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (epoch, ref author) = *self;
+
+        let mut encoded = vec![];
+        encoded.write_u64::<BigEndian>(epoch)?;
+        encoded.extend_from_slice(author.as_ref());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let epoch = (&data[..size_of::<Epoch>()]).read_u64::<BigEndian>()?;
+        let author = Author::try_from(&data[size_of::<Epoch>()..])?;
+
+        Ok((epoch, author))
+    }
+}
+
+impl ValueCodec<EquivocationEvidenceSchema> for EquivocationEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(lcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(lcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,20 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use libra_types::validator_signer::ValidatorSigner;
+use schemadb::schema::assert_encode_decode;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let evidence = EquivocationEvidence::new_proposal_evidence(
+        Block::new_proposal(vec![], 1, 1, certificate_for_genesis(), &signer),
+        Block::new_proposal(vec![], 1, 2, certificate_for_genesis(), &signer),
+    );
+    assert_encode_decode::<EquivocationEvidenceSchema>(
+        &(evidence.epoch(), signer.author()),
+        &evidence,
+    );
+}
@@ -138,6 +138,20 @@ pub static SYNC_INFO_MSGS_SENT_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+//////////////////////
+// EQUIVOCATION COUNTERS
+//////////////////////
+/// Count of the equivocations recorded since last restart, detected locally or gossiped by the
+/// peers. kind is vote or proposal.
+pub static EQUIVOCATION_EVIDENCE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_consensus_equivocation_evidence_count",
+        "Count of the equivocations recorded since last restart. kind is vote or proposal",
+        &["kind"]
+    )
+    .unwrap()
+});
+
 //////////////////////
 // RECONFIGURATION COUNTERS
 //////////////////////
@@ -188,6 +202,34 @@ pub static NUM_TXNS_PER_BLOCK: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Histogram for the number of certified batches per proposed block in batch dissemination mode.
+pub static NUM_BATCHES_PER_BLOCK: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "libra_consensus_num_batches_per_block",
+        "Histogram for the number of certified batches per proposed block."
+    )
+    .unwrap()
+});
+
+/// Count of the batches created by this validator since last restart.
+pub static CREATED_BATCHES_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_created_batches_count",
+        "Count of the batches created by this validator since last restart."
+    )
+    .unwrap()
+});
+
+/// Count of the batches fetched from the peers because they were missing when processing a
+/// proposal.
+pub static FETCHED_BATCHES_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_consensus_fetched_batches_count",
+        "Count of the batches fetched from the peers when processing a proposal."
+    )
+    .unwrap()
+});
+
 /// Histogram of the time it takes for a block to get committed.
 /// Measured as the commit time minus block's timestamp.
 pub static CREATION_TO_COMMIT_S: Lazy<DurationHistogram> = Lazy::new(|| {
@@ -237,6 +279,24 @@ pub static PENDING_ROUND_TIMEOUTS: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the pending requests to the execution pipeline
+pub static PENDING_EXECUTION_REQUESTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_consensus_pending_execution_requests",
+        "Count of the pending requests to the execution pipeline"
+    )
+    .unwrap()
+});
+
+/// Count of the pending events from the execution pipeline
+pub static PENDING_EXECUTION_EVENTS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_consensus_pending_execution_events",
+        "Count of the pending events from the execution pipeline"
+    )
+    .unwrap()
+});
+
 /// Counter of pending network events to Consensus
 pub static PENDING_CONSENSUS_NETWORK_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -266,3 +326,13 @@ pub static BLOCK_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Counters(queued,dequeued,dropped) related to batch retrieval channel
+pub static BATCH_RETRIEVAL_CHANNEL_MSGS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_consensus_batch_retrieval_channel_msgs_count",
+        "Counters(queued,dequeued,dropped) related to batch retrieval channel",
+        &["state"]
+    )
+    .unwrap()
+});
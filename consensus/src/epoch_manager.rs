@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    batch_manager::BatchManager,
     block_storage::BlockStore,
     counters,
+    execution_pipeline::{ExecutionEvent, ExecutionPipeline},
     liveness::{
         leader_reputation::{ActiveInactiveHeuristic, LeaderReputation, LibraDBBackend},
         proposal_generator::ProposalGenerator,
@@ -13,9 +15,16 @@ use crate::{
         round_state::{ExponentialTimeInterval, RoundState},
     },
     metrics_safety_rules::MetricsSafetyRules,
-    network::{IncomingBlockRetrievalRequest, NetworkReceivers, NetworkSender},
+    network::{
+        IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, NetworkReceivers,
+        NetworkSender,
+    },
     network_interface::{ConsensusMsg, ConsensusNetworkSender},
     persistent_liveness_storage::{LedgerRecoveryData, PersistentLivenessStorage, RecoveryData},
+    recorder::{
+        ConsensusRecorder, RecordedEpochStart, RecordedEvent, RecordingProposerElection,
+        RecordingStateComputer, RecordingTimeService,
+    },
     round_manager::{RecoveryManager, RoundManager, UnverifiedEvent, VerifiedEvent},
     state_replication::{StateComputer, TxnManager},
     util::time_service::TimeService,
@@ -37,7 +46,7 @@ use libra_types::{
     on_chain_config::{OnChainConfigPayload, ValidatorSet},
 };
 use network::protocols::network::Event;
-use safety_rules::SafetyRulesManager;
+use safety_rules::{SafetyRulesManager, TSafetyRules};
 use std::{cmp::Ordering, sync::Arc, time::Duration};
 
 /// RecoveryManager is used to process events in order to sync up with peer if we can't recover from local consensusdb
@@ -73,11 +82,13 @@ pub struct EpochManager {
     self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
     network_sender: ConsensusNetworkSender,
     timeout_sender: channel::Sender<Round>,
+    execution_event_sender: channel::Sender<ExecutionEvent>,
     txn_manager: Arc<dyn TxnManager>,
     state_computer: Arc<dyn StateComputer>,
     storage: Arc<dyn PersistentLivenessStorage>,
     safety_rules_manager: SafetyRulesManager,
     processor: Option<RoundProcessor>,
+    recorder: Option<Arc<ConsensusRecorder>>,
 }
 
 impl EpochManager {
@@ -87,6 +98,7 @@ impl EpochManager {
         self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
         network_sender: ConsensusNetworkSender,
         timeout_sender: channel::Sender<Round>,
+        execution_event_sender: channel::Sender<ExecutionEvent>,
         txn_manager: Arc<dyn TxnManager>,
         state_computer: Arc<dyn StateComputer>,
         storage: Arc<dyn PersistentLivenessStorage>,
@@ -95,6 +107,30 @@ impl EpochManager {
         let config = node_config.consensus.clone();
         let sr_config = &mut node_config.consensus.safety_rules;
         let safety_rules_manager = SafetyRulesManager::new(sr_config);
+        let recorder = match &config.recording_path {
+            Some(path) => match ConsensusRecorder::new(path) {
+                Ok(recorder) => {
+                    info!("Recording consensus to {:?}", path);
+                    Some(Arc::new(recorder))
+                }
+                Err(e) => {
+                    error!("[EpochManager] Consensus is not recorded: {:?}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let (time_service, state_computer): (Arc<dyn TimeService>, Arc<dyn StateComputer>) =
+            match &recorder {
+                Some(recorder) => (
+                    Arc::new(RecordingTimeService::new(time_service, recorder.clone())),
+                    Arc::new(RecordingStateComputer::new(
+                        state_computer,
+                        recorder.clone(),
+                    )),
+                ),
+                None => (time_service, state_computer),
+            };
         Self {
             author,
             config,
@@ -102,11 +138,13 @@ impl EpochManager {
             self_sender,
             network_sender,
             timeout_sender,
+            execution_event_sender,
             txn_manager,
             state_computer,
             storage,
             safety_rules_manager,
             processor: None,
+            recorder,
         }
     }
 
@@ -267,6 +305,29 @@ impl EpochManager {
             recovery_data.root_block(),
         );
         let last_vote = recovery_data.last_vote();
+        let epoch_start = self
+            .recorder
+            .as_ref()
+            .map(|_| RecordedEpochStart::new(epoch_state.clone(), &recovery_data));
+
+        let execution_pipeline = if self.config.decoupled_execution {
+            info!("Create ExecutionPipeline");
+            // The pipeline stops once the BlockStore of this epoch is dropped.
+            let (execution_request_sender, execution_request_receiver) =
+                channel::new(1_024, &counters::PENDING_EXECUTION_REQUESTS);
+            tokio::spawn(
+                ExecutionPipeline::new(
+                    Arc::clone(&self.state_computer),
+                    Arc::clone(&self.txn_manager),
+                    execution_request_receiver,
+                    self.execution_event_sender.clone(),
+                )
+                .start(),
+            );
+            Some(execution_request_sender)
+        } else {
+            None
+        };
 
         info!("Create BlockStore");
         let block_store = Arc::new(BlockStore::new(
@@ -275,6 +336,7 @@ impl EpochManager {
             Arc::clone(&self.state_computer),
             self.config.max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
+            execution_pipeline,
         ));
 
         info!("Update SafetyRules");
@@ -285,13 +347,43 @@ impl EpochManager {
             .perform_initialize()
             .expect("Unable to initialize SafetyRules");
 
+        if let (Some(recorder), Some(mut epoch_start)) = (&self.recorder, epoch_start) {
+            match safety_rules.consensus_state() {
+                Ok(consensus_state) => {
+                    epoch_start.last_voted_round = consensus_state.last_voted_round();
+                    epoch_start.preferred_round = consensus_state.preferred_round();
+                    epoch_start.decoupled_execution = self.config.decoupled_execution;
+                    epoch_start.batch_dissemination = self.config.batch_dissemination;
+                    recorder.record(RecordedEvent::EpochStart(Box::new(epoch_start)));
+                }
+                Err(e) => error!("[EpochManager] Epoch start is not recorded: {:?}", e),
+            }
+        }
+
+        let batch_manager = if self.config.batch_dissemination {
+            info!("Create BatchManager");
+            Some(Arc::new(BatchManager::new(
+                self.author,
+                epoch_state.epoch,
+                self.txn_manager.clone(),
+                &self.config,
+            )))
+        } else {
+            None
+        };
+
         info!("Create ProposalGenerator");
         // txn manager is required both by proposal generator (to pull the proposers)
         // and by event processor (to update their status).
+        // In batch dissemination mode the proposals are made of certified batches.
+        let proposal_txn_manager: Arc<dyn TxnManager> = match &batch_manager {
+            Some(batch_manager) => batch_manager.clone(),
+            None => self.txn_manager.clone(),
+        };
         let proposal_generator = ProposalGenerator::new(
             self.author,
             block_store.clone(),
-            self.txn_manager.clone(),
+            proposal_txn_manager,
             self.time_service.clone(),
             self.config.max_block_size,
         );
@@ -301,12 +393,19 @@ impl EpochManager {
             self.create_round_state(self.time_service.clone(), self.timeout_sender.clone());
 
         info!("Create ProposerElection");
-        let proposer_election = self.create_proposer_election(&epoch_state);
+        let mut proposer_election = self.create_proposer_election(&epoch_state);
+        if let Some(recorder) = &self.recorder {
+            proposer_election = Box::new(RecordingProposerElection::new(
+                proposer_election,
+                recorder.clone(),
+            ));
+        }
         let network_sender = NetworkSender::new(
             self.author,
             self.network_sender.clone(),
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
+            self.recorder.clone(),
         );
 
         let mut processor = RoundManager::new(
@@ -319,6 +418,8 @@ impl EpochManager {
             network_sender,
             self.txn_manager.clone(),
             self.storage.clone(),
+            batch_manager,
+            self.config.gossip_equivocation_evidence,
         );
         processor.start(last_vote).await;
         self.processor = Some(RoundProcessor::Normal(processor));
@@ -339,6 +440,7 @@ impl EpochManager {
             self.network_sender.clone(),
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
+            None,
         );
         self.processor = Some(RoundProcessor::Recovery(RecoveryManager::new(
             epoch_state,
@@ -346,6 +448,7 @@ impl EpochManager {
             self.storage.clone(),
             self.state_computer.clone(),
             ledger_recovery_data.commit_round(),
+            self.config.decoupled_execution,
         )));
         info!("SyncProcessor started");
     }
@@ -405,7 +508,15 @@ impl EpochManager {
         msg: ConsensusMsg,
     ) -> anyhow::Result<Option<UnverifiedEvent>> {
         match msg {
-            ConsensusMsg::ProposalMsg(_) | ConsensusMsg::SyncInfo(_) | ConsensusMsg::VoteMsg(_) => {
+            ConsensusMsg::ProposalMsg(_)
+            | ConsensusMsg::SyncInfo(_)
+            | ConsensusMsg::VoteMsg(_)
+            | ConsensusMsg::CommitVoteMsg(_)
+            | ConsensusMsg::BatchMsg(_)
+            | ConsensusMsg::BatchVoteMsg(_)
+            | ConsensusMsg::AvailabilityCertificate(_)
+            | ConsensusMsg::CompactProposalMsg(_)
+            | ConsensusMsg::EquivocationEvidence(_) => {
                 let event: UnverifiedEvent = msg.into();
                 if event.epoch() == self.epoch() {
                     return Ok(Some(event));
@@ -452,18 +563,7 @@ impl EpochManager {
                 self.start_round_manager(recovery_data, epoch_state).await;
                 Ok(())
             }
-            RoundProcessor::Normal(p) => match event {
-                VerifiedEvent::ProposalMsg(proposal) => {
-                    monitor!("process_proposal", p.process_proposal_msg(*proposal).await)
-                }
-                VerifiedEvent::VoteMsg(vote) => {
-                    monitor!("process_vote", p.process_vote_msg(*vote).await)
-                }
-                VerifiedEvent::SyncInfo(sync_info) => monitor!(
-                    "process_sync_info",
-                    p.process_sync_info_msg(*sync_info, peer_id).await
-                ),
-            },
+            RoundProcessor::Normal(p) => p.process_event(peer_id, event).await,
         }
     }
 
@@ -483,6 +583,16 @@ impl EpochManager {
         }
     }
 
+    pub async fn process_batch_retrieval(
+        &mut self,
+        request: IncomingBatchRetrievalRequest,
+    ) -> anyhow::Result<()> {
+        match self.processor_mut() {
+            RoundProcessor::Normal(p) => p.process_batch_retrieval(request).await,
+            _ => bail!("[EpochManager] RoundManager not started yet"),
+        }
+    }
+
     pub async fn process_local_timeout(&mut self, round: u64) -> anyhow::Result<()> {
         match self.processor_mut() {
             RoundProcessor::Normal(p) => p.process_local_timeout(round).await,
@@ -490,11 +600,19 @@ impl EpochManager {
         }
     }
 
+    pub async fn process_execution_event(&mut self, event: ExecutionEvent) -> anyhow::Result<()> {
+        match self.processor_mut() {
+            RoundProcessor::Normal(p) => p.process_execution_event(event).await,
+            _ => bail!("[EpochManager] RoundManager not started yet"),
+        }
+    }
+
     pub async fn start(
         mut self,
         mut round_timeout_sender_rx: channel::Receiver<Round>,
         mut network_receivers: NetworkReceivers,
         mut reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
+        mut execution_events: channel::Receiver<ExecutionEvent>,
     ) {
         // initial start of the processor
         if let Some(payload) = reconfig_events.next().await {
@@ -509,14 +627,26 @@ impl EpochManager {
                         Ok(())
                     }
                     msg = network_receivers.consensus_messages.select_next_some() => {
+                        if let Some(recorder) = &self.recorder {
+                            recorder.record(RecordedEvent::Message(msg.0, msg.1.clone()));
+                        }
                         monitor!("process_message", self.process_message(msg.0, msg.1).await)
                     }
                     block_retrieval = network_receivers.block_retrieval.select_next_some() => {
                         monitor!("process_block_retrieval", self.process_block_retrieval(block_retrieval).await)
                     }
+                    batch_retrieval = network_receivers.batch_retrieval.select_next_some() => {
+                        monitor!("process_batch_retrieval", self.process_batch_retrieval(batch_retrieval).await)
+                    }
                     round = round_timeout_sender_rx.select_next_some() => {
+                        if let Some(recorder) = &self.recorder {
+                            recorder.record(RecordedEvent::LocalTimeout(round));
+                        }
                         monitor!("process_local_timeout", self.process_local_timeout(round).await)
                     }
+                    event = execution_events.select_next_some() => {
+                        monitor!("process_execution_event", self.process_execution_event(event).await)
+                    }
                 }
             ) {
                 counters::ERROR_COUNT.inc();
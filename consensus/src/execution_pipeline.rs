@@ -0,0 +1,366 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! In decoupled execution mode consensus only orders blocks: the ordered blocks are sent to the
+//! execution pipeline, which executes them in order off the critical path of voting. The
+//! execution result of a prefix of ordered blocks is certified by a quorum of commit votes
+//! before it is committed.
+
+use crate::{
+    counters,
+    state_replication::{StateComputer, TxnManager},
+};
+use anyhow::Context;
+use consensus_types::{
+    block::Block,
+    commit_vote::CommitVote,
+    common::{Author, Round},
+    executed_block::ExecutedBlock,
+    vote_proposal::MaybeSignedVoteProposal,
+};
+use futures::{SinkExt, StreamExt};
+use libra_crypto::{ed25519::Ed25519Signature, hash::CryptoHash, HashValue};
+use libra_logger::prelude::*;
+use libra_types::{
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::ValidatorVerifier,
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    sync::Arc,
+};
+use tokio::task;
+
+#[cfg(test)]
+#[path = "execution_pipeline_test.rs"]
+mod execution_pipeline_test;
+
+/// Commit votes are only kept for the rounds within this window above the last commit, so that
+/// the memory used by the votes is bounded. A node falling further behind catches up by syncing.
+pub const COMMIT_VOTE_ROUND_WINDOW: Round = 100;
+
+/// A chain of blocks whose ordering has been certified by the given ordering proof, the last
+/// block is the one committed by the proof.
+#[derive(Clone, Debug)]
+pub struct OrderedBlocks {
+    pub blocks: Vec<Block>,
+    pub ordered_proof: LedgerInfoWithSignatures,
+}
+
+/// The ordered blocks after execution.
+#[derive(Clone, Debug)]
+pub struct ExecutedBlocks {
+    pub blocks: Vec<Arc<ExecutedBlock>>,
+    pub ordered_proof: LedgerInfoWithSignatures,
+}
+
+impl ExecutedBlocks {
+    /// The LedgerInfo carrying the execution result of the last block, this is what the commit
+    /// votes sign.
+    pub fn ledger_info(&self) -> LedgerInfo {
+        let last_block = self
+            .blocks
+            .last()
+            .expect("ExecutedBlocks should not be empty");
+        LedgerInfo::new(
+            last_block.block_info(),
+            self.ordered_proof.ledger_info().consensus_data_hash(),
+        )
+    }
+
+    /// The vote proposal of the last block with its execution result, which backs the
+    /// LedgerInfo signed by the commit votes.
+    pub fn maybe_signed_vote_proposal(&self) -> MaybeSignedVoteProposal {
+        self.blocks
+            .last()
+            .expect("ExecutedBlocks should not be empty")
+            .maybe_signed_vote_proposal()
+    }
+
+    pub fn round(&self) -> Round {
+        self.ordered_proof.ledger_info().round()
+    }
+}
+
+/// Requests processed by the execution pipeline in order.
+#[derive(Debug)]
+pub enum ExecutionRequest {
+    /// Execute the ordered blocks on top of the previously executed ones.
+    Execute(OrderedBlocks),
+    /// Commit the executed blocks with the quorum of commit votes.
+    Commit(ExecutedBlocks, LedgerInfoWithSignatures),
+}
+
+/// Events sent back from the execution pipeline to consensus.
+#[derive(Debug)]
+pub enum ExecutionEvent {
+    /// The ordered blocks have been executed and are waiting for commit votes.
+    Executed(ExecutedBlocks),
+    /// The executed blocks have been committed with the given proof.
+    Committed(ExecutedBlocks, LedgerInfoWithSignatures),
+    /// The ordered blocks certified by the given proof failed to execute, the blocks are
+    /// dropped and consensus has to sync to catch up on their execution result.
+    ExecutionFailed(LedgerInfoWithSignatures),
+}
+
+/// Executes and commits the ordered blocks sent by consensus. It runs in its own task for an
+/// epoch and stops once all the request senders are dropped.
+pub struct ExecutionPipeline {
+    state_computer: Arc<dyn StateComputer>,
+    txn_manager: Arc<dyn TxnManager>,
+    requests: channel::Receiver<ExecutionRequest>,
+    events: channel::Sender<ExecutionEvent>,
+}
+
+impl ExecutionPipeline {
+    pub fn new(
+        state_computer: Arc<dyn StateComputer>,
+        txn_manager: Arc<dyn TxnManager>,
+        requests: channel::Receiver<ExecutionRequest>,
+        events: channel::Sender<ExecutionEvent>,
+    ) -> Self {
+        Self {
+            state_computer,
+            txn_manager,
+            requests,
+            events,
+        }
+    }
+
+    pub async fn start(mut self) {
+        while let Some(request) = self.requests.next().await {
+            let event = match request {
+                ExecutionRequest::Execute(ordered_blocks) => {
+                    let ordered_proof = ordered_blocks.ordered_proof.clone();
+                    match self.execute(ordered_blocks).await {
+                        Ok(executed_blocks) => Ok(ExecutionEvent::Executed(executed_blocks)),
+                        Err(e) => {
+                            counters::ERROR_COUNT.inc();
+                            error!("[ExecutionPipeline] {:?}", e);
+                            Ok(ExecutionEvent::ExecutionFailed(ordered_proof))
+                        }
+                    }
+                }
+                ExecutionRequest::Commit(executed_blocks, commit_proof) => self
+                    .commit(executed_blocks, commit_proof)
+                    .await
+                    .map(|(blocks, proof)| ExecutionEvent::Committed(blocks, proof)),
+            };
+            match event {
+                Ok(event) => {
+                    if let Err(e) = self.events.send(event).await {
+                        error!("[ExecutionPipeline] Failed to send event: {:?}", e);
+                    }
+                }
+                Err(e) => {
+                    counters::ERROR_COUNT.inc();
+                    error!("[ExecutionPipeline] {:?}", e);
+                }
+            }
+        }
+        debug!("[ExecutionPipeline] Stopped");
+    }
+
+    async fn execute(&self, ordered_blocks: OrderedBlocks) -> anyhow::Result<ExecutedBlocks> {
+        let mut blocks = vec![];
+        for block in ordered_blocks.blocks {
+            // The executor takes care of the blocks following a reconfiguration: they inherit
+            // the result of the reconfiguration block without executing their transactions.
+            // Executing a block is CPU heavy, keep it off the async worker threads.
+            let state_computer = Arc::clone(&self.state_computer);
+            let (block, compute_result) = task::spawn_blocking(move || {
+                let compute_result = state_computer.compute(&block, block.parent_id());
+                (block, compute_result)
+            })
+            .await
+            .context("[ExecutionPipeline] Execution task failed")?;
+            let compute_result = compute_result
+                .context(format!("[ExecutionPipeline] Failed to execute {}", block))?;
+            let executed_block = Arc::new(ExecutedBlock::new(block, compute_result));
+            // notify mempool about failed txn
+            if let Err(e) = self
+                .txn_manager
+                .notify(executed_block.block(), executed_block.compute_result())
+                .await
+            {
+                error!(
+                    "[ExecutionPipeline] Failed to notify mempool of rejected txns: {:?}",
+                    e
+                );
+            }
+            blocks.push(executed_block);
+        }
+        Ok(ExecutedBlocks {
+            blocks,
+            ordered_proof: ordered_blocks.ordered_proof,
+        })
+    }
+
+    async fn commit(
+        &self,
+        executed_blocks: ExecutedBlocks,
+        commit_proof: LedgerInfoWithSignatures,
+    ) -> anyhow::Result<(ExecutedBlocks, LedgerInfoWithSignatures)> {
+        self.state_computer
+            .commit(
+                executed_blocks.blocks.iter().map(|b| b.id()).collect(),
+                commit_proof.clone(),
+            )
+            .await
+            .context("[ExecutionPipeline] Failed to persist commit")?;
+        Ok((executed_blocks, commit_proof))
+    }
+}
+
+/// Keeps the locally executed blocks that are waiting for commit votes along with the commit
+/// votes received so far. Once a quorum of commit votes matches a local execution result, the
+/// executed blocks up to it are ready to be committed.
+pub struct PendingCommits {
+    /// Locally executed blocks in the order they were executed.
+    executed: VecDeque<ExecutedBlocks>,
+    /// The commit votes grouped by the hash of the LedgerInfo they sign.
+    votes: HashMap<HashValue, (LedgerInfo, BTreeMap<Author, Ed25519Signature>)>,
+    /// The hash of the LedgerInfo signed by the latest commit vote of each author: only that
+    /// vote is kept, as a quorum on a LedgerInfo also certifies the ones of its ancestors.
+    latest_votes: HashMap<Author, HashValue>,
+    /// The round of the last block ready to be committed, older votes are ignored.
+    committed_round: Round,
+}
+
+impl PendingCommits {
+    pub fn new(committed_round: Round) -> Self {
+        Self {
+            executed: VecDeque::new(),
+            votes: HashMap::new(),
+            latest_votes: HashMap::new(),
+            committed_round,
+        }
+    }
+
+    /// Add the locally executed blocks, returns them with the commit proof in case the commit
+    /// votes for them have already been collected.
+    pub fn add_executed(
+        &mut self,
+        executed_blocks: ExecutedBlocks,
+        verifier: &ValidatorVerifier,
+    ) -> Option<(ExecutedBlocks, LedgerInfoWithSignatures)> {
+        if executed_blocks.round() <= self.committed_round {
+            return None;
+        }
+        let ledger_info_hash = executed_blocks.ledger_info().hash();
+        self.executed.push_back(executed_blocks);
+        self.try_commit(ledger_info_hash, verifier)
+    }
+
+    /// Add a verified commit vote, returns the executed blocks with the commit proof in case
+    /// the vote completes a quorum for a local execution result. Votes outside of the round
+    /// window above the last commit, or older than the latest vote of their author, are ignored.
+    pub fn add_commit_vote(
+        &mut self,
+        commit_vote: &CommitVote,
+        verifier: &ValidatorVerifier,
+    ) -> Option<(ExecutedBlocks, LedgerInfoWithSignatures)> {
+        if commit_vote.round() <= self.committed_round
+            || commit_vote.round() > self.committed_round + COMMIT_VOTE_ROUND_WINDOW
+        {
+            return None;
+        }
+        let author = commit_vote.author();
+        let ledger_info_hash = commit_vote.ledger_info().hash();
+        if let Some(previous_hash) = self.latest_votes.get(&author).copied() {
+            if previous_hash == ledger_info_hash {
+                return None;
+            }
+            if let Some((previous_ledger_info, signatures)) = self.votes.get_mut(&previous_hash) {
+                if previous_ledger_info.round() > commit_vote.round() {
+                    return None;
+                }
+                signatures.remove(&author);
+                if signatures.is_empty() {
+                    self.votes.remove(&previous_hash);
+                }
+            }
+        }
+        self.latest_votes.insert(author, ledger_info_hash);
+        self.votes
+            .entry(ledger_info_hash)
+            .or_insert_with(|| (commit_vote.ledger_info().clone(), BTreeMap::new()))
+            .1
+            .insert(commit_vote.author(), commit_vote.signature().clone());
+        self.try_commit(ledger_info_hash, verifier)
+    }
+
+    /// Add a commit proof received from a peer, it counts as a commit vote from each of its
+    /// signers. The signatures must have been verified.
+    pub fn add_commit_proof(
+        &mut self,
+        commit_proof: &LedgerInfoWithSignatures,
+        verifier: &ValidatorVerifier,
+    ) -> Option<(ExecutedBlocks, LedgerInfoWithSignatures)> {
+        let mut committed = None;
+        for (author, signature) in commit_proof.signatures() {
+            let commit_vote = CommitVote::new_with_signature(
+                *author,
+                commit_proof.ledger_info().clone(),
+                signature.clone(),
+            );
+            if let Some(executed) = self.add_commit_vote(&commit_vote, verifier) {
+                committed = Some(executed);
+            }
+        }
+        committed
+    }
+
+    /// Drop the executed blocks and the commit votes up to the given round once the node synced
+    /// to it, the blocks above it are executed again.
+    pub fn skip_to(&mut self, committed_round: Round) {
+        if committed_round <= self.committed_round {
+            return;
+        }
+        self.committed_round = committed_round;
+        self.executed
+            .retain(|executed| executed.round() > committed_round);
+        self.prune_votes();
+    }
+
+    /// Commits all the executed blocks up to the one certified by the votes: a quorum on the
+    /// execution result of a block also certifies the results of its ancestors.
+    fn try_commit(
+        &mut self,
+        ledger_info_hash: HashValue,
+        verifier: &ValidatorVerifier,
+    ) -> Option<(ExecutedBlocks, LedgerInfoWithSignatures)> {
+        let (ledger_info, signatures) = self.votes.get(&ledger_info_hash)?;
+        verifier.check_voting_power(signatures.keys()).ok()?;
+        let position = self
+            .executed
+            .iter()
+            .position(|executed| executed.ledger_info().hash() == ledger_info_hash)?;
+        let commit_proof = LedgerInfoWithSignatures::new(ledger_info.clone(), signatures.clone());
+
+        let mut blocks = vec![];
+        let mut ordered_proof = None;
+        for executed in self.executed.drain(..=position) {
+            blocks.extend(executed.blocks);
+            ordered_proof = Some(executed.ordered_proof);
+        }
+        self.committed_round = commit_proof.ledger_info().round();
+        self.prune_votes();
+        Some((
+            ExecutedBlocks {
+                blocks,
+                ordered_proof: ordered_proof.expect("Drained at least one executed block"),
+            },
+            commit_proof,
+        ))
+    }
+
+    fn prune_votes(&mut self) {
+        let committed_round = self.committed_round;
+        self.votes
+            .retain(|_, (ledger_info, _)| ledger_info.round() > committed_round);
+        let votes = &self.votes;
+        self.latest_votes
+            .retain(|_, ledger_info_hash| votes.contains_key(ledger_info_hash));
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::execution_pipeline::{ExecutedBlocks, PendingCommits, COMMIT_VOTE_ROUND_WINDOW};
+use consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    commit_vote::CommitVote,
+    common::Round,
+    executed_block::ExecutedBlock,
+};
+use libra_crypto::{hash::CryptoHash, HashValue};
+use libra_types::{
+    block_info::BlockInfo,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{random_validator_verifier, ValidatorVerifier},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+fn commit_vote(signer: &ValidatorSigner, ledger_info: &LedgerInfo) -> CommitVote {
+    CommitVote::new_with_signature(
+        signer.author(),
+        ledger_info.clone(),
+        signer.sign(ledger_info),
+    )
+}
+
+fn ledger_info(round: Round) -> LedgerInfo {
+    LedgerInfo::new(BlockInfo::random(round), HashValue::random())
+}
+
+fn executed_blocks(signer: &ValidatorSigner, round: Round) -> ExecutedBlocks {
+    let block = Block::new_proposal(vec![], round, round, certificate_for_genesis(), signer);
+    let ordered_info = block.gen_block_info(HashValue::zero(), 0, None);
+    ExecutedBlocks {
+        blocks: vec![Arc::new(ExecutedBlock::new_ordered(block))],
+        ordered_proof: LedgerInfoWithSignatures::new(
+            LedgerInfo::new(ordered_info, HashValue::random()),
+            BTreeMap::new(),
+        ),
+    }
+}
+
+fn num_votes(pending_commits: &PendingCommits, ledger_info: &LedgerInfo) -> usize {
+    pending_commits
+        .votes
+        .get(&ledger_info.hash())
+        .map_or(0, |(_, signatures)| signatures.len())
+}
+
+fn add_vote(
+    pending_commits: &mut PendingCommits,
+    signer: &ValidatorSigner,
+    ledger_info: &LedgerInfo,
+    verifier: &ValidatorVerifier,
+) {
+    // Without executed blocks, the votes never complete a commit
+    assert!(pending_commits
+        .add_commit_vote(&commit_vote(signer, ledger_info), verifier)
+        .is_none());
+}
+
+#[test]
+fn test_commit_votes_bounded() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let mut pending_commits = PendingCommits::new(10);
+
+    // Votes for committed rounds or beyond the window are dropped
+    let committed = ledger_info(10);
+    let too_far = ledger_info(10 + COMMIT_VOTE_ROUND_WINDOW + 1);
+    add_vote(&mut pending_commits, &signers[0], &committed, &verifier);
+    add_vote(&mut pending_commits, &signers[0], &too_far, &verifier);
+    assert!(pending_commits.votes.is_empty());
+
+    // Only the latest vote of an author is kept
+    let first = ledger_info(11);
+    let second = ledger_info(12);
+    add_vote(&mut pending_commits, &signers[0], &first, &verifier);
+    add_vote(&mut pending_commits, &signers[1], &first, &verifier);
+    add_vote(&mut pending_commits, &signers[0], &second, &verifier);
+    assert_eq!(num_votes(&pending_commits, &first), 1);
+    assert_eq!(num_votes(&pending_commits, &second), 1);
+    add_vote(&mut pending_commits, &signers[0], &first, &verifier);
+    assert_eq!(num_votes(&pending_commits, &first), 1);
+
+    // A different LedgerInfo for the same round replaces the previous vote
+    let conflicting = ledger_info(12);
+    add_vote(&mut pending_commits, &signers[0], &conflicting, &verifier);
+    assert_eq!(num_votes(&pending_commits, &conflicting), 1);
+    assert!(!pending_commits.votes.contains_key(&second.hash()));
+    assert_eq!(pending_commits.votes.len(), 2);
+    assert_eq!(pending_commits.latest_votes.len(), 2);
+}
+
+#[test]
+fn test_commit_proof_and_skip() {
+    let (signers, verifier) = random_validator_verifier(4, None, false);
+    let mut pending_commits = PendingCommits::new(0);
+
+    // A commit proof from a peer commits the matching local execution result
+    let executed = executed_blocks(&signers[0], 1);
+    let block_id = executed.blocks[0].id();
+    let executed_ledger_info = executed.ledger_info();
+    assert!(pending_commits.add_executed(executed, &verifier).is_none());
+    let signatures = signers
+        .iter()
+        .take(3)
+        .map(|signer| (signer.author(), signer.sign(&executed_ledger_info)))
+        .collect();
+    let proof = LedgerInfoWithSignatures::new(executed_ledger_info, signatures);
+    let (committed, commit_proof) = pending_commits.add_commit_proof(&proof, &verifier).unwrap();
+    assert_eq!(committed.blocks[0].id(), block_id);
+    assert_eq!(commit_proof, proof);
+    assert!(pending_commits.executed.is_empty());
+    assert!(pending_commits
+        .add_commit_proof(&proof, &verifier)
+        .is_none());
+
+    // Syncing drops the executed blocks and the votes up to the synced round
+    for round in 2..=3 {
+        assert!(pending_commits
+            .add_executed(executed_blocks(&signers[0], round), &verifier)
+            .is_none());
+    }
+    let stale = ledger_info(2);
+    add_vote(&mut pending_commits, &signers[1], &stale, &verifier);
+    pending_commits.skip_to(2);
+    assert_eq!(pending_commits.executed.len(), 1);
+    assert_eq!(pending_commits.executed[0].round(), 3);
+    assert!(pending_commits.votes.is_empty());
+    assert!(pending_commits.latest_votes.is_empty());
+}
@@ -12,10 +12,12 @@
 #![cfg_attr(feature = "fuzzing", allow(dead_code))]
 #![recursion_limit = "512"]
 
+mod batch_manager;
 mod block_storage;
 mod consensusdb;
 mod counters;
 mod epoch_manager;
+mod execution_pipeline;
 mod liveness;
 mod metrics_safety_rules;
 mod network;
@@ -23,12 +25,15 @@ mod network;
 mod network_tests;
 mod pending_votes;
 mod persistent_liveness_storage;
+mod recorder;
 mod round_manager;
 mod state_computer;
 mod state_replication;
 #[cfg(any(test, feature = "fuzzing"))]
 mod test_utils;
 #[cfg(test)]
+mod twins_scenario;
+#[cfg(test)]
 mod twins_test;
 mod txn_manager;
 mod util;
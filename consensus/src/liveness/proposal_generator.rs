@@ -23,7 +23,8 @@ mod proposal_generator_test;
 /// round.
 /// ProposalGenerator is the one choosing the branch to extend:
 /// - round is given by the caller (typically determined by RoundState).
-/// The transactions for the proposed block are delivered by TxnManager.
+/// The transactions for the proposed block are delivered by TxnManager. In batch dissemination
+/// mode the TxnManager is the BatchManager, which delivers the transactions of certified batches.
 ///
 /// TxnManager should be aware of the pending transactions in the branch that it is extending,
 /// such that it will filter them out to avoid transaction duplication.
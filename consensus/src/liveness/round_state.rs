@@ -6,10 +6,16 @@ use crate::{
     pending_votes::{PendingVotes, VoteReceptionResult},
     util::time_service::{SendTask, TimeService},
 };
-use consensus_types::{common::Round, sync_info::SyncInfo, vote::Vote};
+use consensus_types::{
+    block::Block,
+    common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
+    sync_info::SyncInfo,
+    vote::Vote,
+};
 use libra_logger::prelude::*;
 use libra_types::validator_verifier::ValidatorVerifier;
-use std::{fmt, sync::Arc, time::Duration};
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
 
 /// A reason for starting a new round: introduced for monitoring / debug purposes.
 #[derive(Eq, Debug, PartialEq)]
@@ -148,6 +154,8 @@ pub struct RoundState {
     pending_votes: PendingVotes,
     // Vote sent locally for the current round.
     vote_sent: Option<Vote>,
+    // Proposals received for the current round by author.
+    proposals_received: HashMap<Author, Block>,
 }
 
 // this is required by structured log
@@ -193,6 +201,7 @@ impl RoundState {
             timeout_sender,
             pending_votes: PendingVotes::new(),
             vote_sent: None,
+            proposals_received: HashMap::new(),
         }
     }
 
@@ -230,6 +239,7 @@ impl RoundState {
             self.current_round = new_round;
             self.pending_votes = PendingVotes::new();
             self.vote_sent = None;
+            self.proposals_received.clear();
             let timeout = self.setup_timeout();
             // The new round reason is QCReady in case both QC and TC are equal
             let new_round_reason = if sync_info.highest_timeout_certificate().is_none() {
@@ -273,6 +283,28 @@ impl RoundState {
         self.vote_sent.clone()
     }
 
+    /// Records a verified proposal of the current round, returns the evidence of an equivocation
+    /// in case its author already proposed a different block in this round.
+    pub fn record_proposal(&mut self, proposal: &Block) -> Option<EquivocationEvidence> {
+        let author = proposal.author()?;
+        if proposal.round() != self.current_round {
+            return None;
+        }
+        match self.proposals_received.get(&author) {
+            Some(previous_proposal) if previous_proposal.id() != proposal.id() => {
+                Some(EquivocationEvidence::new_proposal_evidence(
+                    previous_proposal.clone(),
+                    proposal.clone(),
+                ))
+            }
+            Some(_) => None,
+            None => {
+                self.proposals_received.insert(author, proposal.clone());
+                None
+            }
+        }
+    }
+
     /// Setup the timeout task and return the duration of the current timeout
     fn setup_timeout(&mut self) -> Duration {
         let timeout_sender = self.timeout_sender.clone();
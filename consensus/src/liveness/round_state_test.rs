@@ -9,14 +9,22 @@ use crate::{
 };
 
 use consensus_types::{
-    common::Round, quorum_cert::QuorumCert, sync_info::SyncInfo, timeout::Timeout,
-    timeout_certificate::TimeoutCertificate, vote_data::VoteData,
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Round,
+    equivocation_evidence::EquivocationEvidence,
+    quorum_cert::QuorumCert,
+    sync_info::SyncInfo,
+    timeout::Timeout,
+    timeout_certificate::TimeoutCertificate,
+    vote_data::VoteData,
 };
 use futures::StreamExt;
 use libra_crypto::HashValue;
 use libra_types::{
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::ValidatorVerifier,
 };
 use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
@@ -78,6 +86,36 @@ fn test_round_event_generation() {
     );
 }
 
+#[test]
+fn test_proposal_equivocation() {
+    let (mut pm, _) = make_round_state();
+    pm.process_certificates(generate_sync_info(Some(1), None, None));
+    let signer = ValidatorSigner::random(None);
+    let verifier = ValidatorVerifier::new_single(signer.author(), signer.public_key());
+    let genesis_qc = certificate_for_genesis();
+    let proposal_a = Block::new_proposal(vec![], 2, 1, genesis_qc.clone(), &signer);
+    let proposal_b = Block::new_proposal(vec![], 2, 2, genesis_qc.clone(), &signer);
+
+    // The same proposal received twice is not an equivocation
+    assert!(pm.record_proposal(&proposal_a).is_none());
+    assert!(pm.record_proposal(&proposal_a).is_none());
+
+    // A different proposal of the same author in the same round is an equivocation
+    let evidence = pm.record_proposal(&proposal_b).unwrap();
+    assert_eq!(evidence.author(), Some(signer.author()));
+    assert_eq!(evidence.round(), 2);
+    assert!(evidence.verify(&verifier).is_ok());
+    assert_eq!(
+        evidence,
+        EquivocationEvidence::new_proposal_evidence(proposal_b, proposal_a)
+    );
+
+    // The proposals of the previous round are forgotten in a new round
+    pm.process_certificates(generate_sync_info(Some(2), None, None));
+    let proposal_c = Block::new_proposal(vec![], 3, 3, genesis_qc, &signer);
+    assert!(pm.record_proposal(&proposal_c).is_none());
+}
+
 fn make_round_state() -> (RoundState, channel::Receiver<Round>) {
     let time_interval = Box::new(ExponentialTimeInterval::fixed(Duration::from_millis(2)));
     let simulated_time = SimulatedTimeService::auto_advance_until(Duration::from_millis(4));
@@ -3,12 +3,15 @@
 
 use crate::persistent_liveness_storage::PersistentLivenessStorage;
 use consensus_types::{
-    block::Block, block_data::BlockData, timeout::Timeout, vote::Vote,
-    vote_proposal::MaybeSignedVoteProposal,
+    batch::BatchInfo, block::Block, block_data::BlockData, compact_proposal_msg::CompactBlock,
+    timeout::Timeout, vote::Vote, vote_proposal::MaybeSignedVoteProposal,
 };
 use libra_crypto::ed25519::Ed25519Signature;
 use libra_metrics::monitor;
-use libra_types::epoch_change::EpochChangeProof;
+use libra_types::{
+    epoch_change::EpochChangeProof,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
 use safety_rules::{ConsensusState, Error, TSafetyRules};
 use std::sync::Arc;
 
@@ -82,4 +85,53 @@ impl TSafetyRules for MetricsSafetyRules {
         }
         result
     }
+
+    fn sign_commit_vote(
+        &mut self,
+        ordered_ledger_info: LedgerInfoWithSignatures,
+        executed_ledger_info: LedgerInfo,
+        executed_block: &MaybeSignedVoteProposal,
+    ) -> Result<Ed25519Signature, Error> {
+        let mut result = monitor!(
+            "safety_rules",
+            self.inner.sign_commit_vote(
+                ordered_ledger_info.clone(),
+                executed_ledger_info.clone(),
+                executed_block
+            )
+        );
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!(
+                "safety_rules",
+                self.inner.sign_commit_vote(
+                    ordered_ledger_info,
+                    executed_ledger_info,
+                    executed_block
+                )
+            );
+        }
+        result
+    }
+
+    fn sign_batch_info(&mut self, batch_info: &BatchInfo) -> Result<Ed25519Signature, Error> {
+        let mut result = monitor!("safety_rules", self.inner.sign_batch_info(batch_info));
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!("safety_rules", self.inner.sign_batch_info(batch_info));
+        }
+        result
+    }
+
+    fn sign_compact_block(
+        &mut self,
+        compact_block: &CompactBlock,
+    ) -> Result<Ed25519Signature, Error> {
+        let mut result = monitor!("safety_rules", self.inner.sign_compact_block(compact_block));
+        if let Err(Error::NotInitialized(_res)) = result {
+            self.perform_initialize()?;
+            result = monitor!("safety_rules", self.inner.sign_compact_block(compact_block));
+        }
+        result
+    }
 }
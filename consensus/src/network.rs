@@ -4,18 +4,25 @@
 use crate::{
     counters,
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
+    recorder::{ConsensusRecorder, RecordedEvent},
 };
 use anyhow::{anyhow, ensure};
 use bytes::Bytes;
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
+    batch::{AvailabilityCertificate, Batch, BatchVote},
+    batch_retrieval::BatchRetrievalRequest,
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, MAX_BLOCKS_PER_REQUEST},
+    commit_vote::CommitVote,
     common::Author,
+    compact_proposal_msg::CompactProposalMsg,
+    equivocation_evidence::EquivocationEvidence,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
 };
 use futures::{channel::oneshot, stream::select, SinkExt, Stream, StreamExt, TryStreamExt};
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_metrics::monitor;
 use libra_types::{
@@ -26,6 +33,7 @@ use network::protocols::{network::Event, rpc::error::RpcError};
 use std::{
     mem::{discriminant, Discriminant},
     num::NonZeroUsize,
+    sync::Arc,
     time::Duration,
 };
 
@@ -37,6 +45,14 @@ pub struct IncomingBlockRetrievalRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+/// The batch retrieval request is used internally for implementing RPC: the callback is executed
+/// for carrying the response
+#[derive(Debug)]
+pub struct IncomingBatchRetrievalRequest {
+    pub req: BatchRetrievalRequest,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 /// Just a convenience struct to keep all the network proxy receiving queues in one place.
 /// Will be returned by the NetworkTask upon startup.
 pub struct NetworkReceivers {
@@ -46,6 +62,7 @@ pub struct NetworkReceivers {
         (AccountAddress, ConsensusMsg),
     >,
     pub block_retrieval: libra_channel::Receiver<AccountAddress, IncomingBlockRetrievalRequest>,
+    pub batch_retrieval: libra_channel::Receiver<AccountAddress, IncomingBatchRetrievalRequest>,
 }
 
 /// Implements the actual networking support for all consensus messaging.
@@ -58,6 +75,8 @@ pub struct NetworkSender {
     // Note that we do not support self rpc requests as it might cause infinite recursive calls.
     self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
     validators: ValidatorVerifier,
+    // Records the votes sent and the blocks retrieved when consensus is recorded.
+    recorder: Option<Arc<ConsensusRecorder>>,
 }
 
 impl NetworkSender {
@@ -66,12 +85,20 @@ impl NetworkSender {
         network_sender: ConsensusNetworkSender,
         self_sender: channel::Sender<anyhow::Result<Event<ConsensusMsg>>>,
         validators: ValidatorVerifier,
+        recorder: Option<Arc<ConsensusRecorder>>,
     ) -> Self {
         NetworkSender {
             author,
             network_sender,
             self_sender,
             validators,
+            recorder,
+        }
+    }
+
+    fn record(&self, event: RecordedEvent) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(event);
         }
     }
 
@@ -84,6 +111,21 @@ impl NetworkSender {
         timeout: Duration,
     ) -> anyhow::Result<BlockRetrievalResponse> {
         ensure!(from != self.author, "Retrieve block from self");
+        let response = self
+            .request_block_from_peer(retrieval_request, from, timeout)
+            .await;
+        self.record(RecordedEvent::BlockRetrieval(
+            response.as_ref().ok().cloned(),
+        ));
+        response
+    }
+
+    async fn request_block_from_peer(
+        &mut self,
+        retrieval_request: BlockRetrievalRequest,
+        from: Author,
+        timeout: Duration,
+    ) -> anyhow::Result<BlockRetrievalResponse> {
         let msg = ConsensusMsg::BlockRetrievalRequest(Box::new(retrieval_request.clone()));
         let response_msg = monitor!(
             "block_retrieval",
@@ -109,6 +151,29 @@ impl NetworkSender {
         Ok(response)
     }
 
+    /// Tries to retrieve the batch with the given digest from the given peer.
+    pub async fn request_batch(
+        &mut self,
+        digest: HashValue,
+        from: Author,
+        timeout: Duration,
+    ) -> anyhow::Result<Batch> {
+        ensure!(from != self.author, "Retrieve batch from self");
+        let msg = ConsensusMsg::BatchRetrievalRequest(Box::new(BatchRetrievalRequest::new(digest)));
+        let response_msg = monitor!(
+            "batch_retrieval",
+            self.network_sender.send_rpc(from, msg, timeout).await?
+        );
+        let response = match response_msg {
+            ConsensusMsg::BatchRetrievalResponse(resp) => *resp,
+            _ => return Err(anyhow!("Invalid response to request")),
+        };
+        response.verify(digest, &self.validators)?;
+        response
+            .take_batch()
+            .ok_or_else(|| anyhow!("Batch {} not found", digest))
+    }
+
     /// Tries to send the given proposal (block and proposer metadata) to all the participants.
     /// A validator on the receiving end is going to be notified about a new proposal in the
     /// proposal queue.
@@ -123,6 +188,58 @@ impl NetworkSender {
         self.broadcast(msg).await
     }
 
+    /// Broadcasts the proposal referring to certified batches instead of carrying the
+    /// transactions in batch dissemination mode.
+    pub async fn broadcast_compact_proposal(&mut self, proposal: CompactProposalMsg) {
+        let msg = ConsensusMsg::CompactProposalMsg(Box::new(proposal));
+        self.broadcast(msg).await
+    }
+
+    /// Delivers a compact proposal to self once the batches it misses have been fetched.
+    pub async fn notify_compact_proposal(&mut self, proposal: CompactProposalMsg) {
+        let msg = ConsensusMsg::CompactProposalMsg(Box::new(proposal));
+        let self_msg = Event::Message((self.author, msg));
+        if let Err(e) = self.self_sender.send(Ok(self_msg)).await {
+            warn!("Failed to deliver a compact proposal to self {:?}", e);
+        }
+    }
+
+    /// Broadcasts a batch of transactions created locally to all validators (including self).
+    pub async fn broadcast_batch(&mut self, batch: Batch) {
+        let msg = ConsensusMsg::BatchMsg(Box::new(batch));
+        self.broadcast(msg).await
+    }
+
+    /// Broadcasts the availability certificate of a batch created locally to all validators
+    /// (including self).
+    pub async fn broadcast_availability_certificate(
+        &mut self,
+        certificate: AvailabilityCertificate,
+    ) {
+        let msg = ConsensusMsg::AvailabilityCertificate(Box::new(certificate));
+        self.broadcast(msg).await
+    }
+
+    /// Sends the batch vote to the author of the batch, which might be self.
+    pub async fn send_batch_vote(&self, batch_vote: BatchVote, recipient: Author) {
+        let msg = ConsensusMsg::BatchVoteMsg(Box::new(batch_vote));
+        if self.author == recipient {
+            let mut self_sender = self.self_sender.clone();
+            let self_msg = Event::Message((self.author, msg));
+            if let Err(err) = self_sender.send(Ok(self_msg)).await {
+                error!("Error delivering a self batch vote: {:?}", err);
+            }
+            return;
+        }
+        let mut network_sender = self.network_sender.clone();
+        if let Err(e) = network_sender.send_to(recipient, msg) {
+            warn!(
+                "Failed to send a batch vote to peer {:?}: {:?}",
+                recipient, e
+            );
+        }
+    }
+
     async fn broadcast(&mut self, msg: ConsensusMsg) {
         // Directly send the message to ourself without going through network.
         let self_msg = Event::Message((self.author, msg.clone()));
@@ -153,6 +270,7 @@ impl NetworkSender {
     /// out. It does not give indication about when the message is delivered to the recipients,
     /// as well as there is no indication about the network failures.
     pub async fn send_vote(&self, vote_msg: VoteMsg, recipients: Vec<Author>) {
+        self.record(RecordedEvent::Vote(Box::new(vote_msg.vote().clone())));
         let mut network_sender = self.network_sender.clone();
         let mut self_sender = self.self_sender.clone();
         let msg = ConsensusMsg::VoteMsg(Box::new(vote_msg));
@@ -172,6 +290,7 @@ impl NetworkSender {
 
     /// Broadcasts vote message to all validators
     pub async fn broadcast_vote(&mut self, vote_msg: VoteMsg) {
+        self.record(RecordedEvent::Vote(Box::new(vote_msg.vote().clone())));
         let msg = ConsensusMsg::VoteMsg(Box::new(vote_msg));
         self.broadcast(msg).await
     }
@@ -190,6 +309,19 @@ impl NetworkSender {
         }
     }
 
+    /// Broadcast the commit vote on the execution result of ordered blocks to all validators
+    /// (including self) in decoupled execution mode.
+    pub async fn broadcast_commit_vote(&mut self, commit_vote: CommitVote) {
+        let msg = ConsensusMsg::CommitVoteMsg(Box::new(commit_vote));
+        self.broadcast(msg).await
+    }
+
+    /// Gossip the evidence of an equivocation detected locally to all validators (including self).
+    pub async fn broadcast_equivocation_evidence(&mut self, evidence: EquivocationEvidence) {
+        let msg = ConsensusMsg::EquivocationEvidence(Box::new(evidence));
+        self.broadcast(msg).await
+    }
+
     /// Broadcast about epoch changes with proof to the current validator set (including self)
     /// when we commit the reconfiguration block
     pub async fn broadcast_epoch_change(&mut self, proof: EpochChangeProof) {
@@ -212,6 +344,7 @@ pub struct NetworkTask {
         (AccountAddress, ConsensusMsg),
     >,
     block_retrieval_tx: libra_channel::Sender<AccountAddress, IncomingBlockRetrievalRequest>,
+    batch_retrieval_tx: libra_channel::Sender<AccountAddress, IncomingBatchRetrievalRequest>,
     all_events: Box<dyn Stream<Item = anyhow::Result<Event<ConsensusMsg>>> + Send + Unpin>,
 }
 
@@ -231,17 +364,24 @@ impl NetworkTask {
             NonZeroUsize::new(1).unwrap(),
             Some(&counters::BLOCK_RETRIEVAL_CHANNEL_MSGS),
         );
+        let (batch_retrieval_tx, batch_retrieval) = libra_channel::new(
+            QueueStyle::LIFO,
+            NonZeroUsize::new(1).unwrap(),
+            Some(&counters::BATCH_RETRIEVAL_CHANNEL_MSGS),
+        );
         let network_events = network_events.map_err(Into::<anyhow::Error>::into);
         let all_events = Box::new(select(network_events, self_receiver));
         (
             NetworkTask {
                 consensus_messages_tx,
                 block_retrieval_tx,
+                batch_retrieval_tx,
                 all_events,
             },
             NetworkReceivers {
                 consensus_messages,
                 block_retrieval,
+                batch_retrieval,
             },
         )
     }
@@ -278,6 +418,16 @@ impl NetworkTask {
                             warn!("libra channel closed: {:?}", e);
                         }
                     }
+                    ConsensusMsg::BatchRetrievalRequest(request) => {
+                        debug!("Received batch retrieval request {}", request);
+                        let req_with_callback = IncomingBatchRetrievalRequest {
+                            req: *request,
+                            response_sender: callback,
+                        };
+                        if let Err(e) = self.batch_retrieval_tx.push(peer_id, req_with_callback) {
+                            warn!("libra channel closed: {:?}", e);
+                        }
+                    }
                     _ => {
                         warn!("Unexpected msg from {}: {:?}", peer_id, msg);
                         continue;
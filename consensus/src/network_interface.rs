@@ -6,8 +6,13 @@
 use crate::counters;
 use channel::message_queues::QueueStyle;
 use consensus_types::{
+    batch::{AvailabilityCertificate, Batch, BatchVote},
+    batch_retrieval::{BatchRetrievalRequest, BatchRetrievalResponse},
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    commit_vote::CommitVote,
+    compact_proposal_msg::CompactProposalMsg,
     epoch_retrieval::EpochRetrievalRequest,
+    equivocation_evidence::EquivocationEvidence,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote_msg::VoteMsg,
@@ -47,6 +52,25 @@ pub enum ConsensusMsg {
     /// VoteMsg is the struct that is ultimately sent by the voter in response for receiving a
     /// proposal.
     VoteMsg(Box<VoteMsg>),
+    /// CommitVote is sent in decoupled execution mode to certify the execution result of
+    /// ordered blocks.
+    CommitVoteMsg(Box<CommitVote>),
+    /// Batch of transactions disseminated by its author independently of the proposals.
+    BatchMsg(Box<Batch>),
+    /// BatchVoteMsg is sent back to the author of a batch by the validators that stored it.
+    BatchVoteMsg(Box<BatchVote>),
+    /// The quorum of batch votes gathered by the author of a batch, the certified batch can be
+    /// proposed by its digest only.
+    AvailabilityCertificate(Box<AvailabilityCertificate>),
+    /// CompactProposalMsg refers to the transactions of the proposed block by the availability
+    /// certificates of their batches.
+    CompactProposalMsg(Box<CompactProposalMsg>),
+    /// RPC to get a batch by its digest.
+    BatchRetrievalRequest(Box<BatchRetrievalRequest>),
+    /// Carries the returned batch.
+    BatchRetrievalResponse(Box<BatchRetrievalResponse>),
+    /// Evidence of an equivocation gossiped by the validator that detected it.
+    EquivocationEvidence(Box<EquivocationEvidence>),
 }
 
 /// The interface from Network to Consensus layer.
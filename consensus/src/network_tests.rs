@@ -9,7 +9,7 @@ use crate::{
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
-    common::Author,
+    common::{Author, Round},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     vote::Vote,
@@ -52,9 +52,13 @@ pub struct TwinId {
 /// `deliver_messages` to inspect the direct-send messages sent between peers.
 /// They can also configure network messages to be dropped between specific peers.
 ///
+/// The network can also be partitioned round by round, as in the Twins scenarios:
+/// the direct-send messages of a partitioned round are only delivered within
+/// the partitions of the round.
+///
 /// Currently, RPC messages are delivered immediately and are not controlled by
 /// `wait_for_messages` or `deliver_messages` for delivery. They are also not
-/// currently dropped according to the `NetworkPlayground`'s drop config.
+/// currently dropped according to the `NetworkPlayground`'s round partitions.
 pub struct NetworkPlayground {
     /// Maps each Author to a Sender of their inbound network notifications.
     /// These events will usually be handled by the event loop spawned in
@@ -72,6 +76,8 @@ pub struct NetworkPlayground {
     outbound_msgs_rx: mpsc::Receiver<(TwinId, PeerManagerRequest)>,
     /// Allow test code to drop direct-send messages between peers.
     drop_config: Arc<RwLock<DropConfig>>,
+    /// The partitions of the network in the given rounds.
+    round_partitions: HashMap<Round, Vec<HashSet<TwinId>>>,
     /// An executor for spawning node outbound network event handlers
     executor: Handle,
     // Maps authors to twins IDs
@@ -88,6 +94,7 @@ impl NetworkPlayground {
             outbound_msgs_tx,
             outbound_msgs_rx,
             drop_config: Arc::new(RwLock::new(DropConfig(HashMap::new()))),
+            round_partitions: HashMap::new(),
             executor,
             author_to_twin_ids: Arc::new(RwLock::new(AuthorToTwinIds(HashMap::new()))),
         }
@@ -271,6 +278,7 @@ impl NetworkPlayground {
             };
 
             let dst_twin_ids = self.get_twin_ids(dst);
+            let round = self.message_round(&msg.mdata);
 
             for (idx, dst_twin_id) in dst_twin_ids.iter().enumerate() {
                 let src_twin_id_copy = src_twin_id;
@@ -280,7 +288,7 @@ impl NetworkPlayground {
                     PeerManagerNotification::RecvMessage(src_twin_id.author, msg.clone());
 
                 // Deliver and copy message it if it's not dropped
-                if !self.is_message_dropped(&src_twin_id_copy, &dst_twin_id_copy) {
+                if !self.is_message_dropped(&src_twin_id_copy, &dst_twin_id_copy, round) {
                     let msg_copy = self
                         .deliver_message(src_twin_id_copy, dst_twin_id_copy, msg_notif)
                         .await;
@@ -344,11 +352,58 @@ impl NetworkPlayground {
         self.author_to_twin_ids.read().unwrap().get_twin_ids(author)
     }
 
-    fn is_message_dropped(&self, src_twin_id: &TwinId, dst_twin_id: &TwinId) -> bool {
-        self.drop_config
-            .read()
-            .unwrap()
-            .is_message_dropped(src_twin_id, dst_twin_id)
+    /// A message is dropped according to the drop config, or if its round is partitioned and
+    /// its source and destination are in different partitions.
+    fn is_message_dropped(
+        &self,
+        src_twin_id: &TwinId,
+        dst_twin_id: &TwinId,
+        round: Option<Round>,
+    ) -> bool {
+        let partitioned = round
+            .and_then(|round| self.round_partitions.get(&round))
+            .map_or(false, |partitions| {
+                !partitions.iter().any(|partition| {
+                    partition.contains(src_twin_id) && partition.contains(dst_twin_id)
+                })
+            });
+        partitioned
+            || self
+                .drop_config
+                .read()
+                .unwrap()
+                .is_message_dropped(src_twin_id, dst_twin_id)
+    }
+
+    /// The round of a message for the round partitions, the messages of the other types are
+    /// not partitioned.
+    fn message_round(&self, mdata: &[u8]) -> Option<Round> {
+        if self.round_partitions.is_empty() {
+            return None;
+        }
+        match lcs::from_bytes::<ConsensusMsg>(mdata).ok()? {
+            ConsensusMsg::ProposalMsg(proposal) => Some(proposal.proposal().round()),
+            ConsensusMsg::CompactProposalMsg(proposal) => Some(proposal.round()),
+            ConsensusMsg::VoteMsg(vote_msg) => Some(vote_msg.vote().vote_data().proposed().round()),
+            ConsensusMsg::CommitVoteMsg(commit_vote) => Some(commit_vote.round()),
+            ConsensusMsg::SyncInfo(sync_info) => Some(sync_info.highest_round()),
+            _ => None,
+        }
+    }
+
+    /// Partitions the network in the given rounds, a node missing from the partitions of a
+    /// round is isolated in that round. The rounds missing from the map are not partitioned.
+    pub fn set_round_partitions(&mut self, round_partitions: HashMap<Round, Vec<Vec<TwinId>>>) {
+        self.round_partitions = round_partitions
+            .into_iter()
+            .map(|(round, partitions)| {
+                let partitions = partitions
+                    .into_iter()
+                    .map(|partition| partition.into_iter().collect())
+                    .collect();
+                (round, partitions)
+            })
+            .collect();
     }
 
     pub fn drop_message_for(&mut self, src: &TwinId, dst: &TwinId) -> bool {
@@ -389,13 +444,14 @@ impl NetworkPlayground {
             };
 
             let dst_twin_ids = self.get_twin_ids(dst);
+            let round = self.message_round(&msg.mdata);
 
             for dst_twin_id in dst_twin_ids.iter() {
                 let msg_notif =
                     PeerManagerNotification::RecvMessage(src_twin_id.author, msg.clone());
 
                 // Deliver and copy message it if it's not dropped
-                if !self.is_message_dropped(&src_twin_id, &dst_twin_id) {
+                if !self.is_message_dropped(&src_twin_id, &dst_twin_id, round) {
                     self.deliver_message(src_twin_id, *dst_twin_id, msg_notif)
                         .await;
                 }
@@ -506,6 +562,7 @@ mod tests {
                 network_sender,
                 self_sender,
                 validator_verifier.clone(),
+                None,
             );
             let (task, receiver) = NetworkTask::new(network_events, self_receiver);
             receivers.push(receiver);
@@ -593,6 +650,7 @@ mod tests {
                 network_sender.clone(),
                 self_sender,
                 validator_verifier.clone(),
+                None,
             );
             let (task, receiver) = NetworkTask::new(network_events, self_receiver);
             senders.push(network_sender);
@@ -4,7 +4,8 @@
 use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData};
 use anyhow::{format_err, Context, Result};
 use consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_certificate::TimeoutCertificate, vote::Vote,
 };
 use executor_types::ExecutedTrees;
 use libra_config::config::NodeConfig;
@@ -15,6 +16,7 @@ use libra_types::{
     block_info::Round, epoch_change::EpochChangeProof, ledger_info::LedgerInfo,
     transaction::Version,
 };
+use serde::{Deserialize, Serialize};
 use std::{cmp::max, collections::HashSet, sync::Arc};
 use storage_interface::DbReader;
 
@@ -47,6 +49,13 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
 
+    /// Persist the evidence of an equivocation, returns false if evidence against the same author
+    /// in the same epoch is already stored.
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<bool>;
+
+    /// Retrieve all the persisted evidence of equivocations.
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>>;
+
     /// Returns a handle of the libradb.
     fn libra_db(&self) -> Arc<dyn DbReader>;
 }
@@ -124,6 +133,7 @@ impl LedgerRecoveryData {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RootMetadata {
     pub accu_hash: HashValue,
     pub frozen_root_hashes: Vec<HashValue>,
@@ -223,6 +233,23 @@ impl RecoveryData {
         &self.root.0
     }
 
+    /// The LedgerInfo committing the root block.
+    pub fn root_ledger_info(&self) -> &LedgerInfo {
+        self.root.2.ledger_info().ledger_info()
+    }
+
+    pub fn root_metadata(&self) -> &RootMetadata {
+        &self.root_metadata
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    pub fn quorum_certs(&self) -> &[QuorumCert] {
+        &self.quorum_certs
+    }
+
     pub fn last_vote(&self) -> Option<Vote> {
         self.last_vote.clone()
     }
@@ -409,6 +436,14 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(proofs)
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<bool> {
+        self.db.save_equivocation_evidence(evidence)
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        self.db.get_equivocation_evidence()
+    }
+
     fn libra_db(&self) -> Arc<dyn DbReader> {
         self.libra_db.clone()
     }
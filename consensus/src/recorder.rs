@@ -0,0 +1,339 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The recorder writes to a file everything a validator needs to reproduce the behavior of its
+//! consensus offline: the inbound messages, the local timeouts and the readings of the time
+//! service, along with the execution results, the elected proposers and the retrieved blocks.
+//! The votes and the commits of the validator are recorded as well so that a replay can report
+//! where it diverges from the recording.
+
+use crate::{
+    liveness::proposer_election::ProposerElection,
+    network_interface::ConsensusMsg,
+    persistent_liveness_storage::{RecoveryData, RootMetadata},
+    state_replication::StateComputer,
+    util::time_service::{ScheduledTask, TimeService},
+};
+use anyhow::{Context, Result};
+use consensus_types::{
+    block::Block,
+    block_retrieval::BlockRetrievalResponse,
+    common::{Author, Round},
+    quorum_cert::QuorumCert,
+    timeout_certificate::TimeoutCertificate,
+    vote::Vote,
+};
+use executor_types::{Error, StateComputeResult};
+use libra_crypto::HashValue;
+use libra_logger::prelude::*;
+use libra_types::{
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(any(test, feature = "fuzzing"))]
+use crate::persistent_liveness_storage::LedgerRecoveryData;
+#[cfg(any(test, feature = "fuzzing"))]
+use std::io::{BufReader, ErrorKind, Read};
+
+#[cfg(test)]
+#[path = "recorder_test.rs"]
+mod recorder_test;
+
+/// The state a RoundManager starts an epoch from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordedEpochStart {
+    pub epoch_state: EpochState,
+    /// The LedgerInfo committing the root block.
+    pub root_ledger_info: LedgerInfo,
+    pub root_metadata: RootMetadata,
+    /// The root block followed by the pending blocks.
+    pub blocks: Vec<Block>,
+    pub quorum_certs: Vec<QuorumCert>,
+    pub last_vote: Option<Vote>,
+    pub highest_timeout_certificate: Option<TimeoutCertificate>,
+    /// The voting state of SafetyRules, set once SafetyRules is initialized.
+    pub last_voted_round: Round,
+    pub preferred_round: Round,
+    /// The modes the epoch runs in, the execution events and the fetched batches of these modes
+    /// are not recorded.
+    pub decoupled_execution: bool,
+    pub batch_dissemination: bool,
+}
+
+impl RecordedEpochStart {
+    pub fn new(epoch_state: EpochState, recovery_data: &RecoveryData) -> Self {
+        Self {
+            epoch_state,
+            root_ledger_info: recovery_data.root_ledger_info().clone(),
+            root_metadata: recovery_data.root_metadata().clone(),
+            blocks: std::iter::once(recovery_data.root_block())
+                .chain(recovery_data.blocks())
+                .cloned()
+                .collect(),
+            quorum_certs: recovery_data.quorum_certs().to_vec(),
+            last_vote: recovery_data.last_vote(),
+            highest_timeout_certificate: recovery_data.highest_timeout_certificate(),
+            last_voted_round: 0,
+            preferred_round: 0,
+            decoupled_execution: false,
+            batch_dissemination: false,
+        }
+    }
+
+    /// Rebuilds the recovery data the RoundManager started from.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn recovery_data(&self) -> Result<RecoveryData> {
+        RecoveryData::new(
+            self.last_vote.clone(),
+            LedgerRecoveryData::new(self.root_ledger_info.clone()),
+            self.blocks.clone(),
+            self.root_metadata.clone(),
+            self.quorum_certs.clone(),
+            self.highest_timeout_certificate.clone(),
+        )
+    }
+}
+
+/// The events of a recording. The inputs of consensus are the epoch starts, the messages and the
+/// local timeouts, the votes and the commits are its outputs, the other events carry the results
+/// of the components consensus depends on.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordedEvent {
+    EpochStart(Box<RecordedEpochStart>),
+    /// A message processed by the EpochManager, including the ones sent to self.
+    Message(Author, ConsensusMsg),
+    LocalTimeout(Round),
+    Timestamp(Duration),
+    Proposer(Round, Author),
+    Executed(HashValue, Box<StateComputeResult>),
+    /// The response to a block retrieval request, None if the request failed.
+    BlockRetrieval(Option<BlockRetrievalResponse>),
+    Vote(Box<Vote>),
+    Commit(Box<LedgerInfoWithSignatures>),
+}
+
+impl RecordedEvent {
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn is_input(&self) -> bool {
+        match self {
+            RecordedEvent::EpochStart(_)
+            | RecordedEvent::Message(_, _)
+            | RecordedEvent::LocalTimeout(_) => true,
+            _ => false,
+        }
+    }
+}
+
+enum RecorderSink {
+    File(File),
+    #[cfg(any(test, feature = "fuzzing"))]
+    Memory(Vec<RecordedEvent>),
+}
+
+/// ConsensusRecorder appends the recorded events to a file: each event is serialized with LCS
+/// and prefixed by its length. A recording survives restarts, every start of a RoundManager is
+/// recorded.
+pub struct ConsensusRecorder {
+    sink: Mutex<RecorderSink>,
+}
+
+impl ConsensusRecorder {
+    pub fn new(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open the consensus recording {:?}", path))?;
+        Ok(Self {
+            sink: Mutex::new(RecorderSink::File(file)),
+        })
+    }
+
+    /// Keeps the recorded events in memory, used to collect the outputs of a replay.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn new_in_memory() -> Self {
+        Self {
+            sink: Mutex::new(RecorderSink::Memory(vec![])),
+        }
+    }
+
+    pub fn record(&self, event: RecordedEvent) {
+        match &mut *self.sink.lock().unwrap() {
+            RecorderSink::File(file) => {
+                if let Err(e) = write_event(file, &event) {
+                    error!("[ConsensusRecorder] Failed to record event: {:?}", e);
+                }
+            }
+            #[cfg(any(test, feature = "fuzzing"))]
+            RecorderSink::Memory(events) => events.push(event),
+        }
+    }
+
+    /// Returns the events recorded in memory since the last call.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn take_events(&self) -> Vec<RecordedEvent> {
+        match &mut *self.sink.lock().unwrap() {
+            RecorderSink::File(_) => vec![],
+            RecorderSink::Memory(events) => std::mem::take(events),
+        }
+    }
+}
+
+fn write_event(writer: &mut impl Write, event: &RecordedEvent) -> Result<()> {
+    let bytes = lcs::to_bytes(event)?;
+    let mut frame = (bytes.len() as u32).to_le_bytes().to_vec();
+    frame.extend(bytes);
+    // A single write keeps the frames whole in case the node crashes.
+    writer.write_all(&frame)?;
+    Ok(())
+}
+
+/// Reads the events of a recording, an event truncated by a crash at the end of the file is
+/// ignored.
+#[cfg(any(test, feature = "fuzzing"))]
+pub fn read_recording(path: &Path) -> Result<Vec<RecordedEvent>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open the consensus recording {:?}", path))?;
+    let mut reader = BufReader::new(file);
+    let mut events = vec![];
+    let mut len = [0u8; 4];
+    loop {
+        match reader.read_exact(&mut len) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                warn!("[ConsensusRecorder] Ignore the truncated event at the end of the recording");
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        events.push(lcs::from_bytes(&bytes).context("Failed to deserialize a recorded event")?);
+    }
+    Ok(events)
+}
+
+/// Records the readings of the time service.
+pub struct RecordingTimeService {
+    time_service: Arc<dyn TimeService>,
+    recorder: Arc<ConsensusRecorder>,
+}
+
+impl RecordingTimeService {
+    pub fn new(time_service: Arc<dyn TimeService>, recorder: Arc<ConsensusRecorder>) -> Self {
+        Self {
+            time_service,
+            recorder,
+        }
+    }
+}
+
+impl TimeService for RecordingTimeService {
+    fn run_after(&self, timeout: Duration, task: Box<dyn ScheduledTask>) {
+        self.time_service.run_after(timeout, task)
+    }
+
+    fn get_current_timestamp(&self) -> Duration {
+        let now = self.time_service.get_current_timestamp();
+        self.recorder.record(RecordedEvent::Timestamp(now));
+        now
+    }
+
+    fn sleep(&self, t: Duration) {
+        self.time_service.sleep(t)
+    }
+}
+
+/// Records the execution results and the commits.
+pub struct RecordingStateComputer {
+    state_computer: Arc<dyn StateComputer>,
+    recorder: Arc<ConsensusRecorder>,
+}
+
+impl RecordingStateComputer {
+    pub fn new(state_computer: Arc<dyn StateComputer>, recorder: Arc<ConsensusRecorder>) -> Self {
+        Self {
+            state_computer,
+            recorder,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl StateComputer for RecordingStateComputer {
+    fn compute(
+        &self,
+        block: &Block,
+        parent_block_id: HashValue,
+    ) -> Result<StateComputeResult, Error> {
+        let result = self.state_computer.compute(block, parent_block_id)?;
+        self.recorder.record(RecordedEvent::Executed(
+            block.id(),
+            Box::new(result.clone()),
+        ));
+        Ok(result)
+    }
+
+    async fn commit(
+        &self,
+        block_ids: Vec<HashValue>,
+        finality_proof: LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        self.state_computer
+            .commit(block_ids, finality_proof.clone())
+            .await?;
+        self.recorder
+            .record(RecordedEvent::Commit(Box::new(finality_proof)));
+        Ok(())
+    }
+
+    async fn sync_to(&self, target: LedgerInfoWithSignatures) -> Result<()> {
+        self.state_computer.sync_to(target).await
+    }
+}
+
+/// Records the proposers elected, the same election is only recorded once in a row.
+pub struct RecordingProposerElection {
+    proposer_election: Box<dyn ProposerElection + Send + Sync>,
+    recorder: Arc<ConsensusRecorder>,
+    last_recorded: Mutex<Option<(Round, Author)>>,
+}
+
+impl RecordingProposerElection {
+    pub fn new(
+        proposer_election: Box<dyn ProposerElection + Send + Sync>,
+        recorder: Arc<ConsensusRecorder>,
+    ) -> Self {
+        Self {
+            proposer_election,
+            recorder,
+            last_recorded: Mutex::new(None),
+        }
+    }
+}
+
+impl ProposerElection for RecordingProposerElection {
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        let proposer = self.proposer_election.get_valid_proposer(round);
+        let mut last_recorded = self.last_recorded.lock().unwrap();
+        if *last_recorded != Some((round, proposer)) {
+            *last_recorded = Some((round, proposer));
+            self.recorder
+                .record(RecordedEvent::Proposer(round, proposer));
+        }
+        proposer
+    }
+}
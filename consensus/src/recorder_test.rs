@@ -0,0 +1,172 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_interface::ConsensusMsg,
+    recorder::{read_recording, ConsensusRecorder, RecordedEpochStart, RecordedEvent},
+    test_utils::{
+        replay::{replay, replay_file, Divergence},
+        MockStorage,
+    },
+};
+use consensus_types::{block::Block, proposal_msg::ProposalMsg, sync_info::SyncInfo};
+use libra_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
+use libra_types::{
+    epoch_state::EpochState,
+    validator_signer::ValidatorSigner,
+    validator_verifier::{random_validator_verifier, ValidatorVerifier},
+};
+use std::{env, fs, fs::OpenOptions, path::Path, time::Duration};
+
+/// Records the start of the first epoch followed by a proposal of the given timestamp.
+fn make_recording(
+    signer: &ValidatorSigner,
+    validators: &ValidatorVerifier,
+    timestamp_usecs: u64,
+) -> Vec<RecordedEvent> {
+    let (recovery_data, _) = MockStorage::start_for_testing(validators.into());
+    let epoch_state = EpochState {
+        epoch: 1,
+        verifier: validators.clone(),
+    };
+    let epoch_start = RecordedEpochStart::new(epoch_state, &recovery_data);
+    let genesis_qc = epoch_start
+        .quorum_certs
+        .iter()
+        .find(|qc| qc.certified_block().id() == recovery_data.root_block().id())
+        .unwrap()
+        .clone();
+    let proposal = Block::new_proposal(vec![], 1, timestamp_usecs, genesis_qc.clone(), signer);
+    let proposal_msg = ProposalMsg::new(
+        proposal,
+        SyncInfo::new(genesis_qc.clone(), genesis_qc, None),
+    );
+    vec![
+        RecordedEvent::EpochStart(Box::new(epoch_start)),
+        RecordedEvent::Proposer(1, signer.author()),
+        RecordedEvent::Message(
+            signer.author(),
+            ConsensusMsg::ProposalMsg(Box::new(proposal_msg)),
+        ),
+        RecordedEvent::Timestamp(Duration::from_micros(timestamp_usecs)),
+    ]
+}
+
+#[tokio::test]
+async fn test_recording_round_trip() {
+    let (signers, validators) = random_validator_verifier(1, None, false);
+    let events = make_recording(&signers[0], &validators, 1);
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let recorder = ConsensusRecorder::new(file.path()).unwrap();
+    for event in &events {
+        recorder.record(event.clone());
+    }
+
+    // The event truncated by a crash is ignored.
+    let len = file.as_file().metadata().unwrap().len();
+    recorder.record(RecordedEvent::LocalTimeout(2));
+    OpenOptions::new()
+        .write(true)
+        .open(file.path())
+        .unwrap()
+        .set_len(len + 6)
+        .unwrap();
+
+    let recorded = read_recording(file.path()).unwrap();
+    assert_eq!(
+        lcs::to_bytes(&recorded).unwrap(),
+        lcs::to_bytes(&events).unwrap()
+    );
+    let report = replay_file(file.path(), signers[0].clone()).await.unwrap();
+    assert_eq!(report.num_inputs, 2);
+}
+
+#[tokio::test]
+async fn test_replay_votes() {
+    let (signers, validators) = random_validator_verifier(1, None, false);
+    let signer = &signers[0];
+
+    // The vote for the proposal is missing from the recording.
+    let recording = make_recording(signer, &validators, 1);
+    let report = replay(recording.clone(), signer.clone()).await.unwrap();
+    assert_eq!(report.num_inputs, 2);
+    assert_eq!(report.num_votes, 1);
+    let vote = match &report.divergences[..] {
+        [Divergence::Vote {
+            input: 2,
+            recorded: None,
+            replayed: Some(vote),
+        }] => vote.clone(),
+        divergences => panic!("Unexpected divergences: {:?}", divergences),
+    };
+    assert_eq!(vote.vote_data().proposed().round(), 1);
+
+    // The replay matches the recording once the vote is recorded.
+    let mut recording = recording;
+    recording.push(RecordedEvent::Vote(Box::new(vote.clone())));
+    let report = replay(recording, signer.clone()).await.unwrap();
+    assert!(report.divergences.is_empty());
+
+    // The recorded vote is not the vote for the replayed proposal.
+    let mut recording = make_recording(signer, &validators, 2);
+    recording.push(RecordedEvent::Vote(Box::new(vote)));
+    let report = replay(recording, signer.clone()).await.unwrap();
+    assert!(matches!(
+        report.divergences[..],
+        [Divergence::Vote {
+            input: 2,
+            recorded: Some(_),
+            replayed: Some(_),
+        }]
+    ));
+}
+
+#[tokio::test]
+async fn test_replay_rejects_unsupported_modes() {
+    let (signers, validators) = random_validator_verifier(1, None, false);
+    for mode in 0..2 {
+        let mut recording = make_recording(&signers[0], &validators, 1);
+        if let RecordedEvent::EpochStart(epoch_start) = &mut recording[0] {
+            epoch_start.decoupled_execution = mode == 0;
+            epoch_start.batch_dissemination = mode == 1;
+        }
+        assert!(replay(recording, signers[0].clone()).await.is_err());
+    }
+}
+
+/// Replays the recording at the path given by CONSENSUS_REPLAY_RECORDING as the validator whose
+/// hex encoded consensus private key is in the file given by CONSENSUS_REPLAY_KEY:
+/// `cargo test -p consensus replay_recording -- --ignored --nocapture`
+#[tokio::test]
+#[ignore]
+async fn replay_recording() {
+    let recording = env::var("CONSENSUS_REPLAY_RECORDING").expect("CONSENSUS_REPLAY_RECORDING");
+    let key = env::var("CONSENSUS_REPLAY_KEY").expect("CONSENSUS_REPLAY_KEY");
+    let private_key =
+        Ed25519PrivateKey::from_encoded_string(fs::read_to_string(key).unwrap().trim()).unwrap();
+    let public_key = private_key.public_key();
+    let author = read_recording(Path::new(&recording))
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            RecordedEvent::EpochStart(epoch_start) => Some(epoch_start.epoch_state.clone()),
+            _ => None,
+        })
+        .find_map(|epoch_state| {
+            epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .find(|author| {
+                    epoch_state.verifier.get_public_key(author) == Some(public_key.clone())
+                })
+        })
+        .expect("The key is not the consensus key of a recorded validator");
+    let report = replay_file(
+        Path::new(&recording),
+        ValidatorSigner::new(author, private_key),
+    )
+    .await
+    .unwrap();
+    println!("{:#?}", report);
+    assert!(report.divergences.is_empty());
+}
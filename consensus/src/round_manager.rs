@@ -2,26 +2,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    batch_manager::BatchManager,
     block_storage::{BlockReader, BlockRetriever, BlockStore},
     counters,
+    execution_pipeline::{ExecutionEvent, PendingCommits},
     liveness::{
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         round_state::{NewRoundEvent, NewRoundReason, RoundState},
     },
     metrics_safety_rules::MetricsSafetyRules,
-    network::{IncomingBlockRetrievalRequest, NetworkSender},
+    network::{IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, NetworkSender},
     network_interface::ConsensusMsg,
     pending_votes::VoteReceptionResult,
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
     state_replication::{StateComputer, TxnManager},
     util::time_service::duration_since_epoch,
 };
-use anyhow::{bail, ensure, Context, Result};
+use anyhow::{bail, ensure, format_err, Context, Result};
 use consensus_types::{
+    batch::{AvailabilityCertificate, Batch, BatchVote},
+    batch_retrieval::BatchRetrievalResponse,
     block::Block,
     block_retrieval::{BlockRetrievalResponse, BlockRetrievalStatus},
+    commit_vote::CommitVote,
     common::{Author, Round},
+    compact_proposal_msg::{CompactBlock, CompactProposalMsg},
+    equivocation_evidence::EquivocationEvidence,
     proposal_msg::ProposalMsg,
     quorum_cert::QuorumCert,
     sync_info::SyncInfo,
@@ -29,9 +36,13 @@ use consensus_types::{
     vote::Vote,
     vote_msg::VoteMsg,
 };
+use futures::future::join_all;
 use libra_logger::prelude::*;
+use libra_metrics::monitor;
 use libra_trace::prelude::*;
-use libra_types::{epoch_state::EpochState, validator_verifier::ValidatorVerifier};
+use libra_types::{
+    epoch_change::EpochChangeProof, epoch_state::EpochState, validator_verifier::ValidatorVerifier,
+};
 #[cfg(test)]
 use safety_rules::ConsensusState;
 use safety_rules::TSafetyRules;
@@ -44,6 +55,12 @@ pub enum UnverifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    CommitVote(Box<CommitVote>),
+    BatchMsg(Box<Batch>),
+    BatchVote(Box<BatchVote>),
+    AvailabilityCertificate(Box<AvailabilityCertificate>),
+    CompactProposalMsg(Box<CompactProposalMsg>),
+    EquivocationEvidence(Box<EquivocationEvidence>),
 }
 
 impl UnverifiedEvent {
@@ -61,6 +78,30 @@ impl UnverifiedEvent {
                 s.verify(validator)?;
                 VerifiedEvent::SyncInfo(s)
             }
+            UnverifiedEvent::CommitVote(v) => {
+                v.verify(validator)?;
+                VerifiedEvent::CommitVote(v)
+            }
+            UnverifiedEvent::BatchMsg(b) => {
+                b.verify(validator)?;
+                VerifiedEvent::BatchMsg(b)
+            }
+            UnverifiedEvent::BatchVote(v) => {
+                v.verify(validator)?;
+                VerifiedEvent::BatchVote(v)
+            }
+            UnverifiedEvent::AvailabilityCertificate(c) => {
+                c.verify(validator)?;
+                VerifiedEvent::AvailabilityCertificate(c)
+            }
+            UnverifiedEvent::CompactProposalMsg(p) => {
+                p.verify(validator)?;
+                VerifiedEvent::CompactProposalMsg(p)
+            }
+            UnverifiedEvent::EquivocationEvidence(e) => {
+                e.verify(validator)?;
+                VerifiedEvent::EquivocationEvidence(e)
+            }
         })
     }
 
@@ -69,6 +110,12 @@ impl UnverifiedEvent {
             UnverifiedEvent::ProposalMsg(p) => p.epoch(),
             UnverifiedEvent::VoteMsg(v) => v.epoch(),
             UnverifiedEvent::SyncInfo(s) => s.epoch(),
+            UnverifiedEvent::CommitVote(v) => v.epoch(),
+            UnverifiedEvent::BatchMsg(b) => b.epoch(),
+            UnverifiedEvent::BatchVote(v) => v.epoch(),
+            UnverifiedEvent::AvailabilityCertificate(c) => c.epoch(),
+            UnverifiedEvent::CompactProposalMsg(p) => p.epoch(),
+            UnverifiedEvent::EquivocationEvidence(e) => e.epoch(),
         }
     }
 }
@@ -79,6 +126,12 @@ impl From<ConsensusMsg> for UnverifiedEvent {
             ConsensusMsg::ProposalMsg(m) => UnverifiedEvent::ProposalMsg(m),
             ConsensusMsg::VoteMsg(m) => UnverifiedEvent::VoteMsg(m),
             ConsensusMsg::SyncInfo(m) => UnverifiedEvent::SyncInfo(m),
+            ConsensusMsg::CommitVoteMsg(m) => UnverifiedEvent::CommitVote(m),
+            ConsensusMsg::BatchMsg(m) => UnverifiedEvent::BatchMsg(m),
+            ConsensusMsg::BatchVoteMsg(m) => UnverifiedEvent::BatchVote(m),
+            ConsensusMsg::AvailabilityCertificate(m) => UnverifiedEvent::AvailabilityCertificate(m),
+            ConsensusMsg::CompactProposalMsg(m) => UnverifiedEvent::CompactProposalMsg(m),
+            ConsensusMsg::EquivocationEvidence(m) => UnverifiedEvent::EquivocationEvidence(m),
             _ => unreachable!("Unexpected conversion"),
         }
     }
@@ -88,6 +141,12 @@ pub enum VerifiedEvent {
     ProposalMsg(Box<ProposalMsg>),
     VoteMsg(Box<VoteMsg>),
     SyncInfo(Box<SyncInfo>),
+    CommitVote(Box<CommitVote>),
+    BatchMsg(Box<Batch>),
+    BatchVote(Box<BatchVote>),
+    AvailabilityCertificate(Box<AvailabilityCertificate>),
+    CompactProposalMsg(Box<CompactProposalMsg>),
+    EquivocationEvidence(Box<EquivocationEvidence>),
 }
 
 #[cfg(test)]
@@ -106,6 +165,7 @@ pub struct RecoveryManager {
     storage: Arc<dyn PersistentLivenessStorage>,
     state_computer: Arc<dyn StateComputer>,
     last_committed_round: Round,
+    decoupled_execution: bool,
 }
 
 impl RecoveryManager {
@@ -115,6 +175,7 @@ impl RecoveryManager {
         storage: Arc<dyn PersistentLivenessStorage>,
         state_computer: Arc<dyn StateComputer>,
         last_committed_round: Round,
+        decoupled_execution: bool,
     ) -> Self {
         RecoveryManager {
             epoch_state,
@@ -122,6 +183,7 @@ impl RecoveryManager {
             storage,
             state_computer,
             last_committed_round,
+            decoupled_execution,
         }
     }
 
@@ -150,9 +212,19 @@ impl RecoveryManager {
             sync_info.epoch() == self.epoch_state.epoch,
             "[RecoveryManager] Received sync info is in different epoch than committed block"
         );
+        // In decoupled execution mode the commit cert only certifies the ordering, the node
+        // syncs to the commit proof of the execution pipeline instead.
+        let highest_commit_proof = if self.decoupled_execution {
+            Some(sync_info.highest_commit_proof().ok_or_else(|| {
+                format_err!("[RecoveryManager] Received sync info without commit proof")
+            })?)
+        } else {
+            None
+        };
         let mut retriever = BlockRetriever::new(self.network.clone(), peer);
         let recovery_data = BlockStore::fast_forward_sync(
             &sync_info.highest_commit_cert(),
+            highest_commit_proof,
             &mut retriever,
             self.storage.clone(),
             self.state_computer.clone(),
@@ -182,6 +254,12 @@ pub struct RoundManager {
     network: NetworkSender,
     txn_manager: Arc<dyn TxnManager>,
     storage: Arc<dyn PersistentLivenessStorage>,
+    /// Executed blocks waiting for commit votes in decoupled execution mode.
+    pending_commits: PendingCommits,
+    /// The batches and availability certificates in batch dissemination mode.
+    batch_manager: Option<Arc<BatchManager>>,
+    /// Gossip the evidence of the equivocations detected locally.
+    gossip_equivocation_evidence: bool,
 }
 
 impl RoundManager {
@@ -195,7 +273,10 @@ impl RoundManager {
         network: NetworkSender,
         txn_manager: Arc<dyn TxnManager>,
         storage: Arc<dyn PersistentLivenessStorage>,
+        batch_manager: Option<Arc<BatchManager>>,
+        gossip_equivocation_evidence: bool,
     ) -> Self {
+        let pending_commits = PendingCommits::new(block_store.root().round());
         Self {
             epoch_state,
             block_store,
@@ -206,6 +287,9 @@ impl RoundManager {
             txn_manager,
             network,
             storage,
+            pending_commits,
+            batch_manager,
+            gossip_equivocation_evidence,
         }
     }
 
@@ -225,6 +309,9 @@ impl RoundManager {
     /// Replica:
     ///
     /// Do nothing
+    ///
+    /// In batch dissemination mode every replica also broadcasts a new batch of transactions
+    /// and the leader broadcasts the compact version of its proposal.
     async fn process_new_round_event(
         &mut self,
         new_round_event: NewRoundEvent,
@@ -240,18 +327,66 @@ impl RoundManager {
                 counters::TIMEOUT_ROUNDS_COUNT.inc();
             }
         };
+        if let Some(batch_manager) = self.batch_manager.clone() {
+            batch_manager.update_round(new_round_event.round, self.block_store.root().round());
+            if let Err(e) = self.broadcast_new_batch(&batch_manager).await {
+                counters::ERROR_COUNT.inc();
+                warn!("[RoundManager] Failed to create a new batch: {:?}", e);
+            }
+        }
         if self
             .proposer_election
             .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
         {
             let proposal_msg = self.generate_proposal(new_round_event).await?;
             let mut network = self.network.clone();
-            network.broadcast_proposal(proposal_msg).await;
+            match self
+                .batch_manager
+                .as_ref()
+                .and_then(|batch_manager| batch_manager.proposed_batches(&proposal_msg))
+            {
+                Some(certificates) => {
+                    let compact_block = CompactBlock::new(proposal_msg.proposal(), &certificates)?;
+                    let compact_signature = self.safety_rules.sign_compact_block(&compact_block)?;
+                    let compact_proposal_msg =
+                        CompactProposalMsg::new(&proposal_msg, certificates, compact_signature)?;
+                    network
+                        .broadcast_compact_proposal(compact_proposal_msg)
+                        .await
+                }
+                None => network.broadcast_proposal(proposal_msg).await,
+            }
             counters::PROPOSALS_COUNT.inc();
         }
         Ok(())
     }
 
+    /// Pulls a new batch from mempool, the batch is stored and voted locally before it is
+    /// broadcast to the other validators.
+    async fn broadcast_new_batch(&mut self, batch_manager: &BatchManager) -> anyhow::Result<()> {
+        let (batch_info, payload) = match batch_manager.pull_batch().await? {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+        let signature = self.safety_rules.sign_batch_info(&batch_info)?;
+        let batch = Batch::new_with_signature(batch_info, payload, signature);
+        debug!("Created {}", batch);
+        counters::CREATED_BATCHES_COUNT.inc();
+        batch_manager.add_batch(batch.clone());
+        let batch_vote = BatchVote::new_with_signature(
+            batch.author(),
+            batch.info().clone(),
+            batch.signature().clone(),
+        );
+        if let Some(certificate) = batch_manager.add_vote(&batch_vote, &self.epoch_state.verifier) {
+            self.network
+                .broadcast_availability_certificate(certificate)
+                .await;
+        }
+        self.network.broadcast_batch(batch).await;
+        Ok(())
+    }
+
     async fn generate_proposal(
         &mut self,
         new_round_event: NewRoundEvent,
@@ -273,6 +408,46 @@ impl RoundManager {
         ))
     }
 
+    /// Dispatches a verified event of the current epoch to its handler.
+    pub async fn process_event(&mut self, peer_id: Author, event: VerifiedEvent) -> Result<()> {
+        match event {
+            VerifiedEvent::ProposalMsg(proposal) => monitor!(
+                "process_proposal",
+                self.process_proposal_msg(*proposal).await
+            ),
+            VerifiedEvent::VoteMsg(vote) => {
+                monitor!("process_vote", self.process_vote_msg(*vote).await)
+            }
+            VerifiedEvent::SyncInfo(sync_info) => monitor!(
+                "process_sync_info",
+                self.process_sync_info_msg(*sync_info, peer_id).await
+            ),
+            VerifiedEvent::CommitVote(commit_vote) => monitor!(
+                "process_commit_vote",
+                self.process_commit_vote(*commit_vote).await
+            ),
+            VerifiedEvent::BatchMsg(batch) => {
+                monitor!("process_batch", self.process_batch_msg(*batch).await)
+            }
+            VerifiedEvent::BatchVote(batch_vote) => monitor!(
+                "process_batch_vote",
+                self.process_batch_vote_msg(*batch_vote).await
+            ),
+            VerifiedEvent::AvailabilityCertificate(certificate) => monitor!(
+                "process_availability_certificate",
+                self.process_availability_certificate(*certificate)
+            ),
+            VerifiedEvent::CompactProposalMsg(proposal) => monitor!(
+                "process_compact_proposal",
+                self.process_compact_proposal_msg(*proposal).await
+            ),
+            VerifiedEvent::EquivocationEvidence(evidence) => monitor!(
+                "process_equivocation_evidence",
+                self.process_equivocation_evidence(*evidence).await
+            ),
+        }
+    }
+
     /// Process the proposal message:
     /// 1. ensure after processing sync info, we're at the same round as the proposal
     /// 2. execute and decide whether to vode for the proposal
@@ -339,6 +514,9 @@ impl RoundManager {
                 .add_certs(&sync_info, self.create_block_retriever(author))
                 .await;
             self.process_certificates().await?;
+            if self.block_store.decoupled_execution() {
+                self.process_commit_proof(&sync_info).await?;
+            }
             result
         } else {
             Ok(())
@@ -456,6 +634,19 @@ impl RoundManager {
             proposal,
         );
 
+        if let Some(evidence) = self.round_state.record_proposal(&proposal) {
+            send_struct_log!(
+                security_log(security_events::CONSENSUS_EQUIVOCATING_PROPOSAL)
+                    .data("from_peer", evidence.author())
+                    .data("evidence", &evidence)
+            );
+            self.record_equivocation(evidence, true).await?;
+            bail!(
+                "[RoundManager] Proposer sent another proposal {} in this round",
+                proposal
+            );
+        }
+
         let block_time_since_epoch = Duration::from_micros(proposal.timestamp_usecs());
 
         ensure!(
@@ -495,23 +686,30 @@ impl RoundManager {
     /// * then verify the voting rules
     /// * save the updated state to consensus DB
     /// * return a VoteMsg with the LedgerInfo to be committed in case the vote gathers QC.
+    ///
+    /// In decoupled execution mode the block is only inserted and the vote is on its ordering,
+    /// the execution happens in the execution pipeline once the block is committed.
     async fn execute_and_vote(&mut self, proposed_block: Block) -> anyhow::Result<Vote> {
         trace_code_block!("round_manager::execute_and_vote", {"block", proposed_block.id()});
         let executed_block = self
             .block_store
             .execute_and_insert_block(proposed_block)
             .context("[RoundManager] Failed to execute_and_insert the block")?;
-        // notify mempool about failed txn
-        let compute_result = executed_block.compute_result();
-        if let Err(e) = self
-            .txn_manager
-            .notify(executed_block.block(), compute_result)
-            .await
-        {
-            error!(
-                "[RoundManager] Failed to notify mempool of rejected txns: {:?}",
-                e
-            );
+        let decoupled_execution = self.block_store.decoupled_execution();
+        // notify mempool about failed txn, the execution pipeline does it after executing the
+        // block in decoupled execution mode
+        if !decoupled_execution {
+            let compute_result = executed_block.compute_result();
+            if let Err(e) = self
+                .txn_manager
+                .notify(executed_block.block(), compute_result)
+                .await
+            {
+                error!(
+                    "[RoundManager] Failed to notify mempool of rejected txns: {:?}",
+                    e
+                );
+            }
         }
 
         // Short circuit if already voted.
@@ -521,7 +719,11 @@ impl RoundManager {
             self.round_state.current_round()
         );
 
-        let maybe_signed_vote_proposal = executed_block.maybe_signed_vote_proposal();
+        let maybe_signed_vote_proposal = if decoupled_execution {
+            executed_block.maybe_signed_ordered_vote_proposal()
+        } else {
+            executed_block.maybe_signed_vote_proposal()
+        };
         let vote = self
             .safety_rules
             .construct_and_sign_vote(&maybe_signed_vote_proposal)
@@ -609,6 +811,9 @@ impl RoundManager {
                 self.new_qc_aggregated(qc, vote.author()).await
             }
             VoteReceptionResult::NewTimeoutCertificate(tc) => self.new_tc_aggregated(tc).await,
+            VoteReceptionResult::EquivocateVote(evidence) => {
+                self.record_equivocation(*evidence, true).await
+            }
             _ => Ok(()),
         }
     }
@@ -636,6 +841,285 @@ impl RoundManager {
         result
     }
 
+    /// In decoupled execution mode, process the events of the execution pipeline:
+    /// * sign and broadcast the commit vote once the ordered blocks are executed
+    /// * prune the committed blocks and broadcast the epoch change if they end the epoch
+    pub async fn process_execution_event(&mut self, event: ExecutionEvent) -> anyhow::Result<()> {
+        match event {
+            ExecutionEvent::Executed(executed_blocks) => {
+                let epoch = executed_blocks.ordered_proof.ledger_info().epoch();
+                if epoch != self.epoch_state.epoch {
+                    debug!("[RoundManager] Ignore blocks executed in epoch {}", epoch);
+                    return Ok(());
+                }
+                let ledger_info = executed_blocks.ledger_info();
+                let signature = self
+                    .safety_rules
+                    .sign_commit_vote(
+                        executed_blocks.ordered_proof.clone(),
+                        ledger_info.clone(),
+                        &executed_blocks.maybe_signed_vote_proposal(),
+                    )
+                    .context(format!(
+                        "[RoundManager] SafetyRules {}Rejected{} {}",
+                        Fg(Red),
+                        Fg(Reset),
+                        ledger_info
+                    ))?;
+                let commit_vote = CommitVote::new_with_signature(
+                    self.proposal_generator.author(),
+                    ledger_info,
+                    signature,
+                );
+                debug!("{}Commit voted{} {}", Fg(Green), Fg(Reset), commit_vote);
+                self.network.broadcast_commit_vote(commit_vote).await;
+                if let Some((executed_blocks, commit_proof)) = self
+                    .pending_commits
+                    .add_executed(executed_blocks, &self.epoch_state.verifier)
+                {
+                    self.block_store
+                        .commit_executed(executed_blocks, commit_proof)
+                        .await?;
+                }
+                Ok(())
+            }
+            ExecutionEvent::Committed(executed_blocks, commit_proof) => {
+                self.block_store
+                    .process_committed(&executed_blocks.blocks, &commit_proof);
+                if commit_proof.ledger_info().ends_epoch() {
+                    self.network
+                        .broadcast_epoch_change(EpochChangeProof::new(
+                            vec![commit_proof],
+                            /* more = */ false,
+                        ))
+                        .await;
+                }
+                Ok(())
+            }
+            ExecutionEvent::ExecutionFailed(ordered_proof) => {
+                if ordered_proof.ledger_info().epoch() != self.epoch_state.epoch {
+                    return Ok(());
+                }
+                // The blocks are synced along with their execution result once a peer shares a
+                // newer commit proof.
+                warn!(
+                    "[RoundManager] Failed to execute the blocks ordered by {}, sync to the next commit proof",
+                    ordered_proof.ledger_info()
+                );
+                self.block_store.set_execution_failed();
+                Ok(())
+            }
+        }
+    }
+
+    /// Add a commit vote, once a quorum of commit votes matches the local execution result the
+    /// executed blocks are sent back to the execution pipeline for committing.
+    pub async fn process_commit_vote(&mut self, commit_vote: CommitVote) -> anyhow::Result<()> {
+        ensure!(
+            self.block_store.decoupled_execution(),
+            "[RoundManager] Received {} while decoupled execution is disabled",
+            commit_vote
+        );
+        debug!("Add commit vote: {}", commit_vote);
+        if let Some((executed_blocks, commit_proof)) = self
+            .pending_commits
+            .add_commit_vote(&commit_vote, &self.epoch_state.verifier)
+        {
+            self.block_store
+                .commit_executed(executed_blocks, commit_proof)
+                .await
+                .context("[RoundManager] Failed to commit the executed blocks")?;
+        }
+        Ok(())
+    }
+
+    /// In decoupled execution mode, skip the commits the node synced past and commit the local
+    /// execution result certified by the commit proof of the peer, e.g. when the commit votes
+    /// have been missed.
+    async fn process_commit_proof(&mut self, sync_info: &SyncInfo) -> anyhow::Result<()> {
+        self.pending_commits
+            .skip_to(self.block_store.root().round());
+        if let Some(commit_proof) = sync_info.highest_commit_proof() {
+            if let Some((executed_blocks, commit_proof)) = self
+                .pending_commits
+                .add_commit_proof(commit_proof, &self.epoch_state.verifier)
+            {
+                self.block_store
+                    .commit_executed(executed_blocks, commit_proof)
+                    .await
+                    .context("[RoundManager] Failed to commit the executed blocks")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn batch_manager(&self) -> anyhow::Result<Arc<BatchManager>> {
+        self.batch_manager
+            .clone()
+            .ok_or_else(|| format_err!("[RoundManager] Batch dissemination is disabled"))
+    }
+
+    /// Store a batch broadcast by another validator and send the availability vote back to its
+    /// author. The batches created locally are already stored and voted.
+    pub async fn process_batch_msg(&mut self, batch: Batch) -> anyhow::Result<()> {
+        let batch_manager = self.batch_manager()?;
+        let batch_info = batch.info().clone();
+        if !batch_manager.add_batch(batch) {
+            return Ok(());
+        }
+        let signature = self
+            .safety_rules
+            .sign_batch_info(&batch_info)
+            .context(format!(
+                "[RoundManager] SafetyRules {}Rejected{} {}",
+                Fg(Red),
+                Fg(Reset),
+                batch_info
+            ))?;
+        let author = batch_info.author();
+        let batch_vote =
+            BatchVote::new_with_signature(self.proposal_generator.author(), batch_info, signature);
+        debug!("Send {}", batch_vote);
+        self.network.send_batch_vote(batch_vote, author).await;
+        Ok(())
+    }
+
+    /// Add a vote for a batch created locally, broadcast the availability certificate once the
+    /// votes form a quorum.
+    pub async fn process_batch_vote_msg(&mut self, batch_vote: BatchVote) -> anyhow::Result<()> {
+        let batch_manager = self.batch_manager()?;
+        if let Some(certificate) = batch_manager.add_vote(&batch_vote, &self.epoch_state.verifier) {
+            debug!("Certified {}", certificate);
+            self.network
+                .broadcast_availability_certificate(certificate)
+                .await;
+        }
+        Ok(())
+    }
+
+    pub fn process_availability_certificate(
+        &mut self,
+        certificate: AvailabilityCertificate,
+    ) -> anyhow::Result<()> {
+        self.batch_manager()?.add_certificate(certificate);
+        Ok(())
+    }
+
+    /// Process the compact proposal message, whose compact signature has been verified:
+    /// 1. ensure the proposal comes from the valid proposer of its round
+    /// 2. fetch the batches missing locally from the validators that certified them, off the
+    /// event loop: the proposal is delivered again once they are stored
+    /// 3. rebuild the full proposal from the batches and process it as a regular proposal
+    pub async fn process_compact_proposal_msg(
+        &mut self,
+        compact_proposal_msg: CompactProposalMsg,
+    ) -> anyhow::Result<()> {
+        let batch_manager = self.batch_manager()?;
+        ensure!(
+            compact_proposal_msg.round() >= self.round_state.current_round(),
+            "Stale compact proposal {}, current round {}",
+            compact_proposal_msg,
+            self.round_state.current_round()
+        );
+        let proposer = compact_proposal_msg
+            .proposer()
+            .ok_or_else(|| format_err!("{} does not define an author", compact_proposal_msg))?;
+        ensure!(
+            self.proposer_election
+                .is_valid_proposer(proposer, compact_proposal_msg.round()),
+            "[RoundManager] {} is not from the valid proposer",
+            compact_proposal_msg
+        );
+        let missing: Vec<_> = compact_proposal_msg
+            .batches()
+            .iter()
+            .filter(|certificate| batch_manager.get_batch(certificate.digest()).is_none())
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            tokio::spawn(fetch_batches(
+                self.network.clone(),
+                batch_manager,
+                self.proposal_generator.author(),
+                compact_proposal_msg,
+                missing,
+            ));
+            return Ok(());
+        }
+        let payloads = compact_proposal_msg
+            .batches()
+            .iter()
+            .map(|certificate| {
+                batch_manager
+                    .get_batch(certificate.digest())
+                    .map(|batch| batch.payload().clone())
+                    .ok_or_else(|| format_err!("[RoundManager] Missing {}", certificate))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let certificates = compact_proposal_msg.batches().to_vec();
+        let round = compact_proposal_msg.round();
+        let proposal_msg = compact_proposal_msg.into_proposal(payloads)?;
+        proposal_msg
+            .verify(&self.epoch_state.verifier)
+            .context("[RoundManager] Invalid compact proposal")?;
+        counters::NUM_BATCHES_PER_BLOCK.observe(certificates.len() as f64);
+        batch_manager.mark_proposed(&certificates, round);
+        self.process_proposal_msg(proposal_msg).await
+    }
+
+    /// Retrieve a batch stored locally by its digest, the response carries no batch if it is
+    /// missing.
+    pub async fn process_batch_retrieval(
+        &self,
+        request: IncomingBatchRetrievalRequest,
+    ) -> anyhow::Result<()> {
+        let batch = self
+            .batch_manager
+            .as_ref()
+            .and_then(|batch_manager| batch_manager.get_batch(request.req.digest()));
+        let response = Box::new(BatchRetrievalResponse::new(batch));
+        lcs::to_bytes(&ConsensusMsg::BatchRetrievalResponse(response))
+            .and_then(|bytes| {
+                request
+                    .response_sender
+                    .send(Ok(bytes.into()))
+                    .map_err(|e| lcs::Error::Custom(format!("{:?}", e)))
+            })
+            .context("[RoundManager] Failed to process batch retrieval")
+    }
+
+    /// Process the evidence of an equivocation gossiped by another validator.
+    pub async fn process_equivocation_evidence(
+        &mut self,
+        evidence: EquivocationEvidence,
+    ) -> anyhow::Result<()> {
+        self.record_equivocation(evidence, false).await
+    }
+
+    /// Persist the evidence of an equivocation for auditing, the evidence detected locally is
+    /// gossiped to the other validators if enabled.
+    async fn record_equivocation(
+        &mut self,
+        evidence: EquivocationEvidence,
+        detected_locally: bool,
+    ) -> anyhow::Result<()> {
+        if !self
+            .storage
+            .save_equivocation_evidence(&evidence)
+            .context("[RoundManager] Failed to persist equivocation evidence")?
+        {
+            return Ok(());
+        }
+        counters::EQUIVOCATION_EVIDENCE_COUNT
+            .with_label_values(&[evidence.kind()])
+            .inc();
+        warn!("[RoundManager] Recorded {}", evidence);
+        if detected_locally && self.gossip_equivocation_evidence {
+            self.network.broadcast_equivocation_evidence(evidence).await;
+        }
+        Ok(())
+    }
+
     /// Retrieve a n chained blocks from the block store starting from
     /// an initial parent id, returning with <n (as many as possible) if
     /// id or its ancestors can not be found.
@@ -683,6 +1167,19 @@ impl RoundManager {
         if let Some(vote) = last_vote_sent {
             self.round_state.record_vote(vote);
         }
+        // Resume the execution of the blocks ordered before a restart.
+        if self.block_store.decoupled_execution() {
+            let highest_commit_cert = self.block_store.highest_commit_cert();
+            if highest_commit_cert.commit_info().round() > self.block_store.ordered_root().round() {
+                if let Err(e) = self
+                    .block_store
+                    .commit(highest_commit_cert.ledger_info().clone())
+                    .await
+                {
+                    error!("[RoundManager] Error resuming execution: {:?}", e);
+                }
+            }
+        }
         if let Err(e) = self.process_new_round_event(new_round_event).await {
             error!("[RoundManager] Error during start: {:?}", e);
         }
@@ -707,3 +1204,78 @@ impl RoundManager {
         &self.round_state
     }
 }
+
+/// Fetches the batches of a compact proposal missing locally concurrently, the proposal is
+/// delivered back to the event loop once all of them are stored.
+async fn fetch_batches(
+    mut network: NetworkSender,
+    batch_manager: Arc<BatchManager>,
+    author: Author,
+    compact_proposal_msg: CompactProposalMsg,
+    missing: Vec<AvailabilityCertificate>,
+) {
+    let timeout = batch_manager.request_timeout();
+    let fetched = join_all(
+        missing
+            .iter()
+            .map(|certificate| fetch_batch(network.clone(), author, certificate, timeout)),
+    )
+    .await;
+    for (certificate, batch) in missing.iter().zip(fetched) {
+        match batch {
+            Ok(batch) => {
+                batch_manager.add_batch(batch);
+            }
+            Err(e) => {
+                warn!("{:?}", e);
+            }
+        }
+        // The batch might be rejected, e.g. when its author used up its storage.
+        if batch_manager.get_batch(certificate.digest()).is_none() {
+            warn!(
+                "[RoundManager] Drop {}, {} is missing",
+                compact_proposal_msg, certificate
+            );
+            return;
+        }
+    }
+    network.notify_compact_proposal(compact_proposal_msg).await
+}
+
+/// Fetch a batch from the validators that certified it, starting with its author.
+async fn fetch_batch(
+    mut network: NetworkSender,
+    author: Author,
+    certificate: &AvailabilityCertificate,
+    timeout: Duration,
+) -> anyhow::Result<Batch> {
+    let batch_author = certificate.info().author();
+    let peers = std::iter::once(batch_author).chain(
+        certificate
+            .signatures()
+            .keys()
+            .cloned()
+            .filter(|peer| *peer != batch_author),
+    );
+    for peer in peers {
+        if peer == author {
+            continue;
+        }
+        match network
+            .request_batch(certificate.digest(), peer, timeout)
+            .await
+        {
+            Ok(batch) => {
+                counters::FETCHED_BATCHES_COUNT.inc();
+                return Ok(batch);
+            }
+            Err(e) => warn!(
+                "[RoundManager] Failed to fetch {} from {}: {:?}",
+                certificate,
+                peer.short_str(),
+                e
+            ),
+        }
+    }
+    bail!("[RoundManager] Failed to fetch {}", certificate)
+}
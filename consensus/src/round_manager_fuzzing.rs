@@ -70,6 +70,7 @@ fn build_empty_store(
         Arc::new(EmptyStateComputer),
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
+        None, // execution pipeline
     ))
 }
 
@@ -106,7 +107,7 @@ fn create_node_for_fuzzing() -> RoundManager {
 
     // TODO: remove
     let proof = make_initial_epoch_change_proof(&signer);
-    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), false);
+    let mut safety_rules = SafetyRules::new(test_utils::test_storage(&signer), false, false);
     safety_rules.initialize(&proof).unwrap();
 
     // TODO: mock channels
@@ -129,6 +130,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         network_sender,
         self_sender,
         epoch_state.verifier.clone(),
+        None,
     );
 
     // TODO: mock
@@ -164,6 +166,8 @@ fn create_node_for_fuzzing() -> RoundManager {
         network,
         Arc::new(MockTransactionManager::new(None)),
         storage,
+        None,
+        false,
     )
 }
 
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    batch_manager::BatchManager,
     block_storage::{BlockReader, BlockStore},
+    execution_pipeline::{ExecutionEvent, ExecutionPipeline},
     liveness::{
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
@@ -10,7 +12,7 @@ use crate::{
         round_state::{ExponentialTimeInterval, RoundState},
     },
     metrics_safety_rules::MetricsSafetyRules,
-    network::{IncomingBlockRetrievalRequest, NetworkSender},
+    network::{IncomingBatchRetrievalRequest, IncomingBlockRetrievalRequest, NetworkSender},
     network_interface::{ConsensusMsg, ConsensusNetworkEvents, ConsensusNetworkSender},
     network_tests::{NetworkPlayground, TwinId},
     persistent_liveness_storage::RecoveryData,
@@ -23,16 +25,20 @@ use crate::{
 };
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
+    batch::{AvailabilityCertificate, Batch, BatchInfo},
     block::{
-        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        block_test_utils::{certificate_for_genesis, gen_test_certificate, random_payload},
         Block,
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
+    commit_vote::CommitVote,
     common::{Author, Payload},
+    compact_proposal_msg::{CompactBlock, CompactProposalMsg},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     timeout::Timeout,
     timeout_certificate::TimeoutCertificate,
+    vote::Vote,
     vote_msg::VoteMsg,
 };
 use futures::{
@@ -41,7 +47,10 @@ use futures::{
     stream::select,
     Stream, StreamExt, TryStreamExt,
 };
-use libra_crypto::{ed25519::Ed25519PrivateKey, HashValue, Uniform};
+use libra_config::config::ConsensusConfig;
+use libra_crypto::{
+    ed25519::Ed25519PrivateKey, hash::ACCUMULATOR_PLACEHOLDER_HASH, HashValue, Uniform,
+};
 use libra_secure_storage::Storage;
 use libra_types::{
     epoch_state::EpochState,
@@ -70,6 +79,7 @@ pub struct NodeSetup {
     all_events: Box<dyn Stream<Item = anyhow::Result<Event<ConsensusMsg>>> + Send + Unpin>,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
     state_sync_receiver: mpsc::UnboundedReceiver<Payload>,
+    execution_events: Option<channel::Receiver<ExecutionEvent>>,
     id: usize,
 }
 
@@ -89,6 +99,15 @@ impl NodeSetup {
         playground: &mut NetworkPlayground,
         executor: Handle,
         num_nodes: usize,
+    ) -> Vec<Self> {
+        Self::create_nodes_with_execution_mode(playground, executor, num_nodes, false)
+    }
+
+    fn create_nodes_with_execution_mode(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+        decoupled_execution: bool,
     ) -> Vec<Self> {
         let (signers, validators) = random_validator_verifier(num_nodes, None, false);
         let proposer_author = signers[0].author();
@@ -107,7 +126,8 @@ impl NodeSetup {
                 Ed25519PrivateKey::generate_for_testing(),
                 waypoint,
             );
-            let safety_rules_manager = SafetyRulesManager::new_local(safety_storage, false);
+            let safety_rules_manager =
+                SafetyRulesManager::new_local(safety_storage, false, decoupled_execution);
 
             nodes.push(Self::new(
                 playground,
@@ -117,6 +137,7 @@ impl NodeSetup {
                 storage,
                 initial_data,
                 safety_rules_manager,
+                decoupled_execution,
                 id,
             ));
         }
@@ -131,6 +152,7 @@ impl NodeSetup {
         storage: Arc<MockStorage>,
         initial_data: RecoveryData,
         safety_rules_manager: SafetyRulesManager,
+        decoupled_execution: bool,
         id: usize,
     ) -> Self {
         let epoch_state = EpochState {
@@ -159,7 +181,13 @@ impl NodeSetup {
         playground.add_node(twin_id, consensus_tx, network_reqs_rx, conn_mgr_reqs_rx);
 
         let (self_sender, self_receiver) = channel::new_test(1000);
-        let network = NetworkSender::new(author, network_sender, self_sender, validators.clone());
+        let network = NetworkSender::new(
+            author,
+            network_sender,
+            self_sender,
+            validators.clone(),
+            None,
+        );
 
         let all_events = Box::new(select(network_events, self_receiver));
 
@@ -171,7 +199,24 @@ impl NodeSetup {
             commit_cb_sender,
             Arc::clone(&storage),
         ));
-        let time_service = Arc::new(ClockTimeService::new(executor));
+        let time_service = Arc::new(ClockTimeService::new(executor.clone()));
+
+        let (execution_pipeline, execution_events) = if decoupled_execution {
+            let (request_sender, request_receiver) = channel::new_test(1_024);
+            let (event_sender, event_receiver) = channel::new_test(1_024);
+            executor.spawn(
+                ExecutionPipeline::new(
+                    state_computer.clone(),
+                    Arc::new(MockTransactionManager::new(None)),
+                    request_receiver,
+                    event_sender,
+                )
+                .start(),
+            );
+            (Some(request_sender), Some(event_receiver))
+        } else {
+            (None, None)
+        };
 
         let block_store = Arc::new(BlockStore::new(
             storage.clone(),
@@ -179,6 +224,7 @@ impl NodeSetup {
             state_computer,
             10, // max pruned blocks in mem
             time_service.clone(),
+            execution_pipeline,
         ));
 
         let proposal_generator = ProposalGenerator::new(
@@ -205,6 +251,8 @@ impl NodeSetup {
             network,
             Arc::new(MockTransactionManager::new(None)),
             storage.clone(),
+            None,
+            false,
         );
         block_on(round_manager.start(last_vote_sent));
         Self {
@@ -218,6 +266,7 @@ impl NodeSetup {
             all_events,
             commit_cb_receiver,
             state_sync_receiver,
+            execution_events,
             id,
         }
     }
@@ -235,6 +284,7 @@ impl NodeSetup {
             self.storage,
             recover_data,
             self.safety_rules_manager,
+            self.execution_events.is_some(),
             self.id,
         )
     }
@@ -259,6 +309,80 @@ impl NodeSetup {
         }
     }
 
+    pub async fn next_commit_vote(&mut self) -> CommitVote {
+        match self.all_events.next().await.unwrap().unwrap() {
+            Event::Message((_, msg)) => match msg {
+                ConsensusMsg::CommitVoteMsg(v) => *v,
+                msg => panic!("Unexpected Consensus Message: {:?}", msg),
+            },
+            _ => panic!("Unexpected Network Event"),
+        }
+    }
+
+    pub async fn next_execution_event(&mut self) -> ExecutionEvent {
+        self.execution_events
+            .as_mut()
+            .expect("Decoupled execution is not enabled")
+            .next()
+            .await
+            .unwrap()
+    }
+
+    pub async fn next_block_retrieval(&mut self) -> IncomingBlockRetrievalRequest {
+        match self.all_events.next().await.unwrap().unwrap() {
+            Event::RpcRequest((_, msg, response_sender)) => match msg {
+                ConsensusMsg::BlockRetrievalRequest(req) => IncomingBlockRetrievalRequest {
+                    req: *req,
+                    response_sender,
+                },
+                msg => panic!("Unexpected Consensus Message: {:?}", msg),
+            },
+            _ => panic!("Unexpected Network Event"),
+        }
+    }
+
+    pub async fn next_batch_retrieval(&mut self) -> IncomingBatchRetrievalRequest {
+        match self.all_events.next().await.unwrap().unwrap() {
+            Event::RpcRequest((_, msg, response_sender)) => match msg {
+                ConsensusMsg::BatchRetrievalRequest(req) => IncomingBatchRetrievalRequest {
+                    req: *req,
+                    response_sender,
+                },
+                msg => panic!("Unexpected Consensus Message: {:?}", msg),
+            },
+            _ => panic!("Unexpected Network Event"),
+        }
+    }
+
+    pub async fn next_compact_proposal(&mut self) -> CompactProposalMsg {
+        match self.all_events.next().await.unwrap().unwrap() {
+            Event::Message((_, msg)) => match msg {
+                ConsensusMsg::CompactProposalMsg(p) => *p,
+                msg => panic!("Unexpected Consensus Message: {:?}", msg),
+            },
+            _ => panic!("Unexpected Network Event"),
+        }
+    }
+
+    /// Enables batch dissemination on the node, returns its BatchManager.
+    pub fn enable_batch_dissemination(&mut self) -> Arc<BatchManager> {
+        let batch_manager = Arc::new(BatchManager::new(
+            self.signer.author(),
+            1,
+            Arc::new(MockTransactionManager::new(None)),
+            &ConsensusConfig::default(),
+        ));
+        self.round_manager.batch_manager = Some(batch_manager.clone());
+        batch_manager
+    }
+
+    pub fn twin_id(&self) -> TwinId {
+        TwinId {
+            id: self.id,
+            author: self.signer.author(),
+        }
+    }
+
     pub async fn next_sync_info(&mut self) -> SyncInfo {
         match self.all_events.next().await.unwrap().unwrap() {
             Event::Message((_, msg)) => match msg {
@@ -853,7 +977,7 @@ fn safety_rules_crash() {
             node.round_manager.consensus_state().waypoint(),
         );
 
-        node.safety_rules_manager = SafetyRulesManager::new_local(safety_storage, false);
+        node.safety_rules_manager = SafetyRulesManager::new_local(safety_storage, false, false);
         let safety_rules =
             MetricsSafetyRules::new(node.safety_rules_manager.client(), node.storage.clone());
         node.round_manager.set_safety_rules(safety_rules);
@@ -890,3 +1014,508 @@ fn safety_rules_crash() {
         node.next_proposal().await;
     });
 }
+
+#[test]
+fn decoupled_execution_commit() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_execution_mode(
+        &mut playground,
+        runtime.handle().clone(),
+        1,
+        true,
+    );
+    let mut node = nodes.pop().unwrap();
+    runtime.spawn(playground.start());
+    timed_block_on(&mut runtime, async {
+        let genesis = node.block_store.root();
+        // order block 1 after 3 rounds
+        let mut block_1 = None;
+        for _ in 1..=3 {
+            let proposal_msg = node.next_proposal().await;
+            block_1.get_or_insert_with(|| proposal_msg.proposal().clone());
+
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            // The votes only certify the ordering of the blocks
+            assert!(vote_msg.vote().vote_data().proposed().is_ordered_only());
+            // Adding vote to form a QC
+            node.round_manager.process_vote_msg(vote_msg).await.unwrap();
+        }
+        let block_1 = block_1.unwrap();
+        // Block 1 is sent for execution but the root stays until it is committed
+        assert_eq!(node.block_store.ordered_root().id(), block_1.id());
+        assert_eq!(node.block_store.root().id(), genesis.id());
+        let _ = node.next_proposal().await;
+
+        let executed_blocks = match node.next_execution_event().await {
+            ExecutionEvent::Executed(executed_blocks) => executed_blocks,
+            event => panic!("Unexpected execution event: {:?}", event),
+        };
+        assert_eq!(executed_blocks.blocks.len(), 1);
+        assert_eq!(executed_blocks.blocks[0].id(), block_1.id());
+        node.round_manager
+            .process_execution_event(ExecutionEvent::Executed(executed_blocks))
+            .await
+            .unwrap();
+
+        // A single commit vote forms a quorum
+        let commit_vote = node.next_commit_vote().await;
+        assert_eq!(commit_vote.ledger_info().consensus_block_id(), block_1.id());
+        node.round_manager
+            .process_commit_vote(commit_vote)
+            .await
+            .unwrap();
+        let commit_proof = node.commit_cb_receiver.next().await.unwrap();
+        assert_eq!(
+            commit_proof.ledger_info().consensus_block_id(),
+            block_1.id()
+        );
+
+        let event = node.next_execution_event().await;
+        match &event {
+            ExecutionEvent::Committed(_, proof) => assert_eq!(*proof, commit_proof),
+            event => panic!("Unexpected execution event: {:?}", event),
+        }
+        node.round_manager
+            .process_execution_event(event)
+            .await
+            .unwrap();
+        assert_eq!(node.block_store.root().id(), block_1.id());
+    });
+}
+
+#[test]
+fn decoupled_execution_failed() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_execution_mode(
+        &mut playground,
+        runtime.handle().clone(),
+        1,
+        true,
+    );
+    let mut node = nodes.pop().unwrap();
+    runtime.spawn(playground.start());
+    timed_block_on(&mut runtime, async {
+        // order block 1 after 3 rounds
+        for _ in 1..=3 {
+            let proposal_msg = node.next_proposal().await;
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote_msg(vote_msg).await.unwrap();
+        }
+        let executed_blocks = match node.next_execution_event().await {
+            ExecutionEvent::Executed(executed_blocks) => executed_blocks,
+            event => panic!("Unexpected execution event: {:?}", event),
+        };
+        assert!(!node.block_store.execution_failed());
+
+        // The node syncs once it learns about the failure
+        node.round_manager
+            .process_execution_event(ExecutionEvent::ExecutionFailed(
+                executed_blocks.ordered_proof,
+            ))
+            .await
+            .unwrap();
+        assert!(node.block_store.execution_failed());
+    });
+}
+
+#[test]
+fn commit_vote_without_decoupled_execution() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1);
+    let node = &mut nodes[0];
+    let genesis = node.block_store.root();
+    timed_block_on(&mut runtime, async {
+        let ledger_info = LedgerInfo::new(genesis.block_info(), HashValue::zero());
+        let signature = node.signer.sign(&ledger_info);
+        let commit_vote =
+            CommitVote::new_with_signature(node.signer.author(), ledger_info, signature);
+        assert!(node
+            .round_manager
+            .process_commit_vote(commit_vote)
+            .await
+            .is_err());
+    });
+}
+
+/// Processes the next proposal of the node and forms its QC with the vote of the other signer,
+/// returns the proposed block.
+async fn certify_next_proposal(node: &mut NodeSetup, other_signer: &ValidatorSigner) -> Block {
+    let proposal_msg = node.next_proposal().await;
+    let block = proposal_msg.proposal().clone();
+    node.round_manager
+        .process_proposal_msg(proposal_msg)
+        .await
+        .unwrap();
+    let vote_msg = node.next_vote().await;
+    let other_vote = Vote::new(
+        vote_msg.vote().vote_data().clone(),
+        other_signer.author(),
+        vote_msg.vote().ledger_info().clone(),
+        other_signer,
+    );
+    let other_vote_msg = VoteMsg::new(other_vote, vote_msg.sync_info().clone());
+    node.round_manager.process_vote_msg(vote_msg).await.unwrap();
+    node.round_manager
+        .process_vote_msg(other_vote_msg)
+        .await
+        .unwrap();
+    block
+}
+
+/// Syncs the behind node to the given sync info of the ahead node, which serves the retrieval
+/// of the given number of blocks.
+async fn sync_behind_node(
+    behind_node: &mut NodeSetup,
+    ahead_node: &mut NodeSetup,
+    sync_info: &SyncInfo,
+    num_blocks: usize,
+) -> anyhow::Result<bool> {
+    let ahead_author = ahead_node.signer.author();
+    let (result, _) = futures::join!(
+        behind_node.round_manager.ensure_round_and_sync_up(
+            sync_info.highest_round() + 1,
+            sync_info,
+            ahead_author,
+            true,
+        ),
+        async {
+            for _ in 0..num_blocks {
+                let request = ahead_node.next_block_retrieval().await;
+                ahead_node
+                    .round_manager
+                    .process_block_retrieval(request)
+                    .await
+                    .unwrap();
+            }
+        }
+    );
+    result
+}
+
+#[test]
+/// In decoupled execution mode a node missing the blocks syncs to the commit proof of the
+/// execution pipeline and executes the ordered blocks above it.
+fn decoupled_execution_sync_to_commit_proof() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_execution_mode(
+        &mut playground,
+        runtime.handle().clone(),
+        2,
+        true,
+    );
+    let mut behind_node = nodes.pop().unwrap();
+    let mut ahead_node = nodes.pop().unwrap();
+    // The behind node doesn't receive any message from the ahead node
+    playground.drop_message_for(&ahead_node.twin_id(), &behind_node.twin_id());
+    runtime.spawn(playground.start());
+    timed_block_on(&mut runtime, async {
+        // Blocks 1, 2 and 3 are ordered after 5 rounds
+        let mut blocks = vec![];
+        for _ in 1..=5 {
+            blocks.push(certify_next_proposal(&mut ahead_node, &behind_node.signer).await);
+        }
+        assert_eq!(ahead_node.block_store.ordered_root().id(), blocks[2].id());
+        let _ = ahead_node.next_proposal().await;
+
+        // Only block 1 is executed and committed
+        let event = ahead_node.next_execution_event().await;
+        ahead_node
+            .round_manager
+            .process_execution_event(event)
+            .await
+            .unwrap();
+        let commit_vote = ahead_node.next_commit_vote().await;
+        let ledger_info = commit_vote.ledger_info().clone();
+        assert_eq!(ledger_info.consensus_block_id(), blocks[0].id());
+        let other_commit_vote = CommitVote::new_with_signature(
+            behind_node.signer.author(),
+            ledger_info.clone(),
+            behind_node.signer.sign(&ledger_info),
+        );
+        for vote in vec![commit_vote, other_commit_vote] {
+            ahead_node
+                .round_manager
+                .process_commit_vote(vote)
+                .await
+                .unwrap();
+        }
+        let commit_proof = ahead_node.commit_cb_receiver.next().await.unwrap();
+        loop {
+            match ahead_node.next_execution_event().await {
+                event @ ExecutionEvent::Committed(..) => {
+                    ahead_node
+                        .round_manager
+                        .process_execution_event(event)
+                        .await
+                        .unwrap();
+                    break;
+                }
+                ExecutionEvent::Executed(_) => (),
+                event => panic!("Unexpected execution event: {:?}", event),
+            }
+        }
+        let sync_info = ahead_node.block_store.sync_info();
+        assert_eq!(sync_info.highest_commit_proof(), Some(&commit_proof));
+
+        // The behind node retrieves the blocks from 5 down to 1 and syncs to block 1
+        assert!(
+            sync_behind_node(&mut behind_node, &mut ahead_node, &sync_info, 5)
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            behind_node.commit_cb_receiver.next().await.unwrap(),
+            commit_proof
+        );
+        assert_eq!(behind_node.block_store.root().id(), blocks[0].id());
+        assert_eq!(behind_node.block_store.ordered_root().id(), blocks[2].id());
+        assert_eq!(
+            behind_node.block_store.sync_info().highest_commit_proof(),
+            Some(&commit_proof)
+        );
+
+        // Blocks 2 and 3 are executed again on top of the synced state
+        match behind_node.next_execution_event().await {
+            ExecutionEvent::Executed(executed_blocks) => {
+                let ids: Vec<_> = executed_blocks.blocks.iter().map(|b| b.id()).collect();
+                assert_eq!(ids, vec![blocks[1].id(), blocks[2].id()]);
+            }
+            event => panic!("Unexpected execution event: {:?}", event),
+        }
+    });
+}
+
+#[test]
+/// In decoupled execution mode the ordered blocks don't carry the reconfiguration, a node
+/// syncing to a commit proof ending the epoch notifies the epoch change.
+fn decoupled_execution_sync_to_epoch_change() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_execution_mode(
+        &mut playground,
+        runtime.handle().clone(),
+        2,
+        true,
+    );
+    let mut behind_node = nodes.pop().unwrap();
+    let mut ahead_node = nodes.pop().unwrap();
+    playground.drop_message_for(&ahead_node.twin_id(), &behind_node.twin_id());
+    runtime.spawn(playground.start());
+    timed_block_on(&mut runtime, async {
+        let mut blocks = vec![];
+        for _ in 1..=5 {
+            blocks.push(certify_next_proposal(&mut ahead_node, &behind_node.signer).await);
+        }
+        let _ = ahead_node.next_proposal().await;
+
+        // The execution of block 1 ends the epoch
+        let next_epoch_state = EpochState {
+            epoch: 2,
+            verifier: ahead_node.validators.clone(),
+        };
+        let ledger_info = LedgerInfo::new(
+            blocks[0].gen_block_info(*ACCUMULATOR_PLACEHOLDER_HASH, 0, Some(next_epoch_state)),
+            HashValue::zero(),
+        );
+        let signatures = vec![&ahead_node.signer, &behind_node.signer]
+            .into_iter()
+            .map(|signer| (signer.author(), signer.sign(&ledger_info)))
+            .collect();
+        let commit_proof = LedgerInfoWithSignatures::new(ledger_info, signatures);
+        let sync_info = SyncInfo::new_decoupled(
+            ahead_node
+                .block_store
+                .highest_quorum_cert()
+                .as_ref()
+                .clone(),
+            ahead_node
+                .block_store
+                .highest_commit_cert()
+                .as_ref()
+                .clone(),
+            Some(commit_proof.clone()),
+            None,
+        );
+
+        // The node starts the new epoch from the commit proof, the round of the previous epoch
+        // is never reached.
+        assert!(
+            sync_behind_node(&mut behind_node, &mut ahead_node, &sync_info, 5)
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            behind_node.commit_cb_receiver.next().await.unwrap(),
+            commit_proof
+        );
+        assert_eq!(behind_node.block_store.root().epoch(), 2);
+        match behind_node.all_events.next().await.unwrap().unwrap() {
+            Event::Message((_, ConsensusMsg::EpochChangeProof(proof))) => {
+                assert_eq!(proof.ledger_info_with_sigs, vec![commit_proof])
+            }
+            _ => panic!("Unexpected Network Event"),
+        }
+    });
+}
+
+/// Creates a batch of the author certified by all the signers.
+fn certified_batch(
+    signers: &[&ValidatorSigner],
+    author: &ValidatorSigner,
+) -> (Batch, AvailabilityCertificate) {
+    let payload = random_payload(2);
+    let info = BatchInfo::new(author.author(), 1, 0, 10, &payload);
+    let signatures = signers
+        .iter()
+        .map(|signer| (signer.author(), signer.sign(&info)))
+        .collect();
+    let batch = Batch::new_with_signature(info.clone(), payload, author.sign(&info));
+    (batch, AvailabilityCertificate::new(info, signatures))
+}
+
+/// Creates the proposal for round 1 whose payload is made of the given batches.
+fn proposal_with_batches(
+    proposer: &ValidatorSigner,
+    batches: &[&Batch],
+    sync_info: SyncInfo,
+) -> ProposalMsg {
+    let payload = batches
+        .iter()
+        .flat_map(|batch| batch.payload().iter().cloned())
+        .collect();
+    let block = Block::new_proposal(payload, 1, 1, certificate_for_genesis(), proposer);
+    ProposalMsg::new(block, sync_info)
+}
+
+/// Creates the compact version of the proposal with the compact signature of the given signer.
+fn compact_proposal(
+    proposal_msg: &ProposalMsg,
+    certificates: Vec<AvailabilityCertificate>,
+    signer: &ValidatorSigner,
+) -> CompactProposalMsg {
+    let compact_block = CompactBlock::new(proposal_msg.proposal(), &certificates).unwrap();
+    CompactProposalMsg::new(proposal_msg, certificates, signer.sign(&compact_block)).unwrap()
+}
+
+#[test]
+/// The compact proposal is authenticated by the compact signature of the valid proposer before
+/// its batches are fetched, and rebuilt into the full proposal from the payloads of the batches.
+fn compact_proposal_verification() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let mut node = nodes.pop().unwrap();
+    let proposer = nodes.pop().unwrap();
+    let (batch, certificate) = certified_batch(&[&proposer.signer, &node.signer], &proposer.signer);
+    let sync_info = node.block_store.sync_info();
+    let proposal_msg = proposal_with_batches(&proposer.signer, &[&batch], sync_info.clone());
+    let compact_proposal_msg =
+        compact_proposal(&proposal_msg, vec![certificate.clone()], &proposer.signer);
+    assert!(compact_proposal_msg.verify(&node.validators).is_ok());
+
+    // The compact signature must be the one of the proposer
+    let forged = compact_proposal(&proposal_msg, vec![certificate.clone()], &node.signer);
+    assert!(forged.verify(&node.validators).is_err());
+
+    // The payloads of all the batches are required, the signature of the proposer on the full
+    // block authenticates them
+    assert!(compact_proposal_msg.clone().into_proposal(vec![]).is_err());
+    let rebuilt = compact_proposal_msg
+        .clone()
+        .into_proposal(vec![batch.payload().clone()])
+        .unwrap();
+    assert_eq!(rebuilt, proposal_msg);
+    assert!(rebuilt.verify(&node.validators).is_ok());
+    let tampered = compact_proposal_msg
+        .clone()
+        .into_proposal(vec![random_payload(2)])
+        .unwrap();
+    assert!(tampered.verify(&node.validators).is_err());
+
+    let batch_manager = node.enable_batch_dissemination();
+    timed_block_on(&mut runtime, async {
+        // Only the valid proposer of the round can make a compact proposal
+        let invalid_proposer = compact_proposal(
+            &proposal_with_batches(&node.signer, &[&batch], sync_info),
+            vec![certificate],
+            &node.signer,
+        );
+        assert!(invalid_proposer.verify(&node.validators).is_ok());
+        assert!(node
+            .round_manager
+            .process_compact_proposal_msg(invalid_proposer)
+            .await
+            .is_err());
+
+        // With the batches stored locally the proposal is processed right away
+        assert!(batch_manager.add_batch(batch));
+        node.round_manager
+            .process_compact_proposal_msg(compact_proposal_msg)
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.consensus_state().last_voted_round(), 1);
+    });
+}
+
+#[test]
+/// The batches missing locally are fetched off the event loop, the compact proposal is delivered
+/// again once they are stored.
+fn compact_proposal_fetch_batches() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 2);
+    let mut node = nodes.pop().unwrap();
+    let mut proposer = nodes.pop().unwrap();
+    // The node only receives the proposal through the test
+    playground.drop_message_for(&proposer.twin_id(), &node.twin_id());
+    runtime.spawn(playground.start());
+    let (batch, certificate) = certified_batch(&[&proposer.signer, &node.signer], &proposer.signer);
+    let proposal_msg =
+        proposal_with_batches(&proposer.signer, &[&batch], node.block_store.sync_info());
+    let compact_proposal_msg = compact_proposal(&proposal_msg, vec![certificate], &proposer.signer);
+    assert!(proposer
+        .enable_batch_dissemination()
+        .add_batch(batch.clone()));
+    let batch_manager = node.enable_batch_dissemination();
+    timed_block_on(&mut runtime, async {
+        let _ = proposer.next_proposal().await;
+        node.round_manager
+            .process_compact_proposal_msg(compact_proposal_msg.clone())
+            .await
+            .unwrap();
+        assert_eq!(node.round_manager.consensus_state().last_voted_round(), 0);
+
+        // The proposer serves the batch
+        let request = proposer.next_batch_retrieval().await;
+        assert_eq!(request.req.digest(), batch.digest());
+        proposer
+            .round_manager
+            .process_batch_retrieval(request)
+            .await
+            .unwrap();
+        assert_eq!(node.next_compact_proposal().await, compact_proposal_msg);
+        assert!(batch_manager.get_batch(batch.digest()).is_some());
+
+        node.round_manager
+            .process_compact_proposal_msg(compact_proposal_msg)
+            .await
+            .unwrap();
+        let vote_msg = proposer.next_vote().await;
+        assert_eq!(
+            vote_msg.vote().vote_data().proposed().id(),
+            proposal_msg.proposal().id()
+        );
+    });
+}
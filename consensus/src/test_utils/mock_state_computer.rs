@@ -19,7 +19,11 @@ pub struct MockStateComputer {
     state_sync_client: mpsc::UnboundedSender<Payload>,
     commit_callback: mpsc::UnboundedSender<LedgerInfoWithSignatures>,
     consensus_db: Arc<MockStorage>,
-    block_cache: Mutex<HashMap<HashValue, Payload>>,
+    block_cache: Mutex<HashMap<HashValue, Block>>,
+    // The blocks committed so far, in the order of the commits.
+    committed_blocks: Mutex<Vec<Block>>,
+    // The results returned for the given blocks instead of the placeholder result.
+    compute_results: HashMap<HashValue, StateComputeResult>,
 }
 
 impl MockStateComputer {
@@ -33,8 +37,25 @@ impl MockStateComputer {
             commit_callback,
             consensus_db,
             block_cache: Mutex::new(HashMap::new()),
+            committed_blocks: Mutex::new(vec![]),
+            compute_results: HashMap::new(),
         }
     }
+
+    /// Returns the given results when computing the corresponding blocks, e.g. the results
+    /// recorded by a validator.
+    pub fn with_compute_results(
+        mut self,
+        compute_results: HashMap<HashValue, StateComputeResult>,
+    ) -> Self {
+        self.compute_results = compute_results;
+        self
+    }
+
+    /// Returns the blocks committed so far, the blocks the node synced to are not included.
+    pub fn committed_blocks(&self) -> Vec<Block> {
+        self.committed_blocks.lock().unwrap().clone()
+    }
 }
 
 #[async_trait::async_trait]
@@ -47,7 +68,10 @@ impl StateComputer for MockStateComputer {
         self.block_cache
             .lock()
             .unwrap()
-            .insert(block.id(), block.payload().unwrap_or(&vec![]).clone());
+            .insert(block.id(), block.clone());
+        if let Some(result) = self.compute_results.get(&block.id()) {
+            return Ok(result.clone());
+        }
         let result = StateComputeResult::new(
             *ACCUMULATOR_PLACEHOLDER_HASH,
             vec![],
@@ -72,13 +96,14 @@ impl StateComputer for MockStateComputer {
         // mock sending commit notif to state sync
         let mut txns = vec![];
         for block_id in block_ids {
-            let mut payload = self
+            let block = self
                 .block_cache
                 .lock()
                 .unwrap()
                 .remove(&block_id)
                 .ok_or_else(|| format_err!("Cannot find block"))?;
-            txns.append(&mut payload);
+            txns.extend(block.payload().cloned().unwrap_or_default());
+            self.committed_blocks.lock().unwrap().push(block);
         }
         self.state_sync_client
             .unbounded_send(txns)
@@ -7,9 +7,10 @@ use crate::{
         LedgerRecoveryData, PersistentLivenessStorage, RecoveryData, RootMetadata,
     },
 };
-use anyhow::Result;
+use anyhow::{format_err, Result};
 use consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
+    block::Block, common::Author, equivocation_evidence::EquivocationEvidence,
+    quorum_cert::QuorumCert, timeout_certificate::TimeoutCertificate, vote::Vote,
 };
 use libra_crypto::HashValue;
 use libra_types::{
@@ -18,7 +19,7 @@ use libra_types::{
     on_chain_config::ValidatorSet,
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{hash_map::Entry, BTreeMap, HashMap},
     sync::{Arc, Mutex},
 };
 use storage_interface::DbReader;
@@ -33,6 +34,9 @@ pub struct MockSharedStorage {
     // Liveness state
     pub highest_timeout_certificate: Mutex<Option<TimeoutCertificate>>,
     pub validator_set: ValidatorSet,
+
+    // Audit trail
+    pub equivocation_evidence: Mutex<HashMap<(u64, Author), EquivocationEvidence>>,
 }
 
 impl MockSharedStorage {
@@ -44,6 +48,7 @@ impl MockSharedStorage {
             last_vote: Mutex::new(None),
             highest_timeout_certificate: Mutex::new(None),
             validator_set,
+            equivocation_evidence: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -153,14 +158,7 @@ impl MockStorage {
     }
 
     pub fn start_for_testing(validator_set: ValidatorSet) -> (RecoveryData, Arc<Self>) {
-        let shared_storage = Arc::new(MockSharedStorage {
-            block: Mutex::new(HashMap::new()),
-            qc: Mutex::new(HashMap::new()),
-            lis: Mutex::new(HashMap::new()),
-            last_vote: Mutex::new(None),
-            highest_timeout_certificate: Mutex::new(None),
-            validator_set: validator_set.clone(),
-        });
+        let shared_storage = Arc::new(MockSharedStorage::new(validator_set.clone()));
         let genesis_li = LedgerInfo::mock_genesis(Some(validator_set));
         let storage = Self::new_with_ledger_info(shared_storage, genesis_li);
         let recovery_data = storage
@@ -256,6 +254,31 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(EpochChangeProof::new(vec![lis], false))
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<bool> {
+        let author = evidence
+            .author()
+            .ok_or_else(|| format_err!("Equivocation evidence without author: {}", evidence))?;
+        let mut stored = self.shared_storage.equivocation_evidence.lock().unwrap();
+        match stored.entry((evidence.epoch(), author)) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                entry.insert(evidence.clone());
+                Ok(true)
+            }
+        }
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(self
+            .shared_storage
+            .equivocation_evidence
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect())
+    }
+
     fn libra_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
@@ -320,6 +343,14 @@ impl PersistentLivenessStorage for EmptyStorage {
         unimplemented!()
     }
 
+    fn save_equivocation_evidence(&self, _: &EquivocationEvidence) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(vec![])
+    }
+
     fn libra_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
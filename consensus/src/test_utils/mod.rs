@@ -19,6 +19,7 @@ mod mock_state_computer;
 mod mock_storage;
 #[cfg(any(test, feature = "fuzzing"))]
 mod mock_txn_manager;
+pub mod replay;
 
 use crate::util::mock_time_service::SimulatedTimeService;
 use consensus_types::{block::block_test_utils::gen_test_certificate, common::Payload};
@@ -79,6 +80,7 @@ pub fn build_empty_tree() -> Arc<BlockStore> {
         Arc::new(EmptyStateComputer),
         10, // max pruned blocks in mem
         Arc::new(SimulatedTimeService::new()),
+        None, // execution pipeline
     ))
 }
 
@@ -0,0 +1,458 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays a consensus recording (see `recorder`) against a RoundManager built from the mock
+//! components: the inputs of the recording are fed to the RoundManager in order while the clock,
+//! the execution results, the proposers and the retrieved blocks are taken from the recording.
+//! The votes and the commits of the replay are then compared with the recorded ones.
+//!
+//! The replay does not support the decoupled execution and the batch dissemination modes, the
+//! recordings of these modes are rejected.
+
+use crate::{
+    block_storage::BlockStore,
+    liveness::{
+        proposal_generator::ProposalGenerator,
+        proposer_election::ProposerElection,
+        round_state::{ExponentialTimeInterval, RoundState},
+    },
+    metrics_safety_rules::MetricsSafetyRules,
+    network::NetworkSender,
+    network_interface::{ConsensusMsg, ConsensusNetworkSender},
+    persistent_liveness_storage::PersistentLivenessStorage,
+    recorder::{
+        read_recording, ConsensusRecorder, RecordedEpochStart, RecordedEvent,
+        RecordingStateComputer,
+    },
+    round_manager::{RoundManager, UnverifiedEvent},
+    test_utils::{MockSharedStorage, MockStateComputer, MockStorage, MockTransactionManager},
+    util::mock_time_service::SimulatedTimeService,
+};
+use anyhow::{ensure, Context, Result};
+use channel::{self, libra_channel, message_queues::QueueStyle};
+use consensus_types::{
+    block_retrieval::BlockRetrievalResponse,
+    common::{Author, Payload, Round},
+    vote::Vote,
+};
+use executor_types::StateComputeResult;
+use futures::{channel::mpsc, StreamExt};
+use libra_config::config::ConsensusConfig;
+use libra_crypto::HashValue;
+use libra_logger::prelude::*;
+use libra_types::{
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::ValidatorSet,
+    validator_signer::ValidatorSigner,
+    waypoint::Waypoint,
+};
+use network::peer_manager::{
+    ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender,
+};
+use safety_rules::{test_utils::test_storage, SafetyRules, TSafetyRules};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    num::NonZeroUsize,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// A difference between the outputs recorded after an input and the outputs of its replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    Vote {
+        /// The index of the input in the recording.
+        input: usize,
+        recorded: Option<Vote>,
+        replayed: Option<Vote>,
+    },
+    Commit {
+        input: usize,
+        recorded: Option<LedgerInfoWithSignatures>,
+        replayed: Option<LedgerInfoWithSignatures>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// The number of inputs replayed.
+    pub num_inputs: usize,
+    /// The number of votes sent during the replay.
+    pub num_votes: usize,
+    /// The number of commits during the replay.
+    pub num_commits: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// The proposers elected in the recording, the rounds that have not been recorded fall back to
+/// the first validator.
+struct RecordedProposers {
+    proposers: Arc<Mutex<HashMap<Round, Author>>>,
+    default_proposer: Author,
+}
+
+impl ProposerElection for RecordedProposers {
+    fn get_valid_proposer(&self, round: Round) -> Author {
+        *self
+            .proposers
+            .lock()
+            .unwrap()
+            .get(&round)
+            .unwrap_or(&self.default_proposer)
+    }
+}
+
+/// A RoundManager replaying an epoch of the recording.
+struct ReplayNode {
+    epoch_state: EpochState,
+    round_manager: RoundManager,
+    time_service: SimulatedTimeService,
+    // The local timeouts are replayed from the recording.
+    _timeout_receiver: channel::Receiver<Round>,
+    _commit_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
+    _state_sync_receiver: mpsc::UnboundedReceiver<Payload>,
+}
+
+impl ReplayNode {
+    async fn start(
+        epoch_start: &RecordedEpochStart,
+        signer: &ValidatorSigner,
+        compute_results: HashMap<HashValue, StateComputeResult>,
+        proposers: Arc<Mutex<HashMap<Round, Author>>>,
+        retrievals: Arc<Mutex<VecDeque<Option<BlockRetrievalResponse>>>>,
+        outputs: Arc<ConsensusRecorder>,
+    ) -> Result<Self> {
+        let config = ConsensusConfig::default();
+        let epoch_state = epoch_start.epoch_state.clone();
+        let validator_set: ValidatorSet = (&epoch_state.verifier).into();
+        let storage = Arc::new(MockStorage::new_with_ledger_info(
+            Arc::new(MockSharedStorage::new(validator_set)),
+            epoch_start.root_ledger_info.clone(),
+        ));
+        storage.save_tree(epoch_start.blocks.clone(), epoch_start.quorum_certs.clone())?;
+        let recovery_data = epoch_start.recovery_data()?;
+
+        let (state_sync_client, state_sync_receiver) = mpsc::unbounded();
+        let (commit_sender, commit_receiver) = mpsc::unbounded();
+        let state_computer = Arc::new(RecordingStateComputer::new(
+            Arc::new(
+                MockStateComputer::new(state_sync_client, commit_sender, storage.clone())
+                    .with_compute_results(compute_results),
+            ),
+            outputs.clone(),
+        ));
+        let time_service = SimulatedTimeService::new();
+        let block_store = Arc::new(BlockStore::new(
+            storage.clone(),
+            recovery_data,
+            state_computer,
+            config.max_pruned_blocks_in_mem,
+            Arc::new(time_service.clone()),
+            None,
+        ));
+        let proposal_generator = ProposalGenerator::new(
+            signer.author(),
+            block_store.clone(),
+            Arc::new(MockTransactionManager::new(None)),
+            Arc::new(time_service.clone()),
+            config.max_block_size,
+        );
+        let (timeout_sender, timeout_receiver) = channel::new_test(1_024);
+        let round_state = RoundState::new(
+            Box::new(ExponentialTimeInterval::new(
+                Duration::from_millis(config.round_initial_timeout_ms),
+                1.2,
+                6,
+            )),
+            Arc::new(time_service.clone()),
+            timeout_sender,
+        );
+        let proposer_election = Box::new(RecordedProposers {
+            proposers,
+            default_proposer: epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .next()
+                .context("Empty validator set")?,
+        });
+        let safety_rules = MetricsSafetyRules::new(
+            Box::new(Self::safety_rules(epoch_start, signer)?),
+            storage.clone(),
+        );
+        let network = Self::network_sender(signer.author(), &epoch_state, retrievals, outputs);
+
+        let mut round_manager = RoundManager::new(
+            epoch_state.clone(),
+            block_store,
+            round_state,
+            proposer_election,
+            proposal_generator,
+            safety_rules,
+            network,
+            Arc::new(MockTransactionManager::new(None)),
+            storage,
+            None,
+            false,
+        );
+        round_manager.start(epoch_start.last_vote.clone()).await;
+        Ok(Self {
+            epoch_state,
+            round_manager,
+            time_service,
+            _timeout_receiver: timeout_receiver,
+            _commit_receiver: commit_receiver,
+            _state_sync_receiver: state_sync_receiver,
+        })
+    }
+
+    /// Restores the recorded voting state. SafetyRules is initialized with an epoch change proof
+    /// made of a synthetic LedgerInfo carrying the recorded epoch state.
+    fn safety_rules(
+        epoch_start: &RecordedEpochStart,
+        signer: &ValidatorSigner,
+    ) -> Result<SafetyRules> {
+        let epoch_state = &epoch_start.epoch_state;
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                epoch_state.epoch.saturating_sub(1),
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                0,
+                0,
+                Some(epoch_state.clone()),
+            ),
+            HashValue::zero(),
+        );
+        let mut storage = test_storage(signer);
+        // The epoch is set last so that the initialization does not reset the rounds.
+        storage.set_waypoint(&Waypoint::new_epoch_boundary(&ledger_info)?)?;
+        storage.set_last_voted_round(epoch_start.last_voted_round)?;
+        storage.set_preferred_round(epoch_start.preferred_round)?;
+        storage.set_last_vote(epoch_start.last_vote.clone())?;
+        storage.set_epoch(epoch_state.epoch)?;
+
+        let mut safety_rules = SafetyRules::new(storage, false, false);
+        safety_rules.initialize(&EpochChangeProof::new(
+            vec![LedgerInfoWithSignatures::new(ledger_info, BTreeMap::new())],
+            false,
+        ))?;
+        Ok(safety_rules)
+    }
+
+    /// The peers answer the block retrievals with the recorded responses, the other messages
+    /// are dropped.
+    fn network_sender(
+        author: Author,
+        epoch_state: &EpochState,
+        retrievals: Arc<Mutex<VecDeque<Option<BlockRetrievalResponse>>>>,
+        outputs: Arc<ConsensusRecorder>,
+    ) -> NetworkSender {
+        let (network_reqs_tx, mut network_reqs_rx) =
+            libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
+        let (connection_reqs_tx, _) =
+            libra_channel::new(QueueStyle::FIFO, NonZeroUsize::new(8).unwrap(), None);
+        let (self_sender, mut self_receiver) = channel::new_test(8);
+        tokio::spawn(async move {
+            while let Some(request) = network_reqs_rx.next().await {
+                if let PeerManagerRequest::SendRpc(_, request) = request {
+                    let response = retrievals.lock().unwrap().pop_front().flatten();
+                    // Dropping the response channel fails the retrieval.
+                    if let Some(response) = response {
+                        let msg = ConsensusMsg::BlockRetrievalResponse(Box::new(response));
+                        let bytes = lcs::to_bytes(&msg).expect("Failed to serialize response");
+                        let _ = request.res_tx.send(Ok(bytes.into()));
+                    }
+                }
+            }
+        });
+        // The messages to self are replayed from the recording.
+        tokio::spawn(async move { while self_receiver.next().await.is_some() {} });
+        NetworkSender::new(
+            author,
+            ConsensusNetworkSender::new(
+                PeerManagerRequestSender::new(network_reqs_tx),
+                ConnectionRequestSender::new(connection_reqs_tx),
+            ),
+            self_sender,
+            epoch_state.verifier.clone(),
+            Some(outputs),
+        )
+    }
+
+    /// Processes a recorded message or local timeout, the clock is set to its first recorded
+    /// reading.
+    async fn process_input(
+        &mut self,
+        input: &RecordedEvent,
+        timestamp: Option<Duration>,
+    ) -> Result<()> {
+        if let Some(timestamp) = timestamp {
+            self.time_service.set_current_timestamp(timestamp);
+        }
+        match input {
+            RecordedEvent::Message(peer_id, msg) => {
+                self.process_message(*peer_id, msg.clone()).await
+            }
+            RecordedEvent::LocalTimeout(round) => {
+                self.round_manager.process_local_timeout(*round).await
+            }
+            _ => unreachable!("Unexpected input {:?}", input),
+        }
+    }
+
+    async fn process_message(&mut self, peer_id: Author, msg: ConsensusMsg) -> Result<()> {
+        let event: UnverifiedEvent = match msg {
+            ConsensusMsg::ProposalMsg(_)
+            | ConsensusMsg::SyncInfo(_)
+            | ConsensusMsg::VoteMsg(_)
+            | ConsensusMsg::CommitVoteMsg(_)
+            | ConsensusMsg::BatchMsg(_)
+            | ConsensusMsg::BatchVoteMsg(_)
+            | ConsensusMsg::AvailabilityCertificate(_)
+            | ConsensusMsg::CompactProposalMsg(_)
+            | ConsensusMsg::EquivocationEvidence(_) => msg.into(),
+            // The epoch changes are replayed from the recorded epoch starts.
+            _ => return Ok(()),
+        };
+        if event.epoch() != self.epoch_state.epoch {
+            return Ok(());
+        }
+        let event = event.verify(&self.epoch_state.verifier)?;
+        self.round_manager.process_event(peer_id, event).await
+    }
+}
+
+/// Replays the recorded events as the validator of the given signer.
+pub async fn replay(events: Vec<RecordedEvent>, signer: ValidatorSigner) -> Result<ReplayReport> {
+    for event in &events {
+        if let RecordedEvent::EpochStart(epoch_start) = event {
+            ensure!(
+                !epoch_start.decoupled_execution && !epoch_start.batch_dissemination,
+                "[Replay] Epoch {} is recorded in decoupled execution or batch dissemination mode",
+                epoch_start.epoch_state.epoch
+            );
+        }
+    }
+    let compute_results: HashMap<_, _> = events
+        .iter()
+        .filter_map(|event| match event {
+            RecordedEvent::Executed(block_id, result) => Some((*block_id, (**result).clone())),
+            _ => None,
+        })
+        .collect();
+    let inputs: Vec<usize> = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| event.is_input())
+        .map(|(index, _)| index)
+        .collect();
+    let proposers = Arc::new(Mutex::new(HashMap::new()));
+    let retrievals = Arc::new(Mutex::new(VecDeque::new()));
+    let outputs = Arc::new(ConsensusRecorder::new_in_memory());
+
+    let mut report = ReplayReport::default();
+    let mut node: Option<ReplayNode> = None;
+    for (i, &input) in inputs.iter().enumerate() {
+        let window = &events[input + 1..*inputs.get(i + 1).unwrap_or(&events.len())];
+        for event in window {
+            match event {
+                RecordedEvent::Proposer(round, author) => {
+                    proposers.lock().unwrap().insert(*round, *author);
+                }
+                RecordedEvent::BlockRetrieval(response) => {
+                    retrievals.lock().unwrap().push_back(response.clone());
+                }
+                _ => (),
+            }
+        }
+        let timestamp = window.iter().find_map(|event| match event {
+            RecordedEvent::Timestamp(timestamp) => Some(*timestamp),
+            _ => None,
+        });
+
+        let result = if let RecordedEvent::EpochStart(epoch_start) = &events[input] {
+            // Release the previous RoundManager before starting the epoch.
+            node = None;
+            ReplayNode::start(
+                epoch_start,
+                &signer,
+                compute_results.clone(),
+                proposers.clone(),
+                retrievals.clone(),
+                outputs.clone(),
+            )
+            .await
+            .map(|started| node = Some(started))
+        } else if let Some(node) = node.as_mut() {
+            node.process_input(&events[input], timestamp).await
+        } else {
+            // The inputs before the first epoch start cannot be replayed.
+            continue;
+        };
+        if let Err(e) = result {
+            debug!("[Replay] Error processing input {}: {:?}", input, e);
+        }
+        report.num_inputs += 1;
+
+        let replayed = outputs.take_events();
+        let (recorded_votes, recorded_commits) = outputs_of(window);
+        let (replayed_votes, replayed_commits) = outputs_of(&replayed);
+        report.num_votes += replayed_votes.len();
+        report.num_commits += replayed_commits.len();
+        for (recorded, replayed) in diverging_pairs(recorded_votes, replayed_votes) {
+            report.divergences.push(Divergence::Vote {
+                input,
+                recorded,
+                replayed,
+            });
+        }
+        for (recorded, replayed) in diverging_pairs(recorded_commits, replayed_commits) {
+            report.divergences.push(Divergence::Commit {
+                input,
+                recorded,
+                replayed,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Replays the recording at the given path, see `replay`.
+pub async fn replay_file(path: &Path, signer: ValidatorSigner) -> Result<ReplayReport> {
+    replay(read_recording(path)?, signer).await
+}
+
+fn outputs_of(events: &[RecordedEvent]) -> (Vec<Vote>, Vec<LedgerInfoWithSignatures>) {
+    let mut votes = vec![];
+    let mut commits = vec![];
+    for event in events {
+        match event {
+            RecordedEvent::Vote(vote) => votes.push((**vote).clone()),
+            RecordedEvent::Commit(commit) => commits.push((**commit).clone()),
+            _ => (),
+        }
+    }
+    (votes, commits)
+}
+
+/// Pairs the outputs by position and returns the pairs that differ.
+fn diverging_pairs<T: PartialEq>(
+    recorded: Vec<T>,
+    replayed: Vec<T>,
+) -> Vec<(Option<T>, Option<T>)> {
+    let mut recorded = recorded.into_iter();
+    let mut replayed = replayed.into_iter();
+    let mut pairs = vec![];
+    loop {
+        match (recorded.next(), replayed.next()) {
+            (None, None) => return pairs,
+            (recorded, replayed) if recorded != replayed => pairs.push((recorded, replayed)),
+            _ => (),
+        }
+    }
+}
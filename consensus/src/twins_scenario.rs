@@ -0,0 +1,237 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the Twins scenarios: a byzantine node is simulated by running twins of an honest
+//! node, i.e. instances sharing its keys, and a scenario assigns a leader and partitions the
+//! network in each round. The node `i` of a scenario is the honest node `i` if `i < num_nodes`,
+//! and the twin of the node `i - num_nodes` otherwise. The first `num_twins` nodes have twins.
+
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+
+#[cfg(test)]
+#[path = "twins_scenario_test.rs"]
+mod twins_scenario_test;
+
+/// The leader and the partitions of the network in a round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundScenario {
+    /// The index of the leader among the honest nodes, both instances of a node with twins
+    /// propose when it leads.
+    pub leader: usize,
+    /// The messages of the round are only delivered within the partitions.
+    pub partitions: Vec<Vec<usize>>,
+}
+
+/// A Twins scenario, the round `r` is described by `rounds[r - 1]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scenario {
+    pub num_nodes: usize,
+    pub num_twins: usize,
+    pub rounds: Vec<RoundScenario>,
+}
+
+impl Scenario {
+    /// The index of the honest node the given node is a twin of, or of the node itself.
+    pub fn node_index(&self, node: usize) -> usize {
+        if node < self.num_nodes {
+            node
+        } else {
+            node - self.num_nodes
+        }
+    }
+
+    /// The nodes without twins, only their commits are guaranteed to be safe.
+    pub fn honest_nodes(&self) -> impl Iterator<Item = usize> {
+        self.num_twins..self.num_nodes
+    }
+
+    /// The scenarios one step simpler than this one: a round dropped, or the partitions of a
+    /// round merged. The simplest scenarios come first.
+    pub fn simplifications(&self) -> Vec<Scenario> {
+        let mut simplifications = vec![];
+        for round in (0..self.rounds.len()).rev() {
+            let mut scenario = self.clone();
+            scenario.rounds.remove(round);
+            simplifications.push(scenario);
+        }
+        for (round, round_scenario) in self.rounds.iter().enumerate() {
+            let partitions = &round_scenario.partitions;
+            for first in 0..partitions.len() {
+                for second in first + 1..partitions.len() {
+                    let mut scenario = self.clone();
+                    let merged = &mut scenario.rounds[round].partitions;
+                    let mut partition = merged.remove(second);
+                    merged[first].append(&mut partition);
+                    merged[first].sort();
+                    simplifications.push(scenario);
+                }
+            }
+        }
+        simplifications
+    }
+
+    fn node_name(&self, node: usize) -> String {
+        if node < self.num_nodes {
+            format!("n{}", node)
+        } else {
+            format!("twin{}", self.node_index(node))
+        }
+    }
+}
+
+impl Display for Scenario {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Scenario: [{} nodes, {} twins]",
+            self.num_nodes, self.num_twins
+        )?;
+        for (round, round_scenario) in self.rounds.iter().enumerate() {
+            let partitions: Vec<Vec<String>> = round_scenario
+                .partitions
+                .iter()
+                .map(|partition| partition.iter().map(|node| self.node_name(*node)).collect())
+                .collect();
+            write!(
+                f,
+                "\n  round {}: leader {}, partitions {:?}",
+                round + 1,
+                self.node_name(round_scenario.leader),
+                partitions
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// ScenarioGenerator enumerates the scenarios of a given number of rounds: every round combines
+/// a leader with a way to partition the network.
+///
+/// Only the partitions where a quorum of nodes can make progress are considered, so that the
+/// honest nodes catch up once the scenario is over and the network heals.
+pub struct ScenarioGenerator {
+    num_nodes: usize,
+    num_twins: usize,
+    num_rounds: usize,
+    partitions: Vec<Vec<Vec<usize>>>,
+}
+
+impl ScenarioGenerator {
+    pub fn new(
+        num_nodes: usize,
+        num_twins: usize,
+        num_rounds: usize,
+        max_partitions: usize,
+    ) -> Self {
+        assert!(num_nodes >= num_twins);
+        let quorum = num_nodes * 2 / 3 + 1;
+        let mut generator = Self {
+            num_nodes,
+            num_twins,
+            num_rounds,
+            partitions: vec![],
+        };
+        generator.partitions = set_partitions(num_nodes + num_twins, max_partitions)
+            .into_iter()
+            .filter(|partitions| {
+                partitions
+                    .iter()
+                    .any(|partition| generator.num_distinct_nodes(partition) >= quorum)
+            })
+            .collect();
+        generator
+    }
+
+    /// The number of choices of a leader and partitions for a round.
+    pub fn num_round_scenarios(&self) -> usize {
+        self.num_nodes * self.partitions.len()
+    }
+
+    /// The number of scenarios, None if it overflows.
+    pub fn num_scenarios(&self) -> Option<u64> {
+        (self.num_round_scenarios() as u64).checked_pow(self.num_rounds as u32)
+    }
+
+    /// The scenario of the given index in `0..num_scenarios()`.
+    pub fn scenario(&self, mut index: u64) -> Scenario {
+        let num_round_scenarios = self.num_round_scenarios() as u64;
+        let rounds = (0..self.num_rounds)
+            .map(|_| {
+                let round_scenario = self.round_scenario((index % num_round_scenarios) as usize);
+                index /= num_round_scenarios;
+                round_scenario
+            })
+            .collect();
+        self.new_scenario(rounds)
+    }
+
+    /// A scenario drawn uniformly among all the scenarios.
+    pub fn random_scenario(&self, rng: &mut impl Rng) -> Scenario {
+        let rounds = (0..self.num_rounds)
+            .map(|_| self.round_scenario(rng.gen_range(0, self.num_round_scenarios())))
+            .collect();
+        self.new_scenario(rounds)
+    }
+
+    fn new_scenario(&self, rounds: Vec<RoundScenario>) -> Scenario {
+        Scenario {
+            num_nodes: self.num_nodes,
+            num_twins: self.num_twins,
+            rounds,
+        }
+    }
+
+    fn round_scenario(&self, index: usize) -> RoundScenario {
+        RoundScenario {
+            leader: index % self.num_nodes,
+            partitions: self.partitions[index / self.num_nodes].clone(),
+        }
+    }
+
+    /// A node and its twin only count once toward a quorum.
+    fn num_distinct_nodes(&self, partition: &[usize]) -> usize {
+        (0..self.num_nodes)
+            .filter(|index| {
+                partition.contains(index)
+                    || (*index < self.num_twins && partition.contains(&(index + self.num_nodes)))
+            })
+            .count()
+    }
+}
+
+/// All the ways to split the given number of nodes into at most `max_partitions` partitions,
+/// the nodes of a partition are sorted.
+fn set_partitions(num_nodes: usize, max_partitions: usize) -> Vec<Vec<Vec<usize>>> {
+    let mut all_partitions: Vec<Vec<Vec<usize>>> = vec![vec![]];
+    for node in 0..num_nodes {
+        let mut extended = vec![];
+        for partitions in all_partitions {
+            for i in 0..partitions.len() {
+                let mut with_node = partitions.clone();
+                with_node[i].push(node);
+                extended.push(with_node);
+            }
+            if partitions.len() < max_partitions {
+                let mut with_node = partitions;
+                with_node.push(vec![node]);
+                extended.push(with_node);
+            }
+        }
+        all_partitions = extended;
+    }
+    all_partitions
+}
+
+/// Shrinks a failing scenario to a minimal one: a simplification is kept as long as it still
+/// fails, until none of the simplifications of the scenario fails.
+pub fn minimize(mut scenario: Scenario, mut fails: impl FnMut(&Scenario) -> bool) -> Scenario {
+    while let Some(simpler) = scenario
+        .simplifications()
+        .into_iter()
+        .find(|simpler| fails(simpler))
+    {
+        scenario = simpler;
+    }
+    scenario
+}
@@ -0,0 +1,89 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins_scenario::{minimize, set_partitions, RoundScenario, Scenario, ScenarioGenerator};
+use rand::{rngs::StdRng, SeedableRng};
+
+#[test]
+fn test_set_partitions() {
+    assert_eq!(set_partitions(0, 2), vec![Vec::<Vec<usize>>::new()]);
+    assert_eq!(set_partitions(3, 3).len(), 5);
+    let partitions = set_partitions(4, 2);
+    assert_eq!(partitions.len(), 8);
+    assert!(partitions.contains(&vec![vec![0, 3], vec![1, 2]]));
+    assert!(partitions.iter().all(|partitions| partitions.len() <= 2));
+}
+
+#[test]
+fn test_generate_scenarios() {
+    let generator = ScenarioGenerator::new(4, 1, 2, 2);
+    // Out of the 16 ways to split the 5 nodes, the 3 splits putting n0, twin0 and another node
+    // apart from the 2 other nodes leave no partition with a quorum.
+    assert_eq!(generator.num_round_scenarios(), 4 * 13);
+    assert_eq!(generator.num_scenarios(), Some(52 * 52));
+    assert!(ScenarioGenerator::new(4, 1, 100, 2)
+        .num_scenarios()
+        .is_none());
+
+    let first = generator.scenario(0);
+    assert_eq!(first.rounds.len(), 2);
+    assert_eq!(first.rounds[0].partitions, vec![vec![0, 1, 2, 3, 4]]);
+    let last = generator.scenario(52 * 52 - 1);
+    assert_eq!(last.rounds[0], last.rounds[1]);
+    assert_eq!(last.rounds[0].leader, 3);
+    assert_ne!(generator.scenario(1), generator.scenario(52));
+
+    // The seed reproduces the random scenarios.
+    let mut rng = StdRng::seed_from_u64(7);
+    let scenario = generator.random_scenario(&mut rng);
+    assert_eq!(
+        generator.random_scenario(&mut StdRng::seed_from_u64(7)),
+        scenario
+    );
+    assert_eq!(scenario.honest_nodes().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(scenario.node_index(4), 0);
+}
+
+#[test]
+fn test_minimize_scenario() {
+    let partitioned = RoundScenario {
+        leader: 0,
+        partitions: vec![vec![0, 1, 2], vec![3, 4]],
+    };
+    let scenario = Scenario {
+        num_nodes: 4,
+        num_twins: 1,
+        rounds: vec![
+            RoundScenario {
+                leader: 1,
+                partitions: vec![vec![0, 1, 2, 3, 4]],
+            },
+            partitioned.clone(),
+            RoundScenario {
+                leader: 2,
+                partitions: vec![vec![0, 1, 2, 3], vec![4]],
+            },
+        ],
+    };
+    assert_eq!(
+        scenario.to_string(),
+        "Scenario: [4 nodes, 1 twins]\
+         \n  round 1: leader n1, partitions [[\"n0\", \"n1\", \"n2\", \"n3\", \"twin0\"]]\
+         \n  round 2: leader n0, partitions [[\"n0\", \"n1\", \"n2\"], [\"n3\", \"twin0\"]]\
+         \n  round 3: leader n2, partitions [[\"n0\", \"n1\", \"n2\", \"n3\"], [\"twin0\"]]"
+    );
+
+    // The scenario fails as long as n0 leads a partitioned round.
+    let mut num_runs = 0;
+    let minimal = minimize(scenario, |scenario| {
+        num_runs += 1;
+        scenario
+            .rounds
+            .iter()
+            .any(|round| round.leader == 0 && round.partitions.len() > 1)
+    });
+    assert_eq!(minimal.rounds, vec![partitioned]);
+    // Every run stops at the first failing simplification: the last round is dropped after 1
+    // run, the first round after 2 runs, and the 2 simplifications of the minimal scenario pass.
+    assert_eq!(num_runs, 5);
+}
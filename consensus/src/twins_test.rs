@@ -10,33 +10,47 @@ use crate::{
     test_utils::{
         consensus_runtime, timed_block_on, MockStateComputer, MockStorage, MockTransactionManager,
     },
+    twins_scenario::{minimize, RoundScenario, Scenario, ScenarioGenerator},
     util::time_service::ClockTimeService,
 };
+use anyhow::{bail, ensure, Result};
 use channel::{self, libra_channel, message_queues::QueueStyle};
 use consensus_types::{
-    block::Block,
-    common::{Author, Payload},
+    block::{
+        block_test_utils::{certificate_for_genesis, gen_test_certificate},
+        Block,
+    },
+    common::{Author, Payload, Round},
 };
 use futures::channel::mpsc;
 use libra_config::{
     config::{
-        ConsensusProposerType::{self, FixedProposer, RotatingProposer},
+        ConsensusProposerType::{self, FixedProposer, RotatingProposer, RoundProposer},
         NodeConfig, WaypointConfig,
     },
     generator::{self, ValidatorSwarm},
 };
+use libra_crypto::HashValue;
 use libra_mempool::mocks::MockSharedMempool;
 use libra_types::{
     ledger_info::LedgerInfoWithSignatures,
     on_chain_config::{OnChainConfig, OnChainConfigPayload, ValidatorSet},
     validator_info::ValidatorInfo,
+    validator_signer::ValidatorSigner,
     waypoint::Waypoint,
 };
 use network::{
     peer_manager::{conn_notifs_channel, ConnectionRequestSender, PeerManagerRequestSender},
     protocols::network::{NewNetworkEvents, NewNetworkSender},
 };
-use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 use tokio::runtime::{Builder, Runtime};
 
 /// Auxiliary struct that is preparing SMR for the test
@@ -46,6 +60,7 @@ struct SMRNode {
     runtime: Runtime,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
     storage: Arc<MockStorage>,
+    state_computer: Arc<MockStateComputer>,
     state_sync: mpsc::UnboundedReceiver<Payload>,
     shared_mempool: MockSharedMempool,
 }
@@ -108,6 +123,8 @@ impl SMRNode {
         let (timeout_sender, timeout_receiver) =
             channel::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
         let (self_sender, self_receiver) = channel::new(1_024, &counters::PENDING_SELF_MESSAGES);
+        let (execution_event_sender, execution_event_receiver) =
+            channel::new(1_024, &counters::PENDING_EXECUTION_EVENTS);
 
         let epoch_mgr = EpochManager::new(
             &mut config,
@@ -115,20 +132,27 @@ impl SMRNode {
             self_sender,
             network_sender,
             timeout_sender,
+            execution_event_sender,
             txn_manager,
-            state_computer,
+            state_computer.clone(),
             storage.clone(),
         );
         let (network_task, network_receiver) = NetworkTask::new(network_events, self_receiver);
 
         runtime.spawn(network_task.start());
-        runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver, reconfig_events));
+        runtime.spawn(epoch_mgr.start(
+            timeout_receiver,
+            network_receiver,
+            reconfig_events,
+            execution_event_receiver,
+        ));
         Self {
             config,
             smr_id,
             runtime,
             commit_cb_receiver,
             storage,
+            state_computer,
             state_sync,
             shared_mempool,
         }
@@ -142,6 +166,13 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ConsensusProposerType,
     ) -> (Vec<Self>, Vec<Author>) {
+        let (nodes, validator_set) = Self::twin_configs(num_nodes, num_twins);
+        Self::start_nodes(nodes, validator_set, playground, proposer_type)
+    }
+
+    /// Generates the configs of a given number of nodes followed by the configs of the twins
+    /// of the first nodes, along with the ValidatorSet of the nodes.
+    fn twin_configs(num_nodes: usize, num_twins: usize) -> (Vec<NodeConfig>, ValidatorSet) {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm { mut nodes } = generator::validator_swarm_for_testing(num_nodes);
 
@@ -169,7 +200,16 @@ impl SMRNode {
             let twin = nodes[i].clone();
             nodes.push(twin);
         }
+        (nodes, validator_set)
+    }
 
+    /// Starts the nodes of the given configs, the id of a node is the index of its config.
+    fn start_nodes(
+        nodes: Vec<NodeConfig>,
+        validator_set: ValidatorSet,
+        playground: &mut NetworkPlayground,
+        proposer_type: ConsensusProposerType,
+    ) -> (Vec<Self>, Vec<Author>) {
         let mut smr_nodes = vec![];
         let mut node_authors = vec![];
 
@@ -189,7 +229,7 @@ impl SMRNode {
             config.consensus.proposer_type = proposer_type.clone();
             config.consensus.safety_rules.verify_vote_proposal_signature = false;
 
-            let author = config.validator_network.as_ref().unwrap().peer_id();
+            let author = config_author(&config);
 
             let twin_id = TwinId { id: smr_id, author };
 
@@ -200,6 +240,149 @@ impl SMRNode {
     }
 }
 
+fn config_author(config: &NodeConfig) -> Author {
+    config.validator_network.as_ref().unwrap().peer_id()
+}
+
+/// The number of rounds led by the honest nodes once the network heals at the end of a
+/// scenario, enough for them to commit a block.
+const NUM_LIVENESS_ROUNDS: usize = 10;
+/// The time given to the honest nodes to commit a block once the network heals.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runs a Twins scenario: each round of the scenario is led by its leader, and its messages
+/// are delivered within its partitions. The network heals once the scenario is over.
+///
+/// Checks the safety, i.e. the honest nodes never commit conflicting blocks, and the liveness,
+/// i.e. every honest node commits a block past the rounds of the scenario.
+fn run_scenario(runtime: &Runtime, scenario: &Scenario) -> Result<()> {
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let (configs, validator_set) = SMRNode::twin_configs(scenario.num_nodes, scenario.num_twins);
+    let authors: Vec<_> = configs
+        .iter()
+        .take(scenario.num_nodes)
+        .map(config_author)
+        .collect();
+    let honest_nodes: Vec<_> = scenario.honest_nodes().collect();
+
+    let mut round_proposers = HashMap::new();
+    let mut round_partitions = HashMap::new();
+    for (round, round_scenario) in (1..).zip(&scenario.rounds) {
+        round_proposers.insert(round, authors[round_scenario.leader]);
+        let partitions = round_scenario
+            .partitions
+            .iter()
+            .map(|partition| {
+                partition
+                    .iter()
+                    .map(|node| TwinId {
+                        id: *node,
+                        author: authors[scenario.node_index(*node)],
+                    })
+                    .collect()
+            })
+            .collect();
+        round_partitions.insert(round, partitions);
+    }
+    let last_round = scenario.rounds.len() as Round;
+    for (round, node) in (last_round + 1..)
+        .zip(honest_nodes.iter().cycle())
+        .take(NUM_LIVENESS_ROUNDS)
+    {
+        round_proposers.insert(round, authors[*node]);
+    }
+    playground.set_round_partitions(round_partitions);
+
+    let (nodes, _) = SMRNode::start_nodes(
+        configs,
+        validator_set,
+        &mut playground,
+        RoundProposer(round_proposers),
+    );
+    runtime.spawn(playground.start());
+
+    let deadline = Instant::now() + LIVENESS_TIMEOUT;
+    loop {
+        let commits: Vec<_> = honest_nodes
+            .iter()
+            .map(|node| (*node, nodes[*node].state_computer.committed_blocks()))
+            .collect();
+        check_safety(&commits)?;
+        if commits
+            .iter()
+            .all(|(_, blocks)| blocks.iter().any(|block| block.round() > last_round))
+        {
+            return Ok(());
+        }
+        if Instant::now() > deadline {
+            bail!(
+                "The honest nodes did not all commit a block past round {} within {:?}",
+                last_round,
+                LIVENESS_TIMEOUT
+            );
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Checks that the committed chains of the given nodes are prefixes of one another: every block
+/// committed by a node extends the blocks committed by all the nodes in lower or equal rounds.
+/// The blocks a node synced to are not committed locally, the chains are followed through the
+/// parents of the blocks committed by any node.
+fn check_safety(commits: &[(usize, Vec<Block>)]) -> Result<()> {
+    let blocks: HashMap<HashValue, &Block> = commits
+        .iter()
+        .flat_map(|(_, blocks)| blocks)
+        .map(|block| (block.id(), block))
+        .collect();
+    for (node, chain) in commits {
+        for (other_node, other_chain) in commits {
+            for block in chain {
+                for other_block in other_chain
+                    .iter()
+                    .filter(|other_block| other_block.round() >= block.round())
+                {
+                    ensure!(
+                        extends(&blocks, other_block, block) != Some(false),
+                        "n{} committed {} in round {} which does not extend {} committed by n{} \
+                         in round {}",
+                        other_node,
+                        other_block.id(),
+                        other_block.round(),
+                        block.id(),
+                        node,
+                        block.round()
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the block extends the ancestor, None if the chain of the block cannot be followed
+/// down to the round of the ancestor.
+fn extends(blocks: &HashMap<HashValue, &Block>, block: &Block, ancestor: &Block) -> Option<bool> {
+    let (mut round, mut id) = (block.round(), block.id());
+    let mut current = Some(block);
+    while round > ancestor.round() {
+        let parent = current?.quorum_cert().certified_block();
+        round = parent.round();
+        id = parent.id();
+        current = blocks.get(&id).copied();
+    }
+    Some(round == ancestor.round() && id == ancestor.id())
+}
+
+/// Reads an optional numeric setting of the Twins tests from the environment.
+fn env_setting(name: &str) -> Option<u64> {
+    std::env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be a number, got {}", name, value))
+    })
+}
+
 #[test]
 /// This test checks that the first proposal has its parent and
 /// QC pointing to the genesis block.
@@ -376,3 +559,151 @@ fn twins_vote_dedup_test() {
         assert!(!commit_seen);
     });
 }
+
+#[test]
+/// This test checks that the scenario executor runs a Twins
+/// scenario and checks its safety and liveness.
+///
+/// Setup:
+///
+/// 4 honest nodes (n0, n1, n2, n3), and 1 twin (twin0)
+/// Round 1: n0 and twin0 both propose, and the network is
+/// split in p1=[n0, n1, n2], p2=[n3, twin0]
+/// Round 2: n1 proposes to all the nodes
+///
+/// Test:
+///
+/// Check that the honest nodes (n1, n2, n3) commit the same
+/// blocks, and commit a block past round 2 once the network heals.
+///
+/// Run the test:
+/// cargo xtest -p consensus twins_scenario_executor_test -- --nocapture
+fn twins_scenario_executor_test() {
+    let runtime = consensus_runtime();
+    let scenario = Scenario {
+        num_nodes: 4,
+        num_twins: 1,
+        rounds: vec![
+            RoundScenario {
+                leader: 0,
+                partitions: vec![vec![0, 1, 2], vec![3, 4]],
+            },
+            RoundScenario {
+                leader: 1,
+                partitions: vec![vec![0, 1, 2, 3, 4]],
+            },
+        ],
+    };
+    run_scenario(&runtime, &scenario).unwrap();
+}
+
+#[test]
+/// This test runs all the Twins scenarios of 2 rounds without
+/// partitions.
+///
+/// Setup:
+///
+/// 4 honest nodes (n0, n1, n2, n3), and 1 twin (twin0)
+/// Every round of a scenario has a leader, n0 and twin0 both
+/// propose in the rounds led by n0.
+///
+/// Test:
+///
+/// Run the 16 scenarios in order and check their safety and
+/// liveness.
+///
+/// Run the test:
+/// cargo xtest -p consensus twins_exhaustive_scenarios_test -- --nocapture
+fn twins_exhaustive_scenarios_test() {
+    let runtime = consensus_runtime();
+    let generator = ScenarioGenerator::new(4, 1, 2, 1);
+    let num_scenarios = generator.num_scenarios().unwrap();
+    assert_eq!(num_scenarios, 16);
+
+    for index in 0..num_scenarios {
+        let scenario = generator.scenario(index);
+        if let Err(e) = run_scenario(&runtime, &scenario) {
+            panic!("The Twins scenario {} failed: {:?}\n{}", index, e, scenario);
+        }
+    }
+}
+
+#[test]
+#[ignore]
+/// This test runs randomly generated Twins scenarios and reports
+/// the minimal failing scenario, if any.
+///
+/// Setup:
+///
+/// 4 honest nodes (n0, n1, n2, n3), and 1 twin (twin0)
+/// Every round of a scenario has a leader and splits the
+/// network in at most 2 partitions.
+///
+/// Test:
+///
+/// Run every scenario and check its safety and liveness. The
+/// scenario of seed s is the scenario generated by StdRng seeded
+/// with s, the seeds of the scenarios run are consecutive.
+///
+/// Run the test, the seed is random unless TWINS_SEED is set:
+/// TWINS_SEED=0 TWINS_NUM_SCENARIOS=20 TWINS_NUM_ROUNDS=4 \
+/// cargo xtest -p consensus twins_generated_scenarios_test -- --ignored --nocapture
+fn twins_generated_scenarios_test() {
+    let seed = env_setting("TWINS_SEED").unwrap_or_else(|| rand::thread_rng().gen());
+    let num_scenarios = env_setting("TWINS_NUM_SCENARIOS").unwrap_or(20);
+    let num_rounds = env_setting("TWINS_NUM_ROUNDS").unwrap_or(4);
+    let runtime = consensus_runtime();
+    let generator = ScenarioGenerator::new(4, 1, num_rounds as usize, 2);
+
+    for scenario_seed in (0..num_scenarios).map(|i| seed.wrapping_add(i)) {
+        let scenario = generator.random_scenario(&mut StdRng::seed_from_u64(scenario_seed));
+        if let Err(e) = run_scenario(&runtime, &scenario) {
+            let minimal = minimize(scenario.clone(), |simpler| {
+                run_scenario(&runtime, simpler).is_err()
+            });
+            panic!(
+                "The Twins scenario of seed {} failed: {:?}\n{}\nMinimal failing {}",
+                scenario_seed, e, scenario, minimal
+            );
+        }
+    }
+}
+
+#[test]
+/// This test checks that the safety check detects the forks
+/// between the committed chains, including across rounds.
+///
+/// Run the test:
+/// cargo xtest -p consensus check_safety_test -- --nocapture
+fn check_safety_test() {
+    let signer = ValidatorSigner::random(None);
+    let genesis_qc = certificate_for_genesis();
+    let child = |parent: &Block, round| {
+        let qc = gen_test_certificate(
+            vec![&signer],
+            parent.gen_block_info(HashValue::zero(), 0, None),
+            parent.quorum_cert().certified_block().clone(),
+            None,
+        );
+        Block::new_proposal(vec![], round, round, qc, &signer)
+    };
+    let a1 = Block::new_proposal(vec![], 1, 1, genesis_qc.clone(), &signer);
+    let a2 = child(&a1, 2);
+    let a3 = child(&a2, 3);
+
+    // The chains are prefixes of one another, n2 synced to a2 and only committed a3
+    assert!(check_safety(&[
+        (1, vec![a1.clone(), a2.clone(), a3.clone()]),
+        (2, vec![a1.clone()]),
+        (3, vec![a3]),
+    ])
+    .is_ok());
+
+    // b2 is committed in another round than a1 but does not extend it
+    let b2 = Block::new_proposal(vec![], 2, 2, genesis_qc, &signer);
+    assert!(check_safety(&[(1, vec![a1.clone()]), (2, vec![b2])]).is_err());
+
+    // b3 extends a1 but skips a2
+    let b3 = child(&a1, 3);
+    assert!(check_safety(&[(1, vec![a1.clone(), a2]), (2, vec![a1, b3])]).is_err());
+}
@@ -129,6 +129,13 @@ impl SimulatedTimeService {
             futures::executor::block_on(t.run());
         }
     }
+
+    /// Moves the time to the given timestamp without running the pending tasks, used to replay
+    /// the readings of a recorded time service.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn set_current_timestamp(&self, now: Duration) {
+        self.inner.lock().unwrap().now = now;
+    }
 }
 
 impl Clone for SimulatedTimeService {
@@ -0,0 +1,207 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Authentication and rate limiting of clients.
+//!
+//! If `auth_tokens` are configured, every request (including WebSocket handshakes) must carry
+//! one of them in an `Authorization: Bearer <token>` header, or it is rejected with a 401.
+//!
+//! Each JSON RPC request, including each request of a batch, takes a token from the bucket of its
+//! client and method, with the capacity and refill rate configured for the method in
+//! `rate_limit`. Clients are identified by their auth token if requests are authenticated, by
+//! their IP address otherwise. A request finding the bucket empty fails with a `RateLimited`
+//! error, without being executed.
+
+use crate::counters;
+use libra_config::config::{RateLimitConfig, RpcConfig, TokenBucketConfig};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use warp::{
+    http::StatusCode,
+    reject::{self, Reject},
+    Filter, Rejection, Reply,
+};
+
+/// Number of buckets beyond which full buckets are dropped, as they are the same as new ones. If
+/// none of them is full, requests needing a new bucket are rate limited until one is.
+const MAX_BUCKETS: usize = 10_000;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+pub(crate) struct AccessControl {
+    auth_tokens: HashSet<String>,
+    rate_limit: RateLimitConfig,
+    // Buckets by client and method
+    buckets: Mutex<HashMap<(String, String), TokenBucket>>,
+    max_buckets: usize,
+}
+
+impl AccessControl {
+    pub fn new(config: &RpcConfig) -> Self {
+        Self {
+            auth_tokens: config.auth_tokens.iter().cloned().collect(),
+            rate_limit: config.rate_limit.clone(),
+            buckets: Mutex::new(HashMap::new()),
+            max_buckets: MAX_BUCKETS,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_max_buckets(config: &RpcConfig, max_buckets: usize) -> Self {
+        Self {
+            max_buckets,
+            ..Self::new(config)
+        }
+    }
+
+    /// Returns the identity of the client of a request, by which it is rate limited, or None if
+    /// the request isn't authorized.
+    fn authenticate(
+        &self,
+        remote: Option<SocketAddr>,
+        authorization: Option<String>,
+    ) -> Option<String> {
+        if self.auth_tokens.is_empty() {
+            return Some(
+                remote.map_or_else(|| "unknown".to_string(), |addr| addr.ip().to_string()),
+            );
+        }
+        let authorization = authorization?;
+        if !authorization.starts_with(BEARER_PREFIX) {
+            return None;
+        }
+        let token = &authorization[BEARER_PREFIX.len()..];
+        // Every token is compared in full, so that the time taken doesn't tell how much of a
+        // token was guessed right.
+        let authorized = self
+            .auth_tokens
+            .iter()
+            .fold(false, |authorized, auth_token| {
+                constant_time_eq(token.as_bytes(), auth_token.as_bytes()) | authorized
+            });
+        if authorized {
+            Some(token.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Takes a token from the bucket of `client` for `method`. Returns false if there is none,
+    /// i.e. if the request is rate limited.
+    pub fn try_acquire(&self, client: &str, method: &str) -> bool {
+        let config = match self
+            .rate_limit
+            .methods
+            .get(method)
+            .or_else(|| self.rate_limit.default.as_ref())
+        {
+            Some(config) => *config,
+            None => return true,
+        };
+        let now = Instant::now();
+        let mut buckets = self
+            .buckets
+            .lock()
+            .expect("[rpc] rate limiter lock poisoned");
+        let key = (client.to_string(), method.to_string());
+        if buckets.len() >= self.max_buckets && !buckets.contains_key(&key) {
+            buckets.retain(|_, bucket| !bucket.is_full(now));
+        }
+        let acquired = if buckets.len() < self.max_buckets || buckets.contains_key(&key) {
+            buckets
+                .entry(key)
+                .or_insert_with(|| TokenBucket::new(config, now))
+                .try_acquire(now)
+        } else {
+            false
+        };
+        if !acquired {
+            counters::RATE_LIMITED_REQUESTS
+                .with_label_values(&[method])
+                .inc();
+        }
+        acquired
+    }
+}
+
+/// Filter extracting the identity of the client of a request (see `AccessControl::authenticate`),
+/// which rejects unauthorized requests.
+pub(crate) fn client_filter(
+    access_control: Arc<AccessControl>,
+) -> impl Filter<Extract = (String,), Error = Rejection> + Clone {
+    warp::addr::remote()
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(move |remote, authorization| {
+            let client = access_control.authenticate(remote, authorization);
+            async move {
+                client.ok_or_else(|| {
+                    counters::UNAUTHORIZED_REQUESTS.inc();
+                    reject::custom(Unauthorized)
+                })
+            }
+        })
+}
+
+/// Turns the rejection of unauthorized requests into a 401 response.
+pub(crate) async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    if rejection.find::<Unauthorized>().is_some() {
+        Ok(warp::reply::with_status(
+            "Unauthorized",
+            StatusCode::UNAUTHORIZED,
+        ))
+    } else {
+        Err(rejection)
+    }
+}
+
+/// Compares two byte strings in a time which only depends on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Debug)]
+struct Unauthorized;
+
+impl Reject for Unauthorized {}
+
+struct TokenBucket {
+    config: TokenBucketConfig,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            config,
+            tokens: config.capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        (self.tokens + elapsed * self.config.refill_rate as f64).min(self.config.capacity as f64)
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        self.tokens_at(now) >= self.config.capacity as f64
+    }
+
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        self.tokens = self.tokens_at(now);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
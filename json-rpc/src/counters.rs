@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use libra_metrics::{register_int_gauge_vec, IntGaugeVec};
+use libra_metrics::{register_int_counter, register_int_gauge_vec, IntCounter, IntGaugeVec};
 use once_cell::sync::Lazy;
 
 /// Cumulative number of valid requests that the JSON RPC client service receives
@@ -28,3 +28,24 @@ pub static INVALID_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Cumulative number of requests rejected by the rate limiter
+pub static RATE_LIMITED_REQUESTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "libra_client_service_rate_limited_requests_count",
+        "Cumulative number of requests rejected by the JSON RPC rate limiter",
+        &[
+            "type", // type of request, matches JSON RPC method name
+        ]
+    )
+    .unwrap()
+});
+
+/// Cumulative number of HTTP requests rejected for lacking a valid auth token
+pub static UNAUTHORIZED_REQUESTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_client_service_unauthorized_requests_count",
+        "Cumulative number of HTTP requests rejected for lacking a valid auth token"
+    )
+    .unwrap()
+});
@@ -9,16 +9,20 @@
 //! Protocol specification: https://www.jsonrpc.org/specification
 //!
 //! Module organization:
+//! ├── access_control.rs # authentication and rate limiting of clients
 //! ├── methods.rs        # contains all available JSON RPC method handlers
 //! ├── runtime.rs        # implementation of JSON RPC protocol over HTTP
+//! ├── subscriptions.rs  # subscriptions to committed data over WebSocket
 //! ├── tests.rs          # tests
 
 #[macro_use]
 mod util;
 
+mod access_control;
 mod counters;
 mod methods;
 mod runtime;
+mod subscriptions;
 
 pub use libra_json_rpc_types::{errors, views};
 
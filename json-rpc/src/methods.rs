@@ -5,18 +5,21 @@
 use crate::{
     errors::JsonRpcError,
     views::{
-        AccountStateWithProofView, AccountView, BlockMetadata, CurrencyInfoView, EventView,
-        StateProofView, TransactionView,
+        AccountStateWithProofView, AccountView, BlockMetadata, BytesView, CurrencyInfoView,
+        EventView, MoveModuleView, MoveResourceView, StateProofView, TransactionListWithProofView,
+        TransactionSimulationView, TransactionView, VMStatusView, WriteOpView,
     },
 };
-use anyhow::{ensure, format_err, Error, Result};
+use anyhow::{bail, ensure, format_err, Error, Result};
 use core::future::Future;
 use futures::{channel::oneshot, SinkExt};
 use libra_config::config::RoleType;
-use libra_crypto::hash::CryptoHash;
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_mempool::MempoolClientSender;
+use libra_state_view::{StateView, StateViewId};
 use libra_trace::prelude::*;
 use libra_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_config::{from_currency_code_string, CurrencyInfoResource},
     account_state::AccountState,
@@ -25,12 +28,25 @@ use libra_types::{
     mempool_status::MempoolStatusCode,
     move_resource::MoveStorage,
     on_chain_config::{OnChainConfig, RegisteredCurrencies},
-    transaction::SignedTransaction,
+    transaction::{
+        SignedTransaction, Transaction, TransactionStatus, TransactionWithProof, Version,
+    },
+};
+use libra_vm::LibraVM;
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    parser::parse_type_tags,
 };
 use network::counters;
+use resource_viewer::MoveValueAnnotator;
+use scratchpad::SparseMerkleTree;
 use serde_json::Value;
-use std::{collections::HashMap, convert::TryFrom, ops::Deref, pin::Pin, str::FromStr, sync::Arc};
-use storage_interface::DbReader;
+use std::{
+    cell::RefCell, collections::HashMap, convert::TryFrom, ops::Deref, pin::Pin, str::FromStr,
+    sync::Arc,
+};
+use storage_interface::{state_view::VerifiedStateView, DbReader};
 
 #[derive(Clone)]
 pub(crate) struct JsonRpcService {
@@ -97,6 +113,61 @@ async fn submit(mut service: JsonRpcService, request: JsonRpcRequest) -> Result<
     }
 }
 
+/// Simulates the execution of a transaction on top of the latest state, without submitting it.
+/// The signature is only verified if requested, so that unsigned transactions can be simulated.
+async fn simulate_transaction(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<TransactionSimulationView> {
+    let txn_payload: String = serde_json::from_value(request.get_param(0))?;
+    let transaction: SignedTransaction = lcs::from_bytes(&hex::decode(txn_payload)?)?;
+    let check_signature: bool = serde_json::from_value(request.get_param(1))?;
+
+    let (version, state_root) = service.db.get_latest_state_root()?;
+    let db = Arc::clone(&service.db);
+    let txn = transaction.clone();
+    // Executing the transaction is CPU bound, so it must not block the server's event loop.
+    let (_vm_status, output) = tokio::task::spawn_blocking(move || {
+        let smt = SparseMerkleTree::new(state_root);
+        let state_view = VerifiedStateView::new(
+            StateViewId::Miscellaneous,
+            db,
+            Some(version),
+            state_root,
+            &smt,
+        );
+        LibraVM::simulate_signed_transaction(txn, check_signature, &state_view)
+    })
+    .await?;
+
+    let vm_status: VMStatusView = match output.status() {
+        TransactionStatus::Keep(status) => status.into(),
+        TransactionStatus::Discard(status) => {
+            return Err(Error::new(JsonRpcError::vm_status(*status)))
+        }
+        TransactionStatus::Retry => {
+            return Err(format_err!(
+                "Unexpected retry status for a simulated transaction"
+            ))
+        }
+    };
+    let transaction = Transaction::UserTransaction(transaction);
+    Ok(TransactionSimulationView {
+        version: version + 1,
+        hash: transaction.hash().to_hex(),
+        transaction: transaction.into(),
+        events: output
+            .events()
+            .iter()
+            .cloned()
+            .map(|event| (version + 1, event).into())
+            .collect(),
+        vm_status,
+        gas_used: output.gas_used(),
+        write_set: output.write_set().iter().map(WriteOpView::from).collect(),
+    })
+}
+
 /// Returns account state (AccountView) by given address
 async fn get_account(
     service: JsonRpcService,
@@ -162,10 +233,47 @@ async fn get_transactions(
         "limit must be smaller than 1000"
     );
 
+    get_transaction_views(
+        service.db.as_ref(),
+        start_version,
+        limit,
+        request.version(),
+        include_events,
+    )
+}
+
+/// Returns transactions by range, with the proof that they're part of the ledger as of the latest
+/// ledger info
+async fn get_transactions_with_proofs(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<TransactionListWithProofView> {
+    let start_version: u64 = serde_json::from_value(request.get_param(0))?;
+    let limit: u64 = serde_json::from_value(request.get_param(1))?;
+    let include_events: bool = serde_json::from_value(request.get_param(2))?;
+
+    ensure!(
+        limit > 0 && limit <= 1000,
+        "limit must be smaller than 1000"
+    );
+
     let txs =
         service
             .db
             .get_transactions(start_version, limit, request.version(), include_events)?;
+    TransactionListWithProofView::try_from(txs)
+}
+
+/// Returns the views of up to `limit` transactions starting at `start_version`, as of
+/// `ledger_version`
+pub(crate) fn get_transaction_views(
+    db: &dyn DbReader,
+    start_version: u64,
+    limit: u64,
+    ledger_version: u64,
+    include_events: bool,
+) -> Result<Vec<TransactionView>> {
+    let txs = db.get_transactions(start_version, limit, ledger_version, include_events)?;
 
     let mut result = vec![];
 
@@ -220,34 +328,49 @@ async fn get_account_transaction(
     let tx = service
         .db
         .get_txn_by_account(account, sequence, request.version(), include_events)?;
+    tx.map(|tx| transaction_view(tx, include_events))
+        .transpose()
+}
 
-    if let Some(tx) = tx {
-        if include_events {
-            ensure!(
-                tx.events.is_some(),
-                "Storage layer didn't return events when requested!"
-            );
-        }
-        let tx_version = tx.version;
-
-        let events = tx
-            .events
-            .unwrap_or_default()
-            .into_iter()
-            .map(|x| ((tx_version, x).into()))
-            .collect();
-
-        Ok(Some(TransactionView {
-            version: tx_version,
-            hash: tx.transaction.hash().to_hex(),
-            transaction: tx.transaction.into(),
-            events,
-            vm_status: tx.proof.transaction_info().status().into(),
-            gas_used: tx.proof.transaction_info().gas_used(),
-        }))
-    } else {
-        Ok(None)
+/// Returns the transaction with the given hash
+async fn get_transaction_by_hash(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<TransactionView>> {
+    let raw_hash: String = serde_json::from_value(request.get_param(0))?;
+    let include_events: bool = serde_json::from_value(request.get_param(1))?;
+
+    let hash = HashValue::from_hex(&raw_hash)?;
+    let tx = service
+        .db
+        .get_txn_by_hash(hash, request.version(), include_events)?;
+    tx.map(|tx| transaction_view(tx, include_events))
+        .transpose()
+}
+
+fn transaction_view(tx: TransactionWithProof, include_events: bool) -> Result<TransactionView> {
+    if include_events {
+        ensure!(
+            tx.events.is_some(),
+            "Storage layer didn't return events when requested!"
+        );
     }
+    let tx_version = tx.version;
+    let events = tx
+        .events
+        .unwrap_or_default()
+        .into_iter()
+        .map(|x| ((tx_version, x).into()))
+        .collect();
+
+    Ok(TransactionView {
+        version: tx_version,
+        hash: tx.transaction.hash().to_hex(),
+        transaction: tx.transaction.into(),
+        events,
+        vm_status: tx.proof.transaction_info().status().into(),
+        gas_used: tx.proof.transaction_info().gas_used(),
+    })
 }
 
 /// Returns events by given access path
@@ -334,6 +457,187 @@ async fn get_account_state_with_proof(
     )?)
 }
 
+/// Returns a resource published under an account, looked up either by struct tag
+/// (e.g. `0x1::LibraAccount::LibraAccount`) or by hex-encoded access path. If no version is
+/// specified, default to the latest version.
+async fn get_resource(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<MoveResourceView>> {
+    let address: String = serde_json::from_value(request.get_param(0))?;
+    let account_address = AccountAddress::from_str(&address)?;
+    let resource: String = serde_json::from_value(request.get_param(1))?;
+    let version = requested_version(&request, 2)?;
+
+    let struct_tag = if resource.contains("::") {
+        Some(parse_struct_tag(&resource)?)
+    } else {
+        None
+    };
+    let path = match &struct_tag {
+        Some(struct_tag) => AccessPath::resource_access_vec(struct_tag),
+        None => hex::decode(&resource)?,
+    };
+    let access_path = AccessPath::new(account_address, path);
+    let state_view = DbStateView::new(service.db.as_ref(), version);
+    state_view
+        .get(&access_path)?
+        .map(|blob| resource_view(&state_view, access_path, &blob, struct_tag.as_ref()))
+        .transpose()
+}
+
+/// Returns all the resources published under an account, or none if the account doesn't exist.
+/// If no version is specified, default to the latest version.
+async fn get_account_resources(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Vec<MoveResourceView>> {
+    let address: String = serde_json::from_value(request.get_param(0))?;
+    let account_address = AccountAddress::from_str(&address)?;
+    let version = requested_version(&request, 1)?;
+
+    let state_view = DbStateView::new(service.db.as_ref(), version);
+    let account_state = match state_view.account_state(account_address)? {
+        Some(account_state) => account_state,
+        None => return Ok(vec![]),
+    };
+    account_state
+        .iter()
+        .filter(|(path, _blob)| path.first() == Some(&AccessPath::RESOURCE_TAG))
+        .map(|(path, blob)| {
+            let access_path = AccessPath::new(account_address, path.clone());
+            resource_view(&state_view, access_path, blob, None)
+        })
+        .collect()
+}
+
+/// Returns the bytecode of a module published under an account. If no version is specified,
+/// default to the latest version.
+async fn get_module(
+    service: JsonRpcService,
+    request: JsonRpcRequest,
+) -> Result<Option<MoveModuleView>> {
+    let address: String = serde_json::from_value(request.get_param(0))?;
+    let account_address = AccountAddress::from_str(&address)?;
+    let name: String = serde_json::from_value(request.get_param(1))?;
+    let version = requested_version(&request, 2)?;
+
+    let module_id = ModuleId::new(account_address, Identifier::new(name.clone())?);
+    let state_view = DbStateView::new(service.db.as_ref(), version);
+    Ok(state_view
+        .get(&AccessPath::code_access_path(&module_id))?
+        .map(|bytecode| MoveModuleView {
+            address: account_address.to_string(),
+            name,
+            bytecode: BytesView::from(&bytecode),
+        }))
+}
+
+/// Returns the version given by the optional request parameter at `index`, defaulting to the
+/// latest version if it's null.
+fn requested_version(request: &JsonRpcRequest, index: usize) -> Result<Version> {
+    let version = match request.get_param(index) {
+        Value::Null => request.version(),
+        param => serde_json::from_value::<u64>(param)
+            .map_err(|_| Error::new(JsonRpcError::invalid_params()))?,
+    };
+    ensure!(
+        version <= request.version(),
+        "version {} is beyond the latest version {}",
+        version,
+        request.version()
+    );
+    Ok(version)
+}
+
+fn parse_struct_tag(s: &str) -> Result<StructTag> {
+    let mut type_tags = parse_type_tags(s)?;
+    match (type_tags.pop(), type_tags.is_empty()) {
+        (Some(TypeTag::Struct(struct_tag)), true) => Ok(struct_tag),
+        _ => bail!("invalid struct tag: {}", s),
+    }
+}
+
+/// Decodes a resource into JSON. If its type isn't given, this only succeeds for the types known
+/// to the resource viewer; the other resources are returned undecoded.
+fn resource_view(
+    state_view: &DbStateView,
+    access_path: AccessPath,
+    blob: &[u8],
+    struct_tag: Option<&StructTag>,
+) -> Result<MoveResourceView> {
+    let path = BytesView::from(&access_path.path);
+    let annotator = MoveValueAnnotator::new(state_view);
+    let annotated = match struct_tag {
+        Some(struct_tag) => Some(annotator.view_resource(struct_tag, blob)?),
+        None => annotator.view_access_path(access_path, blob).ok(),
+    };
+    Ok(MoveResourceView {
+        type_: annotated
+            .as_ref()
+            .map(|annotated| annotated.type_().to_string()),
+        path,
+        value: annotated.map(serde_json::to_value).transpose()?,
+        raw: BytesView::from(blob),
+    })
+}
+
+/// State as of a given version, read straight from the DB. Used to load the modules declaring the
+/// types of the resources being decoded.
+struct DbStateView<'a> {
+    db: &'a dyn DbReader,
+    version: Version,
+    /// Account states already read. Each read fetches a whole account state with its proof, while
+    /// decoding a resource can load many modules from the same account.
+    account_states: RefCell<HashMap<AccountAddress, Option<Arc<AccountState>>>>,
+}
+
+impl<'a> DbStateView<'a> {
+    fn new(db: &'a dyn DbReader, version: Version) -> Self {
+        Self {
+            db,
+            version,
+            account_states: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn account_state(&self, address: AccountAddress) -> Result<Option<Arc<AccountState>>> {
+        if let Some(account_state) = self.account_states.borrow().get(&address) {
+            return Ok(account_state.clone());
+        }
+        let account_state = self
+            .db
+            .get_account_state_with_proof_by_version(address, self.version)?
+            .0
+            .map(|blob| AccountState::try_from(&blob))
+            .transpose()?
+            .map(Arc::new);
+        self.account_states
+            .borrow_mut()
+            .insert(address, account_state.clone());
+        Ok(account_state)
+    }
+}
+
+impl<'a> StateView for DbStateView<'a> {
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        Ok(self
+            .account_state(access_path.address)?
+            .and_then(|account_state| account_state.get(&access_path.path).cloned()))
+    }
+
+    fn multi_get(&self, access_paths: &[AccessPath]) -> Result<Vec<Option<Vec<u8>>>> {
+        access_paths
+            .iter()
+            .map(|access_path| self.get(access_path))
+            .collect()
+    }
+
+    fn is_genesis(&self) -> bool {
+        false
+    }
+}
+
 /// Returns the number of peers this node is connected to
 async fn get_network_status(service: JsonRpcService, _request: JsonRpcRequest) -> Result<u64> {
     let blah = counters::LIBRA_NETWORK_PEERS
@@ -347,15 +651,28 @@ async fn get_network_status(service: JsonRpcService, _request: JsonRpcRequest) -
 pub(crate) fn build_registry() -> RpcRegistry {
     let mut registry = RpcRegistry::new();
     register_rpc_method!(registry, "submit", submit, 1);
+    register_rpc_method!(registry, "simulate_transaction", simulate_transaction, 2);
     register_rpc_method!(registry, "get_metadata", get_metadata, 1);
     register_rpc_method!(registry, "get_account", get_account, 1);
     register_rpc_method!(registry, "get_transactions", get_transactions, 3);
+    register_rpc_method!(
+        registry,
+        "get_transactions_with_proofs",
+        get_transactions_with_proofs,
+        3
+    );
     register_rpc_method!(
         registry,
         "get_account_transaction",
         get_account_transaction,
         3
     );
+    register_rpc_method!(
+        registry,
+        "get_transaction_by_hash",
+        get_transaction_by_hash,
+        2
+    );
     register_rpc_method!(registry, "get_events", get_events, 3);
     register_rpc_method!(registry, "get_currencies", currencies_info, 0);
 
@@ -366,6 +683,9 @@ pub(crate) fn build_registry() -> RpcRegistry {
         get_account_state_with_proof,
         3
     );
+    register_rpc_method!(registry, "get_resource", get_resource, 3);
+    register_rpc_method!(registry, "get_account_resources", get_account_resources, 2);
+    register_rpc_method!(registry, "get_module", get_module, 3);
     register_rpc_method!(registry, "get_network_status", get_network_status, 0);
 
     registry
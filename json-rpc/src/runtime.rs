@@ -2,21 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    access_control::{self, AccessControl},
     counters,
     errors::JsonRpcError,
     methods::{build_registry, JsonRpcRequest, JsonRpcService, RpcRegistry},
+    subscriptions::{websocket_route, SubscriptionService},
 };
 use futures::future::join_all;
-use libra_config::config::{NodeConfig, RoleType};
+use libra_config::config::{NodeConfig, RoleType, RpcConfig};
 use libra_json_rpc_types::views::{
     JSONRPC_LIBRA_LEDGER_TIMESTAMPUSECS, JSONRPC_LIBRA_LEDGER_VERSION,
 };
 use libra_mempool::MempoolClientSender;
-use libra_types::ledger_info::LedgerInfoWithSignatures;
+use libra_types::{ledger_info::LedgerInfoWithSignatures, transaction::Version};
 use serde_json::{map::Map, Value};
-use std::{net::SocketAddr, sync::Arc};
+use std::sync::Arc;
 use storage_interface::DbReader;
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::watch,
+};
 use warp::{
     reject::{self, Reject},
     Filter,
@@ -30,13 +35,16 @@ const LABEL_INVALID_PARAMS: &str = "invalid_params";
 const LABEL_MISSING_METHOD: &str = "method_not_found";
 const LABEL_SUCCESS: &str = "success";
 
-/// Creates HTTP server (warp-based) that serves JSON RPC requests
+/// Creates HTTP server (warp-based) that serves JSON RPC requests, and subscriptions over
+/// WebSocket driven by `commit_notifications` (see `subscriptions`), with TLS, authentication and
+/// rate limiting as configured (see `access_control`)
 /// Returns handle to corresponding Tokio runtime
 pub fn bootstrap(
-    address: SocketAddr,
+    config: &RpcConfig,
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     role: RoleType,
+    commit_notifications: watch::Receiver<Version>,
 ) -> Runtime {
     let runtime = Builder::new()
         .thread_name("rpc-")
@@ -46,14 +54,24 @@ pub fn bootstrap(
         .expect("[rpc] failed to create runtime");
 
     let registry = Arc::new(build_registry());
+    let access_control = Arc::new(AccessControl::new(config));
+    let subscription_service = SubscriptionService::new(
+        config,
+        Arc::clone(&libra_db),
+        Arc::clone(&access_control),
+        commit_notifications,
+    );
     let service = JsonRpcService::new(libra_db, mp_sender, role);
+    let client = access_control::client_filter(Arc::clone(&access_control));
 
     let base_route = warp::any()
         .and(warp::post())
         .and(warp::header::exact("content-type", "application/json"))
+        .and(client)
         .and(warp::body::json())
         .and(warp::any().map(move || service.clone()))
         .and(warp::any().map(move || Arc::clone(&registry)))
+        .and(warp::any().map(move || Arc::clone(&access_control)))
         .and_then(rpc_endpoint);
 
     // For now we still allow user to use "/", but user should start to move to "/v1" soon
@@ -63,7 +81,12 @@ pub fn bootstrap(
         .and(warp::path::end())
         .and(base_route);
 
-    let full_route = route_v1.or(route_root);
+    let route_ws = websocket_route(subscription_service);
+
+    let full_route = route_ws
+        .or(route_v1)
+        .or(route_root)
+        .recover(access_control::handle_rejection);
 
     // Ensure that we actually bind to the socket first before spawning the
     // server tasks. This helps in tests to prevent races where a client attempts
@@ -72,8 +95,25 @@ pub fn bootstrap(
     //
     // Note: we need to enter the runtime context first to actually bind, since
     //       tokio TcpListener can only be bound inside a tokio context.
-    let server = runtime.enter(move || warp::serve(full_route).bind(address));
-    runtime.handle().spawn(server);
+    let address = config.address;
+    match (config.tls_cert_path.clone(), config.tls_key_path.clone()) {
+        (Some(cert_path), Some(key_path)) => {
+            let (_, server) = runtime.enter(move || {
+                warp::serve(full_route)
+                    .tls()
+                    .cert_path(cert_path)
+                    .key_path(key_path)
+                    .bind_ephemeral(address)
+            });
+            runtime.handle().spawn(server);
+        }
+        (None, None) => {
+            let server = runtime.enter(move || warp::serve(full_route).bind(address));
+            runtime.handle().spawn(server);
+        }
+        // Not served in plaintext, as TLS is meant to be enabled.
+        _ => panic!("[rpc] tls_cert_path and tls_key_path must be set together"),
+    }
     runtime
 }
 
@@ -82,17 +122,26 @@ pub fn bootstrap_from_config(
     config: &NodeConfig,
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    commit_notifications: watch::Receiver<Version>,
 ) -> Runtime {
-    bootstrap(config.rpc.address, libra_db, mp_sender, config.base.role)
+    bootstrap(
+        &config.rpc,
+        libra_db,
+        mp_sender,
+        config.base.role,
+        commit_notifications,
+    )
 }
 
 /// JSON RPC entry point
 /// Handles all incoming rpc requests
 /// Performs routing based on methods defined in `registry`
 async fn rpc_endpoint(
+    client: String,
     data: Value,
     service: JsonRpcService,
     registry: Arc<RpcRegistry>,
+    access_control: Arc<AccessControl>,
 ) -> Result<Box<dyn warp::Reply>, warp::Rejection> {
     // take snapshot of latest version of DB to be used across all requests, especially for batched requests
     let ledger_info = service
@@ -107,13 +156,23 @@ async fn rpc_endpoint(
                 service.clone(),
                 Arc::clone(&registry),
                 ledger_info.clone(),
+                &client,
+                &access_control,
             )
         });
         let responses = join_all(futures).await;
         warp::reply::json(&Value::Array(responses))
     } else {
         // single API call
-        let resp = rpc_request_handler(data, service, registry, ledger_info).await;
+        let resp = rpc_request_handler(
+            data,
+            service,
+            registry,
+            ledger_info,
+            &client,
+            &access_control,
+        )
+        .await;
         warp::reply::json(&resp)
     });
 
@@ -127,6 +186,8 @@ async fn rpc_request_handler(
     service: JsonRpcService,
     registry: Arc<RpcRegistry>,
     ledger_info: LedgerInfoWithSignatures,
+    client: &str,
+    access_control: &AccessControl,
 ) -> Value {
     let request: Map<String, Value>;
     let mut response = Map::new();
@@ -199,6 +260,9 @@ async fn rpc_request_handler(
     // get rpc handler
     match request.get("method") {
         Some(Value::String(name)) => match registry.get(name) {
+            Some(_) if !access_control.try_acquire(client, name) => {
+                set_response_error(&mut response, JsonRpcError::rate_limited(), None);
+            }
             Some(handler) => match handler(service, request_params).await {
                 Ok(result) => {
                     response.insert("result".to_string(), result);
@@ -252,7 +316,7 @@ fn set_response_error(response: &mut Map<String, Value>, error: JsonRpcError, la
     }
 }
 
-fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError> {
+pub(crate) fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError> {
     match request.get("id") {
         Some(req_id) => {
             if req_id.is_string() || req_id.is_number() || req_id.is_null() {
@@ -265,7 +329,7 @@ fn parse_request_id(request: &Map<String, Value>) -> Result<Value, JsonRpcError>
     }
 }
 
-fn verify_protocol(request: &Map<String, Value>) -> Result<(), JsonRpcError> {
+pub(crate) fn verify_protocol(request: &Map<String, Value>) -> Result<(), JsonRpcError> {
     if let Some(Value::String(protocol)) = request.get("jsonrpc") {
         if protocol == "2.0" {
             return Ok(());
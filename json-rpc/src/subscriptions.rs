@@ -0,0 +1,434 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Subscriptions over WebSocket, so that clients are pushed what's committed instead of polling
+//! `get_transactions` and `get_events`.
+//!
+//! A client connects to `/v1/ws` and sends JSON RPC requests over the socket:
+//! * `subscribe_to_transactions(start_version, include_events)`: a `TransactionView` for every
+//!   transaction from `start_version` on.
+//! * `subscribe_to_events(event_key, start_sequence_number)`: an `EventView` for every event of
+//!   `event_key` from `start_sequence_number` on.
+//! * `subscribe_to_ledger_info(known_version)`: a `StateProofView` for every new LedgerInfo,
+//!   proving it from `known_version` and then from the LedgerInfo before it. LedgerInfos
+//!   committed while the client is behind are skipped.
+//! * `unsubscribe(subscription_id)`
+//!
+//! A subscription request is responded to with the subscription ID, followed by notifications
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "method": "subscription", "params": {"subscription": 0, "result": {...}}}
+//! ```
+//!
+//! carrying the objects in order, starting with the ones already in the DB. Once caught up, a
+//! subscription waits for DB commit notifications to look for new objects. If a subscription
+//! fails, its last notification carries an `error` instead of a `result`.
+//!
+//! Each request is rate limited like the ones over HTTP, under its method name (see
+//! `access_control`).
+//!
+//! Messages to a client are queued up to `websocket_send_buffer_size`, beyond which its
+//! subscriptions stop reading the DB until the client catches up.
+
+use crate::{
+    access_control::{self, AccessControl},
+    errors::JsonRpcError,
+    methods::get_transaction_views,
+    runtime::{parse_request_id, verify_protocol},
+    views::{EventView, StateProofView},
+};
+use anyhow::{ensure, Result};
+use futures::{
+    channel::mpsc,
+    future::{abortable, AbortHandle},
+    SinkExt, StreamExt,
+};
+use libra_config::config::RpcConfig;
+use libra_logger::prelude::*;
+use libra_types::{event::EventKey, transaction::Version};
+use serde_json::{json, map::Map, Value};
+use std::{
+    cmp::min,
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use storage_interface::DbReader;
+use tokio::sync::watch;
+use warp::{
+    http::StatusCode,
+    ws::{Message, WebSocket, Ws},
+    Filter, Rejection, Reply,
+};
+
+/// Max number of objects read from the DB at once for a subscription.
+const BATCH_SIZE: u64 = 100;
+
+#[derive(Clone)]
+pub(crate) struct SubscriptionService {
+    db: Arc<dyn DbReader>,
+    access_control: Arc<AccessControl>,
+    // Notified of the latest LedgerInfo version upon every DB commit
+    commit_notifications: watch::Receiver<Version>,
+    max_subscriptions_per_connection: usize,
+    max_connections: usize,
+    send_buffer_size: usize,
+    num_connections: Arc<AtomicUsize>,
+}
+
+impl SubscriptionService {
+    pub fn new(
+        config: &RpcConfig,
+        db: Arc<dyn DbReader>,
+        access_control: Arc<AccessControl>,
+        commit_notifications: watch::Receiver<Version>,
+    ) -> Self {
+        Self {
+            db,
+            access_control,
+            commit_notifications,
+            max_subscriptions_per_connection: config.max_subscriptions_per_connection,
+            max_connections: config.max_websocket_connections,
+            send_buffer_size: config.websocket_send_buffer_size,
+            num_connections: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    async fn serve_connection(self, client: String, socket: WebSocket, _guard: ConnectionGuard) {
+        let (mut ws_sender, mut ws_receiver) = socket.split();
+        let (sender, mut receiver) = mpsc::channel::<Value>(self.send_buffer_size);
+        tokio::spawn(async move {
+            while let Some(msg) = receiver.next().await {
+                if ws_sender
+                    .send(Message::text(msg.to_string()))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let mut connection = Connection {
+            service: self,
+            client,
+            sender,
+            subscriptions: HashMap::new(),
+            next_subscription_id: 0,
+        };
+        while let Some(Ok(msg)) = ws_receiver.next().await {
+            if msg.is_close() {
+                break;
+            }
+            if let Ok(text) = msg.to_str() {
+                let response = connection.process_request(text);
+                if connection.sender.send(response).await.is_err() {
+                    break;
+                }
+            }
+        }
+        for handle in connection.subscriptions.values() {
+            handle.abort();
+        }
+    }
+}
+
+/// Counts a WebSocket connection towards `max_websocket_connections` for as long as it's alive.
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl ConnectionGuard {
+    fn new(num_connections: Arc<AtomicUsize>, max_connections: usize) -> Option<Self> {
+        if num_connections.fetch_add(1, Ordering::SeqCst) >= max_connections {
+            num_connections.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Self(num_connections))
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Route of the WebSocket endpoint, which authenticates clients like the HTTP one.
+pub(crate) fn websocket_route(
+    service: SubscriptionService,
+) -> impl Filter<Extract = (Box<dyn Reply>,), Error = Rejection> + Clone {
+    warp::path("v1")
+        .and(warp::path("ws"))
+        .and(warp::path::end())
+        .and(access_control::client_filter(Arc::clone(
+            &service.access_control,
+        )))
+        .and(warp::ws())
+        .map(move |client: String, ws: Ws| {
+            let service = service.clone();
+            match ConnectionGuard::new(
+                Arc::clone(&service.num_connections),
+                service.max_connections,
+            ) {
+                Some(guard) => Box::new(
+                    ws.on_upgrade(move |socket| service.serve_connection(client, socket, guard)),
+                ) as Box<dyn Reply>,
+                None => Box::new(warp::reply::with_status(
+                    "Too many WebSocket connections",
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )),
+            }
+        })
+}
+
+struct Connection {
+    service: SubscriptionService,
+    // Identity of the client, by which it is rate limited
+    client: String,
+    // Queue of messages to the client
+    sender: mpsc::Sender<Value>,
+    subscriptions: HashMap<u64, AbortHandle>,
+    next_subscription_id: u64,
+}
+
+impl Connection {
+    /// Handles a JSON RPC request, returning the response.
+    fn process_request(&mut self, text: &str) -> Value {
+        let mut response = Map::new();
+        response.insert("jsonrpc".to_string(), Value::String("2.0".to_string()));
+        response.insert("id".to_string(), Value::Null);
+
+        let result = serde_json::from_str::<Value>(text)
+            .map_err(|_| JsonRpcError::invalid_request())
+            .and_then(|request| match request {
+                Value::Object(request) => Ok(request),
+                _ => Err(JsonRpcError::invalid_request()),
+            })
+            .and_then(|request| {
+                response.insert("id".to_string(), parse_request_id(&request)?);
+                verify_protocol(&request)?;
+                let params = match request.get("params") {
+                    Some(Value::Array(params)) => params.clone(),
+                    _ => return Err(JsonRpcError::invalid_params()),
+                };
+                match request.get("method") {
+                    Some(Value::String(method)) => self.process_method(method, params),
+                    _ => Err(JsonRpcError::invalid_request()),
+                }
+            });
+        match result {
+            Ok(result) => response.insert("result".to_string(), result),
+            Err(err) => response.insert("error".to_string(), err.serialize()),
+        };
+        Value::Object(response)
+    }
+
+    fn process_method(&mut self, method: &str, params: Vec<Value>) -> Result<Value, JsonRpcError> {
+        match method {
+            "subscribe_to_transactions"
+            | "subscribe_to_events"
+            | "subscribe_to_ledger_info"
+            | "unsubscribe" => {
+                if !self
+                    .service
+                    .access_control
+                    .try_acquire(&self.client, method)
+                {
+                    return Err(JsonRpcError::rate_limited());
+                }
+            }
+            _ => return Err(JsonRpcError::method_not_found()),
+        }
+        if method == "unsubscribe" {
+            let id: u64 = parse_params(&params, 1, 0)?;
+            return match self.subscriptions.remove(&id) {
+                Some(handle) => {
+                    handle.abort();
+                    Ok(Value::Bool(true))
+                }
+                None => Ok(Value::Bool(false)),
+            };
+        }
+
+        let subscription = match method {
+            "subscribe_to_transactions" => Subscription::Transactions {
+                next_version: parse_params(&params, 2, 0)?,
+                include_events: parse_params(&params, 2, 1)?,
+            },
+            "subscribe_to_events" => {
+                let raw_event_key: String = parse_params(&params, 2, 0)?;
+                Subscription::Events {
+                    event_key: hex::decode(raw_event_key)
+                        .ok()
+                        .and_then(|bytes| EventKey::try_from(&bytes[..]).ok())
+                        .ok_or_else(JsonRpcError::invalid_params)?,
+                    next_seq_num: parse_params(&params, 2, 1)?,
+                }
+            }
+            "subscribe_to_ledger_info" => Subscription::LedgerInfo {
+                known_version: parse_params(&params, 1, 0)?,
+            },
+            _ => unreachable!(),
+        };
+        // Subscriptions count towards the limit until unsubscribed, even if they have failed.
+        if self.subscriptions.len() >= self.service.max_subscriptions_per_connection {
+            return Err(JsonRpcError::internal_error(format!(
+                "Too many subscriptions, the limit is {}",
+                self.service.max_subscriptions_per_connection
+            )));
+        }
+
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        let (task, handle) = abortable(subscription.run(
+            id,
+            Arc::clone(&self.service.db),
+            self.service.commit_notifications.clone(),
+            self.sender.clone(),
+        ));
+        tokio::spawn(task);
+        self.subscriptions.insert(id, handle);
+        Ok(json!(id))
+    }
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(
+    params: &[Value],
+    num_params: usize,
+    index: usize,
+) -> Result<T, JsonRpcError> {
+    if params.len() != num_params {
+        return Err(JsonRpcError::invalid_params());
+    }
+    serde_json::from_value(params[index].clone()).map_err(|_| JsonRpcError::invalid_params())
+}
+
+enum Subscription {
+    Transactions {
+        next_version: Version,
+        include_events: bool,
+    },
+    Events {
+        event_key: EventKey,
+        next_seq_num: u64,
+    },
+    LedgerInfo {
+        known_version: Version,
+    },
+}
+
+impl Subscription {
+    async fn run(
+        mut self,
+        id: u64,
+        db: Arc<dyn DbReader>,
+        mut commit_notifications: watch::Receiver<Version>,
+        mut sender: mpsc::Sender<Value>,
+    ) {
+        loop {
+            // Keep reading until caught up, then wait for the next commit.
+            let caught_up = match self.next_batch(db.as_ref()) {
+                Ok((batch, caught_up)) => {
+                    for result in batch {
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "subscription",
+                            "params": {"subscription": id, "result": result},
+                        });
+                        if sender.send(notification).await.is_err() {
+                            return;
+                        }
+                    }
+                    caught_up
+                }
+                Err(err) => {
+                    warn!("[json-rpc] subscription {} failed: {}", id, err);
+                    let error = JsonRpcError::internal_error(err.to_string()).serialize();
+                    let _ = sender
+                        .send(json!({
+                            "jsonrpc": "2.0",
+                            "method": "subscription",
+                            "params": {"subscription": id, "error": error},
+                        }))
+                        .await;
+                    return;
+                }
+            };
+            if caught_up && commit_notifications.recv().await.is_none() {
+                return;
+            }
+        }
+    }
+
+    /// Reads the next objects to push as of the latest LedgerInfo, and whether there is nothing
+    /// more to read after them.
+    fn next_batch(&mut self, db: &dyn DbReader) -> Result<(Vec<Value>, bool)> {
+        let ledger_info = db.get_latest_ledger_info()?;
+        let ledger_version = ledger_info.ledger_info().version();
+        match self {
+            Self::Transactions {
+                next_version,
+                include_events,
+            } => {
+                if *next_version > ledger_version {
+                    return Ok((vec![], true));
+                }
+                let limit = min(BATCH_SIZE, ledger_version - *next_version + 1);
+                let txns = get_transaction_views(
+                    db,
+                    *next_version,
+                    limit,
+                    ledger_version,
+                    *include_events,
+                )?;
+                ensure!(
+                    txns.len() as u64 == limit,
+                    "Expected {} transactions from version {}, got {}",
+                    limit,
+                    next_version,
+                    txns.len()
+                );
+                *next_version += limit;
+                Ok((
+                    txns.into_iter()
+                        .map(serde_json::to_value)
+                        .collect::<Result<_, _>>()?,
+                    *next_version > ledger_version,
+                ))
+            }
+            Self::Events {
+                event_key,
+                next_seq_num,
+            } => {
+                let events: Vec<EventView> = db
+                    .get_events(event_key, *next_seq_num, true, BATCH_SIZE)?
+                    .into_iter()
+                    .filter(|(version, _event)| *version <= ledger_version)
+                    .map(|event| event.into())
+                    .collect();
+                *next_seq_num += events.len() as u64;
+                let caught_up = (events.len() as u64) < BATCH_SIZE;
+                Ok((
+                    events
+                        .into_iter()
+                        .map(serde_json::to_value)
+                        .collect::<Result<_, _>>()?,
+                    caught_up,
+                ))
+            }
+            Self::LedgerInfo { known_version } => {
+                if *known_version >= ledger_version {
+                    return Ok((vec![], true));
+                }
+                let (epoch_change_proof, consistency_proof) =
+                    db.get_state_proof_with_ledger_info(*known_version, ledger_info.clone())?;
+                let state_proof =
+                    StateProofView::try_from((ledger_info, epoch_change_proof, consistency_proof))?;
+                *known_version = ledger_version;
+                Ok((vec![serde_json::to_value(state_proof)?], true))
+            }
+        }
+    }
+}
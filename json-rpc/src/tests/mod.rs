@@ -1,6 +1,8 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(test)]
+mod subscriptions_tests;
 #[cfg(test)]
 mod unit_tests;
 mod utils;
@@ -0,0 +1,432 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    access_control::AccessControl,
+    errors::ServerCode,
+    subscriptions::{websocket_route, SubscriptionService},
+};
+use libra_config::config::{RateLimitConfig, RpcConfig, TokenBucketConfig};
+use libra_crypto::hash::CryptoHash;
+use libra_json_rpc_client::views::{EventView, StateProofView, TransactionView};
+use libra_proptest_helpers::ValueGenerator;
+use libra_temppath::TempPath;
+use libra_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{TransactionToCommit, Version},
+};
+use libradb::{test_helper::arb_blocks_to_commit, LibraDB};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+use storage_interface::DbWriter;
+use tokio::{runtime::Runtime, time::timeout};
+use warp::test::WsClient;
+
+type Blocks = Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>;
+
+// At least two blocks, with events.
+fn arb_blocks() -> Blocks {
+    let mut gen = ValueGenerator::new();
+    loop {
+        let blocks = gen.generate(arb_blocks_to_commit());
+        if blocks.len() >= 2
+            && blocks
+                .iter()
+                .flat_map(|(txns, _)| txns)
+                .any(|txn| !txn.events().is_empty())
+        {
+            return blocks;
+        }
+    }
+}
+
+struct TestEnv {
+    _tmp_dir: TempPath,
+    db: Arc<LibraDB>,
+    blocks: Blocks,
+    num_committed_blocks: usize,
+}
+
+impl TestEnv {
+    fn new() -> Self {
+        let tmp_dir = TempPath::new();
+        let db = Arc::new(LibraDB::new_for_test(&tmp_dir));
+        Self {
+            _tmp_dir: tmp_dir,
+            db,
+            blocks: arb_blocks(),
+            num_committed_blocks: 0,
+        }
+    }
+
+    fn service(&self, config: &RpcConfig) -> SubscriptionService {
+        SubscriptionService::new(
+            config,
+            self.db.clone(),
+            Arc::new(AccessControl::new(config)),
+            self.db.subscribe_commits(),
+        )
+    }
+
+    fn next_version(&self) -> Version {
+        self.blocks[..self.num_committed_blocks]
+            .iter()
+            .map(|(txns, _)| txns.len() as u64)
+            .sum()
+    }
+
+    fn commit_next_block(&mut self) {
+        let first_version = self.next_version();
+        let (txns, ledger_info) = &self.blocks[self.num_committed_blocks];
+        self.db
+            .save_transactions(txns, first_version, Some(ledger_info))
+            .unwrap();
+        self.num_committed_blocks += 1;
+    }
+
+    fn committed_txns(&self) -> Vec<&TransactionToCommit> {
+        self.blocks[..self.num_committed_blocks]
+            .iter()
+            .flat_map(|(txns, _)| txns)
+            .collect()
+    }
+
+    // Events of the committed transactions with the key of the first one.
+    fn committed_events(&self) -> Vec<ContractEvent> {
+        let events: Vec<_> = self
+            .committed_txns()
+            .into_iter()
+            .flat_map(|txn| txn.events().to_vec())
+            .collect();
+        let key = *self.first_event().key();
+        events.into_iter().filter(|e| *e.key() == key).collect()
+    }
+
+    fn first_event(&self) -> ContractEvent {
+        self.blocks
+            .iter()
+            .flat_map(|(txns, _)| txns)
+            .flat_map(|txn| txn.events())
+            .next()
+            .unwrap()
+            .clone()
+    }
+}
+
+async fn connect(service: SubscriptionService) -> WsClient {
+    warp::test::ws()
+        .path("/v1/ws")
+        .handshake(websocket_route(service))
+        .await
+        .unwrap()
+}
+
+async fn send_request(client: &mut WsClient, id: u64, method: &str, params: Value) {
+    client
+        .send_text(
+            json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}).to_string(),
+        )
+        .await;
+}
+
+/// Collects responses (by request ID) and notifications (by subscription ID), which can come in
+/// any order.
+#[derive(Default)]
+struct Received {
+    responses: HashMap<u64, Value>,
+    notifications: HashMap<u64, Vec<Value>>,
+}
+
+impl Received {
+    async fn recv(&mut self, client: &mut WsClient) {
+        let msg = timeout(Duration::from_secs(10), client.recv())
+            .await
+            .expect("timed out")
+            .unwrap();
+        let value: Value = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+        if let Some(id) = value["id"].as_u64() {
+            self.responses.insert(id, value);
+        } else {
+            let params = &value["params"];
+            self.notifications
+                .entry(params["subscription"].as_u64().unwrap())
+                .or_default()
+                .push(params["result"].clone());
+        }
+    }
+
+    async fn recv_response(&mut self, client: &mut WsClient, id: u64) -> Value {
+        while !self.responses.contains_key(&id) {
+            self.recv(client).await;
+        }
+        self.responses.remove(&id).unwrap()
+    }
+
+    async fn recv_notifications(
+        &mut self,
+        client: &mut WsClient,
+        subscription: u64,
+        num: usize,
+    ) -> Vec<Value> {
+        while self.notifications.get(&subscription).map_or(0, Vec::len) < num {
+            self.recv(client).await;
+        }
+        self.notifications.remove(&subscription).unwrap()
+    }
+}
+
+#[test]
+fn test_subscribe_to_transactions_and_events() {
+    let mut env = TestEnv::new();
+    env.commit_next_block();
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = connect(env.service(&RpcConfig::default())).await;
+        let mut received = Received::default();
+
+        send_request(
+            &mut client,
+            1,
+            "subscribe_to_transactions",
+            json!([0, true]),
+        )
+        .await;
+        let txns_subscription = received.recv_response(&mut client, 1).await["result"]
+            .as_u64()
+            .unwrap();
+        let event_key = hex::encode(env.first_event().key().as_bytes());
+        send_request(&mut client, 2, "subscribe_to_events", json!([event_key, 0])).await;
+        let events_subscription = received.recv_response(&mut client, 2).await["result"]
+            .as_u64()
+            .unwrap();
+        assert_ne!(txns_subscription, events_subscription);
+
+        // The transactions already in the DB are pushed, then the ones committed afterwards.
+        let mut num_pushed_txns = 0;
+        let mut num_pushed_events = 0;
+        loop {
+            let expected_txns = env.committed_txns();
+            let txns = received
+                .recv_notifications(
+                    &mut client,
+                    txns_subscription,
+                    expected_txns.len() - num_pushed_txns,
+                )
+                .await;
+            for (txn, expected) in txns.into_iter().zip(&expected_txns[num_pushed_txns..]) {
+                let txn: TransactionView = serde_json::from_value(txn).unwrap();
+                assert_eq!(txn.version, num_pushed_txns as u64);
+                assert_eq!(txn.hash, expected.transaction().hash().to_hex());
+                assert_eq!(txn.events.len(), expected.events().len());
+                num_pushed_txns += 1;
+            }
+
+            let expected_events = env.committed_events();
+            if expected_events.len() > num_pushed_events {
+                let events = received
+                    .recv_notifications(
+                        &mut client,
+                        events_subscription,
+                        expected_events.len() - num_pushed_events,
+                    )
+                    .await;
+                for (event, expected) in events
+                    .into_iter()
+                    .zip(&expected_events[num_pushed_events..])
+                {
+                    let event: EventView = serde_json::from_value(event).unwrap();
+                    assert_eq!(event.sequence_number, expected.sequence_number());
+                    num_pushed_events += 1;
+                }
+            }
+
+            if env.num_committed_blocks == env.blocks.len() {
+                break;
+            }
+            env.commit_next_block();
+        }
+        assert_eq!(num_pushed_events, env.committed_events().len());
+
+        // Nothing more is pushed after unsubscribing.
+        send_request(&mut client, 3, "unsubscribe", json!([txns_subscription])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 3).await["result"],
+            json!(true)
+        );
+        send_request(&mut client, 4, "unsubscribe", json!([txns_subscription])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 4).await["result"],
+            json!(false)
+        );
+        assert!(received.notifications.is_empty());
+    });
+}
+
+#[test]
+fn test_subscribe_to_ledger_info() {
+    let mut env = TestEnv::new();
+    env.commit_next_block();
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = connect(env.service(&RpcConfig::default())).await;
+        let mut received = Received::default();
+
+        send_request(&mut client, 1, "subscribe_to_ledger_info", json!([0])).await;
+        let subscription = received.recv_response(&mut client, 1).await["result"]
+            .as_u64()
+            .unwrap();
+        let state_proofs = received
+            .recv_notifications(&mut client, subscription, 1)
+            .await;
+        let state_proof: StateProofView = serde_json::from_value(state_proofs[0].clone()).unwrap();
+        let ledger_info: LedgerInfoWithSignatures = lcs::from_bytes(
+            &state_proof
+                .ledger_info_with_signatures
+                .into_bytes()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ledger_info, env.blocks[0].1);
+
+        env.commit_next_block();
+        let state_proofs = received
+            .recv_notifications(&mut client, subscription, 1)
+            .await;
+        let state_proof: StateProofView = serde_json::from_value(state_proofs[0].clone()).unwrap();
+        let ledger_info: LedgerInfoWithSignatures = lcs::from_bytes(
+            &state_proof
+                .ledger_info_with_signatures
+                .into_bytes()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(ledger_info, env.blocks[1].1);
+    });
+}
+
+#[test]
+fn test_invalid_requests_and_limits() {
+    let mut env = TestEnv::new();
+    env.commit_next_block();
+    let config = RpcConfig {
+        max_subscriptions_per_connection: 1,
+        max_websocket_connections: 1,
+        ..RpcConfig::default()
+    };
+    let service = env.service(&config);
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let mut client = connect(service.clone()).await;
+        let mut received = Received::default();
+
+        client.send_text("not json").await;
+        let msg = client.recv().await.unwrap();
+        let response: Value = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], json!(-32600));
+
+        send_request(&mut client, 1, "subscribe_to_nothing", json!([])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 1).await["error"]["code"],
+            json!(-32601)
+        );
+        send_request(&mut client, 2, "subscribe_to_transactions", json!([0])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 2).await["error"]["code"],
+            json!(-32602)
+        );
+        send_request(&mut client, 3, "subscribe_to_events", json!(["zz", 0])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 3).await["error"]["code"],
+            json!(-32602)
+        );
+
+        send_request(&mut client, 4, "subscribe_to_ledger_info", json!([0])).await;
+        assert!(received.recv_response(&mut client, 4).await["result"].is_u64());
+        send_request(&mut client, 5, "subscribe_to_ledger_info", json!([0])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 5).await["error"]["code"],
+            json!(ServerCode::DefaultServerError as i16)
+        );
+
+        // Only one connection at a time.
+        assert!(warp::test::ws()
+            .path("/v1/ws")
+            .handshake(websocket_route(service.clone()))
+            .await
+            .is_err());
+        drop(client);
+        // The first connection is closed asynchronously.
+        let mut connected = false;
+        for _ in 0..100 {
+            if warp::test::ws()
+                .path("/v1/ws")
+                .handshake(websocket_route(service.clone()))
+                .await
+                .is_ok()
+            {
+                connected = true;
+                break;
+            }
+            tokio::time::delay_for(Duration::from_millis(10)).await;
+        }
+        assert!(connected);
+    });
+}
+
+#[test]
+fn test_auth_and_rate_limit() {
+    let mut env = TestEnv::new();
+    env.commit_next_block();
+    let mut methods = BTreeMap::new();
+    methods.insert(
+        "subscribe_to_ledger_info".to_string(),
+        TokenBucketConfig {
+            capacity: 1,
+            refill_rate: 0,
+        },
+    );
+    let config = RpcConfig {
+        auth_tokens: vec!["token1".to_string()],
+        rate_limit: RateLimitConfig {
+            default: None,
+            methods,
+        },
+        ..RpcConfig::default()
+    };
+    let service = env.service(&config);
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        assert!(warp::test::ws()
+            .path("/v1/ws")
+            .handshake(websocket_route(service.clone()))
+            .await
+            .is_err());
+        let mut client = warp::test::ws()
+            .path("/v1/ws")
+            .header("authorization", "Bearer token1")
+            .handshake(websocket_route(service))
+            .await
+            .unwrap();
+        let mut received = Received::default();
+
+        send_request(&mut client, 1, "subscribe_to_ledger_info", json!([0])).await;
+        let subscription = received.recv_response(&mut client, 1).await["result"].clone();
+        assert!(subscription.is_u64());
+        send_request(&mut client, 2, "subscribe_to_ledger_info", json!([0])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 2).await["error"]["code"],
+            json!(ServerCode::RateLimited as i16)
+        );
+        // Other methods aren't limited.
+        send_request(&mut client, 3, "unsubscribe", json!([subscription])).await;
+        assert_eq!(
+            received.recv_response(&mut client, 3).await["result"],
+            json!(true)
+        );
+    });
+}
@@ -2,16 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    access_control::AccessControl,
     errors::{JsonRpcError, ServerCode},
     tests::utils::{test_bootstrap, MockLibraDB},
 };
+use executor_test_helpers::integration_test_impl::create_db_and_executor;
 use futures::{channel::mpsc::channel, StreamExt};
-use libra_config::utils;
+use libra_config::{
+    config::{RateLimitConfig, RoleType, RpcConfig, TokenBucketConfig},
+    utils,
+};
 use libra_crypto::{ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, Uniform};
 use libra_json_rpc_client::{
     views::{
-        AccountStateWithProofView, BlockMetadata, BytesView, EventView, StateProofView,
-        TransactionDataView, TransactionView, VMStatusView,
+        AccountStateWithProofView, BlockMetadata, BytesView, EventView, MoveModuleView,
+        MoveResourceView, StateProofView, TransactionDataView, TransactionListWithProofView,
+        TransactionSimulationView, TransactionView, VMStatusView,
     },
     JsonRpcAsyncClient, JsonRpcBatch, JsonRpcResponse, ResponseAsView,
 };
@@ -19,9 +25,11 @@ use libra_json_rpc_types::views::{
     JSONRPC_LIBRA_LEDGER_TIMESTAMPUSECS, JSONRPC_LIBRA_LEDGER_VERSION,
 };
 use libra_proptest_helpers::ValueGenerator;
+use libra_state_view::StateViewId;
 use libra_types::{
     account_address::AccountAddress,
-    account_config::AccountResource,
+    account_config::{coin1_tag, libra_root_address, AccountResource},
+    account_state::AccountState,
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::ContractEvent,
     event::EventKey,
@@ -29,20 +37,26 @@ use libra_types::{
     mempool_status::{MempoolStatus, MempoolStatusCode},
     proof::{SparseMerkleProof, TransactionAccumulatorProof, TransactionInfoWithProof},
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::{Transaction, TransactionInfo, TransactionPayload},
-    vm_status::StatusCode,
+    transaction::{
+        authenticator::AuthenticationKey, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionPayload, TransactionStatus,
+    },
+    vm_status::{KeptVMStatus, StatusCode},
 };
+use libra_vm::{LibraVM, VMExecutor};
 use libradb::test_helper::arb_blocks_to_commit;
 use move_core_types::language_storage::TypeTag;
 use proptest::prelude::*;
+use scratchpad::SparseMerkleTree;
 use std::{
     collections::{BTreeMap, HashMap},
     convert::TryFrom,
     str::FromStr,
     sync::Arc,
 };
-use storage_interface::DbReader;
-use tokio::runtime::Runtime;
+use storage_interface::{state_view::VerifiedStateView, DbReader};
+use tokio::{runtime::Runtime, sync::watch};
+use transaction_builder::encode_create_testing_account_script;
 use vm_validator::{
     mocks::mock_vm_validator::MockVMValidator, vm_validator::TransactionValidation,
 };
@@ -191,6 +205,134 @@ fn test_json_rpc_protocol() {
     assert!(data.get(JSONRPC_LIBRA_LEDGER_TIMESTAMPUSECS).is_some());
 }
 
+#[test]
+fn test_auth_and_rate_limit() {
+    let address = format!("127.0.0.1:{}", utils::get_available_port());
+    let mut methods = BTreeMap::new();
+    methods.insert(
+        "get_network_status".to_string(),
+        TokenBucketConfig {
+            capacity: 2,
+            refill_rate: 0,
+        },
+    );
+    let config = RpcConfig {
+        address: address.parse().unwrap(),
+        auth_tokens: vec!["token1".to_string(), "token2".to_string()],
+        rate_limit: RateLimitConfig {
+            default: None,
+            methods,
+        },
+        ..RpcConfig::default()
+    };
+    let (_, commit_notifications) = watch::channel(0);
+    let _runtime = crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1024).0,
+        RoleType::Validator,
+        commit_notifications,
+    );
+    let client = reqwest::blocking::Client::new();
+    let url = format!("http://{}/v1", address);
+    let request = |method: &str, params: serde_json::Value| {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1,
+        })
+    };
+
+    // requests without a valid token are rejected
+    let resp = client
+        .post(&url)
+        .json(&request("get_network_status", serde_json::json!([])))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+    let resp = client
+        .post(&url)
+        .bearer_auth("token3")
+        .json(&request("get_network_status", serde_json::json!([])))
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 401);
+
+    // each request of a batch is limited, other methods aren't
+    let batch = serde_json::json!([
+        request("get_network_status", serde_json::json!([])),
+        request("get_network_status", serde_json::json!([])),
+        request("get_network_status", serde_json::json!([])),
+        request("get_metadata", serde_json::json!([null])),
+    ]);
+    let resp = client
+        .post(&url)
+        .bearer_auth("token1")
+        .json(&batch)
+        .send()
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let responses: Vec<JsonMap> = resp.json().unwrap();
+    assert!(responses[0].get("result").is_some());
+    assert!(responses[1].get("result").is_some());
+    assert_eq!(
+        responses[2]["error"]["code"],
+        serde_json::json!(ServerCode::RateLimited as i16)
+    );
+    assert!(responses[3].get("result").is_some());
+
+    // clients have their own buckets
+    let resp = client
+        .post(&url)
+        .bearer_auth("token2")
+        .json(&request("get_network_status", serde_json::json!([])))
+        .send()
+        .unwrap();
+    let data: JsonMap = resp.json().unwrap();
+    assert!(data.get("result").is_some());
+}
+
+#[test]
+#[should_panic(expected = "tls_cert_path and tls_key_path must be set together")]
+fn test_partial_tls_config_rejected() {
+    let config = RpcConfig {
+        address: format!("127.0.0.1:{}", utils::get_available_port())
+            .parse()
+            .unwrap(),
+        tls_cert_path: Some("cert.pem".into()),
+        ..RpcConfig::default()
+    };
+    let (_, commit_notifications) = watch::channel(0);
+    crate::bootstrap(
+        &config,
+        Arc::new(mock_db()),
+        channel(1024).0,
+        RoleType::Validator,
+        commit_notifications,
+    );
+}
+
+#[test]
+fn test_rate_limit_buckets_capped() {
+    let config = RpcConfig {
+        rate_limit: RateLimitConfig {
+            default: Some(TokenBucketConfig {
+                capacity: 1,
+                refill_rate: 0,
+            }),
+            methods: BTreeMap::new(),
+        },
+        ..RpcConfig::default()
+    };
+    let access_control = AccessControl::with_max_buckets(&config, 2);
+    assert!(access_control.try_acquire("client1", "get_metadata"));
+    assert!(access_control.try_acquire("client2", "get_metadata"));
+    // no bucket is full, so no new client can be tracked
+    assert!(!access_control.try_acquire("client3", "get_metadata"));
+    assert!(!access_control.try_acquire("client1", "get_metadata"));
+}
+
 #[test]
 fn test_transaction_submission() {
     let (mp_sender, mut mp_events) = channel(1);
@@ -248,6 +390,135 @@ fn test_transaction_submission() {
     }
 }
 
+#[test]
+fn test_simulate_transaction_with_invalid_signature() {
+    let (_mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    // sign with a key that doesn't match the transaction's public key
+    let privkey = Ed25519PrivateKey::generate_for_testing();
+    let other_privkey = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+    let sender = AccountAddress::new([9; AccountAddress::LENGTH]);
+    let txn = get_test_signed_txn(sender, 0, &other_privkey, privkey.public_key(), None);
+    let mut batch = JsonRpcBatch::default();
+    batch.add_simulate_transaction_request(txn, true).unwrap();
+    let response = runtime.block_on(client.execute(batch)).unwrap().remove(0);
+
+    let error = response
+        .unwrap_err()
+        .downcast::<JsonRpcError>()
+        .expect("unexpected error format");
+    assert_eq!(error.code, ServerCode::VmValidationError as i16);
+    let status_code: StatusCode = serde_json::from_value(error.data.unwrap()).unwrap();
+    assert_eq!(status_code, StatusCode::INVALID_SIGNATURE);
+}
+
+#[test]
+fn test_simulate_transaction() {
+    // Simulation needs the account configs published in genesis, which the mock DB lacks.
+    let (config, root_key) = config_builder::test_config();
+    let (db, _, _) = create_db_and_executor(&config);
+    let port = utils::get_available_port();
+    let mut runtime = test_bootstrap(
+        format!("0.0.0.0:{}", port).parse().unwrap(),
+        Arc::clone(&db) as Arc<dyn DbReader>,
+        channel(1).0,
+    );
+    let client = JsonRpcAsyncClient::new(
+        reqwest::Url::from_str(format!("http://127.0.0.1:{}/v1", port).as_str())
+            .expect("invalid url"),
+    );
+    let mut simulate = |txn, check_signature| {
+        let mut batch = JsonRpcBatch::default();
+        batch
+            .add_simulate_transaction_request(txn, check_signature)
+            .unwrap();
+        runtime.block_on(client.execute(batch)).unwrap().remove(0)
+    };
+
+    let root = libra_root_address();
+    let sequence_number =
+        AccountState::try_from(&db.get_latest_account_state(root).unwrap().unwrap())
+            .unwrap()
+            .get_account_resource()
+            .unwrap()
+            .unwrap()
+            .sequence_number();
+    let auth_key =
+        AuthenticationKey::ed25519(&Ed25519PrivateKey::generate_for_testing().public_key());
+    let script = encode_create_testing_account_script(
+        coin1_tag(),
+        auth_key.derived_address(),
+        auth_key.prefix().to_vec(),
+        false, /* add all currencies */
+    );
+    let txn = get_test_signed_txn(
+        root,
+        sequence_number,
+        &root_key,
+        root_key.public_key(),
+        Some(script.clone()),
+    );
+
+    // The output of the same transaction executed by the VM on top of the latest state.
+    let (version, state_root) = db.get_latest_state_root().unwrap();
+    let smt = SparseMerkleTree::new(state_root);
+    let state_view = VerifiedStateView::new(
+        StateViewId::Miscellaneous,
+        Arc::clone(&db) as Arc<dyn DbReader>,
+        Some(version),
+        state_root,
+        &smt,
+    );
+    let output =
+        LibraVM::execute_block(vec![Transaction::UserTransaction(txn.clone())], &state_view)
+            .unwrap()
+            .remove(0);
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(KeptVMStatus::Executed)
+    );
+    let expected_events: Vec<EventView> = output
+        .events()
+        .iter()
+        .cloned()
+        .map(|event| (version + 1, event).into())
+        .collect();
+
+    let simulation =
+        TransactionSimulationView::from_response(simulate(txn, true).unwrap()).unwrap();
+    assert_eq!(simulation.version, version + 1);
+    assert_eq!(simulation.vm_status, VMStatusView::Executed);
+    assert!(simulation.gas_used > 0);
+    assert_eq!(simulation.gas_used, output.gas_used());
+    assert_eq!(simulation.events, expected_events);
+
+    // Signed by another key: rejected if the signature is checked, executed the same otherwise.
+    let other_key = Ed25519PrivateKey::try_from(&[7u8; 32][..]).unwrap();
+    let unsigned_txn = get_test_signed_txn(
+        root,
+        sequence_number,
+        &other_key,
+        root_key.public_key(),
+        Some(script),
+    );
+    let error = simulate(unsigned_txn.clone(), true)
+        .unwrap_err()
+        .downcast::<JsonRpcError>()
+        .expect("unexpected error format");
+    assert_eq!(error.code, ServerCode::VmValidationError as i16);
+    let status_code: StatusCode = serde_json::from_value(error.data.unwrap()).unwrap();
+    assert_eq!(status_code, StatusCode::INVALID_SIGNATURE);
+
+    let simulation =
+        TransactionSimulationView::from_response(simulate(unsigned_txn, false).unwrap()).unwrap();
+    assert_eq!(simulation.vm_status, VMStatusView::Executed);
+    assert_eq!(simulation.gas_used, output.gas_used());
+    assert_eq!(simulation.events, expected_events);
+
+    // Nothing is committed.
+    assert_eq!(db.get_latest_version().unwrap(), version);
+}
+
 // TODO: Once account configs are published in the mock DB this test can be turned back on
 //#[test]
 //fn test_get_account() {
@@ -455,6 +726,25 @@ fn test_get_transactions() {
     }
 }
 
+#[test]
+fn test_get_transactions_with_proofs() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let version = mock_db.get_latest_version().unwrap();
+    for include_events in &[false, true] {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_transactions_with_proofs_request(0, 10, *include_events);
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        let view = TransactionListWithProofView::from_response(result).unwrap();
+        assert_eq!(
+            TransactionListWithProof::try_from(view).unwrap(),
+            mock_db
+                .get_transactions(0, 10, version, *include_events)
+                .unwrap()
+        );
+    }
+}
+
 #[test]
 fn test_get_account_transaction() {
     let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
@@ -531,6 +821,110 @@ fn test_get_account_transaction() {
     }
 }
 
+#[test]
+fn test_get_transaction_by_hash() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    for (version, (txn, status)) in mock_db.all_txns.iter().enumerate().take(10) {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_transaction_by_hash_request(txn.hash().to_hex(), true);
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        let tx_view = TransactionView::optional_from_response(result)
+            .unwrap()
+            .expect("Transaction didn't exists!");
+
+        assert_eq!(tx_view.version, version as u64);
+        assert_eq!(tx_view.hash, txn.hash().to_hex());
+        assert_eq!(tx_view.vm_status, VMStatusView::from(status));
+        let num_expected_events = mock_db
+            .events
+            .iter()
+            .filter(|(v, _)| *v == version as u64)
+            .count();
+        assert_eq!(tx_view.events.len(), num_expected_events);
+    }
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_transaction_by_hash_request(HashValue::zero().to_hex(), true);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert!(TransactionView::optional_from_response(result)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_get_resources() {
+    let (mock_db, client, mut runtime) = create_database_client_and_runtime(1);
+
+    let account = get_first_account_from_mock_db(&mock_db);
+    let account_state = AccountState::try_from(&mock_db.all_accounts[&account]).unwrap();
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(account, None);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    let resources = MoveResourceView::vec_from_response(result).unwrap();
+    assert_eq!(resources.len(), account_state.iter().count());
+
+    for (path, blob) in account_state.iter() {
+        let resource = resources
+            .iter()
+            .find(|resource| resource.path == BytesView::from(path))
+            .expect("missing resource");
+        assert_eq!(resource.raw, BytesView::from(blob));
+
+        let mut batch = JsonRpcBatch::default();
+        batch.add_get_resource_request(account, hex::encode(path), Some(mock_db.version));
+        let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+        let view = MoveResourceView::optional_from_response(result)
+            .unwrap()
+            .expect("missing resource");
+        assert_eq!(view, *resource);
+    }
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_resource_request(account, hex::encode(b"missing"), None);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert!(MoveResourceView::optional_from_response(result)
+        .unwrap()
+        .is_none());
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_module_request(account, "Missing".to_string(), None);
+    let result = execute_batch_and_get_first_response(&client, &mut runtime, batch);
+    assert!(MoveModuleView::optional_from_response(result)
+        .unwrap()
+        .is_none());
+
+    let mut batch = JsonRpcBatch::default();
+    batch.add_get_account_resources_request(account, Some(mock_db.version + 1));
+    assert!(runtime
+        .block_on(client.execute(batch))
+        .unwrap()
+        .remove(0)
+        .is_err());
+
+    // Malformed versions are rejected rather than taken for the latest one.
+    for version in &[
+        serde_json::json!("1"),
+        serde_json::json!(-1),
+        serde_json::json!(1.5),
+    ] {
+        let mut batch = JsonRpcBatch::default();
+        batch.add_request(
+            "get_account_resources".to_string(),
+            vec![serde_json::json!(account.to_string()), version.clone()],
+        );
+        let error = runtime
+            .block_on(client.execute(batch))
+            .unwrap()
+            .remove(0)
+            .unwrap_err()
+            .downcast::<JsonRpcError>()
+            .expect("unexpected error format");
+        assert_eq!(error.code, JsonRpcError::invalid_params().code);
+    }
+}
+
 #[test]
 // Check that if version and ledger_version parameters are None, then the server returns the latest
 // known state.
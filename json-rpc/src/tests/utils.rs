@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{Error, Result};
-use libra_config::config::RoleType;
-use libra_crypto::HashValue;
+use libra_config::config::{RoleType, RpcConfig};
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_mempool::MempoolClientSender;
 use libra_types::{
     account_address::AccountAddress,
@@ -24,16 +24,27 @@ use libra_types::{
 };
 use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
 use storage_interface::{DbReader, StartupInfo, TreeState};
-use tokio::runtime::Runtime;
+use tokio::{runtime::Runtime, sync::watch};
 
 /// Creates JSON RPC server for a Validator node
 /// Should only be used for unit-tests
+/// The DB is not expected to change: subscriptions only see what's in it when they are made.
 pub fn test_bootstrap(
     address: SocketAddr,
     libra_db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
 ) -> Runtime {
-    crate::bootstrap(address, libra_db, mp_sender, RoleType::Validator)
+    let (_, commit_notifications) = watch::channel(0);
+    crate::bootstrap(
+        &RpcConfig {
+            address,
+            ..RpcConfig::default()
+        },
+        libra_db,
+        mp_sender,
+        RoleType::Validator,
+        commit_notifications,
+    )
 }
 
 /// Lightweight mock of LibraDB
@@ -47,6 +58,38 @@ pub(crate) struct MockLibraDB {
     pub timestamps: Vec<u64>,
 }
 
+impl MockLibraDB {
+    fn txn_with_proof(&self, version: usize, fetch_events: bool) -> TransactionWithProof {
+        let (txn, status) = &self.all_txns[version];
+        TransactionWithProof {
+            version: version as u64,
+            transaction: txn.clone(),
+            events: if fetch_events {
+                Some(
+                    self.events
+                        .iter()
+                        .filter(|(ev, _)| *ev == version as u64)
+                        .map(|(_, e)| e)
+                        .cloned()
+                        .collect(),
+                )
+            } else {
+                None
+            },
+            proof: TransactionInfoWithProof::new(
+                TransactionAccumulatorProof::new(vec![]),
+                TransactionInfo::new(
+                    Default::default(),
+                    Default::default(),
+                    Default::default(),
+                    0,
+                    status.clone(),
+                ),
+            ),
+        }
+    }
+}
+
 impl DbReader for MockLibraDB {
     fn get_latest_account_state(
         &self,
@@ -87,40 +130,27 @@ impl DbReader for MockLibraDB {
         Ok(self
             .all_txns
             .iter()
-            .enumerate()
-            .find(|(_, (x, _))| {
+            .position(|(x, _)| {
                 if let Ok(t) = x.as_signed_user_txn() {
                     t.sender() == address && t.sequence_number() == seq_num
                 } else {
                     false
                 }
             })
-            .map(|(v, (x, status))| TransactionWithProof {
-                version: v as u64,
-                transaction: x.clone(),
-                events: if fetch_events {
-                    Some(
-                        self.events
-                            .iter()
-                            .filter(|(ev, _)| *ev == v as u64)
-                            .map(|(_, e)| e)
-                            .cloned()
-                            .collect(),
-                    )
-                } else {
-                    None
-                },
-                proof: TransactionInfoWithProof::new(
-                    TransactionAccumulatorProof::new(vec![]),
-                    TransactionInfo::new(
-                        Default::default(),
-                        Default::default(),
-                        Default::default(),
-                        0,
-                        status.clone(),
-                    ),
-                ),
-            }))
+            .map(|v| self.txn_with_proof(v, fetch_events)))
+    }
+
+    fn get_txn_by_hash(
+        &self,
+        hash: HashValue,
+        _ledger_version: u64,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>, Error> {
+        Ok(self
+            .all_txns
+            .iter()
+            .position(|(x, _)| x.hash() == hash)
+            .map(|v| self.txn_with_proof(v, fetch_events)))
     }
 
     fn get_transactions(
@@ -245,7 +275,7 @@ impl DbReader for MockLibraDB {
     }
 
     fn get_latest_state_root(&self) -> Result<(u64, HashValue)> {
-        unimplemented!()
+        Ok((self.version, HashValue::zero()))
     }
 
     fn get_latest_tree_state(&self) -> Result<TreeState> {
@@ -267,4 +297,8 @@ impl DbReader for MockLibraDB {
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         Ok(self.timestamps[version as usize])
     }
+
+    fn get_first_txn_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
 }
@@ -29,6 +29,13 @@ pub enum ServerCode {
     MempoolInvalidUpdate = -32010,
     MempoolVmError = -32011,
     MempoolUnknownError = -32012,
+
+    // Rate limit of the client exceeded
+    RateLimited = -32013,
+
+    // Mempool errors, continued
+    MempoolGasPriceBumpTooLow = -32014,
+    MempoolGasPriceTooLow = -32015,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -83,6 +90,14 @@ impl JsonRpcError {
         }
     }
 
+    pub fn rate_limited() -> Self {
+        Self {
+            code: ServerCode::RateLimited as i16,
+            message: "Server error: rate limit exceeded".to_string(),
+            data: None,
+        }
+    }
+
     pub fn mempool_error(error: MempoolStatus) -> Result<Self> {
         let code = match error.code {
             MempoolStatusCode::InvalidSeqNumber => ServerCode::MempoolInvalidSeqNumber,
@@ -91,6 +106,8 @@ impl JsonRpcError {
             MempoolStatusCode::InvalidUpdate => ServerCode::MempoolInvalidUpdate,
             MempoolStatusCode::VmError => ServerCode::MempoolVmError,
             MempoolStatusCode::UnknownStatus => ServerCode::MempoolUnknownError,
+            MempoolStatusCode::GasPriceBumpTooLow => ServerCode::MempoolGasPriceBumpTooLow,
+            MempoolStatusCode::GasPriceTooLowForFullMempool => ServerCode::MempoolGasPriceTooLow,
             MempoolStatusCode::Accepted => {
                 return Err(anyhow::format_err!(
                     "[JSON RPC] cannot create mempool error for mempool accepted status"
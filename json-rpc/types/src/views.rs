@@ -4,6 +4,7 @@
 use anyhow::{format_err, Error, Result};
 use libra_crypto::HashValue;
 use libra_types::{
+    access_path::AccessPath,
     account_config::{
         AccountResource, AccountRole, BalanceResource, BurnEvent, CancelBurnEvent,
         CurrencyInfoResource, FreezingBit, MintEvent, NewBlockEvent, NewEpochEvent, PreburnEvent,
@@ -13,9 +14,10 @@ use libra_types::{
     contract_event::ContractEvent,
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    proof::{AccountStateProof, AccumulatorConsistencyProof},
-    transaction::{Transaction, TransactionArgument, TransactionPayload},
+    proof::{AccountStateProof, AccumulatorConsistencyProof, TransactionInfoWithProof},
+    transaction::{Transaction, TransactionArgument, TransactionListWithProof, TransactionPayload},
     vm_status::KeptVMStatus,
+    write_set::WriteOp,
 };
 use move_core_types::{
     identifier::Identifier,
@@ -380,6 +382,64 @@ pub struct TransactionView {
     pub gas_used: u64,
 }
 
+/// Output of a simulated transaction, as if it were committed right after the latest version.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct TransactionSimulationView {
+    pub version: u64,
+    pub transaction: TransactionDataView,
+    pub hash: String,
+    pub events: Vec<EventView>,
+    pub vm_status: VMStatusView,
+    pub gas_used: u64,
+    pub write_set: Vec<WriteOpView>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum WriteOpView {
+    #[serde(rename = "value")]
+    Value {
+        address: String,
+        path: BytesView,
+        value: BytesView,
+    },
+    #[serde(rename = "deletion")]
+    Deletion { address: String, path: BytesView },
+}
+
+impl From<&(AccessPath, WriteOp)> for WriteOpView {
+    fn from((access_path, write_op): &(AccessPath, WriteOp)) -> Self {
+        let address = access_path.address.to_string();
+        let path = BytesView::from(&access_path.path);
+        match write_op {
+            WriteOp::Value(value) => WriteOpView::Value {
+                address,
+                path,
+                value: BytesView::from(value),
+            },
+            WriteOp::Deletion => WriteOpView::Deletion { address, path },
+        }
+    }
+}
+
+/// A Move resource published under an account. The type and decoded value are only known if the
+/// resource was looked up by type, or if its type is one of the well-known ones.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveResourceView {
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub path: BytesView,
+    pub value: Option<serde_json::Value>,
+    pub raw: BytesView,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct MoveModuleView {
+    pub address: String,
+    pub name: String,
+    pub bytecode: BytesView,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(tag = "type")]
@@ -647,6 +707,84 @@ impl TryFrom<AccountStateWithProof> for AccountStateWithProofView {
     }
 }
 
+impl TryFrom<AccountStateWithProofView> for AccountStateWithProof {
+    type Error = Error;
+
+    fn try_from(view: AccountStateWithProofView) -> Result<AccountStateWithProof, Error> {
+        let blob = if let Some(account_blob) = view.blob {
+            Some(lcs::from_bytes(&account_blob.into_bytes()?)?)
+        } else {
+            None
+        };
+        Ok(AccountStateWithProof::new(
+            view.version,
+            blob,
+            AccountStateProof::try_from(view.proof)?,
+        ))
+    }
+}
+
+/// A range of transactions, with the proof that they're part of the ledger. Unlike
+/// `TransactionView`, transactions and events are LCS-serialized so that they can be verified.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransactionListWithProofView {
+    pub first_transaction_version: Option<u64>,
+    pub transactions: Vec<BytesView>,
+    /// The events of each transaction, if requested.
+    pub events: Option<Vec<BytesView>>,
+    pub proof: BytesView,
+}
+
+impl TryFrom<TransactionListWithProof> for TransactionListWithProofView {
+    type Error = Error;
+
+    fn try_from(txn_list: TransactionListWithProof) -> Result<TransactionListWithProofView, Error> {
+        Ok(TransactionListWithProofView {
+            first_transaction_version: txn_list.first_transaction_version,
+            transactions: txn_list
+                .transactions
+                .iter()
+                .map(|txn| Ok(BytesView::from(&lcs::to_bytes(txn)?)))
+                .collect::<Result<_>>()?,
+            events: txn_list
+                .events
+                .map(|event_lists| {
+                    event_lists
+                        .iter()
+                        .map(|events| Ok(BytesView::from(&lcs::to_bytes(events)?)))
+                        .collect::<Result<_>>()
+                })
+                .transpose()?,
+            proof: BytesView::from(&lcs::to_bytes(&txn_list.proof)?),
+        })
+    }
+}
+
+impl TryFrom<TransactionListWithProofView> for TransactionListWithProof {
+    type Error = Error;
+
+    fn try_from(view: TransactionListWithProofView) -> Result<TransactionListWithProof, Error> {
+        Ok(TransactionListWithProof {
+            transactions: view
+                .transactions
+                .into_iter()
+                .map(|txn| Ok(lcs::from_bytes(&txn.into_bytes()?)?))
+                .collect::<Result<_>>()?,
+            events: view
+                .events
+                .map(|event_lists| {
+                    event_lists
+                        .into_iter()
+                        .map(|events| Ok(lcs::from_bytes(&events.into_bytes()?)?))
+                        .collect::<Result<_>>()
+                })
+                .transpose()?,
+            first_transaction_version: view.first_transaction_version,
+            proof: lcs::from_bytes(&view.proof.into_bytes()?)?,
+        })
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct AccountStateProofView {
     pub ledger_info_to_transaction_info_proof: BytesView,
@@ -675,3 +813,17 @@ impl TryFrom<AccountStateProof> for AccountStateProofView {
         })
     }
 }
+
+impl TryFrom<AccountStateProofView> for AccountStateProof {
+    type Error = Error;
+
+    fn try_from(view: AccountStateProofView) -> Result<AccountStateProof, Error> {
+        Ok(AccountStateProof::new(
+            TransactionInfoWithProof::new(
+                lcs::from_bytes(&view.ledger_info_to_transaction_info_proof.into_bytes()?)?,
+                lcs::from_bytes(&view.transaction_info.into_bytes()?)?,
+            ),
+            lcs::from_bytes(&view.transaction_info_to_account_proof.into_bytes()?)?,
+        ))
+    }
+}
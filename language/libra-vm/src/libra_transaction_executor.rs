@@ -19,8 +19,9 @@ use libra_types::{
     account_config,
     block_metadata::BlockMetadata,
     transaction::{
-        ChangeSet, Module, Script, SignatureCheckedTransaction, Transaction, TransactionArgument,
-        TransactionOutput, TransactionPayload, TransactionStatus, WriteSetPayload,
+        ChangeSet, Module, Script, SignatureCheckedTransaction, SignedTransaction, Transaction,
+        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
+        WriteSetPayload,
     },
     vm_status::{KeptVMStatus, StatusCode, VMStatus},
     write_set::{WriteSet, WriteSetMut},
@@ -240,6 +241,16 @@ impl LibraVM {
         &mut self,
         remote_cache: &StateViewCache<'_>,
         txn: &SignatureCheckedTransaction,
+    ) -> (VMStatus, TransactionOutput) {
+        self.execute_user_transaction_impl(remote_cache, txn)
+    }
+
+    /// Executes a user transaction whose signature may not have been checked, callers outside of
+    /// simulation must go through `execute_user_transaction`.
+    fn execute_user_transaction_impl(
+        &mut self,
+        remote_cache: &StateViewCache<'_>,
+        txn: &SignedTransaction,
     ) -> (VMStatus, TransactionOutput) {
         macro_rules! unwrap_or_discard {
             ($res: expr) => {
@@ -608,6 +619,32 @@ impl LibraVM {
         let mut vm = LibraVM::new(&state_view_cache);
         vm.execute_block_impl(transactions, &mut state_view_cache)
     }
+
+    /// Executes a single user transaction on top of `state_view` to learn its output, which is
+    /// not meant to be committed. The signature is only verified if `check_signature` is set, so
+    /// that transactions can be simulated before being signed.
+    pub fn simulate_signed_transaction(
+        txn: SignedTransaction,
+        check_signature: bool,
+        state_view: &dyn StateView,
+    ) -> (VMStatus, TransactionOutput) {
+        let txn = if check_signature {
+            match txn.check_signature() {
+                Ok(txn) => txn.into_inner(),
+                Err(_) => {
+                    return discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE))
+                }
+            }
+        } else {
+            txn
+        };
+        if let TransactionPayload::WriteSet(_) = txn.payload() {
+            return discard_error_vm_status(VMStatus::Error(StatusCode::REJECTED_WRITE_SET));
+        }
+        let state_view_cache = StateViewCache::new(state_view);
+        let mut vm = LibraVM::new(&state_view_cache);
+        vm.execute_user_transaction_impl(&state_view_cache, &txn)
+    }
 }
 
 fn preprocess_transaction(txn: Transaction) -> Result<PreprocessedTransaction, VMStatus> {
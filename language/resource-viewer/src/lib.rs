@@ -17,6 +17,7 @@ use move_core_types::{
     language_storage::StructTag,
     value::{MoveStruct, MoveValue},
 };
+use serde::{ser::SerializeMap, Serialize, Serializer};
 use std::{
     collections::btree_map::BTreeMap,
     convert::TryInto,
@@ -41,6 +42,12 @@ pub struct AnnotatedMoveStruct {
     value: Vec<(Identifier, AnnotatedMoveValue)>,
 }
 
+impl AnnotatedMoveStruct {
+    pub fn type_(&self) -> &StructTag {
+        &self.type_
+    }
+}
+
 /// AnnotatedMoveValue is a fully expanded version of on chain move data. This should only be used
 /// for debugging/client purpose right now and just for a better visualization of on chain data. In
 /// the long run, we would like to transform this struct to a Json value so that we can have a cross
@@ -83,6 +90,17 @@ impl<'a> MoveValueAnnotator<'a> {
         self.annotate_struct(&move_struct, &ty)
     }
 
+    /// Unlike `view_access_path`, works for resources of any type, not just the ones in the staged
+    /// type map: modules outside the stdlib are loaded from the state view.
+    pub fn view_resource(&self, tag: &StructTag, blob: &[u8]) -> Result<AnnotatedMoveStruct> {
+        let ty = self.cache.resolve_struct(tag)?;
+        let struct_def = (&ty)
+            .try_into()
+            .map_err(|e: PartialVMError| e.finish(Location::Undefined).into_vm_status())?;
+        let move_struct = MoveStruct::simple_deserialize(blob, &struct_def)?;
+        self.annotate_struct(&move_struct, &ty)
+    }
+
     pub fn view_contract_event(&self, event: &ContractEvent) -> Result<AnnotatedMoveValue> {
         let ty = self.cache.resolve_type(event.type_tag())?;
         let move_ty = (&ty)
@@ -246,6 +264,33 @@ impl Display for AnnotatedAccountStateBlob {
     }
 }
 
+/// Structs serialize to JSON objects of their fields. Bytes and addresses serialize to hex strings,
+/// and u128s, which JSON numbers can't represent, to decimal strings.
+impl Serialize for AnnotatedMoveValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AnnotatedMoveValue::U8(v) => serializer.serialize_u8(*v),
+            AnnotatedMoveValue::U64(v) => serializer.serialize_u64(*v),
+            AnnotatedMoveValue::U128(v) => serializer.serialize_str(&v.to_string()),
+            AnnotatedMoveValue::Bool(b) => serializer.serialize_bool(*b),
+            AnnotatedMoveValue::Address(a) => serializer.serialize_str(&a.to_string()),
+            AnnotatedMoveValue::Vector(v) => v.serialize(serializer),
+            AnnotatedMoveValue::Bytes(v) => serializer.serialize_str(&hex::encode(v)),
+            AnnotatedMoveValue::Struct(s) => s.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for AnnotatedMoveStruct {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.value.len()))?;
+        for (field_name, v) in self.value.iter() {
+            map.serialize_entry(field_name.as_str(), v)?;
+        }
+        map.end()
+    }
+}
+
 #[derive(Default)]
 pub struct NullStateView();
 
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use backup_service::start_backup_service;
-use consensus::{consensus_provider::start_consensus, gen_consensus_reconfig_subscription};
+use consensus::{
+    consensus_provider::{start_consensus, ConsensusDebugHandle},
+    gen_consensus_reconfig_subscription,
+};
 use debug_interface::node_debug_service::NodeDebugService;
 use executor::{db_bootstrapper::bootstrap_db_if_empty, Executor};
 use executor_types::ChunkExecutor;
@@ -12,9 +15,10 @@ use libra_config::{
     network_id::NodeNetworkId,
     utils::get_genesis_txn,
 };
+use libra_indexer::bootstrap as bootstrap_indexer;
 use libra_json_rpc::bootstrap_from_config as bootstrap_rpc;
 use libra_logger::prelude::*;
-use libra_mempool::gen_mempool_reconfig_subscription;
+use libra_mempool::{gen_mempool_reconfig_subscription, MempoolDebugHandle};
 use libra_metrics::metric_server;
 use libra_vm::LibraVM;
 use libradb::LibraDB;
@@ -30,6 +34,7 @@ const INTRA_NODE_CHANNEL_BUFFER_SIZE: usize = 1;
 
 pub struct LibraHandle {
     _rpc: Runtime,
+    _indexer: Option<Runtime>,
     _mempool: Runtime,
     _state_synchronizer: StateSynchronizer,
     _network_runtimes: Vec<Runtime>,
@@ -42,7 +47,11 @@ fn setup_chunk_executor(db: DbReaderWriter) -> Box<dyn ChunkExecutor> {
     Box::new(Executor::<LibraVM>::new(db))
 }
 
-fn setup_debug_interface(config: &NodeConfig) -> NodeDebugService {
+fn setup_debug_interface(
+    config: &NodeConfig,
+    mempool: MempoolDebugHandle,
+    consensus: Option<ConsensusDebugHandle>,
+) -> NodeDebugService {
     let addr = format!(
         "{}:{}",
         config.debug_interface.address, config.debug_interface.admission_control_node_debug_port,
@@ -55,7 +64,7 @@ fn setup_debug_interface(config: &NodeConfig) -> NodeDebugService {
     libra_trace::set_libra_trace(&config.debug_interface.libra_trace.sampling)
         .expect("Failed to set libra trace sampling rate.");
 
-    NodeDebugService::new(addr)
+    NodeDebugService::new(addr, mempool, consensus)
 }
 
 pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
@@ -208,7 +217,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
     let state_synchronizer = StateSynchronizer::bootstrap(
         state_sync_network_handles,
         state_sync_to_mempool_sender,
-        Arc::clone(&db_rw.reader),
+        Arc::clone(&libra_db),
         chunk_executor,
         &node_config,
         waypoint,
@@ -216,13 +225,28 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
     );
     let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
-    let rpc_runtime = bootstrap_rpc(&node_config, libra_db.clone(), mp_client_sender);
+    let rpc_runtime = bootstrap_rpc(
+        &node_config,
+        libra_db.clone(),
+        mp_client_sender,
+        libra_db.subscribe_commits(),
+    );
+    let indexer_runtime = if node_config.indexer.enabled {
+        Some(bootstrap_indexer(
+            &node_config,
+            libra_db.clone(),
+            libra_db.subscribe_commits(),
+        ))
+    } else {
+        None
+    };
 
     let mut consensus_runtime = None;
+    let mut consensus_debug = None;
     let (consensus_to_mempool_sender, consensus_requests) = channel(INTRA_NODE_CHANNEL_BUFFER_SIZE);
 
     instant = Instant::now();
-    let mempool = libra_mempool::bootstrap(
+    let (mempool, mempool_debug) = libra_mempool::bootstrap(
         node_config,
         Arc::clone(&db_rw.reader),
         mempool_network_handles,
@@ -248,7 +272,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
 
         // Initialize and start consensus.
         instant = Instant::now();
-        consensus_runtime = Some(start_consensus(
+        let (runtime, debug_handle) = start_consensus(
             node_config,
             consensus_network_sender,
             consensus_network_events,
@@ -256,11 +280,13 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
             consensus_to_mempool_sender,
             libra_db,
             consensus_reconfig_events,
-        ));
+        );
+        consensus_runtime = Some(runtime);
+        consensus_debug = Some(debug_handle);
         debug!("Consensus started in {} ms", instant.elapsed().as_millis());
     }
 
-    let debug_if = setup_debug_interface(&node_config);
+    let debug_if = setup_debug_interface(&node_config, mempool_debug, consensus_debug);
 
     let metrics_port = node_config.debug_interface.metrics_server_port;
     let metric_host = node_config.debug_interface.address.clone();
@@ -274,6 +300,7 @@ pub fn setup_environment(node_config: &mut NodeConfig) -> LibraHandle {
     LibraHandle {
         _network_runtimes: network_runtimes,
         _rpc: rpc_runtime,
+        _indexer: indexer_runtime,
         _mempool: mempool,
         _state_synchronizer: state_synchronizer,
         _consensus_runtime: consensus_runtime,
@@ -47,7 +47,12 @@ impl PriorityIndex {
         self.data.contains(&self.make_key(txn))
     }
 
-    fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
+    /// returns the transaction with the lowest priority which isn't sent by `excluded_sender`
+    pub(crate) fn lowest(&self, excluded_sender: &AccountAddress) -> Option<&OrderedQueueKey> {
+        self.data.iter().find(|key| key.address != *excluded_sender)
+    }
+
+    pub(crate) fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
             gas_ranking_score: txn.ranking_score,
             expiration_time: txn.expiration_time,
@@ -76,6 +81,15 @@ pub struct OrderedQueueKey {
     pub is_governance_txn: bool,
 }
 
+impl OrderedQueueKey {
+    /// whether a transaction with this key is worth evicting the one with `other` key for
+    /// i.e. it has strictly higher priority, ignoring the tie-breakers of the ordering
+    pub(crate) fn outranks(&self, other: &OrderedQueueKey) -> bool {
+        (self.is_governance_txn, self.gas_ranking_score)
+            > (other.is_governance_txn, other.gas_ranking_score)
+    }
+}
+
 impl PartialOrd for OrderedQueueKey {
     fn partial_cmp(&self, other: &OrderedQueueKey) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -0,0 +1,162 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk journal of the transactions accepted by Mempool, so that they survive a restart of the
+//! node. Entries are written when transactions are inserted into `TransactionStore` and deleted
+//! when they leave it (commit, rejection, expiration, eviction or replacement). The writes of an
+//! operation on `TransactionStore` are batched, and aren't synced to disk: the journal is meant to
+//! survive restarts of the node, a crash of the machine may lose its last writes.
+//! On startup, shared mempool reads the journal and submits the entries again to Mempool, after
+//! revalidating them. Entries which are restored are written again, the other ones are deleted.
+//!
+//! The journal is a schemadb instance with a single column family:
+//! ```text
+//! |<-------key------->|<-------value------->|
+//! | address | seq_num | journal entry bytes |
+//! ```
+
+use crate::core_mempool::transaction::{MempoolTransaction, TimelineState};
+use anyhow::{ensure, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_logger::prelude::*;
+use libra_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName, ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME,
+};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, mem::size_of, path::Path, time::Duration};
+
+const JOURNAL_CF_NAME: ColumnFamilyName = "journal";
+
+define_schema!(JournalSchema, Key, JournalEntry, JOURNAL_CF_NAME);
+
+type Key = (AccountAddress, u64);
+
+/// A transaction accepted by Mempool, as stored in the journal.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JournalEntry {
+    pub txn: SignedTransaction,
+    // system expiration time of transaction, kept across restarts
+    pub expiration_time: Duration,
+    // timeline state upon insertion, updated when the transaction becomes ready: replaying the
+    // entries in the order of their timeline IDs preserves the order of broadcast
+    pub timeline_state: TimelineState,
+}
+
+impl From<&MempoolTransaction> for JournalEntry {
+    fn from(txn: &MempoolTransaction) -> Self {
+        Self {
+            txn: txn.txn.clone(),
+            expiration_time: txn.expiration_time,
+            timeline_state: txn.timeline_state,
+        }
+    }
+}
+
+pub struct MempoolJournal {
+    db: DB,
+    // writes not flushed yet
+    batch: SchemaBatch,
+    num_writes: usize,
+}
+
+impl MempoolJournal {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let column_families = vec![/* UNUSED CF = */ DEFAULT_CF_NAME, JOURNAL_CF_NAME];
+        let db = DB::open(path.as_ref(), "mempool_journal", column_families)
+            .expect("Mempool journal open failed; unable to continue");
+        info!("Opened mempool journal at {:?}", path.as_ref());
+        Self {
+            db,
+            batch: SchemaBatch::new(),
+            num_writes: 0,
+        }
+    }
+
+    /// records transaction accepted by Mempool, on the next `flush`
+    pub fn put(&mut self, txn: &MempoolTransaction) {
+        let result = self.batch.put::<JournalSchema>(
+            &(txn.get_sender(), txn.get_sequence_number()),
+            &JournalEntry::from(txn),
+        );
+        match result {
+            Ok(()) => self.num_writes += 1,
+            Err(e) => error!("[mempool] failed to journal transaction: {:?}", e),
+        }
+    }
+
+    /// forgets transaction which left Mempool, on the next `flush`
+    pub fn delete(&mut self, address: &AccountAddress, sequence_number: u64) {
+        match self
+            .batch
+            .delete::<JournalSchema>(&(*address, sequence_number))
+        {
+            Ok(()) => self.num_writes += 1,
+            Err(e) => error!(
+                "[mempool] failed to delete transaction from journal: {:?}",
+                e
+            ),
+        }
+    }
+
+    /// writes the entries put and deleted since the last flush
+    pub fn flush(&mut self) {
+        if self.num_writes == 0 {
+            return;
+        }
+        self.num_writes = 0;
+        let batch = std::mem::replace(&mut self.batch, SchemaBatch::new());
+        if let Err(e) = self.db.write_schemas_relaxed(batch) {
+            error!("[mempool] failed to write journal: {:?}", e);
+        }
+    }
+
+    /// reads all entries, in the order of their timeline IDs (then non-ready ones)
+    pub fn read_all(&self) -> Result<Vec<JournalEntry>> {
+        let mut iter = self.db.iter::<JournalSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut entries = vec![];
+        for res in iter {
+            let (_, entry) = res?;
+            entries.push(entry);
+        }
+
+        entries.sort_by_key(|entry| match entry.timeline_state {
+            TimelineState::Ready(timeline_id) => (0, timeline_id),
+            _ => (1, 0),
+        });
+        Ok(entries)
+    }
+}
+
+impl KeyCodec<JournalSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (address, sequence_number) = self;
+        let mut encoded = address.to_vec();
+        encoded.write_u64::<BigEndian>(*sequence_number)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == AccountAddress::LENGTH + size_of::<u64>(),
+            "Unexpected data len {}",
+            data.len()
+        );
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let sequence_number = (&data[AccountAddress::LENGTH..]).read_u64::<BigEndian>()?;
+        Ok((address, sequence_number))
+    }
+}
+
+impl ValueCodec<JournalSchema> for JournalEntry {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        lcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        lcs::from_bytes(data).map_err(Into::into)
+    }
+}
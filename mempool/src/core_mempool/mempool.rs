@@ -6,12 +6,15 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
+        journal::JournalEntry,
         transaction::{MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
+    shared_mempool::types::TransactionReadiness,
     OP_COUNTERS,
 };
+use anyhow::Result;
 use libra_config::config::NodeConfig;
 use libra_logger::prelude::*;
 use libra_trace::prelude::*;
@@ -111,6 +114,63 @@ impl Mempool {
         timeline_state: TimelineState,
         is_governance_txn: bool,
     ) -> MempoolStatus {
+        let expiration_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("init timestamp failure")
+            + self.system_transaction_timeout;
+        let txn_info = MempoolTransaction::new(
+            txn,
+            expiration_time,
+            gas_amount,
+            rankin_score,
+            timeline_state,
+            is_governance_txn,
+        );
+        self.insert_txn(txn_info, db_sequence_number)
+    }
+
+    /// Used to add again a transaction persisted before the last restart, once revalidated
+    /// Keeps its system expiration time. It's broadcast again unless it came from another peer
+    pub(crate) fn restore_txn(
+        &mut self,
+        entry: JournalEntry,
+        rankin_score: u64,
+        db_sequence_number: u64,
+        is_governance_txn: bool,
+    ) -> MempoolStatus {
+        let timeline_state = match entry.timeline_state {
+            TimelineState::NonQualified => TimelineState::NonQualified,
+            _ => TimelineState::NotReady,
+        };
+        let gas_amount = entry.txn.max_gas_amount();
+        let txn_info = MempoolTransaction::new(
+            entry.txn,
+            entry.expiration_time,
+            gas_amount,
+            rankin_score,
+            timeline_state,
+            is_governance_txn,
+        );
+        self.insert_txn(txn_info, db_sequence_number)
+    }
+
+    /// Reads the transactions journaled before the last restart, if persistence is enabled.
+    /// The ones which aren't restored must be forgotten with `forget_journaled`.
+    pub(crate) fn read_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.transactions.read_journal()
+    }
+
+    /// Deletes a journaled transaction which isn't restored
+    pub(crate) fn forget_journaled(&mut self, sender: &AccountAddress, sequence_number: u64) {
+        self.transactions.forget_journaled(sender, sequence_number)
+    }
+
+    fn insert_txn(
+        &mut self,
+        txn_info: MempoolTransaction,
+        db_sequence_number: u64,
+    ) -> MempoolStatus {
+        let txn = &txn_info.txn;
         trace_event!("mempool::add_txn", {"txn", txn.sender(), txn.sequence_number()});
         trace!(
             "[Mempool] Adding transaction to mempool: {}:{}:{}",
@@ -133,29 +193,36 @@ impl Mempool {
             ));
         }
 
-        let expiration_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("init timestamp failure")
-            + self.system_transaction_timeout;
-        if timeline_state != TimelineState::NonQualified {
+        if txn_info.timeline_state != TimelineState::NonQualified {
             self.metrics_cache
                 .insert((txn.sender(), txn.sequence_number()), SystemTime::now());
         }
 
-        let txn_info = MempoolTransaction::new(
-            txn,
-            expiration_time,
-            gas_amount,
-            rankin_score,
-            timeline_state,
-            is_governance_txn,
-        );
-
         let status = self.transactions.insert(txn_info, sequence_number);
         OP_COUNTERS.inc(&format!("insert.{:?}", status));
         status
     }
 
+    /// Returns the transactions of `address` in mempool, along with their readiness
+    pub(crate) fn get_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<(MempoolTransaction, TransactionReadiness)> {
+        self.transactions.get_account_transactions(address)
+    }
+
+    /// Explains whether the transaction of `address` with `sequence_number` can be pulled into
+    /// the next block
+    pub(crate) fn get_readiness(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> TransactionReadiness {
+        let account_sequence_number = self.sequence_number_cache.get(address).cloned();
+        self.transactions
+            .get_readiness(address, sequence_number, account_sequence_number)
+    }
+
     /// Fetches next block of transactions for consensus
     /// `batch_size` - size of requested block
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet
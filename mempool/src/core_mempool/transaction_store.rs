@@ -7,11 +7,13 @@ use crate::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
             TimelineIndex,
         },
+        journal::{JournalEntry, MempoolJournal},
         transaction::{MempoolTransaction, TimelineState},
     },
+    shared_mempool::types::TransactionReadiness,
     OP_COUNTERS,
 };
-use anyhow::{format_err, Result};
+use anyhow::Result;
 use libra_config::config::MempoolConfig;
 use libra_logger::prelude::*;
 use libra_types::{
@@ -42,9 +44,13 @@ pub struct TransactionStore {
     // keeps track of "non-ready" txns (transactions that can't be included in next block)
     parking_lot_index: ParkingLotIndex,
 
+    // on-disk copy of the transactions, if persistence is enabled
+    journal: Option<MempoolJournal>,
+
     // configuration
     capacity: usize,
     capacity_per_user: usize,
+    min_gas_price_bump_percent: u64,
 }
 
 impl TransactionStore {
@@ -62,9 +68,12 @@ impl TransactionStore {
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
 
+            journal: config.journal_path().map(MempoolJournal::new),
+
             // configuration
             capacity: config.capacity,
             capacity_per_user: config.capacity_per_user,
+            min_gas_price_bump_percent: config.min_gas_price_bump_percent,
         }
     }
 
@@ -84,6 +93,61 @@ impl TransactionStore {
         None
     }
 
+    /// returns the transactions of `address`, ordered by sequence number, along with their readiness
+    pub(crate) fn get_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<(MempoolTransaction, TransactionReadiness)> {
+        match self.transactions.get(address) {
+            Some(txns) => txns
+                .values()
+                .map(|txn| (txn.clone(), self.readiness(txns, txn)))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// explains whether the transaction of `address` with `sequence_number` is ready
+    /// `account_sequence_number` is the last known sequence number of the account, if any
+    pub(crate) fn get_readiness(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+        account_sequence_number: Option<u64>,
+    ) -> TransactionReadiness {
+        if let Some(txns) = self.transactions.get(address) {
+            if let Some(txn) = txns.get(&sequence_number) {
+                return self.readiness(txns, txn);
+            }
+        }
+        match account_sequence_number {
+            Some(account_sequence_number) if sequence_number < account_sequence_number => {
+                TransactionReadiness::Committed {
+                    account_sequence_number,
+                }
+            }
+            _ => TransactionReadiness::NotFound,
+        }
+    }
+
+    fn readiness(
+        &self,
+        txns: &AccountTransactions,
+        txn: &MempoolTransaction,
+    ) -> TransactionReadiness {
+        if self.priority_index.contains(txn) {
+            return TransactionReadiness::Ready;
+        }
+        // a parked txn waits for the first gap in the account's sequence numbers below it
+        let mut sequence_number = txn.get_sequence_number();
+        while sequence_number > 0 && txns.contains_key(&(sequence_number - 1)) {
+            sequence_number -= 1;
+        }
+        TransactionReadiness::Parked {
+            missing_sequence_number: sequence_number.checked_sub(1),
+        }
+    }
+
     /// insert transaction into TransactionStore
     /// performs validation checks and updates indexes
     pub(crate) fn insert(
@@ -91,19 +155,22 @@ impl TransactionStore {
         txn: MempoolTransaction,
         current_sequence_number: u64,
     ) -> MempoolStatus {
-        if self.handle_gas_price_update(&txn).is_err() {
-            return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(format!(
-                "Failed to update gas price to {}",
-                txn.get_gas_price()
-            ));
+        let status = self.insert_impl(txn, current_sequence_number);
+        self.flush_journal();
+        status
+    }
+
+    fn insert_impl(
+        &mut self,
+        txn: MempoolTransaction,
+        current_sequence_number: u64,
+    ) -> MempoolStatus {
+        if let Err(status) = self.handle_gas_price_update(&txn) {
+            return status;
         }
 
-        if self.check_if_full(&txn, current_sequence_number) {
-            return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
-                "mempool size: {}, capacity: {}",
-                self.system_ttl_index.size(),
-                self.capacity,
-            ));
+        if let Err(status) = self.check_if_full(&txn, current_sequence_number) {
+            return status;
         }
 
         let address = txn.get_sender();
@@ -134,9 +201,41 @@ impl TransactionStore {
             self.track_indices();
         }
         self.process_ready_transactions(&address, current_sequence_number);
+        if let Some(journal) = &mut self.journal {
+            if let Some(txn) = self
+                .transactions
+                .get(&address)
+                .and_then(|txns| txns.get(&sequence_number))
+            {
+                journal.put(txn);
+            }
+        }
         MempoolStatus::new(MempoolStatusCode::Accepted)
     }
 
+    /// reads the transactions journaled before the last restart
+    /// they stay journaled if they're inserted again, the other ones must be forgotten
+    pub(crate) fn read_journal(&self) -> Result<Vec<JournalEntry>> {
+        match &self.journal {
+            Some(journal) => journal.read_all(),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// deletes a journaled transaction which wasn't inserted again
+    pub(crate) fn forget_journaled(&mut self, address: &AccountAddress, sequence_number: u64) {
+        if let Some(journal) = &mut self.journal {
+            journal.delete(address, sequence_number);
+        }
+        self.flush_journal();
+    }
+
+    fn flush_journal(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.flush();
+        }
+    }
+
     fn track_indices(&self) {
         OP_COUNTERS.set("txn.system_ttl_index", self.system_ttl_index.size());
         OP_COUNTERS.set("txn.parking_lot_index", self.parking_lot_index.size());
@@ -144,24 +243,78 @@ impl TransactionStore {
     }
 
     /// checks if Mempool is full
-    /// If it's full, tries to free some space by evicting transactions from ParkingLot
+    /// If it's full, tries to free some space by evicting transactions from ParkingLot, or else
+    /// the ready transaction of another account with the lowest priority if `txn` outranks it
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion
-    fn check_if_full(&mut self, txn: &MempoolTransaction, curr_sequence_number: u64) -> bool {
-        if self.system_ttl_index.size() >= self.capacity
-            && self.check_txn_ready(txn, curr_sequence_number)
-        {
-            // try to free some space in Mempool from ParkingLot
-            if let Some((address, sequence_number)) = self.parking_lot_index.pop() {
-                if let Some(txn) = self
-                    .transactions
-                    .get_mut(&address)
-                    .and_then(|txns| txns.remove(&sequence_number))
-                {
-                    self.index_remove(&txn);
+    fn check_if_full(
+        &mut self,
+        txn: &MempoolTransaction,
+        curr_sequence_number: u64,
+    ) -> Result<(), MempoolStatus> {
+        if self.system_ttl_index.size() < self.capacity {
+            return Ok(());
+        }
+        let full_status =
+            MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
+                "mempool size: {}, capacity: {}",
+                self.system_ttl_index.size(),
+                self.capacity,
+            ));
+        if !self.check_txn_ready(txn, curr_sequence_number) {
+            return Err(full_status);
+        }
+
+        // try to free some space in Mempool from ParkingLot
+        if let Some((address, sequence_number)) = self.parking_lot_index.pop() {
+            if let Some(txn) = self
+                .transactions
+                .get_mut(&address)
+                .and_then(|txns| txns.remove(&sequence_number))
+            {
+                self.index_remove(&txn);
+            }
+        } else {
+            // ParkingLot is empty: the transaction has to outbid a ready one
+            let key = self.priority_index.make_key(txn);
+            let lowest = match self.priority_index.lowest(&txn.get_sender()) {
+                Some(lowest) => lowest.clone(),
+                None => return Err(full_status),
+            };
+            if !key.outranks(&lowest) {
+                return Err(
+                    MempoolStatus::new(MempoolStatusCode::GasPriceTooLowForFullMempool)
+                        .with_message(format!(
+                            "mempool is full, txn ranking score: {}, lowest ranking score: {}",
+                            key.gas_ranking_score, lowest.gas_ranking_score,
+                        )),
+                );
+            }
+            OP_COUNTERS.inc("evict.low_gas_price");
+            self.evict_ready_transaction(&lowest.address, lowest.sequence_number);
+        }
+
+        if self.system_ttl_index.size() >= self.capacity {
+            return Err(full_status);
+        }
+        Ok(())
+    }
+
+    /// removes a ready transaction to make space for one with higher priority
+    /// the following transactions of the same account become non-ready, as after GC
+    fn evict_ready_transaction(&mut self, address: &AccountAddress, sequence_number: u64) {
+        if let Some(txns) = self.transactions.get_mut(address) {
+            for (_, t) in txns.range_mut((Bound::Excluded(sequence_number), Bound::Unbounded)) {
+                self.priority_index.remove(&t);
+                self.timeline_index.remove(&t);
+                if let TimelineState::Ready(_) = t.timeline_state {
+                    t.timeline_state = TimelineState::NotReady;
                 }
+                self.parking_lot_index.insert(&t);
+            }
+            if let Some(txn) = txns.remove(&sequence_number) {
+                self.index_remove(&txn);
             }
         }
-        self.system_ttl_index.size() >= self.capacity
     }
 
     /// check if a transaction would be ready for broadcast in mempool upon insertion (without inserting it)
@@ -192,23 +345,40 @@ impl TransactionStore {
 
     /// check if transaction is already present in Mempool
     /// e.g. given request is update
-    /// we allow increase in gas price to speed up process
-    fn handle_gas_price_update(&mut self, txn: &MempoolTransaction) -> Result<()> {
+    /// we allow increase in gas price, by at least `min_gas_price_bump_percent`, to speed up process
+    /// (replace-by-fee): the current version is then removed from all indexes
+    fn handle_gas_price_update(&mut self, txn: &MempoolTransaction) -> Result<(), MempoolStatus> {
         if let Some(txns) = self.transactions.get_mut(&txn.get_sender()) {
-            if let Some(current_version) = txns.get_mut(&txn.get_sequence_number()) {
-                if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
-                    && current_version.txn.payload() == txn.txn.payload()
-                    && current_version.txn.expiration_timestamp_secs()
-                        == txn.txn.expiration_timestamp_secs()
-                    && current_version.get_gas_price() < txn.get_gas_price()
+            if let Some(current_version) = txns.get(&txn.get_sequence_number()) {
+                let current_gas_price = current_version.get_gas_price();
+                if current_version.txn.max_gas_amount() != txn.txn.max_gas_amount()
+                    || current_version.txn.payload() != txn.txn.payload()
+                    || current_version.txn.expiration_timestamp_secs()
+                        != txn.txn.expiration_timestamp_secs()
+                    || current_gas_price >= txn.get_gas_price()
                 {
-                    if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
-                        self.index_remove(&txn);
-                    }
-                } else {
-                    return Err(format_err!("Invalid gas price update. txn gas price: {}, current_version gas price: {}",
+                    return Err(
+                        MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(format!(
+                            "Failed to update gas price to {}, current_version gas price: {}",
+                            txn.get_gas_price(),
+                            current_gas_price,
+                        )),
+                    );
+                }
+                if u128::from(txn.get_gas_price()) * 100
+                    < u128::from(current_gas_price)
+                        * u128::from(100 + self.min_gas_price_bump_percent)
+                {
+                    return Err(MempoolStatus::new(MempoolStatusCode::GasPriceBumpTooLow)
+                        .with_message(format!(
+                            "gas price {} is less than {}% above current_version gas price {}",
                             txn.get_gas_price(),
-                            current_version.get_gas_price()));
+                            self.min_gas_price_bump_percent,
+                            current_gas_price,
+                        )));
+                }
+                if let Some(txn) = txns.remove(&txn.get_sequence_number()) {
+                    self.index_remove(&txn);
                 }
             }
         }
@@ -229,9 +399,14 @@ impl TransactionStore {
             let mut sequence_number = current_sequence_number;
             while let Some(txn) = txns.get_mut(&sequence_number) {
                 self.priority_index.insert(txn);
+                self.parking_lot_index.remove(txn);
 
                 if txn.timeline_state == TimelineState::NotReady {
                     self.timeline_index.insert(txn);
+                    // journaled again with its timeline ID
+                    if let Some(journal) = &mut self.journal {
+                        journal.put(txn);
+                    }
                 }
                 sequence_number += 1;
             }
@@ -279,6 +454,7 @@ impl TransactionStore {
     ) {
         self.clean_committed_transactions(account, account_sequence_number);
         self.process_ready_transactions(account, account_sequence_number);
+        self.flush_journal();
     }
 
     pub(crate) fn reject_transaction(&mut self, account: &AccountAddress, _sequence_number: u64) {
@@ -287,10 +463,14 @@ impl TransactionStore {
                 self.index_remove(&transaction);
             }
         }
+        self.flush_journal();
     }
 
-    /// removes transaction from all indexes
+    /// removes transaction from all indexes, and from journal
     fn index_remove(&mut self, txn: &MempoolTransaction) {
+        if let Some(journal) = &mut self.journal {
+            journal.delete(&txn.get_sender(), txn.get_sequence_number());
+        }
         self.system_ttl_index.remove(&txn);
         self.expiration_time_index.remove(&txn);
         self.priority_index.remove(&txn);
@@ -389,6 +569,7 @@ impl TransactionStore {
             }
         }
         self.track_indices();
+        self.flush_journal();
     }
 
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
@@ -64,7 +64,8 @@ pub use shared_mempool::{
     types::{
         gen_mempool_reconfig_subscription, CommitNotification, CommitResponse,
         CommittedTransaction, ConsensusRequest, ConsensusResponse, MempoolClientSender,
-        SubmissionStatus, TransactionExclusion,
+        MempoolDebugHandle, PeerBroadcastInfo, PendingTransactionInfo, SubmissionStatus,
+        TransactionExclusion, TransactionReadiness,
     },
 };
 #[cfg(feature = "fuzzing")]
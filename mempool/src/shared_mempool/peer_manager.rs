@@ -256,6 +256,28 @@ impl PeerManager {
         sync_state.broadcast_info.backoff_mode = backoff;
     }
 
+    // returns the peers the txn with `timeline_id` was broadcast to, and for each peer
+    // whether it ACK'ed the txn without asking for a retry
+    pub fn get_broadcast_peers(&self, timeline_id: u64) -> Vec<(PeerNetworkId, bool)> {
+        let peer_info = self
+            .peer_info
+            .lock()
+            .expect("failed to acquire peer_info lock");
+        peer_info
+            .iter()
+            .filter(|(_, state)| state.timeline_id >= timeline_id)
+            .map(|(peer, state)| {
+                let broadcast_info = &state.broadcast_info;
+                let acked = !broadcast_info.total_retry_txns.contains(&timeline_id)
+                    && !broadcast_info
+                        .sent_batches
+                        .values()
+                        .any(|batch| batch.contains(&timeline_id));
+                (peer.clone(), acked)
+            })
+            .collect()
+    }
+
     // if the origin is provided, checks whether this peer is an upstream peer based on configured preferences and
     // connection origin
     // if the origin is not provided, checks whether this peer is an upstream peer that was seen before
@@ -7,7 +7,8 @@ use crate::{
     shared_mempool::{
         coordinator::{coordinator, gc_coordinator},
         peer_manager::PeerManager,
-        types::{SharedMempool, SharedMempoolNotification},
+        tasks,
+        types::{MempoolDebugHandle, SharedMempool, SharedMempoolNotification},
     },
     CommitNotification, ConsensusRequest, SubmissionStatus,
 };
@@ -28,10 +29,12 @@ use tokio::runtime::{Builder, Handle, Runtime};
 use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 
 /// bootstrap of SharedMempool
+/// restores the transactions journaled before the last restart, if any
 /// creates separate Tokio Runtime that runs following routines:
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers)
 ///   - inbound_network_task (task that handles inbound mempool messages and network events)
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL)
+/// returns a handle to inspect the mempool contents
 pub(crate) fn start_shared_mempool<V>(
     executor: &Handle,
     config: &NodeConfig,
@@ -46,7 +49,8 @@ pub(crate) fn start_shared_mempool<V>(
     db: Arc<dyn DbReader>,
     validator: Arc<RwLock<V>>,
     subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
-) where
+) -> MempoolDebugHandle
+where
     V: TransactionValidation + 'static,
 {
     let upstream_config = config.upstream.clone();
@@ -59,6 +63,7 @@ pub(crate) fn start_shared_mempool<V>(
         network_senders.insert(network_id, network_sender);
     }
 
+    let debug_handle = MempoolDebugHandle::new(mempool.clone(), peer_manager.clone());
     let smp = SharedMempool {
         mempool: mempool.clone(),
         config: config.mempool.clone(),
@@ -68,6 +73,7 @@ pub(crate) fn start_shared_mempool<V>(
         peer_manager,
         subscribers,
     };
+    tasks::restore_journaled_transactions(&smp);
 
     executor.spawn(coordinator(
         smp,
@@ -83,9 +89,12 @@ pub(crate) fn start_shared_mempool<V>(
         mempool,
         config.mempool.system_transaction_gc_interval_ms,
     ));
+
+    debug_handle
 }
 
 /// method used to bootstrap shared mempool for a node
+/// returns the runtime running shared mempool and a handle for the node debug interface
 pub fn bootstrap(
     config: &NodeConfig,
     db: Arc<dyn DbReader>,
@@ -96,7 +105,7 @@ pub fn bootstrap(
     consensus_requests: Receiver<ConsensusRequest>,
     state_sync_requests: Receiver<CommitNotification>,
     mempool_reconfig_events: libra_channel::Receiver<(), OnChainConfigPayload>,
-) -> Runtime {
+) -> (Runtime, MempoolDebugHandle) {
     let runtime = Builder::new()
         .thread_name("shared-mem-")
         .threaded_scheduler()
@@ -105,7 +114,7 @@ pub fn bootstrap(
         .expect("[shared mempool] failed to create runtime");
     let mempool = Arc::new(Mutex::new(CoreMempool::new(&config)));
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
-    start_shared_mempool(
+    let debug_handle = start_shared_mempool(
         runtime.handle(),
         config,
         mempool,
@@ -118,5 +127,5 @@ pub fn bootstrap(
         vm_validator,
         vec![],
     );
-    runtime
+    (runtime, debug_handle)
 }
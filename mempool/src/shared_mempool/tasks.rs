@@ -4,7 +4,7 @@
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TxnPointer},
+    core_mempool::{CoreMempool, JournalEntry, TimelineState, TxnPointer},
     counters,
     network::{MempoolNetworkSender, MempoolSyncMsg},
     shared_mempool::types::{
@@ -387,6 +387,89 @@ where
     statuses
 }
 
+/// submits again the transactions journaled before the last restart, if persistence is enabled
+/// they're revalidated first, and dropped if committed or expired in the meantime
+/// each entry is only deleted from the journal once handled, so that a restart in the middle of
+/// the restoration doesn't lose the remaining ones
+pub(crate) fn restore_journaled_transactions<V>(smp: &SharedMempool<V>)
+where
+    V: TransactionValidation,
+{
+    let entries = match smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .read_journal()
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("[shared mempool] failed to read journal: {:?}", e);
+            return;
+        }
+    };
+    if entries.is_empty() {
+        return;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("init timestamp failure");
+    let num_entries = entries.len();
+    let mut num_restored = 0;
+    for entry in entries {
+        let sender = entry.txn.sender();
+        let txn_sequence_number = entry.txn.sequence_number();
+        // restored entries are journaled again in place of the old ones
+        if restore_journal_entry(smp, entry, now) {
+            num_restored += 1;
+        } else {
+            smp.mempool
+                .lock()
+                .expect("[shared mempool] failed to acquire mempool lock")
+                .forget_journaled(&sender, txn_sequence_number);
+        }
+    }
+    info!(
+        "[shared mempool] restored {} of {} journaled transactions",
+        num_restored, num_entries
+    );
+    notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
+}
+
+/// revalidates a journaled transaction and inserts it into mempool, returns whether it's accepted
+fn restore_journal_entry<V>(smp: &SharedMempool<V>, entry: JournalEntry, now: Duration) -> bool
+where
+    V: TransactionValidation,
+{
+    if entry.expiration_time <= now {
+        return false;
+    }
+    let sequence_number = match get_account_sequence_number(smp.db.as_ref(), entry.txn.sender()) {
+        Ok(sequence_number) if entry.txn.sequence_number() >= sequence_number => sequence_number,
+        _ => return false,
+    };
+    let validation_result = match smp
+        .validator
+        .read()
+        .unwrap()
+        .validate_transaction(entry.txn.clone())
+    {
+        Ok(validation_result) if validation_result.status().is_none() => validation_result,
+        _ => return false,
+    };
+    let mempool_status = smp
+        .mempool
+        .lock()
+        .expect("[shared mempool] failed to acquire mempool lock")
+        .restore_txn(
+            entry,
+            validation_result.score(),
+            sequence_number,
+            validation_result.is_governance_txn(),
+        );
+    mempool_status.code == MempoolStatusCode::Accepted
+}
+
 // TODO update counters to ID peers using PeerNetworkId
 fn log_txn_process_results(results: &[SubmissionStatus], sender: Option<PeerId>) {
     let sender = match sender {
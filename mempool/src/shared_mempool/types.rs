@@ -4,7 +4,7 @@
 //! Objects used by/related to shared mempool
 
 use crate::{
-    core_mempool::CoreMempool,
+    core_mempool::{CoreMempool, TimelineState},
     shared_mempool::{network::MempoolNetworkSender, peer_manager::PeerManager},
 };
 use anyhow::Result;
@@ -25,6 +25,7 @@ use libra_types::{
     transaction::SignedTransaction,
     vm_status::DiscardedVMStatus,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     pin::Pin,
@@ -187,6 +188,138 @@ pub struct TransactionExclusion {
     pub sequence_number: u64,
 }
 
+/// Whether a transaction can be pulled into the next block, and if not, why
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TransactionReadiness {
+    /// transaction is in the `PriorityIndex` and can be pulled by consensus
+    Ready,
+    /// transaction is parked in the `ParkingLotIndex` until the account's transaction with
+    /// `missing_sequence_number` is committed or submitted to mempool
+    Parked {
+        /// closest sequence number below the transaction's that mempool doesn't hold
+        missing_sequence_number: Option<u64>,
+    },
+    /// transaction is not in mempool and the account's sequence number is already past it
+    Committed {
+        /// last known sequence number of the account
+        account_sequence_number: u64,
+    },
+    /// transaction is not in mempool
+    NotFound,
+}
+
+/// peer a pending transaction was broadcast to
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PeerBroadcastInfo {
+    /// network the peer was reached through
+    pub network: String,
+    /// ID of the peer
+    pub peer_id: String,
+    /// whether the peer ACK'ed the broadcast without asking for a retry
+    pub acked: bool,
+}
+
+/// transaction pending in mempool, as reported by the debug interface
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PendingTransactionInfo {
+    /// sequence number
+    pub sequence_number: u64,
+    /// gas unit price
+    pub gas_unit_price: u64,
+    /// max gas amount
+    pub max_gas_amount: u64,
+    /// score used to order the transaction in the `PriorityIndex`
+    pub ranking_score: u64,
+    /// whether the transaction is a governance transaction
+    pub is_governance_txn: bool,
+    /// whether the transaction is ready or parked
+    pub readiness: TransactionReadiness,
+    /// position in the log of transactions ready for broadcast, if the transaction is broadcast
+    /// by this node
+    pub timeline_id: Option<u64>,
+    /// client-specified expiration time
+    pub expiration_timestamp_secs: u64,
+    /// time after which mempool drops the transaction regardless of the client expiration
+    pub system_expiration_timestamp_secs: u64,
+    /// peers the transaction was broadcast to
+    pub broadcasts: Vec<PeerBroadcastInfo>,
+}
+
+/// Read-only access to mempool contents, served by the node debug interface
+#[derive(Clone)]
+pub struct MempoolDebugHandle {
+    mempool: Arc<Mutex<CoreMempool>>,
+    peer_manager: Arc<PeerManager>,
+}
+
+impl MempoolDebugHandle {
+    pub(crate) fn new(mempool: Arc<Mutex<CoreMempool>>, peer_manager: Arc<PeerManager>) -> Self {
+        Self {
+            mempool,
+            peer_manager,
+        }
+    }
+
+    /// returns the transactions of `address` pending in mempool, ordered by sequence number
+    pub fn get_account_transactions(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<PendingTransactionInfo> {
+        let txns = self
+            .mempool
+            .lock()
+            .expect("[mempool] failed to acquire mempool lock")
+            .get_account_transactions(address);
+
+        txns.into_iter()
+            .map(|(txn, readiness)| {
+                let timeline_id = match txn.timeline_state {
+                    TimelineState::Ready(timeline_id) => Some(timeline_id),
+                    _ => None,
+                };
+                let broadcasts = timeline_id
+                    .map(|timeline_id| {
+                        self.peer_manager
+                            .get_broadcast_peers(timeline_id)
+                            .into_iter()
+                            .map(|(peer, acked)| PeerBroadcastInfo {
+                                network: peer.raw_network_id().to_string(),
+                                peer_id: peer.peer_id().to_string(),
+                                acked,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                PendingTransactionInfo {
+                    sequence_number: txn.get_sequence_number(),
+                    gas_unit_price: txn.get_gas_price(),
+                    max_gas_amount: txn.txn.max_gas_amount(),
+                    ranking_score: txn.ranking_score,
+                    is_governance_txn: txn.is_governance_txn,
+                    readiness,
+                    timeline_id,
+                    expiration_timestamp_secs: txn.txn.expiration_timestamp_secs(),
+                    system_expiration_timestamp_secs: txn.expiration_time.as_secs(),
+                    broadcasts,
+                }
+            })
+            .collect()
+    }
+
+    /// explains whether the transaction of `address` with `sequence_number` is ready
+    pub fn get_transaction_readiness(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> TransactionReadiness {
+        self.mempool
+            .lock()
+            .expect("[mempool] failed to acquire mempool lock")
+            .get_readiness(address, sequence_number)
+    }
+}
+
 /// Submission Status is represented as combination of vm_validator internal status and core mempool insertion status
 pub type SubmissionStatus = (MempoolStatus, Option<DiscardedVMStatus>);
 
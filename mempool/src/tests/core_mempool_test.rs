@@ -7,9 +7,11 @@ use crate::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
     },
+    TransactionReadiness,
 };
 use libra_config::config::NodeConfig;
-use libra_types::transaction::SignedTransaction;
+use libra_temppath::TempPath;
+use libra_types::{mempool_status::MempoolStatusCode, transaction::SignedTransaction};
 use std::{
     collections::HashSet,
     time::{Duration, SystemTime},
@@ -361,6 +363,319 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     }
 }
 
+fn add_txn_with_status(pool: &mut CoreMempool, transaction: TestTransaction) -> MempoolStatusCode {
+    let txn = transaction.make_signed_transaction();
+    let gas_price = txn.gas_unit_price();
+    pool.add_txn(txn, 0, gas_price, 0, TimelineState::NotReady, false)
+        .code
+}
+
+#[test]
+fn test_replace_by_fee() {
+    let mut config = NodeConfig::random();
+    config.mempool.min_gas_price_bump_percent = 10;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 20)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 21)).unwrap();
+
+    // gas price has to increase, by at least 10%
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(0, 0, 20)),
+        MempoolStatusCode::InvalidUpdate
+    );
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(0, 0, 21)),
+        MempoolStatusCode::GasPriceBumpTooLow
+    );
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert_eq!(timeline.len(), 2);
+
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(0, 0, 22)),
+        MempoolStatusCode::Accepted
+    );
+    let replacement = TestTransaction::new(0, 0, 22).make_signed_transaction();
+    // the replacement takes the place of the original in PriorityIndex
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 2);
+    assert_eq!(block[0], replacement);
+    // and is broadcast again, after the other transaction, in TimelineIndex
+    let (timeline, _) = pool.read_timeline(0, 10);
+    let timeline: Vec<_> = timeline.into_iter().map(|(_id, txn)| txn).collect();
+    assert_eq!(
+        timeline,
+        vec![
+            TestTransaction::new(1, 0, 21).make_signed_transaction(),
+            replacement
+        ]
+    );
+}
+
+#[test]
+fn test_replace_by_fee_in_parking_lot() {
+    let mut pool = setup_mempool().0;
+    add_txn(&mut pool, TestTransaction::new(1, 1, 1)).unwrap();
+    // replacement of a non-ready transaction stays in ParkingLot
+    add_txn(&mut pool, TestTransaction::new(1, 1, 5)).unwrap();
+    assert!(pool.get_block(10, HashSet::new()).is_empty());
+    assert!(pool.read_timeline(0, 10).0.is_empty());
+
+    // until the gap is filled
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 2);
+    assert_eq!(
+        block[1],
+        TestTransaction::new(1, 1, 5).make_signed_transaction()
+    );
+    assert_eq!(pool.read_timeline(0, 10).0.len(), 2);
+}
+
+#[test]
+fn test_fee_eviction() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 2;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 3)).unwrap();
+
+    // Mempool is full, ParkingLot is empty: only higher gas price gets in
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(2, 0, 1)),
+        MempoolStatusCode::GasPriceTooLowForFullMempool
+    );
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(2, 0, 2)),
+        MempoolStatusCode::Accepted
+    );
+    let mut senders: Vec<_> = pool
+        .get_block(10, HashSet::new())
+        .iter()
+        .map(SignedTransaction::sender)
+        .collect();
+    senders.sort();
+    let mut expected = vec![
+        TestTransaction::get_address(1),
+        TestTransaction::get_address(2),
+    ];
+    expected.sort();
+    assert_eq!(senders, expected);
+    // evicted transaction isn't broadcast anymore
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert!(timeline
+        .iter()
+        .all(|(_id, txn)| txn.sender() != TestTransaction::get_address(0)));
+
+    // a transaction which wouldn't be ready doesn't evict anything
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(3, 1, 100)),
+        MempoolStatusCode::MempoolIsFull
+    );
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(1, 1, 100)),
+        MempoolStatusCode::Accepted
+    );
+    // an account doesn't evict its own transactions
+    assert_eq!(
+        add_txn_with_status(&mut pool, TestTransaction::new(1, 2, 1000)),
+        MempoolStatusCode::MempoolIsFull
+    );
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 2);
+}
+
+#[test]
+fn test_fee_eviction_parks_following_transactions() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 4)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 5)).unwrap();
+
+    // evicts txn 0 of account 0, so its txn 1 can't be ready anymore
+    add_txn(&mut pool, TestTransaction::new(2, 0, 3)).unwrap();
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 2);
+    assert!(block
+        .iter()
+        .all(|txn| txn.sender() != TestTransaction::get_address(0)));
+    let (timeline, _) = pool.read_timeline(0, 10);
+    assert!(timeline
+        .iter()
+        .all(|(_id, txn)| txn.sender() != TestTransaction::get_address(0)));
+
+    // it's in ParkingLot, so evicted first when Mempool is full, whatever its gas price
+    add_txn(&mut pool, TestTransaction::new(3, 0, 1)).unwrap();
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 3);
+
+    // and it's ready again once the gap is filled
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    let mut pool = CoreMempool::new(&config);
+    add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(0, 1, 4)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(1, 0, 5)).unwrap();
+    add_txn(&mut pool, TestTransaction::new(2, 0, 3)).unwrap();
+    pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 3);
+    assert_eq!(pool.read_timeline(0, 10).0.len(), 3);
+}
+
+#[test]
+fn test_transaction_readiness() {
+    let mut pool = setup_mempool().0;
+    let address = TestTransaction::get_address(1);
+    for seq in &[0, 1, 3, 4] {
+        add_txn(&mut pool, TestTransaction::new(1, *seq, 1)).unwrap();
+    }
+
+    let parked = TransactionReadiness::Parked {
+        missing_sequence_number: Some(2),
+    };
+    let txns = pool.get_account_transactions(&address);
+    let sequence_numbers: Vec<_> = txns
+        .iter()
+        .map(|(txn, _)| txn.get_sequence_number())
+        .collect();
+    assert_eq!(sequence_numbers, vec![0, 1, 3, 4]);
+    let readiness: Vec<_> = txns.into_iter().map(|(_, readiness)| readiness).collect();
+    assert_eq!(
+        readiness,
+        vec![
+            TransactionReadiness::Ready,
+            TransactionReadiness::Ready,
+            parked.clone(),
+            parked.clone(),
+        ]
+    );
+    assert_eq!(
+        pool.get_readiness(&address, 2),
+        TransactionReadiness::NotFound
+    );
+
+    // once txn 1 is committed, earlier sequence numbers are reported as committed
+    pool.remove_transaction(&address, 1, false);
+    assert_eq!(
+        pool.get_readiness(&address, 0),
+        TransactionReadiness::Committed {
+            account_sequence_number: 2
+        }
+    );
+    assert_eq!(pool.get_readiness(&address, 3), parked);
+
+    // filling the gap unparks the following txns
+    add_txn(&mut pool, TestTransaction::new(1, 2, 1)).unwrap();
+    for seq in 2..5 {
+        assert_eq!(
+            pool.get_readiness(&address, seq),
+            TransactionReadiness::Ready
+        );
+    }
+}
+
+#[test]
+fn test_journal_promoted_transactions() {
+    let tmp_dir = TempPath::new();
+    let mut config = NodeConfig::random();
+    config.mempool.journal_path = Some(tmp_dir.path().to_path_buf());
+    {
+        let mut pool = CoreMempool::new(&config);
+        // parked until the gap is filled
+        add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+        add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+        add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+        // parked until the gap is committed
+        add_txn(&mut pool, TestTransaction::new(2, 1, 1)).unwrap();
+        pool.remove_transaction(&TestTransaction::get_address(2), 0, false);
+    }
+
+    let pool = CoreMempool::new(&config);
+    let entries = pool.read_journal().unwrap();
+    assert!(entries
+        .iter()
+        .all(|entry| matches!(entry.timeline_state, TimelineState::Ready(_))));
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.txn.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            TestTransaction::new(1, 0, 1).make_signed_transaction(),
+            TestTransaction::new(0, 0, 1).make_signed_transaction(),
+            TestTransaction::new(0, 1, 1).make_signed_transaction(),
+            TestTransaction::new(2, 1, 1).make_signed_transaction(),
+        ]
+    );
+}
+
+#[test]
+fn test_journal() {
+    let tmp_dir = TempPath::new();
+    let mut config = NodeConfig::random();
+    config.mempool.journal_path = Some(tmp_dir.path().to_path_buf());
+    {
+        let mut pool = CoreMempool::new(&config);
+        add_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+        add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+        add_txn(&mut pool, TestTransaction::new(1, 0, 2)).unwrap();
+        add_txn(&mut pool, TestTransaction::new(2, 3, 1)).unwrap();
+        // replaced and committed transactions are pruned
+        add_txn(&mut pool, TestTransaction::new(1, 0, 5)).unwrap();
+        pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    }
+
+    // restart
+    let mut pool = CoreMempool::new(&config);
+    let entries = pool.read_journal().unwrap();
+    // ready transactions come first, in the order of the timeline
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| entry.txn.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            TestTransaction::new(0, 1, 1).make_signed_transaction(),
+            TestTransaction::new(1, 0, 5).make_signed_transaction(),
+            TestTransaction::new(2, 3, 1).make_signed_transaction(),
+        ]
+    );
+    // entries are kept until they're restored or forgotten
+    assert_eq!(pool.read_journal().unwrap(), entries);
+
+    for entry in entries {
+        let db_sequence_number = if entry.txn.sender() == TestTransaction::get_address(0) {
+            1
+        } else {
+            0
+        };
+        let gas_price = entry.txn.gas_unit_price();
+        assert_eq!(
+            pool.restore_txn(entry, gas_price, db_sequence_number, false)
+                .code,
+            MempoolStatusCode::Accepted
+        );
+    }
+    let block = pool.get_block(10, HashSet::new());
+    assert_eq!(block.len(), 2);
+    // restored transactions are broadcast again
+    assert_eq!(pool.read_timeline(0, 10).0.len(), 2);
+    drop(pool);
+
+    // restored transactions are journaled again, with their system expiration time
+    let mut pool = CoreMempool::new(&config);
+    let mut entries = pool.read_journal().unwrap();
+    assert_eq!(entries.len(), 3);
+    let forgotten = entries.pop().unwrap();
+    pool.forget_journaled(&forgotten.txn.sender(), forgotten.txn.sequence_number());
+    assert_eq!(pool.read_journal().unwrap(), entries);
+    let mut entry = entries.remove(0);
+    entry.expiration_time = Duration::from_secs(0);
+    pool.restore_txn(entry, 1, 1, false);
+    assert_eq!(pool.get_block(10, HashSet::new()).len(), 1);
+    pool.gc();
+    assert!(pool.get_block(10, HashSet::new()).is_empty());
+}
+
 #[test]
 fn test_gc_ready_transaction() {
     let mut pool = setup_mempool().0;
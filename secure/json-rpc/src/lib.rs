@@ -558,6 +558,15 @@ mod test {
             unimplemented!()
         }
 
+        fn get_txn_by_hash(
+            &self,
+            _hash: HashValue,
+            _ledger_version: u64,
+            _fetch_events: bool,
+        ) -> Result<Option<TransactionWithProof>> {
+            unimplemented!()
+        }
+
         fn get_state_proof_with_ledger_info(
             &self,
             _known_version: u64,
@@ -619,5 +628,9 @@ mod test {
         fn get_block_timestamp(&self, _: u64) -> Result<u64> {
             unimplemented!()
         }
+
+        fn get_first_txn_version(&self) -> Result<Option<Version>> {
+            unimplemented!()
+        }
     }
 }
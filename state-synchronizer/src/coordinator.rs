@@ -8,6 +8,7 @@ use crate::{
     executor_proxy::ExecutorProxyTrait,
     network::{StateSynchronizerEvents, StateSynchronizerMsg, StateSynchronizerSender},
     peer_manager::{PeerManager, PeerScoreUpdateType},
+    state_chunk::{GetStateChunkRequest, GetStateChunkResponse},
     SynchronizerState,
 };
 use anyhow::{bail, ensure, format_err, Result};
@@ -17,14 +18,15 @@ use futures::{
     StreamExt,
 };
 use libra_config::{
-    config::{PeerNetworkId, RoleType, StateSyncConfig, UpstreamConfig},
+    config::{PeerNetworkId, RoleType, StateSyncConfig, SyncMode, UpstreamConfig},
     network_id::NodeNetworkId,
 };
+use libra_crypto::HashValue;
 use libra_logger::prelude::*;
 use libra_mempool::{CommitNotification, CommitResponse, CommittedTransaction};
 use libra_types::{
     contract_event::ContractEvent,
-    epoch_change::Verifier,
+    epoch_change::{EpochChangeProof, Verifier},
     ledger_info::LedgerInfoWithSignatures,
     transaction::{Transaction, TransactionListWithProof, Version},
     waypoint::Waypoint,
@@ -128,6 +130,22 @@ impl PendingLedgerInfos {
     fn target_li(&self) -> Option<LedgerInfoWithSignatures> {
         self.target_li.clone()
     }
+
+    fn highest_li(&self) -> Option<LedgerInfoWithSignatures> {
+        self.pending_li_queue.values().next_back().cloned()
+    }
+}
+
+// The state snapshot being downloaded in fast sync.
+struct StateSnapshotProgress {
+    // Verified LedgerInfo the snapshot is at
+    target_li: LedgerInfoWithSignatures,
+    // Verified epoch ending LedgerInfos from the local trusted epoch to the epoch of target_li
+    epoch_change_lis: Vec<LedgerInfoWithSignatures>,
+    // Key of the last account saved, from which the next chunk continues
+    known_key: Option<HashValue>,
+    // Number of state chunk requests for the target that failed or timed out in a row
+    num_failures: u64,
 }
 
 /// Coordination of synchronization process is driven by SyncCoordinator, which `start()` function
@@ -138,6 +156,10 @@ impl PendingLedgerInfos {
 /// higher within the timeout interval).
 /// * Validator: the ChunkRequests are generated on demand for a specific target LedgerInfo to
 /// synchronize to.
+///
+/// A FullNode with nothing but genesis in its storage can fast sync first if configured so: it
+/// downloads the account state at the highest LedgerInfo of a peer in chunks of accounts
+/// (GetStateChunkRequest), then keeps replaying the transactions after it as usual.
 pub(crate) struct SyncCoordinator<T> {
     // used to process client requests
     client_events: mpsc::UnboundedReceiver<CoordinatorMessage>,
@@ -164,12 +186,19 @@ pub(crate) struct SyncCoordinator<T> {
     sync_request: Option<SyncRequest>,
     // Ledger infos in the future that have not been committed yet
     pending_ledger_infos: PendingLedgerInfos,
+    // Chunk responses that arrived ahead of the chunks before them, to be applied in order
+    // (k, v) - (first version of the chunk, (peer, response))
+    chunk_buffer: BTreeMap<Version, (PeerNetworkId, GetChunkResponse)>,
     // Option initialization listener to be called when the coordinator is caught up with
     // its waypoint.
     initialization_listener: Option<oneshot::Sender<Result<()>>>,
     // queue of incoming long polling requests
     // peer will be notified about new chunk of transactions if it's available before expiry time
     subscriptions: HashMap<PeerNetworkId, PendingRequestInfo>,
+    // Whether the state snapshot is being downloaded instead of transactions.
+    fast_sync: bool,
+    // The state snapshot being downloaded, once its target is known.
+    state_snapshot: Option<StateSnapshotProgress>,
     executor_proxy: T,
 }
 
@@ -189,12 +218,16 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             RoleType::FullNode => config.tick_interval_ms + config.long_poll_timeout_ms,
             RoleType::Validator => 2 * config.tick_interval_ms,
         };
+        let fast_sync = config.sync_mode == SyncMode::FastSync
+            && role == RoleType::FullNode
+            && initial_state.highest_version_in_local_storage() == 0;
 
         Self {
             client_events,
             state_sync_to_mempool_sender,
             local_state: initial_state,
             pending_ledger_infos: PendingLedgerInfos::new(),
+            chunk_buffer: BTreeMap::new(),
             retry_timeout: Duration::from_millis(retry_timeout_val),
             config,
             role,
@@ -204,6 +237,8 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             subscriptions: HashMap::new(),
             sync_request: None,
             initialization_listener: None,
+            fast_sync,
+            state_snapshot: None,
             executor_proxy,
         }
     }
@@ -310,6 +345,29 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
                         .inc();
                 }
             }
+            StateSynchronizerMsg::GetStateChunkRequest(request) => {
+                if let Err(err) = self.process_state_chunk_request(peer.clone(), *request) {
+                    error!("[state sync] failed to serve state chunk request from {:?}, local LI version {}: {}", peer, self.local_state.highest_local_li.ledger_info().version(), err);
+                }
+            }
+            StateSynchronizerMsg::GetStateChunkResponse(response) => {
+                if let Err(err) = self.process_state_chunk_response(&peer, *response).await {
+                    // security log
+                    send_struct_log!(security_log(security_events::STATE_SYNC_INVALID_CHUNK)
+                        .data("from_peer", &peer)
+                        .data_display("error", &err));
+
+                    counters::APPLY_CHUNK_FAILURE
+                        .with_label_values(&[&*peer.peer_id().to_string()])
+                        .inc();
+                } else {
+                    self.peer_manager
+                        .update_score(&peer, PeerScoreUpdateType::Success);
+                    counters::APPLY_CHUNK_SUCCESS
+                        .with_label_values(&[&*peer.peer_id().to_string()])
+                        .inc();
+                }
+            }
         }
     }
 
@@ -697,9 +755,65 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
         Ok(target_li)
     }
 
-    /// * Issue a request for the next chunk.
-    /// * Validate and execute the transactions.
-    /// * Notify the clients in case a sync request has been completed.
+    /// Serves a chunk of the state snapshot at the requested target, or at the highest local LI
+    /// (limited to what an epoch change proof can carry) if there is no target yet.
+    fn process_state_chunk_request(
+        &mut self,
+        peer: PeerNetworkId,
+        request: GetStateChunkRequest,
+    ) -> Result<()> {
+        self.sync_state_with_local_storage()?;
+        debug!(
+            "[state sync] state chunk request: peer_id: {:?}, local li version: {}, req: {}",
+            peer,
+            self.local_state.highest_local_li.ledger_info().version(),
+            request,
+        );
+        let limit = std::cmp::min(request.limit, self.config.max_account_chunk_limit);
+
+        let (target_li, epoch_change_proof) = match request.target_li {
+            Some(target_li) => (target_li, None),
+            None => {
+                let mut target_li = self.local_state.highest_local_li.clone();
+                let mut proof = self.executor_proxy.get_epoch_change_proof(
+                    request.current_epoch,
+                    target_li.ledger_info().epoch(),
+                )?;
+                if proof.more {
+                    // Too many epochs to prove at once, so the snapshot is at the end of the last
+                    // epoch in the proof instead.
+                    target_li = proof
+                        .ledger_info_with_sigs
+                        .pop()
+                        .ok_or_else(|| format_err!("Empty EpochChangeProof"))?;
+                    proof.more = false;
+                }
+                (target_li, Some(proof))
+            }
+        };
+        let version = target_li.ledger_info().version();
+        ensure!(version > 0, "No state snapshot to serve beyond genesis.");
+
+        let txn_list_with_proof = self.executor_proxy.get_chunk(version - 1, 1, version)?;
+        let chunk =
+            self.executor_proxy
+                .get_account_state_chunk(version, request.known_key, limit)?;
+        let response =
+            GetStateChunkResponse::new(target_li, epoch_change_proof, txn_list_with_proof, chunk);
+        let msg = StateSynchronizerMsg::GetStateChunkResponse(Box::new(response));
+
+        let network_sender = self
+            .network_senders
+            .get_mut(&peer.network_id())
+            .expect("missing network sender");
+        if network_sender.send_to(peer.peer_id(), msg).is_err() {
+            error!("[state sync] failed to send p2p message");
+        }
+        Ok(())
+    }
+
+    /// * Buffer the chunk if it's ahead of the local storage and was requested in parallel.
+    /// * Otherwise apply it, followed by the buffered chunks that become sequential.
     async fn process_chunk_response(
         &mut self,
         peer: &PeerNetworkId,
@@ -709,16 +823,51 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             .with_label_values(&[&*peer.peer_id().to_string()])
             .inc();
         debug!("[state sync] Processing chunk response {}", response);
+        ensure!(
+            !self.fast_sync,
+            "[state sync] Chunk response from {:?} while fast syncing",
+            peer
+        );
+        let known_version = self.local_state.highest_version_in_local_storage();
+        let chunk_start_version = response
+            .txn_list_with_proof
+            .first_transaction_version
+            .ok_or_else(|| {
+                self.peer_manager
+                    .update_score(&peer, PeerScoreUpdateType::EmptyChunk);
+                format_err!("[state sync] Empty chunk from {:?}", peer)
+            })?;
+        self.peer_manager
+            .process_response(chunk_start_version, peer);
+
+        if chunk_start_version > known_version + 1 && self.should_buffer_chunk(chunk_start_version)
+        {
+            debug!(
+                "[state sync] Buffering chunk starting at {}, known version: {}",
+                chunk_start_version, known_version
+            );
+            self.chunk_buffer
+                .insert(chunk_start_version, (peer.clone(), response));
+            return Ok(());
+        }
+        self.apply_chunk(peer, response).await?;
+        self.apply_buffered_chunks().await;
+        Ok(())
+    }
+
+    /// * Issue a request for the next chunk.
+    /// * Validate and execute the transactions.
+    /// * Notify the clients in case a sync request has been completed.
+    async fn apply_chunk(
+        &mut self,
+        peer: &PeerNetworkId,
+        response: GetChunkResponse,
+    ) -> Result<()> {
         let txn_list_with_proof = response.txn_list_with_proof.clone();
         let known_version = self.local_state.highest_version_in_local_storage();
-        let chunk_start_version =
-            txn_list_with_proof
-                .first_transaction_version
-                .ok_or_else(|| {
-                    self.peer_manager
-                        .update_score(&peer, PeerScoreUpdateType::EmptyChunk);
-                    format_err!("[state sync] Empty chunk from {:?}", peer)
-                })?;
+        let chunk_start_version = txn_list_with_proof
+            .first_transaction_version
+            .ok_or_else(|| format_err!("[state sync] Empty chunk from {:?}", peer))?;
 
         if chunk_start_version != known_version + 1 {
             // Old / wrong chunk.
@@ -785,6 +934,46 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             .await
     }
 
+    /// Whether a chunk starting ahead of the local storage is one of the chunks requested in
+    /// parallel, which are within `max_in_flight_chunk_requests` chunks of the local storage.
+    fn should_buffer_chunk(&self, chunk_start_version: Version) -> bool {
+        let known_version = self.local_state.highest_version_in_local_storage();
+        let max_start_version = known_version
+            + self.config.max_in_flight_chunk_requests.saturating_sub(1) * self.config.chunk_limit
+            + 1;
+        chunk_start_version <= max_start_version
+            && self
+                .peer_manager
+                .get_last_request_time(chunk_start_version)
+                .is_some()
+    }
+
+    /// Applies the buffered chunks that continue the local storage, dropping the ones that are
+    /// behind it.
+    async fn apply_buffered_chunks(&mut self) {
+        loop {
+            let known_version = self.local_state.highest_version_in_local_storage();
+            self.chunk_buffer = self.chunk_buffer.split_off(&(known_version + 1));
+            let (peer, response) = match self.chunk_buffer.remove(&(known_version + 1)) {
+                Some(buffered) => buffered,
+                None => return,
+            };
+            if let Err(err) = self.apply_chunk(&peer, response).await {
+                error!(
+                    "[state sync] Failed to apply buffered chunk from {:?}: {}",
+                    peer, err
+                );
+                counters::APPLY_CHUNK_FAILURE
+                    .with_label_values(&[&*peer.peer_id().to_string()])
+                    .inc();
+                return;
+            }
+            counters::APPLY_CHUNK_SUCCESS
+                .with_label_values(&[&*peer.peer_id().to_string()])
+                .inc();
+        }
+    }
+
     /// Processing chunk responses that carry a LedgerInfo that should be verified using the
     /// current local trusted validator set.
     fn process_response_with_verifiable_li(
@@ -863,6 +1052,169 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
         self.validate_and_store_chunk(txn_list_with_proof, waypoint_li, end_of_epoch_li)
     }
 
+    /// * Validate and save the chunk of the state snapshot.
+    /// * Issue a request for the next chunk, or switch to replaying transactions once the snapshot
+    /// is complete.
+    async fn process_state_chunk_response(
+        &mut self,
+        peer: &PeerNetworkId,
+        response: GetStateChunkResponse,
+    ) -> Result<()> {
+        counters::RESPONSES_RECEIVED
+            .with_label_values(&[&*peer.peer_id().to_string()])
+            .inc();
+        debug!("[state sync] Processing state chunk response {}", response);
+        ensure!(
+            self.fast_sync,
+            "[state sync] State chunk response from {:?} while not fast syncing",
+            peer
+        );
+
+        let num_accounts = response.chunk.account_blobs.len();
+        let txn_list_with_proof = response.txn_list_with_proof.clone();
+        let complete = match self.validate_and_store_state_chunk(response) {
+            Ok(complete) => complete,
+            Err(e) => {
+                self.peer_manager
+                    .update_score(peer, PeerScoreUpdateType::InvalidChunk);
+                self.process_state_snapshot_failure();
+                bail!("[state sync] failed to apply state chunk: {}", e);
+            }
+        };
+        counters::STATE_SYNC_ACCOUNTS_RESTORED.inc_by(num_accounts as i64);
+
+        if !complete {
+            return self.send_chunk_request(
+                self.local_state.highest_version_in_local_storage(),
+                self.local_state.epoch(),
+            );
+        }
+
+        let snapshot = self
+            .state_snapshot
+            .take()
+            .ok_or_else(|| format_err!("[state sync] No state snapshot in progress"))?;
+        let version = snapshot.target_li.ledger_info().version();
+        self.executor_proxy.finish_state_snapshot(
+            snapshot.target_li,
+            snapshot.epoch_change_lis,
+            txn_list_with_proof,
+        )?;
+        self.fast_sync = false;
+        info!("[state sync] Fast synced to version {}", version);
+
+        self.process_commit(vec![], None).await?;
+        self.send_chunk_request(
+            self.local_state.highest_version_in_local_storage(),
+            self.local_state.epoch(),
+        )
+    }
+
+    /// Verifies the target of the state snapshot if it's not known yet, then saves the chunk.
+    /// Returns whether the snapshot is complete.
+    fn validate_and_store_state_chunk(&mut self, response: GetStateChunkResponse) -> Result<bool> {
+        let GetStateChunkResponse {
+            target_li,
+            epoch_change_proof,
+            txn_list_with_proof,
+            chunk,
+        } = response;
+        if self.state_snapshot.is_none() {
+            let epoch_change_lis =
+                self.verify_state_snapshot_target(&target_li, epoch_change_proof)?;
+            self.state_snapshot = Some(StateSnapshotProgress {
+                target_li: target_li.clone(),
+                epoch_change_lis,
+                known_key: None,
+                num_failures: 0,
+            });
+        }
+        let snapshot = self
+            .state_snapshot
+            .as_mut()
+            .expect("[state sync] State snapshot must be set.");
+        ensure!(
+            snapshot.target_li == target_li,
+            "State chunk at {} but the state snapshot is at {}",
+            target_li.ledger_info(),
+            snapshot.target_li.ledger_info(),
+        );
+
+        let complete = self.executor_proxy.save_account_state_chunk(
+            &snapshot.target_li,
+            &txn_list_with_proof,
+            chunk,
+        )?;
+        snapshot.known_key = self
+            .executor_proxy
+            .get_state_snapshot_progress(target_li.ledger_info().version())?;
+        snapshot.num_failures = 0;
+        Ok(complete)
+    }
+
+    /// Gives up on the target of the state snapshot once too many requests for it failed in a
+    /// row, e.g. because the peers pruned its state, so that the next request asks for a new
+    /// target. The accounts already saved for the old target are not reused.
+    fn process_state_snapshot_failure(&mut self) {
+        let max_failures = self.config.max_state_snapshot_failures;
+        if let Some(snapshot) = self.state_snapshot.as_mut() {
+            snapshot.num_failures += 1;
+            if snapshot.num_failures >= max_failures {
+                warn!(
+                    "[state sync] Giving up on the state snapshot at {} after {} failures",
+                    snapshot.target_li.ledger_info(),
+                    snapshot.num_failures
+                );
+                self.state_snapshot = None;
+                counters::STATE_SNAPSHOT_TARGET_RESETS.inc();
+            }
+        }
+    }
+
+    /// Verifies the target of the state snapshot with the epoch change proof starting from the
+    /// local trusted epoch, and with the waypoint if it's not reached yet. Returns the verified
+    /// epoch ending LIs that are not in the local storage yet.
+    fn verify_state_snapshot_target(
+        &self,
+        target_li: &LedgerInfoWithSignatures,
+        epoch_change_proof: Option<EpochChangeProof>,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let proof = epoch_change_proof
+            .ok_or_else(|| format_err!("No epoch change proof for the state snapshot target"))?;
+        let trusted_epoch = &self.local_state.trusted_epoch;
+        let target_epoch_state = if proof.ledger_info_with_sigs.is_empty() {
+            trusted_epoch.clone()
+        } else {
+            proof
+                .verify(trusted_epoch)?
+                .ledger_info()
+                .next_epoch_state()
+                .cloned()
+                .ok_or_else(|| format_err!("LedgerInfo doesn't carry a ValidatorSet"))?
+        };
+        target_epoch_state.verify(target_li)?;
+
+        let epoch_change_lis: Vec<_> = proof
+            .ledger_info_with_sigs
+            .into_iter()
+            .filter(|li| !trusted_epoch.is_ledger_info_stale(li.ledger_info()))
+            .collect();
+        if !self.is_initialized() && self.waypoint.version() <= target_li.ledger_info().version() {
+            let waypoint_li = epoch_change_lis
+                .iter()
+                .chain(std::iter::once(target_li))
+                .find(|li| li.ledger_info().version() == self.waypoint.version())
+                .ok_or_else(|| {
+                    format_err!(
+                        "No LedgerInfo at the waypoint version {}",
+                        self.waypoint.version()
+                    )
+                })?;
+            self.waypoint.verify(waypoint_li.ledger_info())?;
+        }
+        Ok(epoch_change_lis)
+    }
+
     // Assumes that the target LI has been already verified by the caller.
     fn validate_and_store_chunk(
         &mut self,
@@ -935,6 +1287,9 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             if SystemTime::now().duration_since(tst).is_ok() {
                 self.peer_manager
                     .process_timeout(known_version + 1, self.role == RoleType::Validator);
+                if self.fast_sync {
+                    self.process_state_snapshot_failure();
+                }
                 if let Err(e) = self.send_chunk_request(known_version, self.local_state.epoch()) {
                     error!("[state sync] Failed to send chunk request: {}", e);
                 }
@@ -947,11 +1302,9 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
     /// (might be chosen optimistically).
     /// The request includes a target for Validator and a non-zero timeout for a FullNode.
     fn send_chunk_request(&mut self, known_version: u64, known_epoch: u64) -> Result<()> {
-        let peer = self
-            .peer_manager
-            .pick_peer()
-            .ok_or_else(|| format_err!("No peers found for chunk request."))?;
-
+        if self.fast_sync {
+            return self.send_state_chunk_request(known_version);
+        }
         let target = if !self.is_initialized() {
             let waypoint_version = self.waypoint.version();
             TargetType::Waypoint(waypoint_version)
@@ -978,6 +1331,84 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
             }
         };
 
+        match self.pipeline_target(known_version, known_epoch) {
+            Some(target_li) => {
+                self.send_pipelined_chunk_requests(known_version, known_epoch, target_li)
+            }
+            None => self.send_chunk_request_with_target(known_version, known_epoch, target),
+        }
+    }
+
+    /// The LedgerInfo to fetch chunks towards in parallel, if `max_in_flight_chunk_requests`
+    /// allows it: the target of the sync request for a Validator, the highest LedgerInfo known
+    /// from the peers for a FullNode. It must be in `known_epoch`, so that all the chunks up to it
+    /// can be requested (and verified) without knowing about later epochs.
+    fn pipeline_target(
+        &self,
+        known_version: u64,
+        known_epoch: u64,
+    ) -> Option<LedgerInfoWithSignatures> {
+        if self.config.max_in_flight_chunk_requests <= 1 || !self.is_initialized() {
+            return None;
+        }
+        let target_li = match self.sync_request.as_ref() {
+            Some(sync_req) => sync_req.target.clone(),
+            None => self.pending_ledger_infos.highest_li()?,
+        };
+        if target_li.ledger_info().epoch() == known_epoch
+            && target_li.ledger_info().version() > known_version
+        {
+            Some(target_li)
+        } else {
+            None
+        }
+    }
+
+    /// Requests the chunks of `chunk_limit` transactions after `known_version` up to `target_li`,
+    /// at most `max_in_flight_chunk_requests` of them, skipping the ones already buffered or in
+    /// flight. Each request goes to a peer picked independently, so they are served in parallel.
+    fn send_pipelined_chunk_requests(
+        &mut self,
+        known_version: u64,
+        known_epoch: u64,
+        target_li: LedgerInfoWithSignatures,
+    ) -> Result<()> {
+        for i in 0..self.config.max_in_flight_chunk_requests {
+            let chunk_start_version = known_version + 1 + i * self.config.chunk_limit;
+            if chunk_start_version > target_li.ledger_info().version() {
+                break;
+            }
+            if self.chunk_buffer.contains_key(&chunk_start_version)
+                || self
+                    .peer_manager
+                    .is_request_in_flight(chunk_start_version, self.retry_timeout)
+            {
+                continue;
+            }
+            // Timeouts of the first chunk are handled by `check_progress`, the ones of the chunks
+            // after it are found here.
+            if i > 0 {
+                self.peer_manager.process_timeout(chunk_start_version, true);
+            }
+            self.send_chunk_request_with_target(
+                chunk_start_version - 1,
+                known_epoch,
+                TargetType::TargetLedgerInfo(target_li.clone()),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn send_chunk_request_with_target(
+        &mut self,
+        known_version: u64,
+        known_epoch: u64,
+        target: TargetType,
+    ) -> Result<()> {
+        let peer = self
+            .peer_manager
+            .pick_peer()
+            .ok_or_else(|| format_err!("No peers found for chunk request."))?;
         let req = GetChunkRequest::new(known_version, known_epoch, self.config.chunk_limit, target);
         debug!(
             "[state sync] request next chunk. peer_id: {:?}, chunk req: {}",
@@ -998,6 +1429,41 @@ impl<T: ExecutorProxyTrait> SyncCoordinator<T> {
         Ok(())
     }
 
+    /// Sends a request for the next chunk of the state snapshot, leaving the choice of its target
+    /// to the peer if it's not known yet.
+    fn send_state_chunk_request(&mut self, known_version: u64) -> Result<()> {
+        let peer = self
+            .peer_manager
+            .pick_peer()
+            .ok_or_else(|| format_err!("No peers found for state chunk request."))?;
+
+        let (target_li, known_key) = match self.state_snapshot.as_ref() {
+            Some(snapshot) => (Some(snapshot.target_li.clone()), snapshot.known_key),
+            None => (None, None),
+        };
+        // The last account of a chunk is only persisted along with the next chunk, so a chunk
+        // needs at least two accounts to make progress.
+        let limit = std::cmp::max(self.config.account_chunk_limit, 2);
+        let req = GetStateChunkRequest::new(self.local_state.epoch(), target_li, known_key, limit);
+        debug!(
+            "[state sync] request next state chunk. peer_id: {:?}, state chunk req: {}",
+            peer, req,
+        );
+        let msg = StateSynchronizerMsg::GetStateChunkRequest(Box::new(req));
+        self.peer_manager
+            .process_request(known_version + 1, peer.clone());
+        let sender = self
+            .network_senders
+            .get_mut(&peer.network_id())
+            .expect("missing network sender for peer");
+        let peer_id = peer.peer_id();
+        sender.send_to(peer_id, msg)?;
+        counters::REQUESTS_SENT
+            .with_label_values(&[&*peer_id.to_string()])
+            .inc();
+        Ok(())
+    }
+
     fn deliver_subscription(
         &mut self,
         peer: PeerNetworkId,
@@ -69,6 +69,25 @@ pub static STATE_SYNC_TXN_REPLAYED: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count the overall number of accounts state synchronizer has restored with fast sync since last
+/// restart.
+pub static STATE_SYNC_ACCOUNTS_RESTORED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_state_sync_accounts_restored_total",
+        "Number of accounts the state synchronizer has restored with fast sync since last restart"
+    )
+    .unwrap()
+});
+
+/// Number of times fast sync gave up on the target of the state snapshot and asked for a new one.
+pub static STATE_SNAPSHOT_TARGET_RESETS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_state_sync_state_snapshot_target_resets_total",
+        "Number of times fast sync gave up on the target of the state snapshot"
+    )
+    .unwrap()
+});
+
 /// Number of peers that are currently active and upstream.
 /// They are the set of nodes a node can make sync requests to
 pub static ACTIVE_UPSTREAM_PEERS: Lazy<IntGauge> = Lazy::new(|| {
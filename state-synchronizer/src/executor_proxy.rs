@@ -1,17 +1,24 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::SynchronizerState;
-use anyhow::{format_err, Result};
+use crate::{state_chunk::AccountStateChunkWithProof, SynchronizerState};
+use anyhow::{ensure, format_err, Result};
 use executor_types::{ChunkExecutor, ExecutedTrees};
 use itertools::Itertools;
+use libra_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use libra_types::{
     account_state::AccountState,
     contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
     move_resource::MoveStorage,
     on_chain_config::{config_address, OnChainConfigPayload, ON_CHAIN_CONFIG_REGISTRY},
-    transaction::TransactionListWithProof,
+    proof::SparseMerkleRangeProof,
+    transaction::{TransactionListWithProof, Version},
+};
+use libradb::{
+    backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler},
+    GetRestoreHandler, LibraDB,
 };
 use std::{collections::HashSet, convert::TryFrom, sync::Arc};
 use storage_interface::DbReader;
@@ -45,6 +52,42 @@ pub trait ExecutorProxyTrait: Send {
     /// Get ledger info at an epoch boundary version.
     fn get_epoch_ending_ledger_info(&self, version: u64) -> Result<LedgerInfoWithSignatures>;
 
+    /// Get the ledger infos ending the epochs in [start_epoch, end_epoch).
+    fn get_epoch_change_proof(&self, start_epoch: u64, end_epoch: u64) -> Result<EpochChangeProof>;
+
+    /// Gets at most `limit` accounts of the state tree at `version`, starting with the first one
+    /// after `known_key`, or the first one of the tree if it's `None`.
+    fn get_account_state_chunk(
+        &self,
+        version: Version,
+        known_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof>;
+
+    /// Gets the key of the last account saved by the state snapshot restore at `version`, from
+    /// which the next chunk should continue. The restore survives restarts.
+    fn get_state_snapshot_progress(&self, version: Version) -> Result<Option<HashValue>>;
+
+    /// Verifies and saves a chunk of the state snapshot at the version of `verified_target_li`,
+    /// whose state root hash is proven by the transaction at that version in
+    /// `txn_list_with_proof`. Returns whether the snapshot is complete.
+    fn save_account_state_chunk(
+        &mut self,
+        verified_target_li: &LedgerInfoWithSignatures,
+        txn_list_with_proof: &TransactionListWithProof,
+        chunk: AccountStateChunkWithProof,
+    ) -> Result<bool>;
+
+    /// Makes the completed state snapshot the latest state in storage: saves the transaction at
+    /// the version of `verified_target_li` along with the frozen subtrees of the accumulator
+    /// before it, the epoch ending ledger infos leading to it and the target itself.
+    fn finish_state_snapshot(
+        &mut self,
+        verified_target_li: LedgerInfoWithSignatures,
+        epoch_change_lis: Vec<LedgerInfoWithSignatures>,
+        txn_list_with_proof: TransactionListWithProof,
+    ) -> Result<()>;
+
     /// Load all on-chain configs from storage
     /// Note: this method is being exposed as executor proxy trait temporarily because storage read is currently
     /// using the tonic storage read client, which needs the tokio runtime to block on with no runtime/async issues
@@ -58,6 +101,8 @@ pub trait ExecutorProxyTrait: Send {
 
 pub(crate) struct ExecutorProxy {
     storage: Arc<dyn DbReader>,
+    backup_handler: BackupHandler,
+    restore_handler: RestoreHandler,
     executor: Box<dyn ChunkExecutor>,
     reconfig_subscriptions: Vec<ReconfigSubscription>,
     on_chain_configs: OnChainConfigPayload,
@@ -65,10 +110,13 @@ pub(crate) struct ExecutorProxy {
 
 impl ExecutorProxy {
     pub(crate) fn new(
-        storage: Arc<dyn DbReader>,
+        libra_db: Arc<LibraDB>,
         executor: Box<dyn ChunkExecutor>,
         mut reconfig_subscriptions: Vec<ReconfigSubscription>,
     ) -> Self {
+        let backup_handler = libra_db.get_backup_handler();
+        let restore_handler = libra_db.get_restore_handler();
+        let storage: Arc<dyn DbReader> = libra_db;
         let on_chain_configs = Self::fetch_all_configs(&*storage)
             .expect("[state sync] Failed initial read of on-chain configs");
         for subscription in reconfig_subscriptions.iter_mut() {
@@ -78,12 +126,31 @@ impl ExecutorProxy {
         }
         Self {
             storage,
+            backup_handler,
+            restore_handler,
             executor,
             reconfig_subscriptions,
             on_chain_configs,
         }
     }
 
+    /// Verifies the transaction at the version of `verified_target_li` and returns its state root
+    /// hash.
+    fn verify_state_root_hash(
+        verified_target_li: &LedgerInfoWithSignatures,
+        txn_list_with_proof: &TransactionListWithProof,
+    ) -> Result<HashValue> {
+        let version = verified_target_li.ledger_info().version();
+        ensure!(
+            txn_list_with_proof.len() == 1,
+            "[state sync] Expect the transaction at version {} only, got {}.",
+            version,
+            txn_list_with_proof.len(),
+        );
+        txn_list_with_proof.verify(verified_target_li.ledger_info(), Some(version))?;
+        Ok(txn_list_with_proof.proof.transaction_infos()[0].state_root_hash())
+    }
+
     // TODO make this into more general trait method in `on_chain_config`
     // once `StorageRead` trait is replaced with `DbReader` and `batch_fetch_config` method is no longer async
     fn fetch_all_configs(storage: &dyn DbReader) -> Result<OnChainConfigPayload> {
@@ -176,6 +243,105 @@ impl ExecutorProxyTrait for ExecutorProxy {
         self.storage.get_epoch_ending_ledger_info(version)
     }
 
+    fn get_epoch_change_proof(&self, start_epoch: u64, end_epoch: u64) -> Result<EpochChangeProof> {
+        self.storage
+            .get_epoch_ending_ledger_infos(start_epoch, end_epoch)
+    }
+
+    fn get_account_state_chunk(
+        &self,
+        version: Version,
+        known_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof> {
+        let account_blobs = self
+            .backup_handler
+            .get_account_iter_from(version, known_key.unwrap_or_else(HashValue::zero))?
+            .skip_while(|res| match (res, known_key) {
+                (Ok((key, _blob)), Some(known_key)) => *key <= known_key,
+                _ => false,
+            })
+            .take(limit as usize)
+            .collect::<Result<Vec<_>>>()?;
+        let last_key = account_blobs
+            .last()
+            .map(|(key, _blob)| *key)
+            .ok_or_else(|| {
+                format_err!("No accounts after {:?} at version {}", known_key, version)
+            })?;
+        let proof = self
+            .backup_handler
+            .get_account_state_range_proof(last_key, version)?;
+        Ok(AccountStateChunkWithProof::new(account_blobs, proof))
+    }
+
+    fn get_state_snapshot_progress(&self, version: Version) -> Result<Option<HashValue>> {
+        self.restore_handler.get_state_restore_progress(version)
+    }
+
+    fn save_account_state_chunk(
+        &mut self,
+        verified_target_li: &LedgerInfoWithSignatures,
+        txn_list_with_proof: &TransactionListWithProof,
+        chunk: AccountStateChunkWithProof,
+    ) -> Result<bool> {
+        let version = verified_target_li.ledger_info().version();
+        let state_root_hash =
+            Self::verify_state_root_hash(verified_target_li, txn_list_with_proof)?;
+
+        // A new receiver picks up from the last account persisted, which can be before the last
+        // account added, since the rightmost leaf added is only persisted with the next chunk.
+        let progress = self.restore_handler.get_state_restore_progress(version)?;
+        let complete = is_last_chunk(&chunk.proof);
+        let account_blobs: Vec<_> = chunk
+            .account_blobs
+            .into_iter()
+            .filter(|(key, _blob)| progress.map_or(true, |progress| *key > progress))
+            .collect();
+        if account_blobs.is_empty() {
+            return Ok(false);
+        }
+
+        let mut receiver = self
+            .restore_handler
+            .get_state_restore_receiver(version, state_root_hash)?;
+        receiver.add_chunk(account_blobs, chunk.proof)?;
+        if complete {
+            receiver.finish()?;
+        }
+        Ok(complete)
+    }
+
+    fn finish_state_snapshot(
+        &mut self,
+        verified_target_li: LedgerInfoWithSignatures,
+        mut epoch_change_lis: Vec<LedgerInfoWithSignatures>,
+        txn_list_with_proof: TransactionListWithProof,
+    ) -> Result<()> {
+        let version = verified_target_li.ledger_info().version();
+        Self::verify_state_root_hash(&verified_target_li, &txn_list_with_proof)?;
+
+        let TransactionListWithProof {
+            transactions,
+            proof,
+            ..
+        } = txn_list_with_proof;
+        self.restore_handler
+            .confirm_or_save_frozen_subtrees(version, proof.left_siblings())?;
+        let (_accumulator_proof, txn_infos) = proof.unpack();
+        self.restore_handler
+            .save_transactions(version, &transactions, &txn_infos)?;
+        epoch_change_lis.push(verified_target_li);
+        self.restore_handler.save_ledger_infos(&epoch_change_lis)?;
+
+        // Everything changed, so all the subscribers are notified.
+        self.on_chain_configs = Self::fetch_all_configs(&*self.storage)?;
+        for subscription in self.reconfig_subscriptions.iter_mut() {
+            subscription.publish(self.on_chain_configs.clone())?;
+        }
+        Ok(())
+    }
+
     fn load_on_chain_configs(&mut self) -> Result<()> {
         self.on_chain_configs = Self::fetch_all_configs(&*self.storage)?;
         Ok(())
@@ -222,3 +388,12 @@ impl ExecutorProxyTrait for ExecutorProxy {
         Ok(())
     }
 }
+
+/// Whether a chunk of accounts is the last one of the tree, i.e., there is nothing on the right of
+/// its last account.
+pub(crate) fn is_last_chunk(proof: &SparseMerkleRangeProof) -> bool {
+    proof
+        .right_siblings()
+        .iter()
+        .all(|hash| *hash == *SPARSE_MERKLE_PLACEHOLDER_HASH)
+}
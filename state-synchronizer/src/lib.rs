@@ -18,6 +18,7 @@ mod counters;
 mod executor_proxy;
 pub mod network;
 mod peer_manager;
+mod state_chunk;
 mod synchronizer;
 
 /// The state distinguishes between the following fields:
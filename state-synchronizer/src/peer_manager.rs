@@ -15,21 +15,41 @@ use rand::{
 };
 use std::{
     collections::{BTreeMap, HashMap},
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
 const MAX_SCORE: f64 = 100.0;
 const MIN_SCORE: f64 = 1.0;
+// Weight of the latest response time in the moving average of response times of a peer.
+const RESPONSE_TIME_WEIGHT: f64 = 0.2;
+// A peer whose responses take this long on average is half as likely to be picked as a peer
+// responding immediately with the same score.
+const RESPONSE_TIME_UNIT: Duration = Duration::from_millis(100);
 
 #[derive(Default, Debug, Clone)]
 pub struct PeerInfo {
     is_alive: bool,
     score: f64,
+    // Moving average of the time it takes the peer to respond, None until its first response.
+    avg_response_time: Option<Duration>,
 }
 
 impl PeerInfo {
     pub fn new(is_alive: bool, score: f64) -> Self {
-        Self { is_alive, score }
+        Self {
+            is_alive,
+            score,
+            avg_response_time: None,
+        }
+    }
+
+    // The chance that the peer is picked: proportional to its score, and inversely proportional to
+    // its average response time (in RESPONSE_TIME_UNIT, plus one).
+    fn weight(&self) -> f64 {
+        let response_time = self
+            .avg_response_time
+            .map_or(0.0, |time| time.as_secs_f64());
+        self.score / (1.0 + response_time / RESPONSE_TIME_UNIT.as_secs_f64())
     }
 }
 
@@ -137,7 +157,8 @@ impl PeerManager {
 
     // Updates the information used to select a peer to send a chunk request to:
     // * eligible_peers
-    // * weighted_index: the chance that a peer is selected from `eligible_peers` is weighted by its score
+    // * weighted_index: the chance that a peer is selected from `eligible_peers` is weighted by its
+    //   score and response time
     fn update_peer_selection_data(&mut self) {
         let active_peers = self.get_active_upstream_peers();
         counters::ACTIVE_UPSTREAM_PEERS.set(active_peers.len() as i64);
@@ -148,7 +169,7 @@ impl PeerManager {
             .into_iter()
             .map(|(peer, peer_info)| {
                 eligible_peers.push(peer.clone());
-                peer_info.score
+                peer_info.weight()
             })
             .collect();
         self.eligible_peers = eligible_peers;
@@ -222,6 +243,13 @@ impl PeerManager {
             .map(|req_info| req_info.last_request_time)
     }
 
+    /// Whether the request for `version` was sent less than `timeout` ago.
+    pub fn is_request_in_flight(&self, version: u64, timeout: Duration) -> bool {
+        self.get_last_request_time(version)
+            .and_then(|tst| tst.checked_add(timeout))
+            .map_or(false, |deadline| SystemTime::now() < deadline)
+    }
+
     pub fn get_first_request_time(&self, version: u64) -> Option<SystemTime> {
         self.requests
             .get(&version)
@@ -232,6 +260,30 @@ impl PeerManager {
         self.requests = self.requests.split_off(&(version + 1));
     }
 
+    /// Updates the response time of `peer` if it's the peer the request for `version` was last
+    /// sent to.
+    pub fn process_response(&mut self, version: u64, peer: &PeerNetworkId) {
+        let response_time = match self.requests.get(&version) {
+            Some(request) if &request.last_request_peer == peer => {
+                match SystemTime::now().duration_since(request.last_request_time) {
+                    Ok(response_time) => response_time,
+                    Err(_) => return,
+                }
+            }
+            _ => return,
+        };
+        if let Some(peer_info) = self.peers.get_mut(peer) {
+            peer_info.avg_response_time = Some(match peer_info.avg_response_time {
+                Some(avg) => {
+                    avg.mul_f64(1.0 - RESPONSE_TIME_WEIGHT)
+                        + response_time.mul_f64(RESPONSE_TIME_WEIGHT)
+                }
+                None => response_time,
+            });
+            self.update_peer_selection_data();
+        }
+    }
+
     pub fn process_timeout(&mut self, version: u64, penalize: bool) {
         if !penalize {
             return;
@@ -263,4 +315,9 @@ impl PeerManager {
     pub fn peer_score(&self, peer: &PeerNetworkId) -> Option<f64> {
         self.peers.get(peer).map(|p| p.score)
     }
+
+    #[cfg(test)]
+    pub fn peer_response_time(&self, peer: &PeerNetworkId) -> Option<Duration> {
+        self.peers.get(peer).and_then(|p| p.avg_response_time)
+    }
 }
@@ -0,0 +1,140 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Messages of fast sync, in which a node downloads the account state at a recent version instead
+//! of replaying all the transactions before it.
+
+use libra_crypto::HashValue;
+use libra_types::{
+    account_state_blob::AccountStateBlob, epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures, proof::SparseMerkleRangeProof,
+    transaction::TransactionListWithProof,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+/// A range of accounts of the state tree, ordered by key, with the proof that they are the
+/// leftmost accounts of the tree up to the last one in the range.
+pub struct AccountStateChunkWithProof {
+    pub account_blobs: Vec<(HashValue, AccountStateBlob)>,
+    pub proof: SparseMerkleRangeProof,
+}
+
+impl AccountStateChunkWithProof {
+    pub fn new(
+        account_blobs: Vec<(HashValue, AccountStateBlob)>,
+        proof: SparseMerkleRangeProof,
+    ) -> Self {
+        Self {
+            account_blobs,
+            proof,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct GetStateChunkRequest {
+    /// Epoch of the requester, the start of the epoch change proof if `target_li` is not set.
+    pub current_epoch: u64,
+    /// The LedgerInfo whose state is being downloaded. If not set, the responder picks its highest
+    /// LedgerInfo (or the end of the epoch if it's too far away) and proves it with an epoch
+    /// change proof.
+    pub target_li: Option<LedgerInfoWithSignatures>,
+    /// The response should start with the first account after it, or the first account of the
+    /// tree if not set.
+    pub known_key: Option<HashValue>,
+    /// Max number of accounts in the response.
+    pub limit: u64,
+}
+
+impl GetStateChunkRequest {
+    pub fn new(
+        current_epoch: u64,
+        target_li: Option<LedgerInfoWithSignatures>,
+        known_key: Option<HashValue>,
+        limit: u64,
+    ) -> Self {
+        Self {
+            current_epoch,
+            target_li,
+            known_key,
+            limit,
+        }
+    }
+}
+
+impl fmt::Display for GetStateChunkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[StateChunkRequest: epoch: {}, target: {}, known key: {}, limit: {}]",
+            self.current_epoch,
+            self.target_li
+                .as_ref()
+                .map_or("None".to_string(), |li| li.ledger_info().to_string()),
+            self.known_key
+                .map_or("None".to_string(), |key| format!("{:x}", key)),
+            self.limit,
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct GetStateChunkResponse {
+    /// The LedgerInfo the state is downloaded at.
+    pub target_li: LedgerInfoWithSignatures,
+    /// Proves `target_li` starting from the epoch of the requester, only carried if the request
+    /// doesn't specify the target.
+    pub epoch_change_proof: Option<EpochChangeProof>,
+    /// The transaction at the version of `target_li`, proving the state root hash and carrying
+    /// the frozen subtrees of the transaction accumulator.
+    pub txn_list_with_proof: TransactionListWithProof,
+    /// Accounts with proof against the state root hash.
+    pub chunk: AccountStateChunkWithProof,
+}
+
+impl GetStateChunkResponse {
+    pub fn new(
+        target_li: LedgerInfoWithSignatures,
+        epoch_change_proof: Option<EpochChangeProof>,
+        txn_list_with_proof: TransactionListWithProof,
+        chunk: AccountStateChunkWithProof,
+    ) -> Self {
+        Self {
+            target_li,
+            epoch_change_proof,
+            txn_list_with_proof,
+            chunk,
+        }
+    }
+}
+
+impl fmt::Display for GetStateChunkResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let accounts_repr = match (
+            self.chunk.account_blobs.first(),
+            self.chunk.account_blobs.last(),
+        ) {
+            (Some((first, _)), Some((last, _))) => format!(
+                "{} accounts [{:x} - {:x}]",
+                self.chunk.account_blobs.len(),
+                first,
+                last
+            ),
+            _ => "empty".to_string(),
+        };
+        write!(
+            f,
+            "[StateChunkResponse: target li: {}, epoch change proof: {}, accounts: {}]",
+            self.target_li.ledger_info(),
+            self.epoch_change_proof
+                .as_ref()
+                .map_or("None".to_string(), |proof| format!(
+                    "{} LIs",
+                    proof.ledger_info_with_sigs.len()
+                )),
+            accounts_repr,
+        )
+    }
+}
@@ -23,13 +23,13 @@ use libra_types::{
     contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures, transaction::Transaction,
     waypoint::Waypoint,
 };
+use libradb::LibraDB;
 use std::{
     boxed::Box,
     collections::HashMap,
     sync::Arc,
     time::{Duration, SystemTime},
 };
-use storage_interface::DbReader;
 use subscription_service::ReconfigSubscription;
 use tokio::{
     runtime::{Builder, Runtime},
@@ -50,7 +50,7 @@ impl StateSynchronizer {
             StateSynchronizerEvents,
         )>,
         state_sync_to_mempool_sender: mpsc::Sender<CommitNotification>,
-        storage: Arc<dyn DbReader>,
+        libra_db: Arc<LibraDB>,
         executor: Box<dyn ChunkExecutor>,
         config: &NodeConfig,
         waypoint: Waypoint,
@@ -63,7 +63,7 @@ impl StateSynchronizer {
             .build()
             .expect("[state synchronizer] failed to create runtime");
 
-        let executor_proxy = ExecutorProxy::new(storage, executor, reconfig_event_subscriptions);
+        let executor_proxy = ExecutorProxy::new(libra_db, executor, reconfig_event_subscriptions);
         Self::bootstrap_with_executor_proxy(
             runtime,
             network,
@@ -4,18 +4,21 @@
 use crate::{
     executor_proxy::ExecutorProxyTrait,
     network::{StateSynchronizerEvents, StateSynchronizerSender},
+    state_chunk::AccountStateChunkWithProof,
     tests::mock_storage::MockStorage,
     StateSyncClient, StateSynchronizer, SynchronizerState,
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use channel::{libra_channel, message_queues::QueueStyle};
 use executor_types::ExecutedTrees;
 use futures::{executor::block_on, future::FutureExt, StreamExt};
 use libra_config::{
-    config::{GossipConfig, RoleType},
+    config::{GossipConfig, RoleType, StateSyncConfig, SyncMode},
     network_id::{NetworkContext, NetworkId, NodeNetworkId},
 };
-use libra_crypto::{hash::ACCUMULATOR_PLACEHOLDER_HASH, test_utils::TEST_SEED, x25519, Uniform};
+use libra_crypto::{
+    hash::ACCUMULATOR_PLACEHOLDER_HASH, test_utils::TEST_SEED, x25519, HashValue, Uniform,
+};
 use libra_mempool::mocks::MockSharedMempool;
 use libra_network_address::{
     encrypted::{
@@ -24,11 +27,19 @@ use libra_network_address::{
     NetworkAddress, RawNetworkAddress,
 };
 use libra_types::{
-    chain_id::ChainId, contract_event::ContractEvent, ledger_info::LedgerInfoWithSignatures,
-    on_chain_config::ValidatorSet, proof::TransactionListProof,
-    transaction::TransactionListWithProof, validator_config::ValidatorConfig,
-    validator_info::ValidatorInfo, validator_signer::ValidatorSigner,
-    validator_verifier::random_validator_verifier, waypoint::Waypoint, PeerId,
+    chain_id::ChainId,
+    contract_event::ContractEvent,
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::ValidatorSet,
+    proof::TransactionListProof,
+    transaction::{TransactionListWithProof, Version},
+    validator_config::ValidatorConfig,
+    validator_info::ValidatorInfo,
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+    waypoint::Waypoint,
+    PeerId,
 };
 use netcore::transport::{ConnectionOrigin, ConnectionOrigin::*};
 use network::{
@@ -123,6 +134,65 @@ impl ExecutorProxyTrait for MockExecutorProxy {
             .get_epoch_ending_ledger_info(version)
     }
 
+    fn get_epoch_change_proof(&self, start_epoch: u64, end_epoch: u64) -> Result<EpochChangeProof> {
+        Ok(self
+            .storage
+            .read()
+            .unwrap()
+            .get_epoch_change_proof(start_epoch, end_epoch))
+    }
+
+    fn get_account_state_chunk(
+        &self,
+        version: Version,
+        known_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof> {
+        self.storage
+            .read()
+            .unwrap()
+            .get_account_state_chunk(version, known_key, limit)
+    }
+
+    fn get_state_snapshot_progress(&self, version: Version) -> Result<Option<HashValue>> {
+        Ok(self
+            .storage
+            .read()
+            .unwrap()
+            .get_state_snapshot_progress(version))
+    }
+
+    fn save_account_state_chunk(
+        &mut self,
+        verified_target_li: &LedgerInfoWithSignatures,
+        txn_list_with_proof: &TransactionListWithProof,
+        chunk: AccountStateChunkWithProof,
+    ) -> Result<bool> {
+        ensure!(
+            txn_list_with_proof.first_transaction_version
+                == Some(verified_target_li.ledger_info().version()),
+            "Expect the transaction at the target version"
+        );
+        Ok(self
+            .storage
+            .write()
+            .unwrap()
+            .save_account_state_chunk(verified_target_li.ledger_info().version(), chunk))
+    }
+
+    fn finish_state_snapshot(
+        &mut self,
+        verified_target_li: LedgerInfoWithSignatures,
+        epoch_change_lis: Vec<LedgerInfoWithSignatures>,
+        _txn_list_with_proof: TransactionListWithProof,
+    ) -> Result<()> {
+        self.storage
+            .write()
+            .unwrap()
+            .finish_state_snapshot(verified_target_li, epoch_change_lis);
+        Ok(())
+    }
+
     fn load_on_chain_configs(&mut self) -> Result<()> {
         Ok(())
     }
@@ -267,7 +337,7 @@ impl SynchronizerEnv {
             handler,
             role,
             waypoint,
-            60_000,
+            StateSyncConfig::default(),
             mock_network,
             upstream_networks,
         );
@@ -278,7 +348,7 @@ impl SynchronizerEnv {
         handler: MockRpcHandler,
         role: RoleType,
         waypoint: Waypoint,
-        timeout_ms: u64,
+        state_sync_config: StateSyncConfig,
         mock_network: bool,
         upstream_networks: Option<Vec<NetworkId>>,
     ) {
@@ -298,7 +368,7 @@ impl SynchronizerEnv {
         // set up config
         let mut config = config_builder::test_config().0;
         config.base.role = role;
-        config.state_sync.sync_request_timeout_ms = timeout_ms;
+        config.state_sync = state_sync_config;
 
         let network = config.validator_network.unwrap();
         let network_id = if role.is_validator() {
@@ -671,6 +741,120 @@ fn test_flaky_peer_sync() {
     assert_eq!(env.latest_li(1).ledger_info().version(), 20);
 }
 
+#[test]
+fn test_pipelined_sync_from_heterogeneous_peers() {
+    let mut env = SynchronizerEnv::new(4);
+    // peer 0 responds right away
+    env.start_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    // peer 1 is slow
+    env.start_next_synchronizer(
+        Box::new(|resp| -> Result<TransactionListWithProof> {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            Ok(resp)
+        }),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    // peer 2 fails every third request
+    let attempt = AtomicUsize::new(0);
+    env.start_next_synchronizer(
+        Box::new(move |resp| -> Result<TransactionListWithProof> {
+            if attempt.fetch_add(1, Ordering::Relaxed) % 3 == 0 {
+                bail!("chunk fetch failed")
+            } else {
+                Ok(resp)
+            }
+        }),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    env.commit(0, 1000);
+    let target_li = env.latest_li(0);
+    env.sync_to(1, target_li.clone());
+    env.sync_to(2, target_li.clone());
+
+    // peer 3 keeps several small chunks in flight to all of them, which arrive out of order
+    env.setup_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::Validator,
+        Waypoint::default(),
+        StateSyncConfig {
+            chunk_limit: 50,
+            max_in_flight_chunk_requests: 4,
+            ..StateSyncConfig::default()
+        },
+        false,
+        None,
+    );
+    env.sync_to(3, target_li);
+    assert_eq!(env.latest_li(3).ledger_info().version(), 1000);
+    assert_eq!(
+        env.storage_proxies[3].read().unwrap().accounts(),
+        env.storage_proxies[0].read().unwrap().accounts()
+    );
+
+    env.commit(0, 1500);
+    env.sync_to(3, env.latest_li(0));
+    assert_eq!(env.latest_li(3).ledger_info().version(), 1500);
+}
+
+#[test]
+fn test_pipelined_sync_full_node() {
+    let mut env = SynchronizerEnv::new(3);
+    env.start_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    env.start_next_synchronizer(
+        Box::new(|resp| -> Result<TransactionListWithProof> {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            Ok(resp)
+        }),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    // the chunks of the first epoch are fetched one at a time, the ones of the second epoch in
+    // parallel once its LedgerInfo is trusted
+    env.commit(0, 500);
+    env.move_to_next_epoch();
+    env.commit(0, 1000);
+    env.sync_to(1, env.latest_li(0));
+
+    env.setup_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::FullNode,
+        Waypoint::default(),
+        StateSyncConfig {
+            chunk_limit: 50,
+            max_in_flight_chunk_requests: 4,
+            ..StateSyncConfig::default()
+        },
+        false,
+        None,
+    );
+    assert!(env.wait_for_version(2, 1000, Some(1000)));
+    assert_eq!(env.latest_li(2).ledger_info().epoch(), 2);
+
+    // back to long polling once caught up
+    env.commit(0, 1100);
+    assert!(env.wait_for_version(2, 1100, Some(1100)));
+}
+
 #[test]
 #[should_panic]
 fn test_request_timeout() {
@@ -688,7 +872,10 @@ fn test_request_timeout() {
         SynchronizerEnv::default_handler(),
         RoleType::Validator,
         Waypoint::default(),
-        100,
+        StateSyncConfig {
+            sync_request_timeout_ms: 100,
+            ..StateSyncConfig::default()
+        },
         false,
         None,
     );
@@ -722,6 +909,110 @@ fn test_full_node() {
     assert!(env.wait_for_version(1, 20, None));
 }
 
+#[test]
+fn test_full_node_fast_sync() {
+    let mut env = SynchronizerEnv::new(2);
+    env.start_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::Validator,
+        Waypoint::default(),
+        false,
+        None,
+    );
+    // peer 0 goes through a few epochs before the full node joins
+    env.commit(0, 100);
+    env.move_to_next_epoch();
+    env.commit(0, 200);
+    env.move_to_next_epoch();
+    env.commit(0, 300);
+
+    env.setup_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::FullNode,
+        Waypoint::default(),
+        StateSyncConfig {
+            sync_mode: SyncMode::FastSync,
+            // small, so that the state snapshot is downloaded in multiple chunks
+            account_chunk_limit: 7,
+            ..StateSyncConfig::default()
+        },
+        false,
+        None,
+    );
+    assert!(env.wait_for_version(1, 300, Some(300)));
+    {
+        let storage_0 = env.storage_proxies[0].read().unwrap();
+        let storage_1 = env.storage_proxies[1].read().unwrap();
+        // the transactions before the snapshot are not replayed
+        assert_eq!(storage_1.snapshot_version(), 300);
+        assert_eq!(storage_1.accounts(), storage_0.accounts());
+        assert_eq!(storage_1.epoch_num(), 3);
+    }
+
+    // the transactions after the snapshot are replayed as usual
+    env.commit(0, 400);
+    assert!(env.wait_for_version(1, 400, Some(400)));
+    assert_eq!(
+        env.storage_proxies[1].read().unwrap().accounts(),
+        env.storage_proxies[0].read().unwrap().accounts()
+    );
+}
+
+#[test]
+fn test_fast_sync_target_no_longer_served() {
+    let mut env = SynchronizerEnv::new(2);
+    env.start_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::Validator,
+        Waypoint::default(),
+        true,
+        None,
+    );
+    env.setup_next_synchronizer(
+        SynchronizerEnv::default_handler(),
+        RoleType::FullNode,
+        Waypoint::default(),
+        StateSyncConfig {
+            sync_mode: SyncMode::FastSync,
+            account_chunk_limit: 2,
+            long_poll_timeout_ms: 1000,
+            max_state_snapshot_failures: 2,
+            ..StateSyncConfig::default()
+        },
+        true,
+        None,
+    );
+    let validator = (0, 0);
+    let full_node = (1, 0);
+    env.send_peer_event(full_node, validator, true, Inbound);
+    env.send_peer_event(validator, full_node, true, Outbound);
+
+    // the first chunk of the state snapshot at version 3
+    env.commit(0, 3);
+    env.deliver_msg(full_node);
+    env.deliver_msg(validator);
+
+    // the mock storage only serves the latest state, so the state at version 3 is gone
+    env.commit(0, 5);
+    // the request sent right after the first chunk, and the one after its timeout
+    for _ in 0..2 {
+        env.deliver_msg(full_node);
+        env.assert_no_message_sent(validator);
+    }
+
+    // the full node asks for a new target after the second timeout, and restores the 5 accounts
+    // at version 5 from scratch
+    for _ in 0..3 {
+        env.deliver_msg(full_node);
+        env.deliver_msg(validator);
+    }
+    assert!(env.wait_for_version(1, 5, Some(5)));
+    let storage_0 = env.storage_proxies[0].read().unwrap();
+    let storage_1 = env.storage_proxies[1].read().unwrap();
+    assert_eq!(storage_1.snapshot_version(), 5);
+    assert_eq!(storage_1.accounts(), storage_0.accounts());
+}
+
 #[test]
 fn catch_up_through_epochs_validators() {
     let mut env = SynchronizerEnv::new(2);
@@ -1,19 +1,24 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::SynchronizerState;
-use anyhow::{anyhow, bail, Result};
+use crate::{
+    executor_proxy::is_last_chunk, state_chunk::AccountStateChunkWithProof, SynchronizerState,
+};
+use anyhow::{anyhow, bail, ensure, Result};
 use executor_types::ExecutedTrees;
-use libra_crypto::HashValue;
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_types::{
     account_address::AccountAddress,
     account_config::lbr_type_tag,
+    account_state_blob::AccountStateBlob,
     block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     on_chain_config::ValidatorSet,
+    proof::SparseMerkleRangeProof,
     test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::{authenticator::AuthenticationKey, SignedTransaction, Transaction},
+    transaction::{authenticator::AuthenticationKey, SignedTransaction, Transaction, Version},
     validator_signer::ValidatorSigner,
 };
 use std::collections::{BTreeMap, HashMap};
@@ -22,8 +27,15 @@ use vm_genesis::GENESIS_KEYPAIR;
 
 #[derive(Clone)]
 pub struct MockStorage {
-    // some mock transactions in the storage
+    // some mock transactions in the storage, following the state snapshot (if any)
     transactions: Vec<Transaction>,
+    // version of the state snapshot the storage was fast synced to, 0 if it wasn't
+    snapshot_version: Version,
+    // the account state after applying the txns above: (account key, version of its last txn)
+    accounts: BTreeMap<HashValue, AccountStateBlob>,
+    // the accounts of the state snapshot being restored, and its version
+    restored_accounts: BTreeMap<HashValue, AccountStateBlob>,
+    restored_version: Version,
     // the executed trees after applying the txns above.
     synced_trees: ExecutedTrees,
     // latest ledger info per epoch
@@ -45,6 +57,10 @@ impl MockStorage {
         ledger_infos.insert(0, genesis_li);
         Self {
             transactions: vec![],
+            snapshot_version: 0,
+            accounts: BTreeMap::new(),
+            restored_accounts: BTreeMap::new(),
+            restored_version: 0,
             synced_trees: ExecutedTrees::new_empty(),
             ledger_infos,
             epoch_num,
@@ -54,18 +70,36 @@ impl MockStorage {
     }
 
     fn add_txns(&mut self, txns: &mut Vec<Transaction>) {
-        self.transactions.append(txns);
-        let num_leaves = self.transactions.len() + 1;
+        for txn in txns.iter() {
+            let version = self.version() + 1;
+            if let Transaction::UserTransaction(signed_txn) = txn {
+                self.accounts.insert(
+                    signed_txn.sender().hash(),
+                    AccountStateBlob::from(version.to_le_bytes().to_vec()),
+                );
+            }
+            self.transactions.push(txn.clone());
+        }
+        txns.clear();
+        let num_leaves = self.version() + 1;
         let frozen_subtree_roots = vec![HashValue::zero(); num_leaves.count_ones() as usize];
         self.synced_trees = ExecutedTrees::new(
             HashValue::zero(), /* dummy_state_root */
             frozen_subtree_roots,
-            num_leaves as u64,
+            num_leaves,
         );
     }
 
     pub fn version(&self) -> u64 {
-        self.transactions.len() as u64
+        self.snapshot_version + self.transactions.len() as u64
+    }
+
+    pub fn snapshot_version(&self) -> Version {
+        self.snapshot_version
+    }
+
+    pub fn accounts(&self) -> &BTreeMap<HashValue, AccountStateBlob> {
+        &self.accounts
     }
 
     pub fn synced_trees(&self) -> &ExecutedTrees {
@@ -107,13 +141,99 @@ impl MockStorage {
         let mut version = start_version;
         let mut res = vec![];
         let limit = std::cmp::min(limit, target_version - start_version + 1);
-        while version - 1 < self.transactions.len() as u64 && version - start_version < limit {
-            res.push(self.transactions[(version - 1) as usize].clone());
+        while version <= self.version() && version - start_version < limit {
+            res.push(self.transactions[(version - self.snapshot_version - 1) as usize].clone());
             version += 1;
         }
         res
     }
 
+    pub fn get_epoch_change_proof(&self, start_epoch: u64, end_epoch: u64) -> EpochChangeProof {
+        let ledger_infos = (start_epoch..end_epoch)
+            .map(|epoch| self.ledger_infos.get(&epoch).unwrap().clone())
+            .collect();
+        EpochChangeProof::new(ledger_infos, false)
+    }
+
+    // Only the latest state is available. The proof is fake: it only tells whether there are
+    // more accounts on the right.
+    pub fn get_account_state_chunk(
+        &self,
+        version: Version,
+        known_key: Option<HashValue>,
+        limit: u64,
+    ) -> Result<AccountStateChunkWithProof> {
+        ensure!(
+            version == self.version(),
+            "State at version {} is not available, latest version: {}",
+            version,
+            self.version()
+        );
+        let mut accounts = self
+            .accounts
+            .iter()
+            .filter(|(key, _blob)| known_key.map_or(true, |known_key| **key > known_key));
+        let account_blobs: Vec<_> = accounts
+            .by_ref()
+            .take(limit as usize)
+            .map(|(key, blob)| (*key, blob.clone()))
+            .collect();
+        let right_siblings = if accounts.next().is_some() {
+            vec![HashValue::zero()]
+        } else {
+            vec![]
+        };
+        Ok(AccountStateChunkWithProof::new(
+            account_blobs,
+            SparseMerkleRangeProof::new(right_siblings),
+        ))
+    }
+
+    pub fn get_state_snapshot_progress(&self, version: Version) -> Option<HashValue> {
+        if version != self.restored_version {
+            return None;
+        }
+        self.restored_accounts.keys().next_back().cloned()
+    }
+
+    // Returns whether the snapshot is complete. The restore of another version starts over.
+    pub fn save_account_state_chunk(
+        &mut self,
+        version: Version,
+        chunk: AccountStateChunkWithProof,
+    ) -> bool {
+        if version != self.restored_version {
+            self.restored_accounts.clear();
+            self.restored_version = version;
+        }
+        self.restored_accounts.extend(chunk.account_blobs);
+        is_last_chunk(&chunk.proof)
+    }
+
+    pub fn finish_state_snapshot(
+        &mut self,
+        target_li: LedgerInfoWithSignatures,
+        epoch_change_lis: Vec<LedgerInfoWithSignatures>,
+    ) {
+        if let Some(li) = epoch_change_lis.last() {
+            self.epoch_state = li.ledger_info().next_epoch_state().unwrap().clone();
+        }
+        for li in epoch_change_lis {
+            self.ledger_infos.insert(li.ledger_info().epoch(), li);
+        }
+        self.epoch_num = target_li.ledger_info().epoch();
+        if let Some(next_epoch_state) = target_li.ledger_info().next_epoch_state() {
+            self.epoch_num = next_epoch_state.epoch;
+            self.epoch_state = next_epoch_state.clone();
+        }
+        self.snapshot_version = target_li.ledger_info().version();
+        self.ledger_infos
+            .insert(target_li.ledger_info().epoch(), target_li);
+        self.transactions.clear();
+        self.accounts = std::mem::take(&mut self.restored_accounts);
+        self.add_txns(&mut vec![]);
+    }
+
     pub fn add_txns_with_li(
         &mut self,
         mut transactions: Vec<Transaction>,
@@ -4,7 +4,7 @@
 use crate::peer_manager::{PeerManager, PeerScoreUpdateType};
 use libra_config::config::{PeerNetworkId, UpstreamConfig};
 use netcore::transport::ConnectionOrigin;
-use std::collections::HashMap;
+use std::{collections::HashMap, thread, time::Duration};
 
 #[test]
 fn test_peer_manager() {
@@ -84,3 +84,51 @@ fn test_peer_manager_request_metadata() {
             <= peer_manager.get_last_request_time(1).unwrap()
     );
 }
+
+#[test]
+fn test_peer_manager_response_time() {
+    let peers = vec![
+        PeerNetworkId::random_validator(),
+        PeerNetworkId::random_validator(),
+    ];
+    let mut peer_manager = PeerManager::new(UpstreamConfig::default());
+    for peer in peers.iter() {
+        peer_manager.enable_peer(peer.clone(), ConnectionOrigin::Outbound);
+    }
+
+    peer_manager.process_request(1, peers[0].clone());
+    thread::sleep(Duration::from_millis(300));
+    peer_manager.process_response(1, &peers[0]);
+    peer_manager.process_request(2, peers[1].clone());
+    peer_manager.process_response(2, &peers[1]);
+    // responses to requests sent to another peer are not measured
+    peer_manager.process_request(3, peers[0].clone());
+    peer_manager.process_response(3, &peers[1]);
+    peer_manager.process_response(4, &peers[1]);
+
+    let slow_response_time = peer_manager.peer_response_time(&peers[0]).unwrap();
+    let fast_response_time = peer_manager.peer_response_time(&peers[1]).unwrap();
+    assert!(slow_response_time >= Duration::from_millis(300));
+    assert!(fast_response_time < slow_response_time);
+
+    // with the same score, the slow peer is picked less often
+    let mut pick_counts = HashMap::new();
+    for _ in 0..1000 {
+        let picked_peer_id = peer_manager.pick_peer().unwrap();
+        *pick_counts.entry(picked_peer_id).or_insert(0) += 1;
+    }
+    assert!(pick_counts.get(&peers[0]).unwrap_or(&0) < pick_counts.get(&peers[1]).unwrap());
+}
+
+#[test]
+fn test_request_in_flight() {
+    let peer = PeerNetworkId::random_validator();
+    let mut peer_manager = PeerManager::new(UpstreamConfig::default());
+    peer_manager.enable_peer(peer.clone(), ConnectionOrigin::Outbound);
+
+    assert!(!peer_manager.is_request_in_flight(1, Duration::from_secs(10)));
+    peer_manager.process_request(1, peer);
+    assert!(peer_manager.is_request_in_flight(1, Duration::from_secs(10)));
+    thread::sleep(Duration::from_millis(10));
+    assert!(!peer_manager.is_request_in_flight(1, Duration::from_millis(1)));
+}
@@ -3,6 +3,7 @@
 
 use crate::{
     backup_types::epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
+    metadata::{save_metadata, Metadata},
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
@@ -107,9 +108,14 @@ impl EpochEndingBackupController {
 
 impl EpochEndingBackupController {
     fn backup_name(&self) -> ShellSafeName {
-        format!("epoch_ending_{}-", self.start_epoch)
-            .try_into()
-            .unwrap()
+        format!(
+            "epoch_ending_{}-{}.{:04x}",
+            self.start_epoch,
+            self.end_epoch - 1,
+            rand::random::<u16>()
+        )
+        .try_into()
+        .unwrap()
     }
 
     fn manifest_name() -> &'static ShellSafeName {
@@ -145,6 +151,7 @@ impl EpochEndingBackupController {
             .create_for_write(backup_handle, &Self::chunk_name(first_epoch))
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
+        chunk_file.shutdown().await?;
         Ok(EpochEndingChunk {
             first_epoch,
             last_epoch,
@@ -158,6 +165,8 @@ impl EpochEndingBackupController {
         waypoints: Vec<Waypoint>,
         chunks: Vec<EpochEndingChunk>,
     ) -> Result<FileHandle> {
+        let first_version = waypoints.first().map_or(0, Waypoint::version);
+        let last_version = waypoints.last().map_or(0, Waypoint::version);
         let manifest = EpochEndingBackup {
             first_epoch: self.start_epoch,
             last_epoch: self.end_epoch - 1,
@@ -171,6 +180,16 @@ impl EpochEndingBackupController {
         manifest_file
             .write_all(&serde_json::to_vec(&manifest)?)
             .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_epoch_ending_backup(
+            self.start_epoch,
+            self.end_epoch - 1,
+            first_version,
+            last_version,
+            manifest_handle.clone(),
+        );
+        save_metadata(&self.storage, &metadata).await?;
 
         Ok(manifest_handle)
     }
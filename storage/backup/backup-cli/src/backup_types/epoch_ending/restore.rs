@@ -4,27 +4,41 @@
 use crate::{
     backup_types::epoch_ending::manifest::EpochEndingBackup,
     storage::{BackupStorage, FileHandle},
-    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt,
+        RestoreRunMode,
+    },
 };
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use libra_types::{
-    ledger_info::LedgerInfoWithSignatures, transaction::Version, waypoint::Waypoint,
+    epoch_change::Verifier,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    transaction::Version,
+    waypoint::Waypoint,
 };
-use libradb::backup::restore_handler::RestoreHandler;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
 pub struct EpochEndingRestoreOpt {
     #[structopt(long = "epoch-ending-manifest")]
     pub manifest_handle: FileHandle,
+    #[structopt(
+        long = "trust-waypoint",
+        help = "Trusted waypoint, repeat to provide multiple. If any is provided, a LedgerInfo not \
+        matching any of them is verified by the signatures against the validator set of the \
+        previous epoch, and the restore fails if it can't be verified either way."
+    )]
+    pub trusted_waypoints: Vec<Waypoint>,
 }
 
 pub struct EpochEndingRestoreController {
     storage: Arc<dyn BackupStorage>,
-    restore_handler: Arc<RestoreHandler>,
+    run_mode: Arc<RestoreRunMode>,
     manifest_handle: FileHandle,
     target_version: Version,
+    /// Trusted waypoints keyed by version.
+    trusted_waypoints: HashMap<Version, Waypoint>,
 }
 
 impl EpochEndingRestoreController {
@@ -32,23 +46,57 @@ impl EpochEndingRestoreController {
         opt: EpochEndingRestoreOpt,
         global_opt: GlobalRestoreOpt,
         storage: Arc<dyn BackupStorage>,
-        restore_handler: Arc<RestoreHandler>,
+        run_mode: Arc<RestoreRunMode>,
     ) -> Self {
         Self {
             storage,
-            restore_handler,
+            run_mode,
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
+            trusted_waypoints: opt
+                .trusted_waypoints
+                .into_iter()
+                .map(|w| (w.version(), w))
+                .collect(),
         }
     }
 
     pub async fn run(self) -> Result<()> {
         let manifest: EpochEndingBackup =
             self.storage.load_json_file(&self.manifest_handle).await?;
+        let previous_li = self.get_previous_epoch_ending_ledger_info(manifest.first_epoch)?;
+        self.run_impl(manifest, previous_li).await?;
+
+        Ok(())
+    }
+
+    /// Like `run()`, but the LedgerInfo ending the epoch before the first one in the backup is
+    /// provided by the caller instead of read from the DB. Returns all LedgerInfos verified.
+    pub async fn run_with_previous_ledger_info(
+        self,
+        previous_li: Option<LedgerInfoWithSignatures>,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let manifest: EpochEndingBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        self.run_impl(manifest, previous_li).await
+    }
+}
+
+impl EpochEndingRestoreController {
+    async fn run_impl(
+        self,
+        manifest: EpochEndingBackup,
+        mut previous_li: Option<LedgerInfoWithSignatures>,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
         manifest.verify()?;
 
+        if self.trusted_waypoints.is_empty() {
+            println!("No trusted waypoints provided, signatures on LedgerInfos are not verified.");
+        }
+
         let mut next_epoch = manifest.first_epoch;
         let mut waypoint_iter = manifest.waypoints.iter();
+        let mut verified = Vec::new();
 
         for chunk in manifest.chunks {
             let lis = self.read_chunk(chunk.ledger_infos).await?;
@@ -71,15 +119,17 @@ impl EpochEndingRestoreController {
                     anyhow!("More LedgerInfo's found than waypoints in manifest.")
                 })?;
                 let wp_li = Waypoint::new_epoch_boundary(li.ledger_info())?;
-                // TODO: verify signature on li
                 ensure!(
                     *wp_manifest == wp_li,
                     "Waypoints don't match. In manifest: {}, In chunk: {}",
                     wp_manifest,
                     wp_li,
                 );
+                self.verify_ledger_info(li, previous_li.as_ref())?;
+                previous_li = Some(li.clone());
                 next_epoch += 1;
             }
+            verified.extend(lis.iter().cloned());
 
             let mut end = lis.len(); // To apply: "[0, end)"
             if let Some(_end) = lis
@@ -97,7 +147,7 @@ impl EpochEndingRestoreController {
 
             // write to db
             if end != 0 {
-                self.restore_handler.save_ledger_infos(&lis[..end])?;
+                self.run_mode.save_ledger_infos(&lis[..end])?;
             }
 
             // skip remaining chunks if beyond target_version
@@ -107,11 +157,55 @@ impl EpochEndingRestoreController {
         }
         println!("Finished restoring epoch ending info.");
 
-        Ok(())
+        Ok(verified)
+    }
+
+    /// Verifies a LedgerInfo against the trusted waypoints, or, if it doesn't match any, by its
+    /// signatures against the validator set carried by the previous epoch ending LedgerInfo.
+    fn verify_ledger_info(
+        &self,
+        li: &LedgerInfoWithSignatures,
+        previous_li: Option<&LedgerInfoWithSignatures>,
+    ) -> Result<()> {
+        if self.trusted_waypoints.is_empty() {
+            return Ok(());
+        }
+        if let Some(wp_trusted) = self.trusted_waypoints.get(&li.ledger_info().version()) {
+            return wp_trusted.verify(li.ledger_info());
+        }
+
+        match previous_li {
+            Some(previous_li) => previous_li
+                .ledger_info()
+                .next_epoch_state()
+                .ok_or_else(|| anyhow!("Epoch ending LedgerInfo carries no next epoch state."))?
+                .verify(li),
+            None => bail!(
+                "LedgerInfo ending epoch {} matches no trusted waypoint, and the previous epoch \
+                ending LedgerInfo is unknown.",
+                li.ledger_info().epoch(),
+            ),
+        }
+    }
+
+    /// Gets the LedgerInfo ending the epoch before `epoch`, if it's already restored to the DB.
+    fn get_previous_epoch_ending_ledger_info(
+        &self,
+        epoch: u64,
+    ) -> Result<Option<LedgerInfoWithSignatures>> {
+        if epoch == 0 || self.trusted_waypoints.is_empty() {
+            return Ok(None);
+        }
+        Ok(match self.run_mode.as_ref() {
+            RestoreRunMode::Restore { restore_handler } => restore_handler
+                .libradb
+                .get_epoch_ending_ledger_infos(epoch - 1, epoch)
+                .ok()
+                .and_then(|(mut lis, _)| lis.pop()),
+            RestoreRunMode::Verify => None,
+        })
     }
-}
 
-impl EpochEndingRestoreController {
     async fn read_chunk(&self, file_handle: FileHandle) -> Result<Vec<LedgerInfoWithSignatures>> {
         let mut file = self.storage.open_for_read(&file_handle).await?;
         let mut chunk = vec![];
@@ -123,3 +217,50 @@ impl EpochEndingRestoreController {
         Ok(chunk)
     }
 }
+
+/// Epoch ending LedgerInfos from epoch 0 on, already verified, with which the LedgerInfos carried
+/// by other types of backups are verified.
+pub struct EpochHistory {
+    epoch_endings: Vec<LedgerInfo>,
+}
+
+impl EpochHistory {
+    pub fn new(epoch_endings: Vec<LedgerInfoWithSignatures>) -> Result<Self> {
+        for (epoch, li) in epoch_endings.iter().enumerate() {
+            ensure!(
+                li.ledger_info().epoch() == epoch as u64,
+                "Epoch history not continuous. Expected epoch: {}, actual: {}.",
+                epoch,
+                li.ledger_info().epoch(),
+            );
+        }
+        Ok(Self {
+            epoch_endings: epoch_endings
+                .into_iter()
+                .map(|li| li.ledger_info().clone())
+                .collect(),
+        })
+    }
+
+    pub fn verify_ledger_info(&self, li_with_sigs: &LedgerInfoWithSignatures) -> Result<()> {
+        let epoch = li_with_sigs.ledger_info().epoch();
+        ensure!(
+            epoch <= self.epoch_endings.len() as u64,
+            "LedgerInfo epoch {} is beyond the epoch history, which ends at epoch {}.",
+            epoch,
+            self.epoch_endings.len(),
+        );
+        if epoch == 0 {
+            ensure!(
+                self.epoch_endings.first() == Some(li_with_sigs.ledger_info()),
+                "Genesis LedgerInfo doesn't match the one in epoch history."
+            );
+            return Ok(());
+        }
+
+        self.epoch_endings[epoch as usize - 1]
+            .next_epoch_state()
+            .ok_or_else(|| anyhow!("Epoch ending LedgerInfo carries no next epoch state."))?
+            .verify(li_with_sigs)
+    }
+}
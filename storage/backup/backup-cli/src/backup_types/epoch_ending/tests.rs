@@ -10,14 +10,15 @@ use crate::{
     utils::{
         backup_service_client::BackupServiceClient,
         test_utils::{tmp_db_empty, tmp_db_with_random_content},
-        GlobalBackupOpt, GlobalRestoreOpt,
+        GlobalBackupOpt, GlobalRestoreOpt, RestoreRunMode,
     },
 };
 use backup_service::start_backup_service;
 use libra_config::utils::get_available_port;
 use libra_temppath::TempPath;
+use libra_types::{transaction::Version, waypoint::Waypoint};
 use libradb::GetRestoreHandler;
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
 use tokio::time::Duration;
 
 #[test]
@@ -53,13 +54,18 @@ fn end_to_end() {
 
     rt.block_on(
         EpochEndingRestoreController::new(
-            EpochEndingRestoreOpt { manifest_handle },
+            EpochEndingRestoreOpt {
+                manifest_handle,
+                trusted_waypoints: vec![],
+            },
             GlobalRestoreOpt {
                 db_dir: PathBuf::new(),
                 target_version,
             },
             store,
-            Arc::new(tgt_db.get_restore_handler()),
+            Arc::new(RestoreRunMode::Restore {
+                restore_handler: Arc::new(tgt_db.get_restore_handler()),
+            }),
         )
         .run(),
     )
@@ -85,3 +91,68 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn trusted_waypoint_mismatch() {
+    let (_src_db_dir, src_db, blocks) = tmp_db_with_random_content();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let port = get_available_port();
+    let mut rt = start_backup_service(port, src_db);
+    let client = Arc::new(BackupServiceClient::new(port));
+
+    let latest_epoch = blocks.last().unwrap().1.ledger_info().next_block_epoch();
+    let first_epoch_ending_version = blocks
+        .iter()
+        .map(|(_, li)| li.ledger_info())
+        .find(|li| li.ends_epoch())
+        .unwrap()
+        .version();
+    let manifest_handle = rt
+        .block_on(
+            EpochEndingBackupController::new(
+                EpochEndingBackupOpt {
+                    start_epoch: 0,
+                    end_epoch: latest_epoch,
+                },
+                GlobalBackupOpt {
+                    max_chunk_size: 1024,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    let bad_waypoint = Waypoint::from_str(&format!(
+        "{}:{}",
+        first_epoch_ending_version,
+        "00".repeat(32),
+    ))
+    .unwrap();
+    assert!(rt
+        .block_on(
+            EpochEndingRestoreController::new(
+                EpochEndingRestoreOpt {
+                    manifest_handle,
+                    trusted_waypoints: vec![bad_waypoint],
+                },
+                GlobalRestoreOpt {
+                    db_dir: PathBuf::new(),
+                    target_version: Version::max_value(),
+                },
+                store,
+                Arc::new(RestoreRunMode::Restore {
+                    restore_handler: Arc::new(tgt_db.get_restore_handler()),
+                }),
+            )
+            .run(),
+        )
+        .is_err());
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
@@ -3,6 +3,7 @@
 
 use crate::{
     backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+    metadata::{save_metadata, Metadata},
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
@@ -114,7 +115,9 @@ impl StateSnapshotBackupController {
 
 impl StateSnapshotBackupController {
     fn backup_name(&self) -> ShellSafeName {
-        format!("state_ver_{}", self.version).try_into().unwrap()
+        format!("state_ver_{}.{:04x}", self.version, rand::random::<u16>())
+            .try_into()
+            .unwrap()
     }
 
     fn manifest_name() -> &'static ShellSafeName {
@@ -159,6 +162,7 @@ impl StateSnapshotBackupController {
             .create_for_write(backup_handle, &Self::chunk_name(first_idx))
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
+        chunk_file.shutdown().await?;
         let (proof_handle, mut proof_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
@@ -171,6 +175,7 @@ impl StateSnapshotBackupController {
             &mut proof_file,
         )
         .await?;
+        proof_file.shutdown().await?;
 
         Ok(StateSnapshotChunk {
             first_idx,
@@ -196,6 +201,7 @@ impl StateSnapshotBackupController {
             .create_for_write(&backup_handle, Self::proof_name())
             .await?;
         proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
 
         let manifest = StateSnapshotBackup {
             version: self.version,
@@ -211,6 +217,10 @@ impl StateSnapshotBackupController {
         manifest_file
             .write_all(&serde_json::to_vec(&manifest)?)
             .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_state_snapshot_backup(self.version, manifest_handle.clone());
+        save_metadata(&self.storage, &metadata).await?;
 
         Ok(manifest_handle)
     }
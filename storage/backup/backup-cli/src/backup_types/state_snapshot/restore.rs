@@ -2,14 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::state_snapshot::manifest::StateSnapshotBackup,
+    backup_types::{
+        epoch_ending::restore::EpochHistory, state_snapshot::manifest::StateSnapshotBackup,
+    },
     storage::{BackupStorage, FileHandle},
-    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt,
+        RestoreRunMode,
+    },
 };
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use libra_crypto::HashValue;
-use libra_types::{account_state_blob::AccountStateBlob, transaction::Version};
-use libradb::backup::restore_handler::RestoreHandler;
+use libra_jellyfish_merkle::{
+    node_type::{LeafNode, Node, NodeKey},
+    restore::JellyfishMerkleRestore,
+    NodeBatch, TreeReader, TreeWriter,
+};
+use libra_types::{
+    account_state_blob::AccountStateBlob, ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof, transaction::Version,
+};
 use std::sync::Arc;
 use structopt::StructOpt;
 
@@ -23,13 +35,15 @@ pub struct StateSnapshotRestoreOpt {
 
 pub struct StateSnapshotRestoreController {
     storage: Arc<dyn BackupStorage>,
-    restore_handler: Arc<RestoreHandler>,
+    run_mode: Arc<RestoreRunMode>,
     /// State snapshot restores to this version.
     version: Version,
     manifest_handle: FileHandle,
     /// Global "target_version" for the entire restore process, if `version` is newer than this,
     /// nothing will be done, otherwise, this has no effect.
     target_version: Version,
+    /// If provided, the LedgerInfo carried by the state root proof is verified against it.
+    epoch_history: Option<Arc<EpochHistory>>,
 }
 
 impl StateSnapshotRestoreController {
@@ -37,14 +51,16 @@ impl StateSnapshotRestoreController {
         opt: StateSnapshotRestoreOpt,
         global_opt: GlobalRestoreOpt,
         storage: Arc<dyn BackupStorage>,
-        restore_handler: Arc<RestoreHandler>,
+        run_mode: Arc<RestoreRunMode>,
+        epoch_history: Option<Arc<EpochHistory>>,
     ) -> Self {
         Self {
             storage,
-            restore_handler,
+            run_mode,
             version: opt.version,
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
+            epoch_history,
         }
     }
 
@@ -60,11 +76,43 @@ impl StateSnapshotRestoreController {
 
         let manifest: StateSnapshotBackup =
             self.storage.load_json_file(&self.manifest_handle).await?;
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_lcs_file(&manifest.proof).await?;
+        if let Some(epoch_history) = &self.epoch_history {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        ensure!(
+            txn_info_with_proof.transaction_info().state_root_hash() == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            txn_info_with_proof.transaction_info().state_root_hash(),
+        );
 
-        let mut receiver = self
-            .restore_handler
-            .get_state_restore_receiver(self.version, manifest.root_hash)?;
+        match self.run_mode.as_ref() {
+            RestoreRunMode::Restore { restore_handler } => {
+                let receiver =
+                    restore_handler.get_state_restore_receiver(self.version, manifest.root_hash)?;
+                self.add_chunks(manifest, receiver).await?;
+            }
+            RestoreRunMode::Verify => {
+                let receiver =
+                    JellyfishMerkleRestore::new(&NoopTreeStore, self.version, manifest.root_hash)?;
+                self.add_chunks(manifest, receiver).await?;
+            }
+        }
 
+        println!("Finished restoring state snapshot.");
+        Ok(())
+    }
+}
+
+impl StateSnapshotRestoreController {
+    async fn add_chunks<'a, S: 'a + TreeReader + TreeWriter>(
+        &self,
+        manifest: StateSnapshotBackup,
+        mut receiver: JellyfishMerkleRestore<'a, S>,
+    ) -> Result<()> {
         for chunk in manifest.chunks {
             let blobs = self.read_account_state_chunk(chunk.blobs).await?;
             let proof = self.storage.load_lcs_file(&chunk.proof).await?;
@@ -72,13 +120,9 @@ impl StateSnapshotRestoreController {
             receiver.add_chunk(blobs, proof)?;
         }
 
-        receiver.finish()?;
-        println!("Finished restoring state snapshot.");
-        Ok(())
+        receiver.finish()
     }
-}
 
-impl StateSnapshotRestoreController {
     async fn read_account_state_chunk(
         &self,
         file_handle: FileHandle,
@@ -94,3 +138,23 @@ impl StateSnapshotRestoreController {
         Ok(chunk)
     }
 }
+
+/// A tree store that holds nothing, with which `JellyfishMerkleRestore` verifies the chunks but
+/// writes them nowhere.
+struct NoopTreeStore;
+
+impl TreeReader for NoopTreeStore {
+    fn get_node_option(&self, _node_key: &NodeKey) -> Result<Option<Node>> {
+        Ok(None)
+    }
+
+    fn get_rightmost_leaf(&self, _version: Version) -> Result<Option<(NodeKey, LeafNode)>> {
+        Ok(None)
+    }
+}
+
+impl TreeWriter for NoopTreeStore {
+    fn write_node_batch(&self, _node_batch: &NodeBatch) -> Result<()> {
+        Ok(())
+    }
+}
@@ -4,13 +4,15 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        manifest::StateSnapshotBackup,
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        storage_ext::BackupStorageExt,
         test_utils::{tmp_db_empty, tmp_db_with_random_content},
-        GlobalBackupOpt, GlobalRestoreOpt,
+        GlobalBackupOpt, GlobalRestoreOpt, RestoreRunMode,
     },
 };
 use backup_service::start_backup_service;
@@ -20,7 +22,7 @@ use libra_types::transaction::{Version, PRE_GENESIS_VERSION};
 use libradb::GetRestoreHandler;
 use std::{path::PathBuf, sync::Arc};
 use storage_interface::DbReader;
-use tokio::time::Duration;
+use tokio::{io::AsyncWriteExt, time::Duration};
 
 #[test]
 fn end_to_end() {
@@ -51,15 +53,18 @@ fn end_to_end() {
     rt.block_on(
         StateSnapshotRestoreController::new(
             StateSnapshotRestoreOpt {
-                manifest_handle,
+                manifest_handle: manifest_handle.clone(),
                 version: PRE_GENESIS_VERSION,
             },
             GlobalRestoreOpt {
                 db_dir: PathBuf::new(),
                 target_version: Version::max_value(),
             },
-            store,
-            Arc::new(tgt_db.get_restore_handler()),
+            Arc::clone(&store),
+            Arc::new(RestoreRunMode::Restore {
+                restore_handler: Arc::new(tgt_db.get_restore_handler()),
+            }),
+            None, /* epoch_history */
         )
         .run(),
     )
@@ -72,5 +77,37 @@ fn end_to_end() {
         state_root_hash,
     );
 
+    // A manifest claiming a version other than the one its proof is for is rejected.
+    let mut manifest: StateSnapshotBackup =
+        rt.block_on(store.load_json_file(&manifest_handle)).unwrap();
+    manifest.version += 1;
+    let backup_handle = rt
+        .block_on(store.create_backup(&"tampered".parse().unwrap()))
+        .unwrap();
+    let (tampered_handle, mut file) = rt
+        .block_on(store.create_for_write(&backup_handle, &"state.manifest".parse().unwrap()))
+        .unwrap();
+    rt.block_on(file.write_all(&serde_json::to_vec(&manifest).unwrap()))
+        .unwrap();
+    rt.block_on(file.shutdown()).unwrap();
+    assert!(rt
+        .block_on(
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: tampered_handle,
+                    version: manifest.version,
+                },
+                GlobalRestoreOpt {
+                    db_dir: PathBuf::new(),
+                    target_version: Version::max_value(),
+                },
+                store,
+                Arc::new(RestoreRunMode::Verify),
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .is_err());
+
     rt.shutdown_timeout(Duration::from_secs(1));
 }
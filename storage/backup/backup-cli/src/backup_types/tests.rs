@@ -15,7 +15,7 @@ use crate::{
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient, test_utils::tmp_db_empty, GlobalBackupOpt,
-        GlobalRestoreOpt,
+        GlobalRestoreOpt, RestoreRunMode,
     },
 };
 use backup_service::start_backup_service;
@@ -118,7 +118,10 @@ fn test_end_to_end_impl(d: TestData) {
                 },
                 global_restore_opt.clone(),
                 Arc::clone(&store),
-                Arc::new(tgt_db.get_restore_handler()),
+                Arc::new(RestoreRunMode::Restore {
+                    restore_handler: Arc::new(tgt_db.get_restore_handler()),
+                }),
+                None, /* epoch_history */
             )
             .run(),
         )
@@ -132,7 +135,10 @@ fn test_end_to_end_impl(d: TestData) {
             },
             global_restore_opt,
             store,
-            Arc::new(tgt_db.get_restore_handler()),
+            Arc::new(RestoreRunMode::Restore {
+                restore_handler: Arc::new(tgt_db.get_restore_handler()),
+            }),
+            None,
         )
         .run(),
     )
@@ -3,6 +3,7 @@
 
 use crate::{
     backup_types::transaction::manifest::{TransactionBackup, TransactionChunk},
+    metadata::{save_metadata, Metadata},
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
         backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
@@ -107,9 +108,14 @@ impl TransactionBackupController {
 
 impl TransactionBackupController {
     fn backup_name(&self) -> ShellSafeName {
-        format!("transaction_{}-", self.start_version)
-            .try_into()
-            .unwrap()
+        format!(
+            "transaction_{}-{}.{:04x}",
+            self.start_version,
+            self.start_version + self.num_transactions as Version - 1,
+            rand::random::<u16>()
+        )
+        .try_into()
+        .unwrap()
     }
 
     fn manifest_name() -> &'static ShellSafeName {
@@ -150,12 +156,14 @@ impl TransactionBackupController {
             &mut proof_file,
         )
         .await?;
+        proof_file.shutdown().await?;
 
         let (chunk_handle, mut chunk_file) = self
             .storage
             .create_for_write(backup_handle, &Self::chunk_name(first_version))
             .await?;
         chunk_file.write_all(&chunk_bytes).await?;
+        chunk_file.shutdown().await?;
 
         Ok(TransactionChunk {
             first_version,
@@ -184,6 +192,11 @@ impl TransactionBackupController {
         manifest_file
             .write_all(&serde_json::to_vec(&manifest)?)
             .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata =
+            Metadata::new_transaction_backup(first_version, last_version, manifest_handle.clone());
+        save_metadata(&self.storage, &metadata).await?;
 
         Ok(manifest_handle)
     }
@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup_types::transaction::manifest::{TransactionBackup, TransactionChunk},
+    backup_types::{
+        epoch_ending::restore::EpochHistory,
+        transaction::manifest::{TransactionBackup, TransactionChunk},
+    },
     storage::{BackupStorage, FileHandle},
-    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOpt,
+        RestoreRunMode,
+    },
 };
-use anyhow::{ensure, Result};
+use anyhow::{bail, ensure, Result};
 use executor::Executor;
 use executor_types::TransactionReplayer;
 use libra_types::{
@@ -15,7 +21,6 @@ use libra_types::{
     transaction::{Transaction, TransactionInfo, TransactionListWithProof, Version},
 };
 use libra_vm::LibraVM;
-use libradb::backup::restore_handler::RestoreHandler;
 use std::{
     cmp::{max, min},
     sync::Arc,
@@ -29,17 +34,21 @@ pub struct TransactionRestoreOpt {
     pub manifest_handle: FileHandle,
     #[structopt(
         long = "replay-transaction-from-version",
-        default_value = "Version::max_value()"
+        default_value = "18446744073709551615",
+        help = "Transactions at and after this version are replayed to recreate the state and \
+        events, while older ones are saved directly. [Defaults to not replaying anything.]"
     )]
     pub replay_from_version: Version,
 }
 
 pub struct TransactionRestoreController {
     storage: Arc<dyn BackupStorage>,
-    restore_handler: Arc<RestoreHandler>,
+    run_mode: Arc<RestoreRunMode>,
     manifest_handle: FileHandle,
     target_version: Version,
     replay_from_version: Version,
+    /// If provided, the LedgerInfo carried by each chunk is verified against it.
+    epoch_history: Option<Arc<EpochHistory>>,
     state: State,
 }
 
@@ -58,7 +67,11 @@ struct LoadedChunk {
 }
 
 impl LoadedChunk {
-    async fn load(manifest: TransactionChunk, storage: &Arc<dyn BackupStorage>) -> Result<Self> {
+    async fn load(
+        manifest: TransactionChunk,
+        storage: &Arc<dyn BackupStorage>,
+        epoch_history: Option<&Arc<EpochHistory>>,
+    ) -> Result<Self> {
         let mut file = storage.open_for_read(&manifest.transactions).await?;
         let mut txns = Vec::new();
         let mut txn_infos = Vec::new();
@@ -82,7 +95,9 @@ impl LoadedChunk {
                 &manifest.proof,
             )
             .await?;
-        // TODO: verify signatures
+        if let Some(epoch_history) = epoch_history {
+            epoch_history.verify_ledger_info(&ledger_info)?;
+        }
 
         // make a `TransactionListWithProof` to reuse its verification code.
         let txn_list_with_proof = TransactionListWithProof::new(
@@ -111,21 +126,29 @@ impl TransactionRestoreController {
         opt: TransactionRestoreOpt,
         global_opt: GlobalRestoreOpt,
         storage: Arc<dyn BackupStorage>,
-        restore_handler: Arc<RestoreHandler>,
+        run_mode: Arc<RestoreRunMode>,
+        epoch_history: Option<Arc<EpochHistory>>,
     ) -> Self {
+        // Nothing can be replayed without a DB.
+        let replay_from_version = if run_mode.is_verify() {
+            Version::max_value()
+        } else {
+            opt.replay_from_version
+        };
         Self {
             storage,
-            restore_handler,
+            run_mode,
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
-            replay_from_version: opt.replay_from_version,
+            replay_from_version,
+            epoch_history,
             state: State::default(),
         }
     }
 
     fn maybe_save_frozen_subtrees(&mut self, chunk: &LoadedChunk) -> Result<()> {
         if !self.state.frozen_subtree_confirmed {
-            self.restore_handler.confirm_or_save_frozen_subtrees(
+            self.run_mode.confirm_or_save_frozen_subtrees(
                 chunk.manifest.first_version,
                 chunk.range_proof.left_siblings(),
             )?;
@@ -136,10 +159,13 @@ impl TransactionRestoreController {
 
     fn transaction_replayer(&mut self) -> Result<&mut Executor<LibraVM>> {
         if self.state.transaction_replayer.is_none() {
+            let restore_handler = match self.run_mode.as_ref() {
+                RestoreRunMode::Restore { restore_handler } => restore_handler,
+                RestoreRunMode::Verify => bail!("Transactions can't be replayed in verify mode."),
+            };
             let replayer = Executor::new_on_unbootstrapped_db(
-                DbReaderWriter::from_arc(Arc::clone(&self.restore_handler.libradb)),
-                self.restore_handler
-                    .get_tree_state(self.replay_from_version)?,
+                DbReaderWriter::from_arc(Arc::clone(&restore_handler.libradb)),
+                restore_handler.get_tree_state(self.replay_from_version)?,
             );
             self.state.transaction_replayer = Some(replayer);
         }
@@ -163,7 +189,9 @@ impl TransactionRestoreController {
                 break;
             }
 
-            let mut chunk = LoadedChunk::load(chunk_manifest, &self.storage).await?;
+            let mut chunk =
+                LoadedChunk::load(chunk_manifest, &self.storage, self.epoch_history.as_ref())
+                    .await?;
             self.maybe_save_frozen_subtrees(&chunk)?;
 
             let last = min(self.target_version, chunk.manifest.last_version);
@@ -171,9 +199,9 @@ impl TransactionRestoreController {
 
             // Transactions to save without replaying:
             if first_to_replay > chunk.manifest.first_version {
-                let last_to_save = min(last, first_to_replay);
+                let last_to_save = min(last, first_to_replay - 1);
                 let num_txns_to_save = (last_to_save - chunk.manifest.first_version + 1) as usize;
-                self.restore_handler.save_transactions(
+                self.run_mode.save_transactions(
                     chunk.manifest.first_version,
                     &chunk.txns[..num_txns_to_save],
                     &chunk.txn_infos[..num_txns_to_save],
@@ -193,10 +221,10 @@ impl TransactionRestoreController {
                 )?;
             }
 
-            // Last chunk
-            if chunk.manifest.last_version == manifest.last_version {
-                self.restore_handler
-                    .save_ledger_info_if_newer(chunk.ledger_info)?;
+            // Only a LedgerInfo not beyond the target version can be saved, otherwise the DB will
+            // have a committed version with no transaction restored at it.
+            if chunk.ledger_info.ledger_info().version() <= self.target_version {
+                self.run_mode.save_ledger_info_if_newer(chunk.ledger_info)?;
             }
         }
 
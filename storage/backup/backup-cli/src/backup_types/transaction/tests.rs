@@ -10,7 +10,7 @@ use crate::{
     utils::{
         backup_service_client::BackupServiceClient,
         test_utils::{tmp_db_empty, tmp_db_with_random_content},
-        GlobalBackupOpt, GlobalRestoreOpt,
+        GlobalBackupOpt, GlobalRestoreOpt, RestoreRunMode,
     },
 };
 use backup_service::start_backup_service;
@@ -81,7 +81,10 @@ fn end_to_end() {
                 target_version,
             },
             store,
-            Arc::new(tgt_db.get_restore_handler()),
+            Arc::new(RestoreRunMode::Restore {
+                restore_handler: Arc::new(tgt_db.get_restore_handler()),
+            }),
+            None,
         )
         .run(),
     )
@@ -8,12 +8,18 @@ use backup_cli::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        verify::{VerifyCoordinator, VerifyCoordinatorOpt},
+    },
+    metadata::cache::{sync_and_load, MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
         backup_service_client::{BackupServiceClient, BackupServiceClientOpt},
         GlobalBackupOpt,
     },
 };
+use libra_types::transaction::Version;
 use std::sync::Arc;
 use structopt::StructOpt;
 
@@ -22,6 +28,11 @@ use structopt::StructOpt;
 enum Command {
     #[structopt(about = "Manually run one shot commands.")]
     OneShot(OneShotCommand),
+    #[structopt(about = "Long running process backing up the chain continuously.")]
+    Coordinator(CoordinatorCommand),
+    #[structopt(about = "Verify the backups in the backup storage can be restored, \
+    without writing a DB.")]
+    Verify(VerifyOpt),
 }
 
 #[derive(StructOpt)]
@@ -30,6 +41,38 @@ enum OneShotCommand {
     Query(OneShotQueryOpt),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(about = "List backups in the backup storage.")]
+    List(OneShotListOpt),
+}
+
+#[derive(StructOpt)]
+enum CoordinatorCommand {
+    #[structopt(about = "Run the coordinator.")]
+    Run(CoordinatorRunOpt),
+}
+
+#[derive(StructOpt)]
+struct CoordinatorRunOpt {
+    #[structopt(flatten)]
+    global: GlobalBackupOpt,
+
+    #[structopt(flatten)]
+    client: BackupServiceClientOpt,
+
+    #[structopt(flatten)]
+    coordinator: BackupCoordinatorOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
+#[derive(StructOpt)]
+struct VerifyOpt {
+    #[structopt(flatten)]
+    opt: VerifyCoordinatorOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
 }
 
 #[derive(StructOpt)]
@@ -43,6 +86,21 @@ struct OneShotQueryOpt {
     latest_version: bool,
 }
 
+#[derive(StructOpt)]
+struct OneShotListOpt {
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+
+    #[structopt(
+        long = "target-version",
+        help = "If specified, only lists the minimal set of backups needed to restore to this version."
+    )]
+    target_version: Option<Version>,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -132,7 +190,38 @@ async fn main() -> Result<()> {
                     }
                 }
             }
+            OneShotCommand::List(opt) => {
+                let view =
+                    sync_and_load(&opt.metadata_cache, &opt.storage.init_storage().await?).await?;
+                match opt.target_version {
+                    Some(version) => println!("{:#?}", view.select_for_restore(version)?),
+                    None => {
+                        println!("{:#?}", view.epoch_ending_backups());
+                        println!("{:#?}", view.state_snapshot_backups());
+                        println!("{:#?}", view.transaction_backups());
+                    }
+                }
+            }
+        },
+        Command::Coordinator(coordinator_cmd) => match coordinator_cmd {
+            CoordinatorCommand::Run(opt) => {
+                BackupCoordinator::new(
+                    opt.coordinator,
+                    opt.global,
+                    Arc::new(BackupServiceClient::new_with_opt(opt.client)),
+                    opt.storage.init_storage().await?,
+                )
+                .run()
+                .await
+                .context("Backup coordinator failed.")?;
+            }
         },
+        Command::Verify(opt) => {
+            VerifyCoordinator::new(opt.opt, opt.storage.init_storage().await?)
+                .run()
+                .await
+                .context("Backup verification failed.")?;
+        }
     }
     Ok(())
 }
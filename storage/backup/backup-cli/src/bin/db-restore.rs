@@ -8,8 +8,9 @@ use backup_cli::{
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
     storage::StorageOpt,
-    utils::GlobalRestoreOpt,
+    utils::{GlobalRestoreOpt, RestoreRunMode},
 };
 use libradb::{GetRestoreHandler, LibraDB};
 use std::sync::Arc;
@@ -44,6 +45,12 @@ enum RestoreType {
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    Auto {
+        #[structopt(flatten)]
+        opt: RestoreCoordinatorOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
 }
 
 #[tokio::main]
@@ -58,7 +65,9 @@ async fn main() -> Result<()> {
         )
         .expect("Failed opening DB."),
     );
-    let restore_handler = Arc::new(db.get_restore_handler());
+    let run_mode = Arc::new(RestoreRunMode::Restore {
+        restore_handler: Arc::new(db.get_restore_handler()),
+    });
     let global_opt = opt.global;
 
     match opt.restore_type {
@@ -67,7 +76,7 @@ async fn main() -> Result<()> {
                 opt,
                 global_opt,
                 storage.init_storage().await?,
-                restore_handler,
+                run_mode,
             )
             .run()
             .await
@@ -79,7 +88,8 @@ async fn main() -> Result<()> {
                 opt,
                 global_opt,
                 storage.init_storage().await?,
-                restore_handler,
+                run_mode,
+                None, /* epoch_history */
             )
             .run()
             .await
@@ -91,13 +101,20 @@ async fn main() -> Result<()> {
                 opt,
                 global_opt,
                 storage.init_storage().await?,
-                restore_handler,
+                run_mode,
+                None, /* epoch_history */
             )
             .run()
             .await
             .map(|_| println!("Transactions restore success."))
             .context("Failed restoring state snapshot.")?;
         }
+        RestoreType::Auto { opt, storage } => {
+            RestoreCoordinator::new(opt, global_opt, storage.init_storage().await?, run_mode)
+                .run()
+                .await
+                .context("Failed restoring DB.")?;
+        }
     }
 
     Ok(())
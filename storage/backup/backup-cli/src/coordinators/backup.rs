@@ -0,0 +1,220 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        view::BackupStorageState,
+    },
+    storage::BackupStorage,
+    utils::{backup_service_client::BackupServiceClient, GlobalBackupOpt},
+};
+use anyhow::{ensure, Result};
+use libra_types::transaction::Version;
+use libradb::backup::backup_handler::DbState;
+use std::{sync::Arc, time::Duration};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct BackupCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+
+    #[structopt(
+        long = "state-snapshot-interval",
+        default_value = "100000",
+        help = "A state snapshot is taken whenever the committed version crosses a multiple of this."
+    )]
+    pub state_snapshot_interval: u64,
+
+    #[structopt(
+        long = "transaction-batch-size",
+        default_value = "100000",
+        help = "Number of transactions in each transaction backup. Only full batches are backed up."
+    )]
+    pub transaction_batch_size: usize,
+
+    #[structopt(
+        long = "poll-interval-secs",
+        default_value = "60",
+        help = "Seconds between two polls of the backup service."
+    )]
+    pub poll_interval_secs: u64,
+}
+
+/// Keeps backing up whatever is new in the local Libra node, in rounds.
+///
+/// In each round, all ended epochs not yet in the backup storage are backed up first, so that the
+/// `LedgerInfoWithSignatures` carried by the state snapshot and transaction backups made in the
+/// same round can always be verified with what's already in the same storage.
+pub struct BackupCoordinator {
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+    global_opt: GlobalBackupOpt,
+    metadata_cache_opt: MetadataCacheOpt,
+    state_snapshot_interval: u64,
+    transaction_batch_size: usize,
+    poll_interval: Duration,
+}
+
+impl BackupCoordinator {
+    pub fn new(
+        opt: BackupCoordinatorOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            client,
+            storage,
+            global_opt,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            state_snapshot_interval: opt.state_snapshot_interval,
+            transaction_batch_size: opt.transaction_batch_size,
+            poll_interval: Duration::from_secs(opt.poll_interval_secs),
+        }
+    }
+
+    /// Runs forever. The progress is recovered from the backup storage on start, so it's safe to
+    /// restart at any time.
+    pub async fn run(self) -> Result<()> {
+        ensure!(
+            self.state_snapshot_interval > 0 && self.transaction_batch_size > 0,
+            "State snapshot interval and transaction batch size must be positive."
+        );
+
+        let mut state = sync_and_load(&self.metadata_cache_opt, &self.storage)
+            .await?
+            .get_storage_state();
+        println!("Backup storage state on start: {:?}", state);
+
+        loop {
+            if let Err(e) = self.run_round(&mut state).await {
+                println!("Backup round failed, will retry: {:?}", e);
+            }
+            tokio::time::delay_for(self.poll_interval).await;
+        }
+    }
+
+    /// Backs up everything that's new in the DB since `state`, updating `state` along the way.
+    pub async fn run_round(&self, state: &mut BackupStorageState) -> Result<()> {
+        let db_state = match self.client.get_db_state().await? {
+            Some(db_state) => db_state,
+            None => {
+                println!("DB not bootstrapped yet.");
+                return Ok(());
+            }
+        };
+
+        self.backup_epoch_endings(state, db_state).await?;
+        self.backup_state_snapshot(state, db_state).await?;
+        self.backup_transactions(state, db_state).await
+    }
+}
+
+impl BackupCoordinator {
+    fn next_epoch_ending_epoch(state: &BackupStorageState) -> u64 {
+        state.latest_epoch_ending_epoch.map_or(0, |e| e + 1)
+    }
+
+    fn next_transaction_version(state: &BackupStorageState) -> Version {
+        state.latest_transaction_version.map_or(0, |v| v + 1)
+    }
+
+    async fn backup_epoch_endings(
+        &self,
+        state: &mut BackupStorageState,
+        db_state: DbState,
+    ) -> Result<()> {
+        let start_epoch = Self::next_epoch_ending_epoch(state);
+        if start_epoch >= db_state.epoch {
+            return Ok(());
+        }
+
+        let manifest = EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch,
+                end_epoch: db_state.epoch,
+            },
+            self.global_opt.clone(),
+            Arc::clone(&self.client),
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await?;
+        println!(
+            "Epoch ending backup [{}, {}) success. Manifest: {}",
+            start_epoch, db_state.epoch, manifest
+        );
+
+        state.latest_epoch_ending_epoch = Some(db_state.epoch - 1);
+        Ok(())
+    }
+
+    async fn backup_state_snapshot(
+        &self,
+        state: &mut BackupStorageState,
+        db_state: DbState,
+    ) -> Result<()> {
+        let version = db_state.committed_version / self.state_snapshot_interval
+            * self.state_snapshot_interval;
+        if let Some(latest) = state.latest_state_snapshot_version {
+            if latest >= version {
+                return Ok(());
+            }
+        }
+
+        let manifest = StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt { version },
+            self.global_opt.clone(),
+            Arc::clone(&self.client),
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await?;
+        println!(
+            "State snapshot backup at version {} success. Manifest: {}",
+            version, manifest
+        );
+
+        state.latest_state_snapshot_version = Some(version);
+        Ok(())
+    }
+
+    async fn backup_transactions(
+        &self,
+        state: &mut BackupStorageState,
+        db_state: DbState,
+    ) -> Result<()> {
+        loop {
+            let start_version = Self::next_transaction_version(state);
+            let last_version = start_version + self.transaction_batch_size as Version - 1;
+            if last_version > db_state.committed_version {
+                return Ok(());
+            }
+
+            let manifest = TransactionBackupController::new(
+                TransactionBackupOpt {
+                    start_version,
+                    num_transactions: self.transaction_batch_size,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.client),
+                Arc::clone(&self.storage),
+            )
+            .run()
+            .await?;
+            println!(
+                "Transaction backup [{}, {}] success. Manifest: {}",
+                start_version, last_version, manifest
+            );
+
+            state.latest_transaction_version = Some(last_version);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod backup;
+pub mod restore;
+pub mod verify;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backup_types::epoch_ending::restore::{
+        EpochEndingRestoreController, EpochEndingRestoreOpt, EpochHistory,
+    },
+    metadata::EpochEndingBackupMeta,
+    storage::BackupStorage,
+    utils::{GlobalRestoreOpt, RestoreRunMode},
+};
+use anyhow::Result;
+use libra_types::waypoint::Waypoint;
+use std::sync::Arc;
+
+/// Runs epoch ending restores for `backups`, which together cover consecutive epochs from 0 on and
+/// can overlap with each other, and returns the epoch history verified along the way.
+pub(crate) async fn restore_epoch_history(
+    backups: Vec<EpochEndingBackupMeta>,
+    trusted_waypoints: &[Waypoint],
+    global_opt: &GlobalRestoreOpt,
+    storage: &Arc<dyn BackupStorage>,
+    run_mode: &Arc<RestoreRunMode>,
+) -> Result<EpochHistory> {
+    let mut epoch_endings = Vec::new();
+    for backup in backups {
+        let previous_li = backup
+            .first_epoch
+            .checked_sub(1)
+            .and_then(|epoch| epoch_endings.get(epoch as usize))
+            .cloned();
+        let lis = EpochEndingRestoreController::new(
+            EpochEndingRestoreOpt {
+                manifest_handle: backup.manifest,
+                trusted_waypoints: trusted_waypoints.to_vec(),
+            },
+            global_opt.clone(),
+            Arc::clone(storage),
+            Arc::clone(run_mode),
+        )
+        .run_with_previous_ledger_info(previous_li)
+        .await?;
+
+        let num_known = epoch_endings.len() as u64;
+        epoch_endings.extend(
+            lis.into_iter()
+                .filter(|li| li.ledger_info().epoch() >= num_known),
+        );
+    }
+
+    EpochHistory::new(epoch_endings)
+}
@@ -0,0 +1,148 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
+    },
+    coordinators::restore_epoch_history,
+    metadata::cache::{sync_and_load, MetadataCacheOpt},
+    storage::BackupStorage,
+    utils::{GlobalRestoreOpt, RestoreRunMode},
+};
+use anyhow::{anyhow, ensure, Result};
+use libra_types::waypoint::Waypoint;
+use std::{cmp::max, sync::Arc};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct RestoreCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+
+    #[structopt(
+        long = "trust-waypoint",
+        required = true,
+        help = "Trusted waypoint, repeat to provide multiple. Used to verify the epoch ending \
+        LedgerInfos, see `db-restore epoch-ending --help`. At least one is required, usually the \
+        genesis waypoint."
+    )]
+    pub trusted_waypoints: Vec<Waypoint>,
+}
+
+/// Restores a DB to the target version with whatever is in the backup storage, by picking the
+/// latest state snapshot not newer than the target version and replaying transactions on top of
+/// it, after restoring and verifying all epoch ending LedgerInfos up to the target version.
+pub struct RestoreCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    global_opt: GlobalRestoreOpt,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints: Vec<Waypoint>,
+}
+
+impl RestoreCoordinator {
+    pub fn new(
+        opt: RestoreCoordinatorOpt,
+        global_opt: GlobalRestoreOpt,
+        storage: Arc<dyn BackupStorage>,
+        run_mode: Arc<RestoreRunMode>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode,
+            global_opt,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            trusted_waypoints: opt.trusted_waypoints,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        // Without a trusted waypoint, signatures on the LedgerInfos are not verified at all.
+        ensure!(
+            !self.trusted_waypoints.is_empty(),
+            "At least one trusted waypoint is required to restore."
+        );
+        let metadata_view = sync_and_load(&self.metadata_cache_opt, &self.storage).await?;
+        let latest_transaction_version = metadata_view
+            .get_storage_state()
+            .latest_transaction_version
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        let target_version = if self.global_opt.target_version > latest_transaction_version {
+            println!(
+                "Target version {} is newer than the latest transaction in the backup storage, \
+                restoring to version {} instead.",
+                self.global_opt.target_version, latest_transaction_version,
+            );
+            latest_transaction_version
+        } else {
+            self.global_opt.target_version
+        };
+        let global_opt = GlobalRestoreOpt {
+            target_version,
+            ..self.global_opt.clone()
+        };
+
+        let plan = metadata_view.select_for_restore(target_version)?;
+        println!("Restore plan: {:#?}", plan);
+
+        let epoch_history = Arc::new(
+            restore_epoch_history(
+                plan.epoch_endings,
+                &self.trusted_waypoints,
+                &global_opt,
+                &self.storage,
+                &self.run_mode,
+            )
+            .await?,
+        );
+
+        if let Some(backup) = &plan.state_snapshot {
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                    version: backup.version,
+                },
+                global_opt.clone(),
+                Arc::clone(&self.storage),
+                Arc::clone(&self.run_mode),
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await?;
+        }
+
+        // Everything after the state snapshot is replayed, or everything if there's no snapshot.
+        let first_to_replay = plan.state_snapshot.as_ref().map_or(0, |s| s.version + 1);
+        let mut next_version = plan.transactions.first().map_or(0, |t| t.first_version);
+        for backup in plan.transactions {
+            // Transactions already restored from the previous backup are saved over instead of
+            // being replayed again.
+            TransactionRestoreController::new(
+                TransactionRestoreOpt {
+                    manifest_handle: backup.manifest,
+                    replay_from_version: max(first_to_replay, next_version),
+                },
+                global_opt.clone(),
+                Arc::clone(&self.storage),
+                Arc::clone(&self.run_mode),
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await?;
+            next_version = backup.last_version + 1;
+        }
+
+        if let RestoreRunMode::Restore { restore_handler } = self.run_mode.as_ref() {
+            ensure!(
+                restore_handler.libradb.get_startup_info()?.is_some(),
+                "Restored DB is not usable: no LedgerInfo was restored."
+            );
+        }
+        println!("Finished restoring DB to version {}.", target_version);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+        verify::{VerifyCoordinator, VerifyCoordinatorOpt},
+    },
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        view::BackupStorageState,
+    },
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{tmp_db_empty, tmp_db_with_random_content},
+        GlobalBackupOpt, GlobalRestoreOpt, RestoreRunMode,
+    },
+};
+use backup_service::start_backup_service;
+use executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+use libra_config::utils::get_available_port;
+use libra_temppath::TempPath;
+use libra_types::waypoint::Waypoint;
+use libradb::GetRestoreHandler;
+use std::{fs::remove_file, path::PathBuf, str::FromStr, sync::Arc};
+use storage_interface::DbReader;
+use tokio::time::Duration;
+
+#[test]
+fn backup_coordinator_round() {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let db_state = src_db.get_backup_handler().get_db_state().unwrap().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let port = get_available_port();
+    let mut rt = start_backup_service(port, src_db);
+    let client = Arc::new(BackupServiceClient::new(port));
+
+    let state_snapshot_interval = 5;
+    let transaction_batch_size = 3;
+    let coordinator = BackupCoordinator::new(
+        BackupCoordinatorOpt {
+            metadata_cache_opt: MetadataCacheOpt::default(),
+            state_snapshot_interval,
+            transaction_batch_size,
+            poll_interval_secs: 1,
+        },
+        GlobalBackupOpt {
+            max_chunk_size: 1024,
+        },
+        client,
+        Arc::clone(&store),
+    );
+
+    let mut state = BackupStorageState::default();
+    rt.block_on(coordinator.run_round(&mut state)).unwrap();
+
+    let num_full_batches = (db_state.committed_version + 1) / transaction_batch_size as u64;
+    assert_eq!(
+        state,
+        BackupStorageState {
+            latest_epoch_ending_epoch: db_state.epoch.checked_sub(1),
+            latest_state_snapshot_version: Some(
+                db_state.committed_version / state_snapshot_interval * state_snapshot_interval
+            ),
+            latest_transaction_version: (num_full_batches * transaction_batch_size as u64)
+                .checked_sub(1),
+        }
+    );
+
+    // Progress is recoverable from the storage, e.g. after a restart.
+    assert_eq!(
+        rt.block_on(sync_and_load(&MetadataCacheOpt::default(), &store))
+            .unwrap()
+            .get_storage_state(),
+        state
+    );
+
+    // Nothing new to back up.
+    let num_metadata_files = rt.block_on(store.list_metadata_files()).unwrap().len();
+    rt.block_on(coordinator.run_round(&mut state)).unwrap();
+    assert_eq!(
+        rt.block_on(store.list_metadata_files()).unwrap().len(),
+        num_metadata_files
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn backup_verify_and_restore_coordinators() {
+    let src_db = test_execution_with_storage_impl();
+    let latest_version = src_db.get_latest_version().unwrap();
+    let genesis_li = src_db
+        .get_epoch_ending_ledger_infos(0, 1)
+        .unwrap()
+        .0
+        .remove(0);
+    let waypoint = Waypoint::new_epoch_boundary(genesis_li.ledger_info()).unwrap();
+    let (_tgt_db_dir, tgt_db) = tmp_db_empty();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let port = get_available_port();
+    let mut rt = start_backup_service(port, Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(port));
+
+    let mut state = BackupStorageState::default();
+    rt.block_on(
+        BackupCoordinator::new(
+            BackupCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::default(),
+                state_snapshot_interval: 5,
+                transaction_batch_size: 1,
+                poll_interval_secs: 1,
+            },
+            GlobalBackupOpt {
+                max_chunk_size: 1024,
+            },
+            client,
+            Arc::clone(&store),
+        )
+        .run_round(&mut state),
+    )
+    .unwrap();
+    assert_eq!(state.latest_transaction_version, Some(latest_version));
+
+    let verify = |trusted_waypoints| {
+        VerifyCoordinator::new(
+            VerifyCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::default(),
+                trusted_waypoints,
+            },
+            Arc::clone(&store),
+        )
+        .run()
+    };
+    rt.block_on(verify(vec![waypoint])).unwrap();
+    assert!(rt.block_on(verify(vec![])).is_err());
+    let bad_waypoint = Waypoint::from_str(&format!("0:{}", "00".repeat(32))).unwrap();
+    assert!(rt.block_on(verify(vec![bad_waypoint])).is_err());
+
+    // Nothing is restored without a trusted waypoint.
+    assert!(rt
+        .block_on(
+            RestoreCoordinator::new(
+                RestoreCoordinatorOpt {
+                    metadata_cache_opt: MetadataCacheOpt::default(),
+                    trusted_waypoints: vec![],
+                },
+                GlobalRestoreOpt {
+                    db_dir: PathBuf::new(),
+                    target_version: latest_version,
+                },
+                Arc::clone(&store),
+                Arc::new(RestoreRunMode::Restore {
+                    restore_handler: Arc::new(tgt_db.get_restore_handler()),
+                }),
+            )
+            .run(),
+        )
+        .is_err());
+    assert!(tgt_db.get_startup_info().unwrap().is_none());
+
+    rt.block_on(
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::default(),
+                trusted_waypoints: vec![waypoint],
+            },
+            GlobalRestoreOpt {
+                db_dir: PathBuf::new(),
+                target_version: latest_version,
+            },
+            Arc::clone(&store),
+            Arc::new(RestoreRunMode::Restore {
+                restore_handler: Arc::new(tgt_db.get_restore_handler()),
+            }),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let startup_info = tgt_db.get_startup_info().unwrap().unwrap();
+    assert_eq!(
+        startup_info.latest_ledger_info.ledger_info().version(),
+        latest_version
+    );
+    assert_eq!(
+        src_db.get_latest_state_root().unwrap(),
+        tgt_db.get_latest_state_root().unwrap()
+    );
+    let num_txns = latest_version as usize + 1;
+    assert_eq!(
+        src_db
+            .get_backup_handler()
+            .get_transaction_iter(0, num_txns)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap(),
+        tgt_db
+            .get_backup_handler()
+            .get_transaction_iter(0, num_txns)
+            .unwrap()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap()
+    );
+
+    // A gap in the transaction backups is reported.
+    remove_file(
+        backup_dir
+            .path()
+            .join("metadata")
+            .join("transaction_1-1.meta"),
+    )
+    .unwrap();
+    assert!(rt.block_on(verify(vec![waypoint])).is_err());
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
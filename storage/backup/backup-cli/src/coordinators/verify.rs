@@ -0,0 +1,144 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
+    },
+    coordinators::restore_epoch_history,
+    metadata::cache::{sync_and_load, MetadataCacheOpt},
+    storage::BackupStorage,
+    utils::{GlobalRestoreOpt, RestoreRunMode},
+};
+use anyhow::{ensure, Context, Result};
+use libra_types::{transaction::Version, waypoint::Waypoint};
+use std::{path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct VerifyCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+
+    #[structopt(
+        long = "trust-waypoint",
+        required = true,
+        help = "Trusted waypoint, repeat to provide multiple. Used to verify the epoch ending \
+        LedgerInfos, see `db-restore epoch-ending --help`. At least one is required, usually the \
+        genesis waypoint."
+    )]
+    pub trusted_waypoints: Vec<Waypoint>,
+}
+
+/// Verifies every backup in the backup storage the same way a restore does, without writing
+/// anything anywhere.
+///
+/// The epoch ending backups are verified first, and the epoch history they form is then used to
+/// verify the LedgerInfos carried by the transaction backups. Gaps and backups failing the
+/// verification are reported all together in the end.
+pub struct VerifyCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints: Vec<Waypoint>,
+}
+
+impl VerifyCoordinator {
+    pub fn new(opt: VerifyCoordinatorOpt, storage: Arc<dyn BackupStorage>) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            trusted_waypoints: opt.trusted_waypoints,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        // Without a trusted waypoint, signatures on the LedgerInfos are not verified at all.
+        ensure!(
+            !self.trusted_waypoints.is_empty(),
+            "At least one trusted waypoint is required to verify backups."
+        );
+        let metadata_view = sync_and_load(&self.metadata_cache_opt, &self.storage).await?;
+        let run_mode = Arc::new(RestoreRunMode::Verify);
+        let global_opt = GlobalRestoreOpt {
+            db_dir: PathBuf::new(),
+            target_version: Version::max_value(),
+        };
+
+        // Everything else is verified against the epoch history, so failing to build it is fatal.
+        metadata_view.select_epoch_ending_backups(Version::max_value())?;
+        let epoch_history = Arc::new(
+            restore_epoch_history(
+                metadata_view.epoch_ending_backups().to_vec(),
+                &self.trusted_waypoints,
+                &global_opt,
+                &self.storage,
+                &run_mode,
+            )
+            .await
+            .context("Failed verifying epoch ending backups.")?,
+        );
+
+        let mut errors = Vec::new();
+        for (first, last) in metadata_view.get_transaction_gaps() {
+            errors.push(format!(
+                "No transaction backup covers versions [{}, {}].",
+                first, last,
+            ));
+        }
+
+        for backup in metadata_view.state_snapshot_backups() {
+            if let Err(e) = StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                    version: backup.version,
+                },
+                global_opt.clone(),
+                Arc::clone(&self.storage),
+                Arc::clone(&run_mode),
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await
+            {
+                errors.push(format!(
+                    "State snapshot backup {} failed verification: {:?}",
+                    backup.manifest, e,
+                ));
+            }
+        }
+
+        for backup in metadata_view.transaction_backups() {
+            if let Err(e) = TransactionRestoreController::new(
+                TransactionRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                    replay_from_version: Version::max_value(),
+                },
+                global_opt.clone(),
+                Arc::clone(&self.storage),
+                Arc::clone(&run_mode),
+                Some(Arc::clone(&epoch_history)),
+            )
+            .run()
+            .await
+            {
+                errors.push(format!(
+                    "Transaction backup {} failed verification: {:?}",
+                    backup.manifest, e,
+                ));
+            }
+        }
+
+        for error in &errors {
+            println!("{}", error);
+        }
+        ensure!(
+            errors.is_empty(),
+            "Backup verification failed with {} problem(s).",
+            errors.len(),
+        );
+        println!("Backup verification success.");
+
+        Ok(())
+    }
+}
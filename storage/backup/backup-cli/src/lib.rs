@@ -2,5 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod backup_types;
+pub mod coordinators;
+pub mod metadata;
 pub mod storage;
 pub mod utils;
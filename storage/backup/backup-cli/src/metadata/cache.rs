@@ -0,0 +1,125 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata::{parse_metadata_file, view::MetadataView, Metadata},
+    storage::{BackupStorage, FileHandle},
+    utils::storage_ext::BackupStorageExt,
+};
+use anyhow::{anyhow, Result};
+use libra_crypto::HashValue;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use structopt::StructOpt;
+use tokio::{
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, OpenOptions},
+    io::AsyncWriteExt,
+};
+
+#[derive(Clone, Default, StructOpt)]
+pub struct MetadataCacheOpt {
+    #[structopt(
+        long = "metadata-cache-dir",
+        parse(from_os_str),
+        help = "Local dir to cache metadata files in. If shared across runs, only metadata files \
+        new in the backup storage will be downloaded. Don't share it across backup storages. \
+        [Defaults to no caching.]"
+    )]
+    pub dir: Option<PathBuf>,
+}
+
+impl MetadataCacheOpt {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+}
+
+/// Brings the local cache (if configured) up to date with the metadata files in the backup storage
+/// and loads all metadata.
+pub async fn sync_and_load(
+    opt: &MetadataCacheOpt,
+    storage: &Arc<dyn BackupStorage>,
+) -> Result<MetadataView> {
+    let remote_file_handles = storage.list_metadata_files().await?;
+    let metadata = match &opt.dir {
+        Some(dir) => sync_and_load_cache(dir, storage, &remote_file_handles).await?,
+        None => {
+            let mut metadata = Vec::new();
+            for file_handle in &remote_file_handles {
+                let content = String::from_utf8(storage.read_all(file_handle).await?)?;
+                metadata.extend(parse_metadata_file(&content, file_handle)?);
+            }
+            metadata
+        }
+    };
+
+    Ok(metadata.into())
+}
+
+/// A file in the cache is named by the hash of its `FileHandle` in the backup storage. The content
+/// of a metadata file is never expected to change, so a file already cached is never re-downloaded.
+fn cache_file_name(file_handle: &FileHandle) -> String {
+    HashValue::sha3_256_of(file_handle.as_bytes()).to_hex()
+}
+
+async fn sync_and_load_cache(
+    dir: &Path,
+    storage: &Arc<dyn BackupStorage>,
+    remote_file_handles: &[FileHandle],
+) -> Result<Vec<Metadata>> {
+    create_dir_all(dir).await?;
+
+    let mut cached = HashSet::new();
+    let mut entries = read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        cached.insert(
+            entry
+                .file_name()
+                .into_string()
+                .map_err(|s| anyhow!("into_string failed for OsString '{:?}'", s))?,
+        );
+    }
+
+    let expected: HashSet<_> = remote_file_handles.iter().map(cache_file_name).collect();
+    // Remove files gone from the backup storage, as well as leftover temporary files.
+    for name in cached.difference(&expected) {
+        remove_file(dir.join(name)).await?;
+    }
+
+    let mut num_downloaded = 0;
+    for file_handle in remote_file_handles {
+        let name = cache_file_name(file_handle);
+        if cached.contains(&name) {
+            continue;
+        }
+        // Download to a temporary file first, so an interrupted download doesn't leave a
+        // partial file in the cache.
+        let tmp_path = dir.join(format!("{}.tmp", name));
+        let mut tmp_file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+            .await?;
+        tmp_file
+            .write_all(&storage.read_all(file_handle).await?)
+            .await?;
+        tmp_file.sync_all().await?;
+        rename(&tmp_path, dir.join(&name)).await?;
+        num_downloaded += 1;
+    }
+    println!(
+        "Metadata cache synced. {} files in total, {} newly downloaded.",
+        expected.len(),
+        num_downloaded,
+    );
+
+    let mut metadata = Vec::new();
+    for file_handle in remote_file_handles {
+        let content = read_to_string(dir.join(cache_file_name(file_handle))).await?;
+        metadata.extend(parse_metadata_file(&content, file_handle)?);
+    }
+    Ok(metadata)
+}
@@ -0,0 +1,120 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod cache;
+pub mod view;
+
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{BackupStorage, FileHandle, FileHandleRef, ShellSafeName, TextLine};
+use anyhow::{Context, Result};
+use libra_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryInto, sync::Arc};
+
+/// A line of metadata saved alongside each backup, describing what the backup contains and where
+/// its manifest is, so that the content of a `BackupStorage` can be discovered by listing and
+/// reading the metadata files.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub enum Metadata {
+    EpochEndingBackup(EpochEndingBackupMeta),
+    StateSnapshotBackup(StateSnapshotBackupMeta),
+    TransactionBackup(TransactionBackupMeta),
+}
+
+impl Metadata {
+    pub fn new_epoch_ending_backup(
+        first_epoch: u64,
+        last_epoch: u64,
+        first_version: Version,
+        last_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::EpochEndingBackup(EpochEndingBackupMeta {
+            first_epoch,
+            last_epoch,
+            first_version,
+            last_version,
+            manifest,
+        })
+    }
+
+    pub fn new_state_snapshot_backup(version: Version, manifest: FileHandle) -> Self {
+        Self::StateSnapshotBackup(StateSnapshotBackupMeta { version, manifest })
+    }
+
+    pub fn new_transaction_backup(
+        first_version: Version,
+        last_version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::TransactionBackup(TransactionBackupMeta {
+            first_version,
+            last_version,
+            manifest,
+        })
+    }
+
+    pub fn name(&self) -> ShellSafeName {
+        match self {
+            Self::EpochEndingBackup(e) => {
+                format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
+            }
+            Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::TransactionBackup(t) => {
+                format!("transaction_{}-{}.meta", t.first_version, t.last_version)
+            }
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    pub fn to_text_line(&self) -> Result<TextLine> {
+        TextLine::new(&serde_json::to_string(self)?)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, Ord, PartialEq, PartialOrd)]
+pub struct EpochEndingBackupMeta {
+    pub first_epoch: u64,
+    pub last_epoch: u64,
+    pub first_version: Version,
+    pub last_version: Version,
+    pub manifest: FileHandle,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, Ord, PartialEq, PartialOrd)]
+pub struct StateSnapshotBackupMeta {
+    pub version: Version,
+    pub manifest: FileHandle,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, Ord, PartialEq, PartialOrd)]
+pub struct TransactionBackupMeta {
+    pub first_version: Version,
+    pub last_version: Version,
+    pub manifest: FileHandle,
+}
+
+/// Saves the metadata of a backup to the storage, should be called after the manifest is written.
+pub async fn save_metadata(storage: &Arc<dyn BackupStorage>, metadata: &Metadata) -> Result<()> {
+    storage
+        .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+        .await
+}
+
+/// Parses a metadata file, which consists of lines of `Metadata` in JSON.
+pub(crate) fn parse_metadata_file(
+    content: &str,
+    file_handle: &FileHandleRef,
+) -> Result<Vec<Metadata>> {
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed parsing metadata file {}", file_handle))
+        })
+        .collect()
+}
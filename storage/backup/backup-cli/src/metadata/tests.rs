@@ -0,0 +1,191 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        parse_metadata_file, save_metadata,
+        view::MetadataView,
+        Metadata,
+    },
+    storage::{local_fs::LocalFs, BackupStorage},
+};
+use libra_temppath::TempPath;
+use std::{fs::read_dir, sync::Arc};
+use tokio::runtime::Runtime;
+
+fn epoch_ending(first_epoch: u64, last_epoch: u64, first_ver: u64, last_ver: u64) -> Metadata {
+    Metadata::new_epoch_ending_backup(
+        first_epoch,
+        last_epoch,
+        first_ver,
+        last_ver,
+        format!("epoch_ending_{}-{}", first_epoch, last_epoch),
+    )
+}
+
+fn state_snapshot(version: u64) -> Metadata {
+    Metadata::new_state_snapshot_backup(version, format!("state_{}", version))
+}
+
+fn transaction(first_ver: u64, last_ver: u64) -> Metadata {
+    Metadata::new_transaction_backup(
+        first_ver,
+        last_ver,
+        format!("txn_{}-{}", first_ver, last_ver),
+    )
+}
+
+fn manifests<T>(selected: &[T], f: impl Fn(&T) -> String) -> Vec<String> {
+    selected.iter().map(f).collect()
+}
+
+#[test]
+fn test_text_line_round_trip() {
+    let metadata = vec![
+        epoch_ending(0, 1, 0, 10),
+        state_snapshot(5),
+        transaction(0, 9),
+    ];
+    let content = metadata
+        .iter()
+        .map(|m| m.to_text_line().unwrap().as_ref().to_string())
+        .collect::<String>();
+    assert_eq!(parse_metadata_file(&content, "file").unwrap(), metadata);
+    assert!(parse_metadata_file("{}\n", "file").is_err());
+}
+
+#[test]
+fn test_storage_state() {
+    let view: MetadataView = vec![
+        transaction(10, 19),
+        transaction(0, 9),
+        state_snapshot(7),
+        state_snapshot(3),
+    ]
+    .into();
+    let state = view.get_storage_state();
+    assert_eq!(state.latest_epoch_ending_epoch, None);
+    assert_eq!(state.latest_state_snapshot_version, Some(7));
+    assert_eq!(state.latest_transaction_version, Some(19));
+}
+
+#[test]
+fn test_select_transaction_backups() {
+    let view: MetadataView = vec![
+        transaction(20, 29),
+        transaction(0, 9),
+        transaction(5, 19),
+        transaction(10, 14),
+        transaction(0, 9), // duplicated
+    ]
+    .into();
+    let select = |first, target| {
+        view.select_transaction_backups(first, target)
+            .map(|selected| manifests(&selected, |t| t.manifest.clone()).join(","))
+    };
+
+    assert_eq!(select(0, 25).unwrap(), "txn_0-9,txn_5-19,txn_20-29");
+    assert_eq!(select(11, 12).unwrap(), "txn_5-19");
+    assert_eq!(select(3, 3).unwrap(), "txn_0-9");
+    assert!(select(25, 30).is_err());
+}
+
+#[test]
+fn test_transaction_gaps() {
+    let view: MetadataView = vec![
+        transaction(5, 9),
+        transaction(10, 19),
+        transaction(12, 14),
+        transaction(30, 39),
+    ]
+    .into();
+    assert_eq!(view.get_transaction_gaps(), vec![(0, 4), (20, 29)]);
+
+    let view: MetadataView = vec![transaction(0, 9), transaction(5, 19)].into();
+    assert!(view.get_transaction_gaps().is_empty());
+}
+
+#[test]
+fn test_select_epoch_ending_backups() {
+    let view: MetadataView = vec![
+        epoch_ending(0, 2, 0, 100),
+        epoch_ending(1, 4, 50, 200),
+        epoch_ending(5, 5, 300, 300),
+    ]
+    .into();
+    let select = |target| {
+        view.select_epoch_ending_backups(target)
+            .map(|selected| manifests(&selected, |e| e.manifest.clone()).join(","))
+    };
+
+    assert_eq!(select(0).unwrap(), "epoch_ending_0-2");
+    assert_eq!(select(150).unwrap(), "epoch_ending_0-2,epoch_ending_1-4");
+    assert_eq!(
+        select(1000).unwrap(),
+        "epoch_ending_0-2,epoch_ending_1-4,epoch_ending_5-5"
+    );
+
+    let with_gap: MetadataView =
+        vec![epoch_ending(0, 2, 0, 100), epoch_ending(4, 5, 300, 400)].into();
+    assert!(with_gap.select_epoch_ending_backups(200).is_err());
+}
+
+#[test]
+fn test_select_for_restore() {
+    let view: MetadataView = vec![
+        epoch_ending(0, 0, 0, 0),
+        epoch_ending(1, 1, 15, 15),
+        state_snapshot(0),
+        state_snapshot(12),
+        state_snapshot(20),
+        transaction(0, 9),
+        transaction(10, 19),
+    ]
+    .into();
+
+    let plan = view.select_for_restore(15).unwrap();
+    assert_eq!(plan.state_snapshot.unwrap().version, 12);
+    assert_eq!(
+        manifests(&plan.transactions, |t| t.manifest.clone()),
+        vec!["txn_10-19"]
+    );
+    assert_eq!(
+        manifests(&plan.epoch_endings, |e| e.manifest.clone()),
+        vec!["epoch_ending_0-0", "epoch_ending_1-1"]
+    );
+
+    // No transactions after version 19.
+    assert!(view.select_for_restore(25).is_err());
+}
+
+#[test]
+fn test_sync_and_load_with_cache() {
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let cache_dir = TempPath::new();
+    let opt = MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf()));
+    let mut rt = Runtime::new().unwrap();
+
+    rt.block_on(save_metadata(&store, &transaction(0, 9)))
+        .unwrap();
+    let view = rt.block_on(sync_and_load(&opt, &store)).unwrap();
+    assert_eq!(view.transaction_backups().len(), 1);
+
+    rt.block_on(save_metadata(&store, &transaction(10, 19)))
+        .unwrap();
+    rt.block_on(save_metadata(&store, &state_snapshot(15)))
+        .unwrap();
+    let view = rt.block_on(sync_and_load(&opt, &store)).unwrap();
+    assert_eq!(view.transaction_backups().len(), 2);
+    assert_eq!(view.state_snapshot_backups().len(), 1);
+    assert_eq!(read_dir(cache_dir.path()).unwrap().count(), 3);
+
+    // Same result without a cache.
+    let view = rt
+        .block_on(sync_and_load(&MetadataCacheOpt::default(), &store))
+        .unwrap();
+    assert_eq!(view.transaction_backups().len(), 2);
+    assert_eq!(view.state_snapshot_backups().len(), 1);
+}
@@ -0,0 +1,193 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metadata::{
+    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+};
+use anyhow::{anyhow, ensure, Result};
+use libra_types::transaction::Version;
+use std::cmp::max;
+
+/// An index over all the metadata in a backup storage.
+pub struct MetadataView {
+    epoch_ending_backups: Vec<EpochEndingBackupMeta>,
+    state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    transaction_backups: Vec<TransactionBackupMeta>,
+}
+
+/// What's already in the backup storage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BackupStorageState {
+    pub latest_epoch_ending_epoch: Option<u64>,
+    pub latest_state_snapshot_version: Option<Version>,
+    pub latest_transaction_version: Option<Version>,
+}
+
+/// The minimal set of backups needed to restore a DB to a target version.
+#[derive(Debug)]
+pub struct RestorePlan {
+    /// Covering epochs from 0 up to the epoch the target version is in.
+    pub epoch_endings: Vec<EpochEndingBackupMeta>,
+    /// The latest state snapshot at or before the target version, if any.
+    pub state_snapshot: Option<StateSnapshotBackupMeta>,
+    /// Covering versions from the state snapshot (or genesis if there's none) up to the target
+    /// version.
+    pub transactions: Vec<TransactionBackupMeta>,
+}
+
+impl From<Vec<Metadata>> for MetadataView {
+    fn from(metadata_vec: Vec<Metadata>) -> Self {
+        let mut epoch_ending_backups = Vec::new();
+        let mut state_snapshot_backups = Vec::new();
+        let mut transaction_backups = Vec::new();
+
+        for meta in metadata_vec {
+            match meta {
+                Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
+                Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::TransactionBackup(t) => transaction_backups.push(t),
+            }
+        }
+        epoch_ending_backups.sort_unstable();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort_unstable();
+        state_snapshot_backups.dedup();
+        transaction_backups.sort_unstable();
+        transaction_backups.dedup();
+
+        Self {
+            epoch_ending_backups,
+            state_snapshot_backups,
+            transaction_backups,
+        }
+    }
+}
+
+impl MetadataView {
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
+    pub fn get_storage_state(&self) -> BackupStorageState {
+        BackupStorageState {
+            latest_epoch_ending_epoch: self.epoch_ending_backups.iter().map(|e| e.last_epoch).max(),
+            latest_state_snapshot_version: self
+                .state_snapshot_backups
+                .iter()
+                .map(|s| s.version)
+                .max(),
+            latest_transaction_version: self
+                .transaction_backups
+                .iter()
+                .map(|t| t.last_version)
+                .max(),
+        }
+    }
+
+    /// Ranges of versions (both sides inclusive) before the latest transaction in the backup
+    /// storage that no transaction backup covers.
+    pub fn get_transaction_gaps(&self) -> Vec<(Version, Version)> {
+        let mut gaps = Vec::new();
+        let mut next_version = 0;
+        for backup in &self.transaction_backups {
+            if backup.first_version > next_version {
+                gaps.push((next_version, backup.first_version - 1));
+            }
+            next_version = max(next_version, backup.last_version + 1);
+        }
+        gaps
+    }
+
+    /// Selects the latest state snapshot at or before `target_version`.
+    pub fn select_state_snapshot(
+        &self,
+        target_version: Version,
+    ) -> Option<StateSnapshotBackupMeta> {
+        self.state_snapshot_backups
+            .iter()
+            .filter(|s| s.version <= target_version)
+            .max_by_key(|s| s.version)
+            .cloned()
+    }
+
+    /// Selects the fewest transaction backups that together cover
+    /// [`first_version`, `target_version`] (right side inclusive).
+    pub fn select_transaction_backups(
+        &self,
+        first_version: Version,
+        target_version: Version,
+    ) -> Result<Vec<TransactionBackupMeta>> {
+        let mut res = Vec::new();
+        let mut next_version = first_version;
+        while next_version <= target_version {
+            let backup = self
+                .transaction_backups
+                .iter()
+                .filter(|t| t.first_version <= next_version && t.last_version >= next_version)
+                .max_by_key(|t| t.last_version)
+                .ok_or_else(|| anyhow!("No transaction backup has version {}.", next_version))?;
+            res.push(backup.clone());
+            next_version = backup.last_version + 1;
+        }
+        Ok(res)
+    }
+
+    /// Selects the fewest epoch ending backups that together cover all epochs from 0 until the one
+    /// `target_version` is in, or until the latest ended epoch if that's earlier.
+    pub fn select_epoch_ending_backups(
+        &self,
+        target_version: Version,
+    ) -> Result<Vec<EpochEndingBackupMeta>> {
+        let mut res: Vec<EpochEndingBackupMeta> = Vec::new();
+        let mut next_epoch = 0;
+        while res
+            .last()
+            .map_or(true, |last| last.last_version < target_version)
+        {
+            match self
+                .epoch_ending_backups
+                .iter()
+                .filter(|e| e.first_epoch <= next_epoch && e.last_epoch >= next_epoch)
+                .max_by_key(|e| e.last_epoch)
+            {
+                Some(backup) => {
+                    res.push(backup.clone());
+                    next_epoch = backup.last_epoch + 1;
+                }
+                None => {
+                    ensure!(
+                        self.epoch_ending_backups
+                            .iter()
+                            .all(|e| e.first_epoch < next_epoch),
+                        "No epoch ending backup has epoch {}.",
+                        next_epoch,
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// Selects the backups needed to restore a DB to `target_version`.
+    pub fn select_for_restore(&self, target_version: Version) -> Result<RestorePlan> {
+        let state_snapshot = self.select_state_snapshot(target_version);
+        // The transaction at the state snapshot version is needed as well, so the DB knows the
+        // transaction accumulator at that version.
+        let first_txn_version = state_snapshot.as_ref().map_or(0, |s| s.version);
+
+        Ok(RestorePlan {
+            epoch_endings: self.select_epoch_ending_backups(target_version)?,
+            state_snapshot,
+            transactions: self.select_transaction_backups(first_txn_version, target_version)?,
+        })
+    }
+}
@@ -0,0 +1,290 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming authenticated encryption of backup files, with AES-256-GCM.
+//!
+//! A file is encrypted in segments so that it can be written and read without being held in
+//! memory as a whole:
+//!
+//! ```txt
+//! file        := nonce_prefix || segment*
+//! segment     := len_and_flag || AES-256-GCM(key, nonce, plaintext) // ciphertext with the tag
+//! nonce       := nonce_prefix || segment_index || last_flag
+//! ```
+//!
+//! where `nonce_prefix` is 7 random bytes picked for each file, `segment_index` is a u32
+//! big-endian integer counting from 0, and `last_flag` is 1 for the last segment and 0 otherwise.
+//! `len_and_flag` is the length of the ciphertext as a u32 big-endian integer, with the highest
+//! bit set for the last segment. Because the segment index and the last flag go into the nonce,
+//! reordered, dropped or truncated segments all fail the decryption.
+//!
+//! Each segment is encrypted with the handle of the file in the inner storage as the associated
+//! data, so a file swapped with another one, e.g. a chunk or a manifest replaced by one from
+//! another backup, fails the decryption too.
+
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
+    Aes256Gcm,
+};
+use anyhow::{bail, ensure, Result};
+use futures::{
+    future, ready,
+    stream::{self, TryStreamExt},
+};
+use std::{
+    cmp::min,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// The length in bytes of the AES-256-GCM key.
+pub const KEY_LEN: usize = 32;
+
+const NONCE_PREFIX_LEN: usize = 7;
+const TAG_LEN: usize = 16;
+/// Max size of the plaintext of a segment.
+const SEGMENT_SIZE: usize = 64 * 1024;
+const LAST_SEGMENT_FLAG: u32 = 1 << 31;
+
+#[derive(Clone)]
+pub struct EncryptionKey([u8; KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        ensure!(
+            key.len() == KEY_LEN,
+            "Encryption key must be {} bytes, got {}.",
+            KEY_LEN,
+            key.len(),
+        );
+        let mut bytes = [0u8; KEY_LEN];
+        bytes.copy_from_slice(key);
+        Ok(Self(bytes))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(GenericArray::from_slice(&self.0))
+    }
+}
+
+fn nonce(prefix: &[u8; NONCE_PREFIX_LEN], segment_index: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..11].copy_from_slice(&segment_index.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+fn to_io_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// Encrypts what's written to it and writes the result to `inner`. The last segment is only
+/// written on shutdown, without which the file can't be decrypted.
+pub struct EncryptWriter<W> {
+    inner: W,
+    cipher: Aes256Gcm,
+    /// Associated data authenticated with each segment.
+    aad: Vec<u8>,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+    next_segment_index: u32,
+    /// Plaintext of the current segment.
+    plaintext: Vec<u8>,
+    /// Encrypted bytes not yet written to `inner`, and how many of them are written.
+    pending: Vec<u8>,
+    pending_written: usize,
+    finished: bool,
+}
+
+impl<W: AsyncWrite + Unpin> EncryptWriter<W> {
+    /// `aad` is authenticated but not encrypted, and must be provided again to decrypt.
+    pub fn new(inner: W, key: EncryptionKey, aad: Vec<u8>) -> Self {
+        let nonce_prefix: [u8; NONCE_PREFIX_LEN] = rand::random();
+        Self {
+            inner,
+            cipher: key.cipher(),
+            aad,
+            nonce_prefix,
+            next_segment_index: 0,
+            plaintext: Vec::with_capacity(SEGMENT_SIZE),
+            pending: nonce_prefix.to_vec(),
+            pending_written: 0,
+            finished: false,
+        }
+    }
+
+    fn seal_segment(&mut self, last: bool) -> io::Result<()> {
+        let nonce = nonce(&self.nonce_prefix, self.next_segment_index, last);
+        self.next_segment_index = self
+            .next_segment_index
+            .checked_add(1)
+            .ok_or_else(|| to_io_error("File too large to encrypt."))?;
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: &self.plaintext,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| to_io_error("Encryption failed."))?;
+        self.plaintext.clear();
+
+        let len_and_flag = (ciphertext.len() as u32) | (if last { LAST_SEGMENT_FLAG } else { 0 });
+        self.pending.extend_from_slice(&len_and_flag.to_be_bytes());
+        self.pending.extend_from_slice(&ciphertext);
+        Ok(())
+    }
+
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.pending_written < self.pending.len() {
+            let n = ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.pending[self.pending_written..])
+            )?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.pending_written += n;
+        }
+        self.pending.clear();
+        self.pending_written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for EncryptWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(Err(to_io_error("Writer already shut down.")));
+        }
+        ready!(this.poll_write_pending(cx))?;
+
+        let n = min(buf.len(), SEGMENT_SIZE - this.plaintext.len());
+        this.plaintext.extend_from_slice(&buf[..n]);
+        if this.plaintext.len() == SEGMENT_SIZE {
+            this.seal_segment(false)?;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    /// N.B. the current segment is not sealed until full, so not all data written is flushed.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.finished {
+            this.seal_segment(true)?;
+            this.finished = true;
+        }
+        ready!(this.poll_write_pending(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+struct Decryptor {
+    inner: Box<dyn AsyncRead + Send + Unpin>,
+    cipher: Aes256Gcm,
+    aad: Vec<u8>,
+    /// `None` until the header is read.
+    nonce_prefix: Option<[u8; NONCE_PREFIX_LEN]>,
+    next_segment_index: u32,
+}
+
+impl Decryptor {
+    /// Returns the plaintext of the next segment, and whether it's the last one.
+    async fn next_segment(&mut self) -> Result<(Vec<u8>, bool)> {
+        let nonce_prefix = match self.nonce_prefix {
+            Some(nonce_prefix) => nonce_prefix,
+            None => {
+                let mut nonce_prefix = [0u8; NONCE_PREFIX_LEN];
+                self.inner.read_exact(&mut nonce_prefix).await?;
+                self.nonce_prefix = Some(nonce_prefix);
+                nonce_prefix
+            }
+        };
+
+        let len_and_flag = self.inner.read_u32().await?;
+        let last = len_and_flag & LAST_SEGMENT_FLAG != 0;
+        let len = (len_and_flag & !LAST_SEGMENT_FLAG) as usize;
+        ensure!(
+            len >= TAG_LEN && len <= SEGMENT_SIZE + TAG_LEN,
+            "Bad segment length: {}",
+            len,
+        );
+        let mut ciphertext = vec![0u8; len];
+        self.inner.read_exact(&mut ciphertext).await?;
+
+        let nonce = nonce(&nonce_prefix, self.next_segment_index, last);
+        let plaintext = match self
+            .cipher
+            .decrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &self.aad,
+                },
+            )
+        {
+            Ok(plaintext) => plaintext,
+            Err(_) => bail!(
+                "Failed to decrypt segment {}, the file is corrupted or misplaced, or the key is wrong.",
+                self.next_segment_index,
+            ),
+        };
+        self.next_segment_index += 1;
+
+        if last {
+            let mut byte = [0u8; 1];
+            ensure!(
+                self.inner.read(&mut byte).await? == 0,
+                "Unexpected data after the last segment."
+            );
+        }
+        Ok((plaintext, last))
+    }
+}
+
+/// Decrypts what's written by `EncryptWriter`. Fails on read if the data is tampered with, the
+/// key or the associated data is wrong, or the file is truncated.
+pub fn decrypt(
+    inner: Box<dyn AsyncRead + Send + Unpin>,
+    key: EncryptionKey,
+    aad: Vec<u8>,
+) -> impl AsyncRead + Send + Unpin {
+    let decryptor = Decryptor {
+        inner,
+        cipher: key.cipher(),
+        aad,
+        nonce_prefix: None,
+        next_segment_index: 0,
+    };
+    let segments = stream::unfold(Some(decryptor), |decryptor| async move {
+        let mut decryptor = match decryptor {
+            Some(decryptor) => decryptor,
+            None => return None,
+        };
+        match decryptor.next_segment().await {
+            Ok((plaintext, last)) => {
+                Some((Ok(plaintext), if last { None } else { Some(decryptor) }))
+            }
+            Err(e) => Some((Err(to_io_error(e)), None)),
+        }
+    });
+
+    Box::pin(segments.try_filter(|plaintext| future::ready(!plaintext.is_empty())))
+        .into_async_read()
+        .compat()
+}
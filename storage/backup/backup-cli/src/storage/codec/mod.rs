@@ -0,0 +1,272 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod encryption;
+#[cfg(test)]
+mod tests;
+
+use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
+
+use crate::storage::{
+    codec::encryption::{decrypt, EncryptWriter, EncryptionKey},
+    BackupStorage, ShellSafeName, TextLine,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use async_compression::tokio_02::{bufread::ZstdDecoder, write::ZstdEncoder};
+use async_trait::async_trait;
+use libra_config::config::SecureBackend;
+use libra_secure_storage::{KVStorage, Storage};
+use std::{
+    collections::HashSet,
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use structopt::StructOpt;
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+
+#[derive(StructOpt)]
+pub struct CodecOpt {
+    #[structopt(
+        long = "compress",
+        help = "Compress new files with zstd. Compressed files are always decompressed on read, \
+        regardless of this flag."
+    )]
+    pub compress: bool,
+
+    #[structopt(
+        long = "encryption-key-storage",
+        parse(from_os_str),
+        help = "YAML file describing the secure storage that holds the encryption key, in the same \
+        format as secure backends in node configs. If set, new files are encrypted with \
+        AES-256-GCM, and encrypted files can be read."
+    )]
+    pub encryption_key_storage: Option<PathBuf>,
+
+    #[structopt(
+        long = "encryption-key-name",
+        default_value = "backup_encryption_key",
+        help = "Name of the encryption key in the secure storage. It must be 32 raw bytes."
+    )]
+    pub encryption_key_name: String,
+
+    #[structopt(
+        long = "allow-unencrypted",
+        help = "Read files that are not encrypted even though an encryption key is configured, e.g. \
+        backups made before encryption was turned on. Without it, such files are rejected, since \
+        file handles are not authenticated and anyone able to write to the storage could swap in \
+        plaintext files."
+    )]
+    pub allow_unencrypted: bool,
+}
+
+impl CodecOpt {
+    pub fn load_encryption_key(&self) -> Result<Option<EncryptionKey>> {
+        let path = match &self.encryption_key_storage {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let backend: SecureBackend = serde_yaml::from_str(&std::fs::read_to_string(path)?)
+            .with_context(|| format!("Failed to parse secure storage config {:?}.", path))?;
+        let key = Storage::from(&backend)
+            .get(&self.encryption_key_name)?
+            .value
+            .bytes()?;
+        Ok(Some(EncryptionKey::new(&key)?))
+    }
+
+    pub fn wrap(self, inner: Arc<dyn BackupStorage>) -> Result<Arc<dyn BackupStorage>> {
+        Ok(Arc::new(CodecStorage::new(
+            inner,
+            self.compress,
+            self.load_encryption_key()?,
+            self.allow_unencrypted,
+        )))
+    }
+}
+
+/// How a file is encoded.
+///
+/// It's recorded in the file handle, which is what the manifests refer to a file by, so a file
+/// can always be decoded regardless of the options in effect when reading it. Files not encoded
+/// keep the handle given by the underlying storage, so backups made before encoding existed
+/// remain readable.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Codec {
+    /// Compressed with zstd.
+    pub compression: bool,
+    /// Encrypted with AES-256-GCM, see `encryption`.
+    pub encryption: bool,
+}
+
+impl Codec {
+    const HANDLE_PREFIX: &'static str = "codec:";
+    const ZSTD: &'static str = "zstd";
+    const AES_256_GCM: &'static str = "aes256gcm";
+
+    fn is_identity(&self) -> bool {
+        !self.compression && !self.encryption
+    }
+
+    /// Handle of a file encoded with this codec, e.g. "codec:zstd+aes256gcm:<inner handle>".
+    pub fn encode_handle(&self, inner_handle: &FileHandleRef) -> FileHandle {
+        if self.is_identity() {
+            inner_handle.to_string()
+        } else {
+            format!("{}{}:{}", Self::HANDLE_PREFIX, self, inner_handle)
+        }
+    }
+
+    /// Reverse of `encode_handle`.
+    pub fn decode_handle(handle: &FileHandleRef) -> Result<(Self, &FileHandleRef)> {
+        if !handle.starts_with(Self::HANDLE_PREFIX) {
+            return Ok((Self::default(), handle));
+        }
+        let rest = &handle[Self::HANDLE_PREFIX.len()..];
+        let separator = rest
+            .find(':')
+            .ok_or_else(|| anyhow!("Bad file handle: {}", handle))?;
+        Ok((rest[..separator].parse()?, &rest[separator + 1..]))
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut names = Vec::new();
+        if self.compression {
+            names.push(Self::ZSTD);
+        }
+        if self.encryption {
+            names.push(Self::AES_256_GCM);
+        }
+        write!(f, "{}", names.join("+"))
+    }
+}
+
+impl FromStr for Codec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut codec = Self::default();
+        for name in s.split('+') {
+            match name {
+                Self::ZSTD => codec.compression = true,
+                Self::AES_256_GCM => codec.encryption = true,
+                _ => bail!("Unknown codec: {}", name),
+            }
+        }
+        Ok(codec)
+    }
+}
+
+/// Wraps another storage, encoding files on write and decoding them on read, so that any storage
+/// backend gets compression and encryption for free. Files are compressed before being
+/// encrypted, since encrypted data doesn't compress.
+///
+/// Metadata lines are passed through as is: they only carry versions and file handles, and need
+/// to stay readable for discovering what's in the storage.
+///
+/// The codec of a file comes from its handle, which is not authenticated. So once an encryption
+/// key is configured, only encrypted files and the metadata files listed by the storage are read,
+/// unless `allow_unencrypted` is set.
+pub struct CodecStorage {
+    inner: Arc<dyn BackupStorage>,
+    /// Codec for new files.
+    codec: Codec,
+    encryption_key: Option<EncryptionKey>,
+    allow_unencrypted: bool,
+    /// Metadata files listed by the inner storage, readable without encryption.
+    metadata_files: Mutex<HashSet<FileHandle>>,
+}
+
+impl CodecStorage {
+    pub fn new(
+        inner: Arc<dyn BackupStorage>,
+        compress: bool,
+        encryption_key: Option<EncryptionKey>,
+        allow_unencrypted: bool,
+    ) -> Self {
+        Self {
+            inner,
+            codec: Codec {
+                compression: compress,
+                encryption: encryption_key.is_some(),
+            },
+            encryption_key,
+            allow_unencrypted,
+            metadata_files: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl BackupStorage for CodecStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (inner_handle, mut file) = self.inner.create_for_write(backup_handle, name).await?;
+        if let Some(key) = &self.encryption_key {
+            file = Box::new(EncryptWriter::new(
+                file,
+                key.clone(),
+                inner_handle.as_bytes().to_vec(),
+            ));
+        }
+        if self.codec.compression {
+            file = Box::new(ZstdEncoder::new(file));
+        }
+        Ok((self.codec.encode_handle(&inner_handle), file))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let (codec, inner_handle) = Codec::decode_handle(file_handle)?;
+        if self.encryption_key.is_some()
+            && !codec.encryption
+            && !self.allow_unencrypted
+            && !self.metadata_files.lock().unwrap().contains(file_handle)
+        {
+            bail!(
+                "{} is not encrypted, but an encryption key is configured. Pass \
+                --allow-unencrypted to read it anyway.",
+                file_handle
+            );
+        }
+        let mut file = self.inner.open_for_read(inner_handle).await?;
+        if codec.encryption {
+            let key = self.encryption_key.clone().ok_or_else(|| {
+                anyhow!(
+                    "{} is encrypted, but no encryption key is configured.",
+                    file_handle
+                )
+            })?;
+            file = Box::new(decrypt(file, key, inner_handle.as_bytes().to_vec()));
+        }
+        if codec.compression {
+            file = Box::new(ZstdDecoder::new(BufReader::new(file)));
+        }
+        Ok(file)
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.inner.save_metadata_line(name, content).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let file_handles = self.inner.list_metadata_files().await?;
+        self.metadata_files
+            .lock()
+            .unwrap()
+            .extend(file_handles.iter().cloned());
+        Ok(file_handles)
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl, to_file_name,
+    },
+};
+use libra_secure_storage::{OnDiskStorage, Value};
+use libra_temppath::TempPath;
+use proptest::{collection::vec, prelude::*};
+use std::{fs, io::Write};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+fn test_key() -> EncryptionKey {
+    EncryptionKey::new(&[1u8; encryption::KEY_LEN]).unwrap()
+}
+
+fn local_fs(tmpdir: &TempPath) -> Arc<dyn BackupStorage> {
+    Arc::new(LocalFs::new(tmpdir.path().to_path_buf()))
+}
+
+async fn write_file(store: &dyn BackupStorage, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+    let (handle, mut file) = store
+        .create_for_write(&backup_handle, &"file".parse().unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    handle
+}
+
+async fn read_file(store: &dyn BackupStorage, handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = CodecStorage::new(local_fs(&tmpdir), true, Some(test_key()), false);
+
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups, |backup, file| {
+            format!("codec:zstd+aes256gcm:{}", to_file_name(&tmpdir, backup, file))
+        }));
+    }
+
+    #[test]
+    fn test_save_and_list_metadata_files(
+        input in arb_metadata_files()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = CodecStorage::new(local_fs(&tmpdir), true, Some(test_key()), false);
+
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+
+    #[test]
+    fn test_encryption_round_trip(
+        // Spanning a few segments.
+        content in vec(any::<u8>(), 0..200_000),
+        compress in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = CodecStorage::new(local_fs(&tmpdir), compress, Some(test_key()), false);
+
+        let mut rt = Runtime::new().unwrap();
+        let handle = rt.block_on(write_file(&store, &content));
+        prop_assert_eq!(rt.block_on(read_file(&store, &handle)).unwrap(), content);
+    }
+}
+
+#[test]
+fn test_handle() {
+    let codec = Codec {
+        compression: true,
+        encryption: true,
+    };
+    let handle = codec.encode_handle("/a:b");
+    assert_eq!(handle, "codec:zstd+aes256gcm:/a:b");
+    assert_eq!(Codec::decode_handle(&handle).unwrap(), (codec, "/a:b"));
+
+    let codec = Codec {
+        compression: false,
+        encryption: true,
+    };
+    assert_eq!(
+        Codec::decode_handle(&codec.encode_handle("x")).unwrap(),
+        (codec, "x")
+    );
+
+    // Not encoded.
+    assert_eq!(Codec::default().encode_handle("/a:b"), "/a:b");
+    assert_eq!(
+        Codec::decode_handle("/a:b").unwrap(),
+        (Codec::default(), "/a:b")
+    );
+
+    assert!(Codec::decode_handle("codec:rot13:x").is_err());
+    assert!(Codec::decode_handle("codec:zstd").is_err());
+}
+
+#[test]
+fn test_encryption_failures() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = CodecStorage::new(local_fs(&tmpdir), false, Some(test_key()), false);
+    let content = vec![7u8; 300_000];
+    let mut rt = Runtime::new().unwrap();
+    let handle = rt.block_on(write_file(&store, &content));
+    let (_, path) = Codec::decode_handle(&handle).unwrap();
+    let encrypted = fs::read(path).unwrap();
+    assert!(!encrypted
+        .windows(content.len() / 10)
+        .any(|w| w == &content[..content.len() / 10]));
+
+    // Wrong key.
+    let other_key = EncryptionKey::new(&[2u8; encryption::KEY_LEN]).unwrap();
+    let other_store = CodecStorage::new(local_fs(&tmpdir), false, Some(other_key), false);
+    assert!(rt.block_on(read_file(&other_store, &handle)).is_err());
+
+    // No key.
+    let no_key_store = CodecStorage::new(local_fs(&tmpdir), false, None, false);
+    assert!(rt.block_on(read_file(&no_key_store, &handle)).is_err());
+
+    // Tampered.
+    let mut tampered = encrypted.clone();
+    tampered[1000] ^= 1;
+    fs::write(path, &tampered).unwrap();
+    assert!(rt.block_on(read_file(&store, &handle)).is_err());
+
+    // Truncated at a segment boundary: the first segment is intact, but it's not the last one.
+    let first_segment_end = 7 + 4 + 64 * 1024 + 16;
+    fs::write(path, &encrypted[..first_segment_end]).unwrap();
+    assert!(rt.block_on(read_file(&store, &handle)).is_err());
+
+    fs::write(path, &encrypted).unwrap();
+    assert_eq!(rt.block_on(read_file(&store, &handle)).unwrap(), content);
+}
+
+#[test]
+fn test_swapped_files_rejected() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = CodecStorage::new(local_fs(&tmpdir), false, Some(test_key()), false);
+    let mut rt = Runtime::new().unwrap();
+    let handle = rt.block_on(write_file(&store, b"new manifest"));
+    let other_handle = rt.block_on(async {
+        let backup_handle = store
+            .create_backup(&"other_backup".parse().unwrap())
+            .await
+            .unwrap();
+        let (handle, mut file) = store
+            .create_for_write(&backup_handle, &"file".parse().unwrap())
+            .await
+            .unwrap();
+        file.write_all(b"old manifest").await.unwrap();
+        file.shutdown().await.unwrap();
+        handle
+    });
+
+    // Encrypted with the same key, but for another file.
+    let (_, path) = Codec::decode_handle(&handle).unwrap();
+    let (_, other_path) = Codec::decode_handle(&other_handle).unwrap();
+    fs::copy(other_path, path).unwrap();
+    assert!(rt.block_on(read_file(&store, &handle)).is_err());
+    assert_eq!(
+        rt.block_on(read_file(&store, &other_handle)).unwrap(),
+        b"old manifest"
+    );
+}
+
+#[test]
+fn test_not_encoded_files_readable() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let content = b"plain".to_vec();
+    let mut rt = Runtime::new().unwrap();
+    let handle = rt.block_on(write_file(local_fs(&tmpdir).as_ref(), &content));
+
+    let no_key_store = CodecStorage::new(local_fs(&tmpdir), true, None, false);
+    assert_eq!(
+        rt.block_on(read_file(&no_key_store, &handle)).unwrap(),
+        content
+    );
+
+    // With a key, only if explicitly allowed.
+    let store = CodecStorage::new(local_fs(&tmpdir), true, Some(test_key()), false);
+    assert!(rt.block_on(read_file(&store, &handle)).is_err());
+    let store = CodecStorage::new(local_fs(&tmpdir), true, Some(test_key()), true);
+    assert_eq!(rt.block_on(read_file(&store, &handle)).unwrap(), content);
+}
+
+#[test]
+fn test_stripped_codec_rejected() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let store = CodecStorage::new(local_fs(&tmpdir), true, Some(test_key()), false);
+    let mut rt = Runtime::new().unwrap();
+    let handle = rt.block_on(write_file(&store, b"secret"));
+
+    // A plaintext file substituted for the encrypted one, referred to without the codec prefix.
+    let (_, path) = Codec::decode_handle(&handle).unwrap();
+    fs::write(path, b"forged").unwrap();
+    assert!(rt.block_on(read_file(&store, path)).is_err());
+    // Compressed but not encrypted.
+    let compressed_only = Codec {
+        compression: true,
+        encryption: false,
+    }
+    .encode_handle(path);
+    assert!(rt.block_on(read_file(&store, &compressed_only)).is_err());
+
+    // Metadata files are not encrypted, they are readable once listed.
+    rt.block_on(
+        store.save_metadata_line(&"meta".parse().unwrap(), &TextLine::new("line").unwrap()),
+    )
+    .unwrap();
+    let metadata_files = rt.block_on(store.list_metadata_files()).unwrap();
+    assert_eq!(
+        rt.block_on(read_file(&store, &metadata_files[0])).unwrap(),
+        b"line\n"
+    );
+}
+
+#[test]
+fn test_load_encryption_key() {
+    let storage_path = TempPath::new();
+    let mut storage = OnDiskStorage::new(storage_path.path().to_path_buf());
+    storage
+        .set("backup_encryption_key", Value::Bytes(vec![1u8; 32]))
+        .unwrap();
+    storage
+        .set("short_key", Value::Bytes(vec![1u8; 16]))
+        .unwrap();
+
+    let config_path = TempPath::new();
+    let mut config = fs::File::create(config_path.path()).unwrap();
+    writeln!(
+        config,
+        "type: on_disk_storage\npath: {}",
+        storage_path.path().to_str().unwrap()
+    )
+    .unwrap();
+
+    let mut opt = CodecOpt {
+        compress: false,
+        encryption_key_storage: Some(config_path.path().to_path_buf()),
+        encryption_key_name: "backup_encryption_key".to_string(),
+        allow_unencrypted: false,
+    };
+    assert!(opt.load_encryption_key().unwrap().is_some());
+    opt.encryption_key_name = "short_key".to_string();
+    assert!(opt.load_encryption_key().is_err());
+    opt.encryption_key_name = "missing_key".to_string();
+    assert!(opt.load_encryption_key().is_err());
+    opt.encryption_key_storage = None;
+    assert!(opt.load_encryption_key().unwrap().is_none());
+}
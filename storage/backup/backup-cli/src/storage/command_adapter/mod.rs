@@ -9,12 +9,13 @@ mod tests;
 use crate::storage::{
     command_adapter::config::{CommandAdapterConfig, EnvVar},
     BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+    TextLine,
 };
 use anyhow::{anyhow, ensure, Result};
 use async_trait::async_trait;
 use std::{path::PathBuf, process::Stdio};
 use structopt::StructOpt;
-use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 #[derive(StructOpt)]
 pub struct CommandAdapterOpt {
@@ -113,6 +114,46 @@ impl BackupStorage for CommandAdapter {
             .ok_or_else(|| anyhow!("Child process stdout is None."))?;
         Ok(Box::new(stdout))
     }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        let mut cmd = self.cmd(
+            &self.config.commands.save_metadata_line,
+            vec![EnvVar::file_name(name.to_string())],
+        );
+        let mut child = cmd.spawn().await?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Child process stdin is None."))?;
+        stdin.write_all(content.as_ref().as_bytes()).await?;
+        // Close stdin so the command sees EOF.
+        drop(stdin);
+        let output = child.wait_with_output().await?;
+        ensure!(
+            output.status.success(),
+            "Failed running command: {:?}, Exit code: {:?}",
+            cmd,
+            output.status.code(),
+        );
+        Ok(())
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let mut cmd = self.cmd(&self.config.commands.list_metadata_files, vec![]);
+        let output = cmd.spawn().await?.wait_with_output().await?;
+        ensure!(
+            output.status.success(),
+            "Failed running command: {:?}, Exit code: {:?}",
+            cmd,
+            output.status.code(),
+        );
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
 }
 
 #[derive(Debug)]
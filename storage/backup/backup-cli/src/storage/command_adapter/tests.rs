@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::storage::test_util::{arb_backups, test_write_and_read_impl};
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl, to_file_name,
+};
 use libra_temppath::TempPath;
 use proptest::prelude::*;
 use tokio::runtime::Runtime;
@@ -28,10 +31,41 @@ proptest! {
                 create_backup = 'cd "$FOLDER" && mkdir $BACKUP_NAME && echo $BACKUP_NAME'
                 create_for_write = 'cd "$FOLDER" && cd "$BACKUP_HANDLE" && test ! -f $FILE_NAME && touch $FILE_NAME && echo `pwd`/$FILE_NAME && exec >&- && cat > $FILE_NAME'
                 open_for_read = 'cat "$FILE_HANDLE"'
+                save_metadata_line = 'cd "$FOLDER" && mkdir -p metadata && cd metadata && test ! -f $FILE_NAME && cat > $FILE_NAME'
+                list_metadata_files = 'mkdir -p "$FOLDER/metadata" && find "$FOLDER/metadata" -mindepth 1 -maxdepth 1 -type f'
             "#, tmpdir.path().to_str().unwrap()),
         ).unwrap();
 
         let store = CommandAdapter::new(config);
-        rt.block_on(test_write_and_read_impl(Box::new(store), &tmpdir, backups));
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups, |backup, file| {
+            to_file_name(&tmpdir, backup, file)
+        }));
+    }
+
+    #[test]
+    fn test_save_and_list_metadata_files(
+        input in arb_metadata_files()
+    ) {
+        let mut rt = Runtime::new().unwrap();
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+
+        let config = CommandAdapterConfig::load_from_str(
+            &format!(r#"
+                [[env_vars]]
+                key = "FOLDER"
+                value = "{}"
+
+                [commands]
+                create_backup = 'cd "$FOLDER" && mkdir $BACKUP_NAME && echo $BACKUP_NAME'
+                create_for_write = 'cd "$FOLDER" && cd "$BACKUP_HANDLE" && test ! -f $FILE_NAME && touch $FILE_NAME && echo `pwd`/$FILE_NAME && exec >&- && cat > $FILE_NAME'
+                open_for_read = 'cat "$FILE_HANDLE"'
+                save_metadata_line = 'cd "$FOLDER" && mkdir -p metadata && cd metadata && test ! -f $FILE_NAME && cat > $FILE_NAME'
+                list_metadata_files = 'mkdir -p "$FOLDER/metadata" && find "$FOLDER/metadata" -mindepth 1 -maxdepth 1 -type f'
+            "#, tmpdir.path().to_str().unwrap()),
+        ).unwrap();
+
+        let store = CommandAdapter::new(config);
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
     }
 }
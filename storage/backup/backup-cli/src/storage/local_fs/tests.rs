@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::storage::test_util::{arb_backups, test_write_and_read_impl};
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl, to_file_name,
+};
 use libra_temppath::TempPath;
 use proptest::prelude::*;
 use tokio::runtime::Runtime;
@@ -19,6 +22,20 @@ proptest! {
         let store = LocalFs::new(tmpdir.path().to_path_buf());
 
         let mut rt = Runtime::new().unwrap();
-        rt.block_on(test_write_and_read_impl(Box::new(store), &tmpdir, backups));
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups, |backup, file| {
+            to_file_name(&tmpdir, backup, file)
+        }));
+    }
+
+    #[test]
+    fn test_save_and_list_metadata_files(
+        input in arb_metadata_files()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = LocalFs::new(tmpdir.path().to_path_buf());
+
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
     }
 }
@@ -1,8 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod codec;
 pub mod command_adapter;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -10,8 +12,10 @@ mod test_util;
 mod tests;
 
 use crate::storage::{
+    codec::CodecOpt,
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -73,6 +77,26 @@ impl AsRef<str> for ShellSafeName {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, Hash, Eq, Ord, PartialEq, PartialOrd))]
+pub struct TextLine(String);
+
+impl TextLine {
+    pub fn new(value: &str) -> Result<Self> {
+        let newlines: &[_] = &['\n', '\r'];
+        ensure!(value.find(newlines).is_none(), "Newline not allowed.");
+        let mut ret = value.to_string();
+        ret.push('\n');
+        Ok(Self(ret))
+    }
+}
+
+impl AsRef<str> for TextLine {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 #[cfg(test)]
 impl Arbitrary for ShellSafeName {
     type Parameters = ();
@@ -85,6 +109,18 @@ impl Arbitrary for ShellSafeName {
     }
 }
 
+#[cfg(test)]
+impl Arbitrary for TextLine {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        ("[a-zA-Z0-9 _:{},\"-]{0,1000}")
+            .prop_map(|s| TextLine::new(&s).unwrap())
+            .boxed()
+    }
+}
+
 #[async_trait]
 pub trait BackupStorage: Send + Sync {
     /// Hint that a bunch of files are gonna be created related to a backup identified by `name`,
@@ -94,7 +130,8 @@ pub trait BackupStorage: Send + Sync {
     /// Returns a string to identify this operation in potential succeeding file creation requests.
     async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle>;
     /// Ask to create a file for write, `backup_handle` was returned by `create_backup` to identify
-    /// the current backup.
+    /// the current backup. The file is not guaranteed to be complete until the returned writer is
+    /// shut down (see `AsyncWriteExt::shutdown`).
     async fn create_for_write(
         &self,
         backup_handle: &BackupHandleRef,
@@ -105,21 +142,57 @@ pub trait BackupStorage: Send + Sync {
         &self,
         file_handle: &FileHandleRef,
     ) -> Result<Box<dyn AsyncRead + Send + Unpin>>;
+    /// Save a text line to a metadata file identified by `name`. Metadata files are small and
+    /// kept separately from the backups so that the content of the storage can be discovered by
+    /// listing and reading them, see `list_metadata_files`.
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()>;
+    /// List all metadata files. Each file is a series of `TextLine`s. The storage is free to
+    /// reorganize the metadata files (e.g. concatenate them), as long as the lines are preserved.
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
 }
 
 #[derive(StructOpt)]
 pub enum StorageOpt {
     #[structopt(about = "Select the LocalFs backup store.")]
-    LocalFs(LocalFsOpt),
+    LocalFs {
+        #[structopt(flatten)]
+        opt: LocalFsOpt,
+        #[structopt(flatten)]
+        codec_opt: CodecOpt,
+    },
     #[structopt(about = "Select the CommandAdapter backup store.")]
-    CommandAdapter(CommandAdapterOpt),
+    CommandAdapter {
+        #[structopt(flatten)]
+        opt: CommandAdapterOpt,
+        #[structopt(flatten)]
+        codec_opt: CodecOpt,
+    },
+    #[structopt(
+        about = "Select the S3 backup store, which talks to an S3 compatible service \
+    natively."
+    )]
+    S3 {
+        #[structopt(flatten)]
+        opt: S3Opt,
+        #[structopt(flatten)]
+        codec_opt: CodecOpt,
+    },
 }
 
 impl StorageOpt {
+    /// Initializes the selected backup store, wrapped to compress and / or encrypt files as
+    /// configured, see `CodecStorage`.
     pub async fn init_storage(self) -> Result<Arc<dyn BackupStorage>> {
-        Ok(match self {
-            StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
-            StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
-        })
+        let (storage, codec_opt): (Arc<dyn BackupStorage>, _) = match self {
+            StorageOpt::LocalFs { opt, codec_opt } => {
+                (Arc::new(LocalFs::new_with_opt(opt)), codec_opt)
+            }
+            StorageOpt::CommandAdapter { opt, codec_opt } => (
+                Arc::new(CommandAdapter::new_with_opt(opt).await?),
+                codec_opt,
+            ),
+            StorageOpt::S3 { opt, codec_opt } => (Arc::new(S3::new_with_opt(opt)?), codec_opt),
+        };
+        codec_opt.wrap(storage)
     }
 }
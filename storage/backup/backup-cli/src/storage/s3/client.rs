@@ -0,0 +1,436 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, ensure, Result};
+use bytes::Bytes;
+use hmac::{Hmac, Mac, NewMac};
+use libra_retrier::{fixed_retry_strategy, retry_async};
+use reqwest::{Method, Response, StatusCode, Url};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+/// A minimal client of the S3 REST API, covering only what `S3` needs. Requests are signed with
+/// AWS Signature Version 4 and retried on failure. Objects are addressed path-style, i.e.
+/// `{endpoint}/{bucket}/{key}`, which all S3 compatible services understand.
+pub struct S3Client {
+    client: reqwest::Client,
+    endpoint: Url,
+    region: String,
+    bucket: String,
+    credentials: Credentials,
+    max_retries: usize,
+    retry_delay_ms: u64,
+}
+
+/// What's needed to send (and resend, on retries) a request.
+struct S3Request<'a> {
+    method: Method,
+    key: &'a str,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl<'a> S3Request<'a> {
+    fn new(method: Method, key: &'a str) -> Self {
+        Self {
+            method,
+            key,
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    fn query(mut self, key: &str, value: &str) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn body(mut self, body: Bytes) -> Self {
+        self.body = body;
+        self
+    }
+}
+
+impl S3Client {
+    pub fn new(
+        endpoint: &str,
+        region: String,
+        bucket: String,
+        credentials: Credentials,
+        max_retries: usize,
+        retry_delay_ms: u64,
+    ) -> Result<Self> {
+        let endpoint = Url::parse(endpoint)?;
+        ensure!(
+            endpoint.host_str().is_some(),
+            "S3 endpoint has no host: {}",
+            endpoint,
+        );
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            region,
+            bucket,
+            credentials,
+            max_retries,
+            retry_delay_ms,
+        })
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send(S3Request::new(Method::PUT, key).body(body))
+            .await
+            .map(|_| ())
+    }
+
+    /// Reads bytes in [`first`, `last`] of an object, returns the bytes read and the total size of
+    /// the object, or `None` if the object is empty.
+    pub async fn get_object_range(
+        &self,
+        key: &str,
+        first: u64,
+        last: u64,
+    ) -> Result<Option<(Bytes, u64)>> {
+        let response = self
+            .send(
+                S3Request::new(Method::GET, key)
+                    .header("range", &format!("bytes={}-{}", first, last)),
+            )
+            .await?;
+        match response.status() {
+            StatusCode::RANGE_NOT_SATISFIABLE => Ok(None),
+            StatusCode::PARTIAL_CONTENT => {
+                let total = response
+                    .headers()
+                    .get("content-range")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.rsplit('/').next())
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| anyhow!("Missing or bad Content-Range in response."))?;
+                Ok(Some((response.bytes().await?, total)))
+            }
+            // The entire object is returned if the service ignores the range.
+            _ => {
+                let bytes = response.bytes().await?;
+                let total = bytes.len() as u64;
+                Ok(Some((bytes, total)))
+            }
+        }
+    }
+
+    /// Lists the keys of all objects whose key starts with `prefix`.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut request = S3Request::new(Method::GET, "")
+                .query("list-type", "2")
+                .query("prefix", prefix);
+            if let Some(token) = &continuation_token {
+                request = request.query("continuation-token", token);
+            }
+            let text = self.send(request).await?.text().await?;
+            keys.extend(xml_values(&text, "Key"));
+
+            if xml_values(&text, "IsTruncated").first().map(String::as_str) != Some("true") {
+                break;
+            }
+            continuation_token = Some(
+                xml_values(&text, "NextContinuationToken")
+                    .pop()
+                    .ok_or_else(|| anyhow!("Truncated listing without continuation token."))?,
+            );
+        }
+        Ok(keys)
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let text = self
+            .send(S3Request::new(Method::POST, key).query("uploads", ""))
+            .await?
+            .text()
+            .await?;
+        xml_values(&text, "UploadId")
+            .pop()
+            .ok_or_else(|| anyhow!("No UploadId in response: {}", text))
+    }
+
+    /// Uploads a part, returns its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let response = self
+            .send(
+                S3Request::new(Method::PUT, key)
+                    .query("partNumber", &part_number.to_string())
+                    .query("uploadId", upload_id)
+                    .body(body),
+            )
+            .await?;
+        Ok(response
+            .headers()
+            .get("etag")
+            .ok_or_else(|| anyhow!("No ETag in response."))?
+            .to_str()?
+            .to_string())
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (idx, etag) in etags.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                idx + 1,
+                etag,
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let text = self
+            .send(
+                S3Request::new(Method::POST, key)
+                    .query("uploadId", upload_id)
+                    .body(body.into()),
+            )
+            .await?
+            .text()
+            .await?;
+        // The service can respond 200 OK and still fail the request.
+        ensure!(
+            !text.contains("<Error>"),
+            "Failed completing multipart upload: {}",
+            text,
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(S3Request::new(Method::DELETE, key).query("uploadId", upload_id))
+            .await
+            .map(|_| ())
+    }
+}
+
+impl S3Client {
+    async fn send(&self, request: S3Request<'_>) -> Result<Response> {
+        let request = &request;
+        retry_async(
+            fixed_retry_strategy(self.retry_delay_ms, self.max_retries),
+            || Box::pin(async move { self.send_once(request).await }),
+        )
+        .await
+    }
+
+    async fn send_once(&self, request: &S3Request<'_>) -> Result<Response> {
+        let path = format!(
+            "/{}/{}",
+            uri_encode(&self.bucket, false),
+            uri_encode(request.key, false),
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+        let canonical_query = canonical_query_string(&request.query);
+        url.set_query(if canonical_query.is_empty() {
+            None
+        } else {
+            Some(&canonical_query)
+        });
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut headers = request.headers.clone();
+        headers.push(("host".to_string(), host));
+        headers.push((
+            "x-amz-content-sha256".to_string(),
+            hex::encode(Sha256::digest(&request.body)),
+        ));
+        headers.push(("x-amz-date".to_string(), amz_date.clone()));
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let authorization = sign(
+            &self.credentials,
+            &self.region,
+            request.method.as_str(),
+            &path,
+            &request.query,
+            &headers,
+            &amz_date,
+        );
+
+        let mut builder = self.client.request(request.method.clone(), url);
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let response = builder
+            .header("authorization", authorization)
+            .body(request.body.clone())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_success() || status == StatusCode::RANGE_NOT_SATISFIABLE {
+            Ok(response)
+        } else {
+            bail!(
+                "S3 request {} {} failed. Status: {}, response: {}",
+                request.method,
+                path,
+                status,
+                response.text().await.unwrap_or_default(),
+            )
+        }
+    }
+}
+
+/// URI-encodes a string the way AWS Signature Version 4 requires, `/` is kept as is unless
+/// `encode_slash`.
+pub(super) fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut res = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                res.push(byte as char)
+            }
+            b'/' if !encode_slash => res.push('/'),
+            _ => res.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    res
+}
+
+fn canonical_query_string(query: &[(String, String)]) -> String {
+    let mut pairs: Vec<_> = query
+        .iter()
+        .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any size.");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Signs a request with AWS Signature Version 4, returns the value of the `Authorization` header.
+/// `path` is already URI-encoded, and `headers` must include all headers to be signed, with
+/// lower case names, among which `x-amz-content-sha256` is the hash of the payload.
+pub(super) fn sign(
+    credentials: &Credentials,
+    region: &str,
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    headers: &[(String, String)],
+    amz_date: &str,
+) -> String {
+    let mut headers: Vec<_> = headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+        .collect();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let payload_hash = headers
+        .iter()
+        .find(|(name, _)| name == "x-amz-content-sha256")
+        .map_or("UNSIGNED-PAYLOAD", |(_, value)| value.as_str());
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        path,
+        canonical_query_string(query),
+        canonical_headers,
+        signed_headers,
+        payload_hash,
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    );
+
+    let signing_key = [region, "s3", "aws4_request"].iter().fold(
+        hmac_sha256(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        ),
+        |key, data| hmac_sha256(&key, data.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature,
+    )
+}
+
+/// Extracts the text of all elements named `tag` out of an XML document. Enough for the simple
+/// responses S3 gives.
+pub(super) fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut res = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        match rest.find(&close) {
+            Some(end) => {
+                res.push(xml_unescape(&rest[..end]));
+                rest = &rest[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    res
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,220 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An in-process, in-memory imitation of the small part of the S3 API that `S3` uses, so that it
+//! can be tested without a real service. Requests are not authenticated, and everything lives in
+//! a single bucket regardless of the bucket name in the request.
+
+use crate::storage::s3::client::xml_values;
+use bytes::Bytes;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+use tokio::runtime::{Builder, Runtime};
+use warp::{
+    http::{Method, Response, StatusCode},
+    path::FullPath,
+    Filter,
+};
+
+#[derive(Default)]
+struct State {
+    objects: BTreeMap<String, Vec<u8>>,
+    /// Upload ID -> part number -> content.
+    uploads: HashMap<String, BTreeMap<usize, Vec<u8>>>,
+    next_upload_id: usize,
+    failures_to_inject: usize,
+}
+
+#[derive(Default)]
+pub struct MockS3 {
+    state: Mutex<State>,
+}
+
+impl MockS3 {
+    /// Page size of object listings, small so that pagination is exercised.
+    const MAX_KEYS: usize = 3;
+
+    /// Makes the next `n` requests fail with 500.
+    pub fn inject_failures(&self, n: usize) {
+        self.state.lock().unwrap().failures_to_inject = n;
+    }
+
+    pub fn num_uploads_in_progress(&self) -> usize {
+        self.state.lock().unwrap().uploads.len()
+    }
+
+    fn handle(
+        &self,
+        method: Method,
+        path: FullPath,
+        query: HashMap<String, String>,
+        range: Option<String>,
+        body: Bytes,
+    ) -> Response<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        if state.failures_to_inject > 0 {
+            state.failures_to_inject -= 1;
+            return respond(StatusCode::INTERNAL_SERVER_ERROR, "Injected failure.");
+        }
+
+        // "/{bucket}/{key}"
+        let key = path
+            .as_str()
+            .trim_start_matches('/')
+            .splitn(2, '/')
+            .nth(1)
+            .unwrap_or_default()
+            .to_string();
+
+        match method {
+            Method::GET if query.contains_key("list-type") => {
+                let prefix = query.get("prefix").cloned().unwrap_or_default();
+                let keys: Vec<_> = state
+                    .objects
+                    .keys()
+                    .filter(|k| k.starts_with(&prefix))
+                    .collect();
+                let start = query
+                    .get("continuation-token")
+                    .map_or(0, |token| token.parse().unwrap());
+                let end = std::cmp::min(start + Self::MAX_KEYS, keys.len());
+                let mut xml = String::from("<ListBucketResult>");
+                for key in &keys[start..end] {
+                    xml.push_str(&format!("<Contents><Key>{}</Key></Contents>", key));
+                }
+                if end < keys.len() {
+                    xml.push_str(&format!(
+                        "<IsTruncated>true</IsTruncated>\
+                        <NextContinuationToken>{}</NextContinuationToken>",
+                        end
+                    ));
+                } else {
+                    xml.push_str("<IsTruncated>false</IsTruncated>");
+                }
+                xml.push_str("</ListBucketResult>");
+                respond(StatusCode::OK, &xml)
+            }
+            Method::GET => {
+                let object = match state.objects.get(&key) {
+                    Some(object) => object,
+                    None => return respond(StatusCode::NOT_FOUND, "<Error>NoSuchKey</Error>"),
+                };
+                let (first, last) = match range {
+                    Some(range) => {
+                        let range: Vec<u64> = range
+                            .trim_start_matches("bytes=")
+                            .split('-')
+                            .map(|n| n.parse().unwrap())
+                            .collect();
+                        (range[0] as usize, range[1] as usize)
+                    }
+                    None => return Response::new(object.clone()),
+                };
+                if first >= object.len() {
+                    return respond(StatusCode::RANGE_NOT_SATISFIABLE, "");
+                }
+                let last = std::cmp::min(last, object.len() - 1);
+                Response::builder()
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(
+                        "content-range",
+                        format!("bytes {}-{}/{}", first, last, object.len()),
+                    )
+                    .body(object[first..=last].to_vec())
+                    .unwrap()
+            }
+            Method::PUT if query.contains_key("uploadId") => {
+                let part_number: usize = query["partNumber"].parse().unwrap();
+                match state.uploads.get_mut(&query["uploadId"]) {
+                    Some(parts) => {
+                        parts.insert(part_number, body.to_vec());
+                        Response::builder()
+                            .header("etag", format!("\"{}\"", part_number))
+                            .body(Vec::new())
+                            .unwrap()
+                    }
+                    None => respond(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+                }
+            }
+            Method::PUT => {
+                state.objects.insert(key, body.to_vec());
+                respond(StatusCode::OK, "")
+            }
+            Method::POST if query.contains_key("uploads") => {
+                let upload_id = state.next_upload_id.to_string();
+                state.next_upload_id += 1;
+                state.uploads.insert(upload_id.clone(), BTreeMap::new());
+                respond(
+                    StatusCode::OK,
+                    &format!(
+                        "<InitiateMultipartUploadResult><UploadId>{}</UploadId>\
+                        </InitiateMultipartUploadResult>",
+                        upload_id
+                    ),
+                )
+            }
+            Method::POST if query.contains_key("uploadId") => {
+                let parts = match state.uploads.remove(&query["uploadId"]) {
+                    Some(parts) => parts,
+                    None => return respond(StatusCode::NOT_FOUND, "<Error>NoSuchUpload</Error>"),
+                };
+                let mut object = Vec::new();
+                for part_number in xml_values(&String::from_utf8_lossy(&body), "PartNumber") {
+                    match parts.get(&part_number.parse::<usize>().unwrap()) {
+                        Some(part) => object.extend_from_slice(part),
+                        // Real services respond 200 OK with an error in the body in some cases.
+                        None => return respond(StatusCode::OK, "<Error>InvalidPart</Error>"),
+                    }
+                }
+                state.objects.insert(key, object);
+                respond(StatusCode::OK, "<CompleteMultipartUploadResult/>")
+            }
+            Method::DELETE if query.contains_key("uploadId") => {
+                state.uploads.remove(&query["uploadId"]);
+                respond(StatusCode::NO_CONTENT, "")
+            }
+            _ => respond(StatusCode::BAD_REQUEST, "Not supported."),
+        }
+    }
+}
+
+fn respond(status: StatusCode, body: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .body(body.as_bytes().to_vec())
+        .unwrap()
+}
+
+pub fn start_mock_s3(port: u16) -> (Runtime, Arc<MockS3>) {
+    let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
+    let mock = Arc::new(MockS3::default());
+    let mock_clone = Arc::clone(&mock);
+    let routes = warp::any()
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::header::optional::<String>("range"))
+        .and(warp::body::bytes())
+        .map(
+            move |method: Method,
+                  path: FullPath,
+                  query: HashMap<String, String>,
+                  range: Option<String>,
+                  body: Bytes| { mock_clone.handle(method, path, query, range, body) },
+        );
+
+    let runtime = Builder::new()
+        .thread_name("mock-s3-")
+        .threaded_scheduler()
+        .enable_all()
+        .build()
+        .expect("[mock-s3] failed to create runtime");
+    // Bind before returning, so that requests don't race with the server start.
+    let server = runtime.enter(move || warp::serve(routes).bind(address));
+    runtime.handle().spawn(server);
+
+    (runtime, mock)
+}
@@ -0,0 +1,376 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+pub use client::Credentials;
+
+#[cfg(test)]
+mod mock_server;
+#[cfg(test)]
+mod tests;
+
+use super::{BackupHandle, BackupHandleRef, FileHandle, FileHandleRef};
+
+use crate::storage::{s3::client::S3Client, BackupStorage, ShellSafeName, TextLine};
+use anyhow::{anyhow, bail, ensure, Result};
+use async_trait::async_trait;
+use futures::{
+    channel::mpsc,
+    ready,
+    sink::Sink,
+    stream::{self, StreamExt, TryStreamExt},
+};
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(Clone, StructOpt)]
+pub struct S3Opt {
+    #[structopt(
+        long = "endpoint",
+        help = "URL of the S3 compatible service, e.g. https://s3.us-west-2.amazonaws.com. \
+        Credentials are read from the environment variables AWS_ACCESS_KEY_ID, \
+        AWS_SECRET_ACCESS_KEY and, optionally, AWS_SESSION_TOKEN."
+    )]
+    pub endpoint: String,
+
+    #[structopt(long = "region", default_value = "us-east-1")]
+    pub region: String,
+
+    #[structopt(long = "bucket", help = "Bucket to hold backups.")]
+    pub bucket: String,
+
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Everything is stored under this prefix in the bucket."
+    )]
+    pub prefix: String,
+
+    #[structopt(
+        long = "part-size",
+        default_value = "16777216",
+        help = "Files are uploaded and downloaded in parts of this many bytes. Files larger than \
+        this are uploaded with multipart uploads, in which case S3 requires it to be at least 5MiB."
+    )]
+    pub part_size: usize,
+
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "How many times a failed request is retried."
+    )]
+    pub max_retries: usize,
+
+    #[structopt(long = "retry-delay-ms", default_value = "1000")]
+    pub retry_delay_ms: u64,
+}
+
+/// A storage backend that talks to an S3 compatible object store directly, without relying on
+/// external tools.
+pub struct S3 {
+    client: Arc<S3Client>,
+    /// Empty, or ending with a '/'.
+    prefix: String,
+    part_size: usize,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(opt: S3Opt, credentials: Credentials) -> Result<Self> {
+        ensure!(opt.part_size > 0, "Part size can't be 0.");
+        let prefix = opt.prefix.trim_matches('/');
+        let prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", prefix)
+        };
+
+        Ok(Self {
+            client: Arc::new(S3Client::new(
+                &opt.endpoint,
+                opt.region,
+                opt.bucket,
+                credentials,
+                opt.max_retries,
+                opt.retry_delay_ms,
+            )?),
+            prefix,
+            part_size: opt.part_size,
+        })
+    }
+
+    pub fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        let env_var = |name: &str| {
+            std::env::var(name).map_err(|_| anyhow!("Environment variable {} not set.", name))
+        };
+        let credentials = Credentials {
+            access_key_id: env_var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: env_var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: env_var("AWS_SESSION_TOKEN").ok(),
+        };
+        Self::new(opt, credentials)
+    }
+
+    fn metadata_prefix(&self) -> String {
+        format!("{}{}/", self.prefix, Self::METADATA_DIR)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    /// There are no directories in S3, so nothing is created.
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        Ok(format!("{}{}", self.prefix, name.as_ref()))
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let key = format!("{}/{}", backup_handle, name.as_ref());
+        let writer = S3Writer::new(Arc::clone(&self.client), key.clone(), self.part_size);
+        Ok((key, Box::new(writer)))
+    }
+
+    /// The file is downloaded in parts as it's read. The first part is requested right away, so
+    /// a missing file fails here instead of on read.
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let part_size = self.part_size as u64;
+        let first_part = self
+            .client
+            .get_object_range(file_handle, 0, part_size - 1)
+            .await?;
+        let next_offset = match &first_part {
+            Some((bytes, total)) if (bytes.len() as u64) < *total => Some(bytes.len() as u64),
+            _ => None,
+        };
+
+        let client = Arc::clone(&self.client);
+        let key = file_handle.to_string();
+        let rest = stream::unfold(next_offset, move |offset| {
+            let client = Arc::clone(&client);
+            let key = key.clone();
+            async move {
+                let offset = match offset {
+                    Some(offset) => offset,
+                    None => return None,
+                };
+                match client
+                    .get_object_range(&key, offset, offset + part_size - 1)
+                    .await
+                {
+                    Ok(Some((bytes, total))) => {
+                        let next = offset + bytes.len() as u64;
+                        let next_offset = if !bytes.is_empty() && next < total {
+                            Some(next)
+                        } else {
+                            None
+                        };
+                        Some((Ok(bytes), next_offset))
+                    }
+                    Ok(None) => None,
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        });
+
+        Ok(Box::new(
+            Box::pin(
+                stream::iter(first_part.map(|(bytes, _)| Ok(bytes)))
+                    .chain(rest)
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
+            )
+            .into_async_read()
+            .compat(),
+        ))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        let key = format!("{}{}", self.metadata_prefix(), name.as_ref());
+        self.client
+            .put_object(&key, content.as_ref().to_string().into())
+            .await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.client.list_objects(&self.metadata_prefix()).await
+    }
+}
+
+/// Uploads what's written to it in the background. Data is sent to the upload task through a
+/// bounded channel, so a slow upload slows down the writer instead of piling up in memory.
+///
+/// The object doesn't show up until the writer is shut down, and the upload is abandoned if the
+/// writer is dropped without being shut down.
+struct S3Writer {
+    /// `None` once shut down.
+    sender: Option<mpsc::Sender<Vec<u8>>>,
+    /// `None` once the upload result is taken.
+    upload: Option<JoinHandle<Result<()>>>,
+}
+
+impl S3Writer {
+    /// Number of writes that can be buffered in the channel.
+    const CHANNEL_SIZE: usize = 16;
+
+    fn new(client: Arc<S3Client>, key: String, part_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(Self::CHANNEL_SIZE);
+        let upload = Upload {
+            client,
+            key,
+            part_size,
+            buf: Vec::new(),
+            multipart: None,
+        };
+        Self {
+            sender: Some(sender),
+            upload: Some(tokio::spawn(upload.run(receiver))),
+        }
+    }
+
+    fn to_io_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, e)
+    }
+}
+
+impl AsyncWrite for S3Writer {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // An empty chunk marks the end of the data, see `Upload::receive_and_upload`.
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let sender = match self.sender.as_mut() {
+            Some(sender) => sender,
+            None => return Poll::Ready(Err(Self::to_io_error("Writer already shut down."))),
+        };
+        // The channel only closes if the upload failed, the error is reported on shutdown.
+        ready!(Pin::new(&mut *sender).poll_ready(cx)).map_err(Self::to_io_error)?;
+        Pin::new(sender)
+            .start_send(buf.to_vec())
+            .map_err(Self::to_io_error)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Data is only guaranteed to be uploaded on shutdown.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(sender) = self.sender.as_mut() {
+            if ready!(Pin::new(&mut *sender).poll_ready(cx)).is_ok() {
+                // Failing to send means the upload failed, which is reported below.
+                let _ = Pin::new(sender).start_send(Vec::new());
+            }
+            self.sender = None;
+        }
+
+        match self.upload.as_mut() {
+            Some(upload) => {
+                let res = ready!(Pin::new(upload).poll(cx));
+                self.upload = None;
+                Poll::Ready(match res {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(e)) => Err(Self::to_io_error(e)),
+                    Err(e) => Err(Self::to_io_error(e)),
+                })
+            }
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+/// The background task of `S3Writer`.
+struct Upload {
+    client: Arc<S3Client>,
+    key: String,
+    part_size: usize,
+    /// Data not uploaded yet.
+    buf: Vec<u8>,
+    /// Upload ID and ETags of uploaded parts, once a multipart upload is started, which only
+    /// happens if the object is larger than a part.
+    multipart: Option<(String, Vec<String>)>,
+}
+
+impl Upload {
+    async fn run(mut self, receiver: mpsc::Receiver<Vec<u8>>) -> Result<()> {
+        let res = self.receive_and_upload(receiver).await;
+        if res.is_err() {
+            if let Some((upload_id, _)) = &self.multipart {
+                // Best effort, so that the service doesn't keep the uploaded parts around.
+                let _ = self
+                    .client
+                    .abort_multipart_upload(&self.key, upload_id)
+                    .await;
+            }
+        }
+        res
+    }
+
+    async fn receive_and_upload(&mut self, mut receiver: mpsc::Receiver<Vec<u8>>) -> Result<()> {
+        while let Some(data) = receiver.next().await {
+            if data.is_empty() {
+                return self.finish().await;
+            }
+            self.buf.extend_from_slice(&data);
+            while self.buf.len() >= self.part_size {
+                let rest = self.buf.split_off(self.part_size);
+                let part = std::mem::replace(&mut self.buf, rest);
+                self.upload_part(part).await?;
+            }
+        }
+        bail!(
+            "Writer dropped before being shut down, {} not saved.",
+            self.key
+        )
+    }
+
+    async fn upload_part(&mut self, part: Vec<u8>) -> Result<()> {
+        if self.multipart.is_none() {
+            let upload_id = self.client.create_multipart_upload(&self.key).await?;
+            self.multipart = Some((upload_id, Vec::new()));
+        }
+        let (upload_id, etags) = self.multipart.as_mut().expect("Set above.");
+        let etag = self
+            .client
+            .upload_part(&self.key, upload_id, etags.len() + 1, part.into())
+            .await?;
+        etags.push(etag);
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        let buf = std::mem::take(&mut self.buf);
+        if self.multipart.is_none() {
+            return self.client.put_object(&self.key, buf.into()).await;
+        }
+
+        if !buf.is_empty() {
+            self.upload_part(buf).await?;
+        }
+        let (upload_id, etags) = self.multipart.as_ref().expect("Checked above.");
+        self.client
+            .complete_multipart_upload(&self.key, upload_id, etags)
+            .await
+    }
+}
@@ -1,16 +1,16 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::{BackupStorage, ShellSafeName};
+use crate::storage::{BackupStorage, ShellSafeName, TextLine};
 use libra_temppath::TempPath;
 use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-fn to_file_name(tmpdir: &TempPath, backup_name: &str, file_name: &str) -> String {
+pub fn to_file_name(tmpdir: &TempPath, backup_name: &str, file_name: &str) -> String {
     tmpdir
         .path()
         .to_path_buf()
@@ -23,22 +23,23 @@ fn to_file_name(tmpdir: &TempPath, backup_name: &str, file_name: &str) -> String
 
 pub async fn test_write_and_read_impl(
     store: Box<dyn BackupStorage>,
-    tmpdir: &TempPath,
     backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+    to_file_handle: impl Fn(&str, &str) -> String,
 ) {
     for (backup_name, files) in &backups {
         let backup_handle = store.create_backup(backup_name).await.unwrap();
         assert_eq!(backup_handle, backup_name.as_ref());
         for (name, content) in files {
             let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
-            assert_eq!(handle, to_file_name(&tmpdir, backup_name, name));
+            assert_eq!(handle, to_file_handle(backup_name, name));
             file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
         }
     }
 
     for (backup_name, files) in &backups {
         for (name, content) in files {
-            let handle = to_file_name(&tmpdir, backup_name, name);
+            let handle = to_file_handle(backup_name, name);
             let mut file = store.open_for_read(&handle).await.unwrap();
             let mut buf = Vec::new();
             file.read_to_end(&mut buf).await.unwrap();
@@ -47,6 +48,33 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+pub async fn test_save_and_list_metadata_files_impl(
+    store: Box<dyn BackupStorage>,
+    input: HashMap<ShellSafeName, TextLine>,
+) {
+    assert!(store.list_metadata_files().await.unwrap().is_empty());
+
+    for (name, content) in &input {
+        store.save_metadata_line(name, content).await.unwrap();
+    }
+
+    let file_handles = store.list_metadata_files().await.unwrap();
+    assert_eq!(file_handles.len(), input.len());
+
+    let mut read_back = HashSet::new();
+    for file_handle in &file_handles {
+        let mut file = store.open_for_read(file_handle).await.unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await.unwrap();
+        read_back.insert(buf);
+    }
+    let expected: HashSet<_> = input
+        .values()
+        .map(|line| line.as_ref().to_string())
+        .collect();
+    assert_eq!(read_back, expected);
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
@@ -59,3 +87,7 @@ pub fn arb_backups(
         1..10,
     )
 }
+
+pub fn arb_metadata_files() -> impl Strategy<Value = HashMap<ShellSafeName, TextLine>> {
+    hash_map(any::<ShellSafeName>(), any::<TextLine>(), 1..10)
+}
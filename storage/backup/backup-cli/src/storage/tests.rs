@@ -1,7 +1,7 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::ShellSafeName;
+use crate::storage::{ShellSafeName, TextLine};
 use std::str::FromStr;
 
 #[test]
@@ -17,3 +17,13 @@ fn test_shell_safe_name() {
 
     assert!(ShellSafeName::from_str(&"x".repeat(127)).is_ok());
 }
+
+#[test]
+fn test_text_line() {
+    assert!(TextLine::new("a\nb").is_err());
+    assert!(TextLine::new("a\rb").is_err());
+    assert!(TextLine::new("\n").is_err());
+
+    assert_eq!(TextLine::new("").unwrap().as_ref(), "\n");
+    assert_eq!(TextLine::new("a b").unwrap().as_ref(), "a b\n");
+}
@@ -8,8 +8,15 @@ pub mod storage_ext;
 #[cfg(test)]
 pub mod test_utils;
 
-use libra_types::transaction::Version;
-use std::{mem::size_of, path::PathBuf};
+use anyhow::Result;
+use libra_crypto::HashValue;
+use libra_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::definition::LeafCount,
+    transaction::{Transaction, TransactionInfo, Version},
+};
+use libradb::backup::restore_handler::RestoreHandler;
+use std::{mem::size_of, path::PathBuf, sync::Arc};
 use structopt::StructOpt;
 
 #[derive(Clone, StructOpt)]
@@ -24,12 +31,72 @@ pub struct GlobalRestoreOpt {
     pub db_dir: PathBuf,
     #[structopt(
         long = "target-version",
-        default_value = "Version::max_value()",
+        default_value = "18446744073709551615",
         help = "Content newer than this version will not be recovered to DB."
     )]
     pub target_version: Version,
 }
 
+/// Whether the restore controllers write what's in the backups to the DB, or only verify them.
+pub enum RestoreRunMode {
+    Restore {
+        restore_handler: Arc<RestoreHandler>,
+    },
+    Verify,
+}
+
+impl RestoreRunMode {
+    pub fn is_verify(&self) -> bool {
+        match self {
+            Self::Restore { .. } => false,
+            Self::Verify => true,
+        }
+    }
+
+    pub fn save_ledger_infos(&self, ledger_infos: &[LedgerInfoWithSignatures]) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => restore_handler.save_ledger_infos(ledger_infos),
+            Self::Verify => Ok(()),
+        }
+    }
+
+    pub fn save_ledger_info_if_newer(&self, ledger_info: LedgerInfoWithSignatures) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => {
+                restore_handler.save_ledger_info_if_newer(ledger_info)
+            }
+            Self::Verify => Ok(()),
+        }
+    }
+
+    pub fn confirm_or_save_frozen_subtrees(
+        &self,
+        num_leaves: LeafCount,
+        frozen_subtrees: &[HashValue],
+    ) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => {
+                restore_handler.confirm_or_save_frozen_subtrees(num_leaves, frozen_subtrees)
+            }
+            Self::Verify => Ok(()),
+        }
+    }
+
+    pub fn save_transactions(
+        &self,
+        first_version: Version,
+        txns: &[Transaction],
+        txn_infos: &[TransactionInfo],
+    ) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => {
+                restore_handler.save_transactions(first_version, txns, txn_infos)
+            }
+            Self::Verify => Ok(()),
+        }
+    }
+}
+
 pub(crate) fn should_cut_chunk(chunk: &[u8], record: &[u8], max_chunk_size: usize) -> bool {
     !chunk.is_empty() && chunk.len() + record.len() + size_of::<u32>() > max_chunk_size
 }
@@ -88,6 +88,10 @@ mod tests {
         let resp = get(&format!("http://127.0.0.1:{}/latest_state_root", port,)).unwrap();
         assert_eq!(resp.status(), 500);
 
+        // Non-bootstrapped DB has no db_state, which is not an error.
+        let resp = get(&format!("http://127.0.0.1:{}/db_state", port,)).unwrap();
+        assert_eq!(resp.status(), 200);
+
         // a endpoint handled by `reply_with_async_channel_writer' always returns 200,
         // connection terminates prematurely when the channel writer errors.
         let resp = get(&format!("http://127.0.0.1:{}/state_snapshot/1", port,)).unwrap();
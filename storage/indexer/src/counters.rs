@@ -0,0 +1,39 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_metrics::{
+    register_int_counter, register_int_counter_vec, register_int_gauge, IntCounter, IntCounterVec,
+    IntGauge,
+};
+use once_cell::sync::Lazy;
+
+/// Version of the first transaction which isn't indexed yet
+pub static NEXT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "libra_indexer_next_version",
+        "Version of the first transaction which isn't indexed yet"
+    )
+    .unwrap()
+});
+
+/// Cumulative number of failures to index committed transactions
+pub static INDEXING_ERRORS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "libra_indexer_indexing_errors_count",
+        "Cumulative number of failures to index committed transactions"
+    )
+    .unwrap()
+});
+
+/// Cumulative number of requests that the indexer receives
+pub static REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "libra_indexer_requests_count",
+        "Cumulative number of requests that the indexer receives",
+        &[
+            "type",   // type of request, matches JSON RPC method name (e.g. "get_account_events")
+            "result", // result of request: "success", "fail"
+        ]
+    )
+    .unwrap()
+});
@@ -0,0 +1,132 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use libra_json_rpc_types::views::EventView;
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::{
+        BurnEvent, CancelBurnEvent, MintEvent, NewEpochEvent, PreburnEvent, ReceivedPaymentEvent,
+        SentPaymentEvent, ToLBRExchangeRateUpdateEvent, UpgradeEvent,
+    },
+    contract_event::ContractEvent,
+    transaction::Version,
+};
+use move_core_types::{language_storage::TypeTag, move_resource::MoveResource};
+use num_derive::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// The kinds of events which are indexed. Other events (e.g. `NewBlockEvent`, emitted by every
+/// block) are skipped.
+#[derive(
+    Clone, Copy, Debug, Deserialize, Eq, FromPrimitive, Hash, PartialEq, Serialize, ToPrimitive,
+)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum EventKind {
+    ReceivedPayment = 0,
+    SentPayment = 1,
+    Mint = 2,
+    Burn = 3,
+    Preburn = 4,
+    CancelBurn = 5,
+    ToLbrExchangeRateUpdate = 6,
+    NewEpoch = 7,
+    Upgrade = 8,
+}
+
+impl EventKind {
+    /// The kind of `event`, if it's indexed.
+    pub fn of(event: &ContractEvent) -> Option<Self> {
+        let struct_tag = match event.type_tag() {
+            TypeTag::Struct(struct_tag) => struct_tag,
+            _ => return None,
+        };
+        [
+            (
+                ReceivedPaymentEvent::struct_tag(),
+                EventKind::ReceivedPayment,
+            ),
+            (SentPaymentEvent::struct_tag(), EventKind::SentPayment),
+            (MintEvent::struct_tag(), EventKind::Mint),
+            (BurnEvent::struct_tag(), EventKind::Burn),
+            (PreburnEvent::struct_tag(), EventKind::Preburn),
+            (CancelBurnEvent::struct_tag(), EventKind::CancelBurn),
+            (
+                ToLBRExchangeRateUpdateEvent::struct_tag(),
+                EventKind::ToLbrExchangeRateUpdate,
+            ),
+            (NewEpochEvent::struct_tag(), EventKind::NewEpoch),
+            (UpgradeEvent::struct_tag(), EventKind::Upgrade),
+        ]
+        .iter()
+        .find(|(tag, _)| tag == struct_tag)
+        .map(|(_, kind)| *kind)
+    }
+
+    /// The code of the currency `event` (of this kind) is about, if any. None if the event can't
+    /// be decoded.
+    pub fn currency_code(self, event: &ContractEvent) -> Option<String> {
+        match self {
+            EventKind::ReceivedPayment => ReceivedPaymentEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::SentPayment => SentPaymentEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::Mint => MintEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::Burn => BurnEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::Preburn => PreburnEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::CancelBurn => CancelBurnEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::ToLbrExchangeRateUpdate => ToLBRExchangeRateUpdateEvent::try_from(event)
+                .ok()
+                .map(|event| event.currency_code().to_string()),
+            EventKind::NewEpoch | EventKind::Upgrade => None,
+        }
+    }
+}
+
+/// An event of a committed transaction, with the time of the block of the transaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct IndexedEvent {
+    pub transaction_version: Version,
+    /// Index of the event among the events of the transaction
+    pub event_index: u64,
+    /// Timestamp of the block of the transaction, 0 for genesis
+    pub timestamp_usecs: u64,
+    pub event: ContractEvent,
+}
+
+impl IndexedEvent {
+    /// The account which emitted the event, by which it is indexed.
+    pub fn account(&self) -> AccountAddress {
+        self.event.key().get_creator_address()
+    }
+}
+
+/// The JSON representation of an `IndexedEvent`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IndexedEventView {
+    pub timestamp_usecs: u64,
+    pub event_index: u64,
+    #[serde(flatten)]
+    pub event: EventView,
+}
+
+impl From<IndexedEvent> for IndexedEventView {
+    fn from(event: IndexedEvent) -> Self {
+        Self {
+            timestamp_usecs: event.timestamp_usecs,
+            event_index: event.event_index,
+            event: EventView::from((event.transaction_version, event.event)),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    event::{EventKind, IndexedEvent},
+    schema::{
+        event::IndexedEventSchema,
+        event_by_account::EventByAccountSchema,
+        event_by_currency::EventByCurrencySchema,
+        indexer_progress::{IndexerProgressKey, IndexerProgressSchema},
+        EVENT_BY_ACCOUNT_CF_NAME, EVENT_BY_CURRENCY_CF_NAME, EVENT_CF_NAME,
+        INDEXER_PROGRESS_CF_NAME,
+    },
+};
+use anyhow::{ensure, format_err, Result};
+use libra_logger::prelude::*;
+use libra_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    transaction::{Transaction, Version},
+};
+use schemadb::{ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
+use std::{path::Path, time::Instant};
+
+/// The secondary indexes of the events of committed transactions, kept apart from LibraDB.
+pub struct IndexerDB {
+    db: DB,
+}
+
+impl IndexerDB {
+    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        let column_families = vec![
+            /* UNUSED CF = */ DEFAULT_CF_NAME,
+            EVENT_CF_NAME,
+            EVENT_BY_ACCOUNT_CF_NAME,
+            EVENT_BY_CURRENCY_CF_NAME,
+            INDEXER_PROGRESS_CF_NAME,
+        ];
+
+        let path = db_root_path.as_ref().join("indexerdb");
+        let instant = Instant::now();
+        let db = DB::open(path.clone(), "indexer", column_families)
+            .expect("IndexerDB open failed; unable to continue");
+
+        info!(
+            "Opened IndexerDB at {:?} in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        Self { db }
+    }
+
+    /// Version of the first transaction which isn't indexed yet, from which indexing resumes.
+    pub fn next_version(&self) -> Result<Version> {
+        Ok(self
+            .db
+            .get::<IndexerProgressSchema>(&IndexerProgressKey::NextVersion)?
+            .unwrap_or(0))
+    }
+
+    /// Skips the transactions before `version`, which can't be indexed as LibraDB doesn't have
+    /// them (e.g. because it was restored from a backup). Transactions already indexed aren't
+    /// skipped again.
+    pub fn skip_to(&self, version: Version) -> Result<()> {
+        if version > self.next_version()? {
+            self.db
+                .put::<IndexerProgressSchema>(&IndexerProgressKey::NextVersion, &version)?;
+        }
+        Ok(())
+    }
+
+    /// Indexes the events of consecutive committed transactions starting at `first_version`,
+    /// which must be `next_version()`. `timestamp_usecs` is the timestamp of the block of the
+    /// first transaction; it's updated by the `BlockMetadata` transactions starting the next
+    /// blocks. Returns the new `next_version()`.
+    pub fn index_transactions(
+        &self,
+        first_version: Version,
+        mut timestamp_usecs: u64,
+        transactions: &[Transaction],
+        events: &[Vec<ContractEvent>],
+    ) -> Result<Version> {
+        let next_version = self.next_version()?;
+        ensure!(
+            first_version == next_version,
+            "Transactions must be indexed in order, expected version {}, got {}.",
+            next_version,
+            first_version,
+        );
+        ensure!(
+            transactions.len() == events.len(),
+            "Number of transactions ({}) and event lists ({}) mismatch.",
+            transactions.len(),
+            events.len(),
+        );
+
+        let mut batch = SchemaBatch::new();
+        for (idx, (txn, txn_events)) in transactions.iter().zip(events.iter()).enumerate() {
+            let version = first_version + idx as u64;
+            if let Transaction::BlockMetadata(block_metadata) = txn {
+                timestamp_usecs = block_metadata.clone().into_inner()?.1;
+            }
+            for (event_index, event) in txn_events.iter().enumerate() {
+                let kind = match EventKind::of(event) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let event_index = event_index as u64;
+                let indexed_event = IndexedEvent {
+                    transaction_version: version,
+                    event_index,
+                    timestamp_usecs,
+                    event: event.clone(),
+                };
+                batch.put::<EventByAccountSchema>(
+                    &(indexed_event.account(), kind, version, event_index),
+                    &(),
+                )?;
+                if let Some(currency_code) = kind.currency_code(event) {
+                    batch.put::<EventByCurrencySchema>(
+                        &(kind, currency_code, version, event_index),
+                        &(),
+                    )?;
+                }
+                batch.put::<IndexedEventSchema>(&(version, event_index), &indexed_event)?;
+            }
+        }
+        let next_version = first_version + transactions.len() as u64;
+        batch.put::<IndexerProgressSchema>(&IndexerProgressKey::NextVersion, &next_version)?;
+        self.db.write_schemas(batch)?;
+        Ok(next_version)
+    }
+
+    /// Up to `limit` events of `kind` emitted by `address`, in transactions at or after
+    /// `start_version`.
+    pub fn get_account_events(
+        &self,
+        address: AccountAddress,
+        kind: EventKind,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<IndexedEvent>> {
+        let mut iter = self
+            .db
+            .iter::<EventByAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, kind, start_version, 0))?;
+
+        let mut result = Vec::new();
+        for res in iter.take(limit as usize) {
+            let ((addr, knd, version, event_index), ()) = res?;
+            if addr != address || knd != kind {
+                break;
+            }
+            result.push(self.get_event(version, event_index)?);
+        }
+        Ok(result)
+    }
+
+    /// Up to `limit` events of `kind` about `currency_code`, in transactions at or after
+    /// `start_version`.
+    pub fn get_currency_events(
+        &self,
+        currency_code: &str,
+        kind: EventKind,
+        start_version: Version,
+        limit: u64,
+    ) -> Result<Vec<IndexedEvent>> {
+        let mut iter = self
+            .db
+            .iter::<EventByCurrencySchema>(ReadOptions::default())?;
+        iter.seek(&(kind, currency_code.to_string(), start_version, 0))?;
+
+        let mut result = Vec::new();
+        for res in iter.take(limit as usize) {
+            let ((knd, code, version, event_index), ()) = res?;
+            if knd != kind || code != currency_code {
+                break;
+            }
+            result.push(self.get_event(version, event_index)?);
+        }
+        Ok(result)
+    }
+
+    fn get_event(&self, version: Version, event_index: u64) -> Result<IndexedEvent> {
+        self.db
+            .get::<IndexedEventSchema>(&(version, event_index))?
+            .ok_or_else(|| {
+                format_err!(
+                    "DB corrupt: indexed event {} of transaction {} not found.",
+                    event_index,
+                    version
+                )
+            })
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{event::EventKind, indexerdb::IndexerDB};
+use libra_crypto::HashValue;
+use libra_temppath::TempPath;
+use libra_types::{
+    account_address::AccountAddress,
+    account_config::{MintEvent, SentPaymentEvent},
+    block_metadata::BlockMetadata,
+    contract_event::ContractEvent,
+    event::EventKey,
+    transaction::Transaction,
+};
+use move_core_types::{
+    identifier::Identifier, language_storage::TypeTag, move_resource::MoveResource,
+};
+
+fn block(timestamp_usecs: u64) -> Transaction {
+    Transaction::BlockMetadata(BlockMetadata::new(
+        HashValue::zero(),
+        1,
+        timestamp_usecs,
+        vec![],
+        AccountAddress::random(),
+    ))
+}
+
+fn sent_payment(sender: &AccountAddress, seq_num: u64, currency_code: &str) -> ContractEvent {
+    ContractEvent::new(
+        EventKey::new_from_address(sender, 1),
+        seq_num,
+        TypeTag::Struct(SentPaymentEvent::struct_tag()),
+        lcs::to_bytes(&SentPaymentEvent::new(
+            10,
+            Identifier::new(currency_code).unwrap(),
+            AccountAddress::random(),
+            vec![],
+        ))
+        .unwrap(),
+    )
+}
+
+fn mint(currency_code: &str) -> ContractEvent {
+    ContractEvent::new(
+        EventKey::random(),
+        0,
+        TypeTag::Struct(MintEvent::struct_tag()),
+        lcs::to_bytes(&(100u64, Identifier::new(currency_code).unwrap())).unwrap(),
+    )
+}
+
+#[test]
+fn test_index_and_query() {
+    let tmp_dir = TempPath::new();
+    let db = IndexerDB::new(&tmp_dir);
+    assert_eq!(db.next_version().unwrap(), 0);
+
+    let alice = AccountAddress::random();
+    let bob = AccountAddress::random();
+    let unindexed = ContractEvent::new(EventKey::random(), 0, TypeTag::Bool, vec![]);
+    let events = vec![
+        vec![unindexed, mint("Coin1")],
+        vec![
+            sent_payment(&alice, 0, "Coin1"),
+            sent_payment(&bob, 0, "Coin2"),
+        ],
+        vec![],
+        vec![sent_payment(&alice, 1, "Coin2")],
+    ];
+    let txns = vec![block(100), block(200), block(300), block(400)];
+    assert_eq!(db.index_transactions(0, 0, &txns, &events).unwrap(), 4);
+
+    let alice_events = db
+        .get_account_events(alice, EventKind::SentPayment, 0, 10)
+        .unwrap();
+    assert_eq!(alice_events.len(), 2);
+    assert_eq!(alice_events[0].transaction_version, 1);
+    assert_eq!(alice_events[0].event_index, 0);
+    assert_eq!(alice_events[0].timestamp_usecs, 200);
+    assert_eq!(alice_events[1].transaction_version, 3);
+    assert_eq!(
+        db.get_account_events(alice, EventKind::SentPayment, 2, 10)
+            .unwrap(),
+        alice_events[1..].to_vec()
+    );
+    assert_eq!(
+        db.get_account_events(alice, EventKind::SentPayment, 0, 1)
+            .unwrap(),
+        alice_events[..1].to_vec()
+    );
+    assert!(db
+        .get_account_events(alice, EventKind::ReceivedPayment, 0, 10)
+        .unwrap()
+        .is_empty());
+
+    let coin2_events = db
+        .get_currency_events("Coin2", EventKind::SentPayment, 0, 10)
+        .unwrap();
+    assert_eq!(coin2_events.len(), 2);
+    assert_eq!(coin2_events[0].account(), bob);
+    assert_eq!(coin2_events[1].account(), alice);
+    let mints = db
+        .get_currency_events("Coin1", EventKind::Mint, 0, 10)
+        .unwrap();
+    assert_eq!(mints.len(), 1);
+    assert_eq!(mints[0].event_index, 1);
+    assert!(db
+        .get_currency_events("Coin", EventKind::Mint, 0, 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_resume() {
+    let tmp_dir = TempPath::new();
+    let alice = AccountAddress::random();
+    {
+        let db = IndexerDB::new(&tmp_dir);
+        db.index_transactions(
+            0,
+            0,
+            &[block(100)],
+            &[vec![sent_payment(&alice, 0, "Coin1")]],
+        )
+        .unwrap();
+    }
+
+    let db = IndexerDB::new(&tmp_dir);
+    assert_eq!(db.next_version().unwrap(), 1);
+    // Transactions are indexed only once, in order.
+    assert!(db
+        .index_transactions(0, 0, &[block(100)], &[vec![]])
+        .is_err());
+    assert!(db
+        .index_transactions(2, 0, &[block(100)], &[vec![]])
+        .is_err());
+    assert_eq!(
+        db.index_transactions(
+            1,
+            100,
+            &[block(200)],
+            &[vec![sent_payment(&alice, 1, "Coin1")]]
+        )
+        .unwrap(),
+        2
+    );
+    assert_eq!(
+        db.get_account_events(alice, EventKind::SentPayment, 0, 10)
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_skip_to() {
+    let tmp_dir = TempPath::new();
+    let alice = AccountAddress::random();
+    let db = IndexerDB::new(&tmp_dir);
+    db.skip_to(10).unwrap();
+    assert_eq!(db.next_version().unwrap(), 10);
+    assert!(db
+        .index_transactions(0, 0, &[block(100)], &[vec![]])
+        .is_err());
+    assert_eq!(
+        db.index_transactions(
+            10,
+            0,
+            &[block(100)],
+            &[vec![sent_payment(&alice, 0, "Coin1")]]
+        )
+        .unwrap(),
+        11
+    );
+    // Indexed transactions aren't skipped again.
+    db.skip_to(5).unwrap();
+    db.skip_to(11).unwrap();
+    assert_eq!(db.next_version().unwrap(), 11);
+    let events = db
+        .get_account_events(alice, EventKind::SentPayment, 0, 10)
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].transaction_version, 10);
+}
@@ -0,0 +1,24 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! This crate provides [`IndexerDB`] and the service maintaining it, which tails the transactions
+//! committed to LibraDB and keeps secondary indexes of their payment, mint/burn and configuration
+//! events, by account and by currency, in its own schemadb instance. The indexer resumes from the
+//! last transaction it indexed after a restart, and serves the indexes as JSON RPC methods (see
+//! [`methods`]).
+//!
+//! [`IndexerDB`]: indexerdb/struct.IndexerDB.html
+//! [`methods`]: methods/index.html
+
+mod counters;
+pub mod event;
+pub mod indexerdb;
+#[cfg(test)]
+mod indexerdb_test;
+pub mod methods;
+mod runtime;
+mod schema;
+
+pub use runtime::{bootstrap, catch_up};
@@ -0,0 +1,122 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The JSON-RPC methods served by the indexer. Like the ones of the node, they take positional
+//! parameters:
+//! * `get_account_events(account_address, event_kind, start_version, limit)`: the events of a kind
+//!   emitted by an account, e.g. the payments it sent;
+//! * `get_currency_events(currency_code, event_kind, start_version, limit)`: the events of a kind
+//!   about a currency, e.g. all the mints of `Coin1`;
+//! * `get_indexer_status()`: how far the indexer is behind the ledger.
+//!
+//! Event kinds are `received_payment`, `sent_payment`, `mint`, `burn`, `preburn`, `cancel_burn`,
+//! `to_lbr_exchange_rate_update`, `new_epoch` and `upgrade`.
+
+use crate::{
+    event::{EventKind, IndexedEvent, IndexedEventView},
+    indexerdb::IndexerDB,
+};
+use anyhow::{ensure, Result};
+use libra_json_rpc_types::errors::JsonRpcError;
+use libra_types::{account_address::AccountAddress, transaction::Version};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{str::FromStr, sync::Arc};
+use storage_interface::DbReader;
+
+/// Maximum number of events returned by a single request
+pub const MAX_EVENTS_LIMIT: u64 = 1000;
+
+/// The names of the methods served by `execute`
+pub(crate) const METHODS: &[&str] = &[
+    "get_account_events",
+    "get_currency_events",
+    "get_indexer_status",
+];
+
+#[derive(Clone)]
+pub(crate) struct IndexerService {
+    pub db: Arc<IndexerDB>,
+    pub libra_db: Arc<dyn DbReader>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IndexerStatusView {
+    /// Version of the first transaction which isn't indexed yet
+    pub next_version: Version,
+    pub ledger_version: Version,
+}
+
+/// Executes `method` with `params`. Errors which are `JsonRpcError`s are returned as is to the
+/// client, the others as internal errors.
+pub(crate) fn execute(service: &IndexerService, method: &str, params: &[Value]) -> Result<Value> {
+    match method {
+        "get_account_events" => get_account_events(service, params),
+        "get_currency_events" => get_currency_events(service, params),
+        "get_indexer_status" => get_indexer_status(service, params),
+        _ => Err(JsonRpcError::method_not_found().into()),
+    }
+}
+
+fn get_account_events(service: &IndexerService, params: &[Value]) -> Result<Value> {
+    ensure_num_params(params, 4)?;
+    let address: String = param(params, 0)?;
+    let kind: EventKind = param(params, 1)?;
+    let start_version: Version = param(params, 2)?;
+    let limit: u64 = param(params, 3)?;
+    ensure_limit(limit)?;
+
+    let address = AccountAddress::from_str(&address)?;
+    let events = service
+        .db
+        .get_account_events(address, kind, start_version, limit)?;
+    to_value(events)
+}
+
+fn get_currency_events(service: &IndexerService, params: &[Value]) -> Result<Value> {
+    ensure_num_params(params, 4)?;
+    let currency_code: String = param(params, 0)?;
+    let kind: EventKind = param(params, 1)?;
+    let start_version: Version = param(params, 2)?;
+    let limit: u64 = param(params, 3)?;
+    ensure_limit(limit)?;
+
+    let events = service
+        .db
+        .get_currency_events(&currency_code, kind, start_version, limit)?;
+    to_value(events)
+}
+
+fn get_indexer_status(service: &IndexerService, params: &[Value]) -> Result<Value> {
+    ensure_num_params(params, 0)?;
+    Ok(serde_json::to_value(IndexerStatusView {
+        next_version: service.db.next_version()?,
+        ledger_version: service.libra_db.get_latest_version()?,
+    })?)
+}
+
+fn ensure_num_params(params: &[Value], num: usize) -> Result<()> {
+    if params.len() != num {
+        return Err(JsonRpcError::invalid_params().into());
+    }
+    Ok(())
+}
+
+fn ensure_limit(limit: u64) -> Result<()> {
+    ensure!(
+        limit <= MAX_EVENTS_LIMIT,
+        "limit {} exceeds the maximum of {}",
+        limit,
+        MAX_EVENTS_LIMIT
+    );
+    Ok(())
+}
+
+fn param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<T> {
+    serde_json::from_value(params[index].clone()).map_err(|_| JsonRpcError::invalid_params().into())
+}
+
+fn to_value(events: Vec<IndexedEvent>) -> Result<Value> {
+    let views: Vec<IndexedEventView> = events.into_iter().map(Into::into).collect();
+    Ok(serde_json::to_value(views)?)
+}
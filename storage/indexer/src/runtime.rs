@@ -0,0 +1,213 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters,
+    indexerdb::IndexerDB,
+    methods::{self, IndexerService},
+};
+use anyhow::{ensure, format_err, Result};
+use futures::future::join_all;
+use libra_config::config::NodeConfig;
+use libra_json_rpc_types::errors::JsonRpcError;
+use libra_logger::prelude::*;
+use libra_types::transaction::Version;
+use serde_json::{map::Map, Value};
+use std::sync::Arc;
+use storage_interface::DbReader;
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::watch,
+    task,
+};
+use warp::Filter;
+
+// Counter labels for runtime metrics
+const LABEL_FAIL: &str = "fail";
+const LABEL_MISSING_METHOD: &str = "method_not_found";
+const LABEL_SUCCESS: &str = "success";
+
+/// Indexes the transactions committed to `libra_db` which aren't indexed yet, in batches of
+/// `batch_size`, starting from the first one `libra_db` has. Returns the new
+/// `IndexerDB::next_version()`.
+pub fn catch_up(db: &IndexerDB, libra_db: &dyn DbReader, batch_size: u64) -> Result<Version> {
+    let first_version = match libra_db.get_first_txn_version()? {
+        Some(first_version) => first_version,
+        None => return db.next_version(),
+    };
+    let ledger_version = libra_db.get_latest_version()?;
+    if db.next_version()? < first_version {
+        info!(
+            "[indexer] skipping to version {}, the first one in LibraDB",
+            first_version
+        );
+        db.skip_to(first_version)?;
+    }
+    let mut next_version = db.next_version()?;
+    while next_version <= ledger_version {
+        let txn_list = libra_db.get_transactions(next_version, batch_size, ledger_version, true)?;
+        ensure!(
+            !txn_list.transactions.is_empty(),
+            "No transaction returned at version {}.",
+            next_version
+        );
+        let events = txn_list
+            .events
+            .ok_or_else(|| format_err!("No events returned at version {}.", next_version))?;
+        // The block of the first transaction may have started before it, when the transactions
+        // before it were left out of LibraDB. Its timestamp is unknown then, and left as 0.
+        let timestamp_usecs = if next_version == first_version {
+            libra_db.get_block_timestamp(next_version).unwrap_or(0)
+        } else {
+            libra_db.get_block_timestamp(next_version)?
+        };
+        next_version = db.index_transactions(
+            next_version,
+            timestamp_usecs,
+            &txn_list.transactions,
+            &events,
+        )?;
+        counters::NEXT_VERSION.set(next_version as i64);
+    }
+    Ok(next_version)
+}
+
+/// Starts indexing the transactions committed to `libra_db`, from where it stopped before, each
+/// time `commit_notifications` fires, and serves the indexes over HTTP (warp-based) as JSON RPC
+/// methods (see `methods`).
+/// Returns handle to corresponding Tokio runtime
+pub fn bootstrap(
+    config: &NodeConfig,
+    libra_db: Arc<dyn DbReader>,
+    mut commit_notifications: watch::Receiver<Version>,
+) -> Runtime {
+    let runtime = Builder::new()
+        .thread_name("indexer-")
+        .threaded_scheduler()
+        .enable_all()
+        .build()
+        .expect("[indexer] failed to create runtime");
+
+    let db = Arc::new(IndexerDB::new(config.storage.dir()));
+    let service = IndexerService {
+        db: Arc::clone(&db),
+        libra_db: Arc::clone(&libra_db),
+    };
+
+    let batch_size = config.indexer.batch_size;
+    runtime.handle().spawn(async move {
+        loop {
+            let (db, libra_db) = (Arc::clone(&db), Arc::clone(&libra_db));
+            // Indexing blocks on the DBs, so it's kept off the threads serving requests.
+            let result =
+                task::spawn_blocking(move || catch_up(&db, libra_db.as_ref(), batch_size)).await;
+            if let Err(e) = result.map_err(anyhow::Error::from).and_then(|res| res) {
+                counters::INDEXING_ERRORS.inc();
+                error!("[indexer] failed to index committed transactions: {:?}", e);
+            }
+            if commit_notifications.recv().await.is_none() {
+                break;
+            }
+        }
+    });
+
+    let route = warp::path::end()
+        .and(warp::post())
+        .and(warp::header::exact("content-type", "application/json"))
+        .and(warp::body::json())
+        .and(warp::any().map(move || service.clone()))
+        .and_then(rpc_endpoint);
+
+    let address = config.indexer.address;
+    let server = runtime.enter(move || warp::serve(route).bind(address));
+    runtime.handle().spawn(server);
+    runtime
+}
+
+/// JSON RPC entry point, for single and batch requests
+async fn rpc_endpoint(
+    data: Value,
+    service: IndexerService,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let resp = if let Value::Array(requests) = data {
+        let futures = requests
+            .into_iter()
+            .map(|req| rpc_request_handler(req, service.clone()));
+        Value::Array(join_all(futures).await)
+    } else {
+        rpc_request_handler(data, service).await
+    };
+    Ok(warp::reply::json(&resp))
+}
+
+/// Handler of single RPC request
+async fn rpc_request_handler(req: Value, service: IndexerService) -> Value {
+    let mut response = Map::new();
+    response.insert("jsonrpc".to_string(), Value::String("2.0".to_string()));
+    response.insert("id".to_string(), Value::Null);
+
+    let request = match req {
+        Value::Object(request) => request,
+        _ => {
+            set_response_error(&mut response, JsonRpcError::invalid_request());
+            return Value::Object(response);
+        }
+    };
+    match request.get("id") {
+        Some(id) if id.is_string() || id.is_number() || id.is_null() => {
+            response.insert("id".to_string(), id.clone());
+        }
+        _ => {
+            set_response_error(&mut response, JsonRpcError::invalid_request());
+            return Value::Object(response);
+        }
+    }
+    if request.get("jsonrpc") != Some(&Value::String("2.0".to_string())) {
+        set_response_error(&mut response, JsonRpcError::invalid_request());
+        return Value::Object(response);
+    }
+    let params = match request.get("params") {
+        Some(Value::Array(params)) => params,
+        _ => {
+            set_response_error(&mut response, JsonRpcError::invalid_params());
+            return Value::Object(response);
+        }
+    };
+    let method = match request.get("method") {
+        Some(Value::String(method)) => method,
+        _ => {
+            set_response_error(&mut response, JsonRpcError::invalid_request());
+            return Value::Object(response);
+        }
+    };
+
+    // Method names come from clients, only the known ones are used as labels.
+    let method_label = if methods::METHODS.contains(&method.as_str()) {
+        method.as_str()
+    } else {
+        LABEL_MISSING_METHOD
+    };
+    match methods::execute(&service, method, params) {
+        Ok(result) => {
+            response.insert("result".to_string(), result);
+            counters::REQUESTS
+                .with_label_values(&[method_label, LABEL_SUCCESS])
+                .inc();
+        }
+        Err(err) => {
+            let error = match err.downcast_ref::<JsonRpcError>() {
+                Some(error) => error.clone(),
+                None => JsonRpcError::internal_error(err.to_string()),
+            };
+            set_response_error(&mut response, error);
+            counters::REQUESTS
+                .with_label_values(&[method_label, LABEL_FAIL])
+                .inc();
+        }
+    }
+    Value::Object(response)
+}
+
+fn set_response_error(response: &mut Map<String, Value>, error: JsonRpcError) {
+    response.insert("error".to_string(), error.serialize());
+}
@@ -0,0 +1,63 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the indexed events.
+//!
+//! An event is keyed by the version of the transaction it belongs to and the index of it among all
+//! events yielded by the same transaction, as in the `EventSchema` of LibraDB.
+//! ```text
+//! |<-------key----->|<-------value------->|
+//! | version | index | indexed event bytes |
+//! ```
+
+use crate::{
+    event::IndexedEvent,
+    schema::{ensure_slice_len_eq, EVENT_CF_NAME},
+};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_types::transaction::Version;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(IndexedEventSchema, Key, IndexedEvent, EVENT_CF_NAME);
+
+type Index = u64;
+type Key = (Version, Index);
+
+impl KeyCodec<IndexedEventSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (version, index) = *self;
+
+        let mut encoded_key = Vec::with_capacity(size_of::<Version>() + size_of::<Index>());
+        encoded_key.write_u64::<BigEndian>(version)?;
+        encoded_key.write_u64::<BigEndian>(index)?;
+        Ok(encoded_key)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        let version_size = size_of::<Version>();
+
+        let version = (&data[..version_size]).read_u64::<BigEndian>()?;
+        let index = (&data[version_size..]).read_u64::<BigEndian>()?;
+        Ok((version, index))
+    }
+}
+
+impl ValueCodec<IndexedEventSchema> for IndexedEvent {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        lcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        lcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,20 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use libra_types::{contract_event::ContractEvent, event::EventKey};
+use move_core_types::language_storage::TypeTag;
+use schemadb::schema::assert_encode_decode;
+
+#[test]
+fn test_encode_decode() {
+    assert_encode_decode::<IndexedEventSchema>(
+        &(10, 2),
+        &IndexedEvent {
+            transaction_version: 10,
+            event_index: 2,
+            timestamp_usecs: 1_000_000,
+            event: ContractEvent::new(EventKey::random(), 5, TypeTag::Bool, vec![1, 2, 3]),
+        },
+    );
+}
@@ -0,0 +1,73 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of the events by the account which
+//! emitted them, via which the events of a kind emitted by an account can be listed in the order
+//! of the transactions. The event itself is in `IndexedEventSchema`.
+//!
+//! ```text
+//! |<--------------------key-------------------->|<-value->|
+//! | address | event kind | txn_ver | event index |  empty  |
+//! ```
+
+use crate::{
+    event::EventKind,
+    schema::{ensure_slice_len_eq, EVENT_BY_ACCOUNT_CF_NAME},
+};
+use anyhow::{format_err, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_types::{account_address::AccountAddress, transaction::Version};
+use num_traits::{FromPrimitive, ToPrimitive};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(EventByAccountSchema, Key, (), EVENT_BY_ACCOUNT_CF_NAME);
+
+type Index = u64;
+type Key = (AccountAddress, EventKind, Version, Index);
+
+const KEY_LEN: usize = AccountAddress::LENGTH + 1 + size_of::<Version>() + size_of::<Index>();
+
+impl KeyCodec<EventByAccountSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, kind, version, index) = *self;
+
+        let mut encoded = Vec::with_capacity(KEY_LEN);
+        encoded.extend_from_slice(address.as_ref());
+        encoded.write_u8(
+            kind.to_u8()
+                .ok_or_else(|| format_err!("ToPrimitive failed."))?,
+        )?;
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, KEY_LEN)?;
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let mut rest = &data[AccountAddress::LENGTH..];
+        let kind = EventKind::from_u8(rest.read_u8()?)
+            .ok_or_else(|| format_err!("FromPrimitive failed."))?;
+        let version = rest.read_u64::<BigEndian>()?;
+        let index = rest.read_u64::<BigEndian>()?;
+        Ok((address, kind, version, index))
+    }
+}
+
+impl ValueCodec<EventByAccountSchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)
+    }
+}
+
+#[cfg(test)]
+mod test;
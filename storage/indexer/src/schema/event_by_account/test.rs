@@ -0,0 +1,13 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use schemadb::schema::assert_encode_decode;
+
+#[test]
+fn test_encode_decode() {
+    assert_encode_decode::<EventByAccountSchema>(
+        &(AccountAddress::random(), EventKind::ReceivedPayment, 10, 2),
+        &(),
+    );
+}
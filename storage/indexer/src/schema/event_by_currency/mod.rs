@@ -0,0 +1,84 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index of the events by the currency they
+//! are about, via which e.g. all the mints of a currency can be listed in the order of the
+//! transactions. The event itself is in `IndexedEventSchema`.
+//!
+//! The currency code is prefixed with its length, so that the events of a currency are contiguous
+//! and not interleaved with the ones of the currencies of which its code is a prefix.
+//! ```text
+//! |<-------------------------key------------------------>|<-value->|
+//! | event kind | code len | currency code | txn_ver | idx |  empty  |
+//! ```
+
+use crate::{
+    event::EventKind,
+    schema::{ensure_slice_len_eq, ensure_slice_len_gt, EVENT_BY_CURRENCY_CF_NAME},
+};
+use anyhow::{ensure, format_err, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_types::transaction::Version;
+use num_traits::{FromPrimitive, ToPrimitive};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(EventByCurrencySchema, Key, (), EVENT_BY_CURRENCY_CF_NAME);
+
+type Index = u64;
+type Key = (EventKind, String, Version, Index);
+
+impl KeyCodec<EventByCurrencySchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (kind, ref currency_code, version, index) = *self;
+        ensure!(
+            currency_code.len() <= u8::max_value() as usize,
+            "Currency code too long: {}",
+            currency_code
+        );
+
+        let mut encoded = vec![];
+        encoded.write_u8(
+            kind.to_u8()
+                .ok_or_else(|| format_err!("ToPrimitive failed."))?,
+        )?;
+        encoded.write_u8(currency_code.len() as u8)?;
+        encoded.extend_from_slice(currency_code.as_bytes());
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_gt(data, 2)?;
+
+        let kind =
+            EventKind::from_u8(data[0]).ok_or_else(|| format_err!("FromPrimitive failed."))?;
+        let code_len = data[1] as usize;
+        ensure_slice_len_eq(
+            data,
+            2 + code_len + size_of::<Version>() + size_of::<Index>(),
+        )?;
+        let currency_code = String::from_utf8(data[2..2 + code_len].to_vec())?;
+        let mut rest = &data[2 + code_len..];
+        let version = rest.read_u64::<BigEndian>()?;
+        let index = rest.read_u64::<BigEndian>()?;
+        Ok((kind, currency_code, version, index))
+    }
+}
+
+impl ValueCodec<EventByCurrencySchema> for () {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,67 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the progress of the indexer, so that it resumes
+//! where it stopped after a restart.
+//!
+//! There is only one row in this column family, updated atomically with the indexes of each batch
+//! of transactions.
+//! ```text
+//! |<-------key------->|<------value----->|
+//! | progress key (u8) | next txn_ver     |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, INDEXER_PROGRESS_CF_NAME};
+use anyhow::{format_err, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use libra_types::transaction::Version;
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::{FromPrimitive, ToPrimitive};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(
+    IndexerProgressSchema,
+    IndexerProgressKey,
+    Version,
+    INDEXER_PROGRESS_CF_NAME
+);
+
+#[derive(Debug, Eq, PartialEq, FromPrimitive, ToPrimitive)]
+#[repr(u8)]
+pub enum IndexerProgressKey {
+    // Version of the first transaction which isn't indexed yet
+    NextVersion = 0,
+}
+
+impl KeyCodec<IndexerProgressSchema> for IndexerProgressKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(vec![self
+            .to_u8()
+            .ok_or_else(|| format_err!("ToPrimitive failed."))?])
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<u8>())?;
+        IndexerProgressKey::from_u8(data[0]).ok_or_else(|| format_err!("FromPrimitive failed."))
+    }
+}
+
+impl ValueCodec<IndexerProgressSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(size_of::<Version>());
+        encoded.write_u64::<BigEndian>(*self)?;
+        Ok(encoded)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok((&data[..]).read_u64::<BigEndian>()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
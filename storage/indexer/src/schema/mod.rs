@@ -0,0 +1,40 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines representation of the indexer data structures at physical level via schemas
+//! that implement [`schemadb::schema::Schema`].
+//!
+//! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
+
+pub(crate) mod event;
+pub(crate) mod event_by_account;
+pub(crate) mod event_by_currency;
+pub(crate) mod indexer_progress;
+
+use anyhow::{ensure, Result};
+use schemadb::ColumnFamilyName;
+
+pub(super) const EVENT_CF_NAME: ColumnFamilyName = "event";
+pub(super) const EVENT_BY_ACCOUNT_CF_NAME: ColumnFamilyName = "event_by_account";
+pub(super) const EVENT_BY_CURRENCY_CF_NAME: ColumnFamilyName = "event_by_currency";
+pub(super) const INDEXER_PROGRESS_CF_NAME: ColumnFamilyName = "indexer_progress";
+
+fn ensure_slice_len_eq(data: &[u8], len: usize) -> Result<()> {
+    ensure!(
+        data.len() == len,
+        "Unexpected data len {}, expected {}.",
+        data.len(),
+        len,
+    );
+    Ok(())
+}
+
+fn ensure_slice_len_gt(data: &[u8], len: usize) -> Result<()> {
+    ensure!(
+        data.len() > len,
+        "Unexpected data len {}, expected greater than {}.",
+        data.len(),
+        len,
+    );
+    Ok(())
+}
@@ -38,6 +38,13 @@ enum Command {
     },
     #[structopt(name = "list-accounts")]
     ListAccounts,
+    /// Indexes by hash the transactions of a DB created before the index existed. The node must
+    /// be stopped.
+    #[structopt(name = "backfill-txn-by-hash-index")]
+    BackfillTxnByHashIndex {
+        #[structopt(long, default_value = "10000")]
+        batch_size: usize,
+    },
 }
 
 /// Print out latest information stored in the DB.
@@ -165,8 +172,11 @@ fn main() {
     let log_dir = tempfile::tempdir().expect("Unable to get temp dir");
     info!("Opening DB at: {:?}, log at {:?}", p, log_dir.path());
 
-    let db =
-        LibraDB::open(p, true /* readonly */, None /* pruner */).expect("Unable to open LibraDB");
+    let readonly = match opt.cmd {
+        Some(Command::BackfillTxnByHashIndex { .. }) => false,
+        _ => true,
+    };
+    let db = LibraDB::open(p, readonly, None /* pruner */).expect("Unable to open LibraDB");
     info!("DB opened successfully.");
 
     if let Some(cmd) = opt.cmd {
@@ -183,6 +193,12 @@ fn main() {
             Command::ListAccounts => {
                 list_accounts(&db);
             }
+            Command::BackfillTxnByHashIndex { batch_size } => {
+                let num_indexed = db
+                    .backfill_transaction_by_hash_index(batch_size)
+                    .expect("Unable to backfill the index");
+                info!("Indexed {} transactions by hash.", num_indexed);
+            }
         }
     } else {
         print_head(&db).expect("Unable to read information from DB");
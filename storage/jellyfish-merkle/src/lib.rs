@@ -110,9 +110,9 @@ pub trait TreeReader {
     /// Gets node given a node key. Returns `None` if the node does not exist.
     fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>>;
 
-    /// Gets the rightmost leaf. Note that this assumes we are in the process of restoring the tree
-    /// and all nodes are at the same version.
-    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>>;
+    /// Gets the rightmost leaf at `version`. Note that this assumes we are in the process of
+    /// restoring the tree at that version, so the nodes of other versions are not considered.
+    fn get_rightmost_leaf(&self, version: Version) -> Result<Option<(NodeKey, LeafNode)>>;
 }
 
 pub trait TreeWriter {
@@ -155,7 +155,7 @@ where
     S: 'a + TreeReader + TreeWriter,
 {
     pub fn new(store: &'a S, version: Version, expected_root_hash: HashValue) -> Result<Self> {
-        let (partial_nodes, previous_leaf) = match store.get_rightmost_leaf()? {
+        let (partial_nodes, previous_leaf) = match store.get_rightmost_leaf(version)? {
             Some((node_key, leaf_node)) => {
                 // If the system crashed in the middle of the previous restoration attempt, we need
                 // to recover the partial nodes to the state right before the crash.
@@ -61,7 +61,7 @@ proptest! {
         }
 
         {
-            let rightmost_key = match restore_db.get_rightmost_leaf().unwrap() {
+            let rightmost_key = match restore_db.get_rightmost_leaf(version).unwrap() {
                 None => {
                     // Sometimes the batch is too small so nothing is written to DB.
                     return Ok(());
@@ -88,6 +88,62 @@ proptest! {
 
         assert_success(&restore_db, expected_root_hash, &all, version);
     }
+
+    #[test]
+    fn test_restore_with_interruption_over_another_version(
+        (all, batch1_size) in btree_map(any::<HashValue>(), any::<AccountStateBlob>(), 2..1000)
+            .prop_flat_map(|btree| {
+                let len = btree.len();
+                (Just(btree), 1..len)
+            }),
+        existing in btree_map(any::<HashValue>(), any::<AccountStateBlob>(), 1..100),
+    ) {
+        let (db, version) = init_mock_db(&all.clone().into_iter().collect());
+        let tree = JellyfishMerkleTree::new(&db);
+        let expected_root_hash = tree.get_root_hash(version).unwrap();
+        let batch1: Vec<_> = all.clone().into_iter().take(batch1_size).collect();
+
+        // The DB already has a tree at an older version, e.g. the genesis state.
+        let restore_db = MockTreeStore::default();
+        let (existing_root_hash, write_batch) = JellyfishMerkleTree::new(&restore_db)
+            .put_blob_set(existing.clone().into_iter().collect(), 0)
+            .unwrap();
+        restore_db.write_tree_update_batch(write_batch).unwrap();
+
+        {
+            let mut restore =
+                JellyfishMerkleRestore::new(&restore_db, version, expected_root_hash).unwrap();
+            let proof = tree
+                .get_range_proof(batch1.last().map(|(key, _value)| *key).unwrap(), version)
+                .unwrap();
+            restore.add_chunk(batch1, proof).unwrap();
+        }
+
+        {
+            let remaining_accounts: Vec<_> = match restore_db.get_rightmost_leaf(version).unwrap() {
+                Some((_, node)) => all
+                    .clone()
+                    .into_iter()
+                    .filter(|(k, _v)| *k > node.account_key())
+                    .collect(),
+                None => all.clone().into_iter().collect(),
+            };
+
+            let mut restore =
+                JellyfishMerkleRestore::new(&restore_db, version, expected_root_hash).unwrap();
+            let proof = tree
+                .get_range_proof(
+                    remaining_accounts.last().map(|(key, _value)| *key).unwrap(),
+                    version,
+                )
+                .unwrap();
+            restore.add_chunk(remaining_accounts, proof).unwrap();
+            restore.finish().unwrap();
+        }
+
+        assert_success(&restore_db, expected_root_hash, &all, version);
+        assert_success(&restore_db, existing_root_hash, &existing, 0);
+    }
 }
 
 fn assert_success(
@@ -16,6 +16,7 @@ use libra_types::{
     proof::{SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof},
     transaction::{Transaction, TransactionInfo, Version},
 };
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// `BackupHandler` provides functionalities for LibraDB data backup.
@@ -85,11 +86,18 @@ impl BackupHandler {
         &self,
         version: Version,
     ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + Send + Sync>> {
-        let iterator = JellyfishMerkleIterator::new(
-            Arc::clone(&self.state_store),
-            version,
-            HashValue::zero(),
-        )?;
+        self.get_account_iter_from(version, HashValue::zero())
+    }
+
+    /// Gets an iterator which yields accounts in the state tree, starting from the smallest key
+    /// that is greater or equal to `starting_key`.
+    pub fn get_account_iter_from(
+        &self,
+        version: Version,
+        starting_key: HashValue,
+    ) -> Result<Box<dyn Iterator<Item = Result<(HashValue, AccountStateBlob)>> + Send + Sync>> {
+        let iterator =
+            JellyfishMerkleIterator::new(Arc::clone(&self.state_store), version, starting_key)?;
         Ok(Box::new(iterator))
     }
 
@@ -124,6 +132,18 @@ impl BackupHandler {
         Ok((txn_info, ledger_info))
     }
 
+    /// Gets the current open epoch and the latest committed version, `None` if the DB is not
+    /// bootstrapped yet.
+    pub fn get_db_state(&self) -> Result<Option<DbState>> {
+        Ok(self
+            .ledger_store
+            .get_latest_ledger_info_option()
+            .map(|li| DbState {
+                epoch: li.ledger_info().next_block_epoch(),
+                committed_version: li.ledger_info().version(),
+            }))
+    }
+
     pub fn get_epoch_ending_ledger_info_iter(
         &self,
         start_epoch: u64,
@@ -133,3 +153,12 @@ impl BackupHandler {
             .get_epoch_ending_ledger_info_iter(start_epoch, end_epoch)
     }
 }
+
+/// A summary of what's in the DB, used by the backup tooling to figure out what's new.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbState {
+    /// The current open epoch. All epochs before it have ended.
+    pub epoch: u64,
+    /// The version of the latest committed transaction.
+    pub committed_version: Version,
+}
@@ -53,6 +53,15 @@ impl RestoreHandler {
         JellyfishMerkleRestore::new(&*self.state_store, version, expected_root_hash)
     }
 
+    /// Gets the key of the rightmost account persisted by an unfinished state restore at
+    /// `version`, `None` if nothing is persisted yet. A resumed restore continues after it.
+    pub fn get_state_restore_progress(&self, version: Version) -> Result<Option<HashValue>> {
+        Ok(self
+            .state_store
+            .get_rightmost_leaf(version)?
+            .map(|(_node_key, leaf_node)| leaf_node.account_key()))
+    }
+
     pub fn save_ledger_infos(&self, ledger_infos: &[LedgerInfoWithSignatures]) -> Result<()> {
         ensure!(!ledger_infos.is_empty(), "No LedgerInfos to save.");
 
@@ -33,6 +33,7 @@ use libra_types::{
 use schemadb::{ReadOptions, SchemaIterator, DB};
 use std::{ops::Deref, sync::Arc};
 use storage_interface::{StartupInfo, TreeState};
+use tokio::sync::watch;
 
 #[derive(Debug)]
 pub(crate) struct LedgerStore {
@@ -42,6 +43,11 @@ pub(crate) struct LedgerStore {
     /// cache it in memory in order to avoid reading DB and deserializing the object frequently. It
     /// should be updated every time new ledger info and signatures are persisted.
     latest_ledger_info: ArcSwap<Option<LedgerInfoWithSignatures>>,
+
+    /// Notified of the version of the latest ledger info every time it's updated. The receiver is
+    /// kept so that it can be cloned for new subscribers, and so that sending never fails.
+    latest_version_sender: watch::Sender<Version>,
+    latest_version_receiver: watch::Receiver<Version>,
 }
 
 impl LedgerStore {
@@ -58,9 +64,17 @@ impl LedgerStore {
                 .map(|kv| kv.1)
         };
 
+        let (latest_version_sender, latest_version_receiver) = watch::channel(
+            ledger_info
+                .as_ref()
+                .map_or(0, |li| li.ledger_info().version()),
+        );
+
         Self {
             db,
             latest_ledger_info: ArcSwap::from(Arc::new(ledger_info)),
+            latest_version_sender,
+            latest_version_receiver,
         }
     }
 
@@ -122,8 +136,15 @@ impl LedgerStore {
     }
 
     pub fn set_latest_ledger_info(&self, ledger_info_with_sigs: LedgerInfoWithSignatures) {
+        let version = ledger_info_with_sigs.ledger_info().version();
         self.latest_ledger_info
             .store(Arc::new(Some(ledger_info_with_sigs)));
+        // Can't fail since `latest_version_receiver` is alive.
+        let _ = self.latest_version_sender.broadcast(version);
+    }
+
+    pub fn subscribe_latest_version(&self) -> watch::Receiver<Version> {
+        self.latest_version_receiver.clone()
     }
 
     pub fn get_latest_ledger_info_in_epoch(&self, epoch: u64) -> Result<LedgerInfoWithSignatures> {
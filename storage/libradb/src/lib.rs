@@ -76,6 +76,7 @@ use once_cell::sync::Lazy;
 use schemadb::{DB, DEFAULT_CF_NAME};
 use std::{iter::Iterator, path::Path, sync::Arc, time::Instant};
 use storage_interface::{DbReader, DbWriter, StartupInfo, TreeState};
+use tokio::sync::watch;
 
 static OP_COUNTER: Lazy<OpMetrics> = Lazy::new(|| OpMetrics::new_and_registered("storage"));
 
@@ -124,6 +125,7 @@ impl LibraDB {
             TRANSACTION_CF_NAME,
             TRANSACTION_ACCUMULATOR_CF_NAME,
             TRANSACTION_BY_ACCOUNT_CF_NAME,
+            TRANSACTION_BY_HASH_CF_NAME,
             TRANSACTION_INFO_CF_NAME,
         ];
 
@@ -166,6 +168,21 @@ impl LibraDB {
 
     // ================================== Public API ==================================
 
+    /// Returns a receiver notified of the version of the latest ledger info every time new
+    /// transactions are committed with a ledger info, so that readers can look for what's new
+    /// instead of polling. Notifications are coalesced: a slow receiver only sees the latest one.
+    pub fn subscribe_commits(&self) -> watch::Receiver<Version> {
+        self.ledger_store.subscribe_latest_version()
+    }
+
+    /// Indexes by hash all the transactions in the DB, committing every `batch_size` of them, so
+    /// that DBs created before the index existed support `get_txn_by_hash`. Returns the number of
+    /// transactions indexed. Meant to be run offline.
+    pub fn backfill_transaction_by_hash_index(&self, batch_size: usize) -> Result<usize> {
+        self.transaction_store
+            .backfill_transaction_by_hash_index(batch_size)
+    }
+
     /// Returns ledger infos reflecting epoch bumps starting with the given epoch. If there are no
     /// more than `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results, this function returns all of them,
     /// otherwise the first `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results are returned and a flag
@@ -506,6 +523,22 @@ impl DbReader for LibraDB {
             .transpose()
     }
 
+    fn get_txn_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        let _timer = LIBRA_STORAGE_API_LATENCY_SECONDS
+            .with_label_values(&["get_txn_by_hash"])
+            .start_timer();
+
+        self.transaction_store
+            .lookup_transaction_by_hash(hash, ledger_version)?
+            .map(|version| self.get_transaction_with_proof(version, ledger_version, fetch_events))
+            .transpose()
+    }
+
     // ======================= State Synchronizer Internal APIs ===================================
     /// Gets a batch of transactions for the purpose of synchronizing state to another node.
     ///
@@ -727,6 +760,16 @@ impl DbReader for LibraDB {
         Ok(tree_state)
     }
 
+    /// Returns the version of the first transaction in the DB, or None if there's none. It's 0
+    /// unless the DB was restored from a backup, which doesn't need to start from genesis.
+    fn get_first_txn_version(&self) -> Result<Option<Version>> {
+        let _timer = LIBRA_STORAGE_API_LATENCY_SECONDS
+            .with_label_values(&["get_first_txn_version"])
+            .start_timer();
+
+        self.transaction_store.get_first_txn_version()
+    }
+
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         let _timer = LIBRA_STORAGE_API_LATENCY_SECONDS
             .with_label_values(&["get_block_timestamp"])
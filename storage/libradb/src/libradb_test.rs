@@ -59,6 +59,7 @@ fn verify_epochs(db: &LibraDB, ledger_infos_with_sigs: &[LedgerInfoWithSignature
 pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>) {
     let tmp_dir = TempPath::new();
     let db = LibraDB::new_for_test(&tmp_dir);
+    let commits = db.subscribe_commits();
 
     let num_batches = input.len();
     let mut cur_ver = 0;
@@ -74,6 +75,10 @@ pub fn test_save_blocks_impl(input: Vec<(Vec<TransactionToCommit>, LedgerInfoWit
             db.ledger_store.get_latest_ledger_info().unwrap(),
             *ledger_info_with_sigs
         );
+        assert_eq!(
+            *commits.borrow(),
+            ledger_info_with_sigs.ledger_info().version()
+        );
         verify_committed_transactions(
             &db,
             &txns_to_commit,
@@ -329,6 +334,14 @@ fn verify_committed_transactions(
             .verify_user_txn(ledger_info, cur_ver, txn.sender(), txn.sequence_number())
             .unwrap();
 
+        let txn_with_proof = db
+            .get_txn_by_hash(txn_to_commit.transaction().hash(), ledger_version, true)
+            .unwrap()
+            .expect("Should exist.");
+        txn_with_proof
+            .verify_user_txn(ledger_info, cur_ver, txn.sender(), txn.sequence_number())
+            .unwrap();
+
         let txn_list_with_proof = db
             .get_transactions(cur_ver, 1, ledger_version, true /* fetch_events */)
             .unwrap();
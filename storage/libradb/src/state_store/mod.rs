@@ -112,9 +112,12 @@ impl StateStore {
         JellyfishMerkleTree::new(self).get_root_hash_option(version)
     }
 
-    /// Finds the rightmost leaf by scanning the entire DB.
+    /// Finds the rightmost leaf at `version` by scanning the entire DB.
     #[cfg(test)]
-    pub fn get_rightmost_leaf_naive(&self) -> Result<Option<(NodeKey, LeafNode)>> {
+    pub fn get_rightmost_leaf_naive(
+        &self,
+        version: Version,
+    ) -> Result<Option<(NodeKey, LeafNode)>> {
         let mut ret = None;
 
         let mut iter = self
@@ -123,6 +126,9 @@ impl StateStore {
         iter.seek_to_first();
 
         while let Some((node_key, node)) = iter.next().transpose()? {
+            if node_key.version() != version {
+                continue;
+            }
             if let Node::Leaf(leaf_node) = node {
                 match ret {
                     None => ret = Some((node_key, leaf_node)),
@@ -144,18 +150,7 @@ impl TreeReader for StateStore {
         Ok(self.db.get::<JellyfishMerkleNodeSchema>(node_key)?)
     }
 
-    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
-        // Since everything has the same version during restore, we seek to the first node and get
-        // its version.
-        let mut iter = self
-            .db
-            .iter::<JellyfishMerkleNodeSchema>(Default::default())?;
-        iter.seek_to_first();
-        let version = match iter.next().transpose()? {
-            Some((node_key, _node)) => node_key.version(),
-            None => return Ok(None),
-        };
-
+    fn get_rightmost_leaf(&self, version: Version) -> Result<Option<(NodeKey, LeafNode)>> {
         // The encoding of key and value in DB looks like:
         //
         // | <-------------- key --------------> | <- value -> |
@@ -186,7 +181,11 @@ impl TreeReader for StateStore {
             iter.seek_for_prev(&seek_key)?;
 
             if let Some((node_key, node)) = iter.next().transpose()? {
-                debug_assert_eq!(node_key.version(), version);
+                // The range can be empty at this version, in which case we end up in a range of
+                // an older version, e.g. the genesis state the DB is bootstrapped with.
+                if node_key.version() != version {
+                    continue;
+                }
                 debug_assert!(node_key.nibble_path().num_nibbles() < num_nibbles);
 
                 if let Node::Leaf(leaf_node) = node {
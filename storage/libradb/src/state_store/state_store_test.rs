@@ -264,7 +264,8 @@ proptest! {
             .prop_flat_map(|input| {
                 let len = input.len();
                 (Just(input), 1..len)
-            })
+            }),
+        other_account in (any::<AccountAddress>(), any::<AccountStateBlob>()),
     ) {
         let tmp_dir1 = TempPath::new();
         let db1 = LibraDB::new_for_test(&tmp_dir1);
@@ -277,6 +278,8 @@ proptest! {
         let tmp_dir2 = TempPath::new();
         let db2 = LibraDB::new_for_test(&tmp_dir2);
         let store2 = &db2.state_store;
+        // Nodes of other versions, e.g. the genesis state, are not considered.
+        init_store(&store2, std::iter::once(other_account));
 
         let mut restore =
             JellyfishMerkleRestore::new(&**store2, version, expected_root_hash).unwrap();
@@ -322,7 +325,8 @@ proptest! {
             .prop_flat_map(|input| {
                 let len = input.len();
                 (Just(input), 1..len)
-            })
+            }),
+        other_account in (any::<AccountAddress>(), any::<AccountStateBlob>()),
     ) {
         let tmp_dir1 = TempPath::new();
         let db1 = LibraDB::new_for_test(&tmp_dir1);
@@ -335,6 +339,8 @@ proptest! {
         let tmp_dir2 = TempPath::new();
         let db2 = LibraDB::new_for_test(&tmp_dir2);
         let store2 = &db2.state_store;
+        // Nodes of other versions, e.g. the genesis state, are not considered.
+        init_store(&store2, std::iter::once(other_account));
 
         let mut restore =
             JellyfishMerkleRestore::new(&**store2, version, expected_root_hash).unwrap();
@@ -356,8 +362,8 @@ proptest! {
 
         restore.add_chunk(batch1, proof_of_batch1).unwrap();
 
-        let expected = store2.get_rightmost_leaf_naive().unwrap();
-        let actual = store2.get_rightmost_leaf().unwrap();
+        let expected = store2.get_rightmost_leaf_naive(version).unwrap();
+        let actual = store2.get_rightmost_leaf(version).unwrap();
         prop_assert_eq!(actual, expected);
     }
 }
@@ -6,15 +6,19 @@
 use crate::{
     change_set::ChangeSet,
     errors::LibraDbError,
-    schema::{transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema},
+    schema::{
+        transaction::TransactionSchema, transaction_by_account::TransactionByAccountSchema,
+        transaction_by_hash::TransactionByHashSchema,
+    },
 };
 use anyhow::{ensure, format_err, Result};
+use libra_crypto::{hash::CryptoHash, HashValue};
 use libra_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
     transaction::{Transaction, Version},
 };
-use schemadb::{SchemaIterator, DB};
+use schemadb::{SchemaBatch, SchemaIterator, DB};
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -46,6 +50,18 @@ impl TransactionStore {
         Ok(None)
     }
 
+    /// Gets the version of a transaction by its hash.
+    pub fn lookup_transaction_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+    ) -> Result<Option<Version>> {
+        Ok(self
+            .db
+            .get::<TransactionByHashSchema>(&hash)?
+            .filter(|version| *version <= ledger_version))
+    }
+
     /// Get signed transaction given `version`
     pub fn get_transaction(&self, version: Version) -> Result<Transaction> {
         self.db
@@ -53,6 +69,15 @@ impl TransactionStore {
             .ok_or_else(|| LibraDbError::NotFound(format!("Txn {}", version)).into())
     }
 
+    /// Get the version of the first transaction stored, if any
+    pub fn get_first_txn_version(&self) -> Result<Option<Version>> {
+        let mut iter = self.db.iter::<TransactionSchema>(Default::default())?;
+        iter.seek_to_first();
+        iter.next()
+            .map(|res| res.map(|(version, _)| version))
+            .transpose()
+    }
+
     /// Gets an iterator that yields `num_transactions` transactions starting from `start_version`.
     pub fn get_transaction_iter(
         &self,
@@ -108,10 +133,36 @@ impl TransactionStore {
                 &version,
             )?;
         }
+        cs.batch
+            .put::<TransactionByHashSchema>(&transaction.hash(), &version)?;
         cs.batch.put::<TransactionSchema>(&version, &transaction)?;
 
         Ok(())
     }
+
+    /// Indexes by hash all the transactions in the DB, committing every `batch_size` of them.
+    /// Returns the number of transactions indexed. This is for DBs created before the index
+    /// existed.
+    pub fn backfill_transaction_by_hash_index(&self, batch_size: usize) -> Result<usize> {
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let mut iter = self.db.iter::<TransactionSchema>(Default::default())?;
+        iter.seek_to_first();
+
+        let mut batch = SchemaBatch::new();
+        let mut num_indexed = 0;
+        for res in iter {
+            let (version, txn) = res?;
+            batch.put::<TransactionByHashSchema>(&txn.hash(), &version)?;
+            num_indexed += 1;
+            if num_indexed % batch_size == 0 {
+                self.db
+                    .write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+            }
+        }
+        self.db.write_schemas(batch)?;
+
+        Ok(num_indexed)
+    }
 }
 
 pub struct TransactionIter<'a> {
@@ -44,11 +44,58 @@ proptest! {
                     .unwrap(),
                 Some(ver as Version)
             );
+            prop_assert_eq!(
+                store.lookup_transaction_by_hash(txn.hash(), ledger_version).unwrap(),
+                Some(ver as Version)
+            );
+            prop_assert_eq!(
+                store.lookup_transaction_by_hash(txn.hash(), ver as Version).unwrap(),
+                Some(ver as Version)
+            );
+            if ver > 0 {
+                prop_assert_eq!(
+                    store.lookup_transaction_by_hash(txn.hash(), ver as Version - 1).unwrap(),
+                    None
+                );
+            }
         }
 
         prop_assert!(store.get_transaction(ledger_version + 1).is_err());
     }
 
+    #[test]
+    fn test_backfill_transaction_by_hash_index(
+        txns in vec(any::<SignedTransaction>().prop_map(Transaction::UserTransaction), 1..10),
+        batch_size in 1..4usize,
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = LibraDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+
+        // Transactions written without the index, as by an old version of the DB.
+        let mut batch = SchemaBatch::new();
+        for (ver, txn) in txns.iter().enumerate() {
+            batch.put::<TransactionSchema>(&(ver as Version), txn).unwrap();
+        }
+        store.db.write_schemas(batch).unwrap();
+        let ledger_version = txns.len() as Version - 1;
+        prop_assert_eq!(
+            store.lookup_transaction_by_hash(txns[0].hash(), ledger_version).unwrap(),
+            None
+        );
+
+        prop_assert_eq!(
+            store.backfill_transaction_by_hash_index(batch_size).unwrap(),
+            txns.len()
+        );
+        for (ver, txn) in txns.iter().enumerate() {
+            prop_assert_eq!(
+                store.lookup_transaction_by_hash(txn.hash(), ledger_version).unwrap(),
+                Some(ver as Version)
+            );
+        }
+    }
+
     #[test]
     fn test_get_transaction_iter(
         universe in any_with::<AccountInfoUniverse>(3),
@@ -103,6 +150,27 @@ proptest! {
         prop_assert!(store.get_transaction_iter(10, usize::max_value()).is_err());
     }
 
+    #[test]
+    fn test_get_first_txn_version(
+        txns in vec(any::<SignedTransaction>().prop_map(Transaction::UserTransaction), 1..10),
+        first_version in 0..100 as Version,
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = LibraDB::new_for_test(&tmp_dir);
+        let store = &db.transaction_store;
+        prop_assert_eq!(store.get_first_txn_version().unwrap(), None);
+
+        // Transactions not starting from genesis, as restored from a backup.
+        let mut cs = ChangeSet::new();
+        for (idx, txn) in txns.iter().enumerate() {
+            store
+                .put_transaction(first_version + idx as Version, &txn, &mut cs)
+                .unwrap();
+        }
+        store.db.write_schemas(cs.batch).unwrap();
+        prop_assert_eq!(store.get_first_txn_version().unwrap(), Some(first_version));
+    }
+
     #[test]
     fn test_get_block_metadata(
         txns in vec(
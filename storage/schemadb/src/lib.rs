@@ -338,6 +338,16 @@ impl DB {
 
     /// Writes a group of records wrapped in a [`SchemaBatch`].
     pub fn write_schemas(&self, batch: SchemaBatch) -> Result<()> {
+        self.write_schemas_opt(batch, &default_write_options())
+    }
+
+    /// Writes a group of records like `write_schemas`, without waiting for them to be synced to
+    /// disk: they may be lost if the machine crashes, but not if only the process does.
+    pub fn write_schemas_relaxed(&self, batch: SchemaBatch) -> Result<()> {
+        self.write_schemas_opt(batch, &rocksdb::WriteOptions::default())
+    }
+
+    fn write_schemas_opt(&self, batch: SchemaBatch, opts: &rocksdb::WriteOptions) -> Result<()> {
         let _timer = OP_COUNTER.timer(&format!("db_batch_commit_time_{}", self.name));
         let mut db_batch = rocksdb::WriteBatch::default();
         for (cf_name, rows) in &batch.rows {
@@ -351,7 +361,7 @@ impl DB {
         }
         let serialized_size = db_batch.size_in_bytes();
 
-        self.inner.write_opt(db_batch, &default_write_options())?;
+        self.inner.write_opt(db_batch, opts)?;
 
         // Bump counters only after DB write succeeds.
         for (cf_name, rows) in &batch.rows {
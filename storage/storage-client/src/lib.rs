@@ -96,6 +96,10 @@ impl DbReader for StorageClient {
         unimplemented!()
     }
 
+    fn get_first_txn_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
     fn get_txn_by_account(
         &self,
         _address: AccountAddress,
@@ -106,6 +110,15 @@ impl DbReader for StorageClient {
         unimplemented!()
     }
 
+    fn get_txn_by_hash(
+        &self,
+        _hash: HashValue,
+        _ledger_version: u64,
+        _fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        unimplemented!()
+    }
+
     fn get_transactions(
         &self,
         _start_version: u64,
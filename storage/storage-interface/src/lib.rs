@@ -174,6 +174,12 @@ pub trait DbReader: Send + Sync {
     /// Returns the latest ledger info.
     fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures>;
 
+    /// See [`LibraDB::get_first_txn_version`].
+    ///
+    /// [`LibraDB::get_first_txn_version`]:
+    /// ../libradb/struct.LibraDB.html#method.get_first_txn_version
+    fn get_first_txn_version(&self) -> Result<Option<Version>>;
+
     /// Returns the latest ledger info.
     fn get_latest_version(&self) -> Result<Version> {
         Ok(self.get_latest_ledger_info()?.ledger_info().version())
@@ -201,6 +207,15 @@ pub trait DbReader: Send + Sync {
         fetch_events: bool,
     ) -> Result<Option<TransactionWithProof>>;
 
+    /// Returns the transaction with the given hash, if it's committed at or before
+    /// `ledger_version`.
+    fn get_txn_by_hash(
+        &self,
+        hash: HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>>;
+
     /// Returns proof of new state for a given ledger info with signatures relative to version known
     /// to client
     fn get_state_proof_with_ledger_info(
@@ -70,6 +70,10 @@ impl DbReader for MockDbReader {
         unimplemented!()
     }
 
+    fn get_first_txn_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
     fn get_startup_info(&self) -> Result<Option<StartupInfo>> {
         unimplemented!()
     }
@@ -84,6 +88,15 @@ impl DbReader for MockDbReader {
         unimplemented!()
     }
 
+    fn get_txn_by_hash(
+        &self,
+        _hash: HashValue,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Result<Option<TransactionWithProof>> {
+        unimplemented!()
+    }
+
     fn get_state_proof_with_ledger_info(
         &self,
         _known_version: u64,
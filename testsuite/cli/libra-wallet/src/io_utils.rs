@@ -3,25 +3,57 @@
 
 //! A module to generate, store and load known users accounts.
 //! The concept of known users can be helpful for testing to provide reproducible results.
+//!
+//! The recovery data of a wallet is its mnemonic and key leaf, separated by `DELIMITER`, followed
+//! by the hex-encoded public key of each of its multisig accounts, one per line. It is stored
+//! either in plaintext, or in a password-encrypted `Keystore`.
 
-use crate::{mnemonic::Mnemonic, wallet_library::WalletLibrary};
+use crate::{keystore::Keystore, mnemonic::Mnemonic, wallet_library::WalletLibrary};
 use anyhow::{ensure, Result};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Write},
-    path::Path,
-};
+use libra_crypto::multi_ed25519::MultiEd25519PublicKey;
+use std::{convert::TryFrom, fs, path::Path};
 
 /// Delimiter used to ser/deserialize account data.
 pub const DELIMITER: &str = ";";
 
 /// Recover wallet from the path specified.
 pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
-    let input = File::open(path)?;
-    let mut buffered = BufReader::new(input);
+    from_recovery_data(&fs::read_to_string(path)?)
+}
+
+/// Write wallet seed to file.
+pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
+    fs::write(path, recovery_data(wallet))?;
+    Ok(())
+}
+
+/// Recover wallet from the keystore at the path specified, encrypted under password.
+pub fn recover_from_keystore<P: AsRef<Path>>(path: &P, password: &str) -> Result<WalletLibrary> {
+    let data = Keystore::read(path)?.decrypt(password)?;
+    from_recovery_data(&String::from_utf8(data)?)
+}
+
+/// Write wallet seed to a keystore file, encrypted under password.
+pub fn write_keystore<P: AsRef<Path>>(
+    wallet: &WalletLibrary,
+    path: &P,
+    password: &str,
+) -> Result<()> {
+    Keystore::encrypt(recovery_data(wallet).as_bytes(), password)?.write(path)
+}
 
-    let mut line = String::new();
-    let _ = buffered.read_line(&mut line)?;
+fn recovery_data(wallet: &WalletLibrary) -> String {
+    let mut data = format!("{}{}{}\n", wallet.mnemonic(), DELIMITER, wallet.key_leaf());
+    for public_key in wallet.get_multisig_accounts() {
+        data.push_str(&hex::encode(public_key.to_bytes()));
+        data.push('\n');
+    }
+    data
+}
+
+fn from_recovery_data(data: &str) -> Result<WalletLibrary> {
+    let mut lines = data.lines();
+    let line = lines.next().unwrap_or_default();
     let parts: Vec<&str> = line.split(DELIMITER).collect();
     ensure!(parts.len() == 2, format!("Invalid entry '{}'", line));
 
@@ -29,19 +61,10 @@ pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
     let mut wallet = WalletLibrary::new_from_mnemonic(mnemonic);
     wallet.generate_addresses(parts[1].trim().to_string().parse::<u64>()?)?;
 
-    Ok(wallet)
-}
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let public_key = MultiEd25519PublicKey::try_from(&hex::decode(line.trim())?[..])?;
+        wallet.add_multisig_account(public_key);
+    }
 
-/// Write wallet seed to file.
-pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
-    let mut output = File::create(path)?;
-    writeln!(
-        output,
-        "{}{}{}",
-        wallet.mnemonic(),
-        DELIMITER,
-        wallet.key_leaf()
-    )?;
-
-    Ok(())
+    Ok(wallet)
 }
@@ -0,0 +1,185 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A password-encrypted keystore, so that the wallet recovery data (which includes the mnemonic)
+//! doesn't have to be stored in plaintext.
+//!
+//! The encryption key is derived from the password with Argon2id under a random salt, and the data
+//! is encrypted with AES-256-GCM under a random nonce. The KDF parameters, salt and nonce are
+//! stored in the clear next to the ciphertext, in a JSON file. A wrong password (or a tampered
+//! file) is detected by the authentication tag of AES-GCM.
+
+use crate::error::WalletError;
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
+    Aes256Gcm,
+};
+use anyhow::{ensure, Result};
+use rand::{rngs::OsRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Version of the keystore format.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Default Argon2id memory cost, in KiB.
+pub const DEFAULT_MEM_COST: u32 = 65536;
+/// Default Argon2id number of passes.
+pub const DEFAULT_TIME_COST: u32 = 3;
+
+const KEY_LENGTH: u32 = 32;
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+/// Authenticated along with the ciphertext, to bind it to the format.
+const ASSOCIATED_DATA: &[u8] = b"LIBRA::WalletKeystore";
+
+/// Parameters of the Argon2id derivation of the encryption key.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct KdfParams {
+    /// Hex-encoded salt
+    pub salt: String,
+    /// Memory cost, in KiB
+    pub mem_cost: u32,
+    /// Number of passes
+    pub time_cost: u32,
+    /// Degree of parallelism
+    pub lanes: u32,
+}
+
+impl KdfParams {
+    fn new(mem_cost: u32, time_cost: u32) -> Self {
+        let salt: [u8; SALT_LENGTH] = OsRng.gen();
+        Self {
+            salt: hex::encode(salt),
+            mem_cost,
+            time_cost,
+            lanes: 1,
+        }
+    }
+
+    fn derive_key(&self, password: &str) -> Result<Vec<u8>> {
+        let config = argon2::Config {
+            variant: argon2::Variant::Argon2id,
+            version: argon2::Version::Version13,
+            mem_cost: self.mem_cost,
+            time_cost: self.time_cost,
+            lanes: self.lanes,
+            thread_mode: argon2::ThreadMode::Sequential,
+            secret: &[],
+            ad: &[],
+            hash_length: KEY_LENGTH,
+        };
+        Ok(argon2::hash_raw(
+            password.as_bytes(),
+            &hex::decode(&self.salt)?,
+            &config,
+        )?)
+    }
+}
+
+/// Data encrypted under a password.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Keystore {
+    version: u32,
+    kdf: KdfParams,
+    /// Hex-encoded AES-GCM nonce
+    nonce: String,
+    /// Hex-encoded ciphertext, followed by the authentication tag
+    ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypts `data` under `password`, with the default KDF parameters.
+    pub fn encrypt(data: &[u8], password: &str) -> Result<Self> {
+        Self::encrypt_with_params(data, password, DEFAULT_MEM_COST, DEFAULT_TIME_COST)
+    }
+
+    /// Encrypts `data` under `password`, with the given Argon2id memory cost (in KiB) and number
+    /// of passes.
+    pub fn encrypt_with_params(
+        data: &[u8],
+        password: &str,
+        mem_cost: u32,
+        time_cost: u32,
+    ) -> Result<Self> {
+        let kdf = KdfParams::new(mem_cost, time_cost);
+        let key = kdf.derive_key(password)?;
+        let nonce: [u8; NONCE_LENGTH] = OsRng.gen();
+        let ciphertext = Aes256Gcm::new(GenericArray::from_slice(&key))
+            .encrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: data,
+                    aad: ASSOCIATED_DATA,
+                },
+            )
+            .map_err(|_| WalletError::LibraWalletGeneric("Encryption failed".to_string()))?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the data with `password`. Fails if the password is wrong.
+    pub fn decrypt(&self, password: &str) -> Result<Vec<u8>> {
+        ensure!(
+            self.version == KEYSTORE_VERSION,
+            "Unsupported keystore version {}",
+            self.version
+        );
+        let nonce = hex::decode(&self.nonce)?;
+        ensure!(nonce.len() == NONCE_LENGTH, "Invalid keystore nonce");
+        let key = self.kdf.derive_key(password)?;
+        Ok(Aes256Gcm::new(GenericArray::from_slice(&key))
+            .decrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: &hex::decode(&self.ciphertext)?,
+                    aad: ASSOCIATED_DATA,
+                },
+            )
+            .map_err(|_| {
+                WalletError::LibraWalletGeneric(
+                    "Unable to decrypt the keystore: wrong password or corrupted file".to_string(),
+                )
+            })?)
+    }
+
+    /// Reads a keystore from a JSON file.
+    pub fn read<P: AsRef<Path>>(path: &P) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Writes the keystore to a JSON file.
+    pub fn write<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_keystore() {
+    let keystore = Keystore::encrypt_with_params(b"secret", "password", 8, 1).unwrap();
+    assert!(!keystore.ciphertext.contains(&hex::encode(b"secret")));
+    assert_eq!(keystore.decrypt("password").unwrap(), b"secret".to_vec());
+    assert!(keystore.decrypt("wrong password").is_err());
+
+    let file = libra_temppath::TempPath::new();
+    let path = file.path();
+    keystore.write(&path).unwrap();
+    let read = Keystore::read(&path).unwrap();
+    assert_eq!(read, keystore);
+    assert_eq!(read.decrypt("password").unwrap(), b"secret".to_vec());
+
+    // The salt and nonce are random.
+    let other = Keystore::encrypt_with_params(b"secret", "password", 8, 1).unwrap();
+    assert_ne!(other.kdf.salt, keystore.kdf.salt);
+    assert_ne!(other.ciphertext, keystore.ciphertext);
+
+    let mut tampered = keystore;
+    tampered.kdf.time_cost += 1;
+    assert!(tampered.decrypt("password").is_err());
+}
@@ -16,9 +16,18 @@ pub mod io_utils;
 /// Utils for key derivation
 mod key_factory;
 
+/// Utils for password-encrypted keystores
+pub mod keystore;
+
 /// Utils for mnemonic seed
 mod mnemonic;
 
+/// Utils for multisig accounts
+pub mod multisig;
+
+/// Utils for offline signing
+pub mod offline;
+
 /// Utils for wallet library
 mod wallet_library;
 
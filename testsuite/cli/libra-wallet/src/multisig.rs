@@ -0,0 +1,211 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! K-of-N accounts, authenticated by a `MultiEd25519PublicKey` of N keys held by different
+//! wallets. A transaction of such an account is signed by passing a `PartiallySignedTransaction`
+//! around the holders of the keys, each adding its signatures, until K of them are collected and
+//! can be combined into a `TransactionAuthenticator::MultiEd25519`.
+
+use crate::error::WalletError;
+use anyhow::{ensure, Result};
+use libra_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
+    traits::Signature,
+};
+use libra_types::transaction::{
+    authenticator::AuthenticationKey, RawTransaction, SignedTransaction,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// A transaction of a multisig account, with the signatures collected so far.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PartiallySignedTransaction {
+    raw_txn: RawTransaction,
+    public_key: MultiEd25519PublicKey,
+    // Signatures, by index of their key in `public_key`
+    signatures: BTreeMap<u8, Ed25519Signature>,
+}
+
+impl PartiallySignedTransaction {
+    /// Creates a transaction with no signature yet. Fails if `public_key` isn't the one of the
+    /// sender.
+    pub fn new(raw_txn: RawTransaction, public_key: MultiEd25519PublicKey) -> Result<Self> {
+        ensure!(
+            AuthenticationKey::multi_ed25519(&public_key).derived_address() == raw_txn.sender(),
+            "The multisig public key isn't the one of the sender {}",
+            raw_txn.sender()
+        );
+        Ok(Self {
+            raw_txn,
+            public_key,
+            signatures: BTreeMap::new(),
+        })
+    }
+
+    pub fn raw_txn(&self) -> &RawTransaction {
+        &self.raw_txn
+    }
+
+    pub fn public_key(&self) -> &MultiEd25519PublicKey {
+        &self.public_key
+    }
+
+    /// Number of signatures collected so far.
+    pub fn num_signatures(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Whether enough signatures are collected to authenticate the transaction.
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= *self.public_key.threshold() as usize
+    }
+
+    /// Index in the multisig public key of `public_key`, if it is one of its keys.
+    pub fn key_index(&self, public_key: &Ed25519PublicKey) -> Option<u8> {
+        self.public_key
+            .public_keys()
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+
+    /// Whether the key at `index` already signed.
+    pub fn is_signed_by(&self, index: u8) -> bool {
+        self.signatures.contains_key(&index)
+    }
+
+    /// Adds the signature of the key at `index`, after checking it.
+    pub fn add_signature(&mut self, index: u8, signature: Ed25519Signature) -> Result<()> {
+        let public_key = self
+            .public_key
+            .public_keys()
+            .get(index as usize)
+            .ok_or_else(|| {
+                WalletError::LibraWalletGeneric(format!("No multisig key at index {}", index))
+            })?;
+        signature.verify(&self.raw_txn, public_key)?;
+        self.signatures.insert(index, signature);
+        Ok(())
+    }
+
+    /// Combines the signatures into a `SignedTransaction`. Fails if there are not enough of them.
+    pub fn into_signed_transaction(self) -> Result<SignedTransaction> {
+        ensure!(
+            self.is_complete(),
+            "Only {} of the {} signatures required are collected",
+            self.signatures.len(),
+            self.public_key.threshold()
+        );
+        let signature = MultiEd25519Signature::new(
+            self.signatures
+                .into_iter()
+                .map(|(index, signature)| (signature, index))
+                .collect(),
+        )?;
+        Ok(SignedTransaction::new_multisig(
+            self.raw_txn,
+            self.public_key,
+            signature,
+        ))
+    }
+
+    /// Reads a transaction exported by `write`, checking its signatures.
+    pub fn read<P: AsRef<Path>>(path: &P) -> Result<Self> {
+        let bytes = hex::decode(fs::read_to_string(path)?.trim())?;
+        let txn: Self = lcs::from_bytes(&bytes)?;
+        let mut checked = Self::new(txn.raw_txn, txn.public_key)?;
+        for (index, signature) in txn.signatures {
+            checked.add_signature(index, signature)?;
+        }
+        Ok(checked)
+    }
+
+    /// Exports the transaction to a file, as hex-encoded LCS bytes.
+    pub fn write<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        fs::write(path, hex::encode(lcs::to_bytes(self)?))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_multisig_signing() {
+    use crate::{io_utils, WalletLibrary};
+    use libra_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+    use libra_types::{chain_id::ChainId, transaction::Script};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut wallet1 = WalletLibrary::new();
+    let (auth_key1, _) = wallet1.new_address().unwrap();
+    let public_key1 = wallet1
+        .get_public_key(&auth_key1.derived_address())
+        .unwrap();
+    let mut wallet2 = WalletLibrary::new();
+    let (auth_key2, _) = wallet2.new_address().unwrap();
+    let public_key2 = wallet2
+        .get_public_key(&auth_key2.derived_address())
+        .unwrap();
+    let external_key = Ed25519PrivateKey::generate(&mut StdRng::from_seed([0; 32])).public_key();
+
+    // 2-of-3 account, of which each wallet holds a key.
+    let multisig_key =
+        MultiEd25519PublicKey::new(vec![external_key, public_key2, public_key1.clone()], 2)
+            .unwrap();
+    let address = wallet1
+        .add_multisig_account(multisig_key.clone())
+        .derived_address();
+    wallet2.add_multisig_account(multisig_key.clone());
+    let raw_txn = RawTransaction::new_script(
+        address,
+        0,
+        Script::new(vec![], vec![], vec![]),
+        0,
+        0,
+        "LBR".to_string(),
+        0,
+        ChainId::test(),
+    );
+
+    // A single wallet can't sign.
+    assert!(wallet1.sign_txn(raw_txn.clone()).is_err());
+    assert!(PartiallySignedTransaction::new(
+        raw_txn.clone(),
+        MultiEd25519PublicKey::new(vec![public_key1], 1).unwrap()
+    )
+    .is_err());
+
+    let mut txn = PartiallySignedTransaction::new(raw_txn, multisig_key).unwrap();
+    assert_eq!(wallet1.partially_sign_txn(&mut txn).unwrap(), 1);
+    assert_eq!(wallet1.partially_sign_txn(&mut txn).unwrap(), 0);
+    assert!(txn.is_signed_by(2));
+    assert!(!txn.is_complete());
+    assert!(txn.clone().into_signed_transaction().is_err());
+
+    // The transaction is passed to the second wallet through a file.
+    let file = libra_temppath::TempPath::new();
+    let path = file.path();
+    txn.write(&path).unwrap();
+    let mut txn = PartiallySignedTransaction::read(&path).unwrap();
+    assert_eq!(txn.num_signatures(), 1);
+    assert_eq!(wallet2.partially_sign_txn(&mut txn).unwrap(), 1);
+    assert!(txn.is_complete());
+    let signed_txn = txn.into_signed_transaction().unwrap();
+    assert_eq!(
+        signed_txn
+            .authenticator()
+            .authentication_key()
+            .derived_address(),
+        address
+    );
+    signed_txn.check_signature().unwrap();
+
+    // Multisig accounts are recovered along with the wallet.
+    io_utils::write_recovery(&wallet1, &path).unwrap();
+    let recovered = io_utils::recover(&path).unwrap();
+    assert_eq!(
+        recovered.get_multisig_accounts(),
+        wallet1.get_multisig_accounts()
+    );
+}
@@ -0,0 +1,376 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Files to sign transactions on an air-gapped machine.
+//!
+//! An `UnsignedTransactionFile` is built on an online machine, which can look up the sequence
+//! number of the sender. It is carried to the offline machine, reviewed through its summary, and
+//! signed into a `SignedTransactionFile`, which is carried back to be submitted. Both files hold
+//! the hex-encoded LCS bytes of the transaction along with a decoded summary of it, which is
+//! checked against the bytes whenever a file is read, so that what is reviewed is what is signed.
+//!
+//! Reading a transaction out of either file also checks that it is for the expected chain and
+//! isn't expired yet, so that each step of the workflow fails early on a stale or misdirected
+//! transaction.
+
+use anyhow::{ensure, Result};
+use chrono::{TimeZone, Utc};
+use compiled_stdlib::transaction_scripts::StdlibScript;
+use libra_crypto::{
+    ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, SigningKey,
+};
+use libra_types::{
+    chain_id::ChainId,
+    transaction::{
+        RawTransaction, SignedTransaction, Transaction, TransactionArgument, TransactionPayload,
+    },
+};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt, fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Human-readable description of a `RawTransaction`, with its script and arguments decoded.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TransactionSummary {
+    pub sender: String,
+    pub sequence_number: u64,
+    pub chain_id: u8,
+    /// Name of the stdlib script, or hash of the code of any other script
+    pub script: String,
+    /// Type arguments, as `<name>: <type>`
+    pub type_arguments: Vec<String>,
+    /// Arguments, as `<name>: <value>`
+    pub arguments: Vec<String>,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub gas_currency_code: String,
+    pub expiration_timestamp_secs: u64,
+}
+
+impl TransactionSummary {
+    pub fn new(raw_txn: &RawTransaction) -> Self {
+        let (script, type_arguments, arguments) = match raw_txn.payload() {
+            TransactionPayload::Script(script) => {
+                let type_arguments: Vec<_> = script.ty_args().iter().map(format_type_tag).collect();
+                let arguments = format_arguments(script.args());
+                match StdlibScript::try_from(script.code()) {
+                    Ok(stdlib_script) => {
+                        let abi = stdlib_script.abi();
+                        let ty_arg_names: Vec<_> =
+                            abi.ty_args().iter().map(|ty| ty.name()).collect();
+                        let arg_names: Vec<_> = abi.args().iter().map(|arg| arg.name()).collect();
+                        (
+                            stdlib_script.name(),
+                            name_values(&ty_arg_names, &type_arguments),
+                            name_values(&arg_names, &arguments),
+                        )
+                    }
+                    Err(_) => (
+                        format!(
+                            "<unknown script {}>",
+                            HashValue::sha3_256_of(script.code()).to_hex()
+                        ),
+                        name_values(&[], &type_arguments),
+                        name_values(&[], &arguments),
+                    ),
+                }
+            }
+            TransactionPayload::Module(_) => ("<module publishing>".to_string(), vec![], vec![]),
+            TransactionPayload::WriteSet(_) => ("<write set>".to_string(), vec![], vec![]),
+        };
+        Self {
+            sender: raw_txn.sender().to_string(),
+            sequence_number: raw_txn.sequence_number(),
+            chain_id: raw_txn.chain_id().id(),
+            script,
+            type_arguments,
+            arguments,
+            max_gas_amount: raw_txn.max_gas_amount(),
+            gas_unit_price: raw_txn.gas_unit_price(),
+            gas_currency_code: raw_txn.gas_currency_code().to_string(),
+            expiration_timestamp_secs: raw_txn.expiration_timestamp_secs(),
+        }
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Script: {}", self.script)?;
+        for type_argument in &self.type_arguments {
+            writeln!(f, "  type argument {}", type_argument)?;
+        }
+        for argument in &self.arguments {
+            writeln!(f, "  argument {}", argument)?;
+        }
+        writeln!(f, "Sender: {}", self.sender)?;
+        writeln!(f, "Sequence number: {}", self.sequence_number)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(
+            f,
+            "Gas: at most {} units at {} {} each",
+            self.max_gas_amount, self.gas_unit_price, self.gas_currency_code
+        )?;
+        match Utc
+            .timestamp_opt(self.expiration_timestamp_secs as i64, 0)
+            .single()
+        {
+            Some(expiration) if self.expiration_timestamp_secs <= i64::max_value() as u64 => {
+                write!(
+                    f,
+                    "Expiration: {} ({})",
+                    expiration.to_rfc3339(),
+                    self.expiration_timestamp_secs
+                )
+            }
+            _ => write!(f, "Expiration: {}", self.expiration_timestamp_secs),
+        }
+    }
+}
+
+/// Pairs values with their names, or with their index if they aren't named.
+fn name_values(names: &[&str], values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| match names.get(index) {
+            Some(name) => format!("{}: {}", name, value),
+            None => format!("{}: {}", index, value),
+        })
+        .collect()
+}
+
+/// Formats a type with the full addresses of structs, as their short form is ambiguous.
+fn format_type_tag(type_tag: &TypeTag) -> String {
+    match type_tag {
+        TypeTag::Struct(struct_tag) => {
+            let mut formatted = format!(
+                "{}::{}::{}",
+                struct_tag.address, struct_tag.module, struct_tag.name
+            );
+            if !struct_tag.type_params.is_empty() {
+                let type_params: Vec<_> =
+                    struct_tag.type_params.iter().map(format_type_tag).collect();
+                formatted.push_str(&format!("<{}>", type_params.join(", ")));
+            }
+            formatted
+        }
+        TypeTag::Vector(type_tag) => format!("Vector<{}>", format_type_tag(type_tag)),
+        type_tag => type_tag.to_string(),
+    }
+}
+
+fn format_arguments(args: &[TransactionArgument]) -> Vec<String> {
+    args.iter()
+        .map(|arg| match arg {
+            TransactionArgument::U8(value) => value.to_string(),
+            TransactionArgument::U64(value) => value.to_string(),
+            TransactionArgument::U128(value) => value.to_string(),
+            TransactionArgument::Bool(value) => value.to_string(),
+            TransactionArgument::Address(address) => address.to_string(),
+            TransactionArgument::U8Vector(bytes) => format!("0x{}", hex::encode(bytes)),
+        })
+        .collect()
+}
+
+/// Seconds since the Unix epoch.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the Unix epoch")
+        .as_secs()
+}
+
+/// Checks that `raw_txn` is for the chain `chain_id`, and isn't expired at `now_secs`.
+pub fn validate(raw_txn: &RawTransaction, chain_id: ChainId, now_secs: u64) -> Result<()> {
+    ensure!(
+        raw_txn.chain_id() == chain_id,
+        "The transaction is for chain ID {}, not {}",
+        raw_txn.chain_id().id(),
+        chain_id.id()
+    );
+    ensure!(
+        raw_txn.expiration_timestamp_secs() > now_secs,
+        "The transaction expired {} seconds ago",
+        now_secs - raw_txn.expiration_timestamp_secs()
+    );
+    Ok(())
+}
+
+/// Signs `raw_txn` with `private_key`, for a sender authenticated by a single Ed25519 key.
+pub fn sign_with_key(
+    raw_txn: RawTransaction,
+    private_key: &Ed25519PrivateKey,
+) -> SignedTransaction {
+    let signature = private_key.sign(&raw_txn);
+    SignedTransaction::new(raw_txn, private_key.public_key(), signature)
+}
+
+fn check_summary(summary: &TransactionSummary, raw_txn: &RawTransaction) -> Result<()> {
+    ensure!(
+        summary == &TransactionSummary::new(raw_txn),
+        "The summary doesn't match the transaction"
+    );
+    Ok(())
+}
+
+/// A transaction to be signed offline.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UnsignedTransactionFile {
+    /// Hex-encoded LCS bytes of the `RawTransaction`
+    raw_txn: String,
+    summary: TransactionSummary,
+}
+
+impl UnsignedTransactionFile {
+    pub fn new(raw_txn: &RawTransaction) -> Result<Self> {
+        Ok(Self {
+            raw_txn: hex::encode(lcs::to_bytes(raw_txn)?),
+            summary: TransactionSummary::new(raw_txn),
+        })
+    }
+
+    pub fn summary(&self) -> &TransactionSummary {
+        &self.summary
+    }
+
+    /// Decodes the transaction, after checking it against the summary, `chain_id` and the current
+    /// time.
+    pub fn raw_txn(&self, chain_id: ChainId) -> Result<RawTransaction> {
+        let raw_txn: RawTransaction = lcs::from_bytes(&hex::decode(&self.raw_txn)?)?;
+        check_summary(&self.summary, &raw_txn)?;
+        validate(&raw_txn, chain_id, now_secs())?;
+        Ok(raw_txn)
+    }
+
+    /// Reads the transaction from a JSON file.
+    pub fn read<P: AsRef<Path>>(path: &P) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Writes the transaction to a JSON file.
+    pub fn write<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A transaction signed offline, to be submitted.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SignedTransactionFile {
+    /// Hex-encoded LCS bytes of the `SignedTransaction`
+    signed_txn: String,
+    /// Hash of the transaction once committed, to look it up
+    txn_hash: String,
+    summary: TransactionSummary,
+}
+
+impl SignedTransactionFile {
+    pub fn new(signed_txn: &SignedTransaction) -> Result<Self> {
+        let txn_hash = Transaction::UserTransaction(signed_txn.clone()).hash();
+        Ok(Self {
+            signed_txn: hex::encode(lcs::to_bytes(signed_txn)?),
+            txn_hash: txn_hash.to_hex(),
+            summary: TransactionSummary::new(&signed_txn.clone().into_raw_transaction()),
+        })
+    }
+
+    pub fn summary(&self) -> &TransactionSummary {
+        &self.summary
+    }
+
+    pub fn txn_hash(&self) -> &str {
+        &self.txn_hash
+    }
+
+    /// Decodes the transaction, after checking its signature, and checking it against the
+    /// summary, `chain_id` and the current time.
+    pub fn signed_txn(&self, chain_id: ChainId) -> Result<SignedTransaction> {
+        let signed_txn: SignedTransaction = lcs::from_bytes(&hex::decode(&self.signed_txn)?)?;
+        let signed_txn = signed_txn.check_signature()?.into_inner();
+        let raw_txn = signed_txn.clone().into_raw_transaction();
+        check_summary(&self.summary, &raw_txn)?;
+        validate(&raw_txn, chain_id, now_secs())?;
+        Ok(signed_txn)
+    }
+
+    /// Reads the transaction from a JSON file.
+    pub fn read<P: AsRef<Path>>(path: &P) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Writes the transaction to a JSON file.
+    pub fn write<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_offline_signing() {
+    use crate::WalletLibrary;
+    use libra_types::{
+        account_address::AccountAddress,
+        account_config::{coin1_tag, COIN1_NAME},
+        transaction::helpers::create_unsigned_txn,
+    };
+
+    let mut wallet = WalletLibrary::new();
+    let (auth_key, _) = wallet.new_address().unwrap();
+    let sender = auth_key.derived_address();
+    let receiver = AccountAddress::random();
+    let script = transaction_builder::encode_peer_to_peer_with_metadata_script(
+        coin1_tag(),
+        receiver,
+        100,
+        vec![],
+        vec![],
+    );
+    let raw_txn = create_unsigned_txn(
+        TransactionPayload::Script(script),
+        sender,
+        7,
+        1_000_000,
+        0,
+        COIN1_NAME.to_string(),
+        100,
+        ChainId::test(),
+    );
+
+    let unsigned = UnsignedTransactionFile::new(&raw_txn).unwrap();
+    let summary = unsigned.summary();
+    assert_eq!(summary.script, "peer_to_peer_with_metadata");
+    assert_eq!(summary.sequence_number, 7);
+    assert!(summary.arguments.contains(&format!("payee: {}", receiver)));
+    assert!(summary.arguments.contains(&"amount: 100".to_string()));
+    assert!(summary.type_arguments[0].starts_with("currency: "));
+
+    // The unsigned transaction goes through a file to the offline machine.
+    let file = libra_temppath::TempPath::new();
+    let path = file.path();
+    unsigned.write(&path).unwrap();
+    let unsigned = UnsignedTransactionFile::read(&path).unwrap();
+    assert!(unsigned.raw_txn(ChainId::new(42)).is_err());
+    let mut tampered = unsigned.clone();
+    tampered.summary.arguments.reverse();
+    assert!(tampered.raw_txn(ChainId::test()).is_err());
+    assert_eq!(unsigned.raw_txn(ChainId::test()).unwrap(), raw_txn);
+
+    // And back, signed.
+    let signed = SignedTransactionFile::new(&wallet.sign_txn(raw_txn.clone()).unwrap()).unwrap();
+    assert_eq!(signed.summary(), unsigned.summary());
+    signed.write(&path).unwrap();
+    let signed = SignedTransactionFile::read(&path).unwrap();
+    let signed_txn = signed.signed_txn(ChainId::test()).unwrap();
+    assert_eq!(signed_txn.sender(), sender);
+    assert!(signed.signed_txn(ChainId::new(42)).is_err());
+
+    // Expired transactions are rejected at every step.
+    assert!(validate(&raw_txn, ChainId::test(), now_secs()).is_ok());
+    assert!(validate(&raw_txn, ChainId::test(), now_secs() + 1000).is_err());
+}
@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The following document is a minimalist version of Libra Wallet. Note that this Wallet does
-//! not promote security as the mnemonic is stored in unencrypted form. In future iterations,
-//! we will be releasing more robust Wallet implementations. It is our intention to present a
+//! not promote security as the mnemonic is stored in unencrypted form, unless it is written to a
+//! password-encrypted keystore. In future iterations, we will be releasing more robust Wallet
+//! implementations. It is our intention to present a
 //! foundation that is simple to understand and incrementally improve the LibraWallet
 //! implementation and it's security guarantees throughout testnet. For a more robust wallet
 //! reference, the authors suggest to audit the file of the same name in the rust-wallet crate.
@@ -16,8 +17,10 @@ use crate::{
     io_utils,
     key_factory::{ChildNumber, KeyFactory, Seed},
     mnemonic::Mnemonic,
+    multisig::PartiallySignedTransaction,
 };
 use anyhow::Result;
+use libra_crypto::{ed25519::Ed25519PublicKey, multi_ed25519::MultiEd25519PublicKey};
 use libra_types::{
     account_address::AccountAddress,
     transaction::{
@@ -34,6 +37,7 @@ pub struct WalletLibrary {
     key_factory: KeyFactory,
     addr_map: HashMap<AccountAddress, ChildNumber>,
     key_leaf: ChildNumber,
+    multisig_accounts: HashMap<AccountAddress, MultiEd25519PublicKey>,
 }
 
 impl WalletLibrary {
@@ -55,6 +59,7 @@ impl WalletLibrary {
             key_factory: KeyFactory::new(&seed).unwrap(),
             addr_map: HashMap::new(),
             key_leaf: ChildNumber(0),
+            multisig_accounts: HashMap::new(),
         }
     }
 
@@ -77,6 +82,16 @@ impl WalletLibrary {
         io_utils::recover(&input_file_path)
     }
 
+    /// Function that writes the wallet recovery data to a keystore file encrypted under password
+    pub fn write_keystore(&self, output_file_path: &Path, password: &str) -> Result<()> {
+        io_utils::write_keystore(&self, &output_file_path, password)
+    }
+
+    /// Recover wallet from the keystore at input_file_path, encrypted under password
+    pub fn recover_from_keystore(input_file_path: &Path, password: &str) -> Result<WalletLibrary> {
+        io_utils::recover_from_keystore(&input_file_path, password)
+    }
+
     /// Get the current ChildNumber in u64 format
     pub fn key_leaf(&self) -> u64 {
         self.key_leaf.0
@@ -154,10 +169,65 @@ impl WalletLibrary {
         Ok(ret)
     }
 
+    /// Returns the PublicKey of an AccountAddress in the addr_map, e.g. to share it with the
+    /// co-signers of a multisig account
+    pub fn get_public_key(&self, address: &AccountAddress) -> Result<Ed25519PublicKey> {
+        match self.addr_map.get(address) {
+            Some(child) => Ok(self.key_factory.private_child(*child)?.get_public()),
+            None => Err(WalletError::LibraWalletGeneric(format!(
+                "Address {} is not in the wallet",
+                address
+            ))
+            .into()),
+        }
+    }
+
+    /// Adds a K-of-N multisig account, of which some of the keys may be held by this wallet, and
+    /// returns its AuthenticationKey
+    pub fn add_multisig_account(&mut self, public_key: MultiEd25519PublicKey) -> AuthenticationKey {
+        let authentication_key = AuthenticationKey::multi_ed25519(&public_key);
+        self.multisig_accounts
+            .insert(authentication_key.derived_address(), public_key);
+        authentication_key
+    }
+
+    /// Returns the public keys of all multisig accounts added to this wallet
+    pub fn get_multisig_accounts(&self) -> Vec<&MultiEd25519PublicKey> {
+        self.multisig_accounts.values().collect()
+    }
+
+    /// Returns the public key of a multisig account added to this wallet, if any
+    pub fn get_multisig_account(&self, address: &AccountAddress) -> Option<&MultiEd25519PublicKey> {
+        self.multisig_accounts.get(address)
+    }
+
+    /// Adds to a multisig transaction the signatures of all keys of the multisig account held by
+    /// this wallet which didn't sign yet, and returns how many were added
+    pub fn partially_sign_txn(&self, txn: &mut PartiallySignedTransaction) -> Result<usize> {
+        let mut num_signed = 0;
+        for child in self.addr_map.values() {
+            let child_key = self.key_factory.private_child(*child)?;
+            if let Some(index) = txn.key_index(&child_key.get_public()) {
+                if !txn.is_signed_by(index) {
+                    txn.add_signature(index, child_key.sign(txn.raw_txn()))?;
+                    num_signed += 1;
+                }
+            }
+        }
+        Ok(num_signed)
+    }
+
     /// Simple public function that allows to sign a Libra RawTransaction with the PrivateKey
     /// associated to a particular AccountAddress. If the PrivateKey associated to an
-    /// AccountAddress is not contained in the addr_map, then this function will return an Error
+    /// AccountAddress is not contained in the addr_map, then this function will return an Error,
+    /// unless the AccountAddress is the one of a multisig account of which this wallet holds
+    /// enough keys to reach the threshold
     pub fn sign_txn(&self, txn: RawTransaction) -> Result<SignedTransaction> {
+        if let Some(public_key) = self.multisig_accounts.get(&txn.sender()) {
+            let mut partial_txn = PartiallySignedTransaction::new(txn, public_key.clone())?;
+            self.partially_sign_txn(&mut partial_txn)?;
+            return partial_txn.into_signed_transaction();
+        }
         if let Some(child) = self.addr_map.get(&txn.sender()) {
             let child_key = self.key_factory.private_child(*child)?;
             let signature = child_key.sign(&txn);
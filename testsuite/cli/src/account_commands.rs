@@ -22,6 +22,13 @@ impl Command for AccountCommand {
             Box::new(AccountCommandListAccounts {}),
             Box::new(AccountCommandRecoverWallet {}),
             Box::new(AccountCommandWriteRecovery {}),
+            Box::new(AccountCommandRecoverKeystore {}),
+            Box::new(AccountCommandWriteKeystore {}),
+            Box::new(AccountCommandPublicKey {}),
+            Box::new(AccountCommandCreateMultisig {}),
+            Box::new(AccountCommandMultisigTransfer {}),
+            Box::new(AccountCommandSignMultisig {}),
+            Box::new(AccountCommandSubmitMultisig {}),
             Box::new(AccountCommandMint {}),
             Box::new(AccountCommandAddCurrency {}),
         ];
@@ -105,6 +112,182 @@ impl Command for AccountCommandWriteRecovery {
     }
 }
 
+/// Sub command to recover wallet from the password-encrypted keystore specified.
+pub struct AccountCommandRecoverKeystore {}
+
+impl Command for AccountCommandRecoverKeystore {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["recover_keystore", "rk"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Recover Libra wallet from the password-encrypted keystore at the file path"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Recovering Wallet from keystore");
+        match client.recover_wallet_accounts_from_keystore(&params) {
+            Ok(account_data) => {
+                println!(
+                    "Wallet recovered and {} accounts were restored",
+                    account_data.len()
+                );
+                for data in account_data {
+                    println!("#{} address {}", data.index, hex::encode(data.address));
+                }
+            }
+            Err(e) => report_error("Error recovering Libra wallet from keystore", e),
+        }
+    }
+}
+
+/// Sub command to backup wallet to a password-encrypted keystore.
+pub struct AccountCommandWriteKeystore {}
+
+impl Command for AccountCommandWriteKeystore {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["write_keystore", "wk"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Save Libra wallet mnemonic recovery seed to disk, encrypted under a password"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Saving Libra wallet mnemonic recovery seed to keystore");
+        match client.write_keystore(&params) {
+            Ok(_) => println!("Saved encrypted mnemonic seed to disk"),
+            Err(e) => report_error("Error writing keystore to file", e),
+        }
+    }
+}
+
+/// Sub command to print the public key of an account, to share with co-signers of a multisig
+/// account.
+pub struct AccountCommandPublicKey {}
+
+impl Command for AccountCommandPublicKey {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["public_key", "pk"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Print the public key of a wallet account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.get_public_key(&params) {
+            Ok(public_key) => println!("Public key: {}", public_key),
+            Err(e) => report_error("Error getting public key", e),
+        }
+    }
+}
+
+/// Sub command to add a K-of-N multisig account to the wallet.
+pub struct AccountCommandCreateMultisig {}
+
+impl Command for AccountCommandCreateMultisig {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["create_multisig", "cm"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<threshold> <public_key> <public_key> ..."
+    }
+    fn get_description(&self) -> &'static str {
+        "Create a multisig account whose transactions must be signed by threshold of the public \
+         keys. Returns reference ID to use in other operations"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Creating multisig account");
+        match client.create_multisig_account(&params) {
+            Ok(account_data) => println!(
+                "Created multisig account #{} address {}",
+                account_data.index,
+                hex::encode(account_data.address)
+            ),
+            Err(e) => report_error("Error creating multisig account", e),
+        }
+    }
+}
+
+/// Sub command to create a transfer from a multisig account, partially signed by the wallet.
+pub struct AccountCommandMultisigTransfer {}
+
+impl Command for AccountCommandMultisigTransfer {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig_transfer", "mt"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_ref_id>|<sender_account_address> \
+         <receiver_account_ref_id>|<receiver_account_address> <number_of_coins> <currency_code> \
+         <file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Create a transfer from a multisig account, sign it with the keys of the wallet and \
+         export it to the file path, to be signed by the other key holders"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Creating multisig transfer");
+        match client.multisig_transfer(&params) {
+            Ok(num_signatures) => println!(
+                "Exported transfer with {} signature(s) to {}",
+                num_signatures, params[5]
+            ),
+            Err(e) => report_error("Error creating multisig transfer", e),
+        }
+    }
+}
+
+/// Sub command to add the signatures of the wallet to a multisig transaction.
+pub struct AccountCommandSignMultisig {}
+
+impl Command for AccountCommandSignMultisig {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sign_multisig", "sm"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Sign the multisig transaction at the file path with the keys of the wallet"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Signing multisig transaction");
+        match client.sign_multisig(&params) {
+            Ok((num_signatures, threshold)) => println!(
+                "Signed: {} of the {} signature(s) required are collected",
+                num_signatures, threshold
+            ),
+            Err(e) => report_error("Error signing multisig transaction", e),
+        }
+    }
+}
+
+/// Sub command to submit a multisig transaction with enough signatures.
+pub struct AccountCommandSubmitMultisig {}
+
+impl Command for AccountCommandSubmitMultisig {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["submit_multisig", "subm"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Combine the signatures of the multisig transaction at the file path and submit it"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Submitting multisig transaction");
+        match client.submit_multisig(&params) {
+            Ok(_) => println!("Finished multisig transaction!"),
+            Err(e) => report_error("Error submitting multisig transaction", e),
+        }
+    }
+}
+
 /// Sub command to list all accounts information.
 pub struct AccountCommandListAccounts {}
 
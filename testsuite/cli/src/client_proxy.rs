@@ -10,6 +10,7 @@ use anyhow::{bail, ensure, format_err, Error, Result};
 use compiled_stdlib::{transaction_scripts::StdlibScript, StdLibOptions};
 use libra_crypto::{
     ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    multi_ed25519::MultiEd25519PublicKey,
     test_utils::KeyPair,
     traits::ValidCryptoMaterial,
     x25519, ValidCryptoMaterialStringExt,
@@ -44,7 +45,12 @@ use libra_types::{
     },
     waypoint::Waypoint,
 };
-use libra_wallet::{io_utils, WalletLibrary};
+use libra_wallet::{
+    io_utils,
+    multisig::PartiallySignedTransaction,
+    offline::{self, SignedTransactionFile, TransactionSummary, UnsignedTransactionFile},
+    WalletLibrary,
+};
 use num_traits::{
     cast::{FromPrimitive, ToPrimitive},
     identities::Zero,
@@ -56,12 +62,13 @@ use std::{
     collections::HashMap,
     convert::TryFrom,
     fmt, fs,
-    io::{stdout, Write},
+    io::{stdin, stdout, Write},
     path::{Path, PathBuf},
     process::Command,
     str::{self, FromStr},
     thread, time,
 };
+use termion::input::TermRead;
 use transaction_builder::encode_set_validator_config_script;
 
 const CLIENT_WALLET_MNEMONIC_FILE: &str = "client.mnemonic";
@@ -1247,6 +1254,251 @@ impl ClientProxy {
         self.recover_accounts_in_wallet()
     }
 
+    /// Write the wallet recovery seed to the keystore file specified, encrypted under a password
+    /// read from the terminal.
+    pub fn write_keystore(&self, space_delim_strings: &[&str]) -> Result<()> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for writing keystore"
+        );
+        let password = read_password("Password: ")?;
+        let confirmation = read_password("Confirm password: ")?;
+        ensure!(password == confirmation, "Passwords don't match");
+        self.wallet
+            .write_keystore(&Path::new(space_delim_strings[1]), &password)
+    }
+
+    /// Recover wallet accounts from command 'recover_keystore <file>', with a password read from
+    /// the terminal, and return vec<(account_address, index)>.
+    pub fn recover_wallet_accounts_from_keystore(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<Vec<AddressAndIndex>> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for recovering wallets from keystore"
+        );
+        let password = read_password("Password: ")?;
+        let wallet =
+            WalletLibrary::recover_from_keystore(&Path::new(space_delim_strings[1]), &password)?;
+        self.set_wallet(wallet);
+        self.recover_accounts_in_wallet()
+    }
+
+    /// Get the public key of a wallet account, to share with the co-signers of a multisig account.
+    pub fn get_public_key(&self, space_delim_strings: &[&str]) -> Result<Ed25519PublicKey> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for getting public key"
+        );
+        let (address, _) = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        self.wallet.get_public_key(&address)
+    }
+
+    /// Add a multisig account from command 'create_multisig <threshold> <public_key>...' and
+    /// return its address and index.
+    pub fn create_multisig_account(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<AddressAndIndex> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments for creating multisig account"
+        );
+        let threshold = space_delim_strings[1].parse::<u8>().map_err(|error| {
+            format_parse_data_error(
+                "threshold",
+                InputType::UnsignedInt,
+                space_delim_strings[1],
+                error,
+            )
+        })?;
+        let public_keys = space_delim_strings[2..]
+            .iter()
+            .map(|key| Ed25519PublicKey::from_encoded_string(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let public_key = MultiEd25519PublicKey::new(public_keys, threshold)?;
+        let auth_key = self.wallet.add_multisig_account(public_key);
+        let account_data = Self::get_account_data_from_address(
+            &mut self.client,
+            auth_key.derived_address(),
+            true,
+            None,
+            Some(auth_key.to_vec()),
+        )?;
+        Ok(self.insert_account_data(account_data))
+    }
+
+    /// Create a transfer from a multisig account with command
+    /// 'multisig_transfer <sender> <receiver> <num_coins> <currency_code> <file>', sign it with
+    /// the keys of this wallet and export it to the file, to be signed by the other key holders.
+    /// Returns the number of signatures collected.
+    pub fn multisig_transfer(&mut self, space_delim_strings: &[&str]) -> Result<usize> {
+        ensure!(
+            space_delim_strings.len() == 6,
+            "Invalid number of arguments for multisig transfer"
+        );
+        let (sender_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let public_key = self
+            .wallet
+            .get_multisig_account(&sender_address)
+            .ok_or_else(|| {
+                format_err!("{} is not a multisig account of the wallet", sender_address)
+            })?
+            .clone();
+        let (receiver_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let currency = space_delim_strings[4];
+        let num_coins = self.convert_to_on_chain_represenation(space_delim_strings[3], currency)?;
+        let sequence_number = self
+            .get_account_resource_and_update(sender_address)?
+            .sequence_number;
+        let raw_txn = self.prepare_transfer_coins(
+            sender_address,
+            sequence_number,
+            receiver_address,
+            num_coins,
+            currency.to_owned(),
+            None,
+            None,
+            Some(currency.to_owned()),
+        )?;
+        let mut txn = PartiallySignedTransaction::new(raw_txn, public_key)?;
+        self.wallet.partially_sign_txn(&mut txn)?;
+        txn.write(&Path::new(space_delim_strings[5]))?;
+        Ok(txn.num_signatures())
+    }
+
+    /// Add the signatures of the keys of this wallet to the multisig transaction in the file
+    /// specified with command 'sign_multisig <file>'. Returns the number of signatures collected,
+    /// and the number required.
+    pub fn sign_multisig(&self, space_delim_strings: &[&str]) -> Result<(usize, u8)> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for signing multisig transaction"
+        );
+        let path = Path::new(space_delim_strings[1]);
+        let mut txn = PartiallySignedTransaction::read(&path)?;
+        ensure!(
+            self.wallet.partially_sign_txn(&mut txn)? > 0,
+            "The wallet holds no key of the multisig account which didn't sign yet"
+        );
+        txn.write(&path)?;
+        Ok((txn.num_signatures(), *txn.public_key().threshold()))
+    }
+
+    /// Combine the signatures of the multisig transaction in the file specified with command
+    /// 'submit_multisig <file>' and submit it.
+    pub fn submit_multisig(&mut self, space_delim_strings: &[&str]) -> Result<()> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for submitting multisig transaction"
+        );
+        let txn = PartiallySignedTransaction::read(&Path::new(space_delim_strings[1]))?
+            .into_signed_transaction()?;
+        let sender_address = txn.sender();
+        let sender_sequence = txn.sequence_number();
+        let sender = match self.address_to_ref_id.get(&sender_address) {
+            Some(ref_id) => self.accounts.get_mut(*ref_id),
+            None => None,
+        };
+        self.client.submit_transaction(sender, txn)?;
+        self.wait_for_transaction(sender_address, sender_sequence + 1)
+    }
+
+    /// Create a transfer with command 'build_transfer <sender> <receiver> <num_coins>
+    /// <currency_code> <expiration_in_secs> <file>', with the sequence number of the sender looked
+    /// up on chain, and export it unsigned to the file, to be signed offline. Returns its summary.
+    pub fn build_offline_transfer(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<TransactionSummary> {
+        ensure!(
+            space_delim_strings.len() == 7,
+            "Invalid number of arguments for building offline transfer"
+        );
+        let (sender_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let (receiver_address, _) =
+            self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let currency = space_delim_strings[4];
+        let num_coins = self.convert_to_on_chain_represenation(space_delim_strings[3], currency)?;
+        let expiration_secs = space_delim_strings[5].parse::<u64>().map_err(|error| {
+            format_parse_data_error(
+                "expiration_in_secs",
+                InputType::UnsignedInt,
+                space_delim_strings[5],
+                error,
+            )
+        })?;
+        let currency_code = from_currency_code_string(currency)
+            .map_err(|_| format_err!("Invalid currency code {} specified", currency))?;
+        let sequence_number = self
+            .get_account_resource_and_update(sender_address)?
+            .sequence_number;
+        let raw_txn = create_unsigned_txn(
+            TransactionPayload::Script(
+                transaction_builder::encode_peer_to_peer_with_metadata_script(
+                    type_tag_for_currency_code(currency_code),
+                    receiver_address,
+                    num_coins,
+                    vec![],
+                    vec![],
+                ),
+            ),
+            sender_address,
+            sequence_number,
+            MAX_GAS_AMOUNT,
+            GAS_UNIT_PRICE,
+            currency.to_owned(),
+            expiration_secs as i64,
+            self.chain_id,
+        );
+        offline::validate(&raw_txn, self.chain_id, offline::now_secs())?;
+        let file = UnsignedTransactionFile::new(&raw_txn)?;
+        file.write(&Path::new(space_delim_strings[6]))?;
+        Ok(file.summary().clone())
+    }
+
+    /// Sign the unsigned transaction in the first file with the wallet, with command
+    /// 'sign <unsigned_file> <signed_file>', and export it to the second file. Returns its
+    /// summary.
+    pub fn sign_offline_transaction(
+        &self,
+        space_delim_strings: &[&str],
+    ) -> Result<TransactionSummary> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments for signing offline transaction"
+        );
+        let raw_txn = UnsignedTransactionFile::read(&Path::new(space_delim_strings[1]))?
+            .raw_txn(self.chain_id)?;
+        let file = SignedTransactionFile::new(&self.wallet.sign_txn(raw_txn)?)?;
+        file.write(&Path::new(space_delim_strings[2]))?;
+        Ok(file.summary().clone())
+    }
+
+    /// Submit the transaction signed offline in the file, with command 'submit <signed_file>'.
+    /// Returns its hash.
+    pub fn submit_offline_transaction(&mut self, space_delim_strings: &[&str]) -> Result<String> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for submitting offline transaction"
+        );
+        let file = SignedTransactionFile::read(&Path::new(space_delim_strings[1]))?;
+        let txn = file.signed_txn(self.chain_id)?;
+        let sender_address = txn.sender();
+        let sender_sequence = txn.sequence_number();
+        let sender = match self.address_to_ref_id.get(&sender_address) {
+            Some(ref_id) => self.accounts.get_mut(*ref_id),
+            None => None,
+        };
+        self.client.submit_transaction(sender, txn)?;
+        self.wait_for_transaction(sender_address, sender_sequence + 1)?;
+        Ok(file.txn_hash().to_string())
+    }
+
     /// Recover accounts in wallets and sync state if sync_on_wallet_recovery is true.
     pub fn recover_accounts_in_wallet(&mut self) -> Result<Vec<AddressAndIndex>> {
         let wallet_addresses = self.wallet.get_addresses()?;
@@ -1260,6 +1512,21 @@ impl ClientProxy {
                 None,
             )?);
         }
+        let multisig_auth_keys: Vec<_> = self
+            .wallet
+            .get_multisig_accounts()
+            .into_iter()
+            .map(AuthenticationKey::multi_ed25519)
+            .collect();
+        for auth_key in multisig_auth_keys {
+            account_data.push(Self::get_account_data_from_address(
+                &mut self.client,
+                auth_key.derived_address(),
+                self.sync_on_wallet_recovery,
+                None,
+                Some(auth_key.to_vec()),
+            )?);
+        }
         // Clear current cached AccountData as we always swap the entire wallet completely.
         Ok(self.set_accounts(account_data))
     }
@@ -1660,6 +1927,17 @@ fn parse_bool(para: &str) -> Result<bool> {
     Ok(para.to_lowercase().parse::<bool>()?)
 }
 
+/// Read a password from the terminal without echoing it.
+fn read_password(prompt: &str) -> Result<String> {
+    print!("{}", prompt);
+    stdout().flush()?;
+    let password = stdin()
+        .read_passwd(&mut stdout())?
+        .ok_or_else(|| format_err!("No password entered"))?;
+    println!();
+    Ok(password)
+}
+
 impl fmt::Display for AccountEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -3,7 +3,8 @@
 
 use crate::{
     account_commands::AccountCommand, client_proxy::ClientProxy, dev_commands::DevCommand,
-    query_commands::QueryCommand, transfer_commands::TransferCommand,
+    offline_commands::OfflineCommand, query_commands::QueryCommand,
+    transfer_commands::TransferCommand,
 };
 use anyhow::Error;
 use libra_metrics::counters::*;
@@ -48,6 +49,7 @@ pub fn get_commands(
         Arc::new(AccountCommand {}),
         Arc::new(QueryCommand {}),
         Arc::new(TransferCommand {}),
+        Arc::new(OfflineCommand {}),
     ];
     if include_dev {
         commands.push(Arc::new(DevCommand {}));
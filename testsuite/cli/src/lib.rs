@@ -25,6 +25,7 @@ pub mod commands;
 mod dev_commands;
 /// Client wrapper to connect to validator.
 mod libra_client;
+mod offline_commands;
 mod query_commands;
 mod transfer_commands;
 
@@ -0,0 +1,98 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    client_proxy::ClientProxy,
+    commands::{report_error, subcommand_execute, Command},
+};
+
+/// Major command to sign transactions on an offline machine.
+pub struct OfflineCommand {}
+
+impl Command for OfflineCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["offline", "o"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Offline signing of transactions"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        let commands: Vec<Box<dyn Command>> = vec![
+            Box::new(OfflineCommandBuildTransfer {}),
+            Box::new(OfflineCommandSign {}),
+            Box::new(OfflineCommandSubmit {}),
+        ];
+
+        subcommand_execute(&params[0], commands, client, &params[1..]);
+    }
+}
+
+/// Sub command to export an unsigned transfer, to be signed offline.
+pub struct OfflineCommandBuildTransfer {}
+
+impl Command for OfflineCommandBuildTransfer {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["build_transfer", "bt"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<sender_account_address>|<sender_account_ref_id> \
+         <receiver_account_address>|<receiver_account_ref_id> <number_of_coins> <currency_code> \
+         <expiration_in_secs> <file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Create a transfer with the current sequence number of the sender, expiring after the \
+         given number of seconds, and export it unsigned to the file path"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Exporting unsigned transfer");
+        match client.build_offline_transfer(&params) {
+            Ok(summary) => println!("Exported to {}:\n{}", params[6], summary),
+            Err(e) => report_error("Error exporting unsigned transfer", e),
+        }
+    }
+}
+
+/// Sub command to sign an unsigned transaction with the wallet.
+pub struct OfflineCommandSign {}
+
+impl Command for OfflineCommandSign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sign", "s"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<unsigned_file_path> <signed_file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Sign the unsigned transaction at the first file path with the wallet, and export it to \
+         the second file path. Doesn't require a connection to the network"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Signing transaction");
+        match client.sign_offline_transaction(&params) {
+            Ok(summary) => println!("Signed and exported to {}:\n{}", params[2], summary),
+            Err(e) => report_error("Error signing transaction", e),
+        }
+    }
+}
+
+/// Sub command to submit a transaction signed offline.
+pub struct OfflineCommandSubmit {}
+
+impl Command for OfflineCommandSubmit {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["submit", "sb"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<signed_file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Submit the signed transaction at the file path"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Submitting signed transaction");
+        match client.submit_offline_transaction(&params) {
+            Ok(txn_hash) => println!("Finished transaction {}!", txn_hash),
+            Err(e) => report_error("Error submitting signed transaction", e),
+        }
+    }
+}
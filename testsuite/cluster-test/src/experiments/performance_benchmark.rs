@@ -204,6 +204,14 @@ impl Experiment for PerformanceBenchmark {
         let avg_txns_per_block = avg_txns_per_block
             .map_err(|e| warn!("Failed to query avg_txns_per_block: {}", e))
             .ok();
+        let avg_batches_per_block = stats::avg_batches_per_block(&context.prometheus, start, end)
+            .map_err(|e| {
+                info!(
+                    "No avg_batches_per_block, batch dissemination is disabled: {}",
+                    e
+                )
+            })
+            .ok();
         info!(
             "Link to dashboard : {}",
             context.prometheus.link_to_dashboard(start, end)
@@ -219,6 +227,11 @@ impl Experiment for PerformanceBenchmark {
                 .report
                 .report_metric(&self, "avg_txns_per_block", avg_txns_per_block);
         }
+        if let Some(avg_batches_per_block) = avg_batches_per_block {
+            context
+                .report
+                .report_metric(&self, "avg_batches_per_block", avg_batches_per_block);
+        }
         context
             .report
             .report_txn_stats(self.to_string(), stats, window);
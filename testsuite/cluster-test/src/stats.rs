@@ -15,3 +15,20 @@ pub fn avg_txns_per_block(prometheus: &Prometheus, start: Duration, end: Duratio
         )
         .map_err(|e| format_err!("No txns_per_block data: {}", e))
 }
+
+/// The average number of batches per block, only reported when consensus runs with
+/// batch dissemination (`--cfg batch_dissemination=true`).
+pub fn avg_batches_per_block(
+    prometheus: &Prometheus,
+    start: Duration,
+    end: Duration,
+) -> Result<f64> {
+    prometheus
+        .query_range_avg(
+            "irate(libra_consensus_num_batches_per_block_sum[1m])/irate(libra_consensus_num_batches_per_block_count[1m])".to_string(),
+            &start,
+            &end,
+            10, /* step */
+        )
+        .map_err(|e| format_err!("No batches_per_block data: {}", e))
+}
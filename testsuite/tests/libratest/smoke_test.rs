@@ -9,6 +9,7 @@ use libra_crypto::{
 };
 use libra_global_constants::{CONSENSUS_KEY, OPERATOR_KEY, VALIDATOR_NETWORK_KEY};
 use libra_json_rpc::views::{ScriptView, TransactionDataView};
+use libra_json_rpc_client::VerifyingClient;
 use libra_key_manager::{
     self,
     libra_interface::{JsonRpcLibraInterface, LibraInterface},
@@ -29,7 +30,7 @@ use libra_trace::trace::trace_node;
 use libra_types::{
     account_address,
     account_address::AccountAddress,
-    account_config::{libra_root_address, testnet_dd_account_address, COIN1_NAME},
+    account_config::{libra_root_address, testnet_dd_account_address, AccountResource, COIN1_NAME},
     chain_id::ChainId,
     ledger_info::LedgerInfo,
     transaction::{
@@ -39,8 +40,10 @@ use libra_types::{
     waypoint::Waypoint,
 };
 use num_traits::cast::FromPrimitive;
+use reqwest::Url;
 use rust_decimal::Decimal;
 use std::{
+    cmp::min,
     collections::BTreeMap,
     convert::{TryFrom, TryInto},
     fs,
@@ -1217,6 +1220,79 @@ fn test_client_waypoints() {
     assert!(client_with_bad_waypoint.test_trusted_connection().is_err());
 }
 
+#[test]
+fn test_verifying_client() {
+    let (env, mut client_proxy) = setup_swarm_and_client_proxy(1, 0);
+    client_proxy.create_next_account(false).unwrap();
+    client_proxy
+        .mint_coins(&["mintb", "0", "10", "Coin1"], true)
+        .unwrap();
+
+    let url = Url::from_str(&format!(
+        "http://localhost:{}/v1",
+        env.validator_swarm.get_client_port(0)
+    ))
+    .unwrap();
+    let waypoint = env.validator_swarm.config.waypoint;
+    let storage = TempPath::new();
+    let mut client =
+        VerifyingClient::new_with_storage(url.clone(), waypoint, storage.path().to_path_buf())
+            .unwrap();
+    assert!(client.latest_ledger_info().is_none());
+
+    // The account that minted has sent events.
+    let blob = client
+        .get_account_state(testnet_dd_account_address())
+        .unwrap()
+        .expect("Missing DD account");
+    let account = AccountResource::try_from(&blob).unwrap();
+    assert!(account.sequence_number() > 0);
+    let events = client
+        .get_events(account.sent_events().key(), 0, 10)
+        .unwrap();
+    assert!(!events.is_empty());
+    for (i, (_version, event)) in events.iter().enumerate() {
+        assert_eq!(event.key(), account.sent_events().key());
+        assert_eq!(event.sequence_number(), i as u64);
+    }
+
+    let txns = client.get_transactions(0, 10, true).unwrap();
+    let latest_version = client.latest_ledger_info().unwrap().ledger_info().version();
+    assert_eq!(txns.transactions.len() as u64, min(10, latest_version + 1));
+    assert!(client
+        .get_transactions(latest_version + 1000, 10, false)
+        .is_ok());
+
+    // The trusted state is restored from storage, and keeps ratcheting from there.
+    let latest_li = client.latest_ledger_info().unwrap().clone();
+    drop(client);
+    let mut client =
+        VerifyingClient::new_with_storage(url.clone(), waypoint, storage.path().to_path_buf())
+            .unwrap();
+    assert_eq!(client.latest_ledger_info(), Some(&latest_li));
+    client_proxy
+        .mint_coins(&["mintb", "0", "10", "Coin1"], true)
+        .unwrap();
+    client.sync().unwrap();
+    assert!(
+        client.latest_ledger_info().unwrap().ledger_info().version()
+            > latest_li.ledger_info().version()
+    );
+
+    // The storage can't be reused with another waypoint, and a bad waypoint can't be ratcheted
+    // from.
+    let bad_waypoint = Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(None)).unwrap();
+    assert!(VerifyingClient::new_with_storage(
+        url.clone(),
+        bad_waypoint,
+        storage.path().to_path_buf()
+    )
+    .is_err());
+    let mut client = VerifyingClient::new(url, bad_waypoint).unwrap();
+    assert!(client.sync().is_err());
+    assert!(client.latest_ledger_info().is_none());
+}
+
 #[test]
 fn test_vfn_failover() {
     // launch environment of 4 validator nodes and 2 full nodes
@@ -2,9 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{epoch_state::EpochState, on_chain_config::ValidatorSet, transaction::Version};
-use libra_crypto::hash::HashValue;
-#[cfg(any(test, feature = "fuzzing"))]
-use libra_crypto::hash::ACCUMULATOR_PLACEHOLDER_HASH;
+use libra_crypto::hash::{HashValue, ACCUMULATOR_PLACEHOLDER_HASH};
 #[cfg(any(test, feature = "fuzzing"))]
 use proptest_derive::Arbitrary;
 use serde::{Deserialize, Serialize};
@@ -157,6 +155,21 @@ impl BlockInfo {
     pub fn version(&self) -> Version {
         self.version
     }
+
+    /// Whether this BlockInfo only certifies the ordering of a block, i.e. it was produced
+    /// before the block was executed and carries the placeholder execution state.
+    pub fn is_ordered_only(&self) -> bool {
+        self.executed_state_id == *ACCUMULATOR_PLACEHOLDER_HASH && self.version == 0
+    }
+
+    /// Whether this ordered-only BlockInfo refers to the same block as the executed one, i.e.
+    /// everything except the execution output matches.
+    pub fn match_ordered_only(&self, executed_block_info: &BlockInfo) -> bool {
+        self.epoch == executed_block_info.epoch
+            && self.round == executed_block_info.round
+            && self.id == executed_block_info.id
+            && self.timestamp_usecs == executed_block_info.timestamp_usecs
+    }
 }
 
 impl Display for BlockInfo {
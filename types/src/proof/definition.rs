@@ -780,9 +780,13 @@ impl<H: CryptoHasher> AccumulatorExtensionProof<H> {
         }
     }
 
+    /// The tree being extended, whose root hash is checked by `verify`.
+    pub fn original_tree(&self) -> anyhow::Result<InMemoryAccumulator<H>> {
+        InMemoryAccumulator::<H>::new(self.frozen_subtree_roots.clone(), self.num_leaves)
+    }
+
     pub fn verify(&self, original_root: HashValue) -> anyhow::Result<InMemoryAccumulator<H>> {
-        let original_tree =
-            InMemoryAccumulator::<H>::new(self.frozen_subtree_roots.clone(), self.num_leaves)?;
+        let original_tree = self.original_tree()?;
         ensure!(
             original_tree.root_hash() == original_root,
             "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",